@@ -0,0 +1,344 @@
+//! Embedded memory client: the store, governance and embedding steps of the MCP write path as a
+//! blocking Rust API, for applications that keep memory in-process instead of running the daemon.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use prx_memory_core::{compact_query, importance_level_from_numeric, resolve_importance, validate_governed_input};
+use prx_memory_embed::{
+    EmbeddingProvider, EmbeddingProviderConfig, EmbeddingRequest, EmbeddingTask, ProviderError,
+    build_embedding_provider,
+};
+use prx_memory_storage::{
+    FieldCipher, MemoryEntry, MemorySource, NewMemoryEntry, PersistentMemoryStore, RecallQuery, RecallResult,
+    StorageBackend, StorageError,
+};
+use thiserror::Error;
+
+/// Governed writes are refused when the top lexical match scores above this.
+const DUPLICATE_SCORE: f32 = 0.93;
+/// Governed decisions are refused once they make up more than this share of a scope.
+const MAX_DECISION_RATIO: f32 = 0.30;
+
+#[derive(Debug, Error)]
+pub enum MemoryClientError {
+    #[error("governance check failed: {0}")]
+    Governance(String),
+    #[error("memory id not found: {0}")]
+    NotFound(String),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("embedding error: {0}")]
+    Embedding(#[from] ProviderError),
+    #[error("runtime initialization failed: {0}")]
+    Runtime(#[from] std::io::Error),
+}
+
+/// A write for [`MemoryClient::store`]. Start from [`StoreRequest::new`] and override fields.
+#[derive(Debug, Clone)]
+pub struct StoreRequest {
+    pub text: String,
+    pub category: String,
+    pub scope: String,
+    /// `low|medium|high|critical`; `None` means `medium`.
+    pub importance_level: Option<String>,
+    pub tags: Vec<String>,
+    pub source: Option<MemorySource>,
+}
+
+impl StoreRequest {
+    /// A `fact` in the `global` scope at medium importance.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance_level: None,
+            tags: Vec::new(),
+            source: None,
+        }
+    }
+}
+
+/// Fields to change in [`MemoryClient::update`]; `None` keeps the stored value.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryUpdate {
+    pub text: Option<String>,
+    pub category: Option<String>,
+    pub scope: Option<String>,
+    pub importance_level: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+pub struct MemoryClientBuilder {
+    path: PathBuf,
+    cipher: Option<FieldCipher>,
+    backend: Option<Box<dyn StorageBackend>>,
+    embedding: Option<EmbeddingProviderConfig>,
+    embedder: Option<Arc<dyn EmbeddingProvider>>,
+    governed: bool,
+}
+
+impl MemoryClientBuilder {
+    /// Encrypts entry text and embeddings at rest in the JSON store.
+    pub fn cipher(mut self, cipher: FieldCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Uses an already opened backend instead of the JSON store at the builder's path.
+    pub fn backend(mut self, backend: Box<dyn StorageBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Embeds stored text and recall queries with the provider built from `config`.
+    pub fn embedding(mut self, config: EmbeddingProviderConfig) -> Self {
+        self.embedding = Some(config);
+        self
+    }
+
+    /// Embeds with a caller-supplied provider; takes precedence over [`Self::embedding`].
+    pub fn embedding_provider(mut self, provider: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embedder = Some(provider);
+        self
+    }
+
+    /// Applies the governed-mode checks to every write (default: on).
+    pub const fn governed(mut self, governed: bool) -> Self {
+        self.governed = governed;
+        self
+    }
+
+    pub fn build(self) -> Result<MemoryClient, MemoryClientError> {
+        let store = match self.backend {
+            Some(backend) => backend,
+            None => Box::new(PersistentMemoryStore::open_with_cipher(&self.path, self.cipher)?),
+        };
+        let embedder = match (self.embedder, self.embedding) {
+            (Some(provider), _) => Some(provider),
+            (None, Some(config)) => Some(build_embedding_provider(config)?),
+            (None, None) => None,
+        };
+        let runtime = match embedder {
+            Some(_) => Some(tokio::runtime::Builder::new_current_thread().enable_all().build()?),
+            None => None,
+        };
+        Ok(MemoryClient {
+            store: RwLock::new(store),
+            embedder,
+            runtime,
+            governed: self.governed,
+        })
+    }
+}
+
+/// In-process memory store with the same governance rules as the MCP server.
+///
+/// Calls block; from async code, run them on a blocking thread, since embedding drives its own
+/// runtime. Recalls share a read lock and writes take it exclusively.
+pub struct MemoryClient {
+    store: RwLock<Box<dyn StorageBackend>>,
+    embedder: Option<Arc<dyn EmbeddingProvider>>,
+    runtime: Option<tokio::runtime::Runtime>,
+    governed: bool,
+}
+
+impl MemoryClient {
+    /// Builder for a JSON store at `path`, created when missing.
+    pub fn builder(path: impl Into<PathBuf>) -> MemoryClientBuilder {
+        MemoryClientBuilder {
+            path: path.into(),
+            cipher: None,
+            backend: None,
+            embedding: None,
+            embedder: None,
+            governed: true,
+        }
+    }
+
+    /// Opens a governed JSON store at `path` without embeddings.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, MemoryClientError> {
+        Self::builder(path).build()
+    }
+
+    pub fn store(&self, request: StoreRequest) -> Result<MemoryEntry, MemoryClientError> {
+        let (importance, level) =
+            resolve_importance(request.importance_level.as_deref(), None).map_err(MemoryClientError::Governance)?;
+        let tags = normalize_tags(request.tags);
+        if self.governed {
+            validate_governed_input(&request.text, &request.category, &tags, level)
+                .map_err(MemoryClientError::Governance)?;
+        }
+        let (embedding, embedding_model) = self.embed(&request.text, EmbeddingTask::Passage)?;
+
+        let mut store = self.store.write();
+        if self.governed {
+            check_duplicate_and_ratio(store.as_ref(), &request.text, &request.scope, &request.category)?;
+        }
+        let entry = store.store(NewMemoryEntry {
+            text: request.text,
+            category: request.category,
+            scope: request.scope,
+            importance,
+            tags,
+            embedding,
+            embedding_model,
+            source: request.source.and_then(MemorySource::normalized),
+            keywords: Vec::new(),
+        })?;
+        drop(store);
+        Ok(entry)
+    }
+
+    /// Top `limit` entries for `query`, embedding the query when a provider is configured.
+    pub fn recall(&self, query: &str, limit: usize) -> Result<Vec<RecallResult>, MemoryClientError> {
+        self.recall_with(RecallQuery {
+            query: query.to_string(),
+            query_embedding: None,
+            scope: None,
+            category: None,
+            limit,
+            vector_weight: None,
+            lexical_weight: None,
+            diversity: None,
+            fusion: None,
+        })
+    }
+
+    /// Like [`Self::recall`] with every [`RecallQuery`] knob; a missing `query_embedding` is
+    /// filled in when a provider is configured.
+    pub fn recall_with(&self, mut query: RecallQuery) -> Result<Vec<RecallResult>, MemoryClientError> {
+        if query.query_embedding.is_none() {
+            query.query_embedding = self.embed(&query.query, EmbeddingTask::Query)?.0;
+        }
+        let snapshot = self.store.read().snapshot();
+        Ok(snapshot.recall(query))
+    }
+
+    /// Rewrites an entry under a new id, re-embedding changed text and carrying its relations
+    /// over, the way `memory_update` does. Returns the replacement entry.
+    pub fn update(&self, id: &str, update: MemoryUpdate) -> Result<MemoryEntry, MemoryClientError> {
+        let existing = self
+            .store
+            .read()
+            .snapshot()
+            .entries()
+            .iter()
+            .find(|e| e.id == id)
+            .cloned()
+            .ok_or_else(|| MemoryClientError::NotFound(id.to_string()))?;
+        let (importance, level) = match update.importance_level.as_deref() {
+            Some(raw) => resolve_importance(Some(raw), None).map_err(MemoryClientError::Governance)?,
+            None => (existing.importance, importance_level_from_numeric(existing.importance)),
+        };
+        let text = update.text.unwrap_or_else(|| existing.text.clone());
+        let category = update.category.unwrap_or_else(|| existing.category.clone());
+        let tags = update.tags.map_or_else(|| existing.tags.clone(), normalize_tags);
+        if self.governed {
+            validate_governed_input(&text, &category, &tags, level).map_err(MemoryClientError::Governance)?;
+        }
+        let (embedding, embedding_model) = if text == existing.text {
+            (existing.embedding.clone(), existing.embedding_model.clone())
+        } else {
+            self.embed(&text, EmbeddingTask::Passage)?
+        };
+
+        let mut store = self.store.write();
+        let relations = store.relations_for(id);
+        if !store.forget_by_id(id)? {
+            return Err(MemoryClientError::NotFound(id.to_string()));
+        }
+        let updated = store.store(NewMemoryEntry {
+            text,
+            category,
+            scope: update.scope.unwrap_or(existing.scope),
+            importance,
+            tags,
+            embedding,
+            embedding_model,
+            source: existing.source,
+            keywords: existing.keywords,
+        })?;
+        for edge in relations {
+            let from = if edge.from_id == id { &updated.id } else { &edge.from_id };
+            let to = if edge.to_id == id { &updated.id } else { &edge.to_id };
+            let _ = store.link(from, &edge.relation, to);
+        }
+        drop(store);
+        Ok(updated)
+    }
+
+    /// Returns `false` when no entry has `id`.
+    pub fn forget(&self, id: &str) -> Result<bool, MemoryClientError> {
+        Ok(self.store.write().forget_by_id(id)?)
+    }
+
+    fn embed(&self, text: &str, task: EmbeddingTask) -> Result<(Option<Vec<f32>>, Option<String>), MemoryClientError> {
+        let (Some(embedder), Some(runtime)) = (&self.embedder, &self.runtime) else {
+            return Ok((None, None));
+        };
+        let response = runtime.block_on(embedder.embed(EmbeddingRequest {
+            task: Some(task),
+            ..EmbeddingRequest::single(text)
+        }))?;
+        let vector = response
+            .vectors
+            .into_iter()
+            .next()
+            .ok_or_else(|| ProviderError::InvalidResponse("no vector returned".to_string()))?;
+        Ok((Some(vector), Some(response.model)))
+    }
+}
+
+/// Trimmed, lowercased and deduplicated, in first-seen order.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.into_iter().map(|t| t.trim().to_lowercase()) {
+        if !tag.is_empty() && !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
+fn check_duplicate_and_ratio(
+    store: &dyn StorageBackend,
+    text: &str,
+    scope: &str,
+    category: &str,
+) -> Result<(), MemoryClientError> {
+    let probe = store.recall(RecallQuery {
+        query: compact_query(text, 10),
+        query_embedding: None,
+        scope: Some(scope.to_string()),
+        category: Some(category.to_string()),
+        limit: 3,
+        vector_weight: None,
+        lexical_weight: None,
+        diversity: None,
+        fusion: None,
+    });
+    if let Some(top) = probe.first()
+        && top.score > DUPLICATE_SCORE
+    {
+        return Err(MemoryClientError::Governance(format!(
+            "duplicate memory likely exists: {}",
+            top.entry.id
+        )));
+    }
+    if category == "decision" {
+        let snapshot = store.snapshot();
+        let in_scope = snapshot.entries().iter().filter(|e| e.scope == scope);
+        let (total, decisions) = in_scope.fold((0u32, 0u32), |(total, decisions), e| {
+            (total + 1, decisions + u32::from(e.category == "decision"))
+        });
+        if total > 0 && f64::from(decisions) / f64::from(total) > f64::from(MAX_DECISION_RATIO) {
+            return Err(MemoryClientError::Governance(
+                "decision memory ratio exceeds 30% in current scope".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,6 @@
+mod client;
+
+pub use client::{MemoryClient, MemoryClientBuilder, MemoryClientError, MemoryUpdate, StoreRequest};
 pub use prx_memory_core::*;
 pub use prx_memory_embed::{
     EmbeddingProvider, EmbeddingProviderConfig, EmbeddingRequest, EmbeddingResponse, EmbeddingTask, GeminiConfig,
@@ -7,3 +10,6 @@ pub use prx_memory_rerank::{
     CohereRerankConfig, JinaRerankConfig, PineconeRerankConfig, ProviderError as RerankProviderError, RerankItem,
     RerankProvider, RerankProviderConfig, RerankRequest, RerankResponse, build_rerank_provider,
 };
+pub use prx_memory_storage::{
+    FieldCipher, MemoryEntry, MemoryRelation, MemorySource, RecallQuery, RecallResult, StorageBackend, StorageError,
+};
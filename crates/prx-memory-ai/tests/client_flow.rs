@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use prx_memory_ai::{
+    EmbeddingProvider, EmbeddingProviderError, EmbeddingRequest, EmbeddingResponse, MemoryClient, MemoryClientError,
+    MemoryUpdate, StoreRequest,
+};
+
+/// Two-dimensional vectors that separate "cache" texts from everything else.
+struct KeywordEmbedder;
+
+#[async_trait]
+impl EmbeddingProvider for KeywordEmbedder {
+    fn name(&self) -> &'static str {
+        "keyword"
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, EmbeddingProviderError> {
+        Ok(EmbeddingResponse {
+            provider: "keyword".to_string(),
+            model: "keyword-v1".to_string(),
+            vectors: request
+                .inputs
+                .iter()
+                .map(|text| {
+                    if text.contains("cache") {
+                        vec![1.0, 0.0]
+                    } else {
+                        vec![0.0, 1.0]
+                    }
+                })
+                .collect(),
+            usage_tokens: None,
+        })
+    }
+}
+
+fn temp_db_path(name: &str) -> std::path::PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    std::env::temp_dir().join(format!("prx-client-{name}-{}-{now}.json", std::process::id()))
+}
+
+fn governed_tags() -> Vec<String> {
+    ["project:demo", "tool:cargo", "domain:cache"]
+        .map(str::to_string)
+        .to_vec()
+}
+
+#[test]
+fn governed_client_stores_recalls_updates_and_forgets() {
+    let path = temp_db_path("flow");
+    let client = MemoryClient::builder(&path)
+        .embedding_provider(Arc::new(KeywordEmbedder))
+        .build()
+        .expect("client");
+
+    let rejected = client.store(StoreRequest::new("the cache was stale again"));
+    assert!(matches!(rejected, Err(MemoryClientError::Governance(_))));
+
+    let stored = client
+        .store(StoreRequest {
+            tags: governed_tags(),
+            ..StoreRequest::new(
+                "Pitfall: cache served stale rows. Cause: no invalidation. Fix: bust on write. Prevention: add a test.",
+            )
+        })
+        .expect("governed store");
+    assert_eq!(stored.embedding_model.as_deref(), Some("keyword-v1"));
+
+    let duplicate = client.store(StoreRequest {
+        tags: governed_tags(),
+        ..StoreRequest::new(
+            "Pitfall: cache served stale rows. Cause: no invalidation. Fix: bust on write. Prevention: add a test.",
+        )
+    });
+    assert!(matches!(duplicate, Err(MemoryClientError::Governance(_))));
+
+    let recalled = client.recall("stale cache rows", 3).expect("recall");
+    assert_eq!(recalled.first().map(|r| r.entry.id.clone()), Some(stored.id.clone()));
+
+    let updated = client
+        .update(
+            &stored.id,
+            MemoryUpdate {
+                importance_level: Some("high".to_string()),
+                ..MemoryUpdate::default()
+            },
+        )
+        .expect("update");
+    assert!((updated.importance - 0.75).abs() < f32::EPSILON);
+    assert!(matches!(
+        client.update(&stored.id, MemoryUpdate::default()),
+        Err(MemoryClientError::NotFound(_))
+    ));
+
+    assert!(client.forget(&updated.id).expect("forget"));
+    assert!(client.recall("stale cache rows", 3).expect("recall").is_empty());
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn ungoverned_client_accepts_free_text() {
+    let path = temp_db_path("free");
+    let client = MemoryClient::builder(&path).governed(false).build().expect("client");
+    let stored = client
+        .store(StoreRequest::new("Deploys go out on Tuesdays"))
+        .expect("store");
+    assert_eq!(stored.embedding, None);
+    assert_eq!(client.recall("deploys tuesdays", 1).expect("recall").len(), 1);
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+/// Forgetting curve for stored memories.
+///
+/// Retention halves every `stability` days since the entry was last recalled (or created).
+/// Stability starts at `half_life_days`, grows with the log of the recall count, and is
+/// scaled by importance: low-importance entries fade faster, critical ones never fade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecayPolicy {
+    /// Days for an unrecalled, medium-importance entry to fall to half retention.
+    pub half_life_days: f64,
+    /// Stability gained per natural-log unit of recall count; `0` ignores usage.
+    pub access_boost: f64,
+    /// Entries at or above this importance keep full retention.
+    pub pinned_importance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionInput {
+    pub created_ms: u64,
+    pub last_access_ms: Option<u64>,
+    pub access_count: u64,
+    pub importance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionScore {
+    /// `1.0` when fresh or pinned, halving every `stability_days` of idleness.
+    pub retention: f64,
+    pub idle_days: f64,
+    /// `None` for pinned entries.
+    pub stability_days: Option<f64>,
+}
+
+impl Default for DecayPolicy {
+    fn default() -> Self {
+        Self {
+            half_life_days: 30.0,
+            access_boost: 1.0,
+            pinned_importance: 1.0,
+        }
+    }
+}
+
+impl DecayPolicy {
+    pub fn retention(&self, input: &RetentionInput, now_ms: u64) -> RetentionScore {
+        let last_touch = input.last_access_ms.unwrap_or(input.created_ms).max(input.created_ms);
+        let idle_days = Duration::from_millis(now_ms.saturating_sub(last_touch)).as_secs_f64() / 86_400.0;
+        if input.importance >= self.pinned_importance {
+            return RetentionScore {
+                retention: 1.0,
+                idle_days,
+                stability_days: None,
+            };
+        }
+        let accesses = f64::from(u32::try_from(input.access_count).unwrap_or(u32::MAX));
+        let importance = f64::from(input.importance.clamp(0.0, 1.0));
+        let stability_days = self.half_life_days.max(0.01)
+            * self.access_boost.max(0.0).mul_add(accesses.ln_1p(), 1.0)
+            * (0.5 + importance);
+        RetentionScore {
+            retention: 0.5_f64.powf(idle_days / stability_days),
+            idle_days,
+            stability_days: Some(stability_days),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_MS: u64 = 86_400_000;
+
+    fn input(access_count: u64, importance: f32) -> RetentionInput {
+        RetentionInput {
+            created_ms: 0,
+            last_access_ms: None,
+            access_count,
+            importance,
+        }
+    }
+
+    #[test]
+    fn retention_halves_per_half_life_and_grows_with_use() {
+        let policy = DecayPolicy::default();
+        let fresh = policy.retention(&input(0, 0.5), 0);
+        assert!((fresh.retention - 1.0).abs() < 1e-9);
+
+        let idle = policy.retention(&input(0, 0.5), 30 * DAY_MS);
+        assert!((idle.retention - 0.5).abs() < 1e-9);
+        assert!((idle.idle_days - 30.0).abs() < 1e-9);
+
+        let recalled = policy.retention(&input(10, 0.5), 30 * DAY_MS);
+        assert!(recalled.retention > idle.retention);
+        let low = policy.retention(&input(0, 0.25), 30 * DAY_MS);
+        assert!(low.retention < idle.retention);
+
+        let touched = RetentionInput {
+            last_access_ms: Some(29 * DAY_MS),
+            ..input(1, 0.5)
+        };
+        assert!(policy.retention(&touched, 30 * DAY_MS).retention > 0.95);
+    }
+
+    #[test]
+    fn pinned_entries_never_decay() {
+        let score = DecayPolicy::default().retention(&input(0, 1.0), 3650 * DAY_MS);
+        assert!((score.retention - 1.0).abs() < 1e-9);
+        assert_eq!(score.stability_days, None);
+    }
+}
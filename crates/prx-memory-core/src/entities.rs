@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+const KNOWN_TOOLS: &[&str] = &[
+    "bash",
+    "cargo",
+    "clippy",
+    "cmake",
+    "docker",
+    "gcc",
+    "git",
+    "gradle",
+    "helm",
+    "jq",
+    "kubectl",
+    "lancedb",
+    "make",
+    "maven",
+    "mysql",
+    "nginx",
+    "node",
+    "npm",
+    "ollama",
+    "pip",
+    "pnpm",
+    "postgres",
+    "python",
+    "redis",
+    "rustc",
+    "rustfmt",
+    "sqlite",
+    "terraform",
+    "tokio",
+    "vim",
+    "yarn",
+];
+
+const PROJECT_MARKERS: &[&str] = &["project", "repo", "repository", "crate", "service"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EntityKind {
+    Person,
+    Project,
+    Tool,
+}
+
+impl EntityKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Person => "person",
+            Self::Project => "project",
+            Self::Tool => "tool",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "person" | "people" => Some(Self::Person),
+            "project" | "projects" => Some(Self::Project),
+            "tool" | "tools" => Some(Self::Tool),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExtractedEntity {
+    pub kind: EntityKind,
+    pub name: String,
+}
+
+/// Detects people (`@handle`), projects (`project:*` tags or a name following
+/// "project"/"repo"/...) and tools (`tool:*` tags or a known tool name) in a memory.
+pub fn extract_entities(text: &str, tags: &[String]) -> Vec<ExtractedEntity> {
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    let mut push = |kind: EntityKind, raw: &str| {
+        let name = normalize_entity_name(raw);
+        if name.len() >= 2 && seen.insert((kind, name.clone())) {
+            out.push(ExtractedEntity { kind, name });
+        }
+    };
+
+    for tag in tags {
+        let lower = tag.to_ascii_lowercase();
+        if let Some(v) = lower.strip_prefix("person:") {
+            push(EntityKind::Person, v);
+        } else if let Some(v) = lower.strip_prefix("project:") {
+            push(EntityKind::Project, v);
+        } else if let Some(v) = lower.strip_prefix("tool:") {
+            push(EntityKind::Tool, v);
+        }
+    }
+
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    for (idx, word) in words.iter().enumerate() {
+        if let Some(handle) = word.strip_prefix('@') {
+            push(EntityKind::Person, handle);
+            continue;
+        }
+        let bare = normalize_entity_name(word);
+        if PROJECT_MARKERS.contains(&bare.as_str()) {
+            if let Some(next) = words.get(idx + 1) {
+                let candidate = normalize_entity_name(next);
+                if !candidate.is_empty() && !is_stopword(&candidate) {
+                    push(EntityKind::Project, &candidate);
+                }
+            }
+        } else if KNOWN_TOOLS.contains(&bare.as_str()) {
+            push(EntityKind::Tool, &bare);
+        }
+    }
+
+    out
+}
+
+pub fn normalize_entity_name(raw: &str) -> String {
+    raw.trim_matches(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '/'))
+        .trim_end_matches('.')
+        .to_lowercase()
+}
+
+fn is_stopword(word: &str) -> bool {
+    matches!(
+        word,
+        "a" | "an" | "the" | "is" | "was" | "and" | "or" | "to" | "for" | "of" | "in" | "on" | "with" | "that" | "this"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_people_projects_and_tools() {
+        let tags = vec!["project:prx-memory".to_string(), "tool:mcp".to_string()];
+        let found = extract_entities(
+            "@Alice fixed the flaky build in repo billing-api by pinning cargo and docker versions.",
+            &tags,
+        );
+        let has = |kind: EntityKind, name: &str| found.iter().any(|e| e.kind == kind && e.name == name);
+        assert!(has(EntityKind::Person, "alice"));
+        assert!(has(EntityKind::Project, "prx-memory"));
+        assert!(has(EntityKind::Project, "billing-api"));
+        assert!(has(EntityKind::Tool, "mcp"));
+        assert!(has(EntityKind::Tool, "cargo"));
+        assert!(has(EntityKind::Tool, "docker"));
+        assert_eq!(found.len(), 6);
+    }
+}
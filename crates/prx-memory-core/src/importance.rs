@@ -0,0 +1,119 @@
+//! Importance suggestions for writes that leave the importance out: a score from the category,
+//! how specific the text is, and how close it sits to existing high-importance entries.
+
+use serde::Serialize;
+
+/// Words that mark a rule or a costly failure rather than a passing note.
+const SEVERITY_MARKERS: [&str; 8] = [
+    "never",
+    "always",
+    "must",
+    "critical",
+    "security",
+    "data loss",
+    "outage",
+    "corrupt",
+];
+
+/// An existing entry recall matched against the new text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImportanceNeighbor {
+    /// Recall score of the match.
+    pub score: f32,
+    pub importance: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportanceSuggestion {
+    pub level: &'static str,
+    /// Raw score before it was snapped to `level`.
+    pub score: f32,
+    /// Signals that moved the score, e.g. `category:decision` or `specific:numbers`.
+    pub reasons: Vec<String>,
+}
+
+/// Suggests an importance level for `text`.
+///
+/// Categories set the base, concrete details (numbers, identifiers, the pitfall template) and
+/// severity words raise it, very short text lowers it, and recall matches with high importance
+/// pull it up in proportion to how similar they are.
+pub fn suggest_importance(text: &str, category: &str, neighbors: &[ImportanceNeighbor]) -> ImportanceSuggestion {
+    let lowered = text.to_lowercase();
+    let category = category.to_lowercase();
+    let mut reasons = vec![format!("category:{category}")];
+    let mut score = match category.as_str() {
+        "decision" => 0.6,
+        "fact" => 0.5,
+        "preference" => 0.45,
+        _ => 0.35,
+    };
+
+    let mut bump = |applies: bool, delta: f32, reason: &str| {
+        if applies {
+            score += delta;
+            reasons.push(reason.to_string());
+        }
+    };
+    bump(text.chars().any(|c| c.is_ascii_digit()), 0.05, "specific:numbers");
+    bump(
+        text.split_whitespace().any(|word| {
+            word.contains("::") || word.contains('_') || word.contains('/') || word.contains('`') || word.contains('=')
+        }),
+        0.05,
+        "specific:identifiers",
+    );
+    bump(
+        ["cause:", "fix:", "prevention:"].iter().all(|m| lowered.contains(m)),
+        0.1,
+        "specific:template",
+    );
+    bump(SEVERITY_MARKERS.iter().any(|m| lowered.contains(m)), 0.1, "severity");
+    bump(text.trim().chars().count() < 40, -0.1, "short");
+
+    let pull = neighbors
+        .iter()
+        .filter(|n| n.importance > 0.5)
+        .map(|n| n.score.clamp(0.0, 1.0) * (n.importance - 0.5) * 0.4)
+        .fold(0.0_f32, f32::max);
+    if pull > 0.0 {
+        score += pull;
+        reasons.push("similar:high-importance".to_string());
+    }
+
+    let score = score.clamp(0.0, 1.0);
+    let level = match score {
+        s if s < 0.375 => "low",
+        s if s < 0.625 => "medium",
+        s if s < 0.875 => "high",
+        _ => "critical",
+    };
+    ImportanceSuggestion { level, score, reasons }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestions_follow_category_detail_and_neighbors() {
+        let note = suggest_importance("likes dark mode", "other", &[]);
+        assert_eq!(note.level, "low");
+        assert_eq!(note.reasons, vec!["category:other", "short"]);
+
+        let pitfall = "Pitfall: writes lost on restart. Cause: fsync skipped in db_flush. Fix: fsync before rename. \
+                       Prevention: never skip the crash test.";
+        let suggested = suggest_importance(pitfall, "fact", &[]);
+        assert_eq!(suggested.level, "high");
+        assert!(suggested.reasons.contains(&"specific:template".to_string()));
+
+        let plain = "Staging resets every week on the first workday";
+        assert_eq!(suggest_importance(plain, "fact", &[]).level, "medium");
+        let near_critical = [ImportanceNeighbor {
+            score: 0.9,
+            importance: 1.0,
+        }];
+        let pulled = suggest_importance(plain, "fact", &near_critical);
+        assert_eq!(pulled.level, "high");
+        assert!(pulled.reasons.contains(&"similar:high-importance".to_string()));
+    }
+}
@@ -1,7 +1,17 @@
+pub mod decay;
+pub mod entities;
 pub mod evolution;
+pub mod governance;
+pub mod importance;
 pub mod mses;
+pub mod retry;
 pub mod viability;
 
+pub use decay::*;
+pub use entities::*;
 pub use evolution::*;
+pub use governance::*;
+pub use importance::*;
 pub use mses::*;
+pub use retry::*;
 pub use viability::*;
@@ -0,0 +1,181 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How a failed provider call should be treated by a [`RetryPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Timeouts and connection errors.
+    Transient,
+    /// HTTP 429.
+    RateLimited,
+    /// HTTP 5xx.
+    ServerError,
+    /// Anything a retry cannot fix.
+    Permanent,
+}
+
+impl Failure {
+    /// Classifies an HTTP error status: 429 is rate limiting, 5xx a server error and anything
+    /// else permanent.
+    pub const fn from_status(status: u16) -> Self {
+        match status {
+            429 => Self::RateLimited,
+            500.. => Self::ServerError,
+            _ => Self::Permanent,
+        }
+    }
+}
+
+/// Errors a [`RetryPolicy`] can classify; implemented by each provider crate's error type.
+pub trait RetryableError {
+    fn failure(&self) -> Failure;
+}
+
+/// Retry policy for transient provider failures (timeouts, connection errors, 429 and 5xx).
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+    pub retry_on_rate_limit: bool,
+    pub retry_on_server_error: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+            retry_on_rate_limit: true,
+            retry_on_server_error: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn should_retry(&self, err: &impl RetryableError) -> bool {
+        match err.failure() {
+            Failure::Transient => true,
+            Failure::RateLimited => self.retry_on_rate_limit,
+            Failure::ServerError => self.retry_on_server_error,
+            Failure::Permanent => false,
+        }
+    }
+
+    /// Delay before retry number `attempt` (1-based): `base * 2^(attempt-1)`, capped at
+    /// `max_delay`. With jitter the delay is drawn from the upper half of that window.
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(16);
+        let delay = self.base_delay.saturating_mul(1 << exp).min(self.max_delay);
+        if !self.jitter || delay.is_zero() {
+            return delay;
+        }
+        let half = delay / 2;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::from(d.subsec_nanos()));
+        let span = u64::try_from(half.as_millis()).unwrap_or(u64::MAX).max(1);
+        half + Duration::from_millis(nanos % span)
+    }
+
+    pub async fn run<T, E, F, Fut>(&self, mut op: F) -> Result<T, E>
+    where
+        E: RetryableError,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match op().await {
+                Err(err) if attempt < self.max_attempts && self.should_retry(&err) => {
+                    tokio::time::sleep(self.delay_for(attempt)).await;
+                    attempt += 1;
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+/// A provider whose requests are retried according to `policy`. Each provider crate implements
+/// its provider trait for `Retrying<dyn Trait>` by running the inner call through
+/// [`RetryPolicy::run`].
+pub struct Retrying<P: ?Sized> {
+    pub inner: Arc<P>,
+    pub policy: RetryPolicy,
+}
+
+impl<P: ?Sized> Clone for Retrying<P> {
+    fn clone(&self) -> Self {
+        Self::new(Arc::clone(&self.inner), self.policy.clone())
+    }
+}
+
+impl<P: ?Sized> Retrying<P> {
+    pub const fn new(inner: Arc<P>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl RetryableError for Failure {
+        fn failure(&self) -> Failure {
+            *self
+        }
+    }
+
+    #[test]
+    fn retries_only_transient_failures() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(&Failure::Transient));
+        assert!(policy.should_retry(&Failure::RateLimited));
+        assert!(policy.should_retry(&Failure::ServerError));
+        assert!(!policy.should_retry(&Failure::Permanent));
+
+        let strict = RetryPolicy {
+            retry_on_rate_limit: false,
+            ..RetryPolicy::default()
+        };
+        assert!(!strict.should_retry(&Failure::RateLimited));
+    }
+
+    #[test]
+    fn retries_only_transient_statuses() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(&Failure::from_status(429)));
+        assert!(policy.should_retry(&Failure::from_status(503)));
+        assert!(!policy.should_retry(&Failure::from_status(400)));
+        assert!(!policy.should_retry(&Failure::from_status(404)));
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let policy = RetryPolicy {
+            jitter: false,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(350));
+
+        let jittered = RetryPolicy { jitter: true, ..policy };
+        let d = jittered.delay_for(2);
+        assert!(d >= Duration::from_millis(100) && d <= Duration::from_millis(200));
+    }
+}
@@ -44,9 +44,28 @@ impl GeminiConfig {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct OllamaConfig {
+    pub base_url: String,
+    pub model: String,
+    pub timeout: Duration,
+}
+
+impl OllamaConfig {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            base_url: "http://localhost:11434".to_string(),
+            model: model.into(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum EmbeddingProviderConfig {
     OpenAiCompatible(OpenAiCompatibleConfig),
     Jina(OpenAiCompatibleConfig),
     Gemini(GeminiConfig),
+    Mistral(OpenAiCompatibleConfig),
+    Ollama(OllamaConfig),
 }
@@ -2,7 +2,10 @@ use std::sync::Arc;
 
 use crate::config::EmbeddingProviderConfig;
 use crate::error::ProviderError;
-use crate::providers::{GeminiEmbeddingProvider, JinaEmbeddingProvider, OpenAiCompatibleEmbeddingProvider};
+use crate::providers::{
+    GeminiEmbeddingProvider, JinaEmbeddingProvider, MistralEmbeddingProvider, OllamaEmbeddingProvider,
+    OpenAiCompatibleEmbeddingProvider,
+};
 use crate::traits::EmbeddingProvider;
 
 pub fn build_embedding_provider(cfg: EmbeddingProviderConfig) -> Result<Arc<dyn EmbeddingProvider>, ProviderError> {
@@ -10,5 +13,7 @@ pub fn build_embedding_provider(cfg: EmbeddingProviderConfig) -> Result<Arc<dyn
         EmbeddingProviderConfig::OpenAiCompatible(c) => Ok(Arc::new(OpenAiCompatibleEmbeddingProvider::new(c)?)),
         EmbeddingProviderConfig::Jina(c) => Ok(Arc::new(JinaEmbeddingProvider::new(c)?)),
         EmbeddingProviderConfig::Gemini(c) => Ok(Arc::new(GeminiEmbeddingProvider::new(c)?)),
+        EmbeddingProviderConfig::Mistral(c) => Ok(Arc::new(MistralEmbeddingProvider::new(c)?)),
+        EmbeddingProviderConfig::Ollama(c) => Ok(Arc::new(OllamaEmbeddingProvider::new(c)?)),
     }
 }
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use crate::error::ProviderError;
+use crate::traits::EmbeddingProvider;
+use crate::types::{EmbeddingRequest, EmbeddingResponse};
+
+/// Tries each provider in priority order, moving to the next one on network or API errors.
+/// The returned response keeps the `provider` name of whichever provider served it.
+#[derive(Clone)]
+pub struct FailoverEmbeddingProvider {
+    providers: Vec<Arc<dyn EmbeddingProvider>>,
+}
+
+impl FailoverEmbeddingProvider {
+    pub fn new(providers: Vec<Arc<dyn EmbeddingProvider>>) -> Result<Self, ProviderError> {
+        if providers.is_empty() {
+            return Err(ProviderError::Config("failover chain has no providers".to_string()));
+        }
+        Ok(Self { providers })
+    }
+
+    pub fn provider_names(&self) -> Vec<&'static str> {
+        self.providers.iter().map(|p| p.name()).collect()
+    }
+
+    const fn should_fail_over(err: &ProviderError) -> bool {
+        matches!(err, ProviderError::Http(_) | ProviderError::Api { .. })
+    }
+}
+
+#[async_trait::async_trait]
+impl EmbeddingProvider for FailoverEmbeddingProvider {
+    fn name(&self) -> &'static str {
+        "failover"
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, ProviderError> {
+        let mut last_err = None;
+        for provider in &self.providers {
+            match provider.embed(request.clone()).await {
+                Ok(res) => return Ok(res),
+                Err(err) if Self::should_fail_over(&err) => last_err = Some(err),
+                Err(err) => return Err(err),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| ProviderError::Config("failover chain has no providers".to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    struct Fixed {
+        name: &'static str,
+        status: Option<u16>,
+    }
+
+    #[async_trait::async_trait]
+    impl EmbeddingProvider for Fixed {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn embed(&self, _request: EmbeddingRequest) -> Result<EmbeddingResponse, ProviderError> {
+            match self.status {
+                Some(status) => Err(ProviderError::Api {
+                    status,
+                    body: String::new(),
+                }),
+                None => Ok(EmbeddingResponse {
+                    provider: self.name.to_string(),
+                    model: "m".to_string(),
+                    vectors: vec![vec![1.0]],
+                    usage_tokens: None,
+                }),
+            }
+        }
+    }
+
+    fn ready<T>(fut: impl Future<Output = T>) -> T {
+        let mut fut = pin!(fut);
+        let mut cx = Context::from_waker(Waker::noop());
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(v) => v,
+            Poll::Pending => panic!("mock provider future should be ready"),
+        }
+    }
+
+    #[test]
+    fn falls_over_to_next_provider_on_api_error() {
+        let chain = FailoverEmbeddingProvider::new(vec![
+            Arc::new(Fixed {
+                name: "primary",
+                status: Some(503),
+            }),
+            Arc::new(Fixed {
+                name: "secondary",
+                status: None,
+            }),
+        ])
+        .unwrap();
+        let res = ready(chain.embed(EmbeddingRequest::single("x"))).unwrap();
+        assert_eq!(res.provider, "secondary");
+        assert_eq!(chain.provider_names(), vec!["primary", "secondary"]);
+    }
+
+    #[test]
+    fn returns_last_error_when_all_providers_fail() {
+        let chain = FailoverEmbeddingProvider::new(vec![Arc::new(Fixed {
+            name: "only",
+            status: Some(429),
+        })])
+        .unwrap();
+        let err = ready(chain.embed(EmbeddingRequest::single("x"))).unwrap_err();
+        assert!(matches!(err, ProviderError::Api { status: 429, .. }));
+    }
+}
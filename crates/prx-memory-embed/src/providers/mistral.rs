@@ -0,0 +1,40 @@
+use crate::config::OpenAiCompatibleConfig;
+use crate::error::ProviderError;
+use crate::providers::openai_compatible::OpenAiCompatibleEmbeddingProvider;
+use crate::traits::EmbeddingProvider;
+use crate::types::{EmbeddingRequest, EmbeddingResponse};
+
+#[derive(Clone)]
+pub struct MistralEmbeddingProvider {
+    inner: OpenAiCompatibleEmbeddingProvider,
+}
+
+impl MistralEmbeddingProvider {
+    pub fn new(mut config: OpenAiCompatibleConfig) -> Result<Self, ProviderError> {
+        if config.base_url.trim().is_empty() {
+            config.base_url = "https://api.mistral.ai".to_string();
+        }
+        // Mistral rejects the Jina-style task/normalized extensions; its vectors are already unit length.
+        config.task_query = None;
+        config.task_passage = None;
+        config.normalized_default = None;
+        Ok(Self {
+            inner: OpenAiCompatibleEmbeddingProvider::new(config)?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl EmbeddingProvider for MistralEmbeddingProvider {
+    fn name(&self) -> &'static str {
+        "mistral"
+    }
+
+    async fn embed(&self, mut request: EmbeddingRequest) -> Result<EmbeddingResponse, ProviderError> {
+        request.normalized = None;
+        request.dimensions = None;
+        let mut res = self.inner.embed(request).await?;
+        res.provider = self.name().to_string();
+        Ok(res)
+    }
+}
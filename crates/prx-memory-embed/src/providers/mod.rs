@@ -1,7 +1,13 @@
+pub mod failover;
 pub mod gemini;
 pub mod jina;
+pub mod mistral;
+pub mod ollama;
 pub mod openai_compatible;
 
+pub use failover::FailoverEmbeddingProvider;
 pub use gemini::GeminiEmbeddingProvider;
 pub use jina::JinaEmbeddingProvider;
+pub use mistral::MistralEmbeddingProvider;
+pub use ollama::OllamaEmbeddingProvider;
 pub use openai_compatible::OpenAiCompatibleEmbeddingProvider;
@@ -0,0 +1,106 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::config::OllamaConfig;
+use crate::error::ProviderError;
+use crate::traits::EmbeddingProvider;
+use crate::types::{EmbeddingRequest, EmbeddingResponse};
+
+#[derive(Clone)]
+pub struct OllamaEmbeddingProvider {
+    config: OllamaConfig,
+    client: Client,
+}
+
+impl OllamaEmbeddingProvider {
+    pub fn new(config: OllamaConfig) -> Result<Self, ProviderError> {
+        let client = Client::builder().timeout(config.timeout).build()?;
+        Ok(Self { config, client })
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/api/embeddings", self.config.base_url.trim_end_matches('/'))
+    }
+}
+
+#[async_trait::async_trait]
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, ProviderError> {
+        if request.inputs.is_empty() {
+            return Err(ProviderError::Config("embedding input is empty".to_string()));
+        }
+
+        // /api/embeddings takes a single prompt, so batch requests are sent one by one.
+        let mut vectors = Vec::with_capacity(request.inputs.len());
+        for input in &request.inputs {
+            let payload = OllamaEmbedRequest {
+                model: &self.config.model,
+                prompt: input,
+            };
+            let res = self.client.post(self.endpoint()).json(&payload).send().await?;
+            if !res.status().is_success() {
+                let status = res.status().as_u16();
+                let body = res.text().await.unwrap_or_default();
+                return Err(ProviderError::Api { status, body });
+            }
+
+            let parsed: OllamaEmbedResponse = res.json().await?;
+            if parsed.embedding.is_empty() {
+                return Err(ProviderError::InvalidResponse(
+                    "ollama returned an empty embedding".to_string(),
+                ));
+            }
+            let vector = if request.normalized.unwrap_or(false) {
+                l2_normalize(parsed.embedding)
+            } else {
+                parsed.embedding
+            };
+            vectors.push(vector);
+        }
+
+        Ok(EmbeddingResponse {
+            provider: self.name().to_string(),
+            model: self.config.model.clone(),
+            vectors,
+            usage_tokens: None,
+        })
+    }
+}
+
+fn l2_normalize(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in &mut v {
+            *x /= norm;
+        }
+    }
+    v
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaEmbedRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbedResponse {
+    embedding: Vec<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_embedding_and_normalizes() {
+        let parsed: OllamaEmbedResponse = serde_json::from_str(r#"{"embedding":[3.0,4.0]}"#).expect("parse");
+        let v = l2_normalize(parsed.embedding);
+        assert!((v[0] - 0.6).abs() < 1e-6);
+        assert!((v[1] - 0.8).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,119 @@
+//! Regenerates `include/prx_memory.h` from the `extern "C"` functions in `src/lib.rs`, carrying
+//! their doc comments over. Only the handful of types the ABI uses are mapped.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+const PREAMBLE: &str = "/* Generated by build.rs from src/lib.rs; do not edit. */
+
+#ifndef PRX_MEMORY_H
+#define PRX_MEMORY_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+/* Opaque store handle. */
+typedef struct PrxMemory PrxMemory;
+";
+
+const POSTAMBLE: &str = "
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PRX_MEMORY_H */
+";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    let source = fs::read_to_string("src/lib.rs")?;
+    let mut header = PREAMBLE.to_string();
+    for (docs, signature) in exported_functions(&source) {
+        header.push_str("\n/**\n");
+        for line in docs {
+            header.push_str(" *");
+            if !line.is_empty() {
+                header.push(' ');
+                header.push_str(&line);
+            }
+            header.push('\n');
+        }
+        header.push_str(" */\n");
+        header.push_str(&c_declaration(&signature)?);
+        header.push('\n');
+    }
+    header.push_str(POSTAMBLE);
+
+    let target = Path::new("include/prx_memory.h");
+    if fs::read_to_string(target).ok().as_deref() != Some(header.as_str()) {
+        fs::create_dir_all("include")?;
+        fs::write(target, header)?;
+    }
+    Ok(())
+}
+
+/// `(doc lines, signature up to the opening brace)` for every `extern "C" fn`.
+fn exported_functions(source: &str) -> Vec<(Vec<String>, String)> {
+    let mut out = Vec::new();
+    let mut docs = Vec::new();
+    let mut lines = source.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if let Some(doc) = line.strip_prefix("///") {
+            docs.push(doc.trim().to_string());
+        } else if line.contains("extern \"C\" fn ") {
+            let mut signature = line.to_string();
+            while !signature.contains('{') {
+                let Some(next) = lines.next() else { break };
+                signature.push(' ');
+                signature.push_str(next);
+            }
+            out.push((std::mem::take(&mut docs), signature));
+        } else if !line.starts_with("#[") {
+            docs.clear();
+        }
+    }
+    out
+}
+
+fn c_declaration(signature: &str) -> Result<String, Box<dyn Error>> {
+    let after_fn = signature.split("fn ").nth(1).ok_or("missing fn")?;
+    let (name, tail) = after_fn.split_once('(').ok_or("missing (")?;
+    let (params, tail) = tail.split_once(')').ok_or("missing )")?;
+    let returns = tail
+        .split_once("->")
+        .map_or("", |(_, r)| r.trim_end_matches('{').trim());
+    let params = params
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (param, ty) = p.split_once(':').ok_or("parameter without type")?;
+            Ok(format!("{} {}", c_type(ty.trim())?, param.trim()).replace("* ", "*"))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    let params = if params.is_empty() {
+        "void".to_string()
+    } else {
+        params.join(", ")
+    };
+    let returns = if returns.is_empty() { "void" } else { c_type(returns)? };
+    Ok(format!("{returns} {}({params});", name.trim()).replace("* ", "*"))
+}
+
+fn c_type(rust: &str) -> Result<&'static str, Box<dyn Error>> {
+    Ok(match rust {
+        "*const c_char" => "const char *",
+        "*mut c_char" => "char *",
+        "*mut PrxMemory" | "*const PrxMemory" => "PrxMemory *",
+        "usize" => "size_t",
+        "i32" => "int32_t",
+        "bool" => "bool",
+        other => return Err(format!("no C mapping for `{other}`").into()),
+    })
+}
@@ -0,0 +1,79 @@
+/* Generated by build.rs from src/lib.rs; do not edit. */
+
+#ifndef PRX_MEMORY_H
+#define PRX_MEMORY_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque store handle. */
+typedef struct PrxMemory PrxMemory;
+
+/**
+ * Opens (or creates) the JSON store at `path`. `governed` applies the governed-mode checks to
+ * every write. Returns NULL on failure.
+ *
+ * # Safety
+ * `path` must be a NUL-terminated UTF-8 string.
+ */
+PrxMemory *prx_memory_open(const char *path, bool governed);
+
+/**
+ * Stores one entry described by `request_json` (`text`, optional `category`, `scope`,
+ * `importance_level`, `tags`, `source`). Returns the stored entry as JSON, or NULL.
+ *
+ * # Safety
+ * `handle` must come from `prx_memory_open`; `request_json` must be a NUL-terminated string.
+ */
+char *prx_memory_store(PrxMemory *handle, const char *request_json);
+
+/**
+ * Recalls up to `limit` entries for `query`. Returns a JSON array of `{"entry", "score"}`
+ * objects, or NULL.
+ *
+ * # Safety
+ * `handle` must come from `prx_memory_open`; `query` must be a NUL-terminated string.
+ */
+char *prx_memory_recall(PrxMemory *handle, const char *query, size_t limit);
+
+/**
+ * Forgets the entry with `id`. Returns 1 when it was removed, 0 when no entry has that id and
+ * -1 on error.
+ *
+ * # Safety
+ * `handle` must come from `prx_memory_open`; `id` must be a NUL-terminated string.
+ */
+int32_t prx_memory_forget(PrxMemory *handle, const char *id);
+
+/**
+ * Message for the last failed call on this thread, or NULL. Valid until the next failing call
+ * on the same thread; do not free it.
+ */
+const char *prx_memory_last_error(void);
+
+/**
+ * Releases a string returned by `prx_memory_store` or `prx_memory_recall`. NULL is ignored.
+ *
+ * # Safety
+ * `value` must be null or a string returned by this library that was not freed yet.
+ */
+void prx_memory_string_free(char *value);
+
+/**
+ * Closes a handle from `prx_memory_open`. NULL is ignored.
+ *
+ * # Safety
+ * `handle` must be null or a handle that was not closed yet; it must not be used afterwards.
+ */
+void prx_memory_close(PrxMemory *handle);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PRX_MEMORY_H */
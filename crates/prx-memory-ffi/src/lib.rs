@@ -0,0 +1,207 @@
+//! C ABI over [`MemoryClient`] for hosts that cannot link Rust directly.
+//!
+//! Handles are opaque `PrxMemory *` pointers. Results come back as JSON strings owned by the
+//! library and released with `prx_memory_string_free`. On failure a function returns `NULL` (or
+//! `-1`) and `prx_memory_last_error` describes the cause. `build.rs` regenerates
+//! `include/prx_memory.h` from the signatures below.
+
+// The whole crate is the unsafe boundary; every block carries a SAFETY note.
+#![allow(unsafe_code)]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+use prx_memory_ai::{MemoryClient, MemorySource, StoreRequest};
+use serde::Deserialize;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Opaque store handle.
+pub struct PrxMemory {
+    client: MemoryClient,
+}
+
+/// JSON accepted by `prx_memory_store`; only `text` is required.
+#[derive(Debug, Deserialize)]
+struct StoreInput {
+    text: String,
+    category: Option<String>,
+    scope: Option<String>,
+    importance_level: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    source: Option<MemorySource>,
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = CString::new(message).ok());
+}
+
+/// Runs `f`, turning errors and panics into `fallback` plus a last-error message.
+fn guarded<T>(fallback: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            fallback
+        }
+        Err(_) => {
+            set_last_error("internal panic");
+            fallback
+        }
+    }
+}
+
+/// Borrows a caller-owned C string as UTF-8.
+///
+/// # Safety
+/// `raw` must be null or point to a NUL-terminated string that outlives the returned borrow.
+unsafe fn read_str<'a>(raw: *const c_char, what: &str) -> Result<&'a str, String> {
+    if raw.is_null() {
+        return Err(format!("{what} is null"));
+    }
+    // SAFETY: non-null and NUL-terminated per this function's contract.
+    unsafe { CStr::from_ptr(raw) }
+        .to_str()
+        .map_err(|_| format!("{what} is not valid UTF-8"))
+}
+
+/// # Safety
+/// `handle` must be null or a live pointer from `prx_memory_open`.
+unsafe fn client<'a>(handle: *const PrxMemory) -> Result<&'a MemoryClient, String> {
+    // SAFETY: non-null handles come from `Box::into_raw` in `prx_memory_open` and stay valid
+    // until `prx_memory_close`.
+    unsafe { handle.as_ref() }
+        .map(|h| &h.client)
+        .ok_or_else(|| "handle is null".to_string())
+}
+
+fn into_c_string(json: &serde_json::Value) -> Result<*mut c_char, String> {
+    CString::new(json.to_string())
+        .map(CString::into_raw)
+        .map_err(|e| e.to_string())
+}
+
+/// Opens (or creates) the JSON store at `path`. `governed` applies the governed-mode checks to
+/// every write. Returns NULL on failure.
+///
+/// # Safety
+/// `path` must be a NUL-terminated UTF-8 string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn prx_memory_open(path: *const c_char, governed: bool) -> *mut PrxMemory {
+    guarded(ptr::null_mut(), || {
+        // SAFETY: forwarded from this function's contract.
+        let path = unsafe { read_str(path, "path") }?;
+        let client = MemoryClient::builder(path)
+            .governed(governed)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Box::into_raw(Box::new(PrxMemory { client })))
+    })
+}
+
+/// Stores one entry described by `request_json` (`text`, optional `category`, `scope`,
+/// `importance_level`, `tags`, `source`). Returns the stored entry as JSON, or NULL.
+///
+/// # Safety
+/// `handle` must come from `prx_memory_open`; `request_json` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn prx_memory_store(handle: *mut PrxMemory, request_json: *const c_char) -> *mut c_char {
+    guarded(ptr::null_mut(), || {
+        // SAFETY: forwarded from this function's contract.
+        let client = unsafe { client(handle) }?;
+        // SAFETY: forwarded from this function's contract.
+        let raw = unsafe { read_str(request_json, "request_json") }?;
+        let input: StoreInput = serde_json::from_str(raw).map_err(|e| format!("invalid request_json: {e}"))?;
+        let defaults = StoreRequest::new(input.text);
+        let mut entry = client
+            .store(StoreRequest {
+                category: input.category.unwrap_or(defaults.category.clone()),
+                scope: input.scope.unwrap_or(defaults.scope.clone()),
+                importance_level: input.importance_level,
+                tags: input.tags,
+                source: input.source,
+                ..defaults
+            })
+            .map_err(|e| e.to_string())?;
+        entry.embedding = None;
+        into_c_string(&serde_json::to_value(entry).map_err(|e| e.to_string())?)
+    })
+}
+
+/// Recalls up to `limit` entries for `query`. Returns a JSON array of `{"entry", "score"}`
+/// objects, or NULL.
+///
+/// # Safety
+/// `handle` must come from `prx_memory_open`; `query` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn prx_memory_recall(handle: *mut PrxMemory, query: *const c_char, limit: usize) -> *mut c_char {
+    guarded(ptr::null_mut(), || {
+        // SAFETY: forwarded from this function's contract.
+        let client = unsafe { client(handle) }?;
+        // SAFETY: forwarded from this function's contract.
+        let query = unsafe { read_str(query, "query") }?;
+        let results = client.recall(query, limit.max(1)).map_err(|e| e.to_string())?;
+        let items = results
+            .into_iter()
+            .map(|r| {
+                let mut entry = r.entry;
+                entry.embedding = None;
+                serde_json::json!({"entry": entry, "score": r.score})
+            })
+            .collect::<Vec<_>>();
+        into_c_string(&serde_json::Value::Array(items))
+    })
+}
+
+/// Forgets the entry with `id`. Returns 1 when it was removed, 0 when no entry has that id and
+/// -1 on error.
+///
+/// # Safety
+/// `handle` must come from `prx_memory_open`; `id` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn prx_memory_forget(handle: *mut PrxMemory, id: *const c_char) -> i32 {
+    guarded(-1, || {
+        // SAFETY: forwarded from this function's contract.
+        let client = unsafe { client(handle) }?;
+        // SAFETY: forwarded from this function's contract.
+        let id = unsafe { read_str(id, "id") }?;
+        client.forget(id).map(i32::from).map_err(|e| e.to_string())
+    })
+}
+
+/// Message for the last failed call on this thread, or NULL. Valid until the next failing call
+/// on the same thread; do not free it.
+#[unsafe(no_mangle)]
+pub extern "C" fn prx_memory_last_error() -> *const c_char {
+    LAST_ERROR.with(|slot| slot.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// Releases a string returned by `prx_memory_store` or `prx_memory_recall`. NULL is ignored.
+///
+/// # Safety
+/// `value` must be null or a string returned by this library that was not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn prx_memory_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        // SAFETY: the string was produced by `CString::into_raw` in `into_c_string`.
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
+
+/// Closes a handle from `prx_memory_open`. NULL is ignored.
+///
+/// # Safety
+/// `handle` must be null or a handle that was not closed yet; it must not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn prx_memory_close(handle: *mut PrxMemory) {
+    if !handle.is_null() {
+        // SAFETY: the handle was produced by `Box::into_raw` in `prx_memory_open`.
+        drop(unsafe { Box::from_raw(handle) });
+    }
+}
@@ -0,0 +1,112 @@
+#![allow(unsafe_code)]
+
+use std::ffi::{CStr, CString, c_char};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use prx_memory_ffi::{
+    prx_memory_close, prx_memory_forget, prx_memory_last_error, prx_memory_open, prx_memory_recall, prx_memory_store,
+    prx_memory_string_free,
+};
+
+fn temp_db_path(name: &str) -> std::path::PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    std::env::temp_dir().join(format!("prx-ffi-{name}-{}-{now}.json", std::process::id()))
+}
+
+fn c(value: &str) -> CString {
+    CString::new(value).expect("no interior NUL")
+}
+
+/// Copies and frees a string returned by the library.
+fn take_json(raw: *mut c_char) -> serde_json::Value {
+    assert!(!raw.is_null(), "call failed: {}", last_error());
+    // SAFETY: non-null strings from the library are NUL-terminated.
+    let text = unsafe { CStr::from_ptr(raw) }.to_string_lossy().into_owned();
+    // SAFETY: freed exactly once, right after copying.
+    unsafe { prx_memory_string_free(raw) };
+    serde_json::from_str(&text).expect("valid json")
+}
+
+fn last_error() -> String {
+    let raw = prx_memory_last_error();
+    if raw.is_null() {
+        return String::new();
+    }
+    // SAFETY: the message stays valid until the next failing call on this thread.
+    unsafe { CStr::from_ptr(raw) }.to_string_lossy().into_owned()
+}
+
+#[test]
+fn open_store_recall_forget_close() {
+    let path = temp_db_path("flow");
+    let path_c = c(&path.to_string_lossy());
+    // SAFETY: valid C strings and a handle that is closed once at the end.
+    unsafe {
+        let handle = prx_memory_open(path_c.as_ptr(), false);
+        assert!(!handle.is_null(), "open failed: {}", last_error());
+
+        let request = c(r#"{"text":"Deploys go out on Tuesdays","tags":["Team:Ops"],"scope":"ops"}"#);
+        let stored = take_json(prx_memory_store(handle, request.as_ptr()));
+        assert_eq!(stored["scope"], "ops");
+        assert_eq!(stored["tags"], serde_json::json!(["team:ops"]));
+        assert!(stored.get("embedding").is_none_or(serde_json::Value::is_null));
+        let id = c(stored["id"].as_str().expect("id"));
+
+        let query = c("deploys tuesdays");
+        let recalled = take_json(prx_memory_recall(handle, query.as_ptr(), 5));
+        assert_eq!(recalled[0]["entry"]["id"], stored["id"]);
+        assert!(recalled[0]["score"].as_f64().is_some());
+
+        assert_eq!(prx_memory_forget(handle, id.as_ptr()), 1);
+        assert_eq!(prx_memory_forget(handle, id.as_ptr()), 0);
+        prx_memory_close(handle);
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn failures_return_null_and_set_last_error() {
+    let path = temp_db_path("errors");
+    let path_c = c(&path.to_string_lossy());
+    // SAFETY: valid C strings or deliberate nulls, which the library rejects.
+    unsafe {
+        assert!(prx_memory_open(std::ptr::null(), true).is_null());
+        assert_eq!(last_error(), "path is null");
+
+        let handle = prx_memory_open(path_c.as_ptr(), true);
+        assert!(!handle.is_null());
+        let ungoverned = c(r#"{"text":"the cache was stale again"}"#);
+        assert!(prx_memory_store(handle, ungoverned.as_ptr()).is_null());
+        assert!(last_error().starts_with("governance check failed"));
+
+        let malformed = c("{not json");
+        assert!(prx_memory_store(handle, malformed.as_ptr()).is_null());
+        assert!(last_error().starts_with("invalid request_json"));
+
+        let query = c("anything");
+        assert!(prx_memory_recall(std::ptr::null_mut(), query.as_ptr(), 1).is_null());
+        assert_eq!(prx_memory_forget(handle, std::ptr::null()), -1);
+        prx_memory_close(handle);
+        prx_memory_close(std::ptr::null_mut());
+        prx_memory_string_free(std::ptr::null_mut());
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn header_declares_every_exported_function() {
+    let header = include_str!("../include/prx_memory.h");
+    let source = include_str!("../src/lib.rs");
+    let exported = source
+        .lines()
+        .filter_map(|line| line.split("extern \"C\" fn ").nth(1))
+        .filter_map(|rest| rest.split('(').next())
+        .collect::<Vec<_>>();
+    assert_eq!(exported.len(), 7);
+    for name in exported {
+        assert!(header.contains(&format!("{name}(")), "{name} missing from header");
+    }
+}
@@ -0,0 +1,261 @@
+//! Adapters from other memory tools' export files onto `memory_migrate` import entries.
+//!
+//! Each adapter keeps only the remembered text plus what can be inferred from it: a scope from the
+//! owning user or agent, a category where the source has a clear equivalent, and `source:<tool>` /
+//! origin tags. Raw chat messages are not imported; distill them instead.
+
+use serde_json::{Map, Value, json};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceFormat {
+    Mem0,
+    Letta,
+    Zep,
+}
+
+impl SourceFormat {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw.to_ascii_lowercase().as_str() {
+            "mem0" => Ok(Self::Mem0),
+            "letta" | "memgpt" => Ok(Self::Letta),
+            "zep" => Ok(Self::Zep),
+            other => Err(format!("source_format must be one of mem0|letta|zep, got {other}")),
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Mem0 => "mem0",
+            Self::Letta => "letta",
+            Self::Zep => "zep",
+        }
+    }
+}
+
+/// Converts a source export into import-entry objects.
+pub fn read_source(data: &str, format: SourceFormat) -> Result<Vec<Value>, String> {
+    let root = serde_json::from_str::<Value>(data.strip_prefix('\u{feff}').unwrap_or(data))
+        .map_err(|e| format!("invalid {} export: {e}", format.as_str()))?;
+    let records = match format {
+        SourceFormat::Mem0 => mem0_records(&root),
+        SourceFormat::Letta => letta_records(&root),
+        SourceFormat::Zep => zep_records(&root),
+    };
+    if records.is_empty() {
+        return Err(format!("no memories found in {} export", format.as_str()));
+    }
+    Ok(records)
+}
+
+/// `get_all()` output: a list (or `{"results": [...]}`) of `{memory, user_id, agent_id, categories}`.
+fn mem0_records(root: &Value) -> Vec<Value> {
+    list_at(root, &["results", "memories"])
+        .filter_map(|row| {
+            let text = str_field(row, &["memory", "text", "data"])?;
+            let scope = str_field(row, &["user_id"])
+                .map(|id| format!("user:{}", scope_id(id)))
+                .or_else(|| str_field(row, &["agent_id"]).map(|id| format!("agent:{}", scope_id(id))));
+            let categories = row
+                .get("categories")
+                .and_then(Value::as_array)
+                .map(|c| c.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let category = categories
+                .iter()
+                .any(|c| c.to_ascii_lowercase().contains("preference"))
+                .then_some("preference");
+            let mut tags = vec!["source:mem0".to_string()];
+            tags.extend(categories.iter().map(ToString::to_string));
+            Some(record(text, scope, category, &tags))
+        })
+        .collect()
+}
+
+/// Agent files (`.af`): core memory blocks (`{label, value}`) and archival passages per agent.
+fn letta_records(root: &Value) -> Vec<Value> {
+    let mut out = Vec::new();
+    for agent in documents(root, &["agents"]) {
+        let scope = str_field(agent, &["name", "id"]).map(|name| format!("agent:{}", scope_id(name)));
+        let nested = agent
+            .get("memory")
+            .map_or_else(|| [].iter(), |m| list_at(m, &["blocks"]));
+        let blocks = list_at(agent, &["memory_blocks", "core_memory", "blocks"]).chain(nested);
+        for block in blocks {
+            let Some(text) = str_field(block, &["value"]) else {
+                continue;
+            };
+            let label = str_field(block, &["label"]).unwrap_or("block").to_ascii_lowercase();
+            let category = (label == "human").then_some("fact");
+            let tags = vec!["source:letta".to_string(), format!("letta:{}", scope_id(&label))];
+            out.push(record(text, scope.clone(), category, &tags));
+        }
+        for passage in list_at(agent, &["passages", "archival_memory"]) {
+            if let Some(text) = str_field(passage, &["text", "content"]) {
+                let tags = vec!["source:letta".to_string(), "letta:archival".to_string()];
+                out.push(record(text, scope.clone(), None, &tags));
+            }
+        }
+    }
+    out
+}
+
+/// User or session exports with `facts` / graph `edges` (`{fact, name, rating}`) and a `summary`.
+fn zep_records(root: &Value) -> Vec<Value> {
+    let mut out = Vec::new();
+    for doc in documents(root, &["users", "sessions"]) {
+        let scope = str_field(doc, &["user_id"]).map(|id| format!("user:{}", scope_id(id)));
+        for fact in list_at(doc, &["facts", "edges", "relevant_facts"]) {
+            let Some(text) = str_field(fact, &["fact", "content"]) else {
+                continue;
+            };
+            let mut tags = vec!["source:zep".to_string()];
+            if let Some(name) = str_field(fact, &["name"]) {
+                tags.push(format!("relation:{}", scope_id(name)));
+            }
+            let mut entry = record(text, scope.clone(), Some("fact"), &tags);
+            if let Some(rating) = fact.get("rating").and_then(Value::as_f64)
+                && let Some(obj) = entry.as_object_mut()
+            {
+                let level = if rating >= 0.75 {
+                    "high"
+                } else if rating >= 0.5 {
+                    "medium"
+                } else {
+                    "low"
+                };
+                obj.insert("importance_level".to_string(), json!(level));
+            }
+            out.push(entry);
+        }
+        let summary = doc
+            .get("summary")
+            .and_then(|s| s.as_str().or_else(|| str_field(s, &["content"])));
+        if let Some(text) = summary.filter(|s| !s.trim().is_empty()) {
+            let tags = vec!["source:zep".to_string(), "zep:summary".to_string()];
+            out.push(record(text, scope.clone(), None, &tags));
+        }
+    }
+    out
+}
+
+fn record(text: &str, scope: Option<String>, category: Option<&str>, tags: &[String]) -> Value {
+    let mut obj = Map::new();
+    obj.insert("text".to_string(), json!(text.trim()));
+    if let Some(scope) = scope {
+        obj.insert("scope".to_string(), json!(scope));
+    }
+    if let Some(category) = category {
+        obj.insert("category".to_string(), json!(category));
+    }
+    obj.insert("tags".to_string(), json!(tags));
+    Value::Object(obj)
+}
+
+/// Rows of `value` itself when it is an array, else of the first array-valued key.
+fn list_at<'a>(value: &'a Value, keys: &[&str]) -> std::slice::Iter<'a, Value> {
+    value
+        .as_array()
+        .or_else(|| keys.iter().find_map(|k| value.get(*k).and_then(Value::as_array)))
+        .map_or_else(|| [].iter(), |rows| rows.iter())
+}
+
+/// Top-level documents of an export: like [`list_at`], but a bare object is a single document.
+fn documents<'a>(root: &'a Value, keys: &[&str]) -> Vec<&'a Value> {
+    let rows = list_at(root, keys).collect::<Vec<_>>();
+    if rows.is_empty() && root.is_object() && keys.iter().all(|k| root.get(*k).is_none()) {
+        return vec![root];
+    }
+    rows
+}
+
+fn str_field<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .filter_map(|k| value.get(*k).and_then(Value::as_str))
+        .find(|s| !s.trim().is_empty())
+}
+
+/// Lowercases an id or name for use in a scope or tag, replacing anything but `[a-z0-9_.-]`.
+fn scope_id(raw: &str) -> String {
+    raw.trim()
+        .to_ascii_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mem0_export_maps_owner_scope_and_categories() {
+        let export = r#"{"results": [
+            {"id": "a1", "memory": "Prefers dark mode in every editor", "user_id": "Alice", "categories": ["user_preferences"]},
+            {"id": "a2", "memory": "Works on the billing service", "agent_id": "support-bot"},
+            {"id": "a3", "memory": "  "}
+        ]}"#;
+        let records = read_source(export, SourceFormat::Mem0).expect("mem0 records");
+        assert_eq!(
+            records,
+            vec![
+                json!({"text": "Prefers dark mode in every editor", "scope": "user:alice", "category": "preference",
+                       "tags": ["source:mem0", "user_preferences"]}),
+                json!({"text": "Works on the billing service", "scope": "agent:support-bot", "tags": ["source:mem0"]}),
+            ]
+        );
+    }
+
+    #[test]
+    fn letta_agent_file_maps_blocks_and_archival_passages() {
+        let export = r#"{"agents": [{
+            "name": "Ops Helper",
+            "core_memory": [
+                {"label": "persona", "value": "I keep answers short."},
+                {"label": "human", "value": "The user runs Kubernetes on bare metal."}
+            ],
+            "passages": [{"text": "Rolled back release 42 after a failed migration."}]
+        }]}"#;
+        let records = read_source(export, SourceFormat::Letta).expect("letta records");
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records.get(1),
+            Some(
+                &json!({"text": "The user runs Kubernetes on bare metal.", "scope": "agent:ops-helper",
+                         "category": "fact", "tags": ["source:letta", "letta:human"]})
+            )
+        );
+        assert_eq!(
+            records.get(2).and_then(|r| r.get("tags")),
+            Some(&json!(["source:letta", "letta:archival"]))
+        );
+    }
+
+    #[test]
+    fn zep_export_maps_facts_ratings_and_summary() {
+        let export = r#"{"user_id": "u-7", "facts": [
+            {"fact": "Bob manages the data team", "name": "MANAGES", "rating": 0.9},
+            {"content": "Bob prefers async standups"}
+        ], "summary": {"content": "Planning the Q3 data migration."}}"#;
+        let records = read_source(export, SourceFormat::Zep).expect("zep records");
+        assert_eq!(
+            records.first(),
+            Some(
+                &json!({"text": "Bob manages the data team", "scope": "user:u-7", "category": "fact",
+                         "tags": ["source:zep", "relation:manages"], "importance_level": "high"})
+            )
+        );
+        assert_eq!(
+            records.get(2),
+            Some(&json!({"text": "Planning the Q3 data migration.", "scope": "user:u-7",
+                         "tags": ["source:zep", "zep:summary"]}))
+        );
+        assert!(read_source("{}", SourceFormat::Zep).is_err());
+        assert!(SourceFormat::parse("langchain").is_err());
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::BTreeSet;
+use std::io;
+
+use prx_memory_mcp::McpServer;
+use prx_memory_mcp::inspector::{Inspector, InspectorEntry};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+const LIST_LIMIT: usize = 500;
+const SEARCH_LIMIT: usize = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Scopes,
+    Entries,
+}
+
+enum Mode {
+    Browse,
+    Search(String),
+    ConfirmDelete(String),
+}
+
+struct App<'a> {
+    inspector: Inspector<'a>,
+    scopes: Vec<(String, u64)>,
+    scope_state: ListState,
+    entries: Vec<InspectorEntry>,
+    entry_state: ListState,
+    marked: BTreeSet<String>,
+    focus: Focus,
+    mode: Mode,
+    status: String,
+}
+
+impl<'a> App<'a> {
+    fn new(server: &'a McpServer) -> Self {
+        Self {
+            inspector: Inspector::new(server),
+            scopes: Vec::new(),
+            scope_state: ListState::default().with_selected(Some(0)),
+            entries: Vec::new(),
+            entry_state: ListState::default(),
+            marked: BTreeSet::new(),
+            focus: Focus::Scopes,
+            mode: Mode::Browse,
+            status: String::new(),
+        }
+    }
+
+    /// `None` is the leading "all scopes" row.
+    fn selected_scope(&self) -> Option<String> {
+        let index = self.scope_state.selected()?.checked_sub(1)?;
+        self.scopes.get(index).map(|(scope, _)| scope.clone())
+    }
+
+    fn selected_entry(&self) -> Option<&InspectorEntry> {
+        self.entries.get(self.entry_state.selected()?)
+    }
+
+    fn refresh(&mut self) {
+        let result = self.inspector.scopes().and_then(|scopes| {
+            self.scopes = scopes;
+            let scope = self.selected_scope();
+            self.inspector.list(scope.as_deref(), LIST_LIMIT)
+        });
+        match result {
+            Ok(entries) => {
+                self.status = format!("{} entries", entries.len());
+                self.set_entries(entries);
+            }
+            Err(err) => self.status = err,
+        }
+        let last = self.scopes.len();
+        if self.scope_state.selected().is_none_or(|i| i > last) {
+            self.scope_state.select(Some(0));
+        }
+    }
+
+    fn set_entries(&mut self, entries: Vec<InspectorEntry>) {
+        self.entries = entries;
+        self.marked.retain(|id| self.entries.iter().any(|e| &e.entry.id == id));
+        let selected = self
+            .entry_state
+            .selected()
+            .map(|i| i.min(self.entries.len().saturating_sub(1)));
+        self.entry_state.select(if self.entries.is_empty() {
+            None
+        } else {
+            selected.or(Some(0))
+        });
+    }
+
+    fn search(&mut self, query: &str) {
+        let scope = self.selected_scope();
+        match self.inspector.search(query, scope.as_deref(), SEARCH_LIMIT) {
+            Ok(hits) => {
+                self.status = format!("{} results for \"{query}\"", hits.len());
+                self.set_entries(hits);
+                self.focus = Focus::Entries;
+            }
+            Err(err) => self.status = err,
+        }
+    }
+
+    fn forget(&mut self, id: &str) {
+        let status = match self.inspector.forget(id) {
+            Ok(true) => format!("deleted {id}"),
+            Ok(false) => format!("{id} was already gone"),
+            Err(err) => err,
+        };
+        self.refresh();
+        self.status = status;
+    }
+
+    fn merge_marked(&mut self) {
+        if self.marked.len() < 2 {
+            self.status = "mark at least two entries with `m` before merging".to_string();
+            return;
+        }
+        let ids = self.marked.iter().cloned().collect::<Vec<_>>();
+        let status = match self.inspector.merge(&ids) {
+            Ok(merged) => {
+                self.marked.clear();
+                format!("merged {} entries into {merged}", ids.len())
+            }
+            Err(err) => err,
+        };
+        self.refresh();
+        self.status = status;
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let (state, len) = match self.focus {
+            Focus::Scopes => (&mut self.scope_state, self.scopes.len() + 1),
+            Focus::Entries => (&mut self.entry_state, self.entries.len()),
+        };
+        if down {
+            state.select_next();
+        } else {
+            state.select_previous();
+        }
+        if state.selected().is_some_and(|i| i >= len) {
+            state.select(len.checked_sub(1));
+        }
+        if self.focus == Focus::Scopes {
+            self.refresh();
+        }
+    }
+
+    /// Returns `false` when the user asked to quit.
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Search(mut query) => match code {
+                KeyCode::Enter if !query.trim().is_empty() => self.search(query.trim()),
+                KeyCode::Esc | KeyCode::Enter => {}
+                KeyCode::Backspace => {
+                    query.pop();
+                    self.mode = Mode::Search(query);
+                }
+                KeyCode::Char(c) => {
+                    query.push(c);
+                    self.mode = Mode::Search(query);
+                }
+                _ => self.mode = Mode::Search(query),
+            },
+            Mode::ConfirmDelete(id) => {
+                if code == KeyCode::Char('y') {
+                    self.forget(&id);
+                } else {
+                    self.status = "delete cancelled".to_string();
+                }
+            }
+            Mode::Browse => match code {
+                KeyCode::Char('q') | KeyCode::Esc => return false,
+                KeyCode::Tab => {
+                    self.focus = match self.focus {
+                        Focus::Scopes => Focus::Entries,
+                        Focus::Entries => Focus::Scopes,
+                    };
+                }
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection(true),
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection(false),
+                KeyCode::Char('/') => self.mode = Mode::Search(String::new()),
+                KeyCode::Char('r') => self.refresh(),
+                KeyCode::Char('d') => {
+                    if let Some(id) = self.selected_entry().map(|e| e.entry.id.clone()) {
+                        self.status = format!("delete {id}? (y/n)");
+                        self.mode = Mode::ConfirmDelete(id);
+                    }
+                }
+                KeyCode::Char('m') => {
+                    if let Some(id) = self.selected_entry().map(|e| e.entry.id.clone())
+                        && !self.marked.remove(&id)
+                    {
+                        self.marked.insert(id);
+                    }
+                    self.status = format!("{} marked", self.marked.len());
+                }
+                KeyCode::Char('M') => self.merge_marked(),
+                _ => {}
+            },
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] = Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [scopes_area, entries_area, detail_area] =
+            Layout::horizontal([Constraint::Length(28), Constraint::Percentage(40), Constraint::Min(20)]).areas(main);
+        let highlight = Style::new().add_modifier(Modifier::REVERSED);
+        let focused = |focus: Focus| {
+            if self.focus == focus {
+                Style::new().add_modifier(Modifier::BOLD)
+            } else {
+                Style::new()
+            }
+        };
+
+        let total = self.scopes.iter().map(|(_, count)| count).sum::<u64>();
+        let scope_items = std::iter::once(ListItem::new(format!("(all) {total}")))
+            .chain(
+                self.scopes
+                    .iter()
+                    .map(|(scope, count)| ListItem::new(format!("{scope} {count}"))),
+            )
+            .collect::<Vec<_>>();
+        let scopes = List::new(scope_items)
+            .block(Block::bordered().title("Scopes").border_style(focused(Focus::Scopes)))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(scopes, scopes_area, &mut self.scope_state);
+
+        let entry_items = self
+            .entries
+            .iter()
+            .map(|e| {
+                let mark = if self.marked.contains(&e.entry.id) { "*" } else { " " };
+                let score = e.score.map(|s| format!("{s:.3} ")).unwrap_or_default();
+                let first_line = e.entry.text.lines().next().unwrap_or_default();
+                ListItem::new(format!("{mark}{score}[{}] {first_line}", e.entry.category))
+            })
+            .collect::<Vec<_>>();
+        let entries = List::new(entry_items)
+            .block(Block::bordered().title("Entries").border_style(focused(Focus::Entries)))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(entries, entries_area, &mut self.entry_state);
+
+        let detail = self.selected_entry().map(detail_lines).unwrap_or_default();
+        frame.render_widget(
+            Paragraph::new(detail)
+                .block(Block::bordered().title("Detail"))
+                .wrap(Wrap { trim: false }),
+            detail_area,
+        );
+
+        let footer_text = match &self.mode {
+            Mode::Search(query) => format!("search: {query}_"),
+            Mode::ConfirmDelete(_) | Mode::Browse if !self.status.is_empty() => self.status.clone(),
+            _ => "tab focus  / search  d delete  m mark  M merge  r refresh  q quit".to_string(),
+        };
+        frame.render_widget(Paragraph::new(footer_text), footer);
+    }
+}
+
+fn detail_lines(hit: &InspectorEntry) -> Vec<Line<'static>> {
+    let entry = &hit.entry;
+    let mut lines = vec![
+        Line::from(format!("id:         {}", entry.id)),
+        Line::from(format!("scope:      {}", entry.scope)),
+        Line::from(format!("category:   {}", entry.category)),
+        Line::from(format!("importance: {:.2}", entry.importance)),
+        Line::from(format!("tags:       {}", entry.tags.join(", "))),
+    ];
+    if let Some(score) = hit.score {
+        lines.push(Line::from(format!("score:      {score:.4}")));
+    }
+    lines.push(Line::from(""));
+    lines.extend(entry.text.lines().map(|l| Line::from(l.to_string())));
+    if let Some(explain) = &hit.explain {
+        lines.push(Line::from(""));
+        lines.push(Line::from("explain:"));
+        let pretty = serde_json::to_string_pretty(explain).unwrap_or_default();
+        lines.extend(pretty.lines().map(|l| Line::from(l.to_string())));
+    }
+    lines
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App<'_>) -> io::Result<()> {
+    app.refresh();
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && !app.handle_key(key.code)
+        {
+            return Ok(());
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    // No logging subscriber: stderr shares the terminal with the UI.
+    let server = McpServer::new().map_err(io::Error::other)?;
+    let mut app = App::new(&server);
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
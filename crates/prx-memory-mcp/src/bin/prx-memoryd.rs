@@ -1,9 +1,20 @@
-use std::io;
+use std::io::{self, Write};
 
 use prx_memory_mcp::McpServer;
 
 fn main() -> io::Result<()> {
     let mode = std::env::var("PRX_MEMORYD_TRANSPORT").unwrap_or_else(|_| "stdio".to_string());
+    prx_memory_mcp::logging::init();
+    if let Ok(migrate) = std::env::var("PRX_MEMORY_MIGRATE") {
+        if migrate != "dry-run" {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "PRX_MEMORY_MIGRATE must be dry-run",
+            ));
+        }
+        let plan = McpServer::migration_dry_run().map_err(io::Error::other)?;
+        return writeln!(io::stdout(), "{plan:#}");
+    }
     let server = McpServer::new().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
     match mode.as_str() {
         "stdio" => server.serve_stdio(),
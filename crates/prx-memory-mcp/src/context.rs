@@ -0,0 +1,226 @@
+//! Query construction for `memory_recall_context`: salient terms picked from recent conversation
+//! messages, so thin clients can recall by context instead of writing their own queries.
+
+use std::collections::HashMap;
+
+use crate::distill::Message;
+
+/// Filler words that say nothing about what a conversation is about.
+const STOPWORDS: &[&str] = &[
+    "about",
+    "after",
+    "again",
+    "all",
+    "also",
+    "and",
+    "any",
+    "anything",
+    "are",
+    "because",
+    "been",
+    "before",
+    "being",
+    "but",
+    "can",
+    "could",
+    "did",
+    "does",
+    "doing",
+    "done",
+    "each",
+    "everything",
+    "few",
+    "fine",
+    "for",
+    "from",
+    "get",
+    "good",
+    "got",
+    "had",
+    "has",
+    "have",
+    "having",
+    "her",
+    "here",
+    "him",
+    "his",
+    "how",
+    "into",
+    "its",
+    "just",
+    "let",
+    "like",
+    "make",
+    "may",
+    "might",
+    "more",
+    "most",
+    "much",
+    "must",
+    "need",
+    "not",
+    "nothing",
+    "now",
+    "off",
+    "okay",
+    "once",
+    "one",
+    "only",
+    "other",
+    "our",
+    "out",
+    "over",
+    "please",
+    "same",
+    "see",
+    "she",
+    "should",
+    "some",
+    "something",
+    "still",
+    "such",
+    "sure",
+    "than",
+    "thank",
+    "thanks",
+    "that",
+    "the",
+    "their",
+    "them",
+    "then",
+    "there",
+    "these",
+    "they",
+    "thing",
+    "think",
+    "this",
+    "those",
+    "through",
+    "today",
+    "too",
+    "try",
+    "under",
+    "until",
+    "use",
+    "very",
+    "want",
+    "was",
+    "way",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "who",
+    "why",
+    "will",
+    "with",
+    "would",
+    "yes",
+    "yet",
+    "you",
+    "your",
+];
+
+/// Each older message counts this much less than the one after it.
+const RECENCY_DECAY: f32 = 0.7;
+
+/// Up to `max_terms` terms from the last `max_messages` messages, most salient first.
+///
+/// A term's weight sums over its occurrences: newer messages count more, user messages count
+/// 1.5x, and identifier-like terms (`snake_case`, digits) count 2x. Ties keep first appearance.
+pub fn salient_terms(messages: &[Message], max_messages: usize, max_terms: usize) -> Vec<String> {
+    let recent = messages
+        .iter()
+        .filter(|m| !m.content.trim().is_empty())
+        .rev()
+        .take(max_messages)
+        .collect::<Vec<_>>();
+    let mut weights: HashMap<String, (f32, usize)> = HashMap::new();
+    let mut order = 0usize;
+    let mut recency = 1.0_f32;
+    for message in recent {
+        let role = if message.role.eq_ignore_ascii_case("user") {
+            1.5
+        } else {
+            1.0
+        };
+        for token in message
+            .content
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map(|t| t.trim_matches('_').to_lowercase())
+            .filter(|t| t.chars().count() >= 3 && !t.chars().all(|c| c.is_ascii_digit()))
+            .filter(|t| !STOPWORDS.contains(&t.as_str()))
+        {
+            let shape = if token.contains('_') || token.chars().any(|c| c.is_ascii_digit()) {
+                2.0
+            } else {
+                1.0
+            };
+            let slot = weights.entry(token).or_insert((0.0, usize::MAX));
+            slot.0 += recency * role * shape;
+            // Messages are walked newest first, so a smaller `order` means more recent.
+            slot.1 = slot.1.min(order);
+            order += 1;
+        }
+        recency *= RECENCY_DECAY;
+    }
+    let mut ranked = weights.into_iter().collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.1.0.total_cmp(&a.1.0).then(a.1.1.cmp(&b.1.1)));
+    ranked.into_iter().take(max_terms).map(|(term, _)| term).collect()
+}
+
+/// One recall query per leading term, each followed by the rest. Lexical recall only keeps
+/// entries containing a query's first term, so rotating the anchor keeps one rare term from
+/// hiding entries that match the others.
+pub fn anchored_queries(terms: &[String], anchors: usize) -> Vec<String> {
+    (0..anchors.min(terms.len()))
+        .map(|idx| {
+            let mut query = terms.get(idx).cloned().unwrap_or_default();
+            for (other_idx, term) in terms.iter().enumerate() {
+                if other_idx != idx {
+                    query.push(' ');
+                    query.push_str(term);
+                }
+            }
+            query
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn recent_user_identifiers_rank_first() {
+        let messages = vec![
+            message("user", "Old question about the billing export"),
+            message("assistant", "The export writes CSV files nightly."),
+            message(
+                "user",
+                "Now the retry_queue backs up after deploys, why would the retry_queue stall?",
+            ),
+        ];
+        let terms = salient_terms(&messages, 2, 4);
+        assert_eq!(terms.first().map(String::as_str), Some("retry_queue"));
+        assert!(!terms.contains(&"billing".to_string()));
+        assert!(!terms.iter().any(|t| t == "why" || t == "the"));
+
+        let queries = anchored_queries(&terms, 2);
+        assert_eq!(queries.len(), 2);
+        assert!(queries.iter().all(|q| q.split(' ').count() == terms.len()));
+        assert_eq!(
+            queries.get(1).and_then(|q| q.split(' ').next()),
+            terms.get(1).map(String::as_str)
+        );
+    }
+}
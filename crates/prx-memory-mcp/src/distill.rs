@@ -0,0 +1,188 @@
+//! Transcript distillation for `memory_distill`: the instruction sent to the summarize provider and
+//! the parser that turns its reply into dual-layer lesson candidates.
+
+use serde::{Deserialize, Serialize};
+
+/// Asks for lessons in exactly the text shape `memory_store_dual` writes, so candidates pass the
+/// governed template checks unchanged.
+pub const INSTRUCTION: &str = "Extract reusable engineering lessons from the following conversation. \
+For each lesson write exactly two lines:\n\
+Pitfall: <what went wrong>. Cause: <root cause>. Fix: <what resolved it>. Prevention: <how to avoid it>.\n\
+Decision principle (<short-tag>): <general rule>. Trigger: <when it applies>. Action: <what to do>.\n\
+Skip small talk, one-off details, secrets and code. Reply with the lesson lines only, or NONE.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lesson {
+    pub pitfall: String,
+    pub cause: String,
+    pub fix: String,
+    pub prevention: String,
+    pub principle: Option<Principle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principle {
+    pub tag: String,
+    pub rule: String,
+    pub trigger: String,
+    pub action: String,
+}
+
+impl Lesson {
+    pub fn text(&self) -> String {
+        format!(
+            "Pitfall: {}. Cause: {}. Fix: {}. Prevention: {}.",
+            self.pitfall, self.cause, self.fix, self.prevention
+        )
+    }
+}
+
+impl Principle {
+    pub fn text(&self) -> String {
+        format!(
+            "Decision principle ({}): {}. Trigger: {}. Action: {}.",
+            self.tag, self.rule, self.trigger, self.action
+        )
+    }
+}
+
+/// One `role: content` document per non-empty message.
+pub fn transcript(messages: &[Message]) -> Vec<String> {
+    messages
+        .iter()
+        .filter(|m| !m.content.trim().is_empty())
+        .map(|m| format!("{}: {}", m.role.trim().to_ascii_lowercase(), m.content.trim()))
+        .collect()
+}
+
+/// Lessons in reply order. A principle line attaches to the nearest preceding lesson that has none;
+/// lines missing a field are ignored.
+pub fn parse_lessons(reply: &str) -> Vec<Lesson> {
+    let mut lessons: Vec<Lesson> = Vec::new();
+    for line in reply.lines() {
+        let line = strip_list_marker(line);
+        if let Some([pitfall, cause, fix, prevention]) = fields(line, ["pitfall:", "cause:", "fix:", "prevention:"]) {
+            lessons.push(Lesson {
+                pitfall,
+                cause,
+                fix,
+                prevention,
+                principle: None,
+            });
+        } else if let Some([head, trigger, action]) = fields(line, ["decision principle", "trigger:", "action:"])
+            && let Some(principle) = parse_principle_head(&head, trigger, action)
+            && let Some(lesson) = lessons.iter_mut().rev().find(|l| l.principle.is_none())
+        {
+            lesson.principle = Some(principle);
+        }
+    }
+    lessons
+}
+
+/// `(tag): rule` after the `Decision principle` marker.
+fn parse_principle_head(head: &str, trigger: String, action: String) -> Option<Principle> {
+    let (tag, rule) = head.strip_prefix('(')?.split_once(')')?;
+    let rule = clean(rule.trim_start().strip_prefix(':').unwrap_or(rule));
+    let tag = clean(tag);
+    (!tag.is_empty() && !rule.is_empty()).then_some(Principle {
+        tag,
+        rule,
+        trigger,
+        action,
+    })
+}
+
+/// Text following each marker (matched case-insensitively, in order) up to the next one. `None`
+/// unless the line starts with the first marker and every field is non-empty.
+fn fields<const N: usize>(line: &str, markers: [&str; N]) -> Option<[String; N]> {
+    let lower = line.to_ascii_lowercase();
+    if !markers.first().is_some_and(|m| lower.starts_with(m)) {
+        return None;
+    }
+    let mut starts = [0usize; N];
+    let mut cursor = 0usize;
+    for (slot, marker) in starts.iter_mut().zip(markers) {
+        cursor += lower.get(cursor..)?.find(marker)?;
+        *slot = cursor;
+        cursor += marker.len();
+    }
+    let mut out: [String; N] = std::array::from_fn(|_| String::new());
+    for (idx, slot) in out.iter_mut().enumerate() {
+        let begin = starts.get(idx)? + markers.get(idx)?.len();
+        let end = starts.get(idx + 1).copied().unwrap_or(line.len());
+        *slot = clean(line.get(begin..end)?);
+        if slot.is_empty() {
+            return None;
+        }
+    }
+    Some(out)
+}
+
+fn clean(raw: &str) -> String {
+    raw.trim().trim_end_matches(['.', ';']).trim().to_string()
+}
+
+fn strip_list_marker(line: &str) -> &str {
+    let line = line.trim().trim_start_matches(['-', '*', '•']).trim_start();
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    match line.get(digits..) {
+        Some(rest) if digits > 0 && (rest.starts_with('.') || rest.starts_with(')')) => {
+            rest.get(1..).unwrap_or(rest).trim_start()
+        }
+        _ => line,
+    }
+    .trim_matches('*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reply_lines_become_paired_lessons() {
+        let reply = "1. Pitfall: Deploy hung on migrations. Cause: a long-running lock on the users table. \
+Fix: ran the migration with lock_timeout. Prevention: set lock_timeout in every migration.\n\
+   Decision principle (db-migrations): bound every schema lock. Trigger: a migration touches a hot table. \
+Action: set lock_timeout and retry.\n\
+- Pitfall: flaky test. Cause: . Fix: retry. Prevention: none.\n\
+- **Pitfall: CI cache misses. Cause: lockfile not in the cache key. Fix: hash Cargo.lock. Prevention: review cache keys.**\n\
+NONE";
+        let lessons = parse_lessons(reply);
+        assert_eq!(lessons.len(), 2);
+        assert_eq!(
+            lessons[0].text(),
+            "Pitfall: Deploy hung on migrations. Cause: a long-running lock on the users table. \
+Fix: ran the migration with lock_timeout. Prevention: set lock_timeout in every migration."
+        );
+        assert_eq!(
+            lessons[0].principle.as_ref().map(Principle::text).as_deref(),
+            Some(
+                "Decision principle (db-migrations): bound every schema lock. Trigger: a migration touches a hot table. \
+Action: set lock_timeout and retry."
+            )
+        );
+        assert_eq!(lessons[1].fix, "hash Cargo.lock");
+        assert_eq!(lessons[1].principle, None);
+    }
+
+    #[test]
+    fn transcript_skips_empty_messages() {
+        let messages = [
+            Message {
+                role: "User".to_string(),
+                content: " Why did the deploy hang? ".to_string(),
+            },
+            Message {
+                role: "assistant".to_string(),
+                content: "  ".to_string(),
+            },
+        ];
+        assert_eq!(transcript(&messages), ["user: Why did the deploy hang?"]);
+    }
+}
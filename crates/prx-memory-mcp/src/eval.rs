@@ -0,0 +1,204 @@
+//! Retrieval-quality metrics for `memory_eval_baseline`: scoring ranked ids against a golden set
+//! and comparing a run with a saved baseline. Also splits a golden set into train and holdout
+//! halves for ranking experiments in `memory_evolve`.
+
+use serde::{Deserialize, Serialize};
+
+/// One golden query and the entry ids a good ranking returns for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvalCase {
+    pub query: String,
+    pub expected: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseScore {
+    pub query: String,
+    /// 1-based rank of the first expected id within the top `k`.
+    pub first_hit_rank: Option<usize>,
+    pub reciprocal_rank: f64,
+    pub recall: f64,
+}
+
+/// Averages over all cases, each in `[0, 1]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    pub hit_rate: f64,
+    pub mrr: f64,
+    pub recall: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Regression {
+    pub metric: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    pub delta: f64,
+}
+
+pub fn score_case(case: &EvalCase, ranked: &[String], k: usize) -> CaseScore {
+    let top = ranked.get(..k.min(ranked.len())).unwrap_or_default();
+    let first_hit_rank = top.iter().position(|id| case.expected.contains(id)).map(|i| i + 1);
+    let found = case.expected.iter().filter(|id| top.contains(id)).count();
+    CaseScore {
+        query: case.query.clone(),
+        first_hit_rank,
+        reciprocal_rank: first_hit_rank.map_or(0.0, |rank| 1.0 / as_f64(rank)),
+        recall: if case.expected.is_empty() {
+            0.0
+        } else {
+            as_f64(found) / as_f64(case.expected.len())
+        },
+    }
+}
+
+pub fn aggregate(scores: &[CaseScore]) -> Metrics {
+    if scores.is_empty() {
+        return Metrics::default();
+    }
+    let n = as_f64(scores.len());
+    Metrics {
+        hit_rate: as_f64(scores.iter().filter(|s| s.first_hit_rank.is_some()).count()) / n,
+        mrr: scores.iter().map(|s| s.reciprocal_rank).sum::<f64>() / n,
+        recall: scores.iter().map(|s| s.recall).sum::<f64>() / n,
+    }
+}
+
+/// Metrics that dropped by more than `tolerance` (absolute) from `baseline` to `current`.
+pub fn regressions(baseline: &Metrics, current: &Metrics, tolerance: f64) -> Vec<Regression> {
+    [
+        ("hit_rate", baseline.hit_rate, current.hit_rate),
+        ("mrr", baseline.mrr, current.mrr),
+        ("recall", baseline.recall, current.recall),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| before - after > tolerance)
+    .map(|(metric, before, after)| Regression {
+        metric,
+        baseline: before,
+        current: after,
+        delta: after - before,
+    })
+    .collect()
+}
+
+/// Queries that had a hit in `baseline` and have none in `current`.
+pub fn lost_hits(baseline: &[CaseScore], current: &[CaseScore]) -> Vec<String> {
+    current
+        .iter()
+        .filter(|now| now.first_hit_rank.is_none())
+        .filter(|now| {
+            baseline
+                .iter()
+                .any(|before| before.query == now.query && before.first_hit_rank.is_some())
+        })
+        .map(|now| now.query.clone())
+        .collect()
+}
+
+/// Mean reciprocal rank as the `f32` score the evolution runner compares.
+pub fn mrr_score(scores: &[CaseScore]) -> f32 {
+    if scores.is_empty() {
+        return 0.0;
+    }
+    let as_f32 = |n: usize| f32::from(u16::try_from(n).unwrap_or(u16::MAX));
+    let total: f32 = scores
+        .iter()
+        .map(|s| s.first_hit_rank.map_or(0.0, |rank| 1.0 / as_f32(rank)))
+        .sum();
+    total / as_f32(scores.len())
+}
+
+/// Deterministic `(train, holdout)` split: cases are ordered by an FNV-1a hash of the query and
+/// the first `round(len * holdout_ratio)` (at least one) go to holdout, so the same golden set
+/// always splits the same way regardless of the order it was saved in.
+pub fn split_holdout(cases: &[EvalCase], holdout_ratio: f64) -> (Vec<EvalCase>, Vec<EvalCase>) {
+    let mut ordered: Vec<&EvalCase> = cases.iter().collect();
+    ordered.sort_by_key(|c| (fnv1a(&c.query), c.query.as_str()));
+    let wanted = (as_f64(cases.len()) * holdout_ratio.clamp(0.0, 1.0)).round();
+    let holdout_len = (1..cases.len()).find(|n| as_f64(*n) >= wanted).unwrap_or(cases.len());
+    let (holdout, train) = ordered.split_at(holdout_len);
+    (
+        train.iter().map(|c| (*c).clone()).collect(),
+        holdout.iter().map(|c| (*c).clone()).collect(),
+    )
+}
+
+pub fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn as_f64(n: usize) -> f64 {
+    f64::from(u32::try_from(n).unwrap_or(u32::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(query: &str, expected: &[&str]) -> EvalCase {
+        EvalCase {
+            query: query.to_string(),
+            expected: expected.iter().map(|s| (*s).to_string()).collect(),
+            scope: None,
+            category: None,
+        }
+    }
+
+    fn ids(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn scores_rank_and_recall_within_k() {
+        let scores = [
+            score_case(&case("a", &["m1", "m2"]), &ids(&["m9", "m2", "m1"]), 2),
+            score_case(&case("b", &["m3"]), &ids(&["m4", "m5", "m3"]), 2),
+        ];
+        let [first, second] = &scores;
+        assert_eq!(first.first_hit_rank, Some(2));
+        assert!((first.recall - 0.5).abs() < 1e-9);
+        assert_eq!(second.first_hit_rank, None);
+
+        let metrics = aggregate(&scores);
+        assert!((metrics.hit_rate - 0.5).abs() < 1e-9);
+        assert!((metrics.mrr - 0.25).abs() < 1e-9);
+        assert!((metrics.recall - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flags_drops_beyond_tolerance() {
+        let baseline = Metrics {
+            hit_rate: 1.0,
+            mrr: 0.8,
+            recall: 0.9,
+        };
+        let current = Metrics {
+            hit_rate: 0.99,
+            mrr: 0.6,
+            recall: 0.95,
+        };
+        let found = regressions(&baseline, &current, 0.02);
+        assert_eq!(found.iter().map(|r| r.metric).collect::<Vec<_>>(), vec!["mrr"]);
+    }
+
+    #[test]
+    fn holdout_split_is_stable_and_never_empty() {
+        let cases: Vec<EvalCase> = (0..10).map(|i| case(&format!("query {i}"), &["m1"])).collect();
+        let (train, holdout) = split_holdout(&cases, 0.3);
+        assert_eq!((train.len(), holdout.len()), (7, 3));
+
+        let mut reversed = cases.clone();
+        reversed.reverse();
+        assert_eq!(split_holdout(&reversed, 0.3), (train, holdout));
+
+        let (train, holdout) = split_holdout(cases.get(..2).unwrap_or_default(), 0.0);
+        assert_eq!((train.len(), holdout.len()), (1, 1));
+    }
+}
@@ -0,0 +1,112 @@
+//! A/B ranking experiments: `PRX_MEMORY_EXPERIMENT` names a list of `RankingConfig` arms with
+//! traffic weights, and each session is pinned to one arm by hashing its id.
+
+use prx_memory_storage::RankingConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::eval::fnv1a;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExperimentArm {
+    pub id: String,
+    pub weight: u32,
+    /// Ranking config for this arm; `None` keeps the active config (the control arm).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<RankingConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Experiment {
+    pub name: String,
+    pub arms: Vec<ExperimentArm>,
+}
+
+impl Experiment {
+    /// Reads `PRX_MEMORY_EXPERIMENT`, e.g.
+    /// `{"name":"cjk-rollout","arms":[{"id":"control","weight":90},{"id":"cjk","weight":10,"config":{"tokenizer":"cjk-ngram"}}]}`.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Ok(raw) = std::env::var("PRX_MEMORY_EXPERIMENT") else {
+            return Ok(None);
+        };
+        if raw.trim().is_empty() {
+            return Ok(None);
+        }
+        let experiment: Self =
+            serde_json::from_str(&raw).map_err(|e| format!("PRX_MEMORY_EXPERIMENT is not valid JSON: {e}"))?;
+        experiment.validate()?;
+        Ok(Some(experiment))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("experiment name must not be empty".to_string());
+        }
+        if self.arms.is_empty() || self.arms.iter().all(|a| a.weight == 0) {
+            return Err("experiment needs at least one arm with a positive weight".to_string());
+        }
+        for (i, arm) in self.arms.iter().enumerate() {
+            if arm.id.trim().is_empty() || self.arms.iter().skip(i + 1).any(|other| other.id == arm.id) {
+                return Err(format!("experiment arm ids must be unique and non-empty: {:?}", arm.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Arm for `key` (a session id, or the agent id outside sessions). Stable for a given
+    /// experiment name and arm list, so a session keeps its arm across calls and restarts.
+    pub fn assign(&self, key: &str) -> Option<&ExperimentArm> {
+        let total: u64 = self.arms.iter().map(|a| u64::from(a.weight)).sum();
+        let mut point = fnv1a(&format!("{}:{key}", self.name)) % total.max(1);
+        let weighted = self.arms.iter().filter(|a| a.weight > 0);
+        for arm in weighted.clone() {
+            if point < u64::from(arm.weight) {
+                return Some(arm);
+            }
+            point -= u64::from(arm.weight);
+        }
+        weighted.last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prx_memory_storage::TokenizerMode;
+
+    use super::*;
+
+    fn experiment(weights: &[(&str, u32)]) -> Experiment {
+        Experiment {
+            name: "rollout".to_string(),
+            arms: weights
+                .iter()
+                .map(|(id, weight)| ExperimentArm {
+                    id: (*id).to_string(),
+                    weight: *weight,
+                    config: (*id != "control").then_some(RankingConfig {
+                        tokenizer: TokenizerMode::CjkNgram,
+                    }),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn assignment_is_sticky_and_follows_weights() {
+        let split = experiment(&[("control", 80), ("cjk", 20)]);
+        let treated = (0..1000)
+            .filter(|i| split.assign(&format!("sess-{i}")).is_some_and(|arm| arm.id == "cjk"))
+            .count();
+        assert!((120..=280).contains(&treated), "treated={treated}");
+        assert_eq!(split.assign("sess-7"), split.assign("sess-7"));
+
+        let off = experiment(&[("control", 1), ("cjk", 0)]);
+        assert!((0..100).all(|i| off.assign(&format!("sess-{i}")).is_some_and(|arm| arm.id == "control")));
+    }
+
+    #[test]
+    fn rejects_duplicate_or_weightless_arms() {
+        assert!(experiment(&[("a", 1), ("a", 1)]).validate().is_err());
+        assert!(experiment(&[("a", 0)]).validate().is_err());
+        assert!(experiment(&[("control", 1), ("cjk", 1)]).validate().is_ok());
+    }
+}
@@ -0,0 +1,184 @@
+//! Relevance feedback from `memory_feedback`: per-entry useful/irrelevant tallies that can nudge
+//! recall scores, and per-query verdicts that can seed `memory_eval_baseline` golden sets.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use prx_memory_storage::atomic_write;
+use serde::{Deserialize, Serialize};
+
+use crate::eval::EvalCase;
+
+/// Newest per-query verdicts kept; entry tallies are not capped.
+const MAX_QUERIES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryFeedback {
+    pub useful: u64,
+    pub irrelevant: u64,
+    pub last_ms: u64,
+}
+
+impl EntryFeedback {
+    /// Smoothed net vote in `(-1, 1)`; two prior votes keep a single mark from swinging it fully.
+    pub fn net(self) -> f32 {
+        let as_f32 = |n: u64| f32::from(u16::try_from(n).unwrap_or(u16::MAX));
+        (as_f32(self.useful) - as_f32(self.irrelevant)) / (as_f32(self.useful) + as_f32(self.irrelevant) + 2.0)
+    }
+
+    /// Score multiplier for recall: `1 + weight * net`.
+    pub fn boost(self, weight: f32) -> f32 {
+        weight.mul_add(self.net(), 1.0)
+    }
+}
+
+/// Latest verdicts for one query; marking an id again replaces its earlier verdict.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryFeedback {
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub useful: Vec<String>,
+    pub irrelevant: Vec<String>,
+    pub updated_ms: u64,
+}
+
+impl QueryFeedback {
+    fn same_query(&self, query: &str, scope: Option<&str>, category: Option<&str>) -> bool {
+        self.query == query && self.scope.as_deref() == scope && self.category.as_deref() == category
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedbackData {
+    #[serde(default)]
+    pub entries: BTreeMap<String, EntryFeedback>,
+    #[serde(default)]
+    pub queries: Vec<QueryFeedback>,
+}
+
+impl FeedbackData {
+    /// Applies one `memory_feedback` call.
+    pub fn record(&mut self, verdict: &QueryFeedback) {
+        for (ids, useful) in [(&verdict.useful, true), (&verdict.irrelevant, false)] {
+            for mid in ids {
+                let tally = self.entries.entry(mid.clone()).or_default();
+                if useful {
+                    tally.useful += 1;
+                } else {
+                    tally.irrelevant += 1;
+                }
+                tally.last_ms = verdict.updated_ms;
+            }
+        }
+
+        let position = self
+            .queries
+            .iter()
+            .position(|q| q.same_query(&verdict.query, verdict.scope.as_deref(), verdict.category.as_deref()));
+        let mut merged = position.map_or_else(
+            || QueryFeedback {
+                query: verdict.query.clone(),
+                scope: verdict.scope.clone(),
+                category: verdict.category.clone(),
+                ..QueryFeedback::default()
+            },
+            |i| self.queries.remove(i),
+        );
+        merged.useful.retain(|id| !verdict.irrelevant.contains(id));
+        merged.irrelevant.retain(|id| !verdict.useful.contains(id));
+        for mid in &verdict.useful {
+            if !merged.useful.contains(mid) {
+                merged.useful.push(mid.clone());
+            }
+        }
+        for mid in &verdict.irrelevant {
+            if !merged.irrelevant.contains(mid) {
+                merged.irrelevant.push(mid.clone());
+            }
+        }
+        merged.updated_ms = verdict.updated_ms;
+        self.queries.push(merged);
+        if self.queries.len() > MAX_QUERIES {
+            let excess = self.queries.len() - MAX_QUERIES;
+            self.queries.drain(..excess);
+        }
+    }
+
+    /// Golden cases from queries with at least one useful entry, newest verdicts first.
+    pub fn eval_cases(&self) -> Vec<EvalCase> {
+        self.queries
+            .iter()
+            .rev()
+            .filter(|q| !q.useful.is_empty())
+            .map(|q| EvalCase {
+                query: q.query.clone(),
+                expected: q.useful.clone(),
+                scope: q.scope.clone(),
+                category: q.category.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Feedback persisted at `{db_path}.feedback.json`.
+#[derive(Debug)]
+pub struct FeedbackFile {
+    pub path: PathBuf,
+}
+
+impl FeedbackFile {
+    pub fn load(&self) -> Result<FeedbackData, String> {
+        match fs::read(&self.path) {
+            Ok(raw) => serde_json::from_slice(&raw).map_err(|e| format!("failed to parse feedback: {e}")),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(FeedbackData::default()),
+            Err(err) => Err(format!("failed to read feedback: {err}")),
+        }
+    }
+
+    pub fn save(&self, data: &FeedbackData) -> Result<(), String> {
+        let raw = serde_json::to_vec(data).map_err(|e| e.to_string())?;
+        atomic_write(&self.path, &raw).map_err(|e| format!("failed to write feedback: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verdict(query: &str, useful: &[&str], irrelevant: &[&str]) -> QueryFeedback {
+        QueryFeedback {
+            query: query.to_string(),
+            useful: useful.iter().map(|s| (*s).to_string()).collect(),
+            irrelevant: irrelevant.iter().map(|s| (*s).to_string()).collect(),
+            updated_ms: 1,
+            ..QueryFeedback::default()
+        }
+    }
+
+    #[test]
+    fn later_verdicts_replace_earlier_ones_per_query() {
+        let mut data = FeedbackData::default();
+        data.record(&verdict("ci flakes", &["m1"], &["m2"]));
+        data.record(&verdict("ci flakes", &["m2"], &["m3"]));
+        data.record(&verdict("release", &[], &["m1"]));
+
+        assert_eq!(data.queries.len(), 2);
+        let cases = data.eval_cases();
+        assert_eq!(cases.len(), 1);
+        assert_eq!(
+            cases.first().map(|c| c.expected.clone()),
+            Some(vec!["m1".to_string(), "m2".to_string()])
+        );
+
+        let m1 = data.entries.get("m1").copied().unwrap_or_default();
+        assert_eq!((m1.useful, m1.irrelevant), (1, 1));
+        assert!((m1.boost(0.5) - 1.0).abs() < 1e-6);
+        let m3 = data.entries.get("m3").copied().unwrap_or_default();
+        assert!(m3.boost(0.5) < 1.0);
+    }
+}
@@ -0,0 +1,233 @@
+//! Follower mode (`PRX_MEMORY_FOLLOW_URL`): tails another instance's `GET /changes` feed into the
+//! local store and serves reads from it, so several machines can share one agent's memory with
+//! local recall latency. Writes are refused; they belong on the leader.
+//!
+//! The cursor is kept in `<db>.follower.json`, so a restarted follower resumes where it stopped.
+//! Entries the leader held before it enabled its change log are not in the feed; seed the follower
+//! with a copy of the leader's store and start just after the leader's `change_seq` at copy time.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use prx_memory_storage::{ChangePage, StorageBackend, atomic_write};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// Tools a follower serves. Everything else changes the store or state that should stay in step
+/// with the leader, so it has to go to the leader.
+pub const READ_TOOLS: [&str; 18] = [
+    "memory_recall",
+    "memory_recall_context",
+    "memory_recall_plan",
+    "memory_list",
+    "memory_stats",
+    "memory_entities",
+    "memory_export",
+    "memory_review_list",
+    "memory_job_status",
+    "memory_quota_status",
+    "memory_decay_report",
+    "memory_staleness",
+    "memory_usage_report",
+    "memory_skill_manifest",
+    "memory_tool_schemas",
+    "memory_query_log",
+    "memory_index_status",
+    "memory_duplicates",
+];
+
+#[derive(Debug, Clone)]
+pub struct FollowerConfig {
+    leader: String,
+    token: Option<String>,
+    poll: Duration,
+    batch: usize,
+    start_from: u64,
+    cursor_path: PathBuf,
+}
+
+impl FollowerConfig {
+    /// `None` unless `PRX_MEMORY_FOLLOW_URL` is set. `PRX_MEMORY_FOLLOW_TOKEN` is sent as a bearer
+    /// token, `PRX_MEMORY_FOLLOW_POLL_MS` (default 1000) spaces the polls and
+    /// `PRX_MEMORY_FOLLOW_FROM` (default 1) is the first sequence number of a fresh follower.
+    pub fn from_env(db_path: &str) -> Result<Option<Self>, String> {
+        let Some(leader) = std::env::var("PRX_MEMORY_FOLLOW_URL")
+            .ok()
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())
+        else {
+            return Ok(None);
+        };
+        if !leader.starts_with("http://") && !leader.starts_with("https://") {
+            return Err(format!("PRX_MEMORY_FOLLOW_URL must be an http(s) URL, got {leader}"));
+        }
+        let start_from = match std::env::var("PRX_MEMORY_FOLLOW_FROM") {
+            Ok(raw) => raw
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("PRX_MEMORY_FOLLOW_FROM must be a sequence number, got {raw}"))?
+                .max(1),
+            Err(_) => 1,
+        };
+        let poll_ms = std::env::var("PRX_MEMORY_FOLLOW_POLL_MS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(1_000)
+            .clamp(50, 600_000);
+        Ok(Some(Self {
+            leader,
+            token: std::env::var("PRX_MEMORY_FOLLOW_TOKEN")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+            poll: Duration::from_millis(poll_ms),
+            batch: 500,
+            start_from,
+            cursor_path: PathBuf::from(format!("{db_path}.follower.json")),
+        }))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    leader: String,
+    next_from: u64,
+}
+
+#[derive(Debug, Default)]
+struct SyncStatus {
+    next_from: u64,
+    leader_seq: u64,
+    applied: u64,
+    last_sync_ms: Option<u64>,
+    last_error: Option<String>,
+}
+
+pub struct Follower {
+    config: FollowerConfig,
+    status: Mutex<SyncStatus>,
+}
+
+impl Follower {
+    /// Resumes from the saved cursor when it was written for the same leader.
+    pub fn new(config: FollowerConfig) -> Self {
+        let next_from = fs::read(&config.cursor_path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice::<Cursor>(&raw).ok())
+            .filter(|cursor| cursor.leader == config.leader)
+            .map_or(config.start_from, |cursor| cursor.next_from);
+        Self {
+            config,
+            status: Mutex::new(SyncStatus {
+                next_from,
+                ..SyncStatus::default()
+            }),
+        }
+    }
+
+    pub fn leader(&self) -> &str {
+        &self.config.leader
+    }
+
+    /// Sync progress for `memory_stats`; `lag` counts leader events not applied yet.
+    pub fn status_json(&self) -> Value {
+        let status = self.status.lock();
+        json!({
+            "leader": self.config.leader,
+            "next_from": status.next_from,
+            "leader_seq": status.leader_seq,
+            "lag": (status.leader_seq + 1).saturating_sub(status.next_from),
+            "applied": status.applied,
+            "last_sync_ms": status.last_sync_ms,
+            "last_error": status.last_error
+        })
+    }
+
+    /// Polls the leader on a background thread for the life of the process.
+    pub fn spawn(self: &Arc<Self>, rt: Arc<tokio::runtime::Runtime>, store: Arc<RwLock<Box<dyn StorageBackend>>>) {
+        let follower = Arc::clone(self);
+        std::thread::spawn(move || {
+            let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build();
+            let client = match client {
+                Ok(client) => client,
+                Err(err) => {
+                    follower.status.lock().last_error = Some(format!("http client init failed: {err}"));
+                    return;
+                }
+            };
+            loop {
+                let synced = follower.sync(&rt, &client, &store);
+                let mut status = follower.status.lock();
+                status.last_sync_ms = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .ok()
+                    .and_then(|d| u64::try_from(d.as_millis()).ok());
+                match synced {
+                    Ok(()) => status.last_error = None,
+                    Err(err) => {
+                        tracing::warn!(leader = %follower.config.leader, error = %err, "follower sync failed");
+                        status.last_error = Some(err);
+                    }
+                }
+                drop(status);
+                std::thread::sleep(follower.config.poll);
+            }
+        });
+    }
+
+    /// Applies pages until the follower has caught up with the leader.
+    fn sync(
+        &self,
+        rt: &tokio::runtime::Runtime,
+        client: &reqwest::Client,
+        store: &RwLock<Box<dyn StorageBackend>>,
+    ) -> Result<(), String> {
+        loop {
+            let from = self.status.lock().next_from;
+            let page = rt.block_on(self.fetch(client, from))?;
+            if !page.events.is_empty() {
+                store.write().apply_changes(&page.events).map_err(|e| e.to_string())?;
+                self.save_cursor(page.next_from)?;
+            }
+            let mut status = self.status.lock();
+            status.applied += u64::try_from(page.events.len()).unwrap_or(u64::MAX);
+            status.next_from = page.next_from;
+            status.leader_seq = page.latest_seq;
+            if page.events.is_empty() || page.next_from > page.latest_seq {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn fetch(&self, client: &reqwest::Client, from: u64) -> Result<ChangePage, String> {
+        let mut request = client.get(format!("{}/changes", self.config.leader)).query(&[
+            ("from", from),
+            ("limit", u64::try_from(self.config.batch).unwrap_or(500)),
+        ]);
+        if let Some(token) = &self.config.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(|e| format!("leader unreachable: {e}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("leader answered {status}: {}", body.trim()));
+        }
+        response
+            .json::<ChangePage>()
+            .await
+            .map_err(|e| format!("invalid change page: {e}"))
+    }
+
+    fn save_cursor(&self, next_from: u64) -> Result<(), String> {
+        let cursor = Cursor {
+            leader: self.config.leader.clone(),
+            next_from,
+        };
+        let raw = serde_json::to_vec_pretty(&cursor).map_err(|e| e.to_string())?;
+        atomic_write(&self.config.cursor_path, &raw).map_err(|e| format!("failed to write follower cursor: {e}"))
+    }
+}
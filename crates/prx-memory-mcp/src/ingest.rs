@@ -0,0 +1,273 @@
+//! Markdown note ingestion for `memory_ingest_files`: walking a vault, reading front-matter and
+//! splitting note bodies into overlapping chunks small enough for governed entries.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkOptions {
+    /// Maximum chunk length in characters, including the `title / heading:` prefix.
+    pub size: usize,
+    /// Characters of the previous chunk repeated at the start of the next one.
+    pub overlap: usize,
+}
+
+/// One parsed note: its chunks plus the tags derived from front-matter and path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Note {
+    pub rel_path: String,
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+    pub tool: Option<String>,
+    pub domain: Option<String>,
+    pub chunks: Vec<String>,
+}
+
+/// Markdown files under `root` in path order, skipping hidden entries such as `.obsidian` or `.git`.
+pub fn collect_notes(root: &Path, max_files: usize) -> io::Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+            {
+                out.push(path);
+            }
+        }
+    }
+    out.sort();
+    out.truncate(max_files);
+    Ok(out)
+}
+
+pub fn parse_note(rel_path: &str, content: &str, options: ChunkOptions) -> Note {
+    let (front_matter, body) = split_front_matter(content);
+    let mut note = Note {
+        rel_path: rel_path.to_string(),
+        ..Note::default()
+    };
+    let mut title = None;
+    for (key, values) in front_matter {
+        match key.as_str() {
+            "title" => title = values.into_iter().next(),
+            "tags" | "tag" | "keywords" => note.tags.extend(values),
+            "project" => note.project = values.into_iter().next(),
+            "tool" => note.tool = values.into_iter().next(),
+            "domain" => note.domain = values.into_iter().next(),
+            _ => {}
+        }
+    }
+    let mut dirs = rel_path.split('/').collect::<Vec<_>>();
+    let file_name = dirs.pop().unwrap_or(rel_path);
+    if note.domain.is_none() {
+        note.domain = dirs.first().map(|d| (*d).to_string());
+    }
+    note.tags
+        .push(format!("note:{}", rel_path.to_ascii_lowercase().replace(' ', "-")));
+
+    let title = title.unwrap_or_else(|| {
+        file_name
+            .rsplit_once('.')
+            .map_or(file_name, |(stem, _)| stem)
+            .to_string()
+    });
+    for (heading, text) in sections(body) {
+        let prefix = match heading {
+            Some(heading) if heading != title => format!("{title} / {heading}: "),
+            _ => format!("{title}: "),
+        };
+        note.chunks.extend(
+            chunk_text(
+                &text,
+                options.size.saturating_sub(prefix.chars().count()),
+                options.overlap,
+            )
+            .into_iter()
+            .map(|chunk| format!("{prefix}{chunk}")),
+        );
+    }
+    note
+}
+
+/// Splits `---`-delimited front-matter into `key -> values`. Supports the subset notes use:
+/// scalars, `[a, b]` inline lists, comma-separated strings and `- item` block lists.
+fn split_front_matter(content: &str) -> (Vec<(String, Vec<String>)>, &str) {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (Vec::new(), content);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (Vec::new(), content);
+    };
+    let (header, body) = rest.split_at(end);
+    let body = body
+        .trim_start_matches("\n---")
+        .trim_start_matches(['-', '\r'])
+        .trim_start_matches('\n');
+
+    let mut out: Vec<(String, Vec<String>)> = Vec::new();
+    for line in header.lines() {
+        let trimmed = line.trim();
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            if let Some((_, values)) = out.last_mut() {
+                values.extend(front_matter_values(item));
+            }
+        } else if let Some((key, value)) = trimmed.split_once(':') {
+            out.push((key.trim().to_ascii_lowercase(), front_matter_values(value)));
+        }
+    }
+    (out, body)
+}
+
+fn front_matter_values(raw: &str) -> Vec<String> {
+    let raw = raw.trim();
+    let raw = raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')).unwrap_or(raw);
+    raw.split(',')
+        .map(|v| v.trim().trim_matches(['"', '\'']).trim_start_matches('#').trim())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Body text grouped under its nearest heading. Fenced code blocks are dropped (governed entries
+/// reject raw code) and `[[target|alias]]` wiki links are reduced to their visible text.
+fn sections(body: &str) -> Vec<(Option<String>, String)> {
+    let mut out: Vec<(Option<String>, String)> = Vec::new();
+    let mut heading = None;
+    let mut text = String::new();
+    let mut in_fence = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if trimmed.starts_with('#') && trimmed.trim_start_matches('#').starts_with(' ') {
+            if !text.trim().is_empty() {
+                out.push((heading.clone(), std::mem::take(&mut text)));
+            }
+            text.clear();
+            heading = Some(trimmed.trim_start_matches('#').trim().to_string());
+            continue;
+        }
+        text.push_str(&strip_wiki_links(trimmed));
+        text.push('\n');
+    }
+    if !text.trim().is_empty() {
+        out.push((heading, text));
+    }
+    out
+}
+
+fn strip_wiki_links(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("[[") {
+        let Some(len) = rest.get(start + 2..).and_then(|r| r.find("]]")) else {
+            break;
+        };
+        out.push_str(rest.get(..start).unwrap_or_default());
+        let target = rest.get(start + 2..start + 2 + len).unwrap_or_default();
+        out.push_str(target.rsplit('|').next().unwrap_or(target));
+        rest = rest.get(start + 4 + len..).unwrap_or_default();
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Packs whitespace-separated words into chunks of at most `size` characters; each chunk after the
+/// first starts with the last words (up to `overlap` characters) of the previous one.
+fn chunk_text(text: &str, size: usize, overlap: usize) -> Vec<String> {
+    let size = size.max(1);
+    let mut chunks = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut len = 0usize;
+    for word in text.split_whitespace().flat_map(|w| split_long_word(w, size)) {
+        let word_len = word.chars().count();
+        if !current.is_empty() && len + 1 + word_len > size {
+            chunks.push(current.join(" "));
+            let mut carried = Vec::new();
+            let mut carried_len = 0usize;
+            for prev in current.iter().rev() {
+                let next_len = carried_len + prev.chars().count() + usize::from(carried_len > 0);
+                if next_len > overlap || next_len + 1 + word_len > size {
+                    break;
+                }
+                carried.push(prev.clone());
+                carried_len = next_len;
+            }
+            carried.reverse();
+            current = carried;
+            len = carried_len;
+        }
+        len += word_len + usize::from(!current.is_empty());
+        current.push(word);
+    }
+    if !current.is_empty() {
+        chunks.push(current.join(" "));
+    }
+    chunks
+}
+
+fn split_long_word(word: &str, size: usize) -> Vec<String> {
+    let chars = word.chars().collect::<Vec<_>>();
+    chars.chunks(size).map(|c| c.iter().collect()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn note_tags_come_from_front_matter_and_path() {
+        let content = "---\ntitle: Release checklist\ntags: [rust, \"#ci\"]\nproject: prx-memory\naliases:\n  - releases\n---\n# Release checklist\nBump versions, then [[Changelog|update the changelog]].\n\n```bash\ncargo publish\n```\n## Rollback\nRevert the tag and yank the crate.\n";
+        let note = parse_note(
+            "ops/Release checklist.md",
+            content,
+            ChunkOptions { size: 200, overlap: 20 },
+        );
+        assert_eq!(note.tags, ["rust", "ci", "note:ops/release-checklist.md"]);
+        assert_eq!(note.project.as_deref(), Some("prx-memory"));
+        assert_eq!(note.domain.as_deref(), Some("ops"));
+        assert_eq!(
+            note.chunks,
+            [
+                "Release checklist: Bump versions, then update the changelog.",
+                "Release checklist / Rollback: Revert the tag and yank the crate."
+            ]
+        );
+    }
+
+    #[test]
+    fn chunks_respect_size_and_overlap() {
+        let text = "one two three four five six seven eight nine ten";
+        let chunks = chunk_text(text, 20, 9);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 20));
+        assert_eq!(
+            chunks,
+            [
+                "one two three four",
+                "four five six seven",
+                "six seven eight nine",
+                "nine ten"
+            ]
+        );
+        assert_eq!(chunk_text("abcdefghij", 4, 0), ["abcd", "efgh", "ij"]);
+    }
+}
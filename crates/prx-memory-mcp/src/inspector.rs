@@ -0,0 +1,162 @@
+//! Curation model behind the `prx-memory-tui` inspector.
+//!
+//! Every operation goes through the MCP tool surface of an in-process [`McpServer`], so scope
+//! ACLs, governance, tool policy and metrics apply to a human curator exactly as to an agent.
+
+use prx_memory_storage::MemoryEntry;
+use serde_json::{Value, json};
+
+use crate::McpServer;
+use crate::protocol::JsonRpcRequest;
+
+/// One memory row as shown by the inspector, with its recall score when it came from a search.
+#[derive(Debug, Clone)]
+pub struct InspectorEntry {
+    pub entry: MemoryEntry,
+    pub score: Option<f64>,
+    pub explain: Option<Value>,
+}
+
+pub struct Inspector<'a> {
+    server: &'a McpServer,
+    next_id: u64,
+}
+
+impl<'a> Inspector<'a> {
+    pub const fn new(server: &'a McpServer) -> Self {
+        Self { server, next_id: 1 }
+    }
+
+    /// Scopes visible to the configured agent with their entry counts, sorted by name.
+    pub fn scopes(&mut self) -> Result<Vec<(String, u64)>, String> {
+        let stats = self.call("memory_stats", &json!({}))?;
+        let mut scopes = stats
+            .get("scope_counts")
+            .and_then(Value::as_object)
+            .map(|counts| {
+                counts
+                    .iter()
+                    .map(|(scope, count)| (scope.clone(), count.as_u64().unwrap_or(0)))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        scopes.sort();
+        Ok(scopes)
+    }
+
+    /// Newest-first entries, optionally restricted to one scope.
+    pub fn list(&mut self, scope: Option<&str>, limit: usize) -> Result<Vec<InspectorEntry>, String> {
+        let listed = self.call("memory_list", &json!({"scope": scope, "limit": limit}))?;
+        items(&listed, |item| {
+            Some(InspectorEntry {
+                entry: serde_json::from_value(item.clone()).ok()?,
+                score: None,
+                explain: None,
+            })
+        })
+    }
+
+    /// Recall with score explanations, as an agent would see it.
+    pub fn search(&mut self, query: &str, scope: Option<&str>, limit: usize) -> Result<Vec<InspectorEntry>, String> {
+        let recalled = self.call(
+            "memory_recall",
+            &json!({"query": query, "scope": scope, "limit": limit, "explain": true}),
+        )?;
+        items(&recalled, |item| {
+            Some(InspectorEntry {
+                entry: serde_json::from_value(item.get("entry")?.clone()).ok()?,
+                score: item.get("score").and_then(Value::as_f64),
+                explain: item.get("explain").cloned(),
+            })
+        })
+    }
+
+    /// Deletes one entry; `Ok(false)` when it was already gone.
+    pub fn forget(&mut self, id: &str) -> Result<bool, String> {
+        let forgotten = self.call("memory_forget", &json!({"id": id}))?;
+        Ok(forgotten.get("deleted").and_then(Value::as_bool).unwrap_or(false))
+    }
+
+    /// Merges `ids` into one governed entry and returns the new entry's id.
+    pub fn merge(&mut self, ids: &[String]) -> Result<String, String> {
+        let merged = self.call("memory_merge", &json!({"ids": ids}))?;
+        merged
+            .get("entry")
+            .and_then(|entry| entry.get("id"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| "memory_merge returned no entry".to_string())
+    }
+
+    fn call(&mut self, tool: &str, arguments: &Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(id)),
+            method: "tools/call".to_string(),
+            params: json!({"name": tool, "arguments": arguments}),
+        };
+        let response = self
+            .server
+            .handle_request(request)
+            .ok_or_else(|| format!("{tool} returned no response"))?;
+        if let Some(err) = response.error {
+            return Err(format!("{tool} failed ({}): {}", err.code, err.message));
+        }
+        let result = response.result.unwrap_or(Value::Null);
+        Ok(result.get("structuredContent").cloned().unwrap_or(result))
+    }
+}
+
+fn items(content: &Value, parse: impl Fn(&Value) -> Option<InspectorEntry>) -> Result<Vec<InspectorEntry>, String> {
+    content
+        .get("items")
+        .and_then(Value::as_array)
+        .map(|rows| rows.iter().filter_map(parse).collect())
+        .ok_or_else(|| "tool result has no items".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inspector_lists_searches_merges_and_forgets() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let db_path = std::env::temp_dir().join(format!("prx-memory-inspector-{now}.json"));
+        let server = McpServer::with_db_path(db_path.display().to_string()).expect("server");
+        let mut inspector = Inspector::new(&server);
+        for text in [
+            "Pitfall: cargo caches stale lockfiles. Fix: run cargo update after bumping versions.",
+            "Pitfall: stale lockfiles break CI builds. Fix: commit Cargo.lock with the version bump.",
+        ] {
+            inspector
+                .call(
+                    "memory_store",
+                    &json!({"text": text, "category": "fact", "scope": "global"}),
+                )
+                .expect("store");
+        }
+
+        assert_eq!(inspector.scopes().expect("scopes"), vec![("global".to_string(), 2)]);
+        let listed = inspector.list(Some("global"), 10).expect("list");
+        assert_eq!(listed.len(), 2);
+
+        let found = inspector.search("stale lockfiles", None, 5).expect("search");
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|hit| hit.score.is_some() && hit.explain.is_some()));
+
+        let ids = listed.iter().map(|e| e.entry.id.clone()).collect::<Vec<_>>();
+        let merged_id = inspector.merge(&ids).expect("merge");
+        assert_eq!(inspector.list(None, 10).expect("list after merge").len(), 1);
+
+        assert!(inspector.forget(&merged_id).expect("forget"));
+        assert!(inspector.list(None, 10).expect("list after forget").is_empty());
+        assert!(!inspector.forget(&merged_id).expect("forget again"));
+
+        let _ = std::fs::remove_file(db_path);
+    }
+}
@@ -0,0 +1,180 @@
+//! Undo log for a group of writes made under one store lock, so multi-step tools such as
+//! `memory_store_dual` and `memory_transaction` can put the store back when a later step fails.
+//!
+//! Stores are undone by forgetting the new entry. Updates and deletes are undone by writing the
+//! old content back with its relations; the backends assign ids on write, so those entries come
+//! back under new ids.
+
+use std::collections::HashMap;
+
+use prx_memory_storage::{MemoryEntry, MemoryRelation, NewMemoryEntry, StorageBackend};
+
+enum Undo {
+    Stored(String),
+    Replaced {
+        new_id: String,
+        previous: MemoryEntry,
+    },
+    Forgotten {
+        entry: MemoryEntry,
+        relations: Vec<MemoryRelation>,
+    },
+}
+
+#[derive(Default)]
+pub struct WriteJournal {
+    undo: Vec<Undo>,
+}
+
+impl WriteJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stored(&mut self, id: &str) {
+        self.undo.push(Undo::Stored(id.to_string()));
+    }
+
+    /// `previous` was rewritten as `new_id`.
+    pub fn replaced(&mut self, previous: MemoryEntry, new_id: &str) {
+        self.undo.push(Undo::Replaced {
+            new_id: new_id.to_string(),
+            previous,
+        });
+    }
+
+    /// Call before forgetting `entry`, while its relations are still in the store.
+    pub fn forgetting(&mut self, store: &dyn StorageBackend, entry: MemoryEntry) {
+        let relations = store.relations_for(&entry.id);
+        self.undo.push(Undo::Forgotten { entry, relations });
+    }
+
+    pub fn len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Undoes every recorded write, newest first, and returns what could not be undone.
+    pub fn rollback(self, store: &mut dyn StorageBackend) -> Vec<String> {
+        let mut errors = Vec::new();
+        // Entries written back get new ids; later relation edges must point at those.
+        let mut restored_ids: HashMap<String, String> = HashMap::new();
+        for step in self.undo.into_iter().rev() {
+            match step {
+                Undo::Stored(id) => {
+                    if let Err(err) = store.forget_by_id(&id) {
+                        errors.push(format!("{id}: {err}"));
+                    }
+                }
+                Undo::Replaced { new_id, previous } => {
+                    let relations = store.relations_for(&new_id);
+                    match store.replace(&new_id, new_entry_from(&previous)) {
+                        Ok(Some(restored)) => {
+                            relink(store, &relations, &new_id, &restored.id, &restored_ids);
+                            restored_ids.insert(previous.id, restored.id);
+                        }
+                        Ok(None) => errors.push(format!("{new_id}: missing during rollback")),
+                        Err(err) => errors.push(format!("{new_id}: {err}")),
+                    }
+                }
+                Undo::Forgotten { entry, relations } => match store.store(new_entry_from(&entry)) {
+                    Ok(restored) => {
+                        relink(store, &relations, &entry.id, &restored.id, &restored_ids);
+                        restored_ids.insert(entry.id, restored.id);
+                    }
+                    Err(err) => errors.push(format!("{}: {err}", entry.id)),
+                },
+            }
+        }
+        errors
+    }
+}
+
+fn new_entry_from(entry: &MemoryEntry) -> NewMemoryEntry {
+    NewMemoryEntry {
+        text: entry.text.clone(),
+        category: entry.category.clone(),
+        scope: entry.scope.clone(),
+        importance: entry.importance,
+        tags: entry.tags.clone(),
+        embedding: entry.embedding.clone(),
+        embedding_model: entry.embedding_model.clone(),
+        source: entry.source.clone(),
+        keywords: entry.keywords.clone(),
+    }
+}
+
+/// Re-adds `relations` of `old_id` on `new_id`, following ids already restored by the rollback.
+fn relink(
+    store: &mut dyn StorageBackend,
+    relations: &[MemoryRelation],
+    old_id: &str,
+    new_id: &str,
+    restored_ids: &HashMap<String, String>,
+) {
+    let resolve = |id: &str| {
+        if id == old_id {
+            new_id.to_string()
+        } else {
+            restored_ids.get(id).cloned().unwrap_or_else(|| id.to_string())
+        }
+    };
+    for edge in relations {
+        let _ = store.link(&resolve(&edge.from_id), &edge.relation, &resolve(&edge.to_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prx_memory_storage::PersistentMemoryStore;
+
+    use super::*;
+
+    fn entry(text: &str) -> NewMemoryEntry {
+        NewMemoryEntry {
+            text: text.to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: Vec::new(),
+            embedding: None,
+            embedding_model: None,
+            source: None,
+            keywords: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rollback_restores_content_and_relations() {
+        let path = std::env::temp_dir().join(format!("prx-journal-{}.json", std::process::id()));
+        let mut store = PersistentMemoryStore::open(&path).expect("store");
+        let kept = store.store(entry("kept entry")).expect("kept");
+        let doomed = store.store(entry("doomed entry")).expect("doomed");
+        store.link(&kept.id, "related-to", &doomed.id).expect("link");
+
+        let mut journal = WriteJournal::new();
+        let added = store.store(entry("added entry")).expect("added");
+        journal.stored(&added.id);
+        let updated = store
+            .replace(&kept.id, entry("kept entry, edited"))
+            .expect("replace")
+            .expect("kept");
+        store.link(&updated.id, "related-to", &doomed.id).expect("carry link");
+        journal.replaced(kept.clone(), &updated.id);
+        journal.forgetting(&store, doomed.clone());
+        store.forget_by_id(&doomed.id).expect("forget");
+
+        assert_eq!(journal.len(), 3);
+        assert!(journal.rollback(&mut store).is_empty());
+        let texts = store.list(10).into_iter().map(|e| e.text).collect::<Vec<_>>();
+        assert_eq!(texts.len(), 2);
+        assert!(texts.contains(&"kept entry".to_string()));
+        assert!(texts.contains(&"doomed entry".to_string()));
+        let restored = store
+            .list(10)
+            .into_iter()
+            .find(|e| e.text == "kept entry")
+            .expect("restored");
+        assert_eq!(store.relations_for(&restored.id).len(), 1);
+        let _ = std::fs::remove_file(path);
+    }
+}
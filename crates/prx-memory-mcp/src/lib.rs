@@ -1,4 +1,31 @@
+#![recursion_limit = "512"]
+
+mod adapters;
+mod context;
+mod distill;
+mod eval;
+mod experiment;
+mod feedback;
+mod follower;
+mod ingest;
+pub mod inspector;
+mod journal;
+pub mod logging;
+#[cfg(feature = "otel")]
+pub mod otel;
+mod packing;
 pub mod protocol;
+mod query_log;
+mod redact;
+mod render;
+mod secondary;
 pub mod server;
+mod session_log;
+mod team_sync;
+mod tenants;
+mod tls;
+mod tool_schemas;
+mod transfer;
+mod usage_log;
 
 pub use server::McpServer;
@@ -0,0 +1,244 @@
+//! Structured logging for the daemon: `tracing` spans and events written to stderr.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry};
+
+/// Installs the global subscriber.
+///
+/// `PRX_LOG` is an env-filter directive (default `info`); `PRX_LOG_FORMAT=json|text` picks the
+/// output format (default `json`). Logs always go to stderr because stdout carries the stdio transport.
+pub fn init() {
+    let filter = EnvFilter::try_from_env("PRX_LOG").unwrap_or_else(|_| EnvFilter::new("info"));
+    let format = std::env::var("PRX_LOG_FORMAT").unwrap_or_default();
+    let registry = Registry::default().with(filter);
+    #[cfg(feature = "otel")]
+    let registry = registry.with(crate::otel::trace_layer());
+    // A subscriber may already be installed (e.g. by an embedding host); keep it.
+    let _ = if format.eq_ignore_ascii_case("text") {
+        registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(io::stderr)
+                    .with_span_events(FmtSpan::CLOSE),
+            )
+            .try_init()
+    } else {
+        registry.with(JsonLayer::new(io::stderr)).try_init()
+    };
+}
+
+/// Writes one JSON object per event, plus one per closed span carrying its `elapsed_ms`.
+pub struct JsonLayer<W> {
+    make_writer: W,
+}
+
+impl<W> JsonLayer<W>
+where
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    pub const fn new(make_writer: W) -> Self {
+        Self { make_writer }
+    }
+
+    fn emit(&self, line: Map<String, Value>) {
+        let mut out = self.make_writer.make_writer();
+        let _ = writeln!(out, "{}", Value::Object(line));
+    }
+}
+
+struct SpanData {
+    fields: Map<String, Value>,
+    started: Instant,
+}
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        attrs.record(&mut JsonVisitor(&mut fields));
+        span.extensions_mut().insert(SpanData {
+            fields,
+            started: Instant::now(),
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(data) = span.extensions_mut().get_mut::<SpanData>()
+        {
+            values.record(&mut JsonVisitor(&mut data.fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+        let message = fields.remove("message").unwrap_or(Value::Null);
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| scope.from_root().map(|span| span_json(&span)).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let meta = event.metadata();
+        let mut line = log_header(*meta.level(), meta.target());
+        line.insert("message".to_string(), message);
+        if !fields.is_empty() {
+            line.insert("fields".to_string(), Value::Object(fields));
+        }
+        if !spans.is_empty() {
+            line.insert("spans".to_string(), Value::Array(spans));
+        }
+        self.emit(line);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let elapsed_ms = span
+            .extensions()
+            .get::<SpanData>()
+            .map_or(0.0, |data| data.started.elapsed().as_secs_f64() * 1000.0);
+        let parents = span
+            .parent()
+            .map(|parent| parent.scope().from_root().map(|s| span_json(&s)).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let meta = span.metadata();
+        let mut line = log_header(*meta.level(), meta.target());
+        line.insert("message".to_string(), Value::String("close".to_string()));
+        line.insert("span".to_string(), span_json(&span));
+        line.insert("elapsed_ms".to_string(), Value::from(elapsed_ms));
+        if !parents.is_empty() {
+            line.insert("spans".to_string(), Value::Array(parents));
+        }
+        self.emit(line);
+    }
+}
+
+fn log_header(level: tracing::Level, target: &str) -> Map<String, Value> {
+    let ts_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+    let mut line = Map::new();
+    line.insert("ts_ms".to_string(), Value::from(ts_ms));
+    line.insert("level".to_string(), Value::String(level.to_string()));
+    line.insert("target".to_string(), Value::String(target.to_string()));
+    line
+}
+
+fn span_json<S>(span: &SpanRef<'_, S>) -> Value
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    let mut out = Map::new();
+    out.insert("name".to_string(), Value::String(span.name().to_string()));
+    if let Some(data) = span.extensions().get::<SpanData>() {
+        out.extend(data.fields.clone());
+    }
+    Value::Object(out)
+}
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), Value::String(format!("{value:?}")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_layer_writes_events_with_span_context() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = Registry::default().with(JsonLayer::new(move || writer.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let _rpc = tracing::info_span!("rpc", method = "tools/call").entered();
+            let _tool = tracing::info_span!("tool", tool = "memory_recall").entered();
+            tracing::warn!(code = -32002_i64, "tool call failed");
+        });
+
+        let text = String::from_utf8(captured.0.lock().clone()).expect("utf8 log output");
+        let lines = text
+            .lines()
+            .map(|l| serde_json::from_str::<Value>(l).expect("json log line"))
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+
+        let event = &lines[0];
+        assert_eq!(event["level"], "WARN");
+        assert_eq!(event["message"], "tool call failed");
+        assert_eq!(event["fields"]["code"], -32002);
+        assert_eq!(event["spans"][0]["name"], "rpc");
+        assert_eq!(event["spans"][0]["method"], "tools/call");
+        assert_eq!(event["spans"][1]["name"], "tool");
+
+        assert_eq!(lines[1]["message"], "close");
+        assert_eq!(lines[1]["span"]["name"], "tool");
+        assert_eq!(lines[1]["spans"][0]["name"], "rpc");
+        assert!(lines[1]["elapsed_ms"].as_f64().is_some());
+        assert_eq!(lines[2]["span"]["name"], "rpc");
+    }
+}
@@ -1,6 +1,7 @@
 use prx_memory_mcp::McpServer;
 
 fn main() -> std::io::Result<()> {
+    prx_memory_mcp::logging::init();
     let server = McpServer::new().map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
     server.serve_stdio()
 }
@@ -0,0 +1,548 @@
+//! OTLP/HTTP (JSON encoding) export of tool/stage metrics and request traces.
+//!
+//! Enabled by the `otel` feature and `OTEL_EXPORTER_OTLP_ENDPOINT`. Finished `tracing` spans are
+//! buffered by [`trace_layer`] and pushed, together with a metrics snapshot, every
+//! `OTEL_METRIC_EXPORT_INTERVAL` milliseconds through a `reqwest` client on the daemon's runtime,
+//! so collectors may be reached over `http://` or `https://`.
+
+use std::collections::VecDeque;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde_json::{Map, Value, json};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+const SCOPE_NAME: &str = "prx-memory-mcp";
+const MAX_BUFFERED_SPANS: usize = 4096;
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct OtlpConfig {
+    /// Endpoint URL without a trailing `/`; signal paths such as `/v1/metrics` are appended.
+    endpoint: String,
+    headers: Vec<(String, String)>,
+    service_name: String,
+    interval: Duration,
+}
+
+impl OtlpConfig {
+    /// Reads the standard `OTEL_*` variables. Returns `Ok(None)` when no endpoint is configured.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(endpoint) = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let url = reqwest::Url::parse(endpoint.trim())
+            .map_err(|e| format!("OTEL_EXPORTER_OTLP_ENDPOINT is not a URL ({e}): {endpoint}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "OTEL_EXPORTER_OTLP_ENDPOINT must be an http:// or https:// URL: {endpoint}"
+            ));
+        }
+        if url.host_str().is_none_or(str::is_empty) {
+            return Err(format!("OTEL_EXPORTER_OTLP_ENDPOINT has no host: {endpoint}"));
+        }
+        let headers = std::env::var("OTEL_EXPORTER_OTLP_HEADERS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .filter(|(k, _)| !k.is_empty())
+            .collect();
+        let interval_ms = std::env::var("OTEL_METRIC_EXPORT_INTERVAL")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60_000)
+            .clamp(1_000, 3_600_000);
+        Ok(Some(Self {
+            endpoint: url.as_str().trim_end_matches('/').to_string(),
+            headers,
+            service_name: std::env::var("OTEL_SERVICE_NAME")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| "prx-memory".to_string()),
+            interval: Duration::from_millis(interval_ms),
+        }))
+    }
+}
+
+/// Spawns the background exporter, whose requests run on `rt`. `collect_metrics` returns OTLP
+/// metric objects built with [`sum_metric`] / [`histogram_metric`].
+pub fn spawn_exporter<F>(config: OtlpConfig, rt: Arc<tokio::runtime::Runtime>, collect_metrics: F)
+where
+    F: Fn() -> Vec<Value> + Send + 'static,
+{
+    start_nanos();
+    let client = match reqwest::Client::builder().timeout(EXPORT_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to start OTLP exporter");
+            return;
+        }
+    };
+    let spawned = std::thread::Builder::new()
+        .name("prx-memory-otlp".to_string())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(config.interval);
+                rt.block_on(export_once(&client, &config, &collect_metrics()));
+            }
+        });
+    if let Err(err) = spawned {
+        tracing::warn!(error = %err, "failed to start OTLP exporter");
+    }
+}
+
+async fn export_once(client: &reqwest::Client, config: &OtlpConfig, metrics: &[Value]) {
+    let resource = json!({
+        "attributes": [attr("service.name", &config.service_name)]
+    });
+    if !metrics.is_empty() {
+        let body = json!({
+            "resourceMetrics": [{
+                "resource": resource,
+                "scopeMetrics": [{"scope": {"name": SCOPE_NAME}, "metrics": metrics}]
+            }]
+        });
+        if let Err(err) = post_json(client, config, "/v1/metrics", &body).await {
+            tracing::warn!(error = %err, "OTLP metrics export failed");
+        }
+    }
+    let spans = drain_spans();
+    if !spans.is_empty() {
+        let body = json!({
+            "resourceSpans": [{
+                "resource": resource,
+                "scopeSpans": [{"scope": {"name": SCOPE_NAME}, "spans": spans}]
+            }]
+        });
+        if let Err(err) = post_json(client, config, "/v1/traces", &body).await {
+            tracing::warn!(error = %err, "OTLP trace export failed");
+        }
+    }
+}
+
+async fn post_json(
+    client: &reqwest::Client,
+    config: &OtlpConfig,
+    signal_path: &str,
+    body: &Value,
+) -> Result<(), String> {
+    let mut request = client.post(format!("{}{signal_path}", config.endpoint)).json(body);
+    for (name, value) in &config.headers {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("collector unreachable: {e}"))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(format!("collector answered {status}"))
+    }
+}
+
+fn attr(key: &str, value: &str) -> Value {
+    json!({"key": key, "value": {"stringValue": value}})
+}
+
+fn attrs(labels: &[(&str, &str)]) -> Vec<Value> {
+    labels.iter().map(|(k, v)| attr(k, v)).collect()
+}
+
+pub fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX))
+}
+
+/// Start timestamp shared by all cumulative metrics, pinned when the exporter starts.
+fn start_nanos() -> u64 {
+    static START: OnceLock<u64> = OnceLock::new();
+    *START.get_or_init(unix_nanos)
+}
+
+/// Label set and running total of one counter series.
+pub type CounterPoint<'a> = (Vec<(&'a str, &'a str)>, u64);
+/// Label set, count, sum, max (milliseconds) and per-bound bucket counts of one latency series.
+pub type LatencyPoint<'a> = (Vec<(&'a str, &'a str)>, u64, f64, f64, &'a [u64]);
+
+/// A cumulative monotonic counter.
+pub fn sum_metric(name: &str, points: &[CounterPoint<'_>]) -> Value {
+    let (start, now) = (start_nanos(), unix_nanos());
+    let data_points = points
+        .iter()
+        .map(|(labels, value)| {
+            json!({
+                "attributes": attrs(labels),
+                "startTimeUnixNano": start.to_string(),
+                "timeUnixNano": now.to_string(),
+                "asInt": value.to_string()
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "name": name,
+        "sum": {"aggregationTemporality": 2, "isMonotonic": true, "dataPoints": data_points}
+    })
+}
+
+/// A cumulative explicit-bucket latency histogram in milliseconds.
+pub fn histogram_metric(name: &str, bounds: &[f64], points: &[LatencyPoint<'_>]) -> Value {
+    let (start, now) = (start_nanos(), unix_nanos());
+    let data_points = points
+        .iter()
+        .map(|(labels, count, sum, max, buckets)| {
+            let mut bucket_counts = (0..bounds.len())
+                .map(|idx| buckets.get(idx).copied().unwrap_or(0))
+                .collect::<Vec<_>>();
+            let overflow = count.saturating_sub(bucket_counts.iter().sum());
+            bucket_counts.push(overflow);
+            json!({
+                "attributes": attrs(labels),
+                "startTimeUnixNano": start.to_string(),
+                "timeUnixNano": now.to_string(),
+                "count": count.to_string(),
+                "sum": sum,
+                "max": max,
+                "bucketCounts": bucket_counts.iter().map(u64::to_string).collect::<Vec<_>>(),
+                "explicitBounds": bounds
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "name": name,
+        "unit": "ms",
+        "histogram": {"aggregationTemporality": 2, "dataPoints": data_points}
+    })
+}
+
+/// Appends a tenant's metrics to `into`, tagging each data point with a `tenant` attribute and
+/// folding it into the metric of the same name so every series stays in one OTLP metric.
+pub fn merge_tenant_metrics(into: &mut Vec<Value>, tenant: &str, metrics: Vec<Value>) {
+    for mut metric in metrics {
+        let Some(name) = metric.get("name").and_then(Value::as_str).map(str::to_string) else {
+            continue;
+        };
+        let kind = if metric.get("sum").is_some() {
+            "sum"
+        } else {
+            "histogram"
+        };
+        let Some(Value::Array(points)) = metric.get_mut(kind).and_then(|data| data.get_mut("dataPoints")) else {
+            continue;
+        };
+        for point in points.iter_mut() {
+            if let Some(Value::Array(attributes)) = point.get_mut("attributes") {
+                attributes.insert(0, attr("tenant", tenant));
+            }
+        }
+        let target = into
+            .iter_mut()
+            .find(|existing| existing.get("name").and_then(Value::as_str) == Some(name.as_str()))
+            .and_then(|existing| existing.get_mut(kind))
+            .and_then(|data| data.get_mut("dataPoints"))
+            .and_then(Value::as_array_mut);
+        match target {
+            Some(existing) => existing.append(points),
+            None => into.push(metric),
+        }
+    }
+}
+
+fn span_buffer() -> &'static Mutex<VecDeque<Value>> {
+    static BUFFER: OnceLock<Mutex<VecDeque<Value>>> = OnceLock::new();
+    BUFFER.get_or_init(|| Mutex::new(VecDeque::new()))
+}
+
+fn drain_spans() -> Vec<Value> {
+    span_buffer().lock().drain(..).collect()
+}
+
+/// Returns the span-collecting layer when an OTLP endpoint is configured.
+pub fn trace_layer() -> Option<OtlpTraceLayer> {
+    OtlpConfig::from_env().ok().flatten().map(|_| OtlpTraceLayer {
+        ids: RandomState::new(),
+    })
+}
+
+/// Records finished spans as OTLP span objects. Trace ids are inherited from the root span.
+pub struct OtlpTraceLayer {
+    ids: RandomState,
+}
+
+impl OtlpTraceLayer {
+    fn random_id(&self, salt: u64) -> u64 {
+        self.ids.hash_one((salt, unix_nanos())) | 1
+    }
+}
+
+struct OtlpSpan {
+    trace_id: String,
+    span_id: String,
+    parent_span_id: Option<String>,
+    start: u64,
+    attributes: Map<String, Value>,
+    error: Option<String>,
+}
+
+impl<S> Layer<S> for OtlpTraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.parent().and_then(|parent| {
+            parent
+                .extensions()
+                .get::<OtlpSpan>()
+                .map(|p| (p.trace_id.clone(), p.span_id.clone()))
+        });
+        let (trace_id, parent_span_id) = match parent {
+            Some((trace_id, parent_id)) => (trace_id, Some(parent_id)),
+            None => (
+                format!(
+                    "{:016x}{:016x}",
+                    self.random_id(id.into_u64()),
+                    self.random_id(!id.into_u64())
+                ),
+                None,
+            ),
+        };
+        let mut attributes = Map::new();
+        attrs.record(&mut AttrVisitor(&mut attributes));
+        span.extensions_mut().insert(OtlpSpan {
+            trace_id,
+            span_id: format!("{:016x}", self.random_id(id.into_u64())),
+            parent_span_id,
+            start: unix_nanos(),
+            attributes,
+            error: None,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(data) = span.extensions_mut().get_mut::<OtlpSpan>()
+        {
+            values.record(&mut AttrVisitor(&mut data.attributes));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() > Level::WARN {
+            return;
+        }
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        let mut fields = Map::new();
+        event.record(&mut AttrVisitor(&mut fields));
+        let message = ["error", "message"]
+            .iter()
+            .find_map(|key| fields.get(*key)?.get("stringValue")?.as_str())
+            .unwrap_or("error")
+            .to_string();
+        if let Some(data) = span.extensions_mut().get_mut::<OtlpSpan>() {
+            data.error = Some(message);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(data) = span.extensions_mut().remove::<OtlpSpan>() else {
+            return;
+        };
+        let mut out = json!({
+            "traceId": data.trace_id,
+            "spanId": data.span_id,
+            "name": span.name(),
+            "kind": 1,
+            "startTimeUnixNano": data.start.to_string(),
+            "endTimeUnixNano": unix_nanos().to_string(),
+            "attributes": data
+                .attributes
+                .into_iter()
+                .map(|(key, value)| json!({"key": key, "value": value}))
+                .collect::<Vec<_>>(),
+            "status": data
+                .error
+                .map_or_else(|| json!({"code": 0}), |message| json!({"code": 2, "message": message}))
+        });
+        if let (Some(parent), Some(obj)) = (data.parent_span_id, out.as_object_mut()) {
+            obj.insert("parentSpanId".to_string(), Value::String(parent));
+        }
+        let mut buffer = span_buffer().lock();
+        if buffer.len() >= MAX_BUFFERED_SPANS {
+            buffer.pop_front();
+        }
+        buffer.push_back(out);
+    }
+}
+
+/// Collects fields as OTLP `AnyValue` objects.
+struct AttrVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for AttrVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!({"doubleValue": value}));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0
+            .insert(field.name().to_string(), json!({"intValue": value.to_string()}));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0
+            .insert(field.name().to_string(), json!({"intValue": value.to_string()}));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!({"boolValue": value}));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!({"stringValue": value}));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!({"stringValue": format!("{value:?}")}));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    use tracing_subscriber::Registry;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    fn read_request(listener: &TcpListener) -> (String, Value) {
+        let (stream, _) = listener.accept().expect("collector accept");
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).expect("request line");
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).expect("header");
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some(v) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                content_length = v.trim().parse().expect("content length");
+            }
+        }
+        let mut body = vec![0_u8; content_length];
+        reader.read_exact(&mut body).expect("body");
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .expect("response");
+        (request_line, serde_json::from_slice(&body).expect("json body"))
+    }
+
+    #[test]
+    fn exporter_posts_metrics_and_nested_spans() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind collector");
+        let config = OtlpConfig {
+            endpoint: format!("http://{}", listener.local_addr().expect("addr")),
+            headers: vec![("x-tenant".to_string(), "prx".to_string())],
+            service_name: "prx-memory-test".to_string(),
+            interval: Duration::from_secs(30),
+        };
+
+        let layer = OtlpTraceLayer {
+            ids: RandomState::new(),
+        };
+        tracing::subscriber::with_default(Registry::default().with(layer), || {
+            let _rpc = tracing::info_span!("rpc", method = "tools/call").entered();
+            let _provider = tracing::info_span!("provider", kind = "embed").entered();
+            tracing::warn!(error = "upstream 503", "embedding provider call failed");
+        });
+
+        let metrics = [
+            sum_metric("prx_memory_tool_calls_total", &[(vec![("tool", "memory_recall")], 3)]),
+            histogram_metric(
+                "prx_memory_tool_latency_ms",
+                &[5.0, 10.0],
+                &[(vec![("tool", "memory_recall")], 3, 12.5, 7.0, &[1, 1])],
+            ),
+        ];
+        let server = std::thread::spawn(move || (read_request(&listener), read_request(&listener)));
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        let client = reqwest::Client::new();
+        rt.block_on(export_once(&client, &config, &metrics));
+        let ((metrics_line, metrics_body), (traces_line, traces_body)) = server.join().expect("collector thread");
+
+        assert!(metrics_line.starts_with("POST /v1/metrics "));
+        let resource = &metrics_body["resourceMetrics"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0]["value"]["stringValue"],
+            "prx-memory-test"
+        );
+        let exported = &resource["scopeMetrics"][0]["metrics"];
+        assert_eq!(exported[0]["sum"]["dataPoints"][0]["asInt"], "3");
+        let histogram = &exported[1]["histogram"]["dataPoints"][0];
+        assert_eq!(histogram["bucketCounts"], json!(["1", "1", "1"]));
+        assert_eq!(histogram["explicitBounds"], json!([5.0, 10.0]));
+
+        assert!(traces_line.starts_with("POST /v1/traces "));
+        let spans = traces_body["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .expect("spans");
+        assert_eq!(spans.len(), 2);
+        let (provider, rpc) = (&spans[0], &spans[1]);
+        assert_eq!(provider["name"], "provider");
+        assert_eq!(provider["traceId"], rpc["traceId"]);
+        assert_eq!(provider["parentSpanId"], rpc["spanId"]);
+        assert_eq!(provider["status"]["code"], 2);
+        assert_eq!(provider["status"]["message"], "upstream 503");
+        assert_eq!(rpc["status"]["code"], 0);
+        assert!(rpc.get("parentSpanId").is_none());
+        assert_eq!(rpc["attributes"][0]["value"]["stringValue"], "tools/call");
+    }
+
+    #[test]
+    fn tenant_metrics_join_the_daemon_series_with_a_tenant_attribute() {
+        let mut exported = vec![sum_metric("prx_calls", &[(vec![("tool", "x")], 2)])];
+        let tenant = vec![
+            sum_metric("prx_calls", &[(vec![("tool", "x")], 5)]),
+            histogram_metric("prx_latency_ms", &[5.0], &[(vec![], 1, 1.0, 1.0, &[1])]),
+        ];
+        merge_tenant_metrics(&mut exported, "acme", tenant);
+
+        assert_eq!(exported.len(), 2);
+        let calls = &exported[0]["sum"]["dataPoints"];
+        assert_eq!(calls.as_array().map(Vec::len), Some(2));
+        assert_eq!(calls[0]["attributes"][0]["key"], "tool");
+        assert_eq!(calls[1]["attributes"][0]["value"]["stringValue"], "acme");
+        assert_eq!(calls[1]["asInt"], "5");
+        let latency = &exported[1]["histogram"]["dataPoints"][0]["attributes"][0];
+        assert_eq!(latency["key"], "tenant");
+    }
+}
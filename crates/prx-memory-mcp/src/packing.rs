@@ -0,0 +1,137 @@
+//! Context packing for `memory_recall` with `max_chars` / `max_tokens`: results are packed whole,
+//! best first, into the lines of one block ready to paste into a prompt. Results that do not fit
+//! can be clipped to an excerpt of their opening sentence instead of being left out.
+
+/// Rough characters per token for English text, used to turn `max_tokens` into a character budget.
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Excerpts shorter than this say too little to be worth the space.
+const MIN_EXCERPT_CHARS: usize = 40;
+
+/// Longest excerpt taken from one result, so a single overflow item cannot fill the remaining space.
+const MAX_EXCERPT_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Packed {
+    Full,
+    Excerpt,
+}
+
+#[derive(Debug, Default)]
+pub struct Packing {
+    /// One line per packed result, full ones first.
+    pub lines: Vec<String>,
+    /// Index into the input and how it was packed, in line order.
+    pub packed: Vec<(usize, Packed)>,
+    /// Indexes that did not fit.
+    pub omitted: Vec<usize>,
+}
+
+/// Packs result texts, ordered best first, into `budget` characters of lines, each built by
+/// `line(index, text)`. Each result that fits whole is taken; when `excerpts` is set, the rest are
+/// clipped into whatever space is left.
+pub fn pack(texts: &[&str], budget: usize, excerpts: bool, line: impl Fn(usize, &str) -> String) -> Packing {
+    let mut used = 0;
+    let mut packing = Packing::default();
+    for (idx, text) in texts.iter().enumerate() {
+        let full = line(idx, &collapse(text));
+        let next = cost(&full, used);
+        if next <= budget {
+            used = next;
+            packing.lines.push(full);
+            packing.packed.push((idx, Packed::Full));
+        } else {
+            packing.omitted.push(idx);
+        }
+    }
+    if excerpts {
+        let mut left_out = Vec::new();
+        for idx in std::mem::take(&mut packing.omitted) {
+            let Some(text) = texts.get(idx) else {
+                continue;
+            };
+            let room = budget.saturating_sub(cost(&line(idx, ""), used)).min(MAX_EXCERPT_CHARS);
+            match excerpt(text, room) {
+                Some(clipped) => {
+                    let clipped = line(idx, &clipped);
+                    used = cost(&clipped, used);
+                    packing.lines.push(clipped);
+                    packing.packed.push((idx, Packed::Excerpt));
+                }
+                None => left_out.push(idx),
+            }
+        }
+        packing.omitted = left_out;
+    }
+    packing
+}
+
+/// The opening sentence of `text`, clipped to `room` characters at a word boundary with a
+/// trailing ellipsis.
+fn excerpt(text: &str, room: usize) -> Option<String> {
+    if room < MIN_EXCERPT_CHARS {
+        return None;
+    }
+    let text = collapse(text);
+    let sentence = text
+        .char_indices()
+        .find(|&(i, c)| matches!(c, '.' | '!' | '?') && text.get(i + 1..).is_none_or(|rest| rest.starts_with(' ')))
+        .map_or(text.as_str(), |(i, _)| text.get(..=i).unwrap_or(&text));
+    if sentence.chars().count() <= room - 2 && sentence.len() < text.len() {
+        return Some(format!("{sentence} …"));
+    }
+    let clipped = sentence.chars().take(room - 2).collect::<String>();
+    // Cut back to a word boundary unless that would leave almost nothing.
+    let clipped = match clipped.rsplit_once(' ') {
+        Some((head, _)) if head.chars().count() >= MIN_EXCERPT_CHARS / 2 => head,
+        _ => clipped.as_str(),
+    };
+    Some(format!("{} …", clipped.trim_end()))
+}
+
+/// Characters used once `line` is appended to a block of `used` characters.
+fn cost(line: &str, used: usize) -> usize {
+    used + line.chars().count() + usize::from(used > 0)
+}
+
+/// `text` on one line, with whitespace runs collapsed.
+pub fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_best_results_whole_and_clips_the_rest() {
+        let long = "Deploys drain the queue first. Then workers restart one at a time while the load balancer \
+                    holds new jobs, which keeps the backlog under a minute on a normal day.";
+        let texts = ["Deploy on Tuesdays.", long, "Rollbacks\n use   the previous image."];
+        let line = |_: usize, text: &str| format!("- {text}");
+
+        let whole = pack(&texts, 60, false, line);
+        assert_eq!(whole.packed, vec![(0, Packed::Full), (2, Packed::Full)]);
+        assert_eq!(whole.omitted, vec![1]);
+        assert_eq!(
+            whole.lines,
+            ["- Deploy on Tuesdays.", "- Rollbacks use the previous image."]
+        );
+
+        let clipped = pack(&texts, 120, true, line);
+        assert_eq!(clipped.packed.last(), Some(&(1, Packed::Excerpt)));
+        assert!(clipped.omitted.is_empty());
+        assert_eq!(
+            clipped.lines.last().map(String::as_str),
+            Some("- Deploys drain the queue first. …")
+        );
+        assert!(clipped.lines.join("\n").chars().count() <= 120);
+
+        let run_on = "word ".repeat(30);
+        let tight = pack(&[run_on.as_str()], 50, true, line);
+        assert_eq!(tight.packed, vec![(0, Packed::Excerpt)]);
+        assert!(tight.lines.join("\n").chars().count() <= 50);
+        assert!(tight.lines.concat().ends_with("word …"));
+        assert!(pack(&texts, 10, true, line).packed.is_empty());
+    }
+}
@@ -24,6 +24,8 @@ pub struct JsonRpcResponse {
 pub struct JsonRpcError {
     pub code: i64,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Box<Value>>,
 }
 
 impl JsonRpcResponse {
@@ -44,6 +46,21 @@ impl JsonRpcResponse {
             error: Some(JsonRpcError {
                 code,
                 message: message.into(),
+                data: None,
+            }),
+        }
+    }
+
+    /// Error carrying a structured `data` payload (JSON-RPC 2.0 `error.data`).
+    pub fn error_with_data(id: Value, code: i64, message: impl Into<String>, data: Value) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code,
+                message: message.into(),
+                data: Some(Box::new(data)),
             }),
         }
     }
@@ -0,0 +1,198 @@
+//! Opt-in recall query log (`PRX_MEMORY_QUERY_LOG`): what was asked, with which filters, which ids
+//! came back and which of them were used afterwards. Exported with `memory_query_log` as raw
+//! material for evaluation sets and ranking experiments.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use prx_memory_storage::{FieldCipher, MemorySource, atomic_write};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryRecord {
+    pub id: String,
+    pub ts_ms: u64,
+    pub agent_id: String,
+    /// Sealed with the store's [`FieldCipher`] on disk when encryption is configured.
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<MemorySource>,
+    pub limit: usize,
+    pub use_vector: bool,
+    pub result_ids: Vec<String>,
+    /// Result ids that a later call referenced, in the order they were first used.
+    #[serde(default)]
+    pub accessed_ids: Vec<String>,
+}
+
+/// Bounded log persisted as one [`QueryRecord`] per line of `<db>.queries.jsonl`.
+///
+/// Recalls append a line; marking results as accessed and trimming to `max_records` rewrite the
+/// file through a temp file and rename.
+#[derive(Debug)]
+pub struct QueryLog {
+    path: PathBuf,
+    cipher: Option<FieldCipher>,
+    max_records: usize,
+    records: VecDeque<QueryRecord>,
+    seq: u64,
+}
+
+impl QueryLog {
+    /// Loads the persisted log; a missing file starts empty and unreadable lines are skipped.
+    pub fn open(path: impl Into<PathBuf>, cipher: Option<FieldCipher>, max_records: usize) -> Self {
+        let path = path.into();
+        let records = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str::<QueryRecord>(line).ok())
+            .filter_map(|mut record| {
+                if let Some(cipher) = &cipher {
+                    record.query = cipher.open(&record.query, &record.id, "query").ok()?;
+                }
+                Some(record)
+            })
+            .collect::<VecDeque<_>>();
+        Self {
+            path,
+            cipher,
+            max_records: max_records.max(1),
+            seq: u64::try_from(records.len()).unwrap_or_default(),
+            records,
+        }
+    }
+
+    /// Id for the next record, `q-<ms>-<seq>`.
+    pub fn next_id(&mut self, now_ms: u64) -> String {
+        self.seq = self.seq.saturating_add(1);
+        format!("q-{now_ms}-{}", self.seq)
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &QueryRecord> {
+        self.records.iter()
+    }
+
+    pub fn append(&mut self, record: QueryRecord) -> Result<(), String> {
+        let line = self.encode(&record)?;
+        self.records.push_back(record);
+        // Trim in batches so a full log is not rewritten on every recall.
+        if self.records.len() > self.max_records + self.max_records / 10 {
+            let excess = self.records.len() - self.max_records;
+            self.records.drain(..excess);
+            return self.persist();
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("failed to write query log: {e}"))?;
+        writeln!(file, "{line}").map_err(|e| format!("failed to write query log: {e}"))
+    }
+
+    /// Marks `ids` as accessed on every logged recall that returned them. Returns how many
+    /// records changed.
+    pub fn mark_accessed(&mut self, ids: &[String]) -> Result<usize, String> {
+        let mut changed = 0;
+        for record in &mut self.records {
+            let before = record.accessed_ids.len();
+            for mid in ids {
+                if record.result_ids.contains(mid) && !record.accessed_ids.contains(mid) {
+                    record.accessed_ids.push(mid.clone());
+                }
+            }
+            if record.accessed_ids.len() != before {
+                changed += 1;
+            }
+        }
+        if changed > 0 {
+            self.persist()?;
+        }
+        Ok(changed)
+    }
+
+    /// Drops the records matching `keep_out` and returns how many were removed.
+    pub fn remove_where(&mut self, keep_out: impl Fn(&QueryRecord) -> bool) -> Result<usize, String> {
+        let before = self.records.len();
+        self.records.retain(|r| !keep_out(r));
+        let removed = before - self.records.len();
+        if removed > 0 {
+            self.persist()?;
+        }
+        Ok(removed)
+    }
+
+    fn encode(&self, record: &QueryRecord) -> Result<String, String> {
+        let mut stored = record.clone();
+        if let Some(cipher) = &self.cipher {
+            stored.query = cipher
+                .seal(&stored.query, &stored.id, "query")
+                .map_err(|e| e.to_string())?;
+        }
+        serde_json::to_string(&stored).map_err(|e| e.to_string())
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let mut raw = String::new();
+        for record in &self.records {
+            raw.push_str(&self.encode(record)?);
+            raw.push('\n');
+        }
+        atomic_write(&self.path, raw.as_bytes()).map_err(|e| format!("failed to write query log: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, results: &[&str]) -> QueryRecord {
+        QueryRecord {
+            id: id.to_string(),
+            ts_ms: 1,
+            agent_id: "agent".to_string(),
+            query: format!("query {id}"),
+            scope: None,
+            category: None,
+            source: None,
+            limit: 5,
+            use_vector: false,
+            result_ids: results.iter().map(|s| (*s).to_string()).collect(),
+            accessed_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn appends_marks_and_trims_across_reopen() {
+        let path = std::env::temp_dir().join(format!("prx-query-log-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut log = QueryLog::open(&path, None, 10);
+        for i in 0..11 {
+            log.append(record(&format!("q{i}"), &["m1", "m2"])).expect("append");
+        }
+        assert_eq!(log.records().count(), 11);
+        assert_eq!(
+            log.mark_accessed(&["m2".to_string(), "m9".to_string()]).expect("mark"),
+            11
+        );
+
+        log.append(record("q11", &["m3"]))
+            .expect("append past the trim threshold");
+        assert_eq!(log.records().count(), 10);
+
+        let reopened = QueryLog::open(&path, None, 10);
+        let ids: Vec<&str> = reopened.records().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids.first(), Some(&"q2"));
+        assert_eq!(ids.last(), Some(&"q11"));
+        assert_eq!(
+            reopened.records().next().map(|r| r.accessed_ids.clone()),
+            Some(vec!["m2".to_string()])
+        );
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,219 @@
+//! PII detection for the store-time redaction stage: regex detectors for emails, API keys and
+//! phone numbers, plus the heuristics that keep ids, hashes and timestamps from matching. The
+//! credential scanner used by governed validation lives in `prx_memory_core::governance`.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PiiKind {
+    Email,
+    ApiKey,
+    Phone,
+}
+
+impl PiiKind {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::ApiKey => "api_key",
+            Self::Phone => "phone",
+        }
+    }
+}
+
+/// What the store does with an entry that contains PII.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionMode {
+    Off,
+    /// Keep the text and add a `pii:<kind>` tag per detected kind.
+    Tag,
+    /// Replace each match with `[redacted:<kind>]`.
+    Mask,
+    /// Refuse the write.
+    Reject,
+}
+
+impl RedactionMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" | "0" | "false" => Some(Self::Off),
+            "tag" => Some(Self::Tag),
+            "mask" | "redact" => Some(Self::Mask),
+            "reject" | "deny" => Some(Self::Reject),
+            _ => None,
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Tag => "tag",
+            Self::Mask => "mask",
+            Self::Reject => "reject",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    pub kind: PiiKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Non-overlapping PII matches in `text`, ordered by position.
+pub fn detect(text: &str) -> Vec<Finding> {
+    let mut found = Vec::new();
+    for (kind, re) in detectors() {
+        for m in re.find_iter(text) {
+            if accept(*kind, m.as_str()) {
+                found.push(Finding {
+                    kind: *kind,
+                    start: m.start(),
+                    end: m.end(),
+                });
+            }
+        }
+    }
+    found.sort_by_key(|f| (f.start, std::cmp::Reverse(f.end)));
+    let mut out: Vec<Finding> = Vec::with_capacity(found.len());
+    for f in found {
+        if out.last().is_none_or(|prev| f.start >= prev.end) {
+            out.push(f);
+        }
+    }
+    out
+}
+
+/// Distinct kinds in `findings`, in a stable order.
+pub fn kinds(findings: &[Finding]) -> Vec<PiiKind> {
+    let mut out: Vec<PiiKind> = findings.iter().map(|f| f.kind).collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+pub fn mask(text: &str, findings: &[Finding]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for f in findings {
+        out.push_str(text.get(last..f.start).unwrap_or_default());
+        out.push_str("[redacted:");
+        out.push_str(f.kind.label());
+        out.push(']');
+        last = f.end;
+    }
+    out.push_str(text.get(last..).unwrap_or_default());
+    out
+}
+
+fn detectors() -> &'static [(PiiKind, Regex)] {
+    static DETECTORS: OnceLock<Vec<(PiiKind, Regex)>> = OnceLock::new();
+    DETECTORS.get_or_init(|| {
+        [
+            (PiiKind::Email, r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+            (
+                PiiKind::ApiKey,
+                r"\b(?:sk-[A-Za-z0-9_-]{16,}|sk_(?:live|test)_[A-Za-z0-9]{16,}|gh[pousr]_[A-Za-z0-9]{30,}|github_pat_[A-Za-z0-9_]{30,}|xox[abprs]-[A-Za-z0-9-]{10,}|AKIA[0-9A-Z]{16}|AIza[0-9A-Za-z_-]{35})",
+            ),
+            (PiiKind::ApiKey, r"\b[A-Za-z0-9_-]{32,}\b"),
+            (PiiKind::Phone, r"(?:\+\d|\(\d|\b\d)[\d\s().-]{6,}\d\b"),
+        ]
+        .into_iter()
+        .filter_map(|(kind, pattern)| Regex::new(pattern).ok().map(|re| (kind, re)))
+        .collect()
+    })
+}
+
+fn accept(kind: PiiKind, matched: &str) -> bool {
+    match kind {
+        PiiKind::Email => true,
+        PiiKind::ApiKey => looks_like_key(matched),
+        PiiKind::Phone => looks_like_phone(matched),
+    }
+}
+
+/// Known prefixes always count; generic long tokens need mixed case and digits, which rules out
+/// UUIDs, hex digests and identifiers written in snake or kebab case.
+fn looks_like_key(token: &str) -> bool {
+    const PREFIXES: [&str; 11] = [
+        "sk-",
+        "sk_live_",
+        "sk_test_",
+        "ghp_",
+        "gho_",
+        "ghu_",
+        "ghs_",
+        "ghr_",
+        "github_pat_",
+        "AKIA",
+        "AIza",
+    ];
+    if PREFIXES.iter().any(|p| token.starts_with(p)) || token.starts_with("xox") {
+        return true;
+    }
+    token.chars().any(|c| c.is_ascii_uppercase())
+        && token.chars().any(|c| c.is_ascii_lowercase())
+        && token.chars().any(|c| c.is_ascii_digit())
+}
+
+/// 10 to 15 digits, written either with a leading `+` or with separators; bare digit runs are
+/// usually timestamps or ids, dotted groups versions or IP addresses, and `YYYY-MM-DD` dates.
+fn looks_like_phone(candidate: &str) -> bool {
+    let digits = candidate.chars().filter(char::is_ascii_digit).count();
+    if !(10..=15).contains(&digits) || contains_iso_date(candidate) {
+        return false;
+    }
+    if candidate.starts_with('+') {
+        return true;
+    }
+    let has_separator = candidate.contains([' ', '-', '(', ')']);
+    has_separator && !candidate.contains('.')
+}
+
+fn contains_iso_date(text: &str) -> bool {
+    let shape = |w: &[u8]| {
+        w.iter().enumerate().all(|(i, b)| {
+            if i == 4 || i == 7 {
+                *b == b'-'
+            } else {
+                b.is_ascii_digit()
+            }
+        })
+    };
+    text.as_bytes().windows(10).any(shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_and_masks_pii() {
+        let text = "Mail jane.doe@example.com or call +1 415-555-0100, key sk-abcdef0123456789abcdef.";
+        let findings = detect(text);
+        assert_eq!(kinds(&findings), vec![PiiKind::Email, PiiKind::ApiKey, PiiKind::Phone]);
+        assert_eq!(
+            mask(text, &findings),
+            "Mail [redacted:email] or call [redacted:phone], key [redacted:api_key]."
+        );
+    }
+
+    #[test]
+    fn ignores_ids_hashes_timestamps_and_versions() {
+        let text = "entry 550e8400-e29b-41d4-a716-446655440000 at 1712345678901 with sha \
+                    9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08 on 10.0.12.255 \
+                    since 2024-01-01 12:00 \
+                    and snake_case_identifier_that_is_quite_long_indeed";
+        assert!(detect(text).is_empty());
+    }
+
+    #[test]
+    fn parses_modes() {
+        assert_eq!(RedactionMode::parse(" Mask "), Some(RedactionMode::Mask));
+        assert_eq!(RedactionMode::parse("reject"), Some(RedactionMode::Reject));
+        assert_eq!(RedactionMode::parse("loud"), None);
+    }
+}
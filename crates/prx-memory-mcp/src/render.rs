@@ -0,0 +1,137 @@
+//! Canonical memory blocks for prompts: `memory_recall` with `render` formats its results the same
+//! way for every client, with ids, categories and importance, so integrations need no layout of their own.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderFormat {
+    /// `- [category] text`, the lines a budget packs when no `render` is asked for.
+    #[default]
+    Compact,
+    Markdown,
+    Xml,
+    Plain,
+}
+
+impl RenderFormat {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "compact" => Some(Self::Compact),
+            "markdown" | "md" => Some(Self::Markdown),
+            "xml" => Some(Self::Xml),
+            "plain" | "text" => Some(Self::Plain),
+            _ => None,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Compact => "compact",
+            Self::Markdown => "markdown",
+            Self::Xml => "xml",
+            Self::Plain => "plain",
+        }
+    }
+
+    /// The line for one memory, with `text` (already on one line) in place of its full text.
+    pub fn line(self, item: &RenderItem<'_>, text: &str) -> String {
+        let RenderItem {
+            id,
+            category,
+            importance,
+        } = *item;
+        match self {
+            Self::Compact => format!("- [{category}] {text}"),
+            Self::Markdown => format!("- **{category}** `{id}` (importance {importance:.2}): {text}"),
+            Self::Xml => format!(
+                "<memory id=\"{}\" category=\"{}\" importance=\"{importance:.2}\">{}</memory>",
+                escape_xml(id),
+                escape_xml(category),
+                escape_xml(text)
+            ),
+            Self::Plain => format!("[{id}] {category}, importance {importance:.2}: {text}"),
+        }
+    }
+
+    const fn wrapper(self) -> (Option<&'static str>, Option<&'static str>) {
+        match self {
+            Self::Compact => (None, None),
+            Self::Markdown => (Some("## Relevant memories"), None),
+            Self::Xml => (Some("<memories>"), Some("</memories>")),
+            Self::Plain => (Some("Relevant memories:"), None),
+        }
+    }
+
+    /// Characters the header and footer add around a non-empty block, newlines included.
+    pub fn overhead(self) -> usize {
+        let (header, footer) = self.wrapper();
+        [header, footer]
+            .into_iter()
+            .flatten()
+            .map(|s| s.chars().count() + 1)
+            .sum()
+    }
+
+    /// The lines wrapped into a block; no lines render as an empty string.
+    pub fn block(self, lines: &[String]) -> String {
+        if lines.is_empty() {
+            return String::new();
+        }
+        let (header, footer) = self.wrapper();
+        let mut out = Vec::with_capacity(lines.len() + 2);
+        out.extend(header.map(str::to_string));
+        out.extend(lines.iter().cloned());
+        out.extend(footer.map(str::to_string));
+        out.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RenderItem<'a> {
+    pub id: &'a str,
+    pub category: &'a str,
+    pub importance: f64,
+}
+
+fn escape_xml(raw: &str) -> String {
+    raw.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_each_format_with_ids_categories_and_importance() {
+        let item = RenderItem {
+            id: "mem-7",
+            category: "decision",
+            importance: 0.7,
+        };
+        let render = |format: RenderFormat| {
+            let block = format.block(&[format.line(&item, "Use <b> & \"quotes\"")]);
+            assert_eq!(
+                block.chars().count(),
+                format.overhead() + format.line(&item, "Use <b> & \"quotes\"").chars().count()
+            );
+            block
+        };
+        assert_eq!(
+            render(RenderFormat::Markdown),
+            "## Relevant memories\n- **decision** `mem-7` (importance 0.70): Use <b> & \"quotes\""
+        );
+        assert_eq!(
+            render(RenderFormat::Xml),
+            "<memories>\n<memory id=\"mem-7\" category=\"decision\" importance=\"0.70\">Use &lt;b&gt; &amp; &quot;quotes&quot;</memory>\n</memories>"
+        );
+        assert_eq!(
+            render(RenderFormat::Plain),
+            "Relevant memories:\n[mem-7] decision, importance 0.70: Use <b> & \"quotes\""
+        );
+        assert_eq!(render(RenderFormat::Compact), "- [decision] Use <b> & \"quotes\"");
+        assert_eq!(RenderFormat::Xml.block(&[]), "");
+        assert_eq!(RenderFormat::parse(" MD "), Some(RenderFormat::Markdown));
+        assert_eq!(RenderFormat::parse("html"), None);
+    }
+}
@@ -0,0 +1,147 @@
+//! Secondary stores (`PRX_MEMORY_SECONDARY_STORES`): other JSON stores, e.g. a team-shared DB next
+//! to a personal one, that `memory_recall` also searches. They are only read: results are merged
+//! into the primary store's with a per-store weight and labelled with the store they came from,
+//! and every write still goes to the primary store.
+//!
+//! A store is reloaded when its file changes, so another server can keep writing to it.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+use prx_memory_storage::{
+    FieldCipher, MemoryEntry, MigrationOptions, PersistentMemoryStore, RecallQuery, RecallResult, recall_entries,
+};
+use serde_json::{Value, json};
+
+/// Label of the primary store's results when `PRX_MEMORY_STORE_NAME` is not set.
+const DEFAULT_PRIMARY_NAME: &str = "primary";
+
+pub struct SecondaryStores {
+    primary: String,
+    stores: Vec<SecondaryStore>,
+}
+
+struct SecondaryStore {
+    name: String,
+    path: PathBuf,
+    /// Multiplies the store's recall scores; the primary store's weight is 1.
+    weight: f32,
+    cipher: Option<FieldCipher>,
+    /// The entries as of the file's modification time.
+    loaded: Mutex<(Option<SystemTime>, Arc<Vec<MemoryEntry>>)>,
+}
+
+impl SecondaryStores {
+    /// `None` unless `PRX_MEMORY_SECONDARY_STORES` is set. It lists `name=path` pairs separated by
+    /// commas; `name:weight=path` sets a weight other than 1. Each file is opened with `cipher` and
+    /// has to exist. `PRX_MEMORY_STORE_NAME` labels the primary store's results.
+    pub fn from_env(cipher: Option<&FieldCipher>) -> Result<Option<Self>, String> {
+        let Some(raw) = std::env::var("PRX_MEMORY_SECONDARY_STORES")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let primary = std::env::var("PRX_MEMORY_STORE_NAME")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_PRIMARY_NAME.to_string());
+        let mut stores: Vec<SecondaryStore> = Vec::new();
+        for pair in raw.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            let (label, path) = pair.split_once('=').ok_or_else(|| {
+                format!("PRX_MEMORY_SECONDARY_STORES entries must be name=path or name:weight=path, got {pair}")
+            })?;
+            let (name, weight) = match label.split_once(':') {
+                Some((name, weight)) => (
+                    name.trim(),
+                    weight
+                        .trim()
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|w| w.is_finite() && *w > 0.0)
+                        .ok_or_else(|| format!("secondary store {name} needs a positive weight, got {weight}"))?,
+                ),
+                None => (label.trim(), 1.0),
+            };
+            if name.is_empty() || name == primary || stores.iter().any(|s| s.name == name) {
+                return Err(format!(
+                    "secondary store names must be unique and differ from the primary store ({primary}), got {name:?}"
+                ));
+            }
+            let store = SecondaryStore {
+                name: name.to_string(),
+                path: PathBuf::from(path.trim()),
+                weight,
+                cipher: cipher.cloned(),
+                loaded: Mutex::new((None, Arc::new(Vec::new()))),
+            };
+            store.entries().map_err(|e| format!("secondary store {name}: {e}"))?;
+            stores.push(store);
+        }
+        Ok(Some(Self { primary, stores }))
+    }
+
+    /// The label of results from the primary store.
+    pub fn primary(&self) -> &str {
+        &self.primary
+    }
+
+    /// Recalls `query` from every secondary store, on the entries `visible` keeps, with each
+    /// store's weight applied to the scores. A store that fails to reload is searched as last
+    /// loaded.
+    pub fn recall(
+        &self,
+        query: &RecallQuery,
+        visible: impl Fn(Vec<MemoryEntry>) -> Vec<MemoryEntry>,
+    ) -> Vec<(&str, RecallResult)> {
+        let mut out = Vec::new();
+        for store in &self.stores {
+            let entries = store.entries().unwrap_or_else(|error| {
+                tracing::warn!(store = %store.name, %error, "secondary store reload failed");
+                Arc::clone(&store.loaded.lock().1)
+            });
+            for mut hit in recall_entries(&visible(entries.to_vec()), query.clone()) {
+                hit.score *= store.weight;
+                out.push((store.name.as_str(), hit));
+            }
+        }
+        out
+    }
+
+    /// Stores, weights and entry counts for `memory_stats`.
+    pub fn status_json(&self) -> Value {
+        json!({
+            "primary": self.primary,
+            "stores": self.stores.iter().map(|s| json!({
+                "name": s.name,
+                "path": s.path.display().to_string(),
+                "weight": s.weight,
+                "count": s.loaded.lock().1.len()
+            })).collect::<Vec<_>>()
+        })
+    }
+}
+
+impl SecondaryStore {
+    /// The store's entries, re-read when the file changed since the last load.
+    fn entries(&self) -> Result<Arc<Vec<MemoryEntry>>, String> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .map_err(|e| format!("cannot read {}: {e}", self.path.display()))?;
+        let mut loaded = self.loaded.lock();
+        if loaded.0 != Some(modified) {
+            // A dry-run migration leaves an older file as it is; it belongs to another server.
+            let options = MigrationOptions {
+                dry_run: true,
+                ..MigrationOptions::default()
+            };
+            let store = PersistentMemoryStore::open_with_options(&self.path, self.cipher.clone(), options)
+                .map_err(|e| e.to_string())?;
+            *loaded = (Some(modified), Arc::new(store.list(usize::MAX)));
+        }
+        Ok(Arc::clone(&loaded.1))
+    }
+}
@@ -1,51 +1,370 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
 use std::io::{self, BufRead, Read, Write};
 use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::{Arc, OnceLock};
+use std::task::Poll;
 
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use prx_memory_core::{EvolutionPolicy, EvolutionRunner, VariantCandidate};
+use prx_memory_core::{
+    DecayPolicy, EntityKind, EvolutionPolicy, EvolutionRunner, GovernancePolicy, ImportanceNeighbor,
+    ImportanceSuggestion, RetentionInput, RetentionScore, RetryPolicy, TaxonomyMode, VariantCandidate, compact_query,
+    extract_entities, importance_level_from_numeric, parse_tag_taxonomy, resolve_importance, suggest_importance,
+};
 use prx_memory_embed::{
-    EmbeddingProviderConfig, EmbeddingRequest, EmbeddingTask, GeminiConfig, OpenAiCompatibleConfig,
-    ProviderError as EmbeddingProviderError, build_embedding_provider,
+    EmbeddingProviderConfig, EmbeddingRequest, EmbeddingTask, GeminiConfig, OllamaConfig, OpenAiCompatibleConfig,
+    ProviderError as EmbeddingProviderError, build_embedding_provider, providers::FailoverEmbeddingProvider,
+    with_retry as embed_with_retry,
 };
 use prx_memory_rerank::{
     CohereRerankConfig, JinaRerankConfig, PineconeRerankConfig, ProviderError as RerankProviderError,
-    RerankProviderConfig, RerankRequest, build_rerank_provider,
+    RerankProviderConfig, RerankRequest, build_rerank_provider, with_retry as rerank_with_retry,
 };
-use prx_memory_skill::{SKILL_ID, resource_text as skill_resource_text, resources as skill_resources};
-#[cfg(feature = "lancedb-backend")]
-use prx_memory_storage::LanceDbBackend;
+use prx_memory_skill::{SKILL_ID, SKILL_TAGS_TEXT, resource_text as skill_resource_text, resources as skill_resources};
+#[cfg(feature = "redis-backend")]
+use prx_memory_storage::RedisBackend;
 use prx_memory_storage::{
-    MemoryEntry, NewMemoryEntry, PersistentMemoryStore, RecallQuery, RecallResult, StorageBackend,
+    ChangeEvent, ChangeLog, ChangeOp, ChangeRecordingBackend, FieldCipher, FusionMode, IdFormat, MemoryEntry,
+    MemoryRelation, MemorySource, MigrationOptions, NewMemoryEntry, PersistentMemoryStore, RankingConfig, RecallQuery,
+    RecallResult, StorageBackend, StorageError, StoreSnapshot, SynonymTable, TokenizerMode, atomic_write,
+    expansion_keywords, explain_recall_score, mmr_select, parse_query, ranking_config, recall_entries,
+    with_ranking_config, with_synonyms,
+};
+#[cfg(feature = "lancedb-backend")]
+use prx_memory_storage::{LanceDbBackend, TieredBackend, TieredConfig};
+#[cfg(feature = "postgres-backend")]
+use prx_memory_storage::{PostgresBackend, PostgresConfig};
+#[cfg(feature = "qdrant-backend")]
+use prx_memory_storage::{QdrantBackend, QdrantConfig};
+#[cfg(feature = "s3-backend")]
+use prx_memory_storage::{S3Backend, S3Config};
+use prx_memory_summarize::{
+    OpenAiCompatibleSummarizeConfig, ProviderError as SummarizeProviderError, SummarizeProviderConfig,
+    SummarizeRequest, build_summarize_provider,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
+use crate::adapters::{self, SourceFormat};
+use crate::context;
+use crate::distill::{self, Message};
+use crate::eval::{self, EvalCase};
+use crate::experiment::{Experiment, ExperimentArm};
+use crate::feedback::{FeedbackFile, QueryFeedback};
+use crate::follower::{self, Follower, FollowerConfig};
+use crate::ingest::{self, ChunkOptions};
+use crate::journal::WriteJournal;
+use crate::packing;
 use crate::protocol::{JsonRpcRequest, JsonRpcResponse};
+use crate::query_log::{QueryLog, QueryRecord};
+use crate::redact::{self, RedactionMode};
+use crate::render::{RenderFormat, RenderItem};
+use crate::secondary::SecondaryStores;
+use crate::session_log::{SessionLog, SessionRecord};
+use crate::team_sync::{SyncConfig, TeamSync};
+use crate::tenants::{TENANT_HEADER, TenantRegistry, TenantServers, merge_metrics};
+use crate::tool_schemas::{self, SchemaFormat};
+use crate::transfer::{self, ExportFormat, ImportFormat};
+use crate::usage_log::{UsageLog, UsageOp};
 
 const DEFAULT_MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+const AGENT_HEADER: &str = "x-prx-agent";
 const MAX_HTTP_BODY_SIZE: usize = 16 * 1024 * 1024; // 16 MiB
+const RELATION_TYPES: &[&str] = &["supersedes", "derived-from", "contradicts", "related-to"];
+const DISTILL_SOURCE_TAG: &str = "source:distill";
+const SESSION_SOURCE_TAG: &str = "source:session";
+/// Notes a session keeps; older ones are dropped first.
+const MAX_SESSION_NOTES: usize = 200;
+const PENDING_REVIEW_TAG: &str = "review:pending";
+const UNLISTED_TAG: &str = "taxonomy:unlisted";
+const DEFAULT_LATENCY_BUCKETS_MS: &[f64] = &[
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1_000.0, 2_500.0, 5_000.0, 10_000.0,
+];
 
 pub struct McpServer {
-    store: Arc<Mutex<Box<dyn StorageBackend>>>,
+    /// Recall, list and stats share the read lock; only writes take it exclusively.
+    store: Arc<RwLock<Box<dyn StorageBackend>>>,
     scopes: ScopeManager,
     standards: StandardizationConfig,
-    auto_store_counter: Mutex<usize>,
+    auto_store_counter: AtomicUsize,
     metrics: Arc<Mutex<MetricsRegistry>>,
     sessions: Arc<Mutex<HashMap<String, SessionState>>>,
-    session_counter: Mutex<u64>,
+    session_counter: AtomicU64,
+    /// Expired sessions with notes, promoted by the HTTP server's session reaper.
+    ended_sessions: Mutex<Vec<EndedSession>>,
+    session_file: SessionFile,
+    /// Write-through journal of session changes when `PRX_MEMORY_SESSION_LOG` is on.
+    session_log: Option<Mutex<SessionLog>>,
+    /// Set by `POST /admin/drain`: no new sessions, and live ones are kept in `session_file`.
+    draining: AtomicBool,
+    /// Set on SIGINT/SIGTERM: the HTTP listener stops accepting connections.
+    shutting_down: AtomicBool,
+    /// Written by a clean shutdown and removed at startup; see [`check_previous_shutdown`].
+    shutdown_marker: PathBuf,
+    jobs: Arc<Mutex<JobRegistry>>,
+    /// Server notifications not yet pushed into session streams.
+    notices: Arc<Mutex<Vec<ServerNotice>>>,
+    runtime: Arc<tokio::runtime::Runtime>,
+    /// This store's embedding cache and provider rate limit.
+    embed: Arc<Mutex<EmbedRuntime>>,
+    /// Lexical ranking settings and synonym dictionary; every call runs under them (see
+    /// [`Self::with_lexical`]).
+    ranking: RwLock<RankingConfig>,
+    synonyms: Arc<SynonymTable>,
+    resource_watch: Mutex<ResourceWatch>,
+    tool_policy: ToolPolicy,
+    rate_limiter: Mutex<ToolRateLimiter>,
+    quotas: QuotaConfig,
+    decay: DecayTracker,
+    baselines: EvalBaselineFile,
+    active_ranking: ActiveRankingFile,
+    query_log: Option<Mutex<QueryLog>>,
+    usage: Mutex<UsageLog>,
+    feedback: FeedbackFile,
+    experiment: Option<Experiment>,
+    /// Set in follower mode; the store then mirrors the leader and writes are refused.
+    follower: Option<Arc<Follower>>,
+    /// Read-only stores `memory_recall` also searches, from `PRX_MEMORY_SECONDARY_STORES`.
+    secondary: Option<SecondaryStores>,
+    /// Pushes and pulls governed entries to a shared server, from `PRX_MEMORY_SYNC_URL`.
+    team_sync: Option<Arc<TeamSync>>,
+    inflight: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    /// Per-tenant stores when `PRX_MEMORY_TENANTS_DIR` is set; only the daemon's root server has one.
+    tenants: Option<TenantRegistry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+struct RateLimitSpec {
+    rps: f64,
+    burst: f64,
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    spec: RateLimitSpec,
+    last_refill_ms: u64,
+}
+
+/// Token buckets for `tools/call`, one per tool and one per stream session.
+///
+/// Configured with `PRX_MEMORY_RATE_LIMIT_TOOL_RPS`/`_TOOL_BURST` (every tool),
+/// `PRX_MEMORY_RATE_LIMIT_TOOLS` (JSON per-tool overrides, e.g.
+/// `{"memory_recall":{"rps":2,"burst":5}}`) and
+/// `PRX_MEMORY_RATE_LIMIT_SESSION_RPS`/`_SESSION_BURST`. Unset means unlimited.
+#[derive(Debug, Default)]
+struct ToolRateLimiter {
+    tool_default: Option<RateLimitSpec>,
+    tool_overrides: HashMap<String, RateLimitSpec>,
+    session: Option<RateLimitSpec>,
+    tool_buckets: HashMap<String, TokenBucket>,
+    session_buckets: HashMap<String, TokenBucket>,
 }
 
+/// Scope ACLs for the calling agent. Rules keep their `{agent_id}` placeholders and are expanded
+/// per call, since one daemon can serve several agents (see [`with_caller_agent`]).
 #[derive(Debug, Clone)]
 struct ScopeManager {
-    agent_id: String,
+    /// `PRX_MEMORY_AGENT_ID`, used when the request does not name an agent.
+    default_agent_id: String,
+    /// Accept `X-Prx-Agent`, session `agent_id` and `initialize` `clientInfo.name` as the caller.
+    agent_from_request: bool,
     default_scope: String,
     allowed_scope_rules: Vec<String>,
-    agent_access: HashMap<String, Vec<String>>,
+    agent_access: HashMap<String, AgentAccess>,
+    /// `PRX_MEMORY_AGENT_TOKENS`: token label to the agent ids that token may claim.
+    agent_tokens: HashMap<String, HashSet<String>>,
+}
+
+/// What an agent may do in a scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ScopeAction {
+    /// Recall, list, export, stats and the report tools.
+    Read,
+    /// Store, update, import, link and approve.
+    Write,
+    /// Forget, reject, and removing the sources of a merge or summary.
+    Delete,
+    /// Maintenance: applied compaction, re-embedding, archive and restore.
+    Admin,
+}
+
+impl ScopeAction {
+    const ALL: [Self; 4] = [Self::Read, Self::Write, Self::Delete, Self::Admin];
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Delete => "delete",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+/// One agent's `PRX_MEMORY_AGENT_ACCESS` entry: a scope list grants every action in those scopes,
+/// a map grants the listed actions per scope rule, e.g. `{"*":["read"],"agent:{agent_id}":["read","write"]}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum AgentAccess {
+    Scopes(Vec<String>),
+    Actions(BTreeMap<String, Vec<ScopeAction>>),
+}
+
+thread_local! {
+    static CALLER_AGENT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` on behalf of `agent` on the current thread only; `None` keeps the current caller.
+fn with_caller_agent<T>(agent: Option<String>, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<String>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CALLER_AGENT.with(|cell| *cell.borrow_mut() = self.0.take());
+        }
+    }
+    let Some(agent) = agent else {
+        return f();
+    };
+    let _restore = Restore(CALLER_AGENT.with(|cell| cell.replace(Some(agent))));
+    f()
+}
+
+fn caller_agent_override() -> Option<String> {
+    CALLER_AGENT.with(|cell| cell.borrow().clone())
+}
+
+/// Agent ids named by clients: 1-64 chars of ASCII letters, digits, `-`, `_`, `.` and `@`.
+fn validate_agent_id(agent: &str) -> Result<String, String> {
+    let agent = agent.trim();
+    let valid = !agent.is_empty()
+        && agent.len() <= 64
+        && agent
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@'));
+    if valid {
+        Ok(agent.to_string())
+    } else {
+        Err(format!(
+            "agent ids must be 1-64 chars of letters, digits, -, _, . and @, got {agent}"
+        ))
+    }
+}
+
+/// Per-agent tool authorization loaded from `PRX_MEMORY_TOOL_POLICY`.
+///
+/// The env value is a JSON object keyed by agent id (or `*` as the fallback),
+/// e.g. `{"*":{"deny":["memory_forget","memory_compact:apply"]},"ops":{"allow":["*"]}}`.
+/// Entries are tool names or `*`-suffixed patterns; `<tool>:apply` matches
+/// calls that are not dry runs. Deny wins over allow, and a non-empty allow
+/// list rejects everything it does not match.
+#[derive(Debug, Clone, Default)]
+struct ToolPolicy {
+    rules: HashMap<String, ToolPolicyRule>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ToolPolicyRule {
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+/// Store-time quotas.
+///
+/// `PRX_MEMORY_SCOPE_QUOTAS` is a JSON object keyed by scope, `*`-suffixed
+/// pattern or `*`, e.g. `{"*":{"max_entries":50000},"agent:*":{"max_bytes":1000000}}`;
+/// an exact key wins over the longest matching pattern. Scope quotas cap the
+/// entries and text bytes currently stored in a scope.
+/// `PRX_MEMORY_AGENT_QUOTA_ENTRIES`/`_BYTES` cap what the calling agent stored
+/// within `PRX_MEMORY_AGENT_QUOTA_WINDOW_MS` (default 24h).
+#[derive(Debug, Clone, Default)]
+struct QuotaConfig {
+    scopes: HashMap<String, QuotaLimit>,
+    agent: QuotaLimit,
+    agent_window_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+struct QuotaLimit {
+    max_entries: Option<u64>,
+    max_bytes: Option<u64>,
+}
+
+/// Entries and text bytes counted against a [`QuotaLimit`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+struct QuotaUsage {
+    entries: u64,
+    bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+struct QuotaExceeded {
+    quota: &'static str,
+    subject: String,
+    resource: &'static str,
+    max: u64,
+    used: u64,
+    requested: u64,
+}
+
+/// Forgetting-curve settings plus the recall access log they score against.
+///
+/// `PRX_MEMORY_DECAY_HALF_LIFE_DAYS` (default 30) and `PRX_MEMORY_DECAY_ACCESS_BOOST`
+/// (default 1) tune the [`DecayPolicy`]. `PRX_MEMORY_DECAY_ARCHIVE_BELOW` enables the
+/// auto-archive step of periodic maintenance, which moves entries whose retention
+/// fell below it into the [`MemoryArchive`].
+#[derive(Debug)]
+struct DecayTracker {
+    policy: DecayPolicy,
+    archive_below: Option<f64>,
+    archive: MemoryArchive,
+    access: Mutex<AccessLog>,
+}
+
+/// Cold entries moved out of the live store, one [`ArchivedEntry`] per line of `<db>.archive.jsonl`.
+/// Only `memory_recall` with `include_archived` searches them; `memory_restore` moves them back.
+/// Archived text and embeddings are sealed with the same cipher as the store.
+#[derive(Debug, Clone)]
+struct MemoryArchive {
+    path: PathBuf,
+    cipher: Option<FieldCipher>,
+}
+
+/// How often and how recently each entry was returned by `memory_recall`, persisted to `<db>.access.json`.
+#[derive(Debug, Default)]
+struct AccessLog {
+    path: Option<PathBuf>,
+    entries: HashMap<String, EntryAccess>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct EntryAccess {
+    count: u64,
+    last_ms: u64,
+}
+
+/// One line of the [`MemoryArchive`]: the entry plus the relations it had when it was archived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedEntry {
+    entry: MemoryEntry,
+    #[serde(default)]
+    relations: Vec<MemoryRelation>,
+    archived_ms: u64,
+    retention: f64,
+    /// Embedding sealed by the store's [`FieldCipher`]; `entry.text` is sealed alongside it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_embedding: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -60,10 +379,13 @@ struct StandardizationConfig {
     default_project_tag: String,
     default_tool_tag: String,
     default_domain_tag: String,
+    redaction: RedactionMode,
+    governed_redaction: RedactionMode,
+    governance: GovernancePolicy,
 }
 
 impl StandardizationConfig {
-    fn from_env() -> Self {
+    fn from_env() -> Result<Self, String> {
         let profile = match std::env::var("PRX_MEMORY_STANDARD_PROFILE") {
             Ok(v) => {
                 let lowered = v.trim().to_ascii_lowercase();
@@ -90,12 +412,26 @@ impl StandardizationConfig {
             .map(|v| v.trim().to_string())
             .filter(|v| !v.is_empty())
             .unwrap_or_else(|| "general".to_string());
-        Self {
+        // The governed profile is one step stricter for both kinds of writes.
+        let (default_redaction, default_governed_redaction) = match profile {
+            StandardProfile::ZeroConfig => (RedactionMode::Off, RedactionMode::Mask),
+            StandardProfile::Governed => (RedactionMode::Mask, RedactionMode::Reject),
+        };
+        let redaction_from_env = |key: &str, default: RedactionMode| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| RedactionMode::parse(&v))
+                .unwrap_or(default)
+        };
+        Ok(Self {
             profile,
             default_project_tag,
             default_tool_tag,
             default_domain_tag,
-        }
+            redaction: redaction_from_env("PRX_MEMORY_REDACTION", default_redaction),
+            governed_redaction: redaction_from_env("PRX_MEMORY_GOVERNED_REDACTION", default_governed_redaction),
+            governance: governance_policy_from_env()?,
+        })
     }
 
     fn profile_label(&self) -> &'static str {
@@ -116,22 +452,155 @@ impl StandardizationConfig {
     fn default_governed_for_import(&self) -> bool {
         matches!(self.profile, StandardProfile::Governed)
     }
+
+    const fn redaction_for(&self, governed: bool) -> RedactionMode {
+        if governed {
+            self.governed_redaction
+        } else {
+            self.redaction
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct StreamEvent {
     seq: u64,
     payload: Value,
     created_ms: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct SessionState {
     next_seq: u64,
     events: VecDeque<StreamEvent>,
     last_touch_ms: u64,
     acked_seq: u64,
     lease_expires_ms: u64,
+    subscriptions: HashSet<String>,
+    /// Caller identity for the session's tool calls, when the client named one.
+    agent_id: Option<String>,
+    /// Working memory from `memory_session_note`, promoted to the review queue when the session ends.
+    notes: Vec<Message>,
+    /// Server notification kinds the session asked for; `None` takes every kind.
+    #[serde(default)]
+    notifications: Option<BTreeSet<String>>,
+}
+
+/// Server notification kinds a session can filter on, each sent as `notifications/prx/<kind>`.
+const NOTICE_KINDS: [&str; 3] = ["maintenance", "quota", "job"];
+
+/// Share of a quota that, once crossed by a write, sends a `quota` warning.
+const QUOTA_WARN_PERCENT: u64 = 90;
+
+/// A server-initiated notification waiting to be pushed into session streams. Subsystems queue
+/// notices without touching the sessions; stream polls and the session reaper deliver them.
+#[derive(Debug, Clone)]
+struct ServerNotice {
+    kind: &'static str,
+    /// Only sessions whose caller can read this scope get the notice.
+    scope: Option<String>,
+    /// Only sessions of this caller get the notice.
+    agent: Option<String>,
+    params: Value,
+}
+
+/// An ended session whose working memory still has to be promoted.
+#[derive(Debug)]
+struct EndedSession {
+    id: String,
+    agent_id: Option<String>,
+    notes: Vec<Message>,
+}
+
+impl SessionState {
+    fn new(agent_id: Option<String>, now: u64) -> Self {
+        Self {
+            next_seq: 1,
+            events: VecDeque::new(),
+            last_touch_ms: now,
+            acked_seq: 0,
+            lease_expires_ms: now.saturating_add(McpServer::session_ttl_ms()),
+            subscriptions: HashSet::new(),
+            agent_id,
+            notes: Vec::new(),
+            notifications: None,
+        }
+    }
+
+    fn wants(&self, kind: &str) -> bool {
+        self.notifications.as_ref().is_none_or(|kinds| kinds.contains(kind))
+    }
+
+    fn add_note(&mut self, note: Message) {
+        self.notes.push(note);
+        let overflow = self.notes.len().saturating_sub(MAX_SESSION_NOTES);
+        self.notes.drain(..overflow);
+    }
+
+    /// Drops events up to `seq` and returns the acked sequence number.
+    fn ack(&mut self, seq: u64) -> u64 {
+        self.acked_seq = self.acked_seq.max(seq);
+        while matches!(self.events.front(), Some(ev) if ev.seq <= self.acked_seq) {
+            let _ = self.events.pop_front();
+        }
+        self.acked_seq
+    }
+
+    /// Journal records that rebuild this session.
+    fn records(&self, session: &str) -> Vec<SessionRecord> {
+        let mut records = vec![SessionRecord::Started {
+            session: session.to_string(),
+            agent_id: self.agent_id.clone(),
+        }];
+        if let Some(kinds) = &self.notifications {
+            records.push(SessionRecord::Filtered {
+                session: session.to_string(),
+                kinds: kinds.iter().cloned().collect(),
+            });
+        }
+        records.extend(self.subscriptions.iter().map(|uri| SessionRecord::Subscribed {
+            session: session.to_string(),
+            uri: uri.clone(),
+        }));
+        records.extend(self.notes.iter().map(|note| SessionRecord::Noted {
+            session: session.to_string(),
+            note: note.clone(),
+        }));
+        // Also keeps the sequence going when every event has been read.
+        records.push(SessionRecord::Acked {
+            session: session.to_string(),
+            seq: self.acked_seq,
+        });
+        records.extend(self.events.iter().map(|event| SessionRecord::Event {
+            session: session.to_string(),
+            seq: event.seq,
+            created_ms: event.created_ms,
+            payload: event.payload.clone(),
+        }));
+        records
+    }
+
+    fn push_event(&mut self, payload: Value, now: u64) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.saturating_add(1);
+        self.events.push_back(StreamEvent {
+            seq,
+            payload,
+            created_ms: now,
+        });
+        while self.events.len() > 512 {
+            let _ = self.events.pop_front();
+        }
+        seq
+    }
+}
+
+/// Last observed resource catalog, diffed on stream polls to emit resource notifications.
+#[derive(Debug, Default)]
+struct ResourceWatch {
+    list_fingerprint: Option<u64>,
+    content: HashMap<String, u64>,
+    checked_ms: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -155,6 +624,7 @@ struct ToolMetric {
     err: u64,
     total_latency_ms: f64,
     max_latency_ms: f64,
+    latency_buckets: Vec<u64>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -162,6 +632,62 @@ struct StageMetric {
     total_latency_ms: f64,
     count: u64,
     max_latency_ms: f64,
+    latency_buckets: Vec<u64>,
+}
+
+/// Recall outcomes for one A/B experiment arm; `latency.count` includes failed recalls.
+#[derive(Debug, Default, Clone)]
+struct ArmMetric {
+    latency: StageMetric,
+    errors: u64,
+    empty: u64,
+    results: u64,
+    useful: u64,
+    irrelevant: u64,
+}
+
+/// Counts `latency_ms` into the first bucket whose upper bound holds it. Buckets are stored
+/// per-bound (not cumulative); observations above the last bound only show up in `+Inf`.
+fn observe_latency(buckets: &mut Vec<u64>, bounds: &[f64], latency_ms: f64) {
+    buckets.resize(bounds.len(), 0);
+    if let Some(slot) = bounds
+        .iter()
+        .position(|bound| latency_ms <= *bound)
+        .and_then(|idx| buckets.get_mut(idx))
+    {
+        *slot = slot.saturating_add(1);
+    }
+}
+
+/// Prometheus `_bucket` lines (cumulative, ending with `le="+Inf"`) for one histogram series.
+fn histogram_bucket_lines(name: &str, labels: &str, bounds: &[f64], buckets: &[u64], count: u64) -> Vec<String> {
+    let mut cumulative = 0_u64;
+    let mut lines = bounds
+        .iter()
+        .enumerate()
+        .map(|(idx, bound)| {
+            cumulative = cumulative.saturating_add(buckets.get(idx).copied().unwrap_or(0));
+            format!("{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}")
+        })
+        .collect::<Vec<_>>();
+    lines.push(format!("{name}_bucket{{{labels},le=\"+Inf\"}} {count}"));
+    lines
+}
+
+fn latency_bounds_from_env() -> Vec<f64> {
+    let mut bounds = std::env::var("PRX_METRICS_LATENCY_BUCKETS_MS")
+        .ok()
+        .map(|raw| {
+            raw.split(',')
+                .filter_map(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v > 0.0)
+                .collect::<Vec<_>>()
+        })
+        .filter(|bounds| !bounds.is_empty())
+        .unwrap_or_else(|| DEFAULT_LATENCY_BUCKETS_MS.to_vec());
+    bounds.sort_by(f64::total_cmp);
+    bounds.dedup();
+    bounds
 }
 
 #[derive(Debug, Clone)]
@@ -208,6 +734,14 @@ struct MetricsRegistry {
     sessions_expired: u64,
     session_access_not_found: u64,
     session_access_poisoned: u64,
+    http_token_requests: HashMap<String, u64>,
+    http_auth_failures: u64,
+    http_connections_rejected: u64,
+    rate_limited: HashMap<(String, String), u64>,
+    /// Agents exported as `agent` labels; operations by agents past the limit count as overflow.
+    usage_agent: BoundedLabelCounter,
+    experiment_arms: HashMap<String, ArmMetric>,
+    latency_bounds: Vec<f64>,
 }
 
 impl MetricsRegistry {
@@ -230,10 +764,85 @@ impl MetricsRegistry {
             sessions_expired: 0,
             session_access_not_found: 0,
             session_access_poisoned: 0,
+            http_token_requests: HashMap::new(),
+            http_auth_failures: 0,
+            http_connections_rejected: 0,
+            rate_limited: HashMap::new(),
+            usage_agent: BoundedLabelCounter::new(env_usize("PRX_METRICS_MAX_AGENT_LABELS", 32, 1, 256)),
+            experiment_arms: HashMap::new(),
+            latency_bounds: latency_bounds_from_env(),
         }
     }
 }
 
+#[cfg(feature = "otel")]
+impl MetricsRegistry {
+    /// Tool/stage metrics in OTLP form, mirroring the Prometheus series of the same names.
+    fn otlp_metrics(&self) -> Vec<Value> {
+        use crate::otel::{histogram_metric, sum_metric};
+
+        let calls = self
+            .tool
+            .iter()
+            .flat_map(|(tool, m)| {
+                [
+                    (vec![("tool", tool.as_str()), ("status", "ok")], m.ok),
+                    (vec![("tool", tool.as_str()), ("status", "error")], m.err),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let tool_latency = self
+            .tool
+            .iter()
+            .map(|(tool, m)| {
+                (
+                    vec![("tool", tool.as_str())],
+                    m.ok + m.err,
+                    m.total_latency_ms,
+                    m.max_latency_ms,
+                    m.latency_buckets.as_slice(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let stage_latency = self
+            .recall_stage
+            .iter()
+            .map(|(stage, m)| {
+                (
+                    vec![("stage", stage.as_str())],
+                    m.count,
+                    m.total_latency_ms,
+                    m.max_latency_ms,
+                    m.latency_buckets.as_slice(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let rate_limited = self
+            .rate_limited
+            .iter()
+            .map(|((limit, tool), count)| (vec![("limit", limit.as_str()), ("tool", tool.as_str())], *count))
+            .collect::<Vec<_>>();
+        vec![
+            sum_metric("prx_memory_tool_calls_total", &calls),
+            histogram_metric("prx_memory_tool_latency_ms", &self.latency_bounds, &tool_latency),
+            histogram_metric(
+                "prx_memory_recall_stage_latency_ms",
+                &self.latency_bounds,
+                &stage_latency,
+            ),
+            sum_metric(
+                "prx_memory_recall_remote_rerank_attempts_total",
+                &[(Vec::new(), self.remote_rerank_attempts)],
+            ),
+            sum_metric(
+                "prx_memory_recall_remote_rerank_warnings_total",
+                &[(Vec::new(), self.remote_rerank_warnings)],
+            ),
+            sum_metric("prx_memory_rate_limited_total", &rate_limited),
+        ]
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 struct EmbedRuntimeStats {
     cache_hits: u64,
@@ -241,15 +850,113 @@ struct EmbedRuntimeStats {
     cache_evictions: u64,
     rate_wait_events: u64,
     rate_wait_ms_total: u64,
+    disk_hits: u64,
+    disk_misses: u64,
+    disk_evictions: u64,
+    disk_write_errors: u64,
+    served_by: HashMap<String, u64>,
+}
+
+/// A background `memory_reembed` run. `pending_ids` is the checkpoint: it is persisted after
+/// every batch so an interrupted job resumes with the entries it has not reached yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReembedJob {
+    id: String,
+    status: String,
+    scope: Option<String>,
+    category: Option<String>,
+    batch_size: usize,
+    total: usize,
+    pending_ids: Vec<String>,
+    updated: usize,
+    failed: usize,
+    batches: usize,
+    errors: Vec<String>,
+    created_ms: u64,
+    updated_ms: u64,
+}
+
+const MAX_JOB_ERRORS: usize = 100;
+const MAX_FINISHED_JOBS: usize = 50;
+
+#[derive(Debug, Default)]
+struct JobRegistry {
+    path: Option<std::path::PathBuf>,
+    jobs: Vec<ReembedJob>,
+    next_seq: u64,
+}
+
+/// An embedding vector together with the `provider/model` label that produced it.
+#[derive(Debug, Clone, PartialEq)]
+struct EmbeddedText {
+    model: String,
+    vector: Vec<f32>,
 }
 
 #[derive(Debug, Clone)]
 struct EmbedCacheEntry {
-    value: Vec<f32>,
+    value: EmbeddedText,
     expire_at_ms: u64,
 }
 
+/// One line of the disk cache log. A later line for the same key replaces the earlier one.
+/// With a [`FieldCipher`] the vector is written as `sealed` and `vector` is left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EmbedDiskRecord {
+    key: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    vector: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed: Option<String>,
+    #[serde(default)]
+    model: String,
+    stored_at_ms: u64,
+}
+
 #[derive(Debug, Clone)]
+struct EmbedDiskEntry {
+    vector: Vec<f32>,
+    model: String,
+    stored_at_ms: u64,
+    /// Position in `EmbedDiskCache::recency`.
+    seq: u64,
+}
+
+/// On-disk embedding cache keyed by a SHA-256 of the in-memory cache key, so vectors
+/// survive restarts without storing the original text. New vectors are appended to a JSON-lines
+/// log; the log is rewritten from the live entries once it grows past twice the capacity.
+#[derive(Debug)]
+struct EmbedDiskCache {
+    entries: HashMap<String, EmbedDiskEntry>,
+    /// Recency order, least recently used first, so eviction never scans the whole cache.
+    recency: BTreeMap<u64, String>,
+    next_seq: u64,
+    capacity: usize,
+    ttl_ms: u64,
+    /// Records not yet appended to the log.
+    pending: Vec<EmbedDiskRecord>,
+    /// Set when the log holds lines that must be rewritten, such as plaintext vectors replayed
+    /// with a cipher configured or values sealed before a key rotation.
+    rewrite_due: bool,
+    log: Arc<Mutex<EmbedDiskLog>>,
+}
+
+#[derive(Debug)]
+struct EmbedDiskLog {
+    path: std::path::PathBuf,
+    /// Seals vectors as they are written; the store's cipher.
+    cipher: Option<FieldCipher>,
+    /// Lines in the file, live or not.
+    lines: usize,
+}
+
+/// Log writes taken from the cache, run after the embedding runtime lock is released.
+enum EmbedDiskWrite {
+    Append(Vec<EmbedDiskRecord>),
+    Rewrite(Vec<EmbedDiskRecord>),
+}
+
+#[derive(Debug)]
 struct EmbedRuntime {
     entries: HashMap<String, EmbedCacheEntry>,
     lru: VecDeque<String>,
@@ -260,37 +967,376 @@ struct EmbedRuntime {
     refill_per_sec: f64,
     last_refill_ms: u64,
     stats: EmbedRuntimeStats,
+    disk: Option<EmbedDiskCache>,
 }
 
 impl McpServer {
     pub fn new() -> Result<Self, String> {
         let db_path = std::env::var("PRX_MEMORY_DB").unwrap_or_else(|_| "./data/memory-db.json".to_string());
-        Self::with_db_path(db_path)
+        let mut server = Self::open(db_path, None)?;
+        server.tenants = TenantRegistry::from_env(&server.runtime)?;
+        #[cfg(feature = "otel")]
+        server.spawn_otlp_exporter()?;
+        Ok(server)
+    }
+
+    /// The migration opening `PRX_MEMORY_DB` would run, without writing anything. Only the JSON
+    /// store is versioned.
+    pub fn migration_dry_run() -> Result<Value, String> {
+        let db_path = std::env::var("PRX_MEMORY_DB").unwrap_or_else(|_| "./data/memory-db.json".to_string());
+        if !Path::new(&db_path).exists() {
+            return Err(format!("no memory store at {db_path}"));
+        }
+        let options = MigrationOptions {
+            dry_run: true,
+            ..migration_options_from_env()
+        };
+        let store = PersistentMemoryStore::open_with_options(&db_path, field_cipher_from_env()?, options)
+            .map_err(|e| e.to_string())?;
+        Ok(json!({
+            "path": db_path,
+            "schema_version": store.schema_version(),
+            "target_version": prx_memory_storage::SCHEMA_VERSION,
+            "migration": store.migration(),
+        }))
     }
 
     pub fn with_db_path(db_path: impl Into<String>) -> Result<Self, String> {
-        let db_path = db_path.into();
+        let server = Self::open(db_path.into(), None)?;
+        #[cfg(feature = "otel")]
+        server.spawn_otlp_exporter()?;
+        Ok(server)
+    }
+
+    /// A tenant's server on the daemon's `runtime`. The daemon's OTLP exporter covers it.
+    pub(crate) fn for_tenant(db_path: String, runtime: Arc<tokio::runtime::Runtime>) -> Result<Self, String> {
+        Self::open(db_path, Some(runtime))
+    }
+
+    /// Opens the store at `db_path`, on `runtime` or a runtime of its own.
+    fn open(db_path: String, runtime: Option<Arc<tokio::runtime::Runtime>>) -> Result<Self, String> {
+        let shutdown_marker = PathBuf::from(format!("{db_path}.shutdown.json"));
+        check_previous_shutdown(&shutdown_marker, Path::new(&db_path).exists());
+        let jobs_path = format!("{db_path}.jobs.json");
+        let cipher = field_cipher_from_env()?;
+        let mut embed = EmbedRuntime::from_env();
+        // Only tenant servers are handed the daemon's runtime.
+        embed.disk = EmbedDiskCache::path_from_env(&db_path, runtime.is_some())
+            .map(|path| EmbedDiskCache::from_env(&path, cipher.as_ref(), now_ms()));
+        let embed = Arc::new(Mutex::new(embed));
+        let decay = DecayTracker::from_env(&db_path, cipher.clone());
+        let baselines = EvalBaselineFile {
+            path: PathBuf::from(format!("{db_path}.baselines.json")),
+        };
+        let active_ranking = ActiveRankingFile {
+            path: PathBuf::from(format!("{db_path}.ranking.json")),
+        };
+        let feedback = FeedbackFile {
+            path: PathBuf::from(format!("{db_path}.feedback.json")),
+        };
+        let session_file = SessionFile {
+            path: PathBuf::from(format!("{db_path}.sessions.json")),
+            cipher: cipher.clone(),
+        };
+        let mut sessions = session_file.restore();
+        let session_log = session_log_enabled().then(|| {
+            let mut log = SessionLog::new(format!("{db_path}.session-events.jsonl"), cipher.clone());
+            let replayed = sessions_from_records(log.replay(Self::session_ttl_ms()));
+            if !replayed.is_empty() {
+                tracing::info!(count = replayed.len(), "replayed sessions from the session journal");
+            }
+            sessions.extend(replayed);
+            let records = sessions
+                .iter()
+                .flat_map(|(id, state)| state.records(id))
+                .collect::<Vec<_>>();
+            if let Err(error) = log.rewrite(&records, now_ms()) {
+                tracing::warn!(%error, "failed to compact session journal");
+            }
+            Mutex::new(log)
+        });
+        let query_log = query_log_enabled().then(|| {
+            Mutex::new(QueryLog::open(
+                format!("{db_path}.queries.jsonl"),
+                cipher.clone(),
+                env_usize("PRX_MEMORY_QUERY_LOG_MAX", 10_000, 100, 1_000_000),
+            ))
+        });
+        let usage = UsageLog::open(
+            format!("{db_path}.usage.jsonl"),
+            env_usize("PRX_MEMORY_USAGE_MAX_EVENTS", 10_000, 100, 1_000_000),
+        );
+        let mut metrics = MetricsRegistry::from_env();
+        let mut seen = usage.agents().keys().collect::<Vec<_>>();
+        seen.sort();
+        for agent in seen {
+            metrics.usage_agent.record(agent);
+        }
+        let change_log = change_log_enabled()
+            .then(|| ChangeLog::open(format!("{db_path}.changes.jsonl"), cipher.clone()))
+            .transpose()
+            .map_err(|e| format!("failed to open change log: {e}"))?;
+        let follower = FollowerConfig::from_env(&db_path)?.map(|config| Arc::new(Follower::new(config)));
+        let secondary = SecondaryStores::from_env(cipher.as_ref())?;
+        let sync_config = SyncConfig::from_env(&db_path)?;
+        let runtime = match runtime {
+            Some(runtime) => runtime,
+            None => Arc::new(build_shared_runtime()?),
+        };
+        let id_format = match std::env::var("PRX_MEMORY_ID_FORMAT") {
+            Ok(raw) => {
+                IdFormat::parse(&raw).ok_or_else(|| "PRX_MEMORY_ID_FORMAT must be ulid|sequential".to_string())?
+            }
+            Err(_) => IdFormat::default(),
+        };
         let backend = std::env::var("PRX_MEMORY_BACKEND").unwrap_or_else(|_| "json".to_string());
-        let store: Box<dyn StorageBackend> = match backend.as_str() {
+        let mut store: Box<dyn StorageBackend> = match backend.as_str() {
+            #[cfg(feature = "lancedb-backend")]
+            "lancedb" => Box::new(
+                LanceDbBackend::open_with_cipher(db_path, Arc::clone(&runtime), cipher)
+                    .map_err(|e| e.to_string())?
+                    .with_id_format(id_format),
+            ),
             #[cfg(feature = "lancedb-backend")]
-            "lancedb" => Box::new(LanceDbBackend::open(db_path).map_err(|e| e.to_string())?),
-            _ => Box::new(PersistentMemoryStore::open(db_path).map_err(|e| e.to_string())?),
+            "tiered" => {
+                let cold_uri = std::env::var("PRX_MEMORY_COLD_URI").unwrap_or_else(|_| format!("{db_path}.cold"));
+                let cold = LanceDbBackend::open_with_cipher(cold_uri, Arc::clone(&runtime), cipher.clone())
+                    .map_err(|e| e.to_string())?;
+                let hot = PersistentMemoryStore::open_with_options(db_path, cipher, migration_options_from_env())
+                    .map_err(|e| e.to_string())?;
+                let config = TieredConfig {
+                    max_hot_entries: env_usize("PRX_MEMORY_HOT_MAX_ENTRIES", 10_000, 1, 1_000_000),
+                    max_hot_age_ms: u64::try_from(env_usize("PRX_MEMORY_HOT_MAX_AGE_DAYS", 30, 1, 36_500))
+                        .unwrap_or(30)
+                        * 86_400_000,
+                    keep_importance: env_f64("PRX_MEMORY_HOT_KEEP_IMPORTANCE", 0.8, 0.0, 1.0) as f32,
+                };
+                Box::new(
+                    TieredBackend::new(hot, Box::new(cold), config)
+                        .map_err(|e| e.to_string())?
+                        .with_id_format(id_format),
+                )
+            }
+            #[cfg(feature = "postgres-backend")]
+            "postgres" => Box::new(
+                PostgresBackend::open(&postgres_config_from_env()?, Arc::clone(&runtime), cipher)
+                    .map_err(|e| e.to_string())?
+                    .with_id_format(id_format),
+            ),
+            #[cfg(feature = "qdrant-backend")]
+            "qdrant" => Box::new(
+                QdrantBackend::open(qdrant_config_from_env()?, Arc::clone(&runtime), cipher)
+                    .map_err(|e| e.to_string())?
+                    .with_id_format(id_format),
+            ),
+            #[cfg(feature = "redis-backend")]
+            "redis" => {
+                let url = std::env::var("PRX_MEMORY_REDIS_URL")
+                    .map_err(|_| "PRX_MEMORY_REDIS_URL is required when PRX_MEMORY_BACKEND=redis".to_string())?;
+                let prefix = std::env::var("PRX_MEMORY_REDIS_PREFIX").unwrap_or_else(|_| "prx-memory".to_string());
+                let search = std::env::var("PRX_MEMORY_REDIS_SEARCH").is_ok_and(|v| {
+                    let lowered = v.trim().to_ascii_lowercase();
+                    lowered == "1" || lowered == "true" || lowered == "on" || lowered == "yes"
+                });
+                Box::new(
+                    RedisBackend::open(&url, &prefix, search, cipher)
+                        .map_err(|e| e.to_string())?
+                        .with_id_format(id_format),
+                )
+            }
+            #[cfg(feature = "s3-backend")]
+            "s3" => Box::new(
+                S3Backend::open(&s3_config_from_env()?, db_path, Arc::clone(&runtime), cipher)
+                    .map_err(|e| e.to_string())?
+                    .with_id_format(id_format),
+            ),
+            _ => Box::new(
+                PersistentMemoryStore::open_with_options(db_path, cipher, migration_options_from_env())
+                    .map_err(|e| e.to_string())?
+                    .with_id_format(id_format),
+            ),
         };
+        if let Some(log) = change_log {
+            store = Box::new(ChangeRecordingBackend::new(store, log));
+        }
+        let build_indexes = match store.index_status() {
+            Ok(status) => !matches!(status.get("health").and_then(Value::as_str), Some("scan" | "healthy")),
+            Err(error) => {
+                tracing::warn!(%error, "failed to read memory store indexes");
+                false
+            }
+        };
+        let ranking = if let Ok(raw) = std::env::var("PRX_MEMORY_TOKENIZER") {
+            let tokenizer = TokenizerMode::parse(&raw)
+                .ok_or_else(|| "PRX_MEMORY_TOKENIZER must be simple|unicode|cjk-ngram".to_string())?;
+            RankingConfig { tokenizer }
+        } else {
+            active_ranking.load()?.map(|active| active.config).unwrap_or_default()
+        };
+        let mut synonyms = SynonymTable::default();
+        if let Ok(path) = std::env::var("PRX_MEMORY_SYNONYMS_FILE") {
+            synonyms
+                .load_file(&path)
+                .map_err(|e| format!("failed to load synonyms from {path}: {e}"))?;
+        }
+        let backend_stats = store.stats();
+        for key in ["recovery", "change_log_recovery"] {
+            if let Some(report) = backend_stats.get(key).filter(|v| !v.is_null()) {
+                tracing::warn!(%report, kind = key, "memory store was repaired while opening");
+            }
+        }
+        if let Some(report) = backend_stats.get("migration").filter(|v| !v.is_null()) {
+            tracing::info!(%report, "memory store was migrated to the current schema");
+        }
+        if let Some(plaintext) = decay
+            .archive
+            .cipher
+            .as_ref()
+            .map(FieldCipher::plaintext_values)
+            .filter(|n| *n > 0)
+        {
+            tracing::warn!(
+                plaintext,
+                "memory store holds unencrypted values; run memory_rekey to seal them"
+            );
+        }
         let initial_count = store.list(200_000).len();
-        let scopes = ScopeManager::from_env();
-        let standards = StandardizationConfig::from_env();
+        let scopes = ScopeManager::from_env()?;
+        let tool_policy = ToolPolicy::from_env()?;
+        let quotas = QuotaConfig::from_env()?;
+        let experiment = Experiment::from_env()?;
+        let standards = StandardizationConfig::from_env()?;
+        let team_sync = match sync_config {
+            Some(_) if follower.is_some() => {
+                return Err("PRX_MEMORY_SYNC_URL cannot be combined with PRX_MEMORY_FOLLOW_URL".to_string());
+            }
+            Some(_) if id_format == IdFormat::Sequential => {
+                return Err(
+                    "team sync needs PRX_MEMORY_ID_FORMAT=ulid; sequential ids collide across stores".to_string(),
+                );
+            }
+            config => config.map(|config| Arc::new(TeamSync::new(config))),
+        };
+        let store = Arc::new(RwLock::new(store));
+        if build_indexes {
+            spawn_index_build(Arc::clone(&store));
+        }
+        let jobs = Arc::new(Mutex::new(JobRegistry::open(jobs_path)));
+        let notices = Arc::new(Mutex::new(Vec::new()));
+        let interrupted = jobs.lock().running_job_ids();
+        for job_id in interrupted {
+            spawn_reembed_job(
+                Arc::clone(&runtime),
+                Arc::clone(&embed),
+                Arc::clone(&store),
+                Arc::clone(&jobs),
+                Arc::clone(&notices),
+                job_id,
+            );
+        }
+        if let Some(follower) = &follower {
+            follower.spawn(Arc::clone(&runtime), Arc::clone(&store));
+        }
+        if let Some(sync) = &team_sync {
+            let policy = standards.governance.clone();
+            sync.spawn(Arc::clone(&runtime), Arc::clone(&store), move |entry| {
+                sync_eligibility(&policy, entry).is_ok()
+            });
+        }
+        let metrics = Arc::new(Mutex::new(metrics));
         Ok(Self {
-            store: Arc::new(Mutex::new(store)),
+            store,
             scopes,
             standards,
-            auto_store_counter: Mutex::new(initial_count),
-            metrics: Arc::new(Mutex::new(MetricsRegistry::from_env())),
-            sessions: Arc::new(Mutex::new(HashMap::new())),
-            session_counter: Mutex::new(1),
+            auto_store_counter: AtomicUsize::new(initial_count),
+            metrics,
+            sessions: Arc::new(Mutex::new(sessions)),
+            session_counter: AtomicU64::new(1),
+            ended_sessions: Mutex::new(Vec::new()),
+            session_file,
+            session_log,
+            draining: AtomicBool::new(false),
+            shutting_down: AtomicBool::new(false),
+            shutdown_marker,
+            jobs,
+            notices,
+            runtime,
+            embed,
+            ranking: RwLock::new(ranking),
+            synonyms: Arc::new(synonyms),
+            resource_watch: Mutex::new(ResourceWatch::default()),
+            tool_policy,
+            rate_limiter: Mutex::new(ToolRateLimiter::from_env()?),
+            quotas,
+            decay,
+            baselines,
+            active_ranking,
+            query_log,
+            usage: Mutex::new(usage),
+            feedback,
+            experiment,
+            follower,
+            secondary,
+            team_sync,
+            inflight: Mutex::new(HashMap::new()),
+            tenants: None,
         })
     }
 
+    /// Starts OTLP export of this server's metrics and, with a tenant `tenant` attribute, those of
+    /// every open tenant.
+    #[cfg(feature = "otel")]
+    fn spawn_otlp_exporter(&self) -> Result<(), String> {
+        let Some(config) = crate::otel::OtlpConfig::from_env()? else {
+            return Ok(());
+        };
+        let metrics = Arc::clone(&self.metrics);
+        let tenants = self.tenants.as_ref().map(TenantRegistry::open_tenants);
+        crate::otel::spawn_exporter(config, Arc::clone(&self.runtime), move || {
+            let mut exported = metrics.lock().otlp_metrics();
+            for (tenant, server) in tenants.iter().flat_map(crate::tenants::OpenTenants::loaded) {
+                let tenant_metrics = server.metrics.lock().otlp_metrics();
+                crate::otel::merge_tenant_metrics(&mut exported, &tenant, tenant_metrics);
+            }
+            exported
+        });
+        Ok(())
+    }
+
     pub fn handle_request(&self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        self.handle_session_request(request, None, None)
+    }
+
+    /// The lexical ranking settings in effect for this store.
+    fn ranking_config(&self) -> RankingConfig {
+        *self.ranking.read()
+    }
+
+    /// Runs `f` with `config` and this store's synonym dictionary in effect on the current thread,
+    /// so tenants sharing the process keep their own lexical settings.
+    fn with_lexical<T>(&self, config: RankingConfig, f: impl FnOnce() -> T) -> T {
+        with_synonyms(Arc::clone(&self.synonyms), || with_ranking_config(config, f))
+    }
+
+    /// Handles a request on behalf of an HTTP stream session (`None` for stdio and plain `/mcp` calls,
+    /// which share one rate-limit bucket). `token_label` is the stream's authenticated token.
+    fn handle_session_request(
+        &self,
+        request: JsonRpcRequest,
+        session_id: Option<&str>,
+        token_label: Option<&str>,
+    ) -> Option<JsonRpcResponse> {
+        self.with_lexical(self.ranking_config(), || {
+            self.route_session_request(request, session_id, token_label)
+        })
+    }
+
+    fn route_session_request(
+        &self,
+        request: JsonRpcRequest,
+        session_id: Option<&str>,
+        token_label: Option<&str>,
+    ) -> Option<JsonRpcResponse> {
         if request.jsonrpc != "2.0" {
             return Some(JsonRpcResponse::error(
                 request.id.unwrap_or(Value::Null),
@@ -299,15 +1345,32 @@ impl McpServer {
             ));
         }
 
+        if let Some(session_id) = session_id
+            && caller_agent_override().is_none()
+            && let Some(agent) = self.sessions.lock().get(session_id).and_then(|s| s.agent_id.clone())
+        {
+            return with_caller_agent(Some(agent), || {
+                self.route_session_request(request, Some(session_id), token_label)
+            });
+        }
+
         let is_notification = request.id.is_none();
         let id = request.id.clone().unwrap_or(Value::Null);
+        let _span = tracing::info_span!("rpc", method = %request.method, id = %id, session = session_id).entered();
 
         if is_notification && request.method == "notifications/initialized" {
             return None;
         }
+        if is_cancel_method(&request.method) {
+            let cancelled = self.cancel_request(&request.params);
+            return (!is_notification).then(|| JsonRpcResponse::success(id, json!({"cancelled": cancelled})));
+        }
 
         let response = match request.method.as_str() {
             "initialize" => {
+                if let Some(session_id) = session_id {
+                    self.adopt_client_agent(session_id, &request.params, token_label);
+                }
                 let protocol_version = request
                     .params
                     .get("protocolVersion")
@@ -323,8 +1386,8 @@ impl McpServer {
                                 "listChanged": false
                             },
                             "resources": {
-                                "subscribe": false,
-                                "listChanged": false
+                                "subscribe": true,
+                                "listChanged": true
                             },
                             "resourceTemplates": {
                                 "listChanged": false
@@ -335,18 +1398,72 @@ impl McpServer {
             }
             "ping" => JsonRpcResponse::success(id, json!({})),
             "tools/list" => JsonRpcResponse::success(id, self.tools_list_result()),
-            "tools/call" => self.handle_tools_call(id, request.params),
+            "tools/call" => {
+                let key = (!is_notification).then(|| id.to_string());
+                let ctx = CallContext {
+                    session: session_id.map(str::to_string),
+                    ..CallContext::default()
+                };
+                if let Some(key) = &key {
+                    self.inflight.lock().insert(key.clone(), Arc::clone(&ctx.cancelled));
+                }
+                let response = self.handle_tools_call(id, request.params, session_id, ctx);
+                if let Some(key) = key {
+                    self.inflight.lock().remove(&key);
+                }
+                response
+            }
             "resources/list" => JsonRpcResponse::success(id, self.resources_list_result()),
             "resources/templates/list" => JsonRpcResponse::success(id, self.resources_templates_list_result()),
             "resources/read" => self.handle_resources_read(id, request.params),
+            "resources/subscribe" | "resources/unsubscribe" => JsonRpcResponse::error(
+                id,
+                -32602,
+                "resource subscriptions require a stream session (POST /mcp/stream?session=...)",
+            ),
             _ => JsonRpcResponse::error(id, -32601, "method not found"),
         };
 
         Some(response)
     }
 
+    /// Names a stream session's caller after the `clientInfo.name` it sent in `initialize`, unless
+    /// the session already has one or the stream's token is not bound to that agent.
+    fn adopt_client_agent(&self, session_id: &str, params: &Value, token_label: Option<&str>) {
+        let name = params.pointer("/clientInfo/name").and_then(Value::as_str);
+        let Ok(Some(agent)) = self.scopes.requested_agent(name) else {
+            return;
+        };
+        if let Err(message) = self.scopes.authorize_agent(&agent, token_label) {
+            tracing::warn!(session = session_id, %message, "ignoring unbound clientInfo.name");
+            return;
+        }
+        if let Some(state) = self.sessions.lock().get_mut(session_id)
+            && state.agent_id.is_none()
+        {
+            state.agent_id = Some(agent.clone());
+            self.log_session(&SessionRecord::Named {
+                session: session_id.to_string(),
+                agent_id: agent,
+            });
+        }
+    }
+
+    /// Flags an in-flight `tools/call` as cancelled. Accepts MCP `notifications/cancelled`
+    /// (`params.requestId`) and `$/cancelRequest` (`params.id`); unknown or finished ids are ignored.
+    fn cancel_request(&self, params: &Value) -> bool {
+        let Some(target) = params.get("requestId").or_else(|| params.get("id")) else {
+            return false;
+        };
+        self.inflight
+            .lock()
+            .get(&target.to_string())
+            .is_some_and(|flag| !flag.swap(true, AtomicOrdering::Relaxed))
+    }
+
     fn record_tool_metrics(&self, tool: &str, latency_ms: f64, is_error: bool) {
-        let mut locked = self.metrics.lock();
+        let mut guard = self.metrics.lock();
+        let locked = &mut *guard;
         let metric = locked.tool.entry(tool.to_string()).or_default();
         if is_error {
             metric.err = metric.err.saturating_add(1);
@@ -355,14 +1472,41 @@ impl McpServer {
         }
         metric.total_latency_ms += latency_ms;
         metric.max_latency_ms = metric.max_latency_ms.max(latency_ms);
+        observe_latency(&mut metric.latency_buckets, &locked.latency_bounds, latency_ms);
     }
 
     fn record_recall_stage(&self, stage: &str, latency_ms: f64) {
-        let mut locked = self.metrics.lock();
+        let mut guard = self.metrics.lock();
+        let locked = &mut *guard;
         let metric = locked.recall_stage.entry(stage.to_string()).or_default();
         metric.count = metric.count.saturating_add(1);
         metric.total_latency_ms += latency_ms;
         metric.max_latency_ms = metric.max_latency_ms.max(latency_ms);
+        observe_latency(&mut metric.latency_buckets, &locked.latency_bounds, latency_ms);
+    }
+
+    fn record_arm_recall(&self, arm: &str, latency_ms: f64, results: Option<u64>) {
+        let mut guard = self.metrics.lock();
+        let locked = &mut *guard;
+        let metric = locked.experiment_arms.entry(arm.to_string()).or_default();
+        metric.latency.count = metric.latency.count.saturating_add(1);
+        metric.latency.total_latency_ms += latency_ms;
+        metric.latency.max_latency_ms = metric.latency.max_latency_ms.max(latency_ms);
+        observe_latency(&mut metric.latency.latency_buckets, &locked.latency_bounds, latency_ms);
+        match results {
+            None => metric.errors = metric.errors.saturating_add(1),
+            Some(0) => metric.empty = metric.empty.saturating_add(1),
+            Some(n) => metric.results = metric.results.saturating_add(n),
+        }
+        drop(guard);
+    }
+
+    fn record_arm_feedback(&self, arm: &str, useful: u64, irrelevant: u64) {
+        let mut locked = self.metrics.lock();
+        let metric = locked.experiment_arms.entry(arm.to_string()).or_default();
+        metric.useful = metric.useful.saturating_add(useful);
+        metric.irrelevant = metric.irrelevant.saturating_add(irrelevant);
+        drop(locked);
     }
 
     fn record_recall_dimensions(&self, scope: Option<&str>, category: Option<&str>, rerank_provider: Option<&str>) {
@@ -386,6 +1530,84 @@ impl McpServer {
         }
     }
 
+    fn record_agent_usage(&self, op: UsageOp, bytes: usize) {
+        let agent = self.scopes.agent_id();
+        self.metrics.lock().usage_agent.record(&agent);
+        let recorded = self.usage.lock().record(&agent, op, bytes as u64, now_ms());
+        if let Err(error) = recorded {
+            tracing::warn!(%error, "failed to persist agent usage");
+        }
+    }
+
+    /// Checks the scope and agent quotas before `adding` is stored in `scope`.
+    fn check_store_quota(
+        &self,
+        store: &dyn StorageBackend,
+        scope: &str,
+        adding: QuotaUsage,
+    ) -> Result<(), QuotaExceeded> {
+        if let Some(limit) = self.quotas.scope_limit(scope) {
+            self.checked_quota(limit, "scope", scope, scope_usage(store, scope), adding)?;
+        }
+        if self.quotas.agent.is_set() {
+            let agent = self.scopes.agent_id();
+            self.checked_quota(self.quotas.agent, "agent", &agent, self.agent_window_usage(), adding)?;
+        }
+        Ok(())
+    }
+
+    /// [`QuotaLimit::check`] that also queues a `quota` notice when the check fails or the write
+    /// crosses the warning share.
+    fn checked_quota(
+        &self,
+        limit: QuotaLimit,
+        quota: &'static str,
+        subject: &str,
+        used: QuotaUsage,
+        adding: QuotaUsage,
+    ) -> Result<(), QuotaExceeded> {
+        let checked = limit.check(quota, subject, used, adding);
+        let params = match &checked {
+            Err(exceeded) => exceeded.data("quota_exceeded"),
+            Ok(()) => match limit.warning(quota, subject, used, adding) {
+                Some(near) => near.data("quota_warning"),
+                None => return checked,
+            },
+        };
+        let (scope, agent) = if quota == "scope" {
+            (Some(subject.to_string()), None)
+        } else {
+            (None, Some(subject.to_string()))
+        };
+        self.notify(ServerNotice {
+            kind: "quota",
+            scope,
+            agent,
+            params,
+        });
+        checked
+    }
+
+    /// Entries and bytes the calling agent stored within the agent quota window.
+    fn agent_window_usage(&self) -> QuotaUsage {
+        let since = now_ms().saturating_sub(self.quotas.agent_window_ms);
+        let usage = self.usage.lock();
+        usage
+            .get(&self.scopes.agent_id())
+            .map_or_else(QuotaUsage::default, |usage| {
+                usage
+                    .events
+                    .iter()
+                    .filter(|e| e.ts_ms >= since && matches!(e.op, UsageOp::Store))
+                    .fold(QuotaUsage::default(), |acc, e| {
+                        acc.plus(QuotaUsage {
+                            entries: 1,
+                            bytes: e.bytes,
+                        })
+                    })
+            })
+    }
+
     fn record_session_expired(&self, count: usize) {
         if count == 0 {
             return;
@@ -396,6 +1618,277 @@ impl McpServer {
         }
     }
 
+    /// Applies bearer auth to a protected endpoint and counts the outcome per token label.
+    fn authenticate_http(&self, req: &HttpRequest) -> Result<Option<&'static str>, HttpResponse> {
+        match check_bearer_auth(req) {
+            Ok(None) => Ok(None),
+            Ok(Some(label)) => {
+                let mut locked = self.metrics.lock();
+                let count = locked.http_token_requests.entry(label.to_string()).or_insert(0);
+                *count = count.saturating_add(1);
+                Ok(Some(label))
+            }
+            Err(rejection) => {
+                let mut locked = self.metrics.lock();
+                locked.http_auth_failures = locked.http_auth_failures.saturating_add(1);
+                Err(rejection)
+            }
+        }
+    }
+
+    /// The tenants `/metrics` may break down for a request. The breakdown names tenants and their
+    /// activity, so only a valid token sees it: an unbound token gets every open tenant, a bound
+    /// one only its own. `None` serves the daemon's own metrics, as without tenants.
+    fn metrics_tenants(&self, req: &HttpRequest) -> Result<Option<TenantServers>, HttpResponse> {
+        let Some(tenants) = &self.tenants else {
+            return Ok(None);
+        };
+        if !req.headers.contains_key("authorization") {
+            return Ok(None);
+        }
+        let Some(label) = self.authenticate_http(req)? else {
+            return Ok(None);
+        };
+        let mut loaded = tenants.loaded();
+        if let Some(bound) = tenants.bound_tenant(label) {
+            loaded.retain(|(tenant, _)| tenant == bound);
+        }
+        Ok(Some(loaded))
+    }
+
+    /// The tenant store a request belongs to, or `None` for the daemon's own store.
+    fn tenant_server(&self, req: &HttpRequest, token_label: Option<&str>) -> Result<Option<Arc<Self>>, HttpResponse> {
+        let Some(tenants) = &self.tenants else {
+            return Ok(None);
+        };
+        let header = req.headers.get(TENANT_HEADER).map(String::as_str);
+        let tenant = match tenants.resolve(token_label, header) {
+            Ok(Some(tenant)) => tenant,
+            Ok(None) => return Ok(None),
+            Err(message) => {
+                return Err(HttpResponse::json(403, json!({"error":"forbidden","message": message})));
+            }
+        };
+        tenants.server(&tenant).map(Some).map_err(|message| {
+            tracing::warn!(tenant = %tenant, error = %message, "tenant store unavailable");
+            HttpResponse::json(503, json!({"error":"tenant_unavailable","message": message}))
+        })
+    }
+
+    /// Serves `/admin/*`: REST wrappers over the maintenance tools for cron jobs and dashboards.
+    /// Calls go through `handle_tools_call`, so tool policy, rate limits and metrics still apply.
+    fn dispatch_admin_request(&self, req: &HttpRequest, token_label: Option<&str>) -> HttpResponse {
+        let Some(label) = token_label else {
+            return HttpResponse::json(
+                403,
+                json!({"error":"forbidden","message":"admin API requires PRX_MEMORY_HTTP_TOKENS"}),
+            );
+        };
+        if !admin_token_allowed(label) {
+            return HttpResponse::json(
+                403,
+                json!({"error":"forbidden","message": format!("token {label} may not use the admin API")}),
+            );
+        }
+
+        if req.path == "/admin/drain" {
+            if req.method != "POST" {
+                return HttpResponse::json(
+                    405,
+                    json!({"error":"method_not_allowed","message":"use POST /admin/drain"}),
+                );
+            }
+            return match admin_body(req).and_then(|body| {
+                serde_json::from_value::<DrainRequest>(body).map_err(|e| format!("invalid drain request: {e}"))
+            }) {
+                Ok(drain) => {
+                    tracing::info!(token = label, exit = drain.exit, "admin drain");
+                    self.drain(&drain)
+                }
+                Err(message) => HttpResponse::json(400, json!({"error":"invalid_request","message": message})),
+            };
+        }
+
+        let (tool, arguments) = match (req.method.as_str(), req.path.as_str()) {
+            ("POST", "/admin/compact") => ("memory_compact", admin_body(req)),
+            ("POST", "/admin/reembed") => ("memory_reembed", admin_body(req)),
+            ("POST", "/admin/backup") => ("memory_export", admin_body(req).map(backup_export_args)),
+            ("POST", "/admin/sync") => ("memory_sync", admin_body(req)),
+            ("GET", "/admin/jobs") => (
+                "memory_job_status",
+                Ok(json!({
+                    "job_id": req.query.get("job_id"),
+                    "limit": req.query.get("limit").and_then(|v| v.parse::<usize>().ok())
+                })),
+            ),
+            (_, "/admin/compact" | "/admin/reembed" | "/admin/backup" | "/admin/sync" | "/admin/jobs") => {
+                return HttpResponse::json(
+                    405,
+                    json!({"error":"method_not_allowed","message":"use POST /admin/compact|reembed|backup|sync or GET /admin/jobs"}),
+                );
+            }
+            _ => {
+                return HttpResponse::json(404, json!({"error":"not_found","message":"unknown admin endpoint"}));
+            }
+        };
+        let arguments = match arguments {
+            Ok(v) => v,
+            Err(message) => return HttpResponse::json(400, json!({"error":"invalid_request","message": message})),
+        };
+
+        tracing::info!(token = label, tool, "admin request");
+        let response = self.handle_tools_call(
+            Value::Null,
+            json!({"name": tool, "arguments": arguments}),
+            None,
+            CallContext::default(),
+        );
+        tool_http_response(response, 200)
+    }
+
+    /// Serves `GET /changes?from=<seq>&limit=<n>` from the change log. The feed carries entries
+    /// of every scope, so with HTTP tokens configured it needs an admin token.
+    fn serve_changes(&self, req: &HttpRequest, token_label: Option<&str>) -> HttpResponse {
+        if req.method != "GET" && req.method != "POST" {
+            return HttpResponse::json(
+                405,
+                json!({"error":"method_not_allowed","message":"use GET /changes or POST /changes"}),
+            );
+        }
+        if let Some(label) = token_label.filter(|label| !admin_token_allowed(label)) {
+            return HttpResponse::json(
+                403,
+                json!({"error":"forbidden","message": format!("token {label} may not use the change feed")}),
+            );
+        }
+        if req.method == "POST" {
+            return self.accept_sync_push(req);
+        }
+        let args = match rest_query_args(&req.query, &[], &["from", "limit"]) {
+            Ok(v) => v,
+            Err(message) => return HttpResponse::json(400, json!({"error":"invalid_request","message": message})),
+        };
+        let from = args.get("from").and_then(Value::as_u64).unwrap_or(1);
+        let limit = args
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map_or(100, |v| usize::try_from(v).unwrap_or(usize::MAX))
+            .clamp(1, 1_000);
+        let changes = self.store.read().changes(from, limit);
+        match changes {
+            Ok(page) => HttpResponse::json(200, json!(page)),
+            Err(StorageError::InvalidInput(message)) => HttpResponse::json(
+                404,
+                json!({"error":"not_found","message": format!("{message}; set PRX_MEMORY_CHANGE_LOG=1")}),
+            ),
+            Err(err) => HttpResponse::json(500, json!({"error":"storage_error","message": err.to_string()})),
+        }
+    }
+
+    /// Applies events a team sync client pushed to `POST /changes`. Entries this server's governance
+    /// policy would not accept are rejected one by one; deletes and rewrites of them still apply.
+    fn accept_sync_push(&self, req: &HttpRequest) -> HttpResponse {
+        #[derive(Deserialize)]
+        struct SyncPush {
+            events: Vec<ChangeEvent>,
+        }
+        if let Some(follower) = &self.follower {
+            return HttpResponse::json(
+                409,
+                json!({"error":"read_only_follower","message": format!("push to the leader at {}", follower.leader())}),
+            );
+        }
+        let push = match serde_json::from_slice::<SyncPush>(&req.body) {
+            Ok(push) => push,
+            Err(err) => {
+                return HttpResponse::json(
+                    400,
+                    json!({"error":"invalid_request","message": format!("expected {{\"events\": [...]}}: {err}")}),
+                );
+            }
+        };
+        let mut accepted = Vec::new();
+        let mut rejected = Vec::new();
+        for mut event in push.events {
+            if let Some(entry) = event.entry.as_ref().filter(|_| event.op != ChangeOp::Delete)
+                && let Err(reason) = sync_eligibility(&self.standards.governance, entry)
+            {
+                rejected.push(json!({"id": event.id, "reason": reason}));
+                // The replaced entry still goes, as it did on the pushing side.
+                let Some(previous_id) = event.previous_id.take() else {
+                    continue;
+                };
+                event = ChangeEvent {
+                    op: ChangeOp::Delete,
+                    id: previous_id,
+                    entry: None,
+                    ..event
+                };
+            }
+            accepted.push(event);
+        }
+        let applied = self.store.write().apply_changes(&accepted);
+        match applied {
+            Ok(()) => HttpResponse::json(
+                200,
+                json!({"applied": accepted.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), "rejected": rejected}),
+            ),
+            Err(StorageError::InvalidInput(message)) => {
+                HttpResponse::json(409, json!({"error":"unsupported","message": message}))
+            }
+            Err(err) => HttpResponse::json(500, json!({"error":"storage_error","message": err.to_string()})),
+        }
+    }
+
+    /// Serves `/v1/memories`: plain REST over store, recall/list and forget for integrations that
+    /// cannot speak JSON-RPC. Like the admin API it goes through `handle_tools_call`.
+    fn dispatch_rest_request(&self, req: &HttpRequest) -> HttpResponse {
+        let collection = req.path == "/v1/memories";
+        let member = req
+            .path
+            .strip_prefix("/v1/memories/")
+            .filter(|id| !id.is_empty() && !id.contains('/'));
+        let (tool, arguments) = match (req.method.as_str(), collection, member) {
+            ("POST", true, _) => ("memory_store", admin_body(req)),
+            ("GET", true, _) if req.query.contains_key("query") => (
+                "memory_recall",
+                rest_query_args(&req.query, &["query", "scope", "category", "cursor"], &["limit"]),
+            ),
+            ("GET", true, _) => (
+                "memory_list",
+                rest_query_args(&req.query, &["scope", "category", "cursor"], &["limit", "offset"]),
+            ),
+            ("DELETE", _, Some(id)) => ("memory_forget", Ok(json!({"id": id}))),
+            (_, true, _) | (_, _, Some(_)) => {
+                return HttpResponse::json(
+                    405,
+                    json!({"error":"method_not_allowed","message":"use POST|GET /v1/memories or DELETE /v1/memories/<id>"}),
+                );
+            }
+            _ => return HttpResponse::json(404, json!({"error":"not_found","message":"unknown REST endpoint"})),
+        };
+        let arguments = match arguments {
+            Ok(v) => v,
+            Err(message) => return HttpResponse::json(400, json!({"error":"invalid_request","message": message})),
+        };
+
+        let response = self.handle_tools_call(
+            Value::Null,
+            json!({"name": tool, "arguments": arguments}),
+            None,
+            CallContext::default(),
+        );
+        let deleted = response
+            .result
+            .as_ref()
+            .and_then(|r| r.pointer("/structuredContent/deleted"))
+            .and_then(Value::as_bool);
+        if deleted == Some(false) {
+            return HttpResponse::json(404, json!({"error":"not_found","message":"memory id not found"}));
+        }
+        tool_http_response(response, if tool == "memory_store" { 201 } else { 200 })
+    }
+
     fn record_session_access_error(&self, err: SessionAccessError) {
         {
             let mut locked = self.metrics.lock();
@@ -410,10 +1903,8 @@ impl McpServer {
     fn render_metrics_text(&self) -> String {
         let mut lines = vec![
             "# TYPE prx_memory_tool_calls_total counter".to_string(),
-            "# TYPE prx_memory_tool_latency_ms_sum counter".to_string(),
-            "# TYPE prx_memory_tool_latency_ms_count counter".to_string(),
-            "# TYPE prx_memory_recall_stage_latency_ms_sum counter".to_string(),
-            "# TYPE prx_memory_recall_stage_latency_ms_count counter".to_string(),
+            "# TYPE prx_memory_tool_latency_ms histogram".to_string(),
+            "# TYPE prx_memory_recall_stage_latency_ms histogram".to_string(),
             "# TYPE prx_memory_recall_scope_requests_total counter".to_string(),
             "# TYPE prx_memory_recall_category_requests_total counter".to_string(),
             "# TYPE prx_memory_recall_rerank_provider_requests_total counter".to_string(),
@@ -429,6 +1920,16 @@ impl McpServer {
             "# TYPE prx_memory_session_access_errors_total counter".to_string(),
             "# TYPE prx_memory_tool_error_ratio gauge".to_string(),
             "# TYPE prx_memory_alert_state gauge".to_string(),
+            "# TYPE prx_memory_agent_operations_total counter".to_string(),
+            "# TYPE prx_memory_agent_bytes_written_total counter".to_string(),
+            "# TYPE prx_memory_http_requests_total counter".to_string(),
+            "# TYPE prx_memory_http_auth_failures_total counter".to_string(),
+            "# TYPE prx_memory_http_connections_rejected_total counter".to_string(),
+            "# TYPE prx_memory_encryption_plaintext_values_total counter".to_string(),
+            "# TYPE prx_memory_rate_limited_total counter".to_string(),
+            "# TYPE prx_memory_experiment_recall_latency_ms histogram".to_string(),
+            "# TYPE prx_memory_experiment_recall_outcomes_total counter".to_string(),
+            "# TYPE prx_memory_experiment_feedback_total counter".to_string(),
         ];
 
         let active_sessions = self.sessions.lock().len();
@@ -462,6 +1963,13 @@ impl McpServer {
                     "prx_memory_tool_latency_ms_max{{tool=\"{}\"}} {:.3}",
                     tool_label, m.max_latency_ms
                 ));
+                lines.extend(histogram_bucket_lines(
+                    "prx_memory_tool_latency_ms",
+                    &format!("tool=\"{tool_label}\""),
+                    &locked.latency_bounds,
+                    &m.latency_buckets,
+                    m.ok + m.err,
+                ));
                 total_calls = total_calls.saturating_add(m.ok + m.err);
                 total_errors = total_errors.saturating_add(m.err);
             }
@@ -483,6 +1991,50 @@ impl McpServer {
                     "prx_memory_recall_stage_latency_ms_max{{stage=\"{}\"}} {:.3}",
                     stage_label, m.max_latency_ms
                 ));
+                lines.extend(histogram_bucket_lines(
+                    "prx_memory_recall_stage_latency_ms",
+                    &format!("stage=\"{stage_label}\""),
+                    &locked.latency_bounds,
+                    &m.latency_buckets,
+                    m.count,
+                ));
+            }
+
+            if let Some(experiment) = &self.experiment {
+                let experiment_label = prom_label_value(&experiment.name);
+                for arm in &experiment.arms {
+                    let m = locked.experiment_arms.get(&arm.id).cloned().unwrap_or_default();
+                    let labels = format!(
+                        "experiment=\"{experiment_label}\",arm=\"{}\"",
+                        prom_label_value(&arm.id)
+                    );
+                    lines.push(format!(
+                        "prx_memory_experiment_recall_latency_ms_sum{{{labels}}} {:.3}",
+                        m.latency.total_latency_ms
+                    ));
+                    lines.push(format!(
+                        "prx_memory_experiment_recall_latency_ms_count{{{labels}}} {}",
+                        m.latency.count
+                    ));
+                    lines.extend(histogram_bucket_lines(
+                        "prx_memory_experiment_recall_latency_ms",
+                        &labels,
+                        &locked.latency_bounds,
+                        &m.latency.latency_buckets,
+                        m.latency.count,
+                    ));
+                    let hits = m.latency.count.saturating_sub(m.errors + m.empty);
+                    for (outcome, count) in [("hit", hits), ("empty", m.empty), ("error", m.errors)] {
+                        lines.push(format!(
+                            "prx_memory_experiment_recall_outcomes_total{{{labels},outcome=\"{outcome}\"}} {count}"
+                        ));
+                    }
+                    for (verdict, count) in [("useful", m.useful), ("irrelevant", m.irrelevant)] {
+                        lines.push(format!(
+                            "prx_memory_experiment_feedback_total{{{labels},verdict=\"{verdict}\"}} {count}"
+                        ));
+                    }
+                }
             }
 
             for (scope, count) in sorted_counter(&locked.recall_scope.counts) {
@@ -518,6 +2070,10 @@ impl McpServer {
                 "prx_memory_metrics_label_overflow_total{{dimension=\"rerank_provider\"}} {}",
                 locked.recall_rerank_provider.overflow
             ));
+            lines.push(format!(
+                "prx_memory_metrics_label_overflow_total{{dimension=\"agent\"}} {}",
+                locked.usage_agent.overflow
+            ));
             lines.push(format!(
                 "prx_memory_metrics_label_limit{{dimension=\"scope\"}} {}",
                 locked.recall_scope.max_labels
@@ -530,6 +2086,10 @@ impl McpServer {
                 "prx_memory_metrics_label_limit{{dimension=\"rerank_provider\"}} {}",
                 locked.recall_rerank_provider.max_labels
             ));
+            lines.push(format!(
+                "prx_memory_metrics_label_limit{{dimension=\"agent\"}} {}",
+                locked.usage_agent.max_labels
+            ));
 
             lines.push(format!(
                 "prx_memory_recall_remote_rerank_attempts_total {}",
@@ -561,6 +2121,37 @@ impl McpServer {
                 "prx_memory_session_access_errors_total{{kind=\"internal\"}} {}",
                 locked.session_access_poisoned
             ));
+            for (token, count) in sorted_counter(&locked.http_token_requests) {
+                lines.push(format!(
+                    "prx_memory_http_requests_total{{token=\"{}\"}} {}",
+                    prom_label_value(&token),
+                    count
+                ));
+            }
+            lines.push(format!(
+                "prx_memory_http_auth_failures_total {}",
+                locked.http_auth_failures
+            ));
+            lines.push(format!(
+                "prx_memory_http_connections_rejected_total {}",
+                locked.http_connections_rejected
+            ));
+            if let Some(cipher) = &self.decay.archive.cipher {
+                lines.push(format!(
+                    "prx_memory_encryption_plaintext_values_total {}",
+                    cipher.plaintext_values()
+                ));
+            }
+            let mut rate_limited = locked.rate_limited.iter().collect::<Vec<_>>();
+            rate_limited.sort();
+            for ((limit, tool), count) in rate_limited {
+                lines.push(format!(
+                    "prx_memory_rate_limited_total{{limit=\"{}\",tool=\"{}\"}} {}",
+                    prom_label_value(limit),
+                    prom_label_value(tool),
+                    count
+                ));
+            }
 
             let tool_error_ratio = if total_calls == 0 {
                 0.0
@@ -573,8 +2164,10 @@ impl McpServer {
             let ratio_crit = env_f64("PRX_ALERT_TOOL_ERROR_RATIO_CRIT", 0.20, 0.0, 1.0);
             let remote_warn = env_f64("PRX_ALERT_REMOTE_WARNING_RATIO_WARN", 0.25, 0.0, 1.0);
             let remote_crit = env_f64("PRX_ALERT_REMOTE_WARNING_RATIO_CRIT", 0.60, 0.0, 1.0);
-            let label_overflow =
-                locked.recall_scope.overflow + locked.recall_category.overflow + locked.recall_rerank_provider.overflow;
+            let label_overflow = locked.recall_scope.overflow
+                + locked.recall_category.overflow
+                + locked.recall_rerank_provider.overflow
+                + locked.usage_agent.overflow;
 
             lines.push(format!(
                 "prx_memory_alert_state{{signal=\"tool_error_ratio\"}} {}",
@@ -588,9 +2181,35 @@ impl McpServer {
                 "prx_memory_alert_state{{signal=\"metrics_label_overflow\"}} {}",
                 if label_overflow > 0 { 2 } else { 0 }
             ));
+
+            let usage_log = self.usage.lock();
+            let mut agents = usage_log
+                .agents()
+                .iter()
+                .filter(|(agent, _)| locked.usage_agent.counts.contains_key(&sanitize_label_value(agent)))
+                .collect::<Vec<_>>();
+            agents.sort_by(|a, b| a.0.cmp(b.0));
+            for (agent, usage) in agents {
+                let agent_label = prom_label_value(&agent);
+                for (op, count) in [
+                    ("store", usage.stores),
+                    ("recall", usage.recalls),
+                    ("forget", usage.forgets),
+                ] {
+                    lines.push(format!(
+                        "prx_memory_agent_operations_total{{agent=\"{}\",op=\"{}\"}} {}",
+                        agent_label, op, count
+                    ));
+                }
+                lines.push(format!(
+                    "prx_memory_agent_bytes_written_total{{agent=\"{}\"}} {}",
+                    agent_label, usage.bytes_written
+                ));
+            }
+            drop(usage_log);
         }
 
-        let embed_stats = embed_runtime_stats();
+        let embed_stats = self.embed.lock().stats.clone();
         lines.push(format!("prx_memory_embed_cache_hits_total {}", embed_stats.cache_hits));
         lines.push(format!(
             "prx_memory_embed_cache_misses_total {}",
@@ -608,6 +2227,31 @@ impl McpServer {
             "prx_memory_embed_rate_wait_ms_total {}",
             embed_stats.rate_wait_ms_total
         ));
+        lines.push(format!(
+            "prx_memory_embed_disk_cache_hits_total {}",
+            embed_stats.disk_hits
+        ));
+        lines.push(format!(
+            "prx_memory_embed_disk_cache_misses_total {}",
+            embed_stats.disk_misses
+        ));
+        lines.push(format!(
+            "prx_memory_embed_disk_cache_evictions_total {}",
+            embed_stats.disk_evictions
+        ));
+        lines.push(format!(
+            "prx_memory_embed_disk_cache_write_errors_total {}",
+            embed_stats.disk_write_errors
+        ));
+        let mut served = embed_stats.served_by.into_iter().collect::<Vec<_>>();
+        served.sort();
+        for (provider, count) in served {
+            lines.push(format!(
+                "prx_memory_embed_requests_total{{provider=\"{}\"}} {}",
+                prom_label_value(&provider),
+                count
+            ));
+        }
         lines.join("\n")
     }
 
@@ -629,8 +2273,10 @@ impl McpServer {
         } else {
             locked.remote_rerank_warnings as f64 / locked.remote_rerank_attempts as f64
         };
-        let label_overflow_total =
-            locked.recall_scope.overflow + locked.recall_category.overflow + locked.recall_rerank_provider.overflow;
+        let label_overflow_total = locked.recall_scope.overflow
+            + locked.recall_category.overflow
+            + locked.recall_rerank_provider.overflow
+            + locked.usage_agent.overflow;
         let ratio_warn = env_f64("PRX_ALERT_TOOL_ERROR_RATIO_WARN", 0.05, 0.0, 1.0);
         let ratio_crit = env_f64("PRX_ALERT_TOOL_ERROR_RATIO_CRIT", 0.20, 0.0, 1.0);
         let remote_warn = env_f64("PRX_ALERT_REMOTE_WARNING_RATIO_WARN", 0.25, 0.0, 1.0);
@@ -644,9 +2290,40 @@ impl McpServer {
             .max()
             .unwrap_or(0);
 
+        let experiment = self.experiment.as_ref().map(|experiment| {
+            let arms = experiment
+                .arms
+                .iter()
+                .map(|arm| {
+                    let m = locked.experiment_arms.get(&arm.id).cloned().unwrap_or_default();
+                    let recalls = m.latency.count;
+                    let as_f64 = |n: u64| f64::from(u32::try_from(n).unwrap_or(u32::MAX));
+                    let ratio = |n: u64, of: u64| if of == 0 { 0.0 } else { as_f64(n) / as_f64(of) };
+                    json!({
+                        "id": arm.id,
+                        "weight": arm.weight,
+                        "config": arm.config,
+                        "recalls": recalls,
+                        "error_ratio": ratio(m.errors, recalls),
+                        "empty_ratio": ratio(m.empty, recalls),
+                        "avg_results": ratio(m.results, recalls.saturating_sub(m.errors)),
+                        "avg_latency_ms": if recalls == 0 { 0.0 } else { m.latency.total_latency_ms / as_f64(recalls) },
+                        "max_latency_ms": m.latency.max_latency_ms,
+                        "feedback": {
+                            "useful": m.useful,
+                            "irrelevant": m.irrelevant,
+                            "useful_ratio": ratio(m.useful, m.useful + m.irrelevant)
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({"name": experiment.name, "arms": arms})
+        });
+
         json!({
             "status": "ok",
             "overall_alert_level": overall,
+            "experiment": experiment,
             "tool_error_ratio": tool_error_ratio,
             "remote_warning_ratio": remote_warning_ratio,
             "label_overflow_total": label_overflow_total,
@@ -667,7 +2344,8 @@ impl McpServer {
             "cardinality_limits": {
                 "recall_scope": locked.recall_scope.max_labels,
                 "recall_category": locked.recall_category.max_labels,
-                "rerank_provider": locked.recall_rerank_provider.max_labels
+                "rerank_provider": locked.recall_rerank_provider.max_labels,
+                "agent": locked.usage_agent.max_labels
             }
         })
     }
@@ -680,112 +2358,455 @@ impl McpServer {
             .clamp(1_000, 86_400_000)
     }
 
-    fn cleanup_expired_sessions_locked(sessions: &mut HashMap<String, SessionState>, now: u64) -> usize {
-        let before = sessions.len();
-        sessions.retain(|_, state| state.lease_expires_ms > now);
-        before.saturating_sub(sessions.len())
+    /// Drops expired sessions and queues their working memory for promotion.
+    fn cleanup_expired_sessions_locked(&self, sessions: &mut HashMap<String, SessionState>, now: u64) -> usize {
+        let expired = sessions
+            .iter()
+            .filter(|(_, state)| state.lease_expires_ms <= now)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        for id in &expired {
+            self.end_session_locked(sessions, id);
+        }
+        expired.len()
     }
 
-    fn create_session(&self) -> (String, u64) {
-        let mut counter = self.session_counter.lock();
-        let id = format!("sess-{}-{}", now_ms(), *counter);
-        *counter = counter.saturating_add(1);
+    /// Removes a session, queueing its notes for promotion. Returns whether it existed.
+    fn end_session_locked(&self, sessions: &mut HashMap<String, SessionState>, session_id: &str) -> bool {
+        let Some(state) = sessions.remove(session_id) else {
+            return false;
+        };
+        self.log_session(&SessionRecord::Ended {
+            session: session_id.to_string(),
+        });
+        if !state.notes.is_empty() {
+            self.ended_sessions.lock().push(EndedSession {
+                id: session_id.to_string(),
+                agent_id: state.agent_id,
+                notes: state.notes,
+            });
+        }
+        true
+    }
 
-        let now = now_ms();
-        let lease_expires_ms = now.saturating_add(Self::session_ttl_ms());
-        let mut sessions = self.sessions.lock();
-        let expired = Self::cleanup_expired_sessions_locked(&mut sessions, now);
+    /// Expires idle sessions and promotes the working memory of every session that has ended,
+    /// on this server and on each open tenant. While draining, the remaining sessions are saved again
+    /// so acks and notes that arrive after the drain are kept.
+    fn reap_sessions(&self) {
+        self.deliver_notices();
+        let expired = self.cleanup_expired_sessions_locked(&mut self.sessions.lock(), now_ms());
         self.record_session_expired(expired);
-        sessions.insert(
-            id.clone(),
-            SessionState {
-                next_seq: 1,
-                events: VecDeque::new(),
-                last_touch_ms: now,
-                acked_seq: 0,
-                lease_expires_ms,
-            },
-        );
+        if self.draining.load(AtomicOrdering::Relaxed)
+            && let Err(error) = self.save_sessions()
         {
-            let mut locked = self.metrics.lock();
-            locked.sessions_created = locked.sessions_created.saturating_add(1);
+            tracing::warn!(%error, "failed to save sessions while draining");
+        }
+        if let Some(log) = &self.session_log {
+            // Hold the sessions lock across the rewrite so no append lands in between.
+            let sessions = self.sessions.lock();
+            let mut log = log.lock();
+            if log.needs_compaction() {
+                let records = sessions
+                    .iter()
+                    .flat_map(|(id, state)| state.records(id))
+                    .collect::<Vec<_>>();
+                if let Err(error) = log.rewrite(&records, now_ms()) {
+                    tracing::warn!(%error, "failed to compact session journal");
+                }
+            }
+            drop(log);
+            drop(sessions);
+        }
+        let ended = std::mem::take(&mut *self.ended_sessions.lock());
+        for session in ended {
+            match self.promote_session(&session) {
+                Ok(promoted) => tracing::info!(session = %session.id, %promoted, "promoted session working memory"),
+                Err((_, error)) => tracing::warn!(session = %session.id, %error, "session promotion failed"),
+            }
+        }
+        for (_, server) in self.tenants.as_ref().map(TenantRegistry::loaded).unwrap_or_default() {
+            server.reap_sessions();
         }
-        (id, lease_expires_ms)
     }
 
-    fn renew_session_lease(&self, session_id: &str) -> Result<u64, SessionAccessError> {
-        let now = now_ms();
-        let mut sessions = self.sessions.lock();
-        let expired = Self::cleanup_expired_sessions_locked(&mut sessions, now);
-        self.record_session_expired(expired);
-        let Some(state) = sessions.get_mut(session_id) else {
-            return Err(SessionAccessError::NotFound);
-        };
-        if state.lease_expires_ms <= now {
-            let _ = sessions.remove(session_id);
-            return Err(SessionAccessError::Expired);
+    /// Distills a session's notes into the review queue on behalf of the session's caller.
+    fn promote_session(&self, session: &EndedSession) -> Result<Value, (i64, String)> {
+        with_caller_agent(session.agent_id.clone(), || {
+            self.distill_to_review(MemoryDistillInput {
+                messages: session.notes.clone(),
+                scope: None,
+                tags: Some(vec![SESSION_SOURCE_TAG.to_string()]),
+                project_tag: None,
+                tool_tag: None,
+                domain_tag: None,
+                max_candidates: None,
+                use_vector: None,
+                dry_run: None,
+            })
+            .map(|(promoted, _)| promoted)
+        })
+    }
+
+    /// Stops new sessions, waits up to `timeout_ms` for in-flight tool calls, then saves the live
+    /// sessions with their unread events and notes so a restarted daemon picks them up. With `exit`
+    /// the process exits once the response is sent.
+    fn drain(&self, request: &DrainRequest) -> HttpResponse {
+        self.draining.store(true, AtomicOrdering::Relaxed);
+        let deadline = Instant::now() + Duration::from_millis(request.timeout_ms.unwrap_or(10_000).min(300_000));
+        let inflight = self.wait_for_inflight(deadline);
+        let mut saved = Vec::new();
+        for (tenant, server) in self.each_server() {
+            let server = server.as_deref().unwrap_or(self);
+            server.draining.store(true, AtomicOrdering::Relaxed);
+            match server.save_sessions() {
+                Ok(summary) => saved.push(json!({"tenant": tenant, "sessions": summary})),
+                Err(message) => {
+                    tracing::warn!(%tenant, error = %message, "failed to save sessions");
+                    return HttpResponse::json(500, json!({"error":"internal_error","message": message}));
+                }
+            }
         }
-        state.last_touch_ms = now;
-        state.lease_expires_ms = now.saturating_add(Self::session_ttl_ms());
-        {
-            let mut locked = self.metrics.lock();
-            locked.sessions_renewed = locked.sessions_renewed.saturating_add(1);
+        if request.exit {
+            self.close_stores("drain", inflight == 0);
+            std::thread::spawn(|| {
+                // Give the connection handler time to write the response.
+                std::thread::sleep(Duration::from_millis(200));
+                tracing::info!("drained; exiting");
+                std::process::exit(0);
+            });
         }
-        Ok(state.lease_expires_ms)
+        HttpResponse::json(
+            200,
+            json!({"draining": true, "inflight": inflight, "saved": saved, "exit": request.exit}),
+        )
     }
 
-    fn append_session_event(&self, session_id: &str, payload: Value) -> Result<(u64, u64), SessionAccessError> {
-        let now = now_ms();
-        let mut sessions = self.sessions.lock();
-        let expired = Self::cleanup_expired_sessions_locked(&mut sessions, now);
-        self.record_session_expired(expired);
-        let Some(state) = sessions.get_mut(session_id) else {
-            return Err(SessionAccessError::NotFound);
-        };
-        if state.lease_expires_ms <= now {
-            let _ = sessions.remove(session_id);
-            return Err(SessionAccessError::Expired);
-        }
-        let seq = state.next_seq;
-        state.next_seq = state.next_seq.saturating_add(1);
-        state.last_touch_ms = now;
-        state.lease_expires_ms = now.saturating_add(Self::session_ttl_ms());
-        state.events.push_back(StreamEvent {
-            seq,
-            payload,
-            created_ms: now,
-        });
-        while state.events.len() > 512 {
-            let _ = state.events.pop_front();
+    /// The root server (`None`) and every loaded tenant server, labelled as in admin responses.
+    fn each_server(&self) -> Vec<(String, Option<Arc<Self>>)> {
+        let mut servers = vec![("default".to_string(), None)];
+        if let Some(tenants) = &self.tenants {
+            servers.extend(
+                tenants
+                    .loaded()
+                    .into_iter()
+                    .map(|(tenant, server)| (tenant, Some(server))),
+            );
         }
-        Ok((seq, state.lease_expires_ms))
+        servers
     }
 
-    fn collect_session_events(
-        &self,
-        session_id: &str,
-        from_seq: u64,
-        limit: usize,
-        ack_seq: Option<u64>,
-    ) -> Result<SessionEventPage, SessionAccessError> {
-        let now = now_ms();
-        let mut sessions = self.sessions.lock();
-        let expired = Self::cleanup_expired_sessions_locked(&mut sessions, now);
-        self.record_session_expired(expired);
-        let Some(state) = sessions.get_mut(session_id) else {
-            return Err(SessionAccessError::NotFound);
-        };
-        if state.lease_expires_ms <= now {
-            let _ = sessions.remove(session_id);
-            return Err(SessionAccessError::Expired);
+    /// Waits for in-flight tool calls until `deadline` and returns how many are still running.
+    fn wait_for_inflight(&self, deadline: Instant) -> usize {
+        while !self.inflight.lock().is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
         }
-        let ack_applied = ack_seq.map(|ack| {
-            state.acked_seq = state.acked_seq.max(ack);
-            while matches!(state.events.front(), Some(ev) if ev.seq <= state.acked_seq) {
-                let _ = state.events.pop_front();
+        self.inflight.lock().len()
+    }
+
+    fn shutdown_timeout() -> Duration {
+        Duration::from_millis(env_usize("PRX_MEMORY_SHUTDOWN_TIMEOUT_MS", 10_000, 0, 300_000) as u64)
+    }
+
+    /// Graceful shutdown after SIGINT/SIGTERM: refuses new sessions, waits for in-flight tool calls
+    /// up to `PRX_MEMORY_SHUTDOWN_TIMEOUT_MS`, saves stream sessions like a drain, then flushes
+    /// every store. Returns whether everything finished in time.
+    fn shutdown(&self, reason: &str) -> bool {
+        self.draining.store(true, AtomicOrdering::Relaxed);
+        let inflight = self.wait_for_inflight(Instant::now() + Self::shutdown_timeout());
+        if inflight > 0 {
+            tracing::warn!(inflight, "shutdown deadline passed with tool calls still running");
+        }
+        let mut clean = inflight == 0;
+        for (tenant, server) in self.each_server() {
+            let server = server.as_deref().unwrap_or(self);
+            server.draining.store(true, AtomicOrdering::Relaxed);
+            if let Err(error) = server.save_sessions() {
+                tracing::warn!(%tenant, %error, "failed to save sessions");
+                clean = false;
+            }
+        }
+        self.close_stores(reason, clean) && clean
+    }
+
+    /// [`Self::close_store`] for the root server and every loaded tenant.
+    fn close_stores(&self, reason: &str, clean: bool) -> bool {
+        // Counted rather than `all`, which would stop closing stores at the first failure.
+        self.each_server()
+            .iter()
+            .map(|(_, server)| server.as_deref().unwrap_or(self).close_store(reason, clean))
+            .filter(|closed| !closed)
+            .count()
+            == 0
+    }
+
+    /// Flushes the usage log and store and, when the shutdown was `clean`, writes the shutdown marker. Unfinished
+    /// calls may still be writing, so an unclean shutdown leaves no marker behind.
+    fn close_store(&self, reason: &str, clean: bool) -> bool {
+        let usage = self.usage.lock().flush();
+        if let Err(error) = usage {
+            tracing::warn!(%error, "failed to persist agent usage");
+        }
+        let flushed = self.store.write().flush();
+        if let Err(error) = flushed {
+            tracing::warn!(%error, "failed to flush store");
+            return false;
+        }
+        if !clean {
+            return false;
+        }
+        let marker = json!({"reason": reason, "shutdown_ms": now_ms()});
+        if let Err(error) = fs::write(&self.shutdown_marker, marker.to_string()) {
+            tracing::warn!(%error, "failed to write shutdown marker");
+            return false;
+        }
+        true
+    }
+
+    /// Runs [`Self::shutdown`] on SIGINT or SIGTERM and exits: `0` when it finished cleanly, `1`
+    /// otherwise. `wake` is the HTTP listener, connected to once so its accept loop notices. The
+    /// watcher ends when the returned guard is dropped, so a server loop can still return on its own.
+    fn exit_on_signal<'scope>(
+        &'scope self,
+        scope: &'scope std::thread::Scope<'scope, '_>,
+        wake: Option<std::net::SocketAddr>,
+    ) -> io::Result<SignalWatch> {
+        let wait = register_shutdown_signals(self.runtime.handle().clone())?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let signals = tx.clone();
+        // Not scoped: it blocks until a signal arrives, which may be never.
+        std::thread::spawn(move || {
+            let _ = signals.send(Some(wait()));
+        });
+        scope.spawn(move || {
+            let Ok(Some(signal)) = rx.recv() else {
+                return;
+            };
+            tracing::info!(signal, "shutting down");
+            self.shutting_down.store(true, AtomicOrdering::Relaxed);
+            if let Some(addr) = wake {
+                let _ = TcpStream::connect(addr);
+            }
+            // A flush stuck behind a hung call must not keep the process alive forever.
+            let hard_deadline = Self::shutdown_timeout() + SHUTDOWN_FLUSH_GRACE;
+            std::thread::spawn(move || {
+                std::thread::sleep(hard_deadline);
+                tracing::error!("shutdown did not finish in time; exiting");
+                std::process::exit(1);
+            });
+            let clean = self.shutdown(signal);
+            tracing::info!(clean, "shutdown finished");
+            std::process::exit(i32::from(!clean));
+        });
+        Ok(SignalWatch(tx))
+    }
+
+    /// Writes the live sessions to `session_file` and returns what was saved.
+    fn save_sessions(&self) -> Result<Value, String> {
+        let sessions = self.sessions.lock().clone();
+        let events = sessions.values().map(|s| s.events.len()).sum::<usize>();
+        let notes = sessions.values().map(|s| s.notes.len()).sum::<usize>();
+        self.session_file.save(&sessions)?;
+        Ok(json!({"count": sessions.len(), "events": events, "notes": notes}))
+    }
+
+    /// Queues a server notification for the session streams; see [`ServerNotice`].
+    fn notify(&self, notice: ServerNotice) {
+        self.notices.lock().push(notice);
+    }
+
+    /// Pushes queued notices into the streams of sessions that asked for their kind and whose
+    /// caller the notice is for.
+    fn deliver_notices(&self) {
+        let pending = std::mem::take(&mut *self.notices.lock());
+        if pending.is_empty() {
+            return;
+        }
+        let now = now_ms();
+        let mut sessions = self.sessions.lock();
+        for (session, state) in sessions.iter_mut() {
+            let agent = state
+                .agent_id
+                .clone()
+                .unwrap_or_else(|| self.scopes.default_agent_id.clone());
+            for notice in &pending {
+                let addressed = state.wants(notice.kind)
+                    && notice.agent.as_ref().is_none_or(|target| *target == agent)
+                    && notice.scope.as_deref().is_none_or(|scope| {
+                        with_caller_agent(Some(agent.clone()), || self.scopes.can_access_scope(scope))
+                    });
+                if !addressed {
+                    continue;
+                }
+                let payload = json!({
+                    "jsonrpc": "2.0",
+                    "method": format!("notifications/prx/{}", notice.kind),
+                    "params": notice.params
+                });
+                let seq = state.push_event(payload.clone(), now);
+                self.log_session(&SessionRecord::Event {
+                    session: session.clone(),
+                    seq,
+                    created_ms: now,
+                    payload,
+                });
             }
-            state.acked_seq
+        }
+        drop(sessions);
+    }
+
+    /// [`store_layer_with_rules`] with this server's rules; a maintenance pass the write triggers
+    /// is announced to sessions.
+    fn store_layer(
+        &self,
+        store: &mut dyn StorageBackend,
+        request: StoreLayerRequest,
+    ) -> Result<StoreLayerOutcome, StoreLayerError> {
+        let outcome = store_layer_with_rules(self, store, request)?;
+        if let Some(report) = &outcome.auto_maintenance {
+            self.notify(ServerNotice {
+                kind: "maintenance",
+                scope: None,
+                agent: None,
+                params: json!(report),
+            });
+        }
+        Ok(outcome)
+    }
+
+    /// Appends to the session journal when `PRX_MEMORY_SESSION_LOG` is on. Called with the
+    /// sessions lock held, so records land in the order the changes were made.
+    fn log_session(&self, record: &SessionRecord) {
+        if let Some(log) = &self.session_log
+            && let Err(error) = log.lock().append(record, now_ms())
+        {
+            tracing::warn!(%error, "failed to write session journal");
+        }
+    }
+
+    /// Ends a session now instead of at lease expiry and promotes its notes before returning.
+    /// The promotion result is `null` when the session kept no notes.
+    fn close_session(&self, session_id: &str) -> Result<Value, SessionAccessError> {
+        let mut sessions = self.sessions.lock();
+        let expired = self.cleanup_expired_sessions_locked(&mut sessions, now_ms());
+        self.record_session_expired(expired);
+        if !self.end_session_locked(&mut sessions, session_id) {
+            return Err(SessionAccessError::NotFound);
+        }
+        drop(sessions);
+        let mut ended = self.ended_sessions.lock();
+        let Some(idx) = ended.iter().position(|s| s.id == session_id) else {
+            return Ok(Value::Null);
+        };
+        let session = ended.swap_remove(idx);
+        drop(ended);
+        Ok(self.promote_session(&session).unwrap_or_else(|(_, error)| {
+            tracing::warn!(session = %session.id, %error, "session promotion failed");
+            json!({"error": error, "notes": session.notes.len()})
+        }))
+    }
+
+    /// Starts a stream session taking the given server notification kinds (`None` for all).
+    fn create_session(&self, notifications: Option<BTreeSet<String>>) -> (String, u64) {
+        let seq = self.session_counter.fetch_add(1, AtomicOrdering::Relaxed);
+        let id = format!("sess-{}-{seq}", now_ms());
+
+        let now = now_ms();
+        let mut state = SessionState::new(caller_agent_override(), now);
+        state.notifications = notifications;
+        let lease_expires_ms = state.lease_expires_ms;
+        let mut sessions = self.sessions.lock();
+        let expired = self.cleanup_expired_sessions_locked(&mut sessions, now);
+        self.record_session_expired(expired);
+        self.log_session(&SessionRecord::Started {
+            session: id.clone(),
+            agent_id: state.agent_id.clone(),
+        });
+        if let Some(kinds) = &state.notifications {
+            self.log_session(&SessionRecord::Filtered {
+                session: id.clone(),
+                kinds: kinds.iter().cloned().collect(),
+            });
+        }
+        sessions.insert(id.clone(), state);
+        drop(sessions);
+        {
+            let mut locked = self.metrics.lock();
+            locked.sessions_created = locked.sessions_created.saturating_add(1);
+        }
+        (id, lease_expires_ms)
+    }
+
+    fn renew_session_lease(&self, session_id: &str) -> Result<u64, SessionAccessError> {
+        let now = now_ms();
+        let mut sessions = self.sessions.lock();
+        let expired = self.cleanup_expired_sessions_locked(&mut sessions, now);
+        self.record_session_expired(expired);
+        let Some(state) = sessions.get_mut(session_id) else {
+            return Err(SessionAccessError::NotFound);
+        };
+        if state.lease_expires_ms <= now {
+            self.end_session_locked(&mut sessions, session_id);
+            return Err(SessionAccessError::Expired);
+        }
+        state.last_touch_ms = now;
+        state.lease_expires_ms = now.saturating_add(Self::session_ttl_ms());
+        {
+            let mut locked = self.metrics.lock();
+            locked.sessions_renewed = locked.sessions_renewed.saturating_add(1);
+        }
+        Ok(state.lease_expires_ms)
+    }
+
+    fn append_session_event(&self, session_id: &str, payload: Value) -> Result<(u64, u64), SessionAccessError> {
+        let now = now_ms();
+        let mut sessions = self.sessions.lock();
+        let expired = self.cleanup_expired_sessions_locked(&mut sessions, now);
+        self.record_session_expired(expired);
+        let Some(state) = sessions.get_mut(session_id) else {
+            return Err(SessionAccessError::NotFound);
+        };
+        if state.lease_expires_ms <= now {
+            self.end_session_locked(&mut sessions, session_id);
+            return Err(SessionAccessError::Expired);
+        }
+        let seq = state.push_event(payload.clone(), now);
+        self.log_session(&SessionRecord::Event {
+            session: session_id.to_string(),
+            seq,
+            created_ms: now,
+            payload,
         });
+        state.last_touch_ms = now;
+        state.lease_expires_ms = now.saturating_add(Self::session_ttl_ms());
+        Ok((seq, state.lease_expires_ms))
+    }
+
+    fn collect_session_events(
+        &self,
+        session_id: &str,
+        from_seq: u64,
+        limit: usize,
+        ack_seq: Option<u64>,
+    ) -> Result<SessionEventPage, SessionAccessError> {
+        let now = now_ms();
+        let mut sessions = self.sessions.lock();
+        let expired = self.cleanup_expired_sessions_locked(&mut sessions, now);
+        self.record_session_expired(expired);
+        let Some(state) = sessions.get_mut(session_id) else {
+            return Err(SessionAccessError::NotFound);
+        };
+        if state.lease_expires_ms <= now {
+            self.end_session_locked(&mut sessions, session_id);
+            return Err(SessionAccessError::Expired);
+        }
+        let before = state.acked_seq;
+        let ack_applied = ack_seq.map(|ack| state.ack(ack));
+        if state.acked_seq > before {
+            self.log_session(&SessionRecord::Acked {
+                session: session_id.to_string(),
+                seq: state.acked_seq,
+            });
+        }
 
         state.last_touch_ms = now;
         state.lease_expires_ms = now.saturating_add(Self::session_ttl_ms());
@@ -809,6 +2830,15 @@ impl McpServer {
     }
 
     fn tools_list_result(&self) -> Value {
+        let source_schema = json!({
+            "type": "object",
+            "properties": {
+                "url": {"type": "string"},
+                "tool": {"type": "string"},
+                "conversation_id": {"type": "string"},
+                "commit": {"type": "string"}
+            }
+        });
         json!({
             "tools": [
                 {
@@ -828,18 +2858,22 @@ impl McpServer {
                             "tags": {"type": "array", "items": {"type": "string"}},
                             "project_tag": {"type": "string"},
                             "tool_tag": {"type": "string"},
-                            "domain_tag": {"type": "string"}
+                            "domain_tag": {"type": "string"},
+                            "extract_entities": {"type": "boolean"},
+                            "override": {"type": "boolean", "description": "Store a governed write even when it duplicates an existing memory."},
+                            "suggest_importance": {"type": "boolean", "description": "Suggest importance_level from the text when neither importance field is given (default PRX_MEMORY_SUGGEST_IMPORTANCE)."},
+                            "source": source_schema
                         }
                     }
                 },
                 {
                     "name": "memory_recall",
-                    "description": "Recall memories using lexical ranking, with optional third-party semantic rerank.",
+                    "description": "Recall memories using lexical ranking, with optional third-party semantic rerank. queries runs several queries concurrently and fuses their results.",
                     "inputSchema": {
                         "type": "object",
-                        "required": ["query"],
                         "properties": {
-                            "query": {"type": "string"},
+                            "query": {"type": "string", "description": "Free text; also accepts \"quoted phrases\", AND/OR/NOT or -term, and tag:/scope:/category: prefixes as filters."},
+                            "queries": {"type": "array", "items": {"type": "string"}, "minItems": 1, "maxItems": 8, "description": "Run these queries instead of query and fuse the results by reciprocal rank, with per-query attribution."},
                             "scope": {"type": "string"},
                             "category": {"type": "string"},
                             "limit": {"type": "integer"},
@@ -849,17 +2883,105 @@ impl McpServer {
                             "rerank_provider": {"type": "string", "enum": ["jina", "none"]},
                             "vector_weight": {"type": "number"},
                             "lexical_weight": {"type": "number"},
-                            "candidate_pool": {"type": "integer"}
+                            "candidate_pool": {"type": "integer"},
+                            "expand_relations": {"type": "boolean"},
+                            "cursor": {"type": "string"},
+                            "explain": {"type": "boolean"},
+                            "diversity": {"type": "number", "minimum": 0, "maximum": 1},
+                            "fusion": {"type": "string", "enum": ["linear", "rrf"]},
+                            "timeout_ms": {"type": "integer", "minimum": 0},
+                            "include_pending": {"type": "boolean"},
+                            "include_archived": {"type": "boolean"},
+                            "include_secondary": {"type": "boolean", "description": "Also search the read-only secondary stores and label each result with its store (default true)."},
+                            "feedback_weight": {"type": "number", "minimum": 0, "maximum": 1},
+                            "source": source_schema,
+                            "stream": {"type": "boolean", "description": "In a stream session with use_remote, push the local ranking as a notifications/prx/recall_partial event before the rerank finishes."},
+                            "max_chars": {"type": "integer", "minimum": 1, "description": "Pack the best results whole into a context block of at most this many characters."},
+                            "max_tokens": {"type": "integer", "minimum": 1, "description": "Like max_chars, at about 4 characters per token."},
+                            "summarize_overflow": {"type": "boolean", "description": "Clip results that do not fit whole to an excerpt of their first sentence."},
+                            "render": {"type": "string", "enum": ["markdown", "xml", "plain", "compact"], "description": "Format the results as a ready-to-inject memory block with ids, categories and importance."}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_recall_context",
+                    "description": "Recall memories relevant to recent conversation messages. Salient terms are extracted server-side and recalled as several fused queries.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["messages"],
+                        "properties": {
+                            "messages": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["role", "content"],
+                                    "properties": {"role": {"type":"string"}, "content": {"type":"string"}}
+                                }
+                            },
+                            "max_messages": {"type": "integer", "minimum": 1, "maximum": 50},
+                            "max_terms": {"type": "integer", "minimum": 1, "maximum": 32},
+                            "scope": {"type": "string"},
+                            "category": {"type": "string"},
+                            "limit": {"type": "integer"},
+                            "use_vector": {"type": "boolean"},
+                            "use_remote": {"type": "boolean"},
+                            "expand_relations": {"type": "boolean"},
+                            "include_archived": {"type": "boolean"},
+                            "source": source_schema,
+                            "max_chars": {"type": "integer", "minimum": 1},
+                            "max_tokens": {"type": "integer", "minimum": 1},
+                            "summarize_overflow": {"type": "boolean"},
+                            "render": {"type": "string", "enum": ["markdown", "xml", "plain", "compact"]}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_session_note",
+                    "description": "Add a note to the stream session's working memory. When the session is closed or its lease expires, the notes are distilled into governed long-term entries in the review queue.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["text"],
+                        "properties": {
+                            "text": {"type": "string"},
+                            "role": {"type": "string"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_recall_plan",
+                    "description": "Plan a recall for a task: which scopes, categories and tags its memories likely sit in, plus a ready-to-send memory_recall payload.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["task"],
+                        "properties": {
+                            "task": {"type": "string"},
+                            "limit": {"type": "integer", "minimum": 1, "maximum": 50},
+                            "max_terms": {"type": "integer", "minimum": 1, "maximum": 32}
                         }
                     }
                 },
                 {
                     "name": "memory_stats",
-                    "description": "Get memory statistics with scope/category breakdown.",
+                    "description": "Get memory statistics with scope/category and project:/tool:/domain: tag breakdowns, the most used tags and tags outside the taxonomy, and optionally growth per day or week, average importance and embedding coverage.",
                     "inputSchema": {
                         "type": "object",
                         "properties": {
-                            "scope": {"type": "string"}
+                            "scope": {"type": "string"},
+                            "top_tags": {"type": "integer", "minimum": 1, "maximum": 200, "description": "Length of the most-used tags list (default 20)."},
+                            "trend": {"type": "boolean"},
+                            "bucket": {"type": "string", "enum": ["day", "week"]},
+                            "periods": {"type": "integer", "minimum": 1, "maximum": 366}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_usage_report",
+                    "description": "Report per-agent store/recall/forget counts and bytes written in a time window, with lifetime totals and quota consumption.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "agent_id": {"type": "string"},
+                            "window_ms": {"type": "integer"}
                         }
                     }
                 },
@@ -872,7 +2994,9 @@ impl McpServer {
                             "scope": {"type": "string"},
                             "category": {"type": "string"},
                             "limit": {"type": "integer"},
-                            "offset": {"type": "integer"}
+                            "offset": {"type": "integer"},
+                            "cursor": {"type": "string"},
+                            "source": source_schema
                         }
                     }
                 },
@@ -893,7 +3017,8 @@ impl McpServer {
                             "project_tag": {"type": "string"},
                             "tool_tag": {"type": "string"},
                             "domain_tag": {"type": "string"},
-                            "governed": {"type": "boolean"}
+                            "governed": {"type": "boolean"},
+                            "dry_run": {"type": "boolean"}
                         }
                     }
                 },
@@ -921,1097 +3046,5491 @@ impl McpServer {
                             "governed": {"type":"boolean"},
                             "use_vector": {"type":"boolean"},
                             "tech_importance_level": {"type":"string", "enum": ["low", "medium", "high", "critical"]},
-                            "principle_importance_level": {"type":"string", "enum": ["low", "medium", "high", "critical"]}
+                            "principle_importance_level": {"type":"string", "enum": ["low", "medium", "high", "critical"]},
+                            "override": {"type": "boolean", "description": "Store governed layers even when they duplicate existing memories."},
+                            "source": source_schema
                         }
                     }
                 },
                 {
                     "name": "memory_export",
-                    "description": "Export memories by scope/category to JSON payload or file.",
+                    "description": "Export memories by scope/category, or only those a recall query matches, as JSON, JSONL, CSV or Markdown, inline or to a file.",
                     "inputSchema": {
                         "type": "object",
                         "properties": {
+                            "query": {"type": "string", "description": "Export only the entries memory_recall would return for this query (same syntax and scope/category filters), best match first."},
+                            "use_vector": {"type": "boolean", "description": "With query: also rank by embedding similarity."},
+                            "include_pending": {"type": "boolean", "description": "With query: also export matches pending review (default false)."},
                             "scope": {"type": "string"},
                             "category": {"type": "string"},
                             "limit": {"type": "integer"},
                             "include_embeddings": {"type": "boolean"},
+                            "format": {"type": "string", "enum": ["json", "jsonl", "csv", "markdown"]},
                             "output_path": {"type": "string"}
                         }
                     }
                 },
                 {
                     "name": "memory_import",
-                    "description": "Import memory entries from payload into local store.",
+                    "description": "Import memory entries into local store from an entries array or a JSON/JSONL/CSV data payload.",
                     "inputSchema": {
                         "type": "object",
-                        "required": ["entries"],
                         "properties": {
                             "entries": {"type":"array"},
+                            "data": {"type":"string"},
+                            "format": {"type":"string", "enum": ["json", "jsonl", "csv"]},
+                            "columns": {"type":"object", "additionalProperties": {"type":"string"}},
                             "governed": {"type":"boolean"},
                             "use_vector": {"type":"boolean"},
-                            "skip_duplicates": {"type":"boolean"}
+                            "skip_duplicates": {"type":"boolean"},
+                            "dry_run": {"type":"boolean"}
                         }
                     }
                 },
                 {
                     "name": "memory_migrate",
-                    "description": "Migrate memory data from JSON file.",
+                    "description": "Migrate memory data from a JSON, JSONL or CSV file, or from a mem0, Letta or Zep export.",
                     "inputSchema": {
                         "type": "object",
                         "required": ["source_path"],
                         "properties": {
                             "source_path": {"type":"string"},
+                            "source_format": {"type":"string", "enum": ["mem0", "letta", "zep"]},
+                            "scope": {"type":"string"},
+                            "format": {"type":"string", "enum": ["json", "jsonl", "csv"]},
+                            "columns": {"type":"object", "additionalProperties": {"type":"string"}},
                             "governed": {"type":"boolean"},
                             "use_vector": {"type":"boolean"},
-                            "skip_duplicates": {"type":"boolean"}
+                            "skip_duplicates": {"type":"boolean"},
+                            "dry_run": {"type":"boolean"}
                         }
                     }
                 },
                 {
-                    "name": "memory_reembed",
-                    "description": "Rebuild embeddings for existing memories.",
+                    "name": "memory_ingest_files",
+                    "description": "Ingest a directory of Markdown notes (e.g. an Obsidian vault): chunk each note, derive tags from front-matter and path, and store governed entries with embeddings, pending review unless review=false.",
                     "inputSchema": {
                         "type": "object",
+                        "required": ["path"],
                         "properties": {
+                            "path": {"type":"string"},
                             "scope": {"type":"string"},
                             "category": {"type":"string"},
-                            "limit": {"type":"integer"}
+                            "importance_level": {"type":"string", "enum": ["low", "medium", "high", "critical"]},
+                            "tags": {"type":"array","items":{"type":"string"}},
+                            "project_tag": {"type":"string"},
+                            "tool_tag": {"type":"string"},
+                            "domain_tag": {"type":"string"},
+                            "chunk_size": {"type":"integer"},
+                            "chunk_overlap": {"type":"integer"},
+                            "max_files": {"type":"integer"},
+                            "review": {"type":"boolean"},
+                            "governed": {"type":"boolean"},
+                            "use_vector": {"type":"boolean"},
+                            "dry_run": {"type":"boolean"}
                         }
                     }
                 },
                 {
-                    "name": "memory_compact",
-                    "description": "Compact duplicate memories in selected scope/category.",
+                    "name": "memory_distill",
+                    "description": "Distill a conversation transcript into Pitfall/Cause/Fix/Prevention and decision-principle candidates via the summarization provider, and store them as governed dual-layer memories pending review.",
                     "inputSchema": {
                         "type": "object",
+                        "required": ["messages"],
                         "properties": {
+                            "messages": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["role", "content"],
+                                    "properties": {"role": {"type":"string"}, "content": {"type":"string"}}
+                                }
+                            },
                             "scope": {"type":"string"},
-                            "category": {"type":"string"},
-                            "limit": {"type":"integer"},
+                            "tags": {"type":"array","items":{"type":"string"}},
+                            "project_tag": {"type":"string"},
+                            "tool_tag": {"type":"string"},
+                            "domain_tag": {"type":"string"},
+                            "max_candidates": {"type":"integer","minimum":1,"maximum":50},
+                            "use_vector": {"type":"boolean"},
                             "dry_run": {"type":"boolean"}
                         }
                     }
                 },
                 {
-                    "name": "memory_forget",
-                    "description": "Delete memory by id.",
+                    "name": "memory_review_list",
+                    "description": "List memories pending review (from memory_distill or memory_ingest_files), oldest first. Pending memories are excluded from recall until approved.",
                     "inputSchema": {
                         "type": "object",
-                        "required": ["id"],
                         "properties": {
-                            "id": {"type": "string"}
+                            "scope": {"type":"string"},
+                            "category": {"type":"string"},
+                            "limit": {"type":"integer","minimum":1,"maximum":500}
                         }
                     }
                 },
                 {
-                    "name": "memory_evolve",
-                    "description": "Select best memory strategy variant using train+holdout acceptance.",
+                    "name": "memory_approve",
+                    "description": "Approve pending memories so recall returns them. Approved entries are re-stored under new ids; relations are carried over.",
                     "inputSchema": {
                         "type": "object",
-                        "required": ["parent_score", "candidates"],
+                        "required": ["ids"],
                         "properties": {
-                            "parent_score": {"type": "number"},
-                            "lambda": {"type": "number"},
-                            "mu": {"type": "number"},
-                            "candidates": {
-                                "type": "array",
-                                "items": {
-                                    "type": "object",
-                                    "required": ["id", "score_train", "score_holdout", "cost_penalty", "risk_penalty", "constraints_satisfied"],
-                                    "properties": {
-                                        "id": {"type": "string"},
-                                        "score_train": {"type": "number"},
-                                        "score_holdout": {"type": "number"},
-                                        "cost_penalty": {"type": "number"},
-                                        "risk_penalty": {"type": "number"},
-                                        "constraints_satisfied": {"type": "boolean"}
-                                    }
-                                }
-                            }
+                            "ids": {"type": "array", "items": {"type": "string"}}
                         }
                     }
                 },
                 {
-                    "name": "memory_skill_manifest",
-                    "description": "Get built-in governance skill metadata and optional content.",
+                    "name": "memory_reject",
+                    "description": "Reject pending memories, deleting them.",
                     "inputSchema": {
                         "type": "object",
+                        "required": ["ids"],
                         "properties": {
-                            "include_content": {"type":"boolean"}
+                            "ids": {"type": "array", "items": {"type": "string"}}
                         }
                     }
-                }
-            ]
-        })
-    }
-
-    fn resources_list_result(&self) -> Value {
-        let resources = skill_resources()
-            .iter()
-            .map(|resource| {
-                json!({
-                    "uri": resource.uri,
-                    "name": resource.name,
-                    "description": resource.description,
-                    "mimeType": resource.mime_type
-                })
-            })
-            .collect::<Vec<_>>();
-        json!({
-            "resources": resources
-        })
-    }
-
-    fn resources_templates_list_result(&self) -> Value {
-        let templates = resource_templates()
-            .iter()
-            .map(|template| {
-                json!({
-                    "uriTemplate": template.uri_template,
-                    "name": template.name,
-                    "description": template.description,
-                    "mimeType": template.mime_type
-                })
-            })
-            .collect::<Vec<_>>();
-        json!({
-            "resourceTemplates": templates
-        })
+                },
+                {
+                    "name": "memory_quota_status",
+                    "description": "Report entry and byte usage against per-scope and per-agent store quotas.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "scope": {"type":"string"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_decay_report",
+                    "description": "List memories whose forgetting-curve retention (age, recall frequency, importance) is below a threshold.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "scope": {"type":"string"},
+                            "category": {"type":"string"},
+                            "threshold": {"type":"number","minimum":0,"maximum":1},
+                            "limit": {"type":"integer","minimum":1,"maximum":1000}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_staleness",
+                    "description": "Rank memories likely to be outdated for cleanup: superseded by another memory, contradicted by a newer one, old, or never recalled. Each item lists its signals and a combined confidence. Changes nothing.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "scope": {"type": "string"},
+                            "category": {"type": "string"},
+                            "older_than_days": {"type": "number", "minimum": 0, "description": "Age that counts as old (default 180)."},
+                            "min_confidence": {"type": "number", "minimum": 0, "maximum": 1, "description": "Default 0.5."},
+                            "limit": {"type": "integer", "minimum": 1, "maximum": 1000}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_reembed",
+                    "description": "Rebuild embeddings for existing memories as a resumable background job. Returns a job id; poll memory_job_status, or pass wait=true to run inline.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "scope": {"type":"string"},
+                            "category": {"type":"string"},
+                            "limit": {"type":"integer"},
+                            "batch_size": {"type":"integer","minimum":1,"maximum":256},
+                            "wait": {"type":"boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_job_status",
+                    "description": "Report progress and failures of background jobs such as memory_reembed.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "job_id": {"type":"string"},
+                            "limit": {"type":"integer","minimum":1,"maximum":50}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_compact",
+                    "description": "Compact duplicate memories in selected scope/category.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "scope": {"type":"string"},
+                            "category": {"type":"string"},
+                            "limit": {"type":"integer"},
+                            "dry_run": {"type":"boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_duplicates",
+                    "description": "Report clusters of near-duplicate memories in each scope and category, by text signature (as memory_compact) and/or embedding similarity, with scores and a suggested survivor. Deletes nothing.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "scope": {"type": "string"},
+                            "category": {"type": "string"},
+                            "method": {"type": "string", "enum": ["signature", "embedding", "both"], "description": "Default both."},
+                            "threshold": {"type": "number", "minimum": 0, "maximum": 1, "description": "Minimum embedding cosine similarity (default: the governance policy's duplicate_score)."},
+                            "limit": {"type": "integer", "description": "Entries to scan (default 5000, max 20000)."}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_lint",
+                    "description": "Scan existing memories for governance policy violations (length, banned content, category, tag dimensions, templates, importance). Previews unless dry_run=false, which adds missing default tag dimensions.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "scope": {"type":"string"},
+                            "category": {"type":"string"},
+                            "limit": {"type":"integer","minimum":1,"maximum":1000},
+                            "dry_run": {"type":"boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_recategorize",
+                    "description": "Rename categories on existing memories, e.g. after changing the category taxonomy. Defaults to the governance policy's category_aliases and to a dry run.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "renames": {"type":"object","additionalProperties":{"type":"string"}},
+                            "scope": {"type":"string"},
+                            "dry_run": {"type":"boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_retag",
+                    "description": "Add, remove or rename tags on every memory matching scope/category/tag filters in one pass, e.g. after evolving the tag taxonomy. Reports each change and defaults to a dry run.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "add": {"type":"array","items":{"type":"string"}},
+                            "remove": {"type":"array","items":{"type":"string"}},
+                            "rename": {"type":"object","additionalProperties":{"type":"string"},"description":"Old tag to new tag, e.g. {\"domain:general\": \"domain:retrieval\"}."},
+                            "scope": {"type":"string"},
+                            "category": {"type":"string"},
+                            "tag": {"type":"string","description":"Only touch entries carrying this tag."},
+                            "dry_run": {"type":"boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_move_scope",
+                    "description": "Move every memory in one scope (optionally one category) to another scope, e.g. after a project rename or when moving agent: memories to a project: scope. Needs delete on the source and write on the target; defaults to a dry run.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["from", "to"],
+                        "properties": {
+                            "from": {"type":"string"},
+                            "to": {"type":"string"},
+                            "category": {"type":"string"},
+                            "dry_run": {"type":"boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_merge",
+                    "description": "Merge several memories into one entry: union tags, keep max importance, and remove the originals.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["ids"],
+                        "properties": {
+                            "ids": {"type": "array", "items": {"type": "string"}},
+                            "text": {"type": "string"},
+                            "category": {"type": "string"},
+                            "scope": {"type": "string"},
+                            "governed": {"type": "boolean"},
+                            "use_vector": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_summarize",
+                    "description": "Condense the oldest memories of a scope/category into one LLM-written summary memory and remove the originals.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "scope": {"type": "string"},
+                            "category": {"type": "string"},
+                            "batch_size": {"type": "integer"},
+                            "max_chars": {"type": "integer"},
+                            "instruction": {"type": "string"},
+                            "keep_originals": {"type": "boolean"},
+                            "governed": {"type": "boolean"},
+                            "dry_run": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_entities",
+                    "description": "List extracted entities (people, projects, tools), or recall the memories that mention one entity.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "kind": {"type": "string", "enum": ["person", "project", "tool"]},
+                            "name": {"type": "string"},
+                            "scope": {"type": "string"},
+                            "limit": {"type": "integer"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_link",
+                    "description": "Record a directed relation between two memories (supersedes, derived-from, contradicts, related-to).",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["from_id", "relation", "to_id"],
+                        "properties": {
+                            "from_id": {"type": "string"},
+                            "relation": {"type": "string", "enum": ["supersedes", "derived-from", "contradicts", "related-to"]},
+                            "to_id": {"type": "string"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_unlink",
+                    "description": "Remove a relation between two memories; omit relation to remove all edges between them.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["from_id", "to_id"],
+                        "properties": {
+                            "from_id": {"type": "string"},
+                            "relation": {"type": "string"},
+                            "to_id": {"type": "string"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_forget",
+                    "description": "Delete memory by id. dry_run=true reports the entry, relations and entity links that would go.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {
+                            "id": {"type": "string"},
+                            "dry_run": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_transaction",
+                    "description": "Apply an ordered list of store/update/forget operations atomically under one write lock. If any operation fails, the earlier ones are rolled back; updated and forgotten entries come back under new ids.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["operations"],
+                        "properties": {
+                            "operations": {
+                                "type": "array",
+                                "minItems": 1,
+                                "maxItems": 100,
+                                "items": {
+                                    "type": "object",
+                                    "required": ["op"],
+                                    "properties": {
+                                        "op": {"type": "string", "enum": ["store", "update", "forget"]}
+                                    },
+                                    "additionalProperties": true
+                                }
+                            }
+                        }
+                    }
+                },
+                {
+                    "name": "memory_archive",
+                    "description": "Move memories into the archive instead of deleting them: by ids, or entries older than older_than_days with importance at most max_importance. Archived memories leave normal recall.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "ids": {"type": "array", "items": {"type": "string"}},
+                            "scope": {"type": "string"},
+                            "category": {"type": "string"},
+                            "older_than_days": {"type": "number", "minimum": 0},
+                            "max_importance": {"type": "number", "minimum": 0, "maximum": 1},
+                            "limit": {"type": "integer", "minimum": 1, "maximum": 10000},
+                            "dry_run": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_restore",
+                    "description": "Move archived memories back into the live store. Restored memories get new ids.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["ids"],
+                        "properties": {
+                            "ids": {"type": "array", "items": {"type": "string"}}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_eval_baseline",
+                    "description": "Save golden-set recall metrics (hit rate, MRR, recall@k) as a named baseline, or replay a saved baseline and fail or warn when quality dropped beyond a tolerance.",
+                    "inputSchema": {
+                        "type": "object",
+                        "required": ["op"],
+                        "properties": {
+                            "op": {"type": "string", "enum": ["save", "compare"]},
+                            "name": {"type": "string"},
+                            "cases": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["query", "expected"],
+                                    "properties": {
+                                        "query": {"type": "string"},
+                                        "expected": {"type": "array", "items": {"type": "string"}},
+                                        "scope": {"type": "string"},
+                                        "category": {"type": "string"}
+                                    }
+                                }
+                            },
+                            "k": {"type": "integer", "minimum": 1, "maximum": 50},
+                            "use_vector": {"type": "boolean"},
+                            "tolerance": {"type": "number", "minimum": 0, "maximum": 1},
+                            "on_regression": {"type": "string", "enum": ["fail", "warn"]},
+                            "include_feedback": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_feedback",
+                    "description": "Mark recalled memories as useful or irrelevant for a query (or a logged query_id). Feedback can boost recall scores (feedback_weight) and seed memory_eval_baseline golden sets (include_feedback).",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "query": {"type": "string"},
+                            "query_id": {"type": "string"},
+                            "scope": {"type": "string"},
+                            "category": {"type": "string"},
+                            "useful": {"type": "array", "items": {"type": "string"}},
+                            "irrelevant": {"type": "array", "items": {"type": "string"}}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_query_log",
+                    "description": "Export the opt-in recall query log (PRX_MEMORY_QUERY_LOG=1): query text, filters, result ids and which results were later used by id. Optionally clear the exported records.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "since_ms": {"type": "integer", "minimum": 0},
+                            "until_ms": {"type": "integer", "minimum": 0},
+                            "accessed_only": {"type": "boolean"},
+                            "limit": {"type": "integer", "minimum": 1, "maximum": 5000},
+                            "offset": {"type": "integer", "minimum": 0},
+                            "format": {"type": "string", "enum": ["json", "jsonl"]},
+                            "clear": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_index_status",
+                    "description": "Report the storage backend's secondary indexes and how many rows each still has to take in; ensure=true builds missing indexes and refreshes stale ones first.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "ensure": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_sync",
+                    "description": "Push governed local changes to the team server in PRX_MEMORY_SYNC_URL and pull the team's, per PRX_MEMORY_SYNC_SCOPES. Conflicting edits keep the newer version. dry_run=true only counts what would move.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "dry_run": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_rekey",
+                    "description": "Re-encrypt every stored and archived memory with the primary encryption key after a key rotation.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {}
+                    }
+                },
+                {
+                    "name": "memory_evolve",
+                    "description": "Select best memory strategy variant using train+holdout acceptance. mode=ranking replays an eval baseline's queries against RankingConfig variants and persists the accepted one as the active config.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "mode": {"type": "string", "enum": ["scores", "ranking"]},
+                            "parent_score": {"type": "number"},
+                            "lambda": {"type": "number"},
+                            "mu": {"type": "number"},
+                            "candidates": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["id", "score_train", "score_holdout", "cost_penalty", "risk_penalty", "constraints_satisfied"],
+                                    "properties": {
+                                        "id": {"type": "string"},
+                                        "score_train": {"type": "number"},
+                                        "score_holdout": {"type": "number"},
+                                        "cost_penalty": {"type": "number"},
+                                        "risk_penalty": {"type": "number"},
+                                        "constraints_satisfied": {"type": "boolean"}
+                                    }
+                                }
+                            },
+                            "variants": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["id", "tokenizer"],
+                                    "properties": {
+                                        "id": {"type": "string"},
+                                        "tokenizer": {"type": "string", "enum": ["simple", "unicode", "cjk-ngram"]},
+                                        "cost_penalty": {"type": "number"},
+                                        "risk_penalty": {"type": "number"}
+                                    }
+                                }
+                            },
+                            "baseline": {"type": "string"},
+                            "holdout_ratio": {"type": "number"},
+                            "dry_run": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_skill_manifest",
+                    "description": "Get built-in governance skill metadata and optional content.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "include_content": {"type":"boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "memory_tool_schemas",
+                    "description": "Export the memory tools as OpenAI or Anthropic function-calling schemas for non-MCP agents.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "format": {"type": "string", "enum": ["openai", "anthropic"]},
+                            "tools": {"type": "array", "items": {"type": "string"}}
+                        }
+                    }
+                }
+            ]
+        })
+    }
+
+    fn resources_list_result(&self) -> Value {
+        let resources = current_skill_resources()
+            .iter()
+            .map(|resource| {
+                json!({
+                    "uri": resource.uri,
+                    "name": resource.name,
+                    "description": resource.description,
+                    "mimeType": resource.mime_type
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "resources": resources
+        })
+    }
+
+    fn resources_templates_list_result(&self) -> Value {
+        let templates = resource_templates()
+            .iter()
+            .map(|template| {
+                json!({
+                    "uriTemplate": template.uri_template,
+                    "name": template.name,
+                    "description": template.description,
+                    "mimeType": template.mime_type
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "resourceTemplates": templates
+        })
+    }
+
+    fn handle_resources_read(&self, id: Value, params: Value) -> JsonRpcResponse {
+        let parsed: ResourceReadParams = match serde_json::from_value(params) {
+            Ok(v) => v,
+            Err(err) => {
+                return JsonRpcResponse::error(id, -32602, format!("invalid params: {err}"));
+            }
+        };
+
+        let Some(rendered) = self.read_resource(&parsed.uri) else {
+            return JsonRpcResponse::error(id, -32602, "unknown resource uri");
+        };
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "contents": [{
+                    "uri": parsed.uri,
+                    "mimeType": rendered.mime_type,
+                    "text": rendered.text
+                }]
+            }),
+        )
+    }
+
+    fn read_resource(&self, uri: &str) -> Option<RenderedResource> {
+        // Checked first: `memory-recall-plan` also starts with the `memory-recall` template prefix.
+        if uri.starts_with("prx://templates/memory-recall-plan") {
+            let params = parse_uri_query(uri);
+            let task = params.get("task").map(|v| percent_decode(v)).unwrap_or_default();
+            let limit = params.get("limit").and_then(|v| v.parse::<usize>().ok()).unwrap_or(5);
+            let plan = self.recall_plan(&task, limit, 12)?;
+            return Some(RenderedResource {
+                mime_type: "application/json",
+                text: json!({
+                    "jsonrpc":"2.0",
+                    "id":1,
+                    "method":"tools/call",
+                    "params": plan.get("recall").cloned().unwrap_or(Value::Null)
+                })
+                .to_string(),
+            });
+        }
+        render_template_resource(uri, &self.standards).or_else(|| {
+            skill_resource_body(uri).map(|text| RenderedResource {
+                mime_type: "text/markdown",
+                text,
+            })
+        })
+    }
+
+    fn handle_resource_subscription(&self, session_id: &str, request: JsonRpcRequest) -> JsonRpcResponse {
+        let id = request.id.unwrap_or(Value::Null);
+        let parsed: ResourceReadParams = match serde_json::from_value(request.params) {
+            Ok(v) => v,
+            Err(err) => {
+                return JsonRpcResponse::error(id, -32602, format!("invalid params: {err}"));
+            }
+        };
+        let subscribe = request.method == "resources/subscribe";
+        let baseline = if subscribe {
+            let Some(rendered) = self.read_resource(&parsed.uri) else {
+                return JsonRpcResponse::error(id, -32602, "unknown resource uri");
+            };
+            Some(hash_text(&rendered.text))
+        } else {
+            None
+        };
+        {
+            let mut sessions = self.sessions.lock();
+            let Some(state) = sessions.get_mut(session_id) else {
+                return JsonRpcResponse::error(id, -32602, "unknown or expired session");
+            };
+            if subscribe {
+                state.subscriptions.insert(parsed.uri.clone());
+                self.log_session(&SessionRecord::Subscribed {
+                    session: session_id.to_string(),
+                    uri: parsed.uri.clone(),
+                });
+            } else {
+                state.subscriptions.remove(&parsed.uri);
+                self.log_session(&SessionRecord::Unsubscribed {
+                    session: session_id.to_string(),
+                    uri: parsed.uri.clone(),
+                });
+            }
+        }
+        let mut watch = self.resource_watch.lock();
+        if watch.list_fingerprint.is_none() {
+            watch.list_fingerprint = Some(resource_list_fingerprint());
+        }
+        if let Some(hash) = baseline {
+            watch.content.entry(parsed.uri).or_insert(hash);
+        }
+        JsonRpcResponse::success(id, json!({}))
+    }
+
+    /// Diffs the resource catalog against the last observation and queues
+    /// `notifications/resources/list_changed` for every session and
+    /// `notifications/resources/updated` for sessions subscribed to a changed URI.
+    /// Checks are throttled by `PRX_MEMORY_RESOURCE_POLL_MS`.
+    fn poll_resource_changes(&self) {
+        self.deliver_notices();
+        let now = now_ms();
+        let interval = env_usize("PRX_MEMORY_RESOURCE_POLL_MS", 1_000, 0, 60_000) as u64;
+        let mut watch = self.resource_watch.lock();
+        if watch.checked_ms != 0 && now.saturating_sub(watch.checked_ms) < interval {
+            return;
+        }
+        watch.checked_ms = now;
+
+        let list_fingerprint = resource_list_fingerprint();
+        let list_changed = watch.list_fingerprint.is_some_and(|prev| prev != list_fingerprint);
+        watch.list_fingerprint = Some(list_fingerprint);
+
+        let subscribed = self
+            .sessions
+            .lock()
+            .values()
+            .flat_map(|state| state.subscriptions.iter().cloned())
+            .collect::<HashSet<_>>();
+        watch.content.retain(|uri, _| subscribed.contains(uri));
+        let mut updated = HashSet::new();
+        for uri in subscribed {
+            let hash = self.read_resource(&uri).map_or(0, |rendered| hash_text(&rendered.text));
+            if watch.content.insert(uri.clone(), hash).is_some_and(|prev| prev != hash) {
+                updated.insert(uri);
+            }
+        }
+        drop(watch);
+
+        if !list_changed && updated.is_empty() {
+            return;
+        }
+        let mut sessions = self.sessions.lock();
+        for (session, state) in sessions.iter_mut() {
+            let mut payloads = Vec::new();
+            if list_changed {
+                payloads.push(json!({"jsonrpc":"2.0","method":"notifications/resources/list_changed"}));
+            }
+            let mut uris = state.subscriptions.intersection(&updated).cloned().collect::<Vec<_>>();
+            uris.sort();
+            for uri in uris {
+                payloads
+                    .push(json!({"jsonrpc":"2.0","method":"notifications/resources/updated","params":{"uri": uri}}));
+            }
+            for payload in payloads {
+                let seq = state.push_event(payload.clone(), now);
+                self.log_session(&SessionRecord::Event {
+                    session: session.clone(),
+                    seq,
+                    created_ms: now,
+                    payload,
+                });
+            }
+        }
+    }
+
+    fn handle_tools_call(
+        &self,
+        id: Value,
+        params: Value,
+        session_id: Option<&str>,
+        ctx: CallContext,
+    ) -> JsonRpcResponse {
+        let parsed: ToolsCallParams = match serde_json::from_value(params) {
+            Ok(v) => v,
+            Err(err) => {
+                return JsonRpcResponse::error(id, -32602, format!("invalid params: {err}"));
+            }
+        };
+
+        let start = Instant::now();
+        let tool = parsed.name.clone();
+        let agent_id = self.scopes.agent_id();
+        let _span = tracing::info_span!("tool", tool = %tool, agent = %agent_id).entered();
+        if let Err(denial) = self.tool_policy.check(&agent_id, &tool, parsed.arguments.as_ref()) {
+            tracing::warn!(agent = %agent_id, "tool call denied by policy");
+            let response = JsonRpcResponse::error_with_data(
+                id,
+                -32004,
+                format!("permission denied: agent {agent_id} may not call {tool}"),
+                denial,
+            );
+            self.record_tool_metrics(&tool, start.elapsed().as_secs_f64() * 1000.0, true);
+            return response;
+        }
+        if let Some(follower) = self
+            .follower
+            .as_ref()
+            .filter(|_| !follower::READ_TOOLS.contains(&tool.as_str()))
+        {
+            let response = JsonRpcResponse::error_with_data(
+                id,
+                -32004,
+                format!("read-only follower: send {tool} to the leader at {}", follower.leader()),
+                json!({"kind": "read_only_follower", "leader": follower.leader()}),
+            );
+            self.record_tool_metrics(&tool, start.elapsed().as_secs_f64() * 1000.0, true);
+            return response;
+        }
+        let limited = self.rate_limiter.lock().try_acquire(&tool, session_id, now_ms());
+        if let Err((limit, retry_after_ms)) = limited {
+            {
+                let mut locked = self.metrics.lock();
+                let count = locked
+                    .rate_limited
+                    .entry((limit.to_string(), tool.clone()))
+                    .or_insert(0);
+                *count = count.saturating_add(1);
+            }
+            tracing::debug!(limit, retry_after_ms, "tool call rate limited");
+            let response = JsonRpcResponse::error_with_data(
+                id,
+                -32005,
+                format!("rate limited (429): {limit} limit exceeded for {tool}"),
+                json!({
+                    "kind": "rate_limited",
+                    "status": 429,
+                    "limit": limit,
+                    "tool": tool,
+                    "retry_after_ms": retry_after_ms
+                }),
+            );
+            self.record_tool_metrics(&tool, start.elapsed().as_secs_f64() * 1000.0, true);
+            return response;
+        }
+        let used_ids = if self.query_log.is_some() {
+            entry_ids_used_by(&tool, parsed.arguments.as_ref())
+        } else {
+            Vec::new()
+        };
+        let response = match parsed.name.as_str() {
+            "memory_store" => self.exec_memory_store(id, parsed.arguments),
+            "memory_recall" => self.recall_in_experiment(id, parsed.arguments, ctx, session_id),
+            "memory_stats" => self.exec_memory_stats(id, parsed.arguments),
+            "memory_usage_report" => self.exec_memory_usage_report(id, parsed.arguments),
+            "memory_quota_status" => self.exec_memory_quota_status(id, parsed.arguments),
+            "memory_decay_report" => self.exec_memory_decay_report(id, parsed.arguments),
+            "memory_staleness" => self.exec_memory_staleness(id, parsed.arguments),
+            "memory_archive" => self.exec_memory_archive(id, parsed.arguments),
+            "memory_restore" => self.exec_memory_restore(id, parsed.arguments),
+            "memory_eval_baseline" => self.exec_memory_eval_baseline(id, parsed.arguments),
+            "memory_query_log" => self.exec_memory_query_log(id, parsed.arguments),
+            "memory_feedback" => {
+                let response = self.exec_memory_feedback(id, parsed.arguments);
+                if let (Some(arm), Some(result)) = (self.experiment_arm(session_id), &response.result) {
+                    let count = |key: &str| {
+                        result
+                            .pointer(&format!("/structuredContent/{key}"))
+                            .and_then(Value::as_u64)
+                            .unwrap_or(0)
+                    };
+                    self.record_arm_feedback(&arm.id, count("useful"), count("irrelevant"));
+                }
+                response
+            }
+            "memory_rekey" => self.exec_memory_rekey(id),
+            "memory_index_status" => self.exec_memory_index_status(id, parsed.arguments),
+            "memory_sync" => self.exec_memory_sync(id, parsed.arguments),
+            "memory_list" => self.exec_memory_list(id, parsed.arguments),
+            "memory_update" => self.exec_memory_update(id, parsed.arguments),
+            "memory_store_dual" => self.exec_memory_store_dual(id, parsed.arguments),
+            "memory_export" => self.exec_memory_export(id, parsed.arguments),
+            "memory_import" => self.exec_memory_import(id, parsed.arguments),
+            "memory_migrate" => self.exec_memory_migrate(id, parsed.arguments),
+            "memory_ingest_files" => self.exec_memory_ingest_files(id, parsed.arguments, &ctx),
+            "memory_distill" => self.exec_memory_distill(id, parsed.arguments),
+            "memory_recall_context" => self.exec_memory_recall_context(id, parsed.arguments, ctx),
+            "memory_recall_plan" => self.exec_memory_recall_plan(id, parsed.arguments),
+            "memory_session_note" => self.exec_memory_session_note(id, parsed.arguments, session_id),
+            "memory_review_list" => self.exec_memory_review_list(id, parsed.arguments),
+            "memory_approve" => self.exec_memory_review_decision(id, parsed.arguments, true),
+            "memory_reject" => self.exec_memory_review_decision(id, parsed.arguments, false),
+            "memory_reembed" => self.exec_memory_reembed(id, parsed.arguments),
+            "memory_job_status" => self.exec_memory_job_status(id, parsed.arguments),
+            "memory_compact" => self.exec_memory_compact(id, parsed.arguments),
+            "memory_duplicates" => self.exec_memory_duplicates(id, parsed.arguments),
+            "memory_lint" => self.exec_memory_lint(id, parsed.arguments),
+            "memory_recategorize" => self.exec_memory_recategorize(id, parsed.arguments),
+            "memory_retag" => self.exec_memory_retag(id, parsed.arguments),
+            "memory_move_scope" => self.exec_memory_move_scope(id, parsed.arguments),
+            "memory_merge" => self.exec_memory_merge(id, parsed.arguments),
+            "memory_summarize" => self.exec_memory_summarize(id, parsed.arguments),
+            "memory_entities" => self.exec_memory_entities(id, parsed.arguments),
+            "memory_link" => self.exec_memory_link(id, parsed.arguments),
+            "memory_unlink" => self.exec_memory_unlink(id, parsed.arguments),
+            "memory_forget" => self.exec_memory_forget(id, parsed.arguments),
+            "memory_transaction" => self.exec_memory_transaction(id, parsed.arguments),
+            "memory_evolve" => self.exec_memory_evolve(id, parsed.arguments),
+            "memory_skill_manifest" => self.exec_memory_skill_manifest(id, parsed.arguments),
+            "memory_tool_schemas" => self.exec_memory_tool_schemas(id, parsed.arguments),
+            _ => JsonRpcResponse::error(id, -32601, "unknown tool"),
+        };
+        if let Some(err) = &response.error {
+            tracing::warn!(code = err.code, error = %err.message, "tool call failed");
+        } else if let Some(log) = self.query_log.as_ref().filter(|_| !used_ids.is_empty())
+            && let Err(err) = log.lock().mark_accessed(&used_ids)
+        {
+            tracing::warn!(error = %err, "failed to update query log");
+        }
+        self.record_tool_metrics(&tool, start.elapsed().as_secs_f64() * 1000.0, response.error.is_some());
+        response
+    }
+
+    /// Experiment arm for this caller: the session id when there is one, else the agent id.
+    fn experiment_arm(&self, session_id: Option<&str>) -> Option<&ExperimentArm> {
+        let caller = session_id.map_or_else(|| self.scopes.agent_id(), str::to_string);
+        self.experiment.as_ref()?.assign(&caller)
+    }
+
+    /// Runs `memory_recall` under the caller's experiment arm config, records per-arm metrics and
+    /// tags the response with the arm.
+    fn recall_in_experiment(
+        &self,
+        id: Value,
+        arguments: Option<Value>,
+        ctx: CallContext,
+        session_id: Option<&str>,
+    ) -> JsonRpcResponse {
+        let (Some(experiment), Some(arm)) = (&self.experiment, self.experiment_arm(session_id)) else {
+            return self.exec_memory_recall(id, arguments, ctx);
+        };
+        let start = Instant::now();
+        let config = arm.config.unwrap_or_else(|| self.ranking_config());
+        let mut response = with_ranking_config(config, || self.exec_memory_recall(id, arguments, ctx));
+        let results = response
+            .result
+            .as_ref()
+            .and_then(|r| r.pointer("/structuredContent/count"))
+            .and_then(Value::as_u64);
+        self.record_arm_recall(&arm.id, start.elapsed().as_secs_f64() * 1000.0, results);
+        if let Some(content) = response
+            .result
+            .as_mut()
+            .and_then(|r| r.get_mut("structuredContent"))
+            .and_then(Value::as_object_mut)
+        {
+            content.insert(
+                "experiment".to_string(),
+                json!({"name": experiment.name, "arm": arm.id}),
+            );
+        }
+        response
+    }
+
+    fn exec_memory_store(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryStoreInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let extract = args.extract_entities.unwrap_or_else(entity_extraction_enabled);
+        let mut locked = self.store.write();
+        let (request, suggestion) = match self.store_request(locked.as_ref(), args) {
+            Ok(v) => v,
+            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
+        };
+        let governed = request.governed;
+        let importance_level = request.importance_level;
+
+        if let Err(exceeded) =
+            self.check_store_quota(locked.as_ref(), &request.scope, QuotaUsage::of_text(&request.text))
+        {
+            return exceeded.response(id);
+        }
+
+        let outcome = match self.store_layer(locked.as_mut(), request) {
+            Ok(v) => v,
+            Err(err) => return err.response(id, ""),
+        };
+        let entities = if extract {
+            store_entity_links(locked.as_mut(), &outcome.entry)
+        } else {
+            Vec::new()
+        };
+        drop(locked);
+        self.record_agent_usage(UsageOp::Store, outcome.entry.text.len());
+        let mut entry = outcome.entry;
+        entry.embedding = None;
+        let mut structured_content = match serde_json::to_value(&entry) {
+            Ok(v) => v,
+            Err(_) => json!({}),
+        };
+        if let Some(obj) = structured_content.as_object_mut() {
+            obj.insert("auto_maintenance".to_string(), json!(outcome.auto_maintenance));
+            if !outcome.pii.is_empty() {
+                obj.insert("pii".to_string(), json!(outcome.pii));
+            }
+            if !outcome.unlisted_tags.is_empty() {
+                obj.insert("unlisted_tags".to_string(), json!(outcome.unlisted_tags));
+            }
+            if !outcome.similar.is_empty() {
+                obj.insert("warning".to_string(), similar_warning(&outcome.similar, &entry.id));
+            }
+            if let Some(suggestion) = suggestion {
+                log_importance_suggestion(&entry, &suggestion);
+                obj.insert("importance_suggestion".to_string(), json!(suggestion));
+            }
+            if extract {
+                obj.insert("entities".to_string(), json!(entities));
+            }
+        }
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": structured_content,
+                "content": [{"type":"text", "text": format!("stored {}", entry.id)}],
+                "governance": {
+                    "governed": governed,
+                    "importance_level": importance_level,
+                    "redaction": self.standards.redaction_for(governed).label()
+                }
+            }),
+        )
+    }
+
+    /// Applies `memory_store` defaults (category, tags, scope, importance) to `args`, returning
+    /// the importance suggestion when one picked the level.
+    fn store_request(
+        &self,
+        store: &dyn StorageBackend,
+        args: MemoryStoreInput,
+    ) -> Result<(StoreLayerRequest, Option<ImportanceSuggestion>), String> {
+        let governed = args
+            .governed
+            .unwrap_or_else(|| self.standards.default_governed_for_store());
+        if governed && enforce_dual_layer() {
+            return Err("governed single-layer writes are disabled; use memory_store_dual".to_string());
+        }
+        let category = args
+            .category
+            .unwrap_or_else(|| infer_default_category(&args.text).to_string());
+        let tags = normalize_tags_with_defaults(
+            args.tags.unwrap_or_default(),
+            args.project_tag.as_deref(),
+            args.tool_tag.as_deref(),
+            args.domain_tag.as_deref(),
+            &self.standards,
+        );
+        let scope = args.scope.unwrap_or_else(|| self.scopes.default_scope());
+        let suggestion = (args.importance.is_none()
+            && args.importance_level.is_none()
+            && args.suggest_importance.unwrap_or_else(importance_suggestions_enabled))
+        .then(|| suggest_importance_for(store, &args.text, &category, &scope));
+        let level = suggestion
+            .as_ref()
+            .map(|s| s.level)
+            .or(args.importance_level.as_deref());
+        let (importance, importance_level) = resolve_importance(level, args.importance)?;
+        let request = StoreLayerRequest {
+            text: args.text,
+            category,
+            scope,
+            importance,
+            importance_level,
+            tags,
+            governed,
+            use_vector: args.use_vector.unwrap_or(false),
+            enforce_verify: false,
+            allow_auto_maintenance: true,
+            redaction: self.standards.redaction_for(governed),
+            source: args.source.and_then(MemorySource::normalized),
+            override_duplicate: args.override_duplicate.unwrap_or(false),
+        };
+        Ok((request, suggestion))
+    }
+
+    fn exec_memory_store_dual(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryStoreDualInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let governed = args.governed.unwrap_or(true);
+        let use_vector = args.use_vector.unwrap_or(false);
+        let include_principle = args.include_principle.unwrap_or(true);
+        let source = args.source.and_then(MemorySource::normalized);
+        let override_duplicate = args.override_duplicate.unwrap_or(false);
+        if governed && !include_principle {
+            return JsonRpcResponse::error(id, -32602, "governed dual-layer writes require include_principle=true");
+        }
+        let scope = args.scope.unwrap_or_else(|| self.scopes.default_scope());
+        let tags = normalize_tags_with_defaults(
+            args.tags.unwrap_or_default(),
+            args.project_tag.as_deref(),
+            args.tool_tag.as_deref(),
+            args.domain_tag.as_deref(),
+            &self.standards,
+        );
+
+        let (tech_importance, tech_level) = match resolve_importance(args.tech_importance_level.as_deref(), Some(0.75))
+        {
+            Ok(v) => v,
+            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
+        };
+
+        let tech_text = format!(
+            "Pitfall: {}. Cause: {}. Fix: {}. Prevention: {}.",
+            args.symptom.trim(),
+            args.cause.trim(),
+            args.fix.trim(),
+            args.prevention.trim()
+        );
+
+        let mut principle_payload = None::<(String, f32, &'static str)>;
+        if include_principle {
+            let principle_tag = match args.principle_tag {
+                Some(v) if !v.trim().is_empty() => v,
+                _ => {
+                    return JsonRpcResponse::error(id, -32602, "principle_tag is required when include_principle=true");
+                }
+            };
+            let principle_rule = match args.principle_rule {
+                Some(v) if !v.trim().is_empty() => v,
+                _ => {
+                    return JsonRpcResponse::error(
+                        id,
+                        -32602,
+                        "principle_rule is required when include_principle=true",
+                    );
+                }
+            };
+            let trigger = match args.trigger {
+                Some(v) if !v.trim().is_empty() => v,
+                _ => return JsonRpcResponse::error(id, -32602, "trigger is required when include_principle=true"),
+            };
+            let action = match args.action {
+                Some(v) if !v.trim().is_empty() => v,
+                _ => return JsonRpcResponse::error(id, -32602, "action is required when include_principle=true"),
+            };
+            let (principle_importance, principle_level) =
+                match resolve_importance(args.principle_importance_level.as_deref(), Some(0.75)) {
+                    Ok(v) => v,
+                    Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
+                };
+
+            let principle_text = format!(
+                "Decision principle ({}): {}. Trigger: {}. Action: {}.",
+                principle_tag.trim(),
+                principle_rule.trim(),
+                trigger.trim(),
+                action.trim()
+            );
+            principle_payload = Some((principle_text, principle_importance, principle_level));
+        }
+
+        let mut locked = self.store.write();
+        let mut journal = WriteJournal::new();
+        let adding = principle_payload
+            .as_ref()
+            .map_or_else(QuotaUsage::default, |(text, _, _)| QuotaUsage::of_text(text))
+            .plus(QuotaUsage::of_text(&tech_text));
+        if let Err(exceeded) = self.check_store_quota(locked.as_ref(), &scope, adding) {
+            return exceeded.response(id);
+        }
+
+        let technical = match self.store_layer(
+            locked.as_mut(),
+            StoreLayerRequest {
+                text: tech_text,
+                category: "fact".to_string(),
+                scope: scope.clone(),
+                importance: tech_importance,
+                importance_level: tech_level,
+                tags: tags.clone(),
+                governed,
+                use_vector,
+                enforce_verify: true,
+                allow_auto_maintenance: true,
+                redaction: self.standards.redaction_for(governed),
+                source: source.clone(),
+                override_duplicate,
+            },
+        ) {
+            Ok(v) => v,
+            Err(err) => return err.response(id, ""),
+        };
+        journal.stored(&technical.entry.id);
+
+        let principle = if let Some((text, importance, level)) = principle_payload {
+            match self.store_layer(
+                locked.as_mut(),
+                StoreLayerRequest {
+                    text,
+                    category: "decision".to_string(),
+                    scope: scope.clone(),
+                    importance,
+                    importance_level: level,
+                    tags,
+                    governed,
+                    use_vector,
+                    enforce_verify: true,
+                    allow_auto_maintenance: true,
+                    redaction: self.standards.redaction_for(governed),
+                    source,
+                    override_duplicate,
+                },
+            ) {
+                Ok(v) => Some(v),
+                Err(err) => {
+                    let _ = journal.rollback(locked.as_mut());
+                    return err.response(id, "principle layer store failed, rolled back technical layer: ");
+                }
+            }
+        } else {
+            None
+        };
+        drop(locked);
+        self.record_agent_usage(UsageOp::Store, technical.entry.text.len());
+        if let Some(p) = &principle {
+            self.record_agent_usage(UsageOp::Store, p.entry.text.len());
+        }
+        let warnings = std::iter::once(&technical)
+            .chain(principle.as_ref())
+            .filter(|layer| !layer.similar.is_empty())
+            .map(|layer| similar_warning(&layer.similar, &layer.entry.id))
+            .collect::<Vec<_>>();
+        let auto_maintenance = [
+            technical.auto_maintenance,
+            principle.as_ref().and_then(|v| v.auto_maintenance.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        let mut tech_clean = technical.entry;
+        tech_clean.embedding = None;
+        let principle_clean = principle.as_ref().map(|v| {
+            let mut e = v.entry.clone();
+            e.embedding = None;
+            e
+        });
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "technical": tech_clean,
+                    "principle": principle_clean,
+                    "auto_maintenance": auto_maintenance,
+                    "unlisted_tags": technical.unlisted_tags,
+                    "warnings": warnings,
+                    "dual_layer_completed": true
+                },
+                "content": [{"type":"text","text":"dual-layer memory stored and verified"}]
+            }),
+        )
+    }
+
+    fn exec_memory_recall(&self, id: Value, arguments: Option<Value>, ctx: CallContext) -> JsonRpcResponse {
+        if arguments.as_ref().is_some_and(|args| args.get("queries").is_some()) {
+            return self.exec_memory_recall_multi(id, arguments, &ctx);
+        }
+        let total_start = Instant::now();
+        let args: MemoryRecallInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let timeout_ms = args
+            .timeout_ms
+            .unwrap_or_else(|| env_usize("PRX_MEMORY_RECALL_TIMEOUT_MS", 0, 0, 600_000) as u64);
+        let ctx = &ctx.with_timeout((timeout_ms > 0).then(|| Duration::from_millis(timeout_ms)));
+        let query_text = args.query.clone();
+        let limit = args.limit.unwrap_or(5).clamp(1, 20);
+        let cursor = match args.cursor.as_deref().map(RecallCursor::decode) {
+            Some(Some(v)) => Some(v),
+            Some(None) => return JsonRpcResponse::error(id, -32602, "invalid recall cursor"),
+            None => None,
+        };
+        let served = cursor.as_ref().map_or(0, |c| c.served);
+        let candidate_pool = args
+            .candidate_pool
+            .unwrap_or(limit * 6)
+            .max(served + limit + 1)
+            .clamp(limit, 1000);
+        self.record_recall_dimensions(
+            args.scope.as_deref(),
+            args.category.as_deref(),
+            args.rerank_provider.as_deref(),
+        );
+        let query_embedded = if args.use_vector.unwrap_or(false) {
+            // Operators and field prefixes mean nothing to the embedding model.
+            let parsed = parse_query(&query_text);
+            let embed_text = if parsed.text.is_empty() {
+                &query_text
+            } else {
+                &parsed.text
+            };
+            match embed_one(&self.runtime, &self.embed, ctx, embed_text, EmbeddingTask::Query) {
+                Ok(v) => Some(v),
+                Err(msg) => {
+                    if let Some(reason) = ctx.cancel_reason() {
+                        return cancelled_response(id, reason, total_start);
+                    }
+                    return JsonRpcResponse::error(id, -32002, msg);
+                }
+            }
+        } else {
+            None
+        };
+        // One snapshot serves the mismatch check and the recall, so maintenance that runs in
+        // between cannot remove entries out from under this call.
+        let snapshot = self.store.read().snapshot();
+        if let Some(embedded) = &query_embedded {
+            let rows = snapshot.list(usize::MAX);
+            let candidates = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), args.category.as_deref());
+            if let Some(report) = embedding_mismatch_report(&candidates, embedded) {
+                return JsonRpcResponse::error_with_data(
+                    id,
+                    -32003,
+                    "embedding model mismatch between query and stored memories; run memory_reembed",
+                    report,
+                );
+            }
+        }
+        let query_embedding = query_embedded.map(|e| e.vector);
+
+        let expand_relations = args.expand_relations.unwrap_or(false);
+        let source_filter = args.source.clone().and_then(MemorySource::normalized);
+        let logged_filters = self
+            .query_log
+            .is_some()
+            .then(|| (args.scope.clone(), args.category.clone(), source_filter.clone()));
+        let diversity = args.diversity.map(|d| d.clamp(0.0, 1.0));
+        let fusion = match args.fusion.as_deref().map(FusionMode::parse) {
+            Some(Some(v)) => Some(v),
+            Some(None) => return JsonRpcResponse::error(id, -32602, "fusion must be rrf|linear"),
+            None => None,
+        };
+        if let Err(msg) = args.block.block() {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        let explain_query = args.explain.unwrap_or(false).then(|| RecallQuery {
+            query: query_text.clone(),
+            query_embedding: query_embedding.clone(),
+            scope: args.scope.clone(),
+            category: args.category.clone(),
+            limit,
+            vector_weight: args.vector_weight,
+            lexical_weight: args.lexical_weight,
+            diversity,
+            fusion,
+        });
+        let archived_query = args.include_archived.unwrap_or(false).then(|| {
+            (
+                args.scope.clone(),
+                args.category.clone(),
+                RecallQuery {
+                    query: query_text.clone(),
+                    query_embedding: query_embedding.clone(),
+                    scope: None,
+                    category: None,
+                    limit: candidate_pool,
+                    vector_weight: args.vector_weight,
+                    lexical_weight: args.lexical_weight,
+                    diversity: None,
+                    fusion,
+                },
+            )
+        });
+        let secondary_query = self
+            .secondary
+            .as_ref()
+            .filter(|_| args.include_secondary.unwrap_or(true))
+            .map(|stores| {
+                (
+                    stores,
+                    args.scope.clone(),
+                    args.category.clone(),
+                    RecallQuery {
+                        query: query_text.clone(),
+                        query_embedding: query_embedding.clone(),
+                        scope: None,
+                        category: None,
+                        limit: candidate_pool,
+                        vector_weight: args.vector_weight,
+                        lexical_weight: args.lexical_weight,
+                        diversity: None,
+                        fusion,
+                    },
+                )
+            });
+        let local_start = Instant::now();
+        let mut results = recall_with_acl(
+            &snapshot,
+            &self.scopes,
+            RecallAclRequest {
+                query: query_text.clone(),
+                query_embedding,
+                requested_scope: args.scope,
+                category: args.category,
+                candidate_pool,
+                vector_weight: args.vector_weight,
+                lexical_weight: args.lexical_weight,
+                diversity,
+                fusion,
+            },
+        );
+        if !args.include_pending.unwrap_or(false) {
+            results.retain(|r| !is_pending_review(&r.entry));
+        }
+        let mut archived_ids = HashSet::new();
+        if let Some((scope, category, query)) = archived_query {
+            let archived = match self.decay.archive.load() {
+                Ok(items) => items.into_iter().map(|item| item.entry).collect::<Vec<_>>(),
+                Err(err) => return JsonRpcResponse::error(id, -32001, err),
+            };
+            let visible = filter_entries_by_acl(archived, &self.scopes, scope.as_deref(), category.as_deref());
+            for hit in recall_entries(&visible, query) {
+                archived_ids.insert(hit.entry.id.clone());
+                results.push(hit);
+            }
+        }
+        // Secondary hits are labelled by id; one the primary store also holds is served from it.
+        let mut origins: HashMap<String, &str> = HashMap::new();
+        if let Some((stores, scope, category, query)) = secondary_query {
+            let visible = |rows| filter_entries_by_acl(rows, &self.scopes, scope.as_deref(), category.as_deref());
+            for (store, hit) in stores.recall(&query, visible) {
+                if (is_pending_review(&hit.entry) && !args.include_pending.unwrap_or(false))
+                    || results.iter().any(|r| r.entry.id == hit.entry.id)
+                {
+                    continue;
+                }
+                origins.insert(hit.entry.id.clone(), store);
+                results.push(hit);
+            }
+        }
+        if let Some(filter) = &source_filter {
+            results.retain(|r| matches_source(&r.entry, filter));
+        }
+        self.record_recall_stage("local", local_start.elapsed().as_secs_f64() * 1000.0);
+        self.record_agent_usage(UsageOp::Recall, 0);
+        let local_scores = if explain_query.is_some() {
+            results
+                .iter()
+                .map(|r| (r.entry.id.clone(), r.score))
+                .collect::<HashMap<_, _>>()
+        } else {
+            HashMap::new()
+        };
+
+        let mut warning: Option<String> = None;
+        let mut partial_seq = None;
+        if args.use_remote.unwrap_or(false) && !results.is_empty() {
+            if let (true, Some(session)) = (args.stream.unwrap_or(false), ctx.session.as_deref()) {
+                partial_seq = self.stream_partial_recall(session, &id, &results, cursor.as_ref(), limit);
+            }
+            self.record_remote_rerank_attempt();
+            let remote_start = Instant::now();
+            match semantic_rerank_with_remote(
+                &self.runtime,
+                ctx,
+                &query_text,
+                &mut results,
+                args.provider.as_deref(),
+                args.rerank_provider.as_deref(),
+            ) {
+                Ok(maybe_warning) => {
+                    warning = maybe_warning;
+                }
+                Err(msg) => {
+                    warning = Some(msg);
+                }
+            }
+            self.record_recall_stage("remote", remote_start.elapsed().as_secs_f64() * 1000.0);
+            if let Some(reason) = ctx.cancel_reason() {
+                return cancelled_response(id, reason, total_start);
+            }
+            if warning.is_some() {
+                self.record_remote_rerank_warning();
+            }
+        }
+        let feedback_weight = args
+            .feedback_weight
+            .or_else(|| {
+                std::env::var("PRX_MEMORY_FEEDBACK_WEIGHT")
+                    .ok()
+                    .and_then(|v| v.trim().parse::<f32>().ok())
+            })
+            .unwrap_or(0.0)
+            .clamp(0.0, 1.0);
+        let mut feedback_boosts = HashMap::new();
+        if feedback_weight > 0.0 && !results.is_empty() {
+            match self.feedback.load() {
+                Ok(data) => {
+                    for r in &mut results {
+                        if let Some(tally) = data.entries.get(&r.entry.id) {
+                            let boost = tally.boost(feedback_weight);
+                            r.score *= boost;
+                            feedback_boosts.insert(r.entry.id.clone(), boost);
+                        }
+                    }
+                }
+                Err(err) => tracing::warn!(error = %err, "recall feedback boost skipped"),
+            }
+        }
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.entry.id.cmp(&b.entry.id)));
+        if let Some(c) = &cursor {
+            results.retain(|r| c.is_before(r));
+        }
+        let has_more = results.len() > limit;
+        if let Some(d) = diversity {
+            // MMR may skip near-duplicates ranked above the page's weakest pick; the
+            // cursor continues after that pick, so skipped duplicates are not revisited.
+            results = mmr_select(results, d, limit);
+        } else {
+            results.truncate(limit);
+        }
+        let next_cursor = if has_more {
+            results
+                .iter()
+                .min_by(|a, b| a.score.total_cmp(&b.score).then_with(|| b.entry.id.cmp(&a.entry.id)))
+                .map(|r| RecallCursor::after(r, served + limit).encode())
+        } else {
+            None
+        };
+        self.decay.record_recall(
+            results
+                .iter()
+                .map(|r| r.entry.id.as_str())
+                .filter(|mid| !archived_ids.contains(*mid) && !origins.contains_key(*mid)),
+        );
+        let query_id = logged_filters.and_then(|(scope, category, source)| {
+            self.log_recall_query(QueryRecord {
+                id: String::new(),
+                ts_ms: now_ms(),
+                agent_id: self.scopes.agent_id(),
+                query: query_text.clone(),
+                scope,
+                category,
+                source,
+                limit,
+                use_vector: args.use_vector.unwrap_or(false),
+                result_ids: results.iter().map(|r| r.entry.id.clone()).collect(),
+                accessed_ids: Vec::new(),
+            })
+        });
+        let explanations = explain_query.as_ref().map(|q| {
+            results
+                .iter()
+                .map(|r| {
+                    let local = local_scores.get(&r.entry.id).copied().unwrap_or(r.score);
+                    let boost = feedback_boosts.get(&r.entry.id).copied().unwrap_or(1.0);
+                    json!({
+                        "fusion": q.fusion.unwrap_or_default().as_str(),
+                        "local": explain_recall_score(&r.entry, q),
+                        "local_score": local,
+                        "rerank_delta": r.score / boost - local,
+                        "feedback_boost": boost,
+                        "final_score": r.score
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        let relations = if expand_relations {
+            let locked = self.store.read();
+            let expanded = results
+                .iter()
+                .map(|r| {
+                    if origins.contains_key(&r.entry.id) {
+                        Vec::new()
+                    } else {
+                        expand_entry_relations(locked.as_ref(), &self.scopes, &r.entry.id)
+                    }
+                })
+                .collect::<Vec<_>>();
+            drop(locked);
+            expanded
+        } else {
+            Vec::new()
+        };
+        self.record_recall_stage("total", total_start.elapsed().as_secs_f64() * 1000.0);
+
+        let mut response = JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "count": results.len(),
+                    "warning": warning,
+                    "agent_id": self.scopes.agent_id(),
+                    "next_cursor": next_cursor,
+                    "query_id": query_id,
+                    "partial_seq": partial_seq,
+                    "generation": snapshot.generation(),
+                    "items": results.iter().enumerate().map(|(idx, r)| {
+                        let mut e = r.entry.clone();
+                        e.embedding = None;
+                        let mut item = json!({"entry": e, "score": r.score});
+                        if let (true, Some(obj)) = (archived_ids.contains(&r.entry.id), item.as_object_mut()) {
+                            obj.insert("archived".to_string(), json!(true));
+                        }
+                        if let (Some(stores), Some(obj)) = (&self.secondary, item.as_object_mut()) {
+                            let store = origins.get(&r.entry.id).copied().unwrap_or_else(|| stores.primary());
+                            obj.insert("store".to_string(), json!(store));
+                        }
+                        if let (Some(rel), Some(obj)) = (relations.get(idx), item.as_object_mut()) {
+                            obj.insert("relations".to_string(), json!(rel));
+                        }
+                        if let (Some(exp), Some(obj)) = (
+                            explanations.as_ref().and_then(|v| v.get(idx)),
+                            item.as_object_mut(),
+                        ) {
+                            obj.insert("explain".to_string(), exp.clone());
+                        }
+                        item
+                    }).collect::<Vec<_>>()
+                },
+                "content": [{
+                    "type":"text",
+                    "text": if let Some(w) = warning {
+                        format!("Recalled {} entries. {}", results.len(), w)
+                    } else {
+                        format!("Recalled {} entries.", results.len())
+                    }
+                }]
+            }),
+        );
+        args.block.apply(&mut response);
+        response
+    }
+
+    /// Pushes the page as ranked locally, before the remote rerank, into `session` as a
+    /// `notifications/prx/recall_partial` event and returns its sequence number. The reranked page
+    /// follows as the call's response.
+    fn stream_partial_recall(
+        &self,
+        session: &str,
+        id: &Value,
+        results: &[RecallResult],
+        cursor: Option<&RecallCursor>,
+        limit: usize,
+    ) -> Option<u64> {
+        let mut page = results
+            .iter()
+            .filter(|r| cursor.is_none_or(|c| c.is_before(r)))
+            .collect::<Vec<_>>();
+        page.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.entry.id.cmp(&b.entry.id)));
+        page.truncate(limit);
+        let items = page
+            .iter()
+            .map(|r| {
+                let mut e = r.entry.clone();
+                e.embedding = None;
+                json!({"entry": e, "score": r.score})
+            })
+            .collect::<Vec<_>>();
+        let payload = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/prx/recall_partial",
+            "params": {"request_id": id, "stage": "local", "count": items.len(), "items": items}
+        });
+        match self.append_session_event(session, payload) {
+            Ok((seq, _)) => Some(seq),
+            Err(err) => {
+                tracing::debug!(error = session_error_code(err), "partial recall not streamed");
+                None
+            }
+        }
+    }
+
+    /// `memory_recall` with `queries`: one recall per query on its own thread, fused by reciprocal
+    /// rank and deduplicated by id. Each item lists the queries that matched it.
+    fn exec_memory_recall_multi(&self, id: Value, arguments: Option<Value>, ctx: &CallContext) -> JsonRpcResponse {
+        let Some(Value::Object(mut base)) = arguments else {
+            return JsonRpcResponse::error(id, -32602, "invalid params: expected an object");
+        };
+        let Some(Ok(queries)) = base.remove("queries").map(serde_json::from_value::<Vec<String>>) else {
+            return JsonRpcResponse::error(id, -32602, "queries must be an array of strings");
+        };
+        let mut seen = HashSet::new();
+        let queries = queries
+            .into_iter()
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty() && seen.insert(q.clone()))
+            .collect::<Vec<_>>();
+        if queries.is_empty() || queries.len() > MAX_RECALL_QUERIES {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("queries must hold 1-{MAX_RECALL_QUERIES} distinct non-empty queries"),
+            );
+        }
+        if base.contains_key("query") || base.contains_key("cursor") {
+            return JsonRpcResponse::error(id, -32602, "queries cannot be combined with query or cursor");
+        }
+        // Partial pages of single queries would arrive out of fused order, and only the fused
+        // list is packed and rendered.
+        base.remove("stream");
+        let block = match serde_json::from_value::<RecallContextInput>(Value::Object(base.clone())) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid arguments: {err}")),
+        };
+        if let Err(msg) = block.block() {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        for key in RecallContextInput::KEYS {
+            base.remove(key);
+        }
+        let limit = base
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map_or(5, |v| usize::try_from(v).unwrap_or(usize::MAX))
+            .clamp(1, 20);
+
+        // Caller identity, experiment ranking and synonyms are thread-local; carry them into the workers.
+        let caller = caller_agent_override();
+        let config = ranking_config();
+        let responses = std::thread::scope(|scope| {
+            let mut workers = Vec::with_capacity(queries.len());
+            for query in &queries {
+                let mut args = base.clone();
+                args.insert("query".to_string(), json!(query));
+                let (id, ctx, caller) = (id.clone(), ctx.clone(), caller.clone());
+                workers.push(scope.spawn(move || {
+                    with_caller_agent(caller, || {
+                        self.with_lexical(config, || self.exec_memory_recall(id, Some(Value::Object(args)), ctx))
+                    })
+                }));
+            }
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|_| JsonRpcResponse::error(id.clone(), -32603, "recall worker panicked"))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let mut per_query = Vec::with_capacity(queries.len());
+        let mut fused: Vec<(f32, Value, Vec<Value>)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for (idx, (query, mut response)) in queries.iter().zip(responses).enumerate() {
+            if let Some(err) = response.error.as_mut() {
+                err.message = format!("query #{idx}: {}", err.message);
+                return response;
+            }
+            let content = response
+                .result
+                .as_mut()
+                .and_then(|r| r.get_mut("structuredContent"))
+                .map(Value::take)
+                .unwrap_or_default();
+            let items = match content.get("items") {
+                Some(Value::Array(items)) => items.clone(),
+                _ => Vec::new(),
+            };
+            per_query.push(json!({
+                "query": query,
+                "count": items.len(),
+                "query_id": content.get("query_id"),
+                "warning": content.get("warning")
+            }));
+            for (rank, mut item) in items.into_iter().enumerate() {
+                let Some(mid) = item.pointer("/entry/id").and_then(Value::as_str).map(str::to_string) else {
+                    continue;
+                };
+                let attribution = json!({"query": idx, "rank": rank + 1, "score": item.get("score")});
+                let rrf = 1.0 / (MULTI_RECALL_RRF_K + u16::try_from(rank + 1).map_or(f32::MAX, f32::from));
+                if let Some(slot) = positions.get(&mid).and_then(|pos| fused.get_mut(*pos)) {
+                    slot.0 += rrf;
+                    slot.2.push(attribution);
+                } else {
+                    if let Some(obj) = item.as_object_mut() {
+                        obj.remove("score");
+                    }
+                    positions.insert(mid, fused.len());
+                    fused.push((rrf, item, vec![attribution]));
+                }
+            }
+        }
+        fused.sort_by(|a, b| {
+            b.0.total_cmp(&a.0).then_with(|| {
+                let id_of = |item: &Value| item.pointer("/entry/id").and_then(Value::as_str).map(str::to_string);
+                id_of(&a.1).cmp(&id_of(&b.1))
+            })
+        });
+        fused.truncate(limit);
+        let items = fused
+            .into_iter()
+            .map(|(score, mut item, matches)| {
+                if let Some(obj) = item.as_object_mut() {
+                    obj.insert("score".to_string(), json!(score));
+                    obj.insert("matches".to_string(), json!(matches));
+                }
+                item
+            })
+            .collect::<Vec<_>>();
+
+        let mut response = JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "count": items.len(),
+                    "agent_id": self.scopes.agent_id(),
+                    "fusion": "rrf",
+                    "queries": per_query,
+                    "items": items
+                },
+                "content": [{
+                    "type": "text",
+                    "text": format!("Recalled {} entries for {} queries.", items.len(), queries.len())
+                }]
+            }),
+        );
+        block.apply(&mut response);
+        response
+    }
+
+    fn exec_memory_recall_context(&self, id: Value, arguments: Option<Value>, ctx: CallContext) -> JsonRpcResponse {
+        let Some(Value::Object(mut recall_args)) = arguments else {
+            return JsonRpcResponse::error(id, -32602, "invalid params: expected an object");
+        };
+        let args: MemoryRecallContextInput = match serde_json::from_value(Value::Object(recall_args.clone())) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid arguments: {err}")),
+        };
+        let max_messages = args.max_messages.unwrap_or(6).clamp(1, 50);
+        let max_terms = args.max_terms.unwrap_or(12).clamp(1, 32);
+        let terms = context::salient_terms(&args.messages, max_messages, max_terms);
+        if terms.is_empty() {
+            return JsonRpcResponse::error(id, -32602, "messages contain no salient terms to recall by");
+        }
+        for key in ["messages", "max_messages", "max_terms", "query", "queries", "cursor"] {
+            recall_args.remove(key);
+        }
+        recall_args.insert(
+            "queries".to_string(),
+            json!(context::anchored_queries(&terms, CONTEXT_RECALL_ANCHORS)),
+        );
+        let mut response = self.exec_memory_recall(id, Some(Value::Object(recall_args)), ctx);
+        if let Some(content) = response
+            .result
+            .as_mut()
+            .and_then(|r| r.get_mut("structuredContent"))
+            .and_then(Value::as_object_mut)
+        {
+            content.insert("terms".to_string(), json!(terms));
+        }
+        response
+    }
+
+    fn exec_memory_session_note(
+        &self,
+        id: Value,
+        arguments: Option<Value>,
+        session_id: Option<&str>,
+    ) -> JsonRpcResponse {
+        let args: MemorySessionNoteInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let Some(session_id) = session_id else {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                "memory_session_note requires a stream session (POST /mcp/stream?session=...)",
+            );
+        };
+        if args.text.trim().is_empty() {
+            return JsonRpcResponse::error(id, -32602, "text must not be empty");
+        }
+        let notes = {
+            let mut sessions = self.sessions.lock();
+            let Some(state) = sessions.get_mut(session_id) else {
+                return JsonRpcResponse::error(id, -32602, "unknown or expired session");
+            };
+            let note = Message {
+                role: args.role.unwrap_or_else(|| "user".to_string()),
+                content: args.text,
+            };
+            state.add_note(note.clone());
+            self.log_session(&SessionRecord::Noted {
+                session: session_id.to_string(),
+                note,
+            });
+            let notes = state.notes.len();
+            drop(sessions);
+            notes
+        };
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {"session_id": session_id, "notes": notes},
+                "content": [{"type":"text","text": format!("noted; session working memory holds {notes} notes")}]
+            }),
+        )
+    }
+
+    fn exec_memory_recall_plan(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryRecallPlanInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let limit = args.limit.unwrap_or(5).clamp(1, 50);
+        let max_terms = args.max_terms.unwrap_or(12).clamp(1, 32);
+        let Some(plan) = self.recall_plan(&args.task, limit, max_terms) else {
+            return JsonRpcResponse::error(id, -32602, "task contains no salient terms to recall by");
+        };
+        let text = serde_json::to_string_pretty(&plan).unwrap_or_else(|_| "{}".to_string());
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": plan,
+                "content": [{"type": "text", "text": text}]
+            }),
+        )
+    }
+
+    /// Where memories about `task` likely sit: the scopes, categories and tags of readable entries
+    /// mentioning its salient terms, weighted by the share of terms each entry mentions. The
+    /// `memory_recall` payload is narrowed to a scope or category once it carries most of the weight.
+    /// `None` when the task has no salient terms.
+    fn recall_plan(&self, task: &str, limit: usize, max_terms: usize) -> Option<Value> {
+        let message = Message {
+            role: "user".to_string(),
+            content: task.to_string(),
+        };
+        let terms = context::salient_terms(std::slice::from_ref(&message), 1, max_terms);
+        if terms.is_empty() {
+            return None;
+        }
+        let entries = filter_entries_by_acl(self.store.read().list(200_000), &self.scopes, None, None);
+        let term_count = u16::try_from(terms.len()).map_or(f32::MAX, f32::from);
+        let (mut scopes, mut categories, mut tags) = (HashMap::new(), HashMap::new(), HashMap::new());
+        let mut matched = 0usize;
+        for entry in entries.iter().filter(|e| !is_pending_review(e)) {
+            let text = entry.text.to_lowercase();
+            let hits = terms
+                .iter()
+                .filter(|term| {
+                    text.contains(term.as_str())
+                        || entry.tags.iter().any(|tag| tag.to_lowercase().contains(term.as_str()))
+                })
+                .count();
+            if hits == 0 {
+                continue;
+            }
+            matched += 1;
+            let weight = u16::try_from(hits).map_or(f32::MAX, f32::from) / term_count;
+            let add = |facets: &mut HashMap<String, (f32, usize)>, name: &str| {
+                let slot = facets.entry(name.to_string()).or_insert((0.0, 0));
+                slot.0 += weight;
+                slot.1 += 1;
+            };
+            add(&mut scopes, &entry.scope);
+            add(&mut categories, &entry.category);
+            for tag in &entry.tags {
+                add(&mut tags, tag);
+            }
+        }
+        let scopes = ranked_facets(scopes);
+        let categories = ranked_facets(categories);
+        let tags = ranked_facets(tags);
+
+        let mut arguments = serde_json::Map::new();
+        arguments.insert(
+            "queries".to_string(),
+            json!(context::anchored_queries(&terms, CONTEXT_RECALL_ANCHORS)),
+        );
+        for (key, facets) in [("scope", &scopes), ("category", &categories)] {
+            if let Some(top) = facets.first().filter(|f| f.share >= RECALL_PLAN_DOMINANT_SHARE) {
+                arguments.insert(key.to_string(), json!(top.name));
+            }
+        }
+        arguments.insert("limit".to_string(), json!(limit));
+        Some(json!({
+            "terms": terms,
+            "matched": matched,
+            "scopes": scopes,
+            "categories": categories,
+            "tags": tags.into_iter().take(RECALL_PLAN_MAX_TAGS).collect::<Vec<_>>(),
+            "recall": {"name": "memory_recall", "arguments": arguments}
+        }))
+    }
+
+    fn exec_memory_forget(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryForgetInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let mut locked = self.store.write();
+        let outcome = self.forget_locked(locked.as_mut(), &args, None);
+        drop(locked);
+        self.finish_locked_write(id, outcome)
+    }
+
+    /// `memory_forget` against a locked store; deletions are recorded in `journal` when given.
+    fn forget_locked(
+        &self,
+        store: &mut dyn StorageBackend,
+        args: &MemoryForgetInput,
+        mut journal: Option<&mut WriteJournal>,
+    ) -> LockedWriteResult {
+        // Verify scope access before deletion
+        let entry = store.list(200_000).into_iter().find(|e| e.id == args.id);
+        if let Some(ref entry) = entry {
+            if !self.scopes.permits(&entry.scope, ScopeAction::Delete) {
+                return Err((-32603, format!("scope access denied for memory {}", args.id)));
+            }
+        }
+        let entity_links = if entry.is_some() {
+            entity_link_ids(store, &args.id)
+        } else {
+            Vec::new()
+        };
+        if args.dry_run.unwrap_or(false) {
+            let relations = entry.as_ref().map_or(0, |e| store.relations_for(&e.id).len());
+            let found = entry.is_some();
+            let entry = entry.map(|mut e| {
+                e.embedding = None;
+                e
+            });
+            return Ok(LockedWrite {
+                result: json!({
+                    "structuredContent": {
+                        "dry_run": true,
+                        "deleted": false,
+                        "would_delete": found,
+                        "id": args.id,
+                        "entry": entry,
+                        "relations": relations,
+                        "entity_links": entity_links
+                    },
+                    "content": [{"type":"text", "text": if found {"would delete"} else {"not found"}}]
+                }),
+                usage: None,
+            });
+        }
+
+        if let (Some(journal), Some(entry)) = (journal.as_deref_mut(), entry) {
+            journal.forgetting(store, entry);
+        }
+        let deleted = store.forget_by_id(&args.id).map_err(|err| (-32001, err.to_string()))?;
+        if deleted {
+            let rows = store.list(200_000);
+            for link_id in &entity_links {
+                if let (Some(journal), Some(linked)) = (journal.as_deref_mut(), rows.iter().find(|e| &e.id == link_id))
+                {
+                    journal.forgetting(store, linked.clone());
+                }
+                let _ = store.forget_by_id(link_id);
+            }
+        }
+
+        Ok(LockedWrite {
+            result: json!({
+                "structuredContent": {"deleted": deleted, "id": args.id},
+                "content": [{"type":"text", "text": if deleted {"deleted"} else {"not found"}}]
+            }),
+            usage: deleted.then_some((UsageOp::Forget, 0)),
+        })
+    }
+
+    fn exec_memory_update(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryUpdateInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+
+        let mut locked = self.store.write();
+        let outcome = self.update_locked(locked.as_mut(), args, None);
+        drop(locked);
+        self.finish_locked_write(id, outcome)
+    }
+
+    /// `memory_update` against a locked store; the rewrite is recorded in `journal` when given.
+    fn update_locked(
+        &self,
+        store: &mut dyn StorageBackend,
+        args: MemoryUpdateInput,
+        journal: Option<&mut WriteJournal>,
+    ) -> LockedWriteResult {
+        let governed = args
+            .governed
+            .unwrap_or_else(|| self.standards.default_governed_for_update());
+        let existing = store.list(200_000).into_iter().find(|e| e.id == args.id);
+        let Some(existing) = existing else {
+            return Err((-32602, "memory id not found".into()));
+        };
+
+        if !self.scopes.permits(&existing.scope, ScopeAction::Write) {
+            return Err((-32602, "scope access denied for existing memory".into()));
+        }
+
+        let merged_scope = args.scope.unwrap_or(existing.scope.clone());
+        let merged_category = match args.category {
+            Some(category) => match self.standards.governance.resolve_category(&category) {
+                Ok(v) => v,
+                Err(msg) => return Err((-32602, msg)),
+            },
+            None => existing.category.clone(),
+        };
+        let mut merged_text = args.text.unwrap_or(existing.text.clone());
+        let mut merged_tags = normalize_tags_with_defaults(
+            args.tags.unwrap_or(existing.tags.clone()),
+            args.project_tag.as_deref(),
+            args.tool_tag.as_deref(),
+            args.domain_tag.as_deref(),
+            &self.standards,
+        );
+        let (merged_importance, importance_level) =
+            match resolve_importance(args.importance_level.as_deref(), args.importance) {
+                Ok((importance, level)) => (importance, level),
+                Err(_) => (existing.importance, importance_level_from_numeric(existing.importance)),
+            };
+        if merged_text != existing.text
+            && let Err(msg) = apply_redaction(
+                self.standards.redaction_for(governed),
+                &mut merged_text,
+                &mut merged_tags,
+            )
+        {
+            return Err((-32602, msg));
+        }
+        let dry_run = args.dry_run.unwrap_or(false);
+        let (merged_embedding, merged_embedding_model) = if merged_text != existing.text && !dry_run {
+            match embed_one(
+                &self.runtime,
+                &self.embed,
+                &CallContext::default(),
+                &merged_text,
+                EmbeddingTask::Passage,
+            ) {
+                Ok(v) => split_embedded(Some(v)),
+                Err(_) => (existing.embedding.clone(), existing.embedding_model.clone()),
+            }
+        } else {
+            (existing.embedding.clone(), existing.embedding_model.clone())
+        };
+
+        if !self.scopes.permits(&merged_scope, ScopeAction::Write) {
+            return Err((-32602, "scope access denied for target scope".into()));
+        }
+        if let Some(msg) = self.scopes.validate_scope_write(&merged_scope, &merged_tags) {
+            return Err((-32602, msg));
+        }
+
+        if governed {
+            if let Err(msg) =
+                self.standards
+                    .governance
+                    .validate(&merged_text, &merged_category, &merged_tags, importance_level)
+            {
+                return Err((-32602, msg));
+            }
+        }
+
+        if dry_run {
+            let changed = [
+                ("text", merged_text != existing.text),
+                ("category", merged_category != existing.category),
+                ("scope", merged_scope != existing.scope),
+                (
+                    "importance",
+                    (merged_importance - existing.importance).abs() > f32::EPSILON,
+                ),
+                ("tags", merged_tags != existing.tags),
+            ]
+            .into_iter()
+            .filter_map(|(field, changed)| changed.then_some(field))
+            .collect::<Vec<_>>();
+            let reembed = changed.contains(&"text");
+            let mut before = existing;
+            before.embedding = None;
+            return Ok(LockedWrite {
+                result: json!({
+                    "structuredContent": {
+                        "dry_run": true,
+                        "id": args.id,
+                        "changed": changed,
+                        "reembed": reembed,
+                        "before": before,
+                        "after": {
+                            "text": merged_text,
+                            "category": merged_category,
+                            "scope": merged_scope,
+                            "importance": merged_importance,
+                            "tags": merged_tags
+                        }
+                    },
+                    "content": [{"type":"text", "text": format!("update preview: {} field(s) would change", changed.len())}]
+                }),
+                usage: None,
+            });
+        }
+
+        let relations = store.relations_for(&args.id);
+        let previous = journal.is_some().then(|| existing.clone());
+        let replaced = store.replace(
+            &args.id,
+            NewMemoryEntry {
+                category: merged_category,
+                scope: merged_scope,
+                importance: merged_importance,
+                tags: merged_tags,
+                embedding: merged_embedding,
+                embedding_model: merged_embedding_model,
+                keywords: stored_keywords(&self.standards.governance, &merged_text),
+                text: merged_text,
+                source: existing.source,
+            },
+        );
+        let updated = match replaced {
+            Ok(Some(v)) => v,
+            Ok(None) => return Err((-32602, "memory id not found".into())),
+            Err(err) => return Err((-32001, err.to_string())),
+        };
+        // Carry relations over to the replacement id so updates don't orphan the graph.
+        for edge in relations {
+            let from = if edge.from_id == args.id {
+                &updated.id
+            } else {
+                &edge.from_id
+            };
+            let to = if edge.to_id == args.id {
+                &updated.id
+            } else {
+                &edge.to_id
+            };
+            let _ = store.link(from, &edge.relation, to);
+        }
+        if let (Some(journal), Some(previous)) = (journal, previous) {
+            journal.replaced(previous, &updated.id);
+        }
+
+        let usage = Some((UsageOp::Store, updated.text.len()));
+        let mut updated_clean = updated;
+        updated_clean.embedding = None;
+        Ok(LockedWrite {
+            result: json!({
+                "structuredContent": {
+                    "replaced_id": args.id,
+                    "entry": updated_clean
+                },
+                "content": [{"type":"text", "text": "memory updated"}]
+            }),
+            usage,
+        })
+    }
+
+    fn finish_locked_write(&self, id: Value, outcome: LockedWriteResult) -> JsonRpcResponse {
+        match outcome {
+            Ok(write) => {
+                if let Some((op, bytes)) = write.usage {
+                    self.record_agent_usage(op, bytes);
+                }
+                JsonRpcResponse::success(id, write.result)
+            }
+            Err((code, msg)) => JsonRpcResponse::error(id, code, msg),
+        }
+    }
+
+    fn exec_memory_transaction(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryTransactionInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        if args.operations.is_empty() || args.operations.len() > MAX_TRANSACTION_OPS {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("operations must hold 1-{MAX_TRANSACTION_OPS} entries"),
+            );
+        }
+        // Parse and authorize every operation before anything is written.
+        let agent_id = self.scopes.agent_id();
+        let mut ops = Vec::with_capacity(args.operations.len());
+        for (idx, raw) in args.operations.into_iter().enumerate() {
+            let op: TransactionOp = match serde_json::from_value(raw.clone()) {
+                Ok(v) => v,
+                Err(err) => return JsonRpcResponse::error(id, -32602, format!("operation #{idx}: {err}")),
+            };
+            if op.dry_run() {
+                return JsonRpcResponse::error(
+                    id,
+                    -32602,
+                    format!("operation #{idx}: dry_run is not supported inside memory_transaction"),
+                );
+            }
+            if let Err(denial) = self.tool_policy.check(&agent_id, op.tool(), Some(&raw)) {
+                return JsonRpcResponse::error_with_data(
+                    id,
+                    -32004,
+                    format!(
+                        "permission denied: agent {agent_id} may not call {} (operation #{idx})",
+                        op.tool()
+                    ),
+                    denial,
+                );
+            }
+            ops.push(op);
+        }
+
+        let mut locked = self.store.write();
+        let mut journal = WriteJournal::new();
+        let mut results = Vec::with_capacity(ops.len());
+        let mut usage = Vec::new();
+        for (idx, op) in ops.into_iter().enumerate() {
+            let outcome = match op {
+                TransactionOp::Store(args) => self.transaction_store(locked.as_mut(), args, &mut journal),
+                TransactionOp::Update(args) => self.update_locked(locked.as_mut(), args, Some(&mut journal)),
+                TransactionOp::Forget(args) => self.forget_locked(locked.as_mut(), &args, Some(&mut journal)),
+            };
+            match outcome {
+                Ok(write) => {
+                    usage.extend(write.usage);
+                    results.push(write.result.get("structuredContent").cloned().unwrap_or(Value::Null));
+                }
+                Err((code, msg)) => {
+                    let undone = journal.len();
+                    let rollback_errors = journal.rollback(locked.as_mut());
+                    drop(locked);
+                    return JsonRpcResponse::error_with_data(
+                        id,
+                        code,
+                        format!("operation #{idx} failed: {msg}; rolled back {undone} earlier write(s)"),
+                        json!({
+                            "kind": "transaction_rolled_back",
+                            "failed_operation": idx,
+                            "rolled_back": undone,
+                            "rollback_errors": rollback_errors
+                        }),
+                    );
+                }
+            }
+        }
+        drop(locked);
+        for (op, bytes) in usage {
+            self.record_agent_usage(op, bytes);
+        }
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "committed": true,
+                    "operations": results.len(),
+                    "results": results
+                },
+                "content": [{"type":"text", "text": format!("transaction committed {} operation(s)", results.len())}]
+            }),
+        )
+    }
+
+    /// A `memory_store` step of a transaction. Auto-maintenance and entity extraction are skipped
+    /// because their writes are not journaled.
+    fn transaction_store(
+        &self,
+        store: &mut dyn StorageBackend,
+        args: MemoryStoreInput,
+        journal: &mut WriteJournal,
+    ) -> LockedWriteResult {
+        let (mut request, suggestion) = self.store_request(store, args).map_err(|msg| (-32602, msg))?;
+        request.allow_auto_maintenance = false;
+        self.check_store_quota(store, &request.scope, QuotaUsage::of_text(&request.text))
+            .map_err(|exceeded| (-32007, exceeded.message()))?;
+        let outcome = self
+            .store_layer(store, request)
+            .map_err(|err| (-32602, err.to_string()))?;
+        journal.stored(&outcome.entry.id);
+        if let Some(suggestion) = &suggestion {
+            log_importance_suggestion(&outcome.entry, suggestion);
+        }
+        let mut entry = outcome.entry;
+        entry.embedding = None;
+        Ok(LockedWrite {
+            usage: Some((UsageOp::Store, entry.text.len())),
+            result: json!({
+                "structuredContent": entry,
+                "content": [{"type":"text", "text": format!("stored {}", entry.id)}]
+            }),
+        })
+    }
+
+    fn exec_memory_export(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryExportInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+
+        if let Some(scope) = &args.scope {
+            if !self.scopes.can_access_scope(scope) {
+                return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
+            }
+        }
+
+        let format = match ExportFormat::parse(args.format.as_deref()) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32602, err),
+        };
+        let limit = args.limit.unwrap_or(500).clamp(1, 20_000);
+        let include_embeddings = args.include_embeddings.unwrap_or(false);
+        let mut items = match args.query.as_deref().map(str::trim) {
+            Some("") => return JsonRpcResponse::error(id, -32602, "query cannot be empty"),
+            Some(query) => match self.export_matches(query, &args, limit) {
+                Ok(items) => items,
+                Err(resp) => return with_id(resp, id),
+            },
+            None => {
+                let locked = self.store.read();
+                let rows = locked.list(200_000);
+                drop(locked);
+                filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), args.category.as_deref())
+            }
+        };
+        items.truncate(limit);
+        if !include_embeddings {
+            for row in &mut items {
+                row.embedding = None;
+                row.embedding_model = None;
+                row.embedding_dim = None;
+            }
+        }
+
+        if let Some(raw_path) = args.output_path {
+            let safe_path = match validate_safe_path(&raw_path) {
+                Ok(p) => p,
+                Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid output path: {err}")),
+            };
+            let written = fs::File::create(&safe_path)
+                .and_then(|file| transfer::write_entries(&mut io::BufWriter::new(file), format, &items));
+            if let Err(err) = written {
+                return JsonRpcResponse::error(id, -32001, err.to_string());
+            }
+            let path_display = safe_path.display().to_string();
+            return JsonRpcResponse::success(
+                id,
+                json!({
+                    "structuredContent": {
+                        "count": items.len(),
+                        "format": format.as_str(),
+                        "output_path": path_display
+                    },
+                    "content": [{"type":"text","text":"memory export completed"}]
+                }),
+            );
+        }
+
+        if format != ExportFormat::Json {
+            let mut rendered = Vec::new();
+            if let Err(err) = transfer::write_entries(&mut rendered, format, &items) {
+                return JsonRpcResponse::error(id, -32001, err.to_string());
+            }
+            let data = String::from_utf8_lossy(&rendered).into_owned();
+            return JsonRpcResponse::success(
+                id,
+                json!({
+                    "structuredContent": {
+                        "count": items.len(),
+                        "format": format.as_str(),
+                        "data": data
+                    },
+                    "content": [{"type":"text","text": data}]
+                }),
+            );
+        }
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "count": items.len(),
+                    "items": items
+                },
+                "content": [{"type":"text","text": format!("exported {} memories", items.len())}]
+            }),
+        )
+    }
+
+    /// The entries `memory_recall` would return for an export's `query`, best match first.
+    fn export_matches(
+        &self,
+        query: &str,
+        args: &MemoryExportInput,
+        limit: usize,
+    ) -> Result<Vec<MemoryEntry>, JsonRpcResponse> {
+        let query_embedding = if args.use_vector.unwrap_or(false) {
+            let parsed = parse_query(query);
+            let embed_text = if parsed.text.is_empty() { query } else { &parsed.text };
+            let embedded = embed_one(
+                &self.runtime,
+                &self.embed,
+                &CallContext::default(),
+                embed_text,
+                EmbeddingTask::Query,
+            )
+            .map_err(|msg| JsonRpcResponse::error(Value::Null, -32002, msg))?;
+            Some(embedded.vector)
+        } else {
+            None
+        };
+        let snapshot = self.store.read().snapshot();
+        let results = recall_with_acl(
+            &snapshot,
+            &self.scopes,
+            RecallAclRequest {
+                query: query.to_string(),
+                query_embedding,
+                requested_scope: args.scope.clone(),
+                category: args.category.clone(),
+                candidate_pool: limit,
+                vector_weight: None,
+                lexical_weight: None,
+                diversity: None,
+                fusion: None,
+            },
+        );
+        let include_pending = args.include_pending.unwrap_or(false);
+        Ok(results
+            .into_iter()
+            .map(|r| r.entry)
+            .filter(|entry| include_pending || !is_pending_review(entry))
+            .collect())
+    }
+
+    fn exec_memory_import(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryImportInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let options = ImportOptions {
+            governed: args
+                .governed
+                .unwrap_or_else(|| self.standards.default_governed_for_import()),
+            use_vector: args.use_vector.unwrap_or(false),
+            skip_duplicates: args.skip_duplicates.unwrap_or(true),
+            dry_run: args.dry_run.unwrap_or(false),
+        };
+        let entries = match (args.entries, args.data) {
+            (Some(_), Some(_)) => return JsonRpcResponse::error(id, -32602, "pass either entries or data, not both"),
+            (Some(entries), None) => entries,
+            (None, Some(data)) => {
+                let decoded = ImportFormat::resolve(args.format.as_deref(), None)
+                    .and_then(|format| transfer::read_records(&data, format, &args.columns.unwrap_or_default()))
+                    .and_then(decode_import_entries);
+                match decoded {
+                    Ok(v) => v,
+                    Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid import data: {err}")),
+                }
+            }
+            (None, None) => return JsonRpcResponse::error(id, -32602, "entries or data is required"),
+        };
+        let dry_run = options.dry_run;
+        let summary = self.import_entries(entries, options);
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "dry_run": dry_run,
+                    "created": summary.created,
+                    "skipped": summary.skipped,
+                    "failed": summary.failed,
+                    "errors": summary.errors,
+                    "planned": summary.planned
+                },
+                "content": [{"type":"text","text": format!("import {}: created={}, skipped={}, failed={}", if dry_run {"preview"} else {"done"}, summary.created, summary.skipped, summary.failed)}]
+            }),
+        )
+    }
+
+    fn exec_memory_migrate(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryMigrateInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+
+        let safe_path = match validate_safe_path(&args.source_path) {
+            Ok(p) => p,
+            Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid source path: {err}")),
+        };
+
+        let source_format = match args.source_format.as_deref().map(SourceFormat::parse).transpose() {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32602, err),
+        };
+        if source_format.is_some() && (args.format.is_some() || args.columns.is_some()) {
+            return JsonRpcResponse::error(id, -32602, "source_format cannot be combined with format or columns");
+        }
+        let format = match ImportFormat::resolve(args.format.as_deref(), Some(&safe_path)) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32602, err),
+        };
+        let data = match fs::read_to_string(&safe_path) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+        };
+        let records = match source_format {
+            Some(source) => adapters::read_source(&data, source),
+            None => transfer::read_records(&data, format, &args.columns.unwrap_or_default()),
+        };
+        let mut entries = match records.and_then(decode_import_entries) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid migrate file: {err}")),
+        };
+        for entry in &mut entries {
+            if let Some(scope) = &args.scope {
+                entry.scope = Some(scope.clone());
+            }
+            // Adapters only set a category when the source has a clear equivalent.
+            if source_format.is_some() && entry.category.is_none() {
+                entry.category = Some(infer_default_category(&entry.text).to_string());
+            }
+        }
+
+        let options = ImportOptions {
+            governed: args.governed.unwrap_or(false),
+            use_vector: args.use_vector.unwrap_or(false),
+            skip_duplicates: args.skip_duplicates.unwrap_or(true),
+            dry_run: args.dry_run.unwrap_or(false),
+        };
+        let dry_run = options.dry_run;
+        let summary = self.import_entries(entries, options);
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "source_path": args.source_path,
+                    "source_format": source_format.map(SourceFormat::as_str),
+                    "dry_run": dry_run,
+                    "created": summary.created,
+                    "skipped": summary.skipped,
+                    "failed": summary.failed,
+                    "errors": summary.errors,
+                    "planned": summary.planned
+                },
+                "content": [{"type":"text","text": if dry_run {"memory migration preview"} else {"memory migration completed"}}]
+            }),
+        )
+    }
+
+    fn exec_memory_ingest_files(&self, id: Value, arguments: Option<Value>, ctx: &CallContext) -> JsonRpcResponse {
+        let args: MemoryIngestFilesInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let root = match validate_safe_path(&args.path) {
+            Ok(p) if p.is_dir() => p,
+            Ok(p) => return JsonRpcResponse::error(id, -32602, format!("not a directory: {}", p.display())),
+            Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid path: {err}")),
+        };
+        let governed = args.governed.unwrap_or(true);
+        let use_vector = args.use_vector.unwrap_or(true);
+        let review = args.review.unwrap_or(true);
+        let dry_run = args.dry_run.unwrap_or(false);
+        // Governed chunks must fit the policy's entry size.
+        let max_chunk = if governed {
+            self.standards.governance.max_chars.clamp(80, 4000)
+        } else {
+            4000
+        };
+        let chunk_size = args.chunk_size.unwrap_or(480).clamp(80, max_chunk);
+        let options = ChunkOptions {
+            size: chunk_size,
+            overlap: args.chunk_overlap.unwrap_or(60).min(chunk_size / 2),
+        };
+        let scope = args.scope.unwrap_or_else(|| self.scopes.default_scope());
+        let category = args.category.unwrap_or_else(|| "other".to_string());
+        let (importance, importance_level) = match resolve_importance(args.importance_level.as_deref(), None) {
+            Ok(v) => v,
+            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
+        };
+        if use_vector
+            && !dry_run
+            && let Err(err) = build_embedding_provider_from_env(None)
+        {
+            return JsonRpcResponse::error(
+                id,
+                -32002,
+                format!("embedding provider unavailable: {err}; pass use_vector=false to ingest without embeddings"),
+            );
+        }
+        let paths = match ingest::collect_notes(&root, args.max_files.unwrap_or(1000).clamp(1, 10_000)) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+        };
+
+        let (mut chunks, mut created, mut skipped, mut failed) = (0usize, 0usize, 0usize, 0usize);
+        let mut errors = Vec::new();
+        let mut files = Vec::new();
+        let mut stopped = None;
+        'files: for path in &paths {
+            let rel_path = path
+                .strip_prefix(&root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let content = match fs::read_to_string(path) {
+                Ok(v) => v,
+                Err(err) => {
+                    failed += 1;
+                    errors.push(format!("{rel_path}: {err}"));
+                    continue;
+                }
+            };
+            let note = ingest::parse_note(&rel_path, &content, options);
+            chunks += note.chunks.len();
+            files.push(json!({"path": rel_path, "chunks": note.chunks.len()}));
+            if dry_run {
+                continue;
+            }
+            let mut raw_tags = note.tags;
+            raw_tags.extend(args.tags.iter().flatten().cloned());
+            if review {
+                raw_tags.push(PENDING_REVIEW_TAG.to_string());
+            }
+            let tags = normalize_tags_with_defaults(
+                raw_tags,
+                args.project_tag.as_deref().or(note.project.as_deref()),
+                args.tool_tag.as_deref().or(note.tool.as_deref()),
+                args.domain_tag.as_deref().or(note.domain.as_deref()),
+                &self.standards,
+            );
+            for (idx, text) in note.chunks.into_iter().enumerate() {
+                if let Some(reason) = ctx.cancel_reason() {
+                    stopped = Some(reason);
+                    break 'files;
+                }
+                let bytes = text.len();
+                let mut locked = self.store.write();
+                if let Err(exceeded) = self.check_store_quota(locked.as_ref(), &scope, QuotaUsage::of_text(&text)) {
+                    failed += 1;
+                    errors.push(format!("{rel_path}#{idx}: {}", exceeded.message()));
+                    break 'files;
+                }
+                let stored = self.store_layer(
+                    locked.as_mut(),
+                    StoreLayerRequest {
+                        text,
+                        category: category.clone(),
+                        scope: scope.clone(),
+                        importance,
+                        importance_level,
+                        tags: tags.clone(),
+                        governed,
+                        use_vector,
+                        enforce_verify: false,
+                        allow_auto_maintenance: true,
+                        redaction: self.standards.redaction_for(governed),
+                        source: Some(MemorySource {
+                            url: Some(path.display().to_string()),
+                            tool: Some("memory_ingest_files".to_string()),
+                            ..MemorySource::default()
+                        }),
+                        override_duplicate: false,
+                    },
+                );
+                drop(locked);
+                match stored {
+                    Ok(_) => {
+                        created += 1;
+                        self.record_agent_usage(UsageOp::Store, bytes);
+                    }
+                    Err(StoreLayerError::Duplicate { .. }) => skipped += 1,
+                    Err(err) => {
+                        failed += 1;
+                        if errors.len() < 50 {
+                            errors.push(format!("{rel_path}#{idx}: {err}"));
+                        }
+                    }
+                }
+            }
+        }
+
+        let text = if dry_run {
+            format!("dry run: {} files, {chunks} chunks", files.len())
+        } else {
+            format!("ingest done: created={created}, skipped={skipped}, failed={failed}")
+        };
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "dry_run": dry_run,
+                    "files": files,
+                    "chunks": chunks,
+                    "created": created,
+                    "skipped": skipped,
+                    "failed": failed,
+                    "errors": errors,
+                    "stopped": stopped
+                },
+                "content": [{"type":"text","text": text}]
+            }),
+        )
+    }
+
+    fn exec_memory_distill(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryDistillInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        match self.distill_to_review(args) {
+            Ok((structured, text)) => JsonRpcResponse::success(
+                id,
+                json!({
+                    "structuredContent": structured,
+                    "content": [{"type":"text","text": text}]
+                }),
+            ),
+            Err((code, msg)) => JsonRpcResponse::error(id, code, msg),
+        }
+    }
+
+    /// Distills `args.messages` into governed dual-layer lessons queued for review. Returns the
+    /// structured result and its summary line.
+    fn distill_to_review(&self, args: MemoryDistillInput) -> Result<(Value, String), (i64, String)> {
+        let documents = distill::transcript(&args.messages);
+        if documents.is_empty() {
+            return Err((
+                -32602,
+                "messages must contain at least one non-empty message".to_string(),
+            ));
+        }
+        let scope = args.scope.unwrap_or_else(|| self.scopes.default_scope());
+        self.scopes
+            .check(&scope, ScopeAction::Write)
+            .map_err(|denied| (-32602, denied))?;
+        let dry_run = args.dry_run.unwrap_or(false);
+        let use_vector = args.use_vector.unwrap_or(false);
+        let max_candidates = args.max_candidates.unwrap_or(10).clamp(1, 50);
+        let mut raw_tags = args.tags.unwrap_or_default();
+        raw_tags.extend([DISTILL_SOURCE_TAG.to_string(), PENDING_REVIEW_TAG.to_string()]);
+        let tags = normalize_tags_with_defaults(
+            raw_tags,
+            args.project_tag.as_deref(),
+            args.tool_tag.as_deref(),
+            args.domain_tag.as_deref(),
+            &self.standards,
+        );
+
+        let provider = build_summarize_provider_from_env().map_err(|msg| (-32002, msg))?;
+        let span = tracing::info_span!(
+            "provider",
+            kind = "summarize",
+            provider = provider.name(),
+            inputs = documents.len()
+        )
+        .entered();
+        let output = match self.runtime.block_on(async {
+            provider
+                .summarize(SummarizeRequest {
+                    documents,
+                    instruction: Some(distill::INSTRUCTION.to_string()),
+                    max_chars: None,
+                })
+                .await
+        }) {
+            Ok(v) => v,
+            Err(e) => {
+                let msg = format!("distillation failed: {}", provider_error_en_summarize(&e));
+                tracing::warn!(error = %msg, "distill provider call failed");
+                return Err((-32002, msg));
+            }
+        };
+        drop(span);
+
+        // Governed dual-layer writes need both layers; lessons without a principle are reported only.
+        let (mut lessons, incomplete): (Vec<_>, Vec<_>) = distill::parse_lessons(&output.summary)
+            .into_iter()
+            .partition(|l| l.principle.is_some());
+        lessons.truncate(max_candidates);
+        let candidates = lessons
+            .iter()
+            .map(|l| json!({"technical": l.text(), "principle": l.principle.as_ref().map(distill::Principle::text)}))
+            .collect::<Vec<_>>();
+
+        let mut created = Vec::new();
+        let (mut skipped, mut failed) = (0usize, 0usize);
+        let mut errors = Vec::new();
+        for (idx, lesson) in lessons.iter().enumerate() {
+            if dry_run {
+                break;
+            }
+            let Some(principle) = &lesson.principle else {
+                continue;
+            };
+            let layer = |text: String, category: &str| StoreLayerRequest {
+                text,
+                category: category.to_string(),
+                scope: scope.clone(),
+                importance: 0.75,
+                importance_level: "high",
+                tags: tags.clone(),
+                governed: true,
+                use_vector,
+                enforce_verify: true,
+                allow_auto_maintenance: true,
+                redaction: self.standards.redaction_for(true),
+                source: None,
+                override_duplicate: false,
+            };
+            let mut locked = self.store.write();
+            let adding = QuotaUsage::of_text(&lesson.text()).plus(QuotaUsage::of_text(&principle.text()));
+            if let Err(exceeded) = self.check_store_quota(locked.as_ref(), &scope, adding) {
+                failed += 1;
+                errors.push(format!("candidate {idx}: {}", exceeded.message()));
+                break;
+            }
+            let stored = self
+                .store_layer(locked.as_mut(), layer(lesson.text(), "fact"))
+                .and_then(
+                    |technical| match self.store_layer(locked.as_mut(), layer(principle.text(), "decision")) {
+                        Ok(principle) => Ok((technical, principle)),
+                        Err(msg) => {
+                            let _ = locked.forget_by_id(&technical.entry.id);
+                            Err(msg)
+                        }
+                    },
+                );
+            drop(locked);
+            match stored {
+                Ok((technical, principle)) => {
+                    self.record_agent_usage(UsageOp::Store, technical.entry.text.len());
+                    self.record_agent_usage(UsageOp::Store, principle.entry.text.len());
+                    created.push(json!({"technical_id": technical.entry.id, "principle_id": principle.entry.id}));
+                }
+                Err(StoreLayerError::Duplicate { .. }) => skipped += 1,
+                Err(err) => {
+                    failed += 1;
+                    errors.push(format!("candidate {idx}: {err}"));
+                }
+            }
+        }
+
+        let text = if dry_run {
+            format!("dry run: {} lesson candidates", candidates.len())
+        } else {
+            format!(
+                "distill done: created={}, skipped={skipped}, failed={failed}; pending review",
+                created.len()
+            )
+        };
+        Ok((
+            json!({
+                "dry_run": dry_run,
+                    "provider": output.provider,
+                    "model": output.model,
+                    "scope": scope,
+                    "candidates": candidates,
+                    "incomplete": incomplete.len(),
+                    "created": created,
+                    "skipped": skipped,
+                    "failed": failed,
+                    "errors": errors,
+                "review_tag": PENDING_REVIEW_TAG
+            }),
+            text,
+        ))
+    }
+
+    fn exec_memory_review_list(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryReviewListInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        if let Some(scope) = &args.scope
+            && !self.scopes.can_access_scope(scope)
+        {
+            return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
+        }
+        let limit = args.limit.unwrap_or(50).clamp(1, 500);
+        let rows = self.store.read().list(200_000);
+        let mut pending = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), args.category.as_deref())
+            .into_iter()
+            .filter(is_pending_review)
+            .collect::<Vec<_>>();
+        pending.sort_by(|a, b| a.timestamp_ms.cmp(&b.timestamp_ms).then_with(|| a.id.cmp(&b.id)));
+        let total = pending.len();
+        pending.truncate(limit);
+        for entry in &mut pending {
+            entry.embedding = None;
+        }
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "total": total,
+                    "count": pending.len(),
+                    "items": pending
+                },
+                "content": [{"type":"text","text": format!("{total} memories pending review")}]
+            }),
+        )
+    }
+
+    /// Shared by `memory_approve` (drop the pending tag) and `memory_reject` (delete).
+    fn exec_memory_review_decision(&self, id: Value, arguments: Option<Value>, approve: bool) -> JsonRpcResponse {
+        let args: MemoryReviewDecisionInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let mut ids = Vec::with_capacity(args.ids.len());
+        for mid in args.ids {
+            if !ids.contains(&mid) {
+                ids.push(mid);
+            }
+        }
+        if ids.is_empty() || ids.len() > 100 {
+            return JsonRpcResponse::error(id, -32602, "ids must contain between 1 and 100 memory ids");
+        }
+
+        let mut locked = self.store.write();
+        let rows = locked.list(200_000);
+        let action = if approve {
+            ScopeAction::Write
+        } else {
+            ScopeAction::Delete
+        };
+        let mut done = Vec::new();
+        let (mut missing, mut not_pending, mut denied) = (Vec::new(), Vec::new(), Vec::new());
+        for mid in ids {
+            let Some(entry) = rows
+                .iter()
+                .find(|e| e.id == mid && self.scopes.can_access_scope(&e.scope))
+            else {
+                missing.push(mid);
+                continue;
+            };
+            if !self.scopes.permits(&entry.scope, action) {
+                denied.push(mid);
+                continue;
+            }
+            if !is_pending_review(entry) {
+                not_pending.push(mid);
+                continue;
+            }
+            let outcome = if approve {
+                approve_pending_entry(locked.as_mut(), entry).map(|approved| json!({"id": mid, "new_id": approved.id}))
+            } else {
+                match locked.forget_by_id(&mid) {
+                    Ok(_) => {
+                        forget_entity_links(locked.as_mut(), &mid);
+                        Ok(json!({"id": mid}))
+                    }
+                    Err(err) => Err(err.to_string()),
+                }
+            };
+            match outcome {
+                Ok(v) => done.push(v),
+                Err(err) => return JsonRpcResponse::error(id, -32001, err),
+            }
+        }
+        drop(locked);
+        for _ in &done {
+            self.record_agent_usage(if approve { UsageOp::Store } else { UsageOp::Forget }, 0);
+        }
+
+        let (key, verb) = if approve {
+            ("approved", "approved")
+        } else {
+            ("rejected", "rejected")
+        };
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    key: done,
+                    "missing": missing,
+                    "not_pending": not_pending,
+                    "denied": denied
+                },
+                "content": [{"type":"text","text": format!("{} memories {verb}", done.len())}]
+            }),
+        )
+    }
+
+    fn exec_memory_reembed(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryReembedInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        if let Some(scope) = &args.scope
+            && let Err(denied) = self.scopes.check(scope, ScopeAction::Admin)
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+        let limit = args.limit.unwrap_or(200).clamp(1, 5_000);
+        let batch_size = args
+            .batch_size
+            .unwrap_or_else(|| env_usize("PRX_EMBED_BATCH_SIZE", 32, 1, 256))
+            .clamp(1, 256);
+
+        let locked = self.store.read();
+        let rows = locked.list(200_000);
+        drop(locked);
+
+        let pending_ids = filter_entries_for(
+            rows,
+            &self.scopes,
+            ScopeAction::Admin,
+            args.scope.as_deref(),
+            args.category.as_deref(),
+        )
+        .into_iter()
+        .take(limit)
+        .map(|e| e.id)
+        .collect::<Vec<_>>();
+        let created = self
+            .jobs
+            .lock()
+            .create(args.scope.clone(), args.category.clone(), batch_size, pending_ids);
+        let job_id = match created {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err),
+        };
+
+        if args.wait.unwrap_or(false) {
+            run_reembed_job(
+                &self.runtime,
+                &self.embed,
+                &self.store,
+                &self.jobs,
+                &self.notices,
+                &job_id,
+            );
+        } else {
+            spawn_reembed_job(
+                Arc::clone(&self.runtime),
+                Arc::clone(&self.embed),
+                Arc::clone(&self.store),
+                Arc::clone(&self.jobs),
+                Arc::clone(&self.notices),
+                job_id.clone(),
+            );
+        }
+
+        let Some(job) = self.jobs.lock().get(&job_id).cloned() else {
+            return JsonRpcResponse::error(id, -32001, format!("reembed job disappeared: {job_id}"));
+        };
+        let text = if job.status == "running" {
+            format!("reembed job {} started for {} entries", job.id, job.total)
+        } else {
+            format!(
+                "reembed done: updated={}, failed={}, batches={}",
+                job.updated, job.failed, job.batches
+            )
+        };
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": reembed_job_summary(&job),
+                "content": [{"type":"text","text": text}]
+            }),
+        )
+    }
+
+    fn exec_memory_job_status(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryJobStatusInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let registry = self.jobs.lock();
+        let visible = |job: &&ReembedJob| {
+            job.scope
+                .as_deref()
+                .is_none_or(|scope| self.scopes.can_access_scope(scope))
+        };
+        if let Some(job_id) = args.job_id {
+            let Some(job) = registry.get(&job_id).filter(visible) else {
+                return JsonRpcResponse::error(id, -32602, format!("job not found: {job_id}"));
+            };
+            let summary = reembed_job_summary(job);
+            let text = format!(
+                "job {}: {} ({}/{} processed)",
+                job.id,
+                job.status,
+                job.total - job.pending_ids.len(),
+                job.total
+            );
+            return JsonRpcResponse::success(
+                id,
+                json!({
+                    "structuredContent": summary,
+                    "content": [{"type":"text","text": text}]
+                }),
+            );
+        }
+
+        let limit = args.limit.unwrap_or(20).clamp(1, MAX_FINISHED_JOBS);
+        let jobs = registry
+            .jobs
+            .iter()
+            .rev()
+            .filter(visible)
+            .take(limit)
+            .map(reembed_job_summary)
+            .collect::<Vec<_>>();
+        drop(registry);
+        let count = jobs.len();
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {"jobs": jobs, "count": count},
+                "content": [{"type":"text","text": format!("{count} jobs")}]
+            }),
+        )
+    }
+
+    fn exec_memory_compact(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryCompactInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let dry_run = args.dry_run.unwrap_or(true);
+        let action = if dry_run { ScopeAction::Read } else { ScopeAction::Admin };
+        if let Some(scope) = &args.scope
+            && let Err(denied) = self.scopes.check(scope, action)
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+        let limit = args.limit.unwrap_or(50_000).clamp(1, 200_000);
+
+        let locked = self.store.read();
+        let rows = locked.list(200_000);
+        drop(locked);
+        let filtered = filter_entries_for(
+            rows,
+            &self.scopes,
+            action,
+            args.scope.as_deref(),
+            args.category.as_deref(),
+        )
+        .into_iter()
+        .take(limit)
+        .collect::<Vec<_>>();
+
+        let mut keep_keys = HashSet::new();
+        let mut duplicate_ids = Vec::new();
+        for row in filtered {
+            let key = format!("{}|{}|{}", row.scope, row.category, compact_query(&row.text, 16));
+            if !keep_keys.insert(key) {
+                duplicate_ids.push(row.id);
+            }
+        }
+
+        let mut deleted = 0usize;
+        if !dry_run {
+            let mut locked = self.store.write();
+            for mid in &duplicate_ids {
+                if matches!(locked.forget_by_id(mid), Ok(true)) {
+                    deleted += 1;
+                }
+            }
+        }
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "dry_run": dry_run,
+                    "duplicates": duplicate_ids.len(),
+                    "deleted": deleted,
+                    "candidate_ids": duplicate_ids
+                },
+                "content": [{"type":"text","text": format!("compact {}: duplicates={}, deleted={}", if dry_run {"preview"} else {"apply"}, duplicate_ids.len(), deleted)}]
+            }),
+        )
+    }
+
+    fn exec_memory_duplicates(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryDuplicatesInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        if let Some(scope) = &args.scope
+            && let Err(denied) = self.scopes.check(scope, ScopeAction::Read)
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+        let (by_signature, by_embedding) = match args.method.as_deref() {
+            None | Some("both") => (true, true),
+            Some("signature") => (true, false),
+            Some("embedding") => (false, true),
+            Some(other) => {
+                return JsonRpcResponse::error(
+                    id,
+                    -32602,
+                    format!("method must be signature|embedding|both, got {other}"),
+                );
+            }
+        };
+        let threshold = args
+            .threshold
+            .unwrap_or(self.standards.governance.duplicate_score)
+            .clamp(0.0, 1.0);
+        let limit = args.limit.unwrap_or(5_000).clamp(1, 20_000);
+
+        let rows = self.store.read().list(200_000);
+        let scanned = filter_entries_for(
+            rows,
+            &self.scopes,
+            ScopeAction::Read,
+            args.scope.as_deref(),
+            args.category.as_deref(),
+        )
+        .into_iter()
+        .take(limit)
+        .collect::<Vec<_>>();
+        let clusters = duplicate_clusters(&scanned, by_signature, by_embedding.then_some(threshold));
+        let redundant = clusters.iter().map(|c| c.members.len() - 1).sum::<usize>();
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "scanned": scanned.len(),
+                    "method": args.method.as_deref().unwrap_or("both"),
+                    "threshold": threshold,
+                    "redundant": redundant,
+                    "clusters": clusters.iter().map(DuplicateCluster::to_json).collect::<Vec<_>>()
+                },
+                "content": [{"type":"text","text": format!("duplicates: scanned={}, clusters={}, redundant={}", scanned.len(), clusters.len(), redundant)}]
+            }),
+        )
+    }
+
+    fn exec_memory_lint(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryLintInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let dry_run = args.dry_run.unwrap_or(true);
+        let action = if dry_run { ScopeAction::Read } else { ScopeAction::Write };
+        if let Some(scope) = &args.scope
+            && let Err(denied) = self.scopes.check(scope, action)
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+        let limit = args.limit.unwrap_or(100).clamp(1, 1000);
+        let policy = &self.standards.governance;
+
+        let mut locked = self.store.write();
+        let rows = filter_entries_for(
+            locked.list(200_000),
+            &self.scopes,
+            action,
+            args.scope.as_deref(),
+            args.category.as_deref(),
+        );
+        let scanned = rows.len();
+        let mut by_rule: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut items = Vec::new();
+        let mut violating = 0usize;
+        let mut fixable = 0usize;
+        let mut fixed = Vec::new();
+        let mut failed = Vec::new();
+        for entry in rows {
+            let level = importance_level_from_numeric(entry.importance);
+            let violations = policy.violations(&entry.text, &entry.category, &entry.tags, level);
+            if violations.is_empty() {
+                continue;
+            }
+            violating += 1;
+            for (rule, _) in &violations {
+                *by_rule.entry(rule).or_insert(0) += 1;
+            }
+            // Missing tag dimensions are the one thing lint can repair: add the default tags.
+            let fixed_tags = violations
+                .iter()
+                .any(|(rule, _)| *rule == "tags")
+                .then(|| normalize_tags_with_defaults(entry.tags.clone(), None, None, None, &self.standards))
+                .filter(|tags| policy.check_tags(tags).is_ok());
+            if fixed_tags.is_some() {
+                fixable += 1;
+            }
+            if items.len() < limit {
+                items.push(json!({
+                    "id": entry.id,
+                    "scope": entry.scope,
+                    "category": entry.category,
+                    "violations": violations
+                        .iter()
+                        .map(|(rule, message)| json!({"rule": rule, "message": message}))
+                        .collect::<Vec<_>>(),
+                    "fixed_tags": fixed_tags
+                }));
+            }
+            if dry_run {
+                continue;
+            }
+            if let Some(tags) = fixed_tags {
+                match rewrite_entry(locked.as_mut(), &entry, &entry.scope, &entry.category, tags) {
+                    Ok(updated) => fixed.push(json!({"replaced_id": entry.id, "id": updated.id})),
+                    Err(err) => failed.push(json!({"id": entry.id, "error": err})),
+                }
+            }
+        }
+        drop(locked);
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "dry_run": dry_run,
+                    "scanned": scanned,
+                    "violating": violating,
+                    "fixable": fixable,
+                    "by_rule": by_rule,
+                    "items": items,
+                    "fixed": fixed,
+                    "failed": failed
+                },
+                "content": [{"type":"text","text": format!("lint {}: scanned={}, violating={}, fixable={}, fixed={}", if dry_run {"preview"} else {"apply"}, scanned, violating, fixable, fixed.len())}]
+            }),
+        )
     }
 
-    fn handle_resources_read(&self, id: Value, params: Value) -> JsonRpcResponse {
-        let parsed: ResourceReadParams = match serde_json::from_value(params) {
+    fn exec_memory_recategorize(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryRecategorizeInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let policy = &self.standards.governance;
+        let renames = args.renames.unwrap_or_else(|| policy.category_aliases.clone());
+        if renames.is_empty() {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                "no renames given and the governance policy has no category_aliases",
+            );
+        }
+        let mut targets = BTreeMap::new();
+        for (from, to) in &renames {
+            match policy.resolve_category(to) {
+                Ok(category) => targets.insert(from.trim().to_lowercase(), category),
+                Err(msg) => return JsonRpcResponse::error(id, -32602, format!("{from} -> {to}: {msg}")),
+            };
+        }
+        let dry_run = args.dry_run.unwrap_or(true);
+        let action = if dry_run { ScopeAction::Read } else { ScopeAction::Write };
+        if let Some(scope) = &args.scope
+            && let Err(denied) = self.scopes.check(scope, action)
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+
+        let mut locked = self.store.write();
+        let candidates = filter_entries_for(locked.list(200_000), &self.scopes, action, args.scope.as_deref(), None)
+            .into_iter()
+            .filter_map(|entry| {
+                let target = targets.get(&entry.category.to_lowercase())?;
+                (*target != entry.category).then(|| (entry, target.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut applied = Vec::new();
+        let mut failed = Vec::new();
+        for (entry, category) in &candidates {
+            *counts.entry(format!("{} -> {category}", entry.category)).or_insert(0) += 1;
+            if dry_run {
+                continue;
+            }
+            match rewrite_entry(locked.as_mut(), entry, &entry.scope, category, entry.tags.clone()) {
+                Ok(updated) => applied.push(json!({"replaced_id": entry.id, "id": updated.id})),
+                Err(err) => failed.push(json!({"id": entry.id, "error": err})),
+            }
+        }
+        drop(locked);
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "dry_run": dry_run,
+                    "matched": candidates.len(),
+                    "renames": counts,
+                    "renamed": applied,
+                    "failed": failed,
+                    "candidate_ids": candidates.iter().map(|(e, _)| e.id.clone()).collect::<Vec<_>>()
+                },
+                "content": [{"type":"text","text": format!("recategorize {}: matched={}, renamed={}", if dry_run {"preview"} else {"apply"}, candidates.len(), applied.len())}]
+            }),
+        )
+    }
+
+    fn exec_memory_retag(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryRetagInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let canonical = |tags: Vec<String>| {
+            tags.iter()
+                .map(|t| canonicalize_tag(t))
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+        };
+        let add = canonical(args.add.unwrap_or_default());
+        let remove = canonical(args.remove.unwrap_or_default())
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let mut rename = BTreeMap::new();
+        for (from, to) in args.rename.unwrap_or_default() {
+            let (from, to) = (canonicalize_tag(&from), canonicalize_tag(&to));
+            if from.is_empty() || to.is_empty() {
+                return JsonRpcResponse::error(id, -32602, "rename tags cannot be empty");
+            }
+            if from != to {
+                rename.insert(from, to);
+            }
+        }
+        if add.is_empty() && remove.is_empty() && rename.is_empty() {
+            return JsonRpcResponse::error(id, -32602, "add, remove or rename is required");
+        }
+        if add
+            .iter()
+            .chain(&remove)
+            .chain(rename.keys())
+            .chain(rename.values())
+            .any(|t| t == PENDING_REVIEW_TAG)
+        {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("{PENDING_REVIEW_TAG} is managed by memory_approve and memory_reject"),
+            );
+        }
+        let incoming = add.iter().chain(rename.values()).cloned().collect::<Vec<_>>();
+        if let Err(msg) = self.standards.governance.check_taxonomy(&incoming) {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        let filter_tag = args.tag.as_deref().map(canonicalize_tag);
+        let dry_run = args.dry_run.unwrap_or(true);
+        let action = if dry_run { ScopeAction::Read } else { ScopeAction::Write };
+        if let Some(scope) = &args.scope
+            && let Err(denied) = self.scopes.check(scope, action)
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+
+        let mut locked = self.store.write();
+        let candidates = filter_entries_for(
+            locked.list(200_000),
+            &self.scopes,
+            action,
+            args.scope.as_deref(),
+            args.category.as_deref(),
+        )
+        .into_iter()
+        .filter(|entry| filter_tag.as_ref().is_none_or(|tag| entry.tags.contains(tag)))
+        .filter_map(|entry| {
+            let mut tags = Vec::with_capacity(entry.tags.len() + add.len());
+            for tag in entry.tags.iter().chain(&add) {
+                if remove.contains(tag) {
+                    continue;
+                }
+                let tag = rename.get(tag).unwrap_or(tag);
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+            (tags != entry.tags).then_some((entry, tags))
+        })
+        .collect::<Vec<_>>();
+
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut applied = Vec::new();
+        let mut failed = Vec::new();
+        let mut changes = Vec::with_capacity(candidates.len());
+        for (entry, tags) in &candidates {
+            let added = tags.iter().filter(|t| !entry.tags.contains(t)).collect::<Vec<_>>();
+            let removed = entry.tags.iter().filter(|t| !tags.contains(t)).collect::<Vec<_>>();
+            for tag in &entry.tags {
+                match rename.get(tag) {
+                    Some(to) if !remove.contains(tag) => *counts.entry(format!("{tag} -> {to}")).or_insert(0) += 1,
+                    _ if removed.contains(&tag) => *counts.entry(format!("-{tag}")).or_insert(0) += 1,
+                    _ => {}
+                }
+            }
+            for tag in add.iter().filter(|t| added.contains(t)) {
+                *counts.entry(format!("+{tag}")).or_insert(0) += 1;
+            }
+            changes.push(json!({"id": entry.id, "added": added, "removed": removed}));
+            if dry_run {
+                continue;
+            }
+            match rewrite_entry(locked.as_mut(), entry, &entry.scope, &entry.category, tags.clone()) {
+                Ok(updated) => applied.push(json!({"replaced_id": entry.id, "id": updated.id})),
+                Err(err) => failed.push(json!({"id": entry.id, "error": err})),
+            }
+        }
+        drop(locked);
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "dry_run": dry_run,
+                    "matched": candidates.len(),
+                    "counts": counts,
+                    "changes": changes,
+                    "retagged": applied,
+                    "failed": failed
+                },
+                "content": [{"type":"text","text": format!("retag {}: matched={}, retagged={}", if dry_run {"preview"} else {"apply"}, candidates.len(), applied.len())}]
+            }),
+        )
+    }
+
+    fn exec_memory_move_scope(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryMoveScopeInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let (from, to) = (args.from.trim(), args.to.trim());
+        if from == to {
+            return JsonRpcResponse::error(id, -32602, "from and to must be different scopes");
+        }
+        let dry_run = args.dry_run.unwrap_or(true);
+        let checks = if dry_run {
+            [(from, ScopeAction::Read), (to, ScopeAction::Read)]
+        } else {
+            [(from, ScopeAction::Delete), (to, ScopeAction::Write)]
+        };
+        for (scope, action) in checks {
+            if let Err(denied) = self.scopes.check(scope, action) {
+                return JsonRpcResponse::error(id, -32602, denied);
+            }
+        }
+
+        let mut locked = self.store.write();
+        let candidates = locked
+            .list(200_000)
+            .into_iter()
+            .filter(|e| e.scope == from)
+            .filter(|e| {
+                args.category
+                    .as_deref()
+                    .is_none_or(|c| e.category.eq_ignore_ascii_case(c))
+            })
+            .collect::<Vec<_>>();
+        if !dry_run {
+            let adding = candidates
+                .iter()
+                .fold(QuotaUsage::default(), |acc, e| acc.plus(QuotaUsage::of_text(&e.text)));
+            if let Err(exceeded) = self.check_store_quota(locked.as_ref(), to, adding) {
+                return exceeded.response(id);
+            }
+        }
+
+        let mut moved = Vec::new();
+        let mut failed = Vec::new();
+        for entry in &candidates {
+            if let Some(denied) = self.scopes.validate_scope_write(to, &entry.tags) {
+                failed.push(json!({"id": entry.id, "error": denied}));
+                continue;
+            }
+            if dry_run {
+                continue;
+            }
+            match rewrite_entry(locked.as_mut(), entry, to, &entry.category, entry.tags.clone()) {
+                Ok(updated) => moved.push(json!({"replaced_id": entry.id, "id": updated.id})),
+                Err(err) => failed.push(json!({"id": entry.id, "error": err})),
+            }
+        }
+        drop(locked);
+        if !dry_run {
+            tracing::info!(
+                agent = %self.scopes.agent_id(),
+                from,
+                to,
+                moved = moved.len(),
+                failed = failed.len(),
+                "moved memories between scopes"
+            );
+        }
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "dry_run": dry_run,
+                    "from": from,
+                    "to": to,
+                    "matched": candidates.len(),
+                    "moved": moved,
+                    "failed": failed,
+                    "candidate_ids": candidates.iter().map(|e| e.id.clone()).collect::<Vec<_>>()
+                },
+                "content": [{"type":"text","text": format!("move {from} -> {to} {}: matched={}, moved={}", if dry_run {"preview"} else {"apply"}, candidates.len(), moved.len())}]
+            }),
+        )
+    }
+
+    fn exec_memory_merge(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryMergeInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let mut ids = Vec::with_capacity(args.ids.len());
+        for mid in args.ids {
+            if !ids.contains(&mid) {
+                ids.push(mid);
+            }
+        }
+        if ids.len() < 2 || ids.len() > 50 {
+            return JsonRpcResponse::error(id, -32602, "memory_merge requires between 2 and 50 distinct ids");
+        }
+        let governed = args
+            .governed
+            .unwrap_or_else(|| self.standards.default_governed_for_update());
+
+        let rows = self.store.read().list(200_000);
+        let mut sources = Vec::with_capacity(ids.len());
+        for mid in &ids {
+            let Some(entry) = rows.iter().find(|e| &e.id == mid) else {
+                return JsonRpcResponse::error(id, -32602, format!("memory id not found: {mid}"));
+            };
+            if !self.scopes.permits(&entry.scope, ScopeAction::Delete) {
+                return JsonRpcResponse::error(id, -32602, format!("scope access denied for memory {mid}"));
+            }
+            sources.push(entry.clone());
+        }
+        drop(rows);
+
+        let scope = if let Some(v) = args.scope {
+            v
+        } else {
+            let first = sources.first().map(|e| e.scope.clone()).unwrap_or_default();
+            if sources.iter().any(|e| e.scope != first) {
+                return JsonRpcResponse::error(
+                    id,
+                    -32602,
+                    "source memories span several scopes; pass scope explicitly",
+                );
+            }
+            first
+        };
+        let category = args
+            .category
+            .or_else(|| sources.first().map(|e| e.category.clone()))
+            .unwrap_or_else(|| "other".to_string());
+        let category = match self.standards.governance.resolve_category(&category) {
+            Ok(v) => v,
+            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
+        };
+        let mut text = match args.text {
+            Some(v) if !v.trim().is_empty() => v,
+            _ => {
+                let mut seen = HashSet::new();
+                sources
+                    .iter()
+                    .map(|e| e.text.trim())
+                    .filter(|t| !t.is_empty() && seen.insert(t.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+        };
+        let mut tags = normalize_tags_with_defaults(
+            sources.iter().flat_map(|e| e.tags.iter().cloned()).collect(),
+            None,
+            None,
+            None,
+            &self.standards,
+        );
+        let importance = sources.iter().map(|e| e.importance).fold(0.0_f32, f32::max);
+
+        if let Err(denied) = self.scopes.check(&scope, ScopeAction::Write) {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+        // The merged entry is a new write, so it goes through the same checks as `memory_store`.
+        if let Err(msg) = apply_redaction(self.standards.redaction_for(governed), &mut text, &mut tags) {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        if let Some(msg) = self.scopes.validate_scope_write(&scope, &tags) {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        if governed {
+            let level = importance_level_from_numeric(importance);
+            if let Err(msg) = self.standards.governance.validate(&text, &category, &tags, level) {
+                return JsonRpcResponse::error(id, -32602, msg);
+            }
+        }
+
+        let (embedding, embedding_model) =
+            if args.use_vector.unwrap_or(false) || sources.iter().any(|e| e.embedding.is_some()) {
+                match embed_one(
+                    &self.runtime,
+                    &self.embed,
+                    &CallContext::default(),
+                    &text,
+                    EmbeddingTask::Passage,
+                ) {
+                    Ok(v) => split_embedded(Some(v)),
+                    Err(msg) if args.use_vector.unwrap_or(false) => return JsonRpcResponse::error(id, -32002, msg),
+                    Err(_) => (None, None),
+                }
+            } else {
+                (None, None)
+            };
+
+        // The provider call ran without the lock; a source edited or removed since then aborts the merge.
+        let mut locked = self.store.write();
+        let current = locked.list(200_000);
+        if let Some(changed) = sources.iter().find(|source| !current.iter().any(|e| e == *source)) {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("memory {} changed during the merge; retry", changed.id),
+            );
+        }
+        drop(current);
+        if let Err(exceeded) = self.check_store_quota(locked.as_ref(), &scope, QuotaUsage::of_text(&text)) {
+            return exceeded.response(id);
+        }
+        let merged = match locked.store(NewMemoryEntry {
+            keywords: stored_keywords(&self.standards.governance, &text),
+            text,
+            category,
+            scope,
+            importance,
+            tags,
+            embedding,
+            embedding_model,
+            source: None,
+        }) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+        };
+
+        // The originals are archived, not deleted, so `memory_restore` can bring them back.
+        let retired = move_to_archive(
+            &self.decay,
+            locked.as_mut(),
+            sources,
+            Vec::new(),
+            Some(&merged.id),
+            now_ms(),
+        )
+        .and_then(|moved| {
+            let kept = ids
+                .iter()
+                .filter(|mid| !moved.contains(mid))
+                .cloned()
+                .collect::<Vec<_>>();
+            if kept.is_empty() {
+                Ok(moved)
+            } else {
+                Err(format!("could not retire {}", kept.join(", ")))
+            }
+        });
+        let removed = match retired {
             Ok(v) => v,
             Err(err) => {
-                return JsonRpcResponse::error(id, -32602, format!("invalid params: {err}"));
+                if let Err(rollback) = locked.forget_by_id(&merged.id) {
+                    tracing::warn!(id = %merged.id, error = %rollback, "failed to remove merged entry after a failed merge");
+                }
+                return JsonRpcResponse::error(id, -32001, format!("memory_merge failed: {err}"));
+            }
+        };
+        drop(locked);
+        self.record_agent_usage(UsageOp::Store, merged.text.len());
+
+        let mut merged_clean = merged;
+        merged_clean.embedding = None;
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "entry": merged_clean,
+                    "merged_ids": removed,
+                    "archived": true
+                },
+                "content": [{"type":"text","text": format!("merged {} memories into {}; originals archived", removed.len(), merged_clean.id)}]
+            }),
+        )
+    }
+
+    fn exec_memory_summarize(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemorySummarizeInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let scope = args.scope.unwrap_or_else(|| self.scopes.default_scope());
+        let dry_run = args.dry_run.unwrap_or(false);
+        // Applying replaces the batch with its summary, so it needs write and delete.
+        let needed: &[ScopeAction] = if dry_run {
+            &[ScopeAction::Read]
+        } else {
+            &[ScopeAction::Write, ScopeAction::Delete]
+        };
+        if let Some(denied) = needed
+            .iter()
+            .find_map(|action| self.scopes.check(&scope, *action).err())
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+        let batch_size = args.batch_size.unwrap_or(20).clamp(2, 100);
+
+        let mut batch = {
+            let locked = self.store.read();
+            filter_entries_by_acl(
+                locked.list(200_000),
+                &self.scopes,
+                Some(&scope),
+                args.category.as_deref(),
+            )
+        };
+        batch.sort_by(|a, b| a.timestamp_ms.cmp(&b.timestamp_ms).then_with(|| a.id.cmp(&b.id)));
+        batch.truncate(batch_size);
+        let candidate_ids = batch.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+
+        if batch.len() < 2 || dry_run {
+            let text = if batch.len() < 2 {
+                format!("not enough memories to summarize in scope {scope}")
+            } else {
+                format!("dry run: would summarize {} memories", batch.len())
+            };
+            return JsonRpcResponse::success(
+                id,
+                json!({
+                    "structuredContent": {
+                        "dry_run": dry_run,
+                        "scope": scope,
+                        "candidate_ids": candidate_ids,
+                        "summarized_ids": [],
+                        "entry": Value::Null
+                    },
+                    "content": [{"type":"text","text": text}]
+                }),
+            );
+        }
+
+        let provider = match build_summarize_provider_from_env() {
+            Ok(v) => v,
+            Err(msg) => return JsonRpcResponse::error(id, -32002, msg),
+        };
+        let span = tracing::info_span!(
+            "provider",
+            kind = "summarize",
+            provider = provider.name(),
+            inputs = batch.len()
+        )
+        .entered();
+        let output = match self.runtime.block_on(async {
+            provider
+                .summarize(SummarizeRequest {
+                    documents: batch.iter().map(|e| e.text.clone()).collect(),
+                    instruction: args.instruction.clone(),
+                    max_chars: args.max_chars,
+                })
+                .await
+        }) {
+            Ok(v) => v,
+            Err(e) => {
+                let msg = format!("summarization failed: {}", provider_error_en_summarize(&e));
+                tracing::warn!(error = %msg, "summarize provider call failed");
+                return JsonRpcResponse::error(id, -32002, msg);
             }
         };
+        drop(span);
 
-        let rendered = render_template_resource(&parsed.uri, &self.standards).or_else(|| {
-            skill_resource_text(&parsed.uri).map(|text| RenderedResource {
-                mime_type: "text/markdown",
-                text: text.to_string(),
-            })
+        let category = args.category.unwrap_or_else(|| {
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for e in &batch {
+                *counts.entry(e.category.as_str()).or_insert(0) += 1;
+            }
+            batch
+                .iter()
+                .max_by_key(|e| counts.get(e.category.as_str()).copied().unwrap_or(0))
+                .map_or_else(|| "other".to_string(), |e| e.category.clone())
         });
-        let Some(rendered) = rendered else {
-            return JsonRpcResponse::error(id, -32602, "unknown resource uri");
+        let category = match self.standards.governance.resolve_category(&category) {
+            Ok(v) => v,
+            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
+        };
+        let mut tags = normalize_tags_with_defaults(
+            batch.iter().flat_map(|e| e.tags.iter().cloned()).collect(),
+            None,
+            None,
+            None,
+            &self.standards,
+        );
+        let importance = batch.iter().map(|e| e.importance).fold(0.0_f32, f32::max);
+        // The summary is new text from a provider, so it goes through the same checks as a write.
+        let governed = args
+            .governed
+            .unwrap_or_else(|| self.standards.default_governed_for_update());
+        let mut text = output.summary;
+        if let Err(msg) = apply_redaction(self.standards.redaction_for(governed), &mut text, &mut tags) {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        if let Some(msg) = self.scopes.validate_scope_write(&scope, &tags) {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        if governed {
+            let level = importance_level_from_numeric(importance);
+            if let Err(msg) = self.standards.governance.validate(&text, &category, &tags, level) {
+                return JsonRpcResponse::error(id, -32602, msg);
+            }
+        }
+        let (embedding, embedding_model) = if batch.iter().any(|e| e.embedding.is_some()) {
+            split_embedded(
+                embed_one(
+                    &self.runtime,
+                    &self.embed,
+                    &CallContext::default(),
+                    &text,
+                    EmbeddingTask::Passage,
+                )
+                .ok(),
+            )
+        } else {
+            (None, None)
         };
 
+        let mut locked = self.store.write();
+        let summary = match locked.store(NewMemoryEntry {
+            keywords: stored_keywords(&self.standards.governance, &text),
+            text,
+            category,
+            scope: scope.clone(),
+            importance,
+            tags,
+            embedding,
+            embedding_model,
+            source: None,
+        }) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+        };
+
+        let mut summarized = Vec::with_capacity(candidate_ids.len());
+        let mut changed = Vec::new();
+        let mut errors = Vec::new();
+        if !args.keep_originals.unwrap_or(false) {
+            // The provider call ran without the lock; originals edited or removed since then are kept.
+            let current = locked
+                .list(200_000)
+                .into_iter()
+                .map(|e| (e.id.clone(), e))
+                .collect::<HashMap<_, _>>();
+            for original in &batch {
+                if current.get(&original.id) != Some(original) {
+                    changed.push(original.id.clone());
+                    continue;
+                }
+                match locked.forget_by_id(&original.id) {
+                    Ok(true) => summarized.push(original.id.clone()),
+                    Ok(false) => changed.push(original.id.clone()),
+                    Err(err) => errors.push(format!("{}: {err}", original.id)),
+                }
+            }
+        }
+        drop(locked);
+        self.record_agent_usage(UsageOp::Store, summary.text.len());
+
+        let mut summary_clean = summary;
+        summary_clean.embedding = None;
         JsonRpcResponse::success(
             id,
             json!({
-                "contents": [{
-                    "uri": parsed.uri,
-                    "mimeType": rendered.mime_type,
-                    "text": rendered.text
-                }]
+                "structuredContent": {
+                    "dry_run": false,
+                    "scope": scope,
+                    "candidate_ids": candidate_ids,
+                    "summarized_ids": summarized,
+                    "entry": summary_clean,
+                    "provider": output.provider,
+                    "model": output.model,
+                    "usage_tokens": output.usage_tokens,
+                    "changed_ids": changed,
+                    "errors": errors
+                },
+                "content": [{"type":"text","text": format!("summarized {} memories into {}", candidate_ids.len(), summary_clean.id)}]
             }),
         )
     }
 
-    fn handle_tools_call(&self, id: Value, params: Value) -> JsonRpcResponse {
-        let parsed: ToolsCallParams = match serde_json::from_value(params) {
+    fn exec_memory_entities(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryEntitiesInput = match parse_args_optional(arguments) {
             Ok(v) => v,
-            Err(err) => {
-                return JsonRpcResponse::error(id, -32602, format!("invalid params: {err}"));
+            Err(resp) => return with_id(resp, id),
+        };
+        if let Some(scope) = &args.scope {
+            if !self.scopes.can_access_scope(scope) {
+                return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
             }
+        }
+        let kind = match args.kind.as_deref() {
+            Some(raw) => match EntityKind::parse(raw) {
+                Some(v) => Some(v),
+                None => return JsonRpcResponse::error(id, -32602, "kind must be person|project|tool"),
+            },
+            None => None,
         };
-
-        let start = Instant::now();
-        let tool = parsed.name.clone();
-        let response = match parsed.name.as_str() {
-            "memory_store" => self.exec_memory_store(id, parsed.arguments),
-            "memory_recall" => self.exec_memory_recall(id, parsed.arguments),
-            "memory_stats" => self.exec_memory_stats(id, parsed.arguments),
-            "memory_list" => self.exec_memory_list(id, parsed.arguments),
-            "memory_update" => self.exec_memory_update(id, parsed.arguments),
-            "memory_store_dual" => self.exec_memory_store_dual(id, parsed.arguments),
-            "memory_export" => self.exec_memory_export(id, parsed.arguments),
-            "memory_import" => self.exec_memory_import(id, parsed.arguments),
-            "memory_migrate" => self.exec_memory_migrate(id, parsed.arguments),
-            "memory_reembed" => self.exec_memory_reembed(id, parsed.arguments),
-            "memory_compact" => self.exec_memory_compact(id, parsed.arguments),
-            "memory_forget" => self.exec_memory_forget(id, parsed.arguments),
-            "memory_evolve" => self.exec_memory_evolve(id, parsed.arguments),
-            "memory_skill_manifest" => self.exec_memory_skill_manifest(id, parsed.arguments),
-            _ => JsonRpcResponse::error(id, -32601, "unknown tool"),
+        let name = args
+            .name
+            .as_deref()
+            .map(prx_memory_core::normalize_entity_name)
+            .filter(|v| !v.is_empty());
+        let limit = args.limit.unwrap_or(20).clamp(1, 200);
+
+        let rows = {
+            let locked = self.store.read();
+            filter_entries_by_acl(locked.list(200_000), &self.scopes, args.scope.as_deref(), None)
         };
-        self.record_tool_metrics(&tool, start.elapsed().as_secs_f64() * 1000.0, response.error.is_some());
-        response
+
+        let mut grouped: HashMap<(String, String), EntityAggregate> = HashMap::new();
+        for entry in rows.iter().filter(|e| e.category == "entity") {
+            let Some((entry_kind, entry_name, source_id)) = parse_entity_tags(&entry.tags) else {
+                continue;
+            };
+            if kind.is_some_and(|k| k.as_str() != entry_kind) || name.as_ref().is_some_and(|n| n != &entry_name) {
+                continue;
+            }
+            let agg = grouped.entry((entry_kind, entry_name)).or_default();
+            agg.last_seen_ms = agg.last_seen_ms.max(entry.timestamp_ms);
+            if !agg.source_ids.contains(&source_id) {
+                agg.source_ids.push(source_id);
+            }
+        }
+
+        let mut entities = grouped.into_iter().collect::<Vec<_>>();
+        entities.sort_by(|a, b| {
+            b.1.source_ids
+                .len()
+                .cmp(&a.1.source_ids.len())
+                .then_with(|| b.1.last_seen_ms.cmp(&a.1.last_seen_ms))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        entities.truncate(limit);
+
+        let mut sources = Vec::new();
+        if name.is_some() {
+            let wanted = entities
+                .iter()
+                .flat_map(|(_, agg)| agg.source_ids.iter())
+                .collect::<HashSet<_>>();
+            sources = rows
+                .iter()
+                .filter(|e| wanted.contains(&e.id))
+                .cloned()
+                .map(|mut e| {
+                    e.embedding = None;
+                    e
+                })
+                .collect::<Vec<_>>();
+            sources.sort_by(|a, b| b.timestamp_ms.cmp(&a.timestamp_ms));
+            sources.truncate(limit);
+        }
+
+        let items = entities
+            .into_iter()
+            .map(|((entry_kind, entry_name), agg)| {
+                json!({
+                    "kind": entry_kind,
+                    "name": entry_name,
+                    "mentions": agg.source_ids.len(),
+                    "last_seen_ms": agg.last_seen_ms,
+                    "source_ids": agg.source_ids
+                })
+            })
+            .collect::<Vec<_>>();
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "count": items.len(),
+                    "entities": items,
+                    "sources": sources
+                },
+                "content": [{"type":"text","text": format!("found {} entities", items.len())}]
+            }),
+        )
     }
 
-    fn exec_memory_store(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryStoreInput = match parse_args(arguments) {
+    fn exec_memory_link(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryLinkInput = match parse_args(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
-        let governed = args
-            .governed
-            .unwrap_or_else(|| self.standards.default_governed_for_store());
-        if governed && enforce_dual_layer() {
+        let relation = args.relation.trim().to_ascii_lowercase();
+        if !RELATION_TYPES.contains(&relation.as_str()) {
             return JsonRpcResponse::error(
                 id,
                 -32602,
-                "governed single-layer writes are disabled; use memory_store_dual",
+                format!("relation must be one of {}", RELATION_TYPES.join("|")),
             );
         }
-        let category = args
-            .category
-            .unwrap_or_else(|| infer_default_category(&args.text).to_string());
-        let tags = normalize_tags_with_defaults(
-            args.tags.unwrap_or_default(),
-            args.project_tag.as_deref(),
-            args.tool_tag.as_deref(),
-            args.domain_tag.as_deref(),
-            &self.standards,
-        );
-        let target_scope = args.scope.unwrap_or_else(|| self.scopes.default_scope());
-        let (importance, importance_level) = match resolve_importance(args.importance_level.as_deref(), args.importance)
-        {
+
+        let mut locked = self.store.write();
+        if let Err(msg) = self.check_relation_endpoints(locked.as_ref(), &args.from_id, &args.to_id) {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        let created = match locked.link(&args.from_id, &relation, &args.to_id) {
             Ok(v) => v,
-            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
+            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
         };
+        drop(locked);
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "created": created,
+                    "from_id": args.from_id,
+                    "relation": relation,
+                    "to_id": args.to_id
+                },
+                "content": [{"type":"text","text": if created {"linked"} else {"already linked"}}]
+            }),
+        )
+    }
+
+    fn exec_memory_unlink(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryUnlinkInput = match parse_args(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        let relation = args
+            .relation
+            .map(|r| r.trim().to_ascii_lowercase())
+            .filter(|r| !r.is_empty());
+
+        let mut locked = self.store.write();
+        if let Err(msg) = self.check_relation_endpoints(locked.as_ref(), &args.from_id, &args.to_id) {
+            return JsonRpcResponse::error(id, -32602, msg);
+        }
+        let removed = match locked.unlink(&args.from_id, relation.as_deref(), &args.to_id) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+        };
+        drop(locked);
+
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "removed": removed,
+                    "from_id": args.from_id,
+                    "relation": relation,
+                    "to_id": args.to_id
+                },
+                "content": [{"type":"text","text": format!("removed {removed} relations")}]
+            }),
+        )
+    }
+
+    fn check_relation_endpoints(&self, store: &dyn StorageBackend, from_id: &str, to_id: &str) -> Result<(), String> {
+        let rows = store.list(200_000);
+        for mid in [from_id, to_id] {
+            let Some(entry) = rows.iter().find(|e| e.id == mid) else {
+                return Err(format!("memory id not found: {mid}"));
+            };
+            if !self.scopes.permits(&entry.scope, ScopeAction::Write) {
+                return Err(format!("scope access denied for memory {mid}"));
+            }
+        }
+        Ok(())
+    }
+
+    fn import_entries(&self, entries: Vec<ImportedMemoryEntry>, options: ImportOptions) -> ImportSummary {
+        let mut created = 0usize;
+        let mut skipped = 0usize;
+        let mut failed = 0usize;
+        let mut errors = Vec::new();
+        let mut planned = Vec::new();
+
+        for (idx, raw) in entries.into_iter().enumerate() {
+            let scope = raw.scope.unwrap_or_else(|| self.scopes.default_scope());
+            let category = match self
+                .standards
+                .governance
+                .resolve_category(raw.category.as_deref().unwrap_or("other"))
+            {
+                Ok(v) => v,
+                Err(msg) => {
+                    failed += 1;
+                    errors.push(format!("entry#{idx}: {msg}"));
+                    continue;
+                }
+            };
+            let tags = normalize_tags_with_defaults(
+                raw.tags.unwrap_or_default(),
+                raw.project_tag.as_deref(),
+                raw.tool_tag.as_deref(),
+                raw.domain_tag.as_deref(),
+                &self.standards,
+            );
+            let (importance, importance_level) =
+                match resolve_importance(raw.importance_level.as_deref(), raw.importance) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        failed += 1;
+                        errors.push(format!("entry#{idx}: {err}"));
+                        continue;
+                    }
+                };
+
+            if let Err(denied) = self.scopes.check(&scope, ScopeAction::Write) {
+                failed += 1;
+                errors.push(format!("entry#{idx}: {denied}"));
+                continue;
+            }
+            if let Some(msg) = self.scopes.validate_scope_write(&scope, &tags) {
+                failed += 1;
+                errors.push(format!("entry#{idx}: {msg}"));
+                continue;
+            }
+            if options.governed {
+                if let Err(msg) = self
+                    .standards
+                    .governance
+                    .validate(&raw.text, &category, &tags, importance_level)
+                {
+                    failed += 1;
+                    errors.push(format!("entry#{idx}: {msg}"));
+                    continue;
+                }
+            }
+
+            let (embedding, embedding_model) = if let Some(v) = raw.embedding {
+                (Some(v), raw.embedding_model)
+            } else if options.use_vector && !options.dry_run {
+                match embed_one(
+                    &self.runtime,
+                    &self.embed,
+                    &CallContext::default(),
+                    &raw.text,
+                    EmbeddingTask::Passage,
+                ) {
+                    Ok(v) => split_embedded(Some(v)),
+                    Err(err) => {
+                        failed += 1;
+                        errors.push(format!("entry#{idx}: {}", err));
+                        continue;
+                    }
+                }
+            } else {
+                (None, None)
+            };
+
+            let mut locked = self.store.write();
+            if options.skip_duplicates {
+                let similar = locked.recall(RecallQuery {
+                    query: compact_query(&raw.text, 10),
+                    query_embedding: None,
+                    scope: Some(scope.clone()),
+                    category: Some(category.clone()),
+                    limit: 1,
+                    vector_weight: None,
+                    lexical_weight: None,
+                    diversity: None,
+                    fusion: None,
+                });
+                if similar
+                    .first()
+                    .is_some_and(|r| r.score > self.standards.governance.duplicate_score)
+                {
+                    skipped += 1;
+                    continue;
+                }
+            }
+            if let Err(exceeded) = self.check_store_quota(locked.as_ref(), &scope, QuotaUsage::of_text(&raw.text)) {
+                failed += 1;
+                errors.push(format!("entry#{idx}: {}", exceeded.message()));
+                continue;
+            }
+            if options.dry_run {
+                created += 1;
+                planned.push(json!({"entry": idx, "scope": scope, "category": category, "tags": tags}));
+                continue;
+            }
+
+            match locked.store(NewMemoryEntry {
+                keywords: stored_keywords(&self.standards.governance, &raw.text),
+                text: raw.text,
+                category,
+                scope,
+                importance,
+                tags,
+                embedding,
+                embedding_model,
+                source: raw.source.and_then(MemorySource::normalized),
+            }) {
+                Ok(entry) => {
+                    self.record_agent_usage(UsageOp::Store, entry.text.len());
+                    created += 1;
+                }
+                Err(err) => {
+                    failed += 1;
+                    errors.push(format!("entry#{idx}: {}", err));
+                }
+            }
+        }
 
-        let mut locked = self.store.lock();
+        ImportSummary {
+            created,
+            skipped,
+            failed,
+            errors,
+            planned,
+        }
+    }
 
-        let outcome = match store_layer_with_rules(
-            &self.scopes,
-            &self.auto_store_counter,
-            locked.as_mut(),
-            StoreLayerRequest {
-                text: args.text,
-                category,
-                scope: target_scope.clone(),
-                importance,
-                importance_level,
-                tags,
-                governed,
-                use_vector: args.use_vector.unwrap_or(false),
-                enforce_verify: false,
-                allow_auto_maintenance: true,
-            },
-        ) {
+    fn exec_memory_stats(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryStatsInput = match parse_args_optional(arguments) {
             Ok(v) => v,
-            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
+            Err(resp) => return with_id(resp, id),
         };
-        let mut entry = outcome.entry;
-        entry.embedding = None;
-        let mut structured_content = match serde_json::to_value(&entry) {
-            Ok(v) => v,
-            Err(_) => json!({}),
+
+        if let Some(scope) = &args.scope {
+            if !self.scopes.can_access_scope(scope) {
+                return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
+            }
+        }
+
+        let bucket_days = match args.bucket.as_deref() {
+            None | Some("day") => 1,
+            Some("week") => 7,
+            Some(other) => return JsonRpcResponse::error(id, -32602, format!("bucket must be day|week, got {other}")),
         };
-        if let Some(obj) = structured_content.as_object_mut() {
-            obj.insert("auto_maintenance".to_string(), json!(outcome.auto_maintenance));
+        let periods = args
+            .periods
+            .unwrap_or(if bucket_days == 7 { 12 } else { 30 })
+            .clamp(1, 366);
+
+        let locked = self.store.read();
+
+        let backend_stats = locked.stats();
+        let rows = locked.list(200_000);
+        drop(locked);
+        let filtered = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), None);
+
+        let mut scope_counts: HashMap<String, usize> = HashMap::new();
+        let mut category_counts: HashMap<String, usize> = HashMap::new();
+        for entry in &filtered {
+            *scope_counts.entry(entry.scope.clone()).or_insert(0) += 1;
+            *category_counts.entry(entry.category.clone()).or_insert(0) += 1;
         }
+        let decision_ratio = if filtered.is_empty() {
+            0.0
+        } else {
+            (*category_counts.get("decision").unwrap_or(&0) as f32) / (filtered.len() as f32)
+        };
+        let trend = args
+            .trend
+            .unwrap_or(false)
+            .then(|| stats_trend(&filtered, bucket_days, periods, now_ms()));
+        let tags = stats_tags(
+            &filtered,
+            &self.standards.governance,
+            args.top_tags.unwrap_or(20).clamp(1, 200),
+        );
 
         JsonRpcResponse::success(
             id,
             json!({
-                "structuredContent": structured_content,
-                "content": [{"type":"text", "text": format!("stored {}", entry.id)}],
-                "governance": {
-                    "governed": governed,
-                    "importance_level": importance_level
-                }
+                "structuredContent": {
+                    "count": filtered.len(),
+                    "trend": trend,
+                    "decision_ratio": decision_ratio,
+                    "scope_counts": scope_counts,
+                    "category_counts": category_counts,
+                    "tags": tags,
+                    "agent_id": self.scopes.agent_id(),
+                    "allowed_scopes": self.scopes.accessible_scopes(),
+                    "scope_actions": self.scopes.scope_actions_json(),
+                    "standardization": {
+                        "profile": self.standards.profile_label(),
+                        "default_tags": {
+                            "project": self.standards.default_project_tag.clone(),
+                            "tool": self.standards.default_tool_tag.clone(),
+                            "domain": self.standards.default_domain_tag.clone()
+                        },
+                        "redaction": {
+                            "ungoverned": self.standards.redaction.label(),
+                            "governed": self.standards.governed_redaction.label()
+                        },
+                        "governance": &self.standards.governance
+                    },
+                    "backend_stats": backend_stats,
+                    "follower": self.follower.as_ref().map(|f| f.status_json()),
+                    "secondary_stores": self.secondary.as_ref().map(SecondaryStores::status_json),
+                    "team_sync": self.team_sync.as_ref().map(|s| s.status_json())
+                },
+                "content": [{
+                    "type":"text",
+                    "text": format!("stats count={}, decision_ratio={:.3}", filtered.len(), decision_ratio)
+                }]
             }),
         )
     }
 
-    fn exec_memory_store_dual(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryStoreDualInput = match parse_args(arguments) {
+    fn exec_memory_quota_status(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryQuotaStatusInput = match parse_args_optional(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
-        let governed = args.governed.unwrap_or(true);
-        let use_vector = args.use_vector.unwrap_or(false);
-        let include_principle = args.include_principle.unwrap_or(true);
-        if governed && !include_principle {
-            return JsonRpcResponse::error(id, -32602, "governed dual-layer writes require include_principle=true");
-        }
-        let scope = args.scope.unwrap_or_else(|| self.scopes.default_scope());
-        let tags = normalize_tags_with_defaults(
-            args.tags.unwrap_or_default(),
-            args.project_tag.as_deref(),
-            args.tool_tag.as_deref(),
-            args.domain_tag.as_deref(),
-            &self.standards,
-        );
-
-        let (tech_importance, tech_level) = match resolve_importance(args.tech_importance_level.as_deref(), Some(0.75))
+        if let Some(scope) = &args.scope
+            && !self.scopes.can_access_scope(scope)
         {
-            Ok(v) => v,
-            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
-        };
-
-        let tech_text = format!(
-            "Pitfall: {}. Cause: {}. Fix: {}. Prevention: {}.",
-            args.symptom.trim(),
-            args.cause.trim(),
-            args.fix.trim(),
-            args.prevention.trim()
-        );
-
-        let mut principle_payload = None::<(String, f32, &'static str)>;
-        if include_principle {
-            let principle_tag = match args.principle_tag {
-                Some(v) if !v.trim().is_empty() => v,
-                _ => {
-                    return JsonRpcResponse::error(id, -32602, "principle_tag is required when include_principle=true");
-                }
-            };
-            let principle_rule = match args.principle_rule {
-                Some(v) if !v.trim().is_empty() => v,
-                _ => {
-                    return JsonRpcResponse::error(
-                        id,
-                        -32602,
-                        "principle_rule is required when include_principle=true",
-                    );
-                }
-            };
-            let trigger = match args.trigger {
-                Some(v) if !v.trim().is_empty() => v,
-                _ => return JsonRpcResponse::error(id, -32602, "trigger is required when include_principle=true"),
-            };
-            let action = match args.action {
-                Some(v) if !v.trim().is_empty() => v,
-                _ => return JsonRpcResponse::error(id, -32602, "action is required when include_principle=true"),
-            };
-            let (principle_importance, principle_level) =
-                match resolve_importance(args.principle_importance_level.as_deref(), Some(0.75)) {
-                    Ok(v) => v,
-                    Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
-                };
-
-            let principle_text = format!(
-                "Decision principle ({}): {}. Trigger: {}. Action: {}.",
-                principle_tag.trim(),
-                principle_rule.trim(),
-                trigger.trim(),
-                action.trim()
-            );
-            principle_payload = Some((principle_text, principle_importance, principle_level));
+            return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
         }
-
-        let mut locked = self.store.lock();
-
-        let technical = match store_layer_with_rules(
-            &self.scopes,
-            &self.auto_store_counter,
-            locked.as_mut(),
-            StoreLayerRequest {
-                text: tech_text,
-                category: "fact".to_string(),
-                scope: scope.clone(),
-                importance: tech_importance,
-                importance_level: tech_level,
-                tags: tags.clone(),
-                governed,
-                use_vector,
-                enforce_verify: true,
-                allow_auto_maintenance: true,
-            },
-        ) {
-            Ok(v) => v,
-            Err(msg) => return JsonRpcResponse::error(id, -32602, msg),
-        };
-
-        let principle = if let Some((text, importance, level)) = principle_payload {
-            match store_layer_with_rules(
-                &self.scopes,
-                &self.auto_store_counter,
-                locked.as_mut(),
-                StoreLayerRequest {
-                    text,
-                    category: "decision".to_string(),
-                    scope: scope.clone(),
-                    importance,
-                    importance_level: level,
-                    tags,
-                    governed,
-                    use_vector,
-                    enforce_verify: true,
-                    allow_auto_maintenance: true,
-                },
-            ) {
-                Ok(v) => Some(v),
-                Err(msg) => {
-                    let _ = locked.forget_by_id(&technical.entry.id);
-                    return JsonRpcResponse::error(
-                        id,
-                        -32602,
-                        format!("principle layer store failed, rolled back technical layer: {msg}"),
-                    );
-                }
-            }
-        } else {
-            None
-        };
-        let auto_maintenance = [
-            technical.auto_maintenance,
-            principle.as_ref().and_then(|v| v.auto_maintenance.clone()),
-        ]
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
-
-        let mut tech_clean = technical.entry;
-        tech_clean.embedding = None;
-        let principle_clean = principle.as_ref().map(|v| {
-            let mut e = v.entry.clone();
-            e.embedding = None;
-            e
+        let rows = self.store.read().list(200_000);
+        let mut usage: BTreeMap<String, QuotaUsage> = BTreeMap::new();
+        if let Some(scope) = &args.scope {
+            usage.insert(scope.clone(), QuotaUsage::default());
+        }
+        for entry in filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), None) {
+            let used = usage.entry(entry.scope).or_default();
+            *used = used.plus(QuotaUsage::of_text(&entry.text));
+        }
+        let scopes = usage
+            .into_iter()
+            .map(|(scope, used)| {
+                let limit = self.quotas.scope_limit(&scope);
+                json!({"scope": scope, "used": used, "limit": limit})
+            })
+            .collect::<Vec<_>>();
+        let agent = json!({
+            "agent_id": self.scopes.agent_id(),
+            "window_ms": self.quotas.agent_window_ms,
+            "used": self.agent_window_usage(),
+            "limit": self.quotas.agent.is_set().then_some(self.quotas.agent)
         });
         JsonRpcResponse::success(
             id,
             json!({
                 "structuredContent": {
-                    "technical": tech_clean,
-                    "principle": principle_clean,
-                    "auto_maintenance": auto_maintenance,
-                    "dual_layer_completed": true
+                    "agent": agent,
+                    "scopes": scopes
                 },
-                "content": [{"type":"text","text":"dual-layer memory stored and verified"}]
+                "content": [{"type":"text","text": format!("quota status for {} scopes", scopes.len())}]
             }),
         )
     }
 
-    fn exec_memory_recall(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let total_start = Instant::now();
-        let args: MemoryRecallInput = match parse_args(arguments) {
+    fn exec_memory_archive(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryArchiveInput = match parse_args_optional(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
-        let query_text = args.query.clone();
-        let limit = args.limit.unwrap_or(5).clamp(1, 20);
-        let candidate_pool = args.candidate_pool.unwrap_or(limit * 6).clamp(limit, 200);
-        self.record_recall_dimensions(
-            args.scope.as_deref(),
-            args.category.as_deref(),
-            args.rerank_provider.as_deref(),
-        );
-        let query_embedding = if args.use_vector.unwrap_or(false) {
-            match embed_one(&query_text, EmbeddingTask::Query) {
-                Ok(v) => Some(v),
-                Err(msg) => return JsonRpcResponse::error(id, -32002, msg),
-            }
-        } else {
-            None
-        };
-
-        let locked = self.store.lock();
-
-        let local_start = Instant::now();
-        let mut results = recall_with_acl(
-            locked.as_ref(),
-            &self.scopes,
-            RecallAclRequest {
-                query: query_text.clone(),
-                query_embedding,
-                requested_scope: args.scope,
-                category: args.category,
-                candidate_pool,
-                vector_weight: args.vector_weight,
-                lexical_weight: args.lexical_weight,
-            },
-        );
-        drop(locked);
-        self.record_recall_stage("local", local_start.elapsed().as_secs_f64() * 1000.0);
+        let dry_run = args.dry_run.unwrap_or(false);
+        let action = if dry_run { ScopeAction::Read } else { ScopeAction::Admin };
+        if let Some(scope) = &args.scope
+            && let Err(denied) = self.scopes.check(scope, action)
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+        if args.ids.is_none() && args.older_than_days.is_none() {
+            return JsonRpcResponse::error(id, -32602, "memory_archive requires ids or older_than_days");
+        }
+        if args.older_than_days.is_some_and(|d| !d.is_finite() || d < 0.0) {
+            return JsonRpcResponse::error(id, -32602, "older_than_days must be a non-negative number");
+        }
+        let max_importance = args.max_importance.unwrap_or(0.5);
+        let limit = args.limit.unwrap_or(1_000).clamp(1, 10_000);
+        let now = now_ms();
+        let cutoff = args.older_than_days.map(|days| {
+            let age = Duration::try_from_secs_f64(days * 86_400.0).unwrap_or(Duration::MAX);
+            now.saturating_sub(u64::try_from(age.as_millis()).unwrap_or(u64::MAX))
+        });
 
-        let mut warning: Option<String> = None;
-        if args.use_remote.unwrap_or(false) && !results.is_empty() {
-            self.record_remote_rerank_attempt();
-            let remote_start = Instant::now();
-            match semantic_rerank_with_remote(
-                &query_text,
-                &mut results,
-                args.provider.as_deref(),
-                args.rerank_provider.as_deref(),
-            ) {
-                Ok(maybe_warning) => {
-                    warning = maybe_warning;
-                }
-                Err(msg) => {
-                    warning = Some(msg);
+        let mut locked = self.store.write();
+        let rows = filter_entries_for(
+            locked.list(200_000),
+            &self.scopes,
+            action,
+            args.scope.as_deref(),
+            args.category.as_deref(),
+        );
+        let mut missing = Vec::new();
+        let candidates = if let Some(ids) = args.ids {
+            let mut picked = Vec::new();
+            for mid in ids {
+                match rows.iter().find(|e| e.id == mid) {
+                    Some(entry) if !picked.iter().any(|p: &MemoryEntry| p.id == mid) => picked.push(entry.clone()),
+                    Some(_) => {}
+                    None => missing.push(mid),
                 }
             }
-            if warning.is_some() {
-                self.record_remote_rerank_warning();
+            picked
+        } else {
+            rows.into_iter()
+                .filter(|e| {
+                    !is_pending_review(e)
+                        && e.importance <= max_importance
+                        && cutoff.is_some_and(|cutoff| e.timestamp_ms <= cutoff)
+                })
+                .collect()
+        };
+        let candidates = candidates.into_iter().take(limit).collect::<Vec<_>>();
+        let candidate_ids = candidates.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        let archived = if dry_run {
+            Vec::new()
+        } else {
+            match move_to_archive(&self.decay, locked.as_mut(), candidates, Vec::new(), None, now) {
+                Ok(v) => v,
+                Err(err) => return JsonRpcResponse::error(id, -32001, err),
             }
-            self.record_recall_stage("remote", remote_start.elapsed().as_secs_f64() * 1000.0);
-        }
-        results.truncate(limit);
-        self.record_recall_stage("total", total_start.elapsed().as_secs_f64() * 1000.0);
+        };
+        drop(locked);
 
         JsonRpcResponse::success(
             id,
             json!({
                 "structuredContent": {
-                    "count": results.len(),
-                    "warning": warning,
-                    "agent_id": self.scopes.agent_id,
-                    "items": results.iter().map(|r| {
-                        let mut e = r.entry.clone();
-                        e.embedding = None;
-                        json!({"entry": e, "score": r.score})
-                    }).collect::<Vec<_>>()
+                    "dry_run": dry_run,
+                    "candidates": candidate_ids.len(),
+                    "candidate_ids": candidate_ids,
+                    "archived": archived,
+                    "missing": missing
                 },
-                "content": [{
-                    "type":"text",
-                    "text": if let Some(w) = warning {
-                        format!("Recalled {} entries. {}", results.len(), w)
-                    } else {
-                        format!("Recalled {} entries.", results.len())
-                    }
-                }]
+                "content": [{"type":"text","text": format!("archive {}: candidates={}, archived={}", if dry_run {"preview"} else {"apply"}, candidate_ids.len(), archived.len())}]
             }),
         )
     }
 
-    fn exec_memory_forget(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryForgetInput = match parse_args(arguments) {
+    fn exec_memory_eval_baseline(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryEvalBaselineInput = match parse_args(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
+        let name = args
+            .name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| "default".to_string());
+        let fail_on_regression = match args.on_regression.as_deref() {
+            None | Some("fail") => true,
+            Some("warn") => false,
+            Some(other) => {
+                return JsonRpcResponse::error(id, -32602, format!("on_regression must be fail|warn, got {other}"));
+            }
+        };
+        let mut baselines = match self.baselines.load() {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err),
+        };
 
-        let mut locked = self.store.lock();
+        match args.op.as_str() {
+            "save" => {
+                let mut cases = args.cases.unwrap_or_default();
+                if args.include_feedback.unwrap_or(false) {
+                    let from_feedback = match self.feedback.load() {
+                        Ok(data) => data.eval_cases(),
+                        Err(err) => return JsonRpcResponse::error(id, -32001, err),
+                    };
+                    for case in from_feedback {
+                        if !cases.iter().any(|c| c.query == case.query) {
+                            cases.push(case);
+                        }
+                    }
+                }
+                if cases.is_empty()
+                    || cases.len() > 500
+                    || cases.iter().any(|c| c.query.trim().is_empty() || c.expected.is_empty())
+                {
+                    return JsonRpcResponse::error(
+                        id,
+                        -32602,
+                        "cases must contain between 1 and 500 entries, each with a query and expected ids",
+                    );
+                }
+                if let Some(scope) = self.denied_eval_scope(&cases) {
+                    return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
+                }
+                let k = args.k.unwrap_or(5).clamp(1, 50);
+                let use_vector = args.use_vector.unwrap_or(false);
+                let scores = match self.run_eval_cases(&cases, k, use_vector) {
+                    Ok(v) => v,
+                    Err(msg) => return JsonRpcResponse::error(id, -32002, msg),
+                };
+                let metrics = eval::aggregate(&scores);
+                baselines.insert(
+                    name.clone(),
+                    EvalBaseline {
+                        saved_ms: now_ms(),
+                        k,
+                        use_vector,
+                        ranking: self.ranking_config(),
+                        cases,
+                        metrics,
+                        scores: scores.clone(),
+                    },
+                );
+                if let Err(err) = self.baselines.save(&baselines) {
+                    return JsonRpcResponse::error(id, -32001, err);
+                }
+                JsonRpcResponse::success(
+                    id,
+                    json!({
+                        "structuredContent": {
+                            "op": "save",
+                            "name": name,
+                            "k": k,
+                            "metrics": metrics,
+                            "scores": scores
+                        },
+                        "content": [{
+                            "type": "text",
+                            "text": format!(
+                                "saved eval baseline {name}: hit_rate={:.3}, mrr={:.3}, recall={:.3}",
+                                metrics.hit_rate, metrics.mrr, metrics.recall
+                            )
+                        }]
+                    }),
+                )
+            }
+            "compare" => {
+                let Some(baseline) = baselines.remove(&name) else {
+                    return JsonRpcResponse::error(
+                        id,
+                        -32602,
+                        format!("no eval baseline named {name}; save one first"),
+                    );
+                };
+                if let Some(scope) = self.denied_eval_scope(&baseline.cases) {
+                    return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
+                }
+                let tolerance = args.tolerance.unwrap_or(0.02).clamp(0.0, 1.0);
+                let scores = match self.run_eval_cases(&baseline.cases, baseline.k, baseline.use_vector) {
+                    Ok(v) => v,
+                    Err(msg) => return JsonRpcResponse::error(id, -32002, msg),
+                };
+                let current = eval::aggregate(&scores);
+                let regressions = eval::regressions(&baseline.metrics, &current, tolerance);
+                let report = json!({
+                    "op": "compare",
+                    "name": name,
+                    "k": baseline.k,
+                    "tolerance": tolerance,
+                    "status": if regressions.is_empty() { "ok" } else { "regressed" },
+                    "baseline": baseline.metrics,
+                    "baseline_saved_ms": baseline.saved_ms,
+                    "current": current,
+                    "regressions": regressions,
+                    "lost_hits": eval::lost_hits(&baseline.scores, &scores),
+                    "ranking": {"baseline": baseline.ranking, "current": self.ranking_config()}
+                });
+                if !regressions.is_empty() && fail_on_regression {
+                    return JsonRpcResponse::error_with_data(
+                        id,
+                        -32008,
+                        "recall quality regressed beyond tolerance",
+                        report,
+                    );
+                }
+                JsonRpcResponse::success(
+                    id,
+                    json!({
+                        "structuredContent": report,
+                        "content": [{
+                            "type": "text",
+                            "text": format!(
+                                "eval baseline {name}: {} regression(s), mrr {:.3} -> {:.3}",
+                                regressions.len(),
+                                baseline.metrics.mrr,
+                                current.mrr
+                            )
+                        }]
+                    }),
+                )
+            }
+            other => JsonRpcResponse::error(id, -32602, format!("op must be save|compare, got {other}")),
+        }
+    }
 
-        // Verify scope access before deletion
-        let entry = locked.list(200_000).into_iter().find(|e| e.id == args.id);
-        if let Some(ref entry) = entry {
-            if !self.scopes.can_access_scope(&entry.scope) {
-                return JsonRpcResponse::error(id, -32603, format!("scope access denied for memory {}", args.id));
+    /// Appends a recall to the query log and returns its id; logging failures only warn.
+    fn log_recall_query(&self, mut record: QueryRecord) -> Option<String> {
+        let mut log = self.query_log.as_ref()?.lock();
+        record.id = log.next_id(record.ts_ms);
+        let query_id = record.id.clone();
+        match log.append(record) {
+            Ok(()) => Some(query_id),
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to append to query log");
+                None
             }
         }
+    }
 
-        let deleted = match locked.forget_by_id(&args.id) {
+    fn exec_memory_query_log(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryQueryLogInput = match parse_args_optional(arguments) {
             Ok(v) => v,
-            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+            Err(resp) => return with_id(resp, id),
+        };
+        let jsonl = match args.format.as_deref() {
+            None | Some("json") => false,
+            Some("jsonl") => true,
+            Some(other) => {
+                return JsonRpcResponse::error(id, -32602, format!("format must be json|jsonl, got {other}"));
+            }
+        };
+        let Some(log) = &self.query_log else {
+            return JsonRpcResponse::success(
+                id,
+                json!({
+                    "structuredContent": {"enabled": false, "total": 0, "count": 0, "items": []},
+                    "content": [{"type": "text", "text": "query logging is disabled; set PRX_MEMORY_QUERY_LOG=1"}]
+                }),
+            );
         };
+        let limit = args.limit.unwrap_or(100).clamp(1, 5000);
+        let offset = args.offset.unwrap_or(0);
+        let accessed_only = args.accessed_only.unwrap_or(false);
+        let agent_id = self.scopes.agent_id();
+        let wanted = |r: &QueryRecord| {
+            r.agent_id == agent_id
+                && args.since_ms.is_none_or(|since| r.ts_ms >= since)
+                && args.until_ms.is_none_or(|until| r.ts_ms < until)
+                && (!accessed_only || !r.accessed_ids.is_empty())
+        };
+
+        let mut log = log.lock();
+        let matched = log.records().filter(|r| wanted(r)).cloned().collect::<Vec<_>>();
+        let total = matched.len();
+        let items = matched.into_iter().skip(offset).take(limit).collect::<Vec<_>>();
+        let next_offset = (offset + items.len() < total).then_some(offset + items.len());
+        let cleared = if args.clear.unwrap_or(false) {
+            let exported = items.iter().map(|r| r.id.as_str()).collect::<HashSet<_>>();
+            match log.remove_where(|r| exported.contains(r.id.as_str())) {
+                Ok(n) => n,
+                Err(err) => return JsonRpcResponse::error(id, -32001, err),
+            }
+        } else {
+            0
+        };
+        drop(log);
 
+        let text = if jsonl {
+            items
+                .iter()
+                .filter_map(|r| serde_json::to_string(r).ok())
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            format!("exported {} of {total} logged queries", items.len())
+        };
         JsonRpcResponse::success(
             id,
             json!({
-                "structuredContent": {"deleted": deleted, "id": args.id},
-                "content": [{"type":"text", "text": if deleted {"deleted"} else {"not found"}}]
+                "structuredContent": {
+                    "enabled": true,
+                    "total": total,
+                    "count": items.len(),
+                    "offset": offset,
+                    "next_offset": next_offset,
+                    "cleared": cleared,
+                    "items": items
+                },
+                "content": [{"type": "text", "text": text}]
             }),
         )
     }
 
-    fn exec_memory_update(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryUpdateInput = match parse_args(arguments) {
+    fn exec_memory_feedback(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryFeedbackInput = match parse_args(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
-
-        let governed = args
-            .governed
-            .unwrap_or_else(|| self.standards.default_governed_for_update());
-        let mut locked = self.store.lock();
-
-        let existing = locked.list(200_000).into_iter().find(|e| e.id == args.id);
-        let Some(existing) = existing else {
-            return JsonRpcResponse::error(id, -32602, "memory id not found");
-        };
-
-        if !self.scopes.can_access_scope(&existing.scope) {
-            return JsonRpcResponse::error(id, -32602, "scope access denied for existing memory");
-        }
-
-        let merged_scope = args.scope.unwrap_or(existing.scope.clone());
-        let merged_category = args.category.unwrap_or(existing.category.clone());
-        let merged_text = args.text.unwrap_or(existing.text.clone());
-        let merged_tags = normalize_tags_with_defaults(
-            args.tags.unwrap_or(existing.tags.clone()),
-            args.project_tag.as_deref(),
-            args.tool_tag.as_deref(),
-            args.domain_tag.as_deref(),
-            &self.standards,
-        );
-        let (merged_importance, importance_level) =
-            match resolve_importance(args.importance_level.as_deref(), args.importance) {
-                Ok((importance, level)) => (importance, level),
-                Err(_) => (existing.importance, importance_level_from_numeric(existing.importance)),
-            };
-        let merged_embedding = if merged_text != existing.text {
-            match embed_one(&merged_text, EmbeddingTask::Passage) {
-                Ok(v) => Some(v),
-                Err(_) => existing.embedding.clone(),
+        let mut useful = Vec::new();
+        let mut irrelevant = Vec::new();
+        for (ids, out) in [(&args.useful, &mut useful), (&args.irrelevant, &mut irrelevant)] {
+            for mid in ids.iter().map(|m| m.trim()).filter(|m| !m.is_empty()) {
+                if !out.iter().any(|m: &String| m == mid) {
+                    out.push(mid.to_string());
+                }
             }
-        } else {
-            existing.embedding.clone()
-        };
-
-        if !self.scopes.can_access_scope(&merged_scope) {
-            return JsonRpcResponse::error(id, -32602, "scope access denied for target scope");
         }
-        if let Some(msg) = self.scopes.validate_scope_write(&merged_scope, &merged_tags) {
-            return JsonRpcResponse::error(id, -32602, msg);
+        if useful.is_empty() && irrelevant.is_empty() || useful.len() + irrelevant.len() > 100 {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                "useful and irrelevant must name between 1 and 100 memory ids in total",
+            );
+        }
+        if let Some(both) = useful.iter().find(|m| irrelevant.contains(m)) {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("memory {both} is marked both useful and irrelevant"),
+            );
         }
 
-        if governed {
-            if let Err(msg) = validate_governed_input(&merged_text, &merged_category, &merged_tags, importance_level) {
-                return JsonRpcResponse::error(id, -32602, msg);
+        let (query, mut scope, mut category) = match (&args.query_id, &self.query_log) {
+            (Some(query_id), Some(log)) => {
+                let found = log
+                    .lock()
+                    .records()
+                    .find(|r| &r.id == query_id)
+                    .map(|r| (r.query.clone(), r.scope.clone(), r.category.clone()));
+                let Some(found) = found else {
+                    return JsonRpcResponse::error(id, -32602, format!("unknown query_id {query_id}"));
+                };
+                found
+            }
+            (Some(_), None) => {
+                return JsonRpcResponse::error(
+                    id,
+                    -32602,
+                    "query_id needs the query log; set PRX_MEMORY_QUERY_LOG=1 or pass query",
+                );
             }
+            (None, _) => (args.query.clone().unwrap_or_default(), None, None),
+        };
+        if query.trim().is_empty() {
+            return JsonRpcResponse::error(id, -32602, "query or query_id is required");
         }
+        scope = args.scope.or(scope);
+        category = args.category.or(category);
 
-        match locked.forget_by_id(&args.id) {
-            Ok(true) => {}
-            Ok(false) => return JsonRpcResponse::error(id, -32602, "memory id not found"),
-            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+        let visible = self
+            .store
+            .read()
+            .list(200_000)
+            .into_iter()
+            .filter(|e| self.scopes.can_access_scope(&e.scope))
+            .map(|e| e.id)
+            .collect::<HashSet<_>>();
+        if let Some(missing) = useful.iter().chain(&irrelevant).find(|m| !visible.contains(*m)) {
+            return JsonRpcResponse::error(id, -32602, format!("memory not found: {missing}"));
         }
 
-        let updated = match locked.store(NewMemoryEntry {
-            text: merged_text,
-            category: merged_category,
-            scope: merged_scope,
-            importance: merged_importance,
-            tags: merged_tags,
-            embedding: merged_embedding,
-        }) {
+        let mut data = match self.feedback.load() {
             Ok(v) => v,
-            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+            Err(err) => return JsonRpcResponse::error(id, -32001, err),
         };
+        data.record(&QueryFeedback {
+            query: query.clone(),
+            scope,
+            category,
+            useful: useful.clone(),
+            irrelevant: irrelevant.clone(),
+            updated_ms: now_ms(),
+        });
+        if let Err(err) = self.feedback.save(&data) {
+            return JsonRpcResponse::error(id, -32001, err);
+        }
+        if let Some(log) = self.query_log.as_ref().filter(|_| !useful.is_empty())
+            && let Err(err) = log.lock().mark_accessed(&useful)
+        {
+            tracing::warn!(error = %err, "failed to update query log");
+        }
 
-        let mut updated_clean = updated;
-        updated_clean.embedding = None;
+        let entries = useful
+            .iter()
+            .chain(&irrelevant)
+            .map(|mid| {
+                let tally = data.entries.get(mid).copied().unwrap_or_default();
+                json!({"id": mid, "useful": tally.useful, "irrelevant": tally.irrelevant, "net": tally.net()})
+            })
+            .collect::<Vec<_>>();
         JsonRpcResponse::success(
             id,
             json!({
                 "structuredContent": {
-                    "replaced_id": args.id,
-                    "entry": updated_clean
+                    "query": query,
+                    "query_id": args.query_id,
+                    "useful": useful.len(),
+                    "irrelevant": irrelevant.len(),
+                    "entries": entries
                 },
-                "content": [{"type":"text", "text": "memory updated"}]
+                "content": [{
+                    "type": "text",
+                    "text": format!("recorded feedback: {} useful, {} irrelevant", useful.len(), irrelevant.len())
+                }]
             }),
         )
     }
 
-    fn exec_memory_export(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryExportInput = match parse_args_optional(arguments) {
+    fn denied_eval_scope<'a>(&self, cases: &'a [EvalCase]) -> Option<&'a str> {
+        cases
+            .iter()
+            .filter_map(|c| c.scope.as_deref())
+            .find(|scope| !self.scopes.can_access_scope(scope))
+    }
+
+    /// Runs each golden query through local ranking (ACL-filtered, pending entries excluded, no
+    /// remote rerank) and scores the top `k`.
+    fn run_eval_cases(&self, cases: &[EvalCase], k: usize, use_vector: bool) -> Result<Vec<eval::CaseScore>, String> {
+        let embeddings = self.eval_query_embeddings(cases, use_vector)?;
+        let snapshot = self.store.read().snapshot();
+        Ok(self.score_eval_cases(&snapshot, cases, &embeddings, k))
+    }
+
+    /// Query vectors for `cases`, computed up front so scoring can run under a single store lock.
+    fn eval_query_embeddings(&self, cases: &[EvalCase], use_vector: bool) -> Result<Vec<Option<Vec<f32>>>, String> {
+        cases
+            .iter()
+            .map(|case| {
+                if !use_vector {
+                    return Ok(None);
+                }
+                embed_one(
+                    &self.runtime,
+                    &self.embed,
+                    &CallContext::default(),
+                    &case.query,
+                    EmbeddingTask::Query,
+                )
+                .map(|embedded| Some(embedded.vector))
+            })
+            .collect()
+    }
+
+    fn score_eval_cases(
+        &self,
+        store: &StoreSnapshot,
+        cases: &[EvalCase],
+        embeddings: &[Option<Vec<f32>>],
+        k: usize,
+    ) -> Vec<eval::CaseScore> {
+        cases
+            .iter()
+            .zip(embeddings)
+            .map(|(case, query_embedding)| {
+                let results = recall_with_acl(
+                    store,
+                    &self.scopes,
+                    RecallAclRequest {
+                        query: case.query.clone(),
+                        query_embedding: query_embedding.clone(),
+                        requested_scope: case.scope.clone(),
+                        category: case.category.clone(),
+                        candidate_pool: k * 4,
+                        vector_weight: None,
+                        lexical_weight: None,
+                        diversity: None,
+                        fusion: None,
+                    },
+                );
+                let ranked = results
+                    .into_iter()
+                    .filter(|r| !is_pending_review(&r.entry))
+                    .map(|r| r.entry.id)
+                    .collect::<Vec<_>>();
+                eval::score_case(case, &ranked, k)
+            })
+            .collect()
+    }
+
+    fn exec_memory_restore(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryRestoreInput = match parse_args(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
-
-        if let Some(scope) = &args.scope {
-            if !self.scopes.can_access_scope(scope) {
-                return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
+        let mut ids = Vec::with_capacity(args.ids.len());
+        for mid in args.ids {
+            if !ids.contains(&mid) {
+                ids.push(mid);
             }
         }
-
-        let limit = args.limit.unwrap_or(500).clamp(1, 20_000);
-        let include_embeddings = args.include_embeddings.unwrap_or(false);
-        let locked = self.store.lock();
-        let rows = locked.list(200_000);
-        drop(locked);
-
-        let mut items = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), args.category.as_deref());
-        items.truncate(limit);
-        if !include_embeddings {
-            for row in &mut items {
-                row.embedding = None;
-            }
+        if ids.is_empty() || ids.len() > 100 {
+            return JsonRpcResponse::error(id, -32602, "ids must contain between 1 and 100 archived memory ids");
         }
 
-        if let Some(raw_path) = args.output_path {
-            let safe_path = match validate_safe_path(&raw_path) {
-                Ok(p) => p,
-                Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid output path: {err}")),
-            };
-            let payload = json!({ "entries": items });
-            let bytes = match serde_json::to_vec_pretty(&payload) {
-                Ok(v) => v,
-                Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
-            };
-            if let Err(err) = fs::write(&safe_path, bytes) {
-                return JsonRpcResponse::error(id, -32001, err.to_string());
+        let mut locked = self.store.write();
+        let archived = match self.decay.archive.load() {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err),
+        };
+        let (chosen, mut remaining): (Vec<_>, Vec<_>) = archived.into_iter().partition(|item| {
+            ids.contains(&item.entry.id) && self.scopes.permits(&item.entry.scope, ScopeAction::Admin)
+        });
+        let missing = ids
+            .into_iter()
+            .filter(|mid| !chosen.iter().any(|item| &item.entry.id == mid))
+            .collect::<Vec<_>>();
+        let mut adding: BTreeMap<&str, QuotaUsage> = BTreeMap::new();
+        for item in &chosen {
+            let usage = adding.entry(item.entry.scope.as_str()).or_default();
+            *usage = usage.plus(QuotaUsage::of_text(&item.entry.text));
+        }
+        for (scope, usage) in adding {
+            if let Err(exceeded) = self.check_store_quota(locked.as_ref(), scope, usage) {
+                return exceeded.response(id);
             }
-            let path_display = safe_path.display().to_string();
-            return JsonRpcResponse::success(
-                id,
-                json!({
-                    "structuredContent": {
-                        "count": payload["entries"].as_array().map(|v| v.len()).unwrap_or(0),
-                        "output_path": path_display
-                    },
-                    "content": [{"type":"text","text":"memory export completed"}]
-                }),
-            );
+        }
+        let restored = match restore_archived_entries(locked.as_mut(), &chosen) {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err),
+        };
+        let renamed = restored
+            .iter()
+            .map(|(old, entry)| (old.as_str(), entry.id.as_str()))
+            .collect::<HashMap<_, _>>();
+        for edge in remaining.iter_mut().flat_map(|item| item.relations.iter_mut()) {
+            for endpoint in [&mut edge.from_id, &mut edge.to_id] {
+                if let Some(new_id) = renamed.get(endpoint.as_str()) {
+                    *endpoint = (*new_id).to_string();
+                }
+            }
+        }
+        if let Err(err) = self.decay.archive.rewrite(&remaining) {
+            return JsonRpcResponse::error(id, -32001, err);
+        }
+        drop(locked);
+        for _ in &restored {
+            self.record_agent_usage(UsageOp::Store, 0);
         }
 
+        let restored = restored
+            .iter()
+            .map(|(old, entry)| json!({"id": old, "new_id": entry.id}))
+            .collect::<Vec<_>>();
         JsonRpcResponse::success(
             id,
             json!({
                 "structuredContent": {
-                    "count": items.len(),
-                    "items": items
+                    "restored": restored,
+                    "missing": missing
                 },
-                "content": [{"type":"text","text": format!("exported {} memories", items.len())}]
+                "content": [{"type":"text","text": format!("{} memories restored", restored.len())}]
             }),
         )
     }
 
-    fn exec_memory_import(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryImportInput = match parse_args(arguments) {
+    fn exec_memory_index_status(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryIndexStatusInput = match parse_args_optional(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
-        let options = ImportOptions {
-            governed: args
-                .governed
-                .unwrap_or_else(|| self.standards.default_governed_for_import()),
-            use_vector: args.use_vector.unwrap_or(false),
-            skip_duplicates: args.skip_duplicates.unwrap_or(true),
+        let status = if args.ensure.unwrap_or(false) {
+            self.store.write().ensure_indexes()
+        } else {
+            self.store.read().index_status()
+        };
+        let status = match status {
+            Ok(status) => status,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
         };
-        let summary = self.import_entries(args.entries, options);
+        let health = status
+            .get("health")
+            .and_then(Value::as_str)
+            .unwrap_or("scan")
+            .to_string();
         JsonRpcResponse::success(
             id,
             json!({
-                "structuredContent": {
-                    "created": summary.created,
-                    "skipped": summary.skipped,
-                    "failed": summary.failed,
-                    "errors": summary.errors
-                },
-                "content": [{"type":"text","text": format!("import done: created={}, skipped={}, failed={}", summary.created, summary.skipped, summary.failed)}]
+                "structuredContent": status,
+                "content": [{"type": "text", "text": format!("index health: {health}")}]
             }),
         )
     }
 
-    fn exec_memory_migrate(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryMigrateInput = match parse_args(arguments) {
+    fn exec_memory_sync(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemorySyncInput = match parse_args_optional(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
-
-        let safe_path = match validate_safe_path(&args.source_path) {
-            Ok(p) => p,
-            Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid source path: {err}")),
-        };
-
-        let bytes = match fs::read(&safe_path) {
-            Ok(v) => v,
-            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
-        };
-
-        #[derive(Deserialize)]
-        #[serde(untagged)]
-        enum MigratePayload {
-            EntriesObj { entries: Vec<ImportedMemoryEntry> },
-            EntriesArray(Vec<ImportedMemoryEntry>),
-        }
-
-        let entries = match serde_json::from_slice::<MigratePayload>(&bytes) {
-            Ok(MigratePayload::EntriesObj { entries }) => entries,
-            Ok(MigratePayload::EntriesArray(entries)) => entries,
-            Err(err) => return JsonRpcResponse::error(id, -32602, format!("invalid migrate file: {err}")),
+        let Some(sync) = &self.team_sync else {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                "team sync is not configured; set PRX_MEMORY_SYNC_URL and PRX_MEMORY_SYNC_SCOPES",
+            );
         };
-
-        let options = ImportOptions {
-            governed: args.governed.unwrap_or(false),
-            use_vector: args.use_vector.unwrap_or(false),
-            skip_duplicates: args.skip_duplicates.unwrap_or(true),
+        let policy = &self.standards.governance;
+        let eligible = |entry: &MemoryEntry| sync_eligibility(policy, entry).is_ok();
+        let report = match sync.run(&self.runtime, &self.store, &eligible, args.dry_run.unwrap_or(false)) {
+            Ok(report) => report,
+            Err(message) => return JsonRpcResponse::error(id, -32001, message),
         };
-        let summary = self.import_entries(entries, options);
+        let text = format!(
+            "sync{} pushed={} pulled={} conflicts={} rejected={}",
+            if report.dry_run { " (dry run)" } else { "" },
+            report.pushed,
+            report.pulled,
+            report.conflicts.len(),
+            report.rejected.len()
+        );
         JsonRpcResponse::success(
             id,
             json!({
-                "structuredContent": {
-                    "source_path": args.source_path,
-                    "created": summary.created,
-                    "skipped": summary.skipped,
-                    "failed": summary.failed,
-                    "errors": summary.errors
-                },
-                "content": [{"type":"text","text":"memory migration completed"}]
+                "structuredContent": report,
+                "content": [{"type": "text", "text": text}]
             }),
         )
     }
 
-    fn exec_memory_reembed(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryReembedInput = match parse_args_optional(arguments) {
-            Ok(v) => v,
-            Err(resp) => return with_id(resp, id),
+    fn exec_memory_rekey(&self, id: Value) -> JsonRpcResponse {
+        let Some(cipher) = &self.decay.archive.cipher else {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                "encryption is not configured; set PRX_MEMORY_ENCRYPTION_KEY or PRX_MEMORY_ENCRYPTION_KEY_FILE",
+            );
+        };
+        let mut locked = self.store.write();
+        let entries = match locked.rekey() {
+            Ok(n) => n,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err.to_string()),
+        };
+        let archived = match self
+            .decay
+            .archive
+            .load()
+            .and_then(|items| self.decay.archive.rewrite(&items).map(|()| items.len()))
+        {
+            Ok(n) => n,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err),
         };
-        if let Some(scope) = &args.scope {
-            if !self.scopes.can_access_scope(scope) {
-                return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
-            }
-        }
-        let limit = args.limit.unwrap_or(200).clamp(1, 5_000);
-
-        let locked = self.store.lock();
-        let rows = locked.list(200_000);
         drop(locked);
-
-        let targets = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), args.category.as_deref())
-            .into_iter()
-            .take(limit)
-            .collect::<Vec<_>>();
-
-        let mut updated = 0usize;
-        let mut failed = 0usize;
-        let mut errors = Vec::new();
-        for item in targets {
-            let embedding = match embed_one(&item.text, EmbeddingTask::Passage) {
-                Ok(v) => Some(v),
-                Err(err) => {
-                    failed += 1;
-                    errors.push(format!("{}: {}", item.id, err));
-                    continue;
-                }
-            };
-
-            let mut locked = self.store.lock();
-            match locked.forget_by_id(&item.id) {
-                Ok(true) => {}
-                Ok(false) => {
-                    failed += 1;
-                    errors.push(format!("{}: missing during reembed", item.id));
-                    continue;
-                }
-                Err(err) => {
-                    failed += 1;
-                    errors.push(format!("{}: {}", item.id, err));
-                    continue;
-                }
-            }
-            match locked.store(NewMemoryEntry {
-                text: item.text,
-                category: item.category,
-                scope: item.scope,
-                importance: item.importance,
-                tags: item.tags,
-                embedding,
-            }) {
-                Ok(_) => updated += 1,
-                Err(err) => {
-                    failed += 1;
-                    errors.push(err.to_string());
+        let rewrite = self.embed.lock().take_disk_rewrite();
+        let embed_cache = match rewrite {
+            Some((log, write)) => {
+                let (EmbedDiskWrite::Append(records) | EmbedDiskWrite::Rewrite(records)) = &write;
+                let count = records.len();
+                let written = log.lock().write(&write);
+                if let Err(err) = written {
+                    return JsonRpcResponse::error(
+                        id,
+                        -32001,
+                        format!("failed to rewrite embedding disk cache: {err}"),
+                    );
                 }
+                count
             }
-        }
-
+            None => 0,
+        };
+        tracing::info!(
+            key_id = cipher.primary_key_id(),
+            entries,
+            archived,
+            embed_cache,
+            "memory store rekeyed"
+        );
         JsonRpcResponse::success(
             id,
             json!({
                 "structuredContent": {
-                    "updated": updated,
-                    "failed": failed,
-                    "errors": errors
+                    "key_id": cipher.primary_key_id(),
+                    "entries": entries,
+                    "archived": archived,
+                    "embed_cache": embed_cache
                 },
-                "content": [{"type":"text","text": format!("reembed done: updated={}, failed={}", updated, failed)}]
+                "content": [{"type":"text","text": format!("re-encrypted {entries} memories and {archived} archived memories with key {}", cipher.primary_key_id())}]
             }),
         )
     }
 
-    fn exec_memory_compact(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryCompactInput = match parse_args_optional(arguments) {
+    fn exec_memory_decay_report(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryDecayReportInput = match parse_args_optional(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
-        if let Some(scope) = &args.scope {
-            if !self.scopes.can_access_scope(scope) {
-                return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
-            }
+        if let Some(scope) = &args.scope
+            && !self.scopes.can_access_scope(scope)
+        {
+            return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
         }
-        let limit = args.limit.unwrap_or(50_000).clamp(1, 200_000);
-        let dry_run = args.dry_run.unwrap_or(true);
-
-        let locked = self.store.lock();
-        let rows = locked.list(200_000);
-        drop(locked);
-        let filtered = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), args.category.as_deref())
+        let threshold = match args.threshold.or(self.decay.archive_below).unwrap_or(0.25) {
+            t if (0.0..=1.0).contains(&t) => t,
+            _ => return JsonRpcResponse::error(id, -32602, "threshold must be within [0, 1]"),
+        };
+        let limit = args.limit.unwrap_or(100).clamp(1, 1000);
+        let rows = self.store.read().list(200_000);
+        let candidates = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), args.category.as_deref());
+        let scanned = candidates.len();
+        let now = now_ms();
+        let access = self.decay.access.lock();
+        let mut decayed = candidates
+            .into_iter()
+            .filter_map(|entry| {
+                let used = access.get(&entry.id);
+                let score = self.decay.score(&entry, used, now);
+                (score.retention < threshold).then_some((entry, used, score))
+            })
+            .collect::<Vec<_>>();
+        drop(access);
+        decayed.sort_by(|a, b| {
+            a.2.retention
+                .total_cmp(&b.2.retention)
+                .then_with(|| a.0.id.cmp(&b.0.id))
+        });
+        let below = decayed.len();
+        let items = decayed
             .into_iter()
             .take(limit)
+            .map(|(entry, used, score)| {
+                json!({
+                    "id": entry.id,
+                    "scope": entry.scope,
+                    "category": entry.category,
+                    "importance": entry.importance,
+                    "retention": score.retention,
+                    "idle_days": score.idle_days,
+                    "stability_days": score.stability_days,
+                    "access_count": used.count,
+                    "last_access_ms": (used.count > 0).then_some(used.last_ms),
+                    "text": entry.text
+                })
+            })
             .collect::<Vec<_>>();
-
-        let mut keep_keys = HashSet::new();
-        let mut duplicate_ids = Vec::new();
-        for row in filtered {
-            let key = format!("{}|{}|{}", row.scope, row.category, compact_query(&row.text, 16));
-            if !keep_keys.insert(key) {
-                duplicate_ids.push(row.id);
-            }
-        }
-
-        let mut deleted = 0usize;
-        if !dry_run {
-            let mut locked = self.store.lock();
-            for mid in &duplicate_ids {
-                if matches!(locked.forget_by_id(mid), Ok(true)) {
-                    deleted += 1;
-                }
-            }
-        }
-
         JsonRpcResponse::success(
             id,
             json!({
                 "structuredContent": {
-                    "dry_run": dry_run,
-                    "duplicates": duplicate_ids.len(),
-                    "deleted": deleted,
-                    "candidate_ids": duplicate_ids
+                    "threshold": threshold,
+                    "scanned": scanned,
+                    "below_threshold": below,
+                    "auto_archive_below": self.decay.archive_below,
+                    "items": items
                 },
-                "content": [{"type":"text","text": format!("compact {}: duplicates={}, deleted={}", if dry_run {"preview"} else {"apply"}, duplicate_ids.len(), deleted)}]
+                "content": [{"type":"text","text": format!("{below} of {scanned} memories below retention {threshold}")}]
             }),
         )
     }
 
-    fn import_entries(&self, entries: Vec<ImportedMemoryEntry>, options: ImportOptions) -> ImportSummary {
-        let mut created = 0usize;
-        let mut skipped = 0usize;
-        let mut failed = 0usize;
-        let mut errors = Vec::new();
-
-        for (idx, raw) in entries.into_iter().enumerate() {
-            let scope = raw.scope.unwrap_or_else(|| self.scopes.default_scope());
-            let category = raw.category.unwrap_or_else(|| "other".to_string());
-            let tags = normalize_tags_with_defaults(
-                raw.tags.unwrap_or_default(),
-                raw.project_tag.as_deref(),
-                raw.tool_tag.as_deref(),
-                raw.domain_tag.as_deref(),
-                &self.standards,
+    fn exec_memory_staleness(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryStalenessInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        if let Some(scope) = &args.scope
+            && let Err(denied) = self.scopes.check(scope, ScopeAction::Read)
+        {
+            return JsonRpcResponse::error(id, -32602, denied);
+        }
+        let older_than_days = match args.older_than_days.unwrap_or(180.0) {
+            d if d.is_finite() && d >= 0.0 => d,
+            _ => return JsonRpcResponse::error(id, -32602, "older_than_days must be a non-negative number"),
+        };
+        let min_confidence = match args.min_confidence.unwrap_or(0.5) {
+            c if (0.0..=1.0).contains(&c) => c,
+            _ => return JsonRpcResponse::error(id, -32602, "min_confidence must be within [0, 1]"),
+        };
+        let limit = args.limit.unwrap_or(100).clamp(1, 1000);
+
+        // Relations only count when the caller can read the memory on the other end.
+        let (candidates, relations, visible) = {
+            let locked = self.store.read();
+            let visible = filter_entries_for(locked.list(200_000), &self.scopes, ScopeAction::Read, None, None)
+                .into_iter()
+                .map(|e| (e.id, e.timestamp_ms))
+                .collect::<HashMap<_, _>>();
+            let candidates = filter_entries_for(
+                locked.list(200_000),
+                &self.scopes,
+                ScopeAction::Read,
+                args.scope.as_deref(),
+                args.category.as_deref(),
             );
-            let (importance, importance_level) =
-                match resolve_importance(raw.importance_level.as_deref(), raw.importance) {
-                    Ok(v) => v,
-                    Err(err) => {
-                        failed += 1;
-                        errors.push(format!("entry#{idx}: {err}"));
-                        continue;
-                    }
-                };
-
-            if !self.scopes.can_access_scope(&scope) {
-                failed += 1;
-                errors.push(format!("entry#{idx}: scope access denied: {scope}"));
-                continue;
-            }
-            if let Some(msg) = self.scopes.validate_scope_write(&scope, &tags) {
-                failed += 1;
-                errors.push(format!("entry#{idx}: {msg}"));
+            let relations = candidates
+                .iter()
+                .map(|e| locked.relations_for(&e.id))
+                .collect::<Vec<_>>();
+            drop(locked);
+            (candidates, relations, visible)
+        };
+        let scanned = candidates.len();
+        let now = now_ms();
+        let access = self.decay.access.lock();
+        let mut by_signal: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut stale = Vec::new();
+        for (entry, relations) in candidates.into_iter().zip(relations) {
+            let used = access.get(&entry.id);
+            let signals = stale_signals(&entry, &relations, &visible, used, now, older_than_days);
+            let confidence = 1.0 - signals.iter().map(|(_, weight, _)| 1.0 - weight).product::<f64>();
+            if signals.is_empty() || confidence < min_confidence {
                 continue;
             }
-            if options.governed {
-                if let Err(msg) = validate_governed_input(&raw.text, &category, &tags, importance_level) {
-                    failed += 1;
-                    errors.push(format!("entry#{idx}: {msg}"));
-                    continue;
-                }
-            }
-
-            let embedding = if let Some(v) = raw.embedding {
-                Some(v)
-            } else if options.use_vector {
-                match embed_one(&raw.text, EmbeddingTask::Passage) {
-                    Ok(v) => Some(v),
-                    Err(err) => {
-                        failed += 1;
-                        errors.push(format!("entry#{idx}: {}", err));
-                        continue;
-                    }
-                }
-            } else {
-                None
-            };
-
-            let mut locked = self.store.lock();
-            if options.skip_duplicates {
-                let similar = locked.recall(RecallQuery {
-                    query: compact_query(&raw.text, 10),
-                    query_embedding: None,
-                    scope: Some(scope.clone()),
-                    category: Some(category.clone()),
-                    limit: 1,
-                    vector_weight: None,
-                    lexical_weight: None,
-                });
-                if similar.first().is_some_and(|r| r.score > 0.93) {
-                    skipped += 1;
-                    continue;
-                }
-            }
-
-            match locked.store(NewMemoryEntry {
-                text: raw.text,
-                category,
-                scope,
-                importance,
-                tags,
-                embedding,
-            }) {
-                Ok(_) => created += 1,
-                Err(err) => {
-                    failed += 1;
-                    errors.push(format!("entry#{idx}: {}", err));
-                }
-            }
-        }
-
-        ImportSummary {
-            created,
-            skipped,
-            failed,
-            errors,
+            for (signal, _, _) in &signals {
+                *by_signal.entry(signal).or_insert(0) += 1;
+            }
+            stale.push((entry, used, confidence, signals));
         }
+        drop(access);
+        stale.sort_by(|a, b| {
+            b.2.total_cmp(&a.2)
+                .then_with(|| a.0.timestamp_ms.cmp(&b.0.timestamp_ms))
+                .then_with(|| a.0.id.cmp(&b.0.id))
+        });
+        let flagged = stale.len();
+        let items = stale
+            .into_iter()
+            .take(limit)
+            .map(|(entry, used, confidence, signals)| {
+                json!({
+                    "id": entry.id,
+                    "scope": entry.scope,
+                    "category": entry.category,
+                    "importance": entry.importance,
+                    "confidence": confidence,
+                    "age_days": age_days(entry.timestamp_ms, now),
+                    "access_count": used.count,
+                    "last_access_ms": (used.count > 0).then_some(used.last_ms),
+                    "signals": signals.into_iter().map(|(_, _, item)| item).collect::<Vec<_>>(),
+                    "text": entry.text
+                })
+            })
+            .collect::<Vec<_>>();
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "structuredContent": {
+                    "scanned": scanned,
+                    "flagged": flagged,
+                    "older_than_days": older_than_days,
+                    "min_confidence": min_confidence,
+                    "by_signal": by_signal,
+                    "items": items
+                },
+                "content": [{"type":"text","text": format!("{flagged} of {scanned} memories look stale (confidence >= {min_confidence})")}]
+            }),
+        )
     }
 
-    fn exec_memory_stats(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
-        let args: MemoryStatsInput = match parse_args_optional(arguments) {
+    fn exec_memory_usage_report(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryUsageReportInput = match parse_args_optional(arguments) {
             Ok(v) => v,
             Err(resp) => return with_id(resp, id),
         };
+        let window_ms = args.window_ms.unwrap_or(86_400_000).clamp(60_000, 30 * 86_400_000);
+        let now = now_ms();
+        let since = now.saturating_sub(window_ms);
 
-        if let Some(scope) = &args.scope {
-            if !self.scopes.can_access_scope(scope) {
-                return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
+        let quota = self.quotas.agent.max_bytes;
+        let locked = self.usage.lock();
+        let mut agent_ids = locked
+            .agents()
+            .iter()
+            .filter(|(agent, _)| args.agent_id.as_deref().is_none_or(|wanted| wanted == agent.as_str()))
+            .collect::<Vec<_>>();
+        agent_ids.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut agents = Vec::with_capacity(agent_ids.len());
+        for (agent, usage) in agent_ids {
+            let (mut stores, mut recalls, mut forgets, mut bytes) = (0_u64, 0_u64, 0_u64, 0_u64);
+            for event in usage.events.iter().filter(|e| e.ts_ms >= since) {
+                match event.op {
+                    UsageOp::Store => stores += 1,
+                    UsageOp::Recall => recalls += 1,
+                    UsageOp::Forget => forgets += 1,
+                }
+                bytes = bytes.saturating_add(event.bytes);
             }
+            let window_truncated =
+                usage.events.len() >= locked.max_events() && usage.events.front().is_some_and(|e| e.ts_ms > since);
+            let ops_per_min = (stores + recalls + forgets) as f64 / (window_ms as f64 / 60_000.0);
+            agents.push(json!({
+                "agent_id": agent,
+                "window": {
+                    "stores": stores,
+                    "recalls": recalls,
+                    "forgets": forgets,
+                    "bytes_written": bytes,
+                    "ops_per_min": ops_per_min,
+                    "truncated": window_truncated
+                },
+                "totals": {
+                    "stores": usage.stores,
+                    "recalls": usage.recalls,
+                    "forgets": usage.forgets,
+                    "bytes_written": usage.bytes_written
+                },
+                "quota": {
+                    "bytes_limit": quota,
+                    "bytes_used": usage.bytes_written,
+                    "ratio": quota.map(|limit| usage.bytes_written as f64 / limit as f64)
+                }
+            }));
         }
-
-        let locked = self.store.lock();
-
-        let backend_stats = locked.stats();
-        let rows = locked.list(200_000);
-        let filtered = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), None);
-
-        let mut scope_counts: HashMap<String, usize> = HashMap::new();
-        let mut category_counts: HashMap<String, usize> = HashMap::new();
-        for entry in &filtered {
-            *scope_counts.entry(entry.scope.clone()).or_insert(0) += 1;
-            *category_counts.entry(entry.category.clone()).or_insert(0) += 1;
-        }
-        let decision_ratio = if filtered.is_empty() {
-            0.0
-        } else {
-            (*category_counts.get("decision").unwrap_or(&0) as f32) / (filtered.len() as f32)
-        };
+        drop(locked);
 
         JsonRpcResponse::success(
             id,
             json!({
                 "structuredContent": {
-                    "count": filtered.len(),
-                    "decision_ratio": decision_ratio,
-                    "scope_counts": scope_counts,
-                    "category_counts": category_counts,
-                    "agent_id": self.scopes.agent_id,
-                    "allowed_scopes": self.scopes.accessible_scopes(),
-                    "standardization": {
-                        "profile": self.standards.profile_label(),
-                        "default_tags": {
-                            "project": self.standards.default_project_tag.clone(),
-                            "tool": self.standards.default_tool_tag.clone(),
-                            "domain": self.standards.default_domain_tag.clone()
-                        }
-                    },
-                    "backend_stats": backend_stats
+                    "window_ms": window_ms,
+                    "since_ms": since,
+                    "agent_count": agents.len(),
+                    "agents": agents
                 },
                 "content": [{
                     "type":"text",
-                    "text": format!("stats count={}, decision_ratio={:.3}", filtered.len(), decision_ratio)
+                    "text": format!("usage report for {} agents over {} ms", agents.len(), window_ms)
                 }]
             }),
         )
@@ -2030,14 +8549,42 @@ impl McpServer {
         }
 
         let limit = args.limit.unwrap_or(20).clamp(1, 100);
-        let offset = args.offset.unwrap_or(0).min(20_000);
-        let fetch = (offset + limit).min(200_000);
-
-        let locked = self.store.lock();
+        let cursor = match args.cursor.as_deref().map(ListCursor::decode) {
+            Some(Some(v)) => Some(v),
+            Some(None) => return JsonRpcResponse::error(id, -32602, "invalid list cursor"),
+            None => None,
+        };
+        // A cursor pins the position by (timestamp, id), so offset is ignored when one is given.
+        let offset = if cursor.is_some() {
+            0
+        } else {
+            args.offset.unwrap_or(0).min(20_000)
+        };
 
-        let rows = locked.list(fetch);
-        let filtered = filter_entries_by_acl(rows, &self.scopes, args.scope.as_deref(), args.category.as_deref());
-        let items = filtered.into_iter().skip(offset).take(limit).collect::<Vec<_>>();
+        let snapshot = self.store.read().snapshot();
+        let mut filtered = filter_entries_by_acl(
+            snapshot.list(usize::MAX),
+            &self.scopes,
+            args.scope.as_deref(),
+            args.category.as_deref(),
+        );
+        if let Some(filter) = args.source.and_then(MemorySource::normalized) {
+            filtered.retain(|e| matches_source(e, &filter));
+        }
+        filtered.sort_by(|a, b| ListCursor::of(b).cmp(&ListCursor::of(a)));
+        let mut items = filtered
+            .into_iter()
+            .filter(|e| cursor.as_ref().is_none_or(|c| ListCursor::of(e) < *c))
+            .skip(offset)
+            .take(limit + 1)
+            .collect::<Vec<_>>();
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+        let next_cursor = if has_more {
+            items.last().map(|e| ListCursor::of(e).encode())
+        } else {
+            None
+        };
 
         JsonRpcResponse::success(
             id,
@@ -2046,6 +8593,8 @@ impl McpServer {
                     "count": items.len(),
                     "offset": offset,
                     "limit": limit,
+                    "next_cursor": next_cursor,
+                    "generation": snapshot.generation(),
                     "items": items
                 },
                 "content": [{
@@ -2067,6 +8616,17 @@ impl McpServer {
             mu: args.mu.unwrap_or(0.2),
         };
 
+        match args.mode.as_deref() {
+            None | Some("scores") => {}
+            Some("ranking") => return self.evolve_ranking(id, args, policy),
+            Some(other) => {
+                return JsonRpcResponse::error(id, -32602, format!("mode must be scores|ranking, got {other}"));
+            }
+        }
+        let Some(parent_score) = args.parent_score else {
+            return JsonRpcResponse::error(id, -32602, "parent_score is required in scores mode");
+        };
+
         let candidates = args
             .candidates
             .into_iter()
@@ -2080,7 +8640,7 @@ impl McpServer {
             })
             .collect::<Vec<_>>();
 
-        let decision = EvolutionRunner::new(policy).run_generation(args.parent_score, &candidates);
+        let decision = EvolutionRunner::new(policy).run_generation(parent_score, &candidates);
 
         JsonRpcResponse::success(
             id,
@@ -2105,6 +8665,162 @@ impl McpServer {
         )
     }
 
+    /// Ranking-mode evolution: replays a saved eval baseline's queries against the active
+    /// `RankingConfig` (the parent) and each variant, scoring MRR on a train/holdout split.
+    ///
+    /// The parent score is the active config's holdout MRR; a variant's train score is passed to
+    /// the runner as its improvement over the parent's train MRR, so acceptance still means "better
+    /// on both splits". A variant that loses holdout hits the parent found violates constraints.
+    /// The experiment holds the store lock so concurrent recalls never see a variant config.
+    fn evolve_ranking(&self, id: Value, args: MemoryEvolveInput, policy: EvolutionPolicy) -> JsonRpcResponse {
+        if args.variants.is_empty() || args.variants.len() > 16 {
+            return JsonRpcResponse::error(id, -32602, "variants must contain between 1 and 16 ranking configs");
+        }
+        let name = args
+            .baseline
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| "default".to_string());
+        let mut baselines = match self.baselines.load() {
+            Ok(v) => v,
+            Err(err) => return JsonRpcResponse::error(id, -32001, err),
+        };
+        let Some(baseline) = baselines.remove(&name) else {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("no eval baseline named {name}; save one with memory_eval_baseline first"),
+            );
+        };
+        if let Some(scope) = self.denied_eval_scope(&baseline.cases) {
+            return JsonRpcResponse::error(id, -32602, format!("scope access denied: {scope}"));
+        }
+        if baseline.cases.len() < 2 {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("eval baseline {name} needs at least 2 cases to split into train and holdout"),
+            );
+        }
+        let holdout_ratio = args.holdout_ratio.unwrap_or(0.3);
+        if !(0.0..1.0).contains(&holdout_ratio) {
+            return JsonRpcResponse::error(id, -32602, "holdout_ratio must be in [0, 1)");
+        }
+        let (train, holdout) = eval::split_holdout(&baseline.cases, holdout_ratio);
+        let (train_vectors, holdout_vectors) = match (
+            self.eval_query_embeddings(&train, baseline.use_vector),
+            self.eval_query_embeddings(&holdout, baseline.use_vector),
+        ) {
+            (Ok(train_vectors), Ok(holdout_vectors)) => (train_vectors, holdout_vectors),
+            (Err(msg), _) | (_, Err(msg)) => return JsonRpcResponse::error(id, -32002, msg),
+        };
+
+        // Every config is scored against the same snapshot, so writes during the run cannot
+        // favour one variant over another.
+        let active = self.ranking_config();
+        let snapshot = self.store.read().snapshot();
+        let run = |config: RankingConfig| {
+            with_ranking_config(config, || {
+                let train_scores = self.score_eval_cases(&snapshot, &train, &train_vectors, baseline.k);
+                let holdout_scores = self.score_eval_cases(&snapshot, &holdout, &holdout_vectors, baseline.k);
+                RankingTrial {
+                    train: eval::mrr_score(&train_scores),
+                    holdout: eval::mrr_score(&holdout_scores),
+                    holdout_hits: holdout_scores.iter().filter(|s| s.first_hit_rank.is_some()).count(),
+                }
+            })
+        };
+        let parent = run(active);
+        let scored = args.variants.iter().map(|v| run(v.config)).collect::<Vec<_>>();
+
+        let candidates = args
+            .variants
+            .iter()
+            .zip(&scored)
+            .map(|(variant, trial)| VariantCandidate {
+                id: variant.id.clone(),
+                score_train: parent.holdout + (trial.train - parent.train),
+                score_holdout: trial.holdout,
+                cost_penalty: variant.cost_penalty,
+                risk_penalty: variant.risk_penalty,
+                constraints_satisfied: trial.holdout_hits >= parent.holdout_hits,
+            })
+            .collect::<Vec<_>>();
+        let decision = EvolutionRunner::new(policy).run_generation(parent.holdout, &candidates);
+
+        let dry_run = args.dry_run.unwrap_or(false);
+        let accepted = decision
+            .accepted_variant_id
+            .as_ref()
+            .and_then(|accepted| args.variants.iter().find(|v| &v.id == accepted));
+        let applied = if let Some(variant) = accepted.filter(|_| !dry_run) {
+            let record = ActiveRanking {
+                config: variant.config,
+                variant_id: variant.id.clone(),
+                baseline: name.clone(),
+                effective_score: decision.effective_score,
+                accepted_ms: now_ms(),
+            };
+            if let Err(err) = self.active_ranking.save(&record) {
+                return JsonRpcResponse::error(id, -32001, err);
+            }
+            *self.ranking.write() = variant.config;
+            true
+        } else {
+            false
+        };
+
+        let variants = args
+            .variants
+            .iter()
+            .zip(&scored)
+            .zip(&candidates)
+            .map(|((variant, trial), candidate)| {
+                json!({
+                    "id": variant.id,
+                    "config": variant.config,
+                    "train": trial.train,
+                    "holdout": trial.holdout,
+                    "holdout_hits": trial.holdout_hits,
+                    "constraints_satisfied": candidate.constraints_satisfied
+                })
+            })
+            .collect::<Vec<_>>();
+        JsonRpcResponse::success(
+            id,
+            json!({
+                "content": [{
+                    "type": "text",
+                    "text": format!(
+                        "ranking evolve on {name}: accepted_variant={:?}, parent_holdout={:.4}, effective_score={:.4}, applied={applied}",
+                        decision.accepted_variant_id,
+                        parent.holdout,
+                        decision.effective_score
+                    )
+                }],
+                "structuredContent": {
+                    "mode": "ranking",
+                    "baseline": name,
+                    "k": baseline.k,
+                    "split": {"train": train.len(), "holdout": holdout.len()},
+                    "parent": {
+                        "config": active,
+                        "train": parent.train,
+                        "holdout": parent.holdout,
+                        "holdout_hits": parent.holdout_hits
+                    },
+                    "variants": variants,
+                    "accepted_variant_id": decision.accepted_variant_id,
+                    "effective_score": decision.effective_score,
+                    "reason": decision.reason,
+                    "dry_run": dry_run,
+                    "applied": applied,
+                    "active": self.ranking_config()
+                }
+            }),
+        )
+    }
+
     fn exec_memory_skill_manifest(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
         let args: MemorySkillManifestInput = match parse_args_optional(arguments) {
             Ok(v) => v,
@@ -2149,11 +8865,81 @@ impl McpServer {
         )
     }
 
+    fn exec_memory_tool_schemas(&self, id: Value, arguments: Option<Value>) -> JsonRpcResponse {
+        let args: MemoryToolSchemasInput = match parse_args_optional(arguments) {
+            Ok(v) => v,
+            Err(resp) => return with_id(resp, id),
+        };
+        match self.export_tool_schemas(args.format.as_deref(), args.tools.as_deref()) {
+            Ok(exported) => JsonRpcResponse::success(
+                id,
+                json!({
+                    "structuredContent": exported,
+                    "content": [{
+                        "type": "text",
+                        "text": format!("exported {} tool schemas", exported.get("count").and_then(Value::as_u64).unwrap_or(0))
+                    }]
+                }),
+            ),
+            Err(message) => JsonRpcResponse::error(id, -32602, message),
+        }
+    }
+
+    /// `{format, count, tools}` for every tool except this export itself.
+    fn export_tool_schemas(&self, format: Option<&str>, only: Option<&[String]>) -> Result<Value, String> {
+        let format = SchemaFormat::parse(format)?;
+        let listed = self.tools_list_result();
+        let tools = listed
+            .get("tools")
+            .and_then(Value::as_array)
+            .map(|tools| {
+                tools
+                    .iter()
+                    .filter(|tool| tool.get("name").and_then(Value::as_str) != Some("memory_tool_schemas"))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let exported = tool_schemas::export(&tools, format, only)?;
+        Ok(json!({"format": format.as_str(), "count": exported.len(), "tools": exported}))
+    }
+
     pub fn serve_stdio(&self) -> io::Result<()> {
+        // Frames are read on a separate thread so cancel notifications reach in-flight calls
+        // while the main loop is still busy with the request they target.
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::scope(|scope| {
+            let _watch = self.exit_on_signal(scope, None)?;
+            let reader = scope.spawn(move || self.read_stdio_frames(&tx));
+            let mut stdout = io::stdout();
+            // One client per stdio process, so its `initialize` names the caller for the whole run.
+            let mut caller = None;
+            for message in rx {
+                let response = match message {
+                    StdioMessage::Request(request, frame) => {
+                        if request.method == "initialize" {
+                            let name = request.params.pointer("/clientInfo/name").and_then(Value::as_str);
+                            caller = self.scopes.requested_agent(name).ok().flatten().or(caller);
+                        }
+                        with_caller_agent(caller.clone(), || self.handle_request(request)).map(|r| (r, frame))
+                    }
+                    StdioMessage::Reply(response, frame) => Some((response, frame)),
+                };
+                if let Some((response, frame)) = response {
+                    write_stdio_response(&mut stdout, &response, frame)?;
+                }
+            }
+            reader
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("stdio reader thread panicked")))
+        })
+    }
+
+    fn read_stdio_frames(&self, tx: &std::sync::mpsc::Sender<StdioMessage>) -> io::Result<()> {
         let stdin = io::stdin();
         let mut reader = io::BufReader::new(stdin.lock());
-        let mut stdout = io::stdout();
         let mut line = String::new();
+        let reply = |response, frame| tx.send(StdioMessage::Reply(response, frame)).is_ok();
 
         loop {
             line.clear();
@@ -2172,7 +8958,9 @@ impl McpServer {
                     Err(err) => {
                         let response =
                             JsonRpcResponse::error(Value::Null, -32700, format!("invalid stdio frame: {err}"));
-                        write_stdio_response(&mut stdout, &response, StdioFrame::LineDelimited)?;
+                        if !reply(response, StdioFrame::LineDelimited) {
+                            break;
+                        }
                         continue;
                     }
                 };
@@ -2181,7 +8969,9 @@ impl McpServer {
                 if let Err(err) = reader.read_exact(&mut body) {
                     let response =
                         JsonRpcResponse::error(Value::Null, -32700, format!("invalid stdio frame body: {err}"));
-                    write_stdio_response(&mut stdout, &response, StdioFrame::ContentLength)?;
+                    if !reply(response, StdioFrame::ContentLength) {
+                        break;
+                    }
                     continue;
                 }
                 (body, StdioFrame::ContentLength)
@@ -2189,17 +8979,18 @@ impl McpServer {
                 (trimmed.as_bytes().to_vec(), StdioFrame::LineDelimited)
             };
 
-            let request: JsonRpcRequest = match serde_json::from_slice(&payload) {
-                Ok(v) => v,
-                Err(err) => {
-                    let response = JsonRpcResponse::error(Value::Null, -32700, format!("parse error: {err}"));
-                    write_stdio_response(&mut stdout, &response, frame)?;
-                    continue;
-                }
+            let sent = match serde_json::from_slice::<JsonRpcRequest>(&payload) {
+                Ok(request) if is_cancel_method(&request.method) => self
+                    .handle_request(request)
+                    .is_none_or(|response| reply(response, frame)),
+                Ok(request) => tx.send(StdioMessage::Request(request, frame)).is_ok(),
+                Err(err) => reply(
+                    JsonRpcResponse::error(Value::Null, -32700, format!("parse error: {err}")),
+                    frame,
+                ),
             };
-
-            if let Some(response) = self.handle_request(request) {
-                write_stdio_response(&mut stdout, &response, frame)?;
+            if !sent {
+                break;
             }
         }
 
@@ -2207,36 +8998,140 @@ impl McpServer {
     }
 
     pub fn serve_http(&self, addr: &str) -> io::Result<()> {
+        let tls = crate::tls::server_config_from_env()?;
         let listener = TcpListener::bind(addr)?;
-        eprintln!("prx-memory-mcp http listening on {}", listener.local_addr()?);
-        for stream in listener.incoming() {
-            match stream {
-                Ok(stream) => {
-                    if let Err(err) = self.handle_http_connection(stream) {
-                        eprintln!("prx-memory-mcp http request error: {err}");
-                    }
+        tracing::info!(
+            scheme = if tls.is_some() { "https" } else { "http" },
+            addr = %listener.local_addr()?,
+            "prx-memory-mcp listening"
+        );
+        let local_addr = listener.local_addr()?;
+        // Keep-alive connections stay open between requests, so each one gets its own thread;
+        // `PRX_MEMORY_HTTP_MAX_CONNECTIONS` caps how many are open at once.
+        let max_connections = env_usize("PRX_MEMORY_HTTP_MAX_CONNECTIONS", 256, 1, 65_536);
+        let open_connections = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            let _watch = self.exit_on_signal(scope, Some(local_addr))?;
+            let reap_every = Duration::from_millis(env_usize("PRX_MEMORY_SESSION_REAP_MS", 5_000, 100, 600_000) as u64);
+            scope.spawn(move || {
+                loop {
+                    std::thread::sleep(reap_every);
+                    self.reap_sessions();
                 }
-                Err(err) => {
-                    eprintln!("prx-memory-mcp http accept error: {err}");
+            });
+            for stream in listener.incoming() {
+                if self.shutting_down.load(AtomicOrdering::Relaxed) {
+                    break;
+                }
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        tracing::warn!(error = %err, "http accept failed");
+                        continue;
+                    }
+                };
+                if open_connections.fetch_add(1, AtomicOrdering::AcqRel) >= max_connections {
+                    open_connections.fetch_sub(1, AtomicOrdering::AcqRel);
+                    self.reject_http_connection(stream, tls.is_some());
+                    continue;
                 }
+                let tls = tls.clone();
+                let open_connections = &open_connections;
+                scope.spawn(move || {
+                    if let Err(err) = self.accept_http_connection(stream, tls) {
+                        tracing::warn!(error = %err, "http connection failed");
+                    }
+                    open_connections.fetch_sub(1, AtomicOrdering::AcqRel);
+                });
             }
+            drop(listener);
+            Ok(())
+        })
+    }
+
+    /// Turns away a connection past `PRX_MEMORY_HTTP_MAX_CONNECTIONS` on the accept thread. Plain
+    /// HTTP clients get a 503 after whatever request bytes already arrived are discarded; TLS
+    /// connections are closed before the handshake.
+    fn reject_http_connection(&self, mut stream: TcpStream, tls: bool) {
+        {
+            let mut locked = self.metrics.lock();
+            locked.http_connections_rejected = locked.http_connections_rejected.saturating_add(1);
+        }
+        tracing::debug!("http connection limit reached; rejecting connection");
+        if tls {
+            return;
+        }
+        // Closing with unread input resets the connection, which can drop the 503 before the
+        // client reads it.
+        let mut discard = [0u8; 4096];
+        if stream.set_nonblocking(true).is_ok() {
+            while matches!(stream.read(&mut discard), Ok(n) if n > 0) {}
+        }
+        let response = HttpResponse::json(503, json!({"error":"overloaded","message":"too many open connections"}));
+        let written = stream
+            .set_nonblocking(false)
+            .and_then(|()| stream.set_write_timeout(Some(Duration::from_secs(1))))
+            .and_then(|()| write_http_response(&mut stream, response, false));
+        if let Err(err) = written {
+            tracing::debug!(error = %err, "failed to write connection limit response");
         }
-        Ok(())
     }
 
-    fn handle_http_connection(&self, mut stream: TcpStream) -> io::Result<()> {
-        let Some(req) = read_http_request(&stream)? else {
-            return Ok(());
+    fn accept_http_connection(&self, mut stream: TcpStream, tls: Option<Arc<rustls::ServerConfig>>) -> io::Result<()> {
+        let idle_timeout = Duration::from_millis(env_usize("PRX_MEMORY_HTTP_KEEPALIVE_MS", 5_000, 100, 300_000) as u64);
+        stream.set_read_timeout(Some(idle_timeout))?;
+        let Some(config) = tls else {
+            return self.handle_http_connection(&mut stream);
         };
-        if self.is_sse_stream_request(&req) {
-            // Enforce auth on SSE streams
-            if let Some(rejection) = check_bearer_auth(&req) {
-                return write_http_response(&mut stream, rejection);
+        let conn = rustls::ServerConnection::new(config).map_err(io::Error::other)?;
+        let mut tls_stream = rustls::StreamOwned::new(conn, stream);
+        self.handle_http_connection(&mut tls_stream)?;
+        // Skipped on errors: flushing a failed session would block on the peer until the idle timeout.
+        tls_stream.conn.send_close_notify();
+        tls_stream.flush()
+    }
+
+    fn handle_http_connection<S: Read + Write>(&self, stream: &mut S) -> io::Result<()> {
+        let max_requests = env_usize("PRX_MEMORY_HTTP_MAX_REQUESTS_PER_CONN", 100, 1, 100_000);
+        let mut reader = io::BufReader::new(stream);
+        // Pipelined requests queue up in `reader` and are answered in order.
+        for served in 1..=max_requests {
+            let req = match read_http_request(&mut reader) {
+                Ok(Some(req)) => req,
+                Ok(None) => return Ok(()),
+                Err(err) if is_idle_timeout(&err) => return Ok(()),
+                // The rest of the stream can't be framed after a malformed request, so close it.
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                    let response = HttpResponse::json(
+                        400,
+                        json!({
+                            "error": "invalid_request",
+                            "message": err.to_string()
+                        }),
+                    );
+                    return write_http_response(reader.get_mut(), response, false);
+                }
+                Err(err) => return Err(err),
+            };
+            if self.is_sse_stream_request(&req) {
+                // Enforce auth on SSE streams
+                let tenant = self
+                    .authenticate_http(&req)
+                    .and_then(|label| self.tenant_server(&req, label));
+                return match tenant {
+                    Ok(Some(server)) => server.handle_http_stream_sse(reader.get_mut(), req),
+                    Ok(None) => self.handle_http_stream_sse(reader.get_mut(), req),
+                    Err(rejection) => write_http_response(reader.get_mut(), rejection, false),
+                };
+            }
+            let keep_alive = req.keep_alive && served < max_requests;
+            let response = self.dispatch_http_request(req);
+            write_http_response(reader.get_mut(), response, keep_alive)?;
+            if !keep_alive {
+                break;
             }
-            return self.handle_http_stream_sse(&mut stream, req);
         }
-        let response = self.dispatch_http_request(req);
-        write_http_response(&mut stream, response)
+        Ok(())
     }
 
     fn is_sse_stream_request(&self, req: &HttpRequest) -> bool {
@@ -2257,7 +9152,7 @@ impl McpServer {
             .unwrap_or(false)
     }
 
-    fn handle_http_stream_sse(&self, stream: &mut TcpStream, req: HttpRequest) -> io::Result<()> {
+    fn handle_http_stream_sse(&self, stream: &mut impl Write, req: HttpRequest) -> io::Result<()> {
         let Some(session_id) = req.query.get("session").cloned() else {
             return write_http_response(
                 stream,
@@ -2265,6 +9160,7 @@ impl McpServer {
                     400,
                     json!({"error":"invalid_request","message":"missing query param: session"}),
                 ),
+                false,
             );
         };
         let from = req.query.get("from").and_then(|v| v.parse::<u64>().ok()).unwrap_or(1);
@@ -2288,11 +9184,12 @@ impl McpServer {
             .unwrap_or(3_000)
             .clamp(100, 10_000);
 
+        self.poll_resource_changes();
         let mut page = match self.collect_session_events(&session_id, from, limit, ack) {
             Ok(v) => v,
             Err(err) => {
                 self.record_session_access_error(err);
-                return write_http_response(stream, session_error_response(err));
+                return write_http_response(stream, session_error_response(err), false);
             }
         };
         ack = None;
@@ -2309,6 +9206,7 @@ impl McpServer {
 
         while remaining > 0 && Instant::now() < deadline {
             std::thread::sleep(Duration::from_millis(100));
+            self.poll_resource_changes();
             page = match self.collect_session_events(&session_id, next_from, remaining, ack) {
                 Ok(v) => v,
                 Err(err) => {
@@ -2349,53 +9247,178 @@ impl McpServer {
     fn dispatch_http_request(&self, req: HttpRequest) -> HttpResponse {
         // Public endpoints (no auth required)
         if req.method == "GET" && req.path == "/health" {
+            if self.draining.load(AtomicOrdering::Relaxed) {
+                return HttpResponse::json(503, json!({"status":"draining"}));
+            }
             return HttpResponse::json(200, json!({"status":"ok"}));
         }
 
         if req.method == "GET" && req.path == "/metrics" {
-            return HttpResponse::text(
-                200,
-                "text/plain; version=0.0.4; charset=utf-8",
-                self.render_metrics_text(),
-            );
+            let text = match self.metrics_tenants(&req) {
+                Ok(None) => self.render_metrics_text(),
+                Ok(Some(tenants)) => {
+                    let mut sources = vec![("default".to_string(), self.render_metrics_text())];
+                    sources.extend(
+                        tenants
+                            .into_iter()
+                            .map(|(tenant, server)| (tenant, server.render_metrics_text())),
+                    );
+                    merge_metrics(&sources)
+                }
+                Err(rejection) => return rejection,
+            };
+            return HttpResponse::text(200, "text/plain; version=0.0.4; charset=utf-8", text);
         }
 
         if req.method == "GET" && req.path == "/metrics/summary" {
-            return HttpResponse::json(200, self.render_metrics_summary());
+            let mut summary = self.render_metrics_summary();
+            match self.metrics_tenants(&req) {
+                Ok(Some(tenants)) => {
+                    let per_tenant = tenants
+                        .into_iter()
+                        .map(|(tenant, server)| (tenant, server.render_metrics_summary()))
+                        .collect::<serde_json::Map<_, _>>();
+                    if let Some(obj) = summary.as_object_mut() {
+                        obj.insert("tenants".to_string(), Value::Object(per_tenant));
+                    }
+                }
+                Ok(None) => {}
+                Err(rejection) => return rejection,
+            }
+            return HttpResponse::json(200, summary);
+        }
+
+        // Authenticate all MCP endpoints when PRX_MEMORY_HTTP_TOKENS is configured
+        let token_label = match self.authenticate_http(&req) {
+            Ok(label) => label,
+            Err(rejection) => return rejection,
+        };
+
+        let requested = req
+            .headers
+            .get(AGENT_HEADER)
+            .or_else(|| req.query.get("agent").filter(|_| req.path == "/mcp/session/start"));
+        let caller = match self.scopes.requested_agent(requested.map(String::as_str)) {
+            Ok(caller) => caller,
+            Err(message) => return HttpResponse::json(400, json!({"error":"invalid_request","message": message})),
+        };
+        if let Some(agent) = &caller
+            && let Err(message) = self.scopes.authorize_agent(agent, token_label)
+        {
+            return HttpResponse::json(403, json!({"error":"forbidden","message": message}));
+        }
+        with_caller_agent(caller, || self.dispatch_authenticated_request(req, token_label))
+    }
+
+    /// Routes a request that passed auth, on behalf of the caller agent already in effect.
+    fn dispatch_authenticated_request(&self, req: HttpRequest, token_label: Option<&'static str>) -> HttpResponse {
+        match self.tenant_server(&req, token_label) {
+            Ok(Some(server)) => return server.dispatch_http_request(req),
+            Ok(None) => {}
+            Err(rejection) => return rejection,
         }
 
-        // Authenticate all MCP endpoints when PRX_MEMORY_AUTH_TOKEN is configured
-        if let Some(rejection) = check_bearer_auth(&req) {
-            return rejection;
+        if req.path.starts_with("/admin/") {
+            return self.dispatch_admin_request(&req, token_label);
+        }
+
+        if req.path == "/changes" {
+            return self.serve_changes(&req, token_label);
+        }
+
+        if req.path == "/v1/memories" || req.path.starts_with("/v1/memories/") {
+            return self.dispatch_rest_request(&req);
+        }
+
+        if req.method == "GET" && req.path == "/tools/schemas" {
+            let only = req
+                .query
+                .get("tools")
+                .map(|raw| raw.split(',').map(|name| name.trim().to_string()).collect::<Vec<_>>());
+            return match self.export_tool_schemas(req.query.get("format").map(String::as_str), only.as_deref()) {
+                Ok(exported) => HttpResponse::json(200, exported),
+                Err(message) => HttpResponse::json(400, json!({"error":"invalid_request","message": message})),
+            };
         }
 
         if req.method == "POST" && req.path == "/mcp/session/start" {
-            let (session_id, lease_expires_ms) = self.create_session();
+            if self.draining.load(AtomicOrdering::Relaxed) {
+                return HttpResponse::json(
+                    503,
+                    json!({"error":"draining","message":"server is draining; start the session on another instance"}),
+                );
+            }
+            let request = match admin_body(&req).and_then(|body| {
+                serde_json::from_value::<SessionStartRequest>(body).map_err(|e| format!("invalid session request: {e}"))
+            }) {
+                Ok(v) => v,
+                Err(message) => {
+                    return HttpResponse::json(400, json!({"error":"invalid_request","message": message}));
+                }
+            };
+            if let Some(unknown) = request
+                .notifications
+                .iter()
+                .flatten()
+                .find(|kind| !NOTICE_KINDS.contains(&kind.as_str()))
+            {
+                return HttpResponse::json(
+                    400,
+                    json!({
+                        "error": "invalid_request",
+                        "message": format!("unknown notification kind: {unknown} (expected one of {})", NOTICE_KINDS.join(", "))
+                    }),
+                );
+            }
+            let notifications = request
+                .notifications
+                .map(|kinds| kinds.into_iter().collect::<BTreeSet<_>>());
+            let (session_id, lease_expires_ms) = self.create_session(notifications.clone());
             return HttpResponse::json(
                 200,
                 json!({
                     "session_id": session_id,
                     "lease_ttl_ms": Self::session_ttl_ms(),
-                    "lease_expires_ms": lease_expires_ms
+                    "lease_expires_ms": lease_expires_ms,
+                    "notifications": notifications.map_or_else(|| NOTICE_KINDS.iter().map(ToString::to_string).collect(), |kinds| kinds.into_iter().collect::<Vec<_>>())
                 }),
             );
         }
 
-        if req.method == "POST" && req.path == "/mcp/session/renew" {
+        if req.method == "POST" && req.path == "/mcp/session/renew" {
+            let Some(session_id) = req.query.get("session").cloned() else {
+                return HttpResponse::json(
+                    400,
+                    json!({"error":"invalid_request","message":"missing query param: session"}),
+                );
+            };
+            return match self.renew_session_lease(&session_id) {
+                Ok(expires) => HttpResponse::json(
+                    200,
+                    json!({
+                        "session_id": session_id,
+                        "lease_ttl_ms": Self::session_ttl_ms(),
+                        "lease_expires_ms": expires
+                    }),
+                ),
+                Err(err) => {
+                    self.record_session_access_error(err);
+                    session_error_response(err)
+                }
+            };
+        }
+
+        if req.method == "POST" && req.path == "/mcp/session/close" {
             let Some(session_id) = req.query.get("session").cloned() else {
                 return HttpResponse::json(
                     400,
                     json!({"error":"invalid_request","message":"missing query param: session"}),
                 );
             };
-            return match self.renew_session_lease(&session_id) {
-                Ok(expires) => HttpResponse::json(
+            return match self.close_session(&session_id) {
+                Ok(promotion) => HttpResponse::json(
                     200,
-                    json!({
-                        "session_id": session_id,
-                        "lease_ttl_ms": Self::session_ttl_ms(),
-                        "lease_expires_ms": expires
-                    }),
+                    json!({"session_id": session_id, "closed": true, "promotion": promotion}),
                 ),
                 Err(err) => {
                     self.record_session_access_error(err);
@@ -2420,7 +9443,12 @@ impl McpServer {
                     );
                 }
             };
-            let payload = match self.handle_request(rpc) {
+            let response = if matches!(rpc.method.as_str(), "resources/subscribe" | "resources/unsubscribe") {
+                Some(self.handle_resource_subscription(&session_id, rpc))
+            } else {
+                self.handle_session_request(rpc, Some(&session_id), token_label)
+            };
+            let payload = match response {
                 Some(v) => match serde_json::to_value(v) {
                     Ok(payload) => payload,
                     Err(_) => {
@@ -2466,6 +9494,7 @@ impl McpServer {
                 .unwrap_or(50)
                 .clamp(1, 500);
             let ack = req.query.get("ack").and_then(|v| v.parse::<u64>().ok());
+            self.poll_resource_changes();
             match self.collect_session_events(&session_id, from, limit, ack) {
                 Ok(page) => {
                     return HttpResponse::json(
@@ -2498,7 +9527,7 @@ impl McpServer {
         if req.method != "POST" {
             return HttpResponse::json(
                 405,
-                json!({"error":"method_not_allowed","message":"supported endpoints: GET /health, GET /metrics, GET /metrics/summary, POST /mcp, POST /mcp/session/start, POST /mcp/session/renew, POST/GET /mcp/stream"}),
+                json!({"error":"method_not_allowed","message":"supported endpoints: GET /health, GET /metrics, GET /metrics/summary, POST /mcp, POST /mcp/session/start, POST /mcp/session/renew, POST /mcp/session/close, POST/GET /mcp/stream, /admin/*"}),
             );
         }
 
@@ -2559,6 +9588,7 @@ struct HttpRequest {
     query: HashMap<String, String>,
     headers: HashMap<String, String>,
     body: Vec<u8>,
+    keep_alive: bool,
 }
 
 struct HttpResponse {
@@ -2586,36 +9616,167 @@ impl HttpResponse {
     }
 }
 
-/// Checks Bearer token authentication for protected HTTP endpoints.
+/// Bearer tokens accepted by the HTTP transport, each paired with the label used in metrics.
 ///
-/// If `PRX_MEMORY_AUTH_TOKEN` is set in the environment, validates the
-/// `Authorization: Bearer <token>` header against it. Returns `None` when
-/// auth passes (or when no token is configured), or `Some(HttpResponse)` with
-/// a 401 rejection.
-fn check_bearer_auth(req: &HttpRequest) -> Option<HttpResponse> {
-    static AUTH_TOKEN: OnceLock<Option<String>> = OnceLock::new();
-    let expected = AUTH_TOKEN.get_or_init(|| std::env::var("PRX_MEMORY_AUTH_TOKEN").ok().filter(|s| !s.is_empty()));
-    let Some(expected_token) = expected else {
-        return None; // No token configured, allow all
+/// `PRX_MEMORY_HTTP_TOKENS` is a comma-separated list of `label:token` or bare
+/// `token` entries; bare tokens are labelled `token-<n>` by position so the
+/// secret never reaches `/metrics`. The legacy `PRX_MEMORY_AUTH_TOKEN` is
+/// accepted under the label `default`.
+fn http_auth_tokens() -> &'static [(String, String)] {
+    static TOKENS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+    TOKENS.get_or_init(|| {
+        let mut tokens = parse_http_tokens(&std::env::var("PRX_MEMORY_HTTP_TOKENS").unwrap_or_default());
+        if let Ok(legacy) = std::env::var("PRX_MEMORY_AUTH_TOKEN")
+            && !legacy.trim().is_empty()
+        {
+            tokens.push(("default".to_string(), legacy.trim().to_string()));
+        }
+        tokens
+    })
+}
+
+/// Parses `label:token` and bare `token` entries. The text before the first `:` is only a label
+/// when it is made of `a-z`, `0-9`, `-` and `_` and a token follows; otherwise the whole entry is
+/// a bare token, so tokens may contain `:`.
+fn parse_http_tokens(raw: &str) -> Vec<(String, String)> {
+    let is_label = |label: &str| {
+        !label.is_empty()
+            && label
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
     };
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .enumerate()
+        .filter_map(|(idx, entry)| {
+            let (label, token) = match entry.split_once(':') {
+                Some((label, token)) if is_label(label.trim()) && !token.trim().is_empty() => {
+                    (label.trim().to_string(), token.trim())
+                }
+                _ => (format!("token-{}", idx + 1), entry),
+            };
+            (!token.is_empty()).then(|| (label, token.to_string()))
+        })
+        .collect()
+}
+
+/// Checks Bearer token authentication for protected HTTP endpoints.
+///
+/// Returns `Ok(None)` when no tokens are configured, `Ok(Some(label))` when
+/// the `Authorization: Bearer <token>` header matches a configured token, or
+/// `Err(HttpResponse)` with a 401 rejection.
+fn check_bearer_auth(req: &HttpRequest) -> Result<Option<&'static str>, HttpResponse> {
+    let tokens = http_auth_tokens();
+    if tokens.is_empty() {
+        return Ok(None); // No token configured, allow all
+    }
 
     let auth_header = req.headers.get("authorization");
     let provided = auth_header.and_then(|v| v.strip_prefix("Bearer ").or_else(|| v.strip_prefix("bearer ")));
 
-    match provided {
-        Some(token) if token == expected_token => None,
-        _ => Some(HttpResponse::json(
-            401,
-            json!({
-                "error": "unauthorized",
-                "message": "missing or invalid Bearer token in Authorization header"
-            }),
-        )),
+    provided
+        .and_then(|token| {
+            tokens
+                .iter()
+                .find(|(_, expected)| constant_time_eq(token.trim().as_bytes(), expected.as_bytes()))
+        })
+        .map(|(label, _)| Some(label.as_str()))
+        .ok_or_else(|| {
+            HttpResponse::json(
+                401,
+                json!({
+                    "error": "unauthorized",
+                    "message": "missing or invalid Bearer token in Authorization header"
+                }),
+            )
+        })
+}
+
+/// `PRX_MEMORY_HTTP_ADMIN_TOKENS` lists the token labels allowed on `/admin/*`; unset allows every token.
+fn admin_token_allowed(label: &str) -> bool {
+    static LABELS: OnceLock<Vec<String>> = OnceLock::new();
+    let labels = LABELS.get_or_init(|| {
+        std::env::var("PRX_MEMORY_HTTP_ADMIN_TOKENS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+            .collect()
+    });
+    labels.is_empty() || labels.iter().any(|allowed| allowed == label)
+}
+
+fn admin_body(req: &HttpRequest) -> Result<Value, String> {
+    if req.body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(json!({}));
+    }
+    match serde_json::from_slice::<Value>(&req.body) {
+        Ok(v @ Value::Object(_)) => Ok(v),
+        Ok(_) => Err("request body must be a JSON object".to_string()),
+        Err(err) => Err(format!("invalid JSON body: {err}")),
+    }
+}
+
+/// Maps a tool response onto HTTP: `structuredContent` with `status` on success, otherwise the
+/// JSON-RPC error with a matching status code.
+fn tool_http_response(response: JsonRpcResponse, status: u16) -> HttpResponse {
+    if let Some(err) = response.error {
+        let status = match err.code {
+            -32602 => 400,
+            -32004 | -32603 => 403,
+            -32005 => 429,
+            -32007 => 507,
+            -32002 => 502,
+            _ => 500,
+        };
+        return HttpResponse::json(
+            status,
+            json!({"error": "tool_error", "code": err.code, "message": err.message, "data": err.data}),
+        );
+    }
+    let result = response.result.unwrap_or(Value::Null);
+    HttpResponse::json(status, result.get("structuredContent").cloned().unwrap_or(result))
+}
+
+/// Tool arguments from REST query parameters: `strings` are copied as-is and `counts` must parse
+/// as non-negative integers. Other parameters are ignored.
+fn rest_query_args(query: &HashMap<String, String>, strings: &[&str], counts: &[&str]) -> Result<Value, String> {
+    let mut args = serde_json::Map::new();
+    for key in strings {
+        if let Some(value) = query.get(*key) {
+            args.insert((*key).to_string(), json!(value));
+        }
+    }
+    for key in counts {
+        if let Some(value) = query.get(*key) {
+            let count = value
+                .parse::<usize>()
+                .map_err(|_| format!("{key} must be a non-negative integer, got {value}"))?;
+            args.insert((*key).to_string(), json!(count));
+        }
     }
+    Ok(Value::Object(args))
 }
 
-fn read_http_request(stream: &TcpStream) -> io::Result<Option<HttpRequest>> {
-    let mut reader = io::BufReader::new(stream.try_clone()?);
+/// A backup is a full `memory_export` to disk: embeddings included, written to
+/// `memory-backup-<ms>.json` (under `PRX_MEMORY_DATA_DIR` when set) unless `output_path` is given.
+fn backup_export_args(mut args: Value) -> Value {
+    if let Some(obj) = args.as_object_mut() {
+        obj.entry("include_embeddings").or_insert(Value::Bool(true));
+        obj.entry("limit").or_insert(json!(20_000));
+        obj.entry("output_path")
+            .or_insert_with(|| Value::String(format!("memory-backup-{}.json", now_ms())));
+    }
+    args
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0_u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn read_http_request(reader: &mut impl BufRead) -> io::Result<Option<HttpRequest>> {
     let mut line = String::new();
     if reader.read_line(&mut line)? == 0 {
         return Ok(None);
@@ -2638,9 +9799,10 @@ fn read_http_request(stream: &TcpStream) -> io::Result<Option<HttpRequest>> {
             "invalid http request line (missing path)",
         ));
     };
+    let http_10 = parts.next().is_some_and(|v| v.eq_ignore_ascii_case("HTTP/1.0"));
     let (path, query) = parse_path_query(path_with_query);
 
-    let mut content_length = 0usize;
+    let mut content_length: Option<usize> = None;
     let mut headers = HashMap::new();
     loop {
         let mut header = String::new();
@@ -2654,51 +9816,143 @@ fn read_http_request(stream: &TcpStream) -> io::Result<Option<HttpRequest>> {
         if let Some((name, value)) = header.split_once(':') {
             headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
             if name.trim().eq_ignore_ascii_case("content-length") {
-                content_length = value.trim().parse::<usize>().unwrap_or(0);
+                // A second length, even a matching one, is rejected: proxies may pick a different one.
+                if content_length.is_some() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "duplicate content-length header",
+                    ));
+                }
+                content_length = Some(parse_http_content_length(value.trim())?);
             }
         }
     }
 
-    if content_length > MAX_HTTP_BODY_SIZE {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("request body too large ({content_length} bytes, max {MAX_HTTP_BODY_SIZE})"),
-        ));
-    }
-
-    let mut body = vec![0_u8; content_length];
-    if content_length > 0 {
-        reader.read_exact(&mut body)?;
-    }
+    let body = match headers.get("transfer-encoding") {
+        Some(_) if content_length.is_some() => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "content-length and transfer-encoding are both set",
+            ));
+        }
+        Some(encoding) if encoding.eq_ignore_ascii_case("chunked") => read_chunked_body(reader)?,
+        Some(encoding) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported transfer-encoding: {encoding}"),
+            ));
+        }
+        None => {
+            let content_length = content_length.unwrap_or(0);
+            if content_length > MAX_HTTP_BODY_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("request body too large ({content_length} bytes, max {MAX_HTTP_BODY_SIZE})"),
+                ));
+            }
+            let mut body = vec![0_u8; content_length];
+            if content_length > 0 {
+                reader.read_exact(&mut body)?;
+            }
+            body
+        }
+    };
+    let connection = headers
+        .get("connection")
+        .map(|v| v.to_ascii_lowercase())
+        .unwrap_or_default();
+    let keep_alive = if http_10 {
+        connection.split(',').any(|t| t.trim() == "keep-alive")
+    } else {
+        !connection.split(',').any(|t| t.trim() == "close")
+    };
     Ok(Some(HttpRequest {
         method: method.to_string(),
         path,
         query,
         headers,
         body,
+        keep_alive,
     }))
 }
 
-fn write_http_response(stream: &mut TcpStream, response: HttpResponse) -> io::Result<()> {
+/// Parses a Content-Length value, which must be plain decimal digits.
+fn parse_http_content_length(value: &str) -> io::Result<usize> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid content-length: {value}"),
+        ));
+    }
+    value
+        .parse::<usize>()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid content-length: {value}")))
+}
+
+fn read_chunked_body(reader: &mut impl BufRead) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let mut size_line = String::new();
+        if reader.read_line(&mut size_line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated chunked body"));
+        }
+        let size_hex = size_line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid chunk size: {size_hex}")))?;
+        if size == 0 {
+            // Skip trailer headers up to the terminating blank line.
+            loop {
+                let mut trailer = String::new();
+                if reader.read_line(&mut trailer)? == 0 || trailer.trim_end_matches(['\r', '\n']).is_empty() {
+                    return Ok(body);
+                }
+            }
+        }
+        if body.len().saturating_add(size) > MAX_HTTP_BODY_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("request body too large (max {MAX_HTTP_BODY_SIZE})"),
+            ));
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(body.get_mut(start..).unwrap_or_default())?;
+        let mut crlf = [0_u8; 2];
+        reader.read_exact(&mut crlf)?;
+        if &crlf != b"\r\n" {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "missing CRLF after chunk data",
+            ));
+        }
+    }
+}
+
+fn is_idle_timeout(err: &io::Error) -> bool {
+    matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+fn write_http_response(stream: &mut impl Write, response: HttpResponse, keep_alive: bool) -> io::Result<()> {
     let reason = http_reason_phrase(response.status);
     let headers = format!(
-        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: {}\r\n\r\n",
         response.status,
         reason,
         response.content_type,
-        response.body.len()
+        response.body.len(),
+        if keep_alive { "keep-alive" } else { "close" }
     );
     stream.write_all(headers.as_bytes())?;
     stream.write_all(&response.body)?;
     stream.flush()
 }
 
-fn write_sse_headers(stream: &mut TcpStream) -> io::Result<()> {
+fn write_sse_headers(stream: &mut impl Write) -> io::Result<()> {
     let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\nX-Accel-Buffering: no\r\n\r\n";
     stream.write_all(headers.as_bytes())
 }
 
-fn write_sse_events(stream: &mut TcpStream, events: &[StreamEvent]) -> io::Result<()> {
+fn write_sse_events(stream: &mut impl Write, events: &[StreamEvent]) -> io::Result<()> {
     for event in events {
         write_sse_event(
             stream,
@@ -2713,7 +9967,7 @@ fn write_sse_events(stream: &mut TcpStream, events: &[StreamEvent]) -> io::Resul
     Ok(())
 }
 
-fn write_sse_event(stream: &mut TcpStream, kind: &str, payload: &Value) -> io::Result<()> {
+fn write_sse_event(stream: &mut impl Write, kind: &str, payload: &Value) -> io::Result<()> {
     let data = serde_json::to_string(payload).unwrap_or_else(|_| "{}".to_string());
     let frame = format!("event: {kind}\ndata: {data}\n\n");
     stream.write_all(frame.as_bytes())
@@ -2722,6 +9976,7 @@ fn write_sse_event(stream: &mut TcpStream, kind: &str, payload: &Value) -> io::R
 fn http_reason_phrase(status: u16) -> &'static str {
     match status {
         200 => "OK",
+        201 => "Created",
         202 => "Accepted",
         204 => "No Content",
         400 => "Bad Request",
@@ -2729,6 +9984,7 @@ fn http_reason_phrase(status: u16) -> &'static str {
         405 => "Method Not Allowed",
         410 => "Gone",
         500 => "Internal Server Error",
+        503 => "Service Unavailable",
         _ => "OK",
     }
 }
@@ -2744,14 +10000,43 @@ fn parse_path_query(raw: &str) -> (String, HashMap<String, String>) {
             continue;
         }
         if let Some((k, v)) = pair.split_once('=') {
-            query.insert(k.to_string(), v.to_string());
+            query.insert(percent_decode(k), percent_decode(v));
         } else {
-            query.insert(pair.to_string(), String::new());
+            query.insert(percent_decode(pair), String::new());
         }
     }
     (path, query)
 }
 
+/// Decodes `%XX` escapes and `+` in a query component; malformed escapes are kept verbatim.
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while let Some(&byte) = bytes.get(i) {
+        let escaped = (byte == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (byte, escaped) {
+            (_, Some(decoded)) => {
+                out.push(decoded);
+                i += 3;
+            }
+            (b'+', None) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (other, None) => {
+                out.push(other);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
 fn sorted_counter(map: &HashMap<String, u64>) -> Vec<(String, u64)> {
     let mut pairs = map.iter().map(|(k, v)| (k.clone(), *v)).collect::<Vec<_>>();
     pairs.sort_by(|a, b| a.0.cmp(&b.0));
@@ -2783,6 +10068,28 @@ enum StdioFrame {
     ContentLength,
 }
 
+fn is_cancel_method(method: &str) -> bool {
+    matches!(method, "notifications/cancelled" | "$/cancelRequest")
+}
+
+fn cancelled_response(id: Value, reason: &str, started: Instant) -> JsonRpcResponse {
+    JsonRpcResponse::error_with_data(
+        id,
+        -32006,
+        format!("request {reason}"),
+        json!({
+            "kind": "cancelled",
+            "reason": if reason == "cancelled" { "client" } else { "timeout" },
+            "elapsed_ms": started.elapsed().as_millis() as u64
+        }),
+    )
+}
+
+enum StdioMessage {
+    Request(JsonRpcRequest, StdioFrame),
+    Reply(JsonRpcResponse, StdioFrame),
+}
+
 fn write_stdio_response(stdout: &mut io::Stdout, response: &JsonRpcResponse, frame: StdioFrame) -> io::Result<()> {
     match frame {
         StdioFrame::LineDelimited => {
@@ -2833,6 +10140,153 @@ fn parse_content_length(line: &str) -> Option<usize> {
     value.trim().parse::<usize>().ok()
 }
 
+/// Stops the signal watcher from [`McpServer::exit_on_signal`] when dropped.
+struct SignalWatch(std::sync::mpsc::Sender<Option<&'static str>>);
+
+impl Drop for SignalWatch {
+    fn drop(&mut self) {
+        let _ = self.0.send(None);
+    }
+}
+
+/// Extra time after `PRX_MEMORY_SHUTDOWN_TIMEOUT_MS` for saving sessions and flushing stores.
+const SHUTDOWN_FLUSH_GRACE: Duration = Duration::from_secs(5);
+
+/// Registers SIGINT and SIGTERM handlers and returns a call that blocks until one arrives and
+/// names it. Registering up front means a signal sent while the server starts is not lost.
+#[cfg(unix)]
+fn register_shutdown_signals(handle: tokio::runtime::Handle) -> io::Result<impl FnOnce() -> &'static str + Send> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let (mut terminate, mut interrupt) = {
+        let _entered = handle.enter();
+        (signal(SignalKind::terminate())?, signal(SignalKind::interrupt())?)
+    };
+    Ok(move || {
+        handle.block_on(std::future::poll_fn(|cx| {
+            if terminate.poll_recv(cx).is_ready() {
+                Poll::Ready("SIGTERM")
+            } else if interrupt.poll_recv(cx).is_ready() {
+                Poll::Ready("SIGINT")
+            } else {
+                Poll::Pending
+            }
+        }))
+    })
+}
+
+#[cfg(not(unix))]
+fn register_shutdown_signals(handle: tokio::runtime::Handle) -> io::Result<impl FnOnce() -> &'static str + Send> {
+    Ok(move || {
+        let _ = handle.block_on(tokio::signal::ctrl_c());
+        "ctrl-c"
+    })
+}
+
+/// Removes the marker a clean shutdown leaves next to the store. An existing store without one
+/// means the last run died without flushing, which is worth a warning.
+fn check_previous_shutdown(marker: &Path, store_exists: bool) {
+    match fs::read_to_string(marker) {
+        Ok(raw) => {
+            tracing::debug!(marker = %raw.trim(), "previous run shut down cleanly");
+            let _ = fs::remove_file(marker);
+        }
+        Err(_) if store_exists => {
+            tracing::warn!(
+                path = %marker.display(),
+                "no clean shutdown marker; the previous run may have stopped mid-write"
+            );
+        }
+        Err(_) => {}
+    }
+}
+
+/// Builds the tokio runtime shared by provider calls, background jobs, and the storage backend.
+fn build_shared_runtime() -> Result<tokio::runtime::Runtime, String> {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(env_usize("PRX_MEMORY_RUNTIME_THREADS", 2, 1, 64))
+        .thread_name("prx-memory-rt")
+        .enable_all()
+        .build()
+        .map_err(|e| format!("runtime initialization failed: {e}"))
+}
+
+/// Governed-write rules: the JSON file at `PRX_MEMORY_GOVERNANCE_POLICY`, else
+/// `references/governance-policy.json` under `PRX_MEMORY_SKILL_DIR`, else the built-in policy.
+/// A policy that checks the tag taxonomy without listing `allowed_tags` takes the `tool:` and
+/// `domain:` tags named in the skill's `tag-taxonomy.md`.
+fn governance_policy_from_env() -> Result<GovernancePolicy, String> {
+    let mut policy = load_governance_policy()?;
+    if policy.tag_taxonomy != TaxonomyMode::Off && policy.allowed_tags.is_empty() {
+        let markdown = skill_override_dir()
+            .and_then(|dir| fs::read_to_string(dir.join("references").join("tag-taxonomy.md")).ok())
+            .unwrap_or_else(|| SKILL_TAGS_TEXT.to_string());
+        policy.allowed_tags = parse_tag_taxonomy(&markdown, &["tool:", "domain:"]);
+    }
+    Ok(policy)
+}
+
+fn load_governance_policy() -> Result<GovernancePolicy, String> {
+    let path = std::env::var("PRX_MEMORY_GOVERNANCE_POLICY")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            skill_override_dir()
+                .map(|dir| dir.join("references").join("governance-policy.json"))
+                .filter(|path| path.is_file())
+        });
+    let Some(path) = path else {
+        return Ok(GovernancePolicy::default());
+    };
+    let raw =
+        fs::read_to_string(&path).map_err(|e| format!("failed to read governance policy {}: {e}", path.display()))?;
+    GovernancePolicy::from_json(&raw).map_err(|e| format!("invalid governance policy {}: {e}", path.display()))
+}
+
+/// Loads the at-rest encryption keys. `PRX_MEMORY_ENCRYPTION_KEY` (64 hex chars) or the first line of
+/// `PRX_MEMORY_ENCRYPTION_KEY_FILE` is the primary key; later key-file lines and the comma-separated
+/// `PRX_MEMORY_ENCRYPTION_PREVIOUS_KEYS` only decrypt data written before a rotation.
+fn field_cipher_from_env() -> Result<Option<FieldCipher>, String> {
+    let mut keys = Vec::new();
+    if let Ok(raw) = std::env::var("PRX_MEMORY_ENCRYPTION_KEY")
+        && !raw.trim().is_empty()
+    {
+        keys.push(raw.trim().to_string());
+    }
+    if let Ok(path) = std::env::var("PRX_MEMORY_ENCRYPTION_KEY_FILE")
+        && !path.trim().is_empty()
+    {
+        let raw = fs::read_to_string(path.trim())
+            .map_err(|e| format!("failed to read PRX_MEMORY_ENCRYPTION_KEY_FILE: {e}"))?;
+        keys.extend(
+            raw.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(raw) = std::env::var("PRX_MEMORY_ENCRYPTION_PREVIOUS_KEYS") {
+        keys.extend(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(str::to_string),
+        );
+    }
+    let Some((primary, previous)) = keys.split_first() else {
+        return Ok(None);
+    };
+    let previous = previous.iter().map(String::as_str).collect::<Vec<_>>();
+    FieldCipher::from_hex_keys(primary, &previous)
+        .map(Some)
+        .map_err(|e| format!("invalid encryption key configuration: {e}"))
+}
+
 fn env_usize(name: &str, default: usize, min: usize, max: usize) -> usize {
     std::env::var(name)
         .ok()
@@ -2902,6 +10356,99 @@ struct RenderedResource {
     text: String,
 }
 
+#[derive(Debug, Clone)]
+struct SkillResourceEntry {
+    uri: String,
+    name: String,
+    description: String,
+    mime_type: &'static str,
+}
+
+/// Skill resources currently served: the embedded governance package plus any
+/// extra markdown files found under `PRX_MEMORY_SKILL_DIR`.
+fn current_skill_resources() -> Vec<SkillResourceEntry> {
+    let mut out = skill_resources()
+        .iter()
+        .map(|resource| SkillResourceEntry {
+            uri: resource.uri.to_string(),
+            name: resource.name.to_string(),
+            description: resource.description.to_string(),
+            mime_type: resource.mime_type,
+        })
+        .collect::<Vec<_>>();
+    if let Some(dir) = skill_override_dir() {
+        let mut files = Vec::new();
+        collect_markdown_files(&dir, &dir, &mut files);
+        files.sort();
+        for rel in files {
+            let uri = format!("prx://skills/{SKILL_ID}/{rel}");
+            if out.iter().any(|resource| resource.uri == uri) {
+                continue;
+            }
+            out.push(SkillResourceEntry {
+                uri,
+                name: format!("{SKILL_ID}/{rel}"),
+                description: "Skill file loaded from PRX_MEMORY_SKILL_DIR.".to_string(),
+                mime_type: "text/markdown",
+            });
+        }
+    }
+    out
+}
+
+/// Skill text for `uri`, preferring the `PRX_MEMORY_SKILL_DIR` copy so edits
+/// are served (and notified) without a restart.
+fn skill_resource_body(uri: &str) -> Option<String> {
+    if let Some(dir) = skill_override_dir()
+        && let Some(rel) = uri.strip_prefix(&format!("prx://skills/{SKILL_ID}/"))
+        && !rel.split('/').any(|part| part.is_empty() || part == "..")
+        && let Ok(text) = fs::read_to_string(dir.join(rel))
+    {
+        return Some(text);
+    }
+    skill_resource_text(uri).map(str::to_string)
+}
+
+fn skill_override_dir() -> Option<PathBuf> {
+    std::env::var("PRX_MEMORY_SKILL_DIR")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+fn collect_markdown_files(root: &Path, dir: &Path, out: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_markdown_files(root, &path, out);
+        } else if path.extension().is_some_and(|ext| ext == "md")
+            && let Ok(rel) = path.strip_prefix(root)
+        {
+            out.push(rel.to_string_lossy().replace('\\', "/"));
+        }
+    }
+}
+
+fn resource_list_fingerprint() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for resource in current_skill_resources() {
+        resource.uri.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
 fn resource_templates() -> &'static [ResourceTemplateDef] {
     &[
         ResourceTemplateDef {
@@ -2916,6 +10463,12 @@ fn resource_templates() -> &'static [ResourceTemplateDef] {
             description: "Standardized memory_recall payload template.",
             mime_type: "application/json",
         },
+        ResourceTemplateDef {
+            uri_template: "prx://templates/memory-recall-plan{?task,limit}",
+            name: "template:memory-recall-plan",
+            description: "memory_recall payload generated from a task description, narrowed to the scope and category its matching memories sit in.",
+            mime_type: "application/json",
+        },
         ResourceTemplateDef {
             uri_template: "prx://templates/memory-store-dual{?symptom,cause,fix,prevention,scope}",
             name: "template:memory-store-dual",
@@ -2938,6 +10491,61 @@ struct MemoryStoreInput {
     project_tag: Option<String>,
     tool_tag: Option<String>,
     domain_tag: Option<String>,
+    extract_entities: Option<bool>,
+    source: Option<MemorySource>,
+    #[serde(rename = "override")]
+    override_duplicate: Option<bool>,
+    suggest_importance: Option<bool>,
+}
+
+const MAX_RECALL_QUERIES: usize = 8;
+/// Leading salient terms `memory_recall_context` anchors a query on.
+const CONTEXT_RECALL_ANCHORS: usize = 3;
+/// Rank offset of reciprocal rank fusion across `memory_recall` queries.
+const MULTI_RECALL_RRF_K: f32 = 60.0;
+
+/// Share of the matched weight a scope or category needs before a recall plan filters on it.
+const RECALL_PLAN_DOMINANT_SHARE: f32 = 0.6;
+const RECALL_PLAN_MAX_TAGS: usize = 10;
+
+#[derive(Debug, Deserialize)]
+struct MemoryRecallPlanInput {
+    task: String,
+    limit: Option<usize>,
+    max_terms: Option<usize>,
+}
+
+/// A scope, category or tag in a recall plan.
+#[derive(Debug, Serialize)]
+struct PlanFacet {
+    name: String,
+    /// Matching entries under this facet.
+    count: usize,
+    /// Fraction of the plan's matched weight.
+    share: f32,
+}
+
+/// Facets by weight, heaviest first; ties by name.
+fn ranked_facets(facets: HashMap<String, (f32, usize)>) -> Vec<PlanFacet> {
+    let total = facets.values().map(|(weight, _)| weight).sum::<f32>();
+    let mut ranked = facets.into_iter().collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.1.0.total_cmp(&a.1.0).then_with(|| a.0.cmp(&b.0)));
+    ranked
+        .into_iter()
+        .map(|(name, (weight, count))| PlanFacet {
+            name,
+            count,
+            share: if total > 0.0 { weight / total } else { 0.0 },
+        })
+        .collect()
+}
+
+/// Only the context fields; every other argument is passed through to `memory_recall`.
+#[derive(Debug, Deserialize)]
+struct MemoryRecallContextInput {
+    messages: Vec<Message>,
+    max_messages: Option<usize>,
+    max_terms: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -2953,11 +10561,135 @@ struct MemoryRecallInput {
     vector_weight: Option<f32>,
     lexical_weight: Option<f32>,
     candidate_pool: Option<usize>,
+    expand_relations: Option<bool>,
+    cursor: Option<String>,
+    explain: Option<bool>,
+    diversity: Option<f32>,
+    fusion: Option<String>,
+    timeout_ms: Option<u64>,
+    include_pending: Option<bool>,
+    include_archived: Option<bool>,
+    include_secondary: Option<bool>,
+    source: Option<MemorySource>,
+    feedback_weight: Option<f32>,
+    stream: Option<bool>,
+    #[serde(flatten)]
+    block: RecallContextInput,
+}
+
+/// Context block options of `memory_recall`, shared by single and multi-query recall.
+#[derive(Debug, Default, Deserialize)]
+struct RecallContextInput {
+    max_chars: Option<usize>,
+    max_tokens: Option<usize>,
+    summarize_overflow: Option<bool>,
+    render: Option<String>,
+}
+
+impl RecallContextInput {
+    const KEYS: [&'static str; 4] = ["max_chars", "max_tokens", "summarize_overflow", "render"];
+
+    /// The tighter of the two limits in characters, and the block format; both `None` when no block
+    /// was asked for. A budget without `render` packs compact lines.
+    fn block(&self) -> Result<(Option<usize>, Option<RenderFormat>), &'static str> {
+        if self.max_chars == Some(0) || self.max_tokens == Some(0) {
+            return Err("max_chars and max_tokens must be at least 1");
+        }
+        let format = match self.render.as_deref().map(RenderFormat::parse) {
+            Some(Some(v)) => Some(v),
+            Some(None) => return Err("render must be markdown|xml|plain|compact"),
+            None => None,
+        };
+        let tokens = self.max_tokens.map(|t| t.saturating_mul(packing::CHARS_PER_TOKEN));
+        let budget = match (self.max_chars, tokens) {
+            (Some(chars), Some(tokens)) => Some(chars.min(tokens)),
+            (chars, tokens) => chars.or(tokens),
+        };
+        Ok((budget, format.or_else(|| budget.map(|_| RenderFormat::default()))))
+    }
+
+    /// Adds the rendered `context` block to a recall response, which also becomes its text content.
+    /// Under a budget, the items are cut down to those packed into it.
+    fn apply(&self, response: &mut JsonRpcResponse) {
+        let Ok((budget, Some(format))) = self.block() else {
+            return;
+        };
+        let Some(result) = response.result.as_mut() else {
+            return;
+        };
+        let Some(content) = result.get_mut("structuredContent").and_then(Value::as_object_mut) else {
+            return;
+        };
+        let items = match content.remove("items") {
+            Some(Value::Array(items)) => items,
+            _ => Vec::new(),
+        };
+        let packed = {
+            let texts = items
+                .iter()
+                .map(|item| recall_item_field(item, "text"))
+                .collect::<Vec<_>>();
+            let line = |idx: usize, text: &str| {
+                let item = items.get(idx).unwrap_or(&Value::Null);
+                let render_item = RenderItem {
+                    id: recall_item_field(item, "id"),
+                    category: recall_item_field(item, "category"),
+                    importance: item.pointer("/entry/importance").and_then(Value::as_f64).unwrap_or(0.0),
+                };
+                format.line(&render_item, text)
+            };
+            let room = budget.map_or(usize::MAX, |b| b.saturating_sub(format.overhead()));
+            packing::pack(&texts, room, self.summarize_overflow.unwrap_or(false), line)
+        };
+        let kinds = packed.packed.iter().copied().collect::<HashMap<_, _>>();
+        let mut kept = Vec::with_capacity(kinds.len());
+        let mut omitted = Vec::new();
+        for (idx, mut item) in items.into_iter().enumerate() {
+            let id = item.pointer("/entry/id").cloned().unwrap_or(Value::Null);
+            match kinds.get(&idx) {
+                Some(kind) => {
+                    if let (packing::Packed::Excerpt, Some(obj)) = (kind, item.as_object_mut()) {
+                        obj.insert("excerpt".to_string(), json!(true));
+                    }
+                    kept.push(item);
+                }
+                None => omitted.push(id),
+            }
+        }
+        let text = format.block(&packed.lines);
+        let chars = text.chars().count();
+        content.insert("count".to_string(), json!(kept.len()));
+        content.insert("items".to_string(), json!(kept));
+        content.insert(
+            "context".to_string(),
+            json!({
+                "format": format.as_str(),
+                "text": text,
+                "chars": chars,
+                "approx_tokens": chars.div_ceil(packing::CHARS_PER_TOKEN),
+                "budget_chars": budget,
+                "excerpted": packed.packed.iter().filter(|(_, kind)| *kind == packing::Packed::Excerpt).count(),
+                "omitted": omitted
+            }),
+        );
+        if let Some(obj) = result.as_object_mut() {
+            obj.insert("content".to_string(), json!([{"type": "text", "text": text}]));
+        }
+    }
+}
+
+/// A string field of the entry in a recall response item; empty when missing.
+fn recall_item_field<'a>(item: &'a Value, name: &str) -> &'a str {
+    item.get("entry")
+        .and_then(|e| e.get(name))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
 }
 
 #[derive(Debug, Deserialize)]
 struct MemoryForgetInput {
     id: String,
+    dry_run: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -2973,6 +10705,7 @@ struct MemoryUpdateInput {
     tool_tag: Option<String>,
     domain_tag: Option<String>,
     governed: Option<bool>,
+    dry_run: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -2995,8 +10728,53 @@ struct MemoryStoreDualInput {
     use_vector: Option<bool>,
     tech_importance_level: Option<String>,
     principle_importance_level: Option<String>,
+    source: Option<MemorySource>,
+    #[serde(rename = "override")]
+    override_duplicate: Option<bool>,
+}
+
+const MAX_TRANSACTION_OPS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct MemoryTransactionInput {
+    operations: Vec<Value>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum TransactionOp {
+    Store(MemoryStoreInput),
+    Update(MemoryUpdateInput),
+    Forget(MemoryForgetInput),
+}
+
+impl TransactionOp {
+    const fn tool(&self) -> &'static str {
+        match self {
+            Self::Store(_) => "memory_store",
+            Self::Update(_) => "memory_update",
+            Self::Forget(_) => "memory_forget",
+        }
+    }
+
+    fn dry_run(&self) -> bool {
+        match self {
+            Self::Store(_) => false,
+            Self::Update(args) => args.dry_run.unwrap_or(false),
+            Self::Forget(args) => args.dry_run.unwrap_or(false),
+        }
+    }
+}
+
+/// A write made while the caller holds the store lock; usage is recorded once the lock is released.
+struct LockedWrite {
+    result: Value,
+    usage: Option<(UsageOp, usize)>,
+}
+
+/// Error side carries the JSON-RPC code and message.
+type LockedWriteResult = Result<LockedWrite, (i64, String)>;
+
 #[derive(Debug, Clone)]
 struct StoreLayerRequest {
     text: String,
@@ -3009,12 +10787,100 @@ struct StoreLayerRequest {
     use_vector: bool,
     enforce_verify: bool,
     allow_auto_maintenance: bool,
+    redaction: RedactionMode,
+    source: Option<MemorySource>,
+    /// Store governed writes even above the duplicate threshold.
+    override_duplicate: bool,
 }
 
 #[derive(Debug, Clone)]
 struct StoreLayerOutcome {
     entry: MemoryEntry,
     auto_maintenance: Option<AutoMaintenanceReport>,
+    /// PII kinds found in the text, whatever the redaction mode did about them.
+    pii: Vec<&'static str>,
+    /// Governed tags outside the tag taxonomy when it only flags them.
+    unlisted_tags: Vec<String>,
+    /// Existing entries that scored at least the policy's `similar_score`.
+    similar: Vec<SimilarMemory>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SimilarMemory {
+    id: String,
+    score: f32,
+    text: String,
+}
+
+/// Why [`store_layer_with_rules`] refused a write.
+#[derive(Debug)]
+enum StoreLayerError {
+    Rejected(String),
+    /// A governed write scored above the policy's `duplicate_score` without `override`.
+    Duplicate {
+        text: String,
+        similar: Vec<SimilarMemory>,
+    },
+}
+
+impl From<String> for StoreLayerError {
+    fn from(msg: String) -> Self {
+        Self::Rejected(msg)
+    }
+}
+
+impl std::fmt::Display for StoreLayerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rejected(msg) => f.write_str(msg),
+            Self::Duplicate { similar, .. } => write!(
+                f,
+                "duplicate memory likely exists: {}; update it or pass override=true",
+                similar.first().map_or("", |top| top.id.as_str())
+            ),
+        }
+    }
+}
+
+impl StoreLayerError {
+    /// Duplicates carry the similar entries and a suggestion to fold the new text into the closest one.
+    fn data(&self) -> Option<Value> {
+        match self {
+            Self::Rejected(_) => None,
+            Self::Duplicate { text, similar } => Some(json!({
+                "kind": "duplicate_memory",
+                "similar": similar,
+                "suggestion": {
+                    "action": "merged",
+                    "tool": "memory_update",
+                    "arguments": {"id": similar.first().map(|top| top.id.clone()), "text": text}
+                },
+                "override": "pass override=true to store it as a separate memory"
+            })),
+        }
+    }
+
+    fn response(&self, id: Value, context: &str) -> JsonRpcResponse {
+        let msg = format!("{context}{self}");
+        match self.data() {
+            Some(data) => JsonRpcResponse::error_with_data(id, -32602, msg, data),
+            None => JsonRpcResponse::error(id, -32602, msg),
+        }
+    }
+}
+
+/// Warning for a write stored next to similar entries: suggests merging them with `new_id`.
+fn similar_warning(similar: &[SimilarMemory], new_id: &str) -> Value {
+    let mut ids = similar.iter().map(|s| s.id.clone()).collect::<Vec<_>>();
+    ids.push(new_id.to_string());
+    json!({
+        "similar": similar,
+        "suggestion": {
+            "action": "merged",
+            "tool": "memory_merge",
+            "arguments": {"ids": ids}
+        }
+    })
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -3026,32 +10892,322 @@ struct AutoMaintenanceReport {
     duplicate_deleted: usize,
     rebalance_deleted: usize,
     rebalance_scopes: Vec<String>,
+    decay_archived: usize,
+    /// Store generation published by the pass.
+    generation: u64,
     notes: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
 struct MemoryExportInput {
+    query: Option<String>,
+    use_vector: Option<bool>,
+    include_pending: Option<bool>,
     scope: Option<String>,
     category: Option<String>,
     limit: Option<usize>,
     include_embeddings: Option<bool>,
+    format: Option<String>,
     output_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct MemoryImportInput {
-    entries: Vec<ImportedMemoryEntry>,
+    entries: Option<Vec<ImportedMemoryEntry>>,
+    data: Option<String>,
+    format: Option<String>,
+    columns: Option<HashMap<String, String>>,
     governed: Option<bool>,
     use_vector: Option<bool>,
     skip_duplicates: Option<bool>,
+    dry_run: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
 struct MemoryMigrateInput {
     source_path: String,
+    source_format: Option<String>,
+    scope: Option<String>,
+    format: Option<String>,
+    columns: Option<HashMap<String, String>>,
     governed: Option<bool>,
     use_vector: Option<bool>,
     skip_duplicates: Option<bool>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryIngestFilesInput {
+    path: String,
+    scope: Option<String>,
+    category: Option<String>,
+    importance_level: Option<String>,
+    tags: Option<Vec<String>>,
+    project_tag: Option<String>,
+    tool_tag: Option<String>,
+    domain_tag: Option<String>,
+    chunk_size: Option<usize>,
+    chunk_overlap: Option<usize>,
+    max_files: Option<usize>,
+    review: Option<bool>,
+    governed: Option<bool>,
+    use_vector: Option<bool>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemorySessionNoteInput {
+    text: String,
+    /// Speaker recorded for distillation; defaults to `user`.
+    role: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryDistillInput {
+    messages: Vec<Message>,
+    scope: Option<String>,
+    tags: Option<Vec<String>>,
+    project_tag: Option<String>,
+    tool_tag: Option<String>,
+    domain_tag: Option<String>,
+    max_candidates: Option<usize>,
+    use_vector: Option<bool>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryQuotaStatusInput {
+    scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryDecayReportInput {
+    scope: Option<String>,
+    category: Option<String>,
+    threshold: Option<f64>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryStalenessInput {
+    scope: Option<String>,
+    category: Option<String>,
+    older_than_days: Option<f64>,
+    min_confidence: Option<f64>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryArchiveInput {
+    ids: Option<Vec<String>>,
+    scope: Option<String>,
+    category: Option<String>,
+    older_than_days: Option<f64>,
+    max_importance: Option<f32>,
+    limit: Option<usize>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryRestoreInput {
+    ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryFeedbackInput {
+    query: Option<String>,
+    query_id: Option<String>,
+    scope: Option<String>,
+    category: Option<String>,
+    #[serde(default)]
+    useful: Vec<String>,
+    #[serde(default)]
+    irrelevant: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryQueryLogInput {
+    since_ms: Option<u64>,
+    until_ms: Option<u64>,
+    accessed_only: Option<bool>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    format: Option<String>,
+    clear: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryEvalBaselineInput {
+    op: String,
+    name: Option<String>,
+    cases: Option<Vec<EvalCase>>,
+    k: Option<usize>,
+    use_vector: Option<bool>,
+    tolerance: Option<f64>,
+    on_regression: Option<String>,
+    include_feedback: Option<bool>,
+}
+
+/// Golden-set metrics saved by `memory_eval_baseline`, with everything needed to replay them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EvalBaseline {
+    saved_ms: u64,
+    k: usize,
+    use_vector: bool,
+    ranking: RankingConfig,
+    cases: Vec<EvalCase>,
+    metrics: eval::Metrics,
+    scores: Vec<eval::CaseScore>,
+}
+
+/// Named baselines persisted at `{db_path}.baselines.json`.
+#[derive(Debug)]
+struct EvalBaselineFile {
+    path: PathBuf,
+}
+
+impl EvalBaselineFile {
+    fn load(&self) -> Result<BTreeMap<String, EvalBaseline>, String> {
+        match fs::read(&self.path) {
+            Ok(raw) => serde_json::from_slice(&raw).map_err(|e| format!("failed to parse eval baselines: {e}")),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(err) => Err(format!("failed to read eval baselines: {err}")),
+        }
+    }
+
+    fn save(&self, baselines: &BTreeMap<String, EvalBaseline>) -> Result<(), String> {
+        let raw = serde_json::to_vec_pretty(baselines).map_err(|e| e.to_string())?;
+        atomic_write(&self.path, &raw).map_err(|e| format!("failed to write eval baselines: {e}"))
+    }
+}
+
+/// Ranking config accepted by `memory_evolve` in ranking mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ActiveRanking {
+    config: RankingConfig,
+    variant_id: String,
+    baseline: String,
+    effective_score: f32,
+    accepted_ms: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SessionStartRequest {
+    /// Server notification kinds to push into the stream; omitted means all of them.
+    notifications: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DrainRequest {
+    #[serde(default)]
+    exit: bool,
+    timeout_ms: Option<u64>,
+}
+
+/// Live stream sessions saved by a drain to `{db_path}.sessions.json`, sealed with the store's
+/// cipher when one is configured. The next start restores them with fresh leases and removes the
+/// file, so a snapshot is only picked up once.
+#[derive(Debug)]
+struct SessionFile {
+    path: PathBuf,
+    cipher: Option<FieldCipher>,
+}
+
+impl SessionFile {
+    fn save(&self, sessions: &HashMap<String, SessionState>) -> Result<(), String> {
+        let mut raw = serde_json::to_string(sessions).map_err(|e| e.to_string())?;
+        if let Some(cipher) = &self.cipher {
+            let sealed = cipher.seal(&raw, "sessions", "snapshot").map_err(|e| e.to_string())?;
+            raw = json!({ "sealed": sealed }).to_string();
+        }
+        atomic_write(&self.path, raw.as_bytes()).map_err(|e| format!("failed to write sessions: {e}"))
+    }
+
+    /// Sessions left by the last drain, or none when there is no readable snapshot.
+    fn restore(&self) -> HashMap<String, SessionState> {
+        let raw = match fs::read_to_string(&self.path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return HashMap::new(),
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to read saved sessions");
+                return HashMap::new();
+            }
+        };
+        let _ = fs::remove_file(&self.path);
+        let plain = match serde_json::from_str::<Value>(&raw)
+            .ok()
+            .and_then(|v| v.get("sealed").and_then(Value::as_str).map(str::to_string))
+        {
+            Some(sealed) => match self
+                .cipher
+                .as_ref()
+                .map(|cipher| cipher.open(&sealed, "sessions", "snapshot"))
+            {
+                Some(Ok(plain)) => plain,
+                Some(Err(err)) => {
+                    tracing::warn!(error = %err, "failed to open saved sessions");
+                    return HashMap::new();
+                }
+                None => {
+                    tracing::warn!("saved sessions are encrypted but no encryption key is configured");
+                    return HashMap::new();
+                }
+            },
+            None => raw,
+        };
+        let mut sessions: HashMap<String, SessionState> = match serde_json::from_str(&plain) {
+            Ok(sessions) => sessions,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to parse saved sessions");
+                return HashMap::new();
+            }
+        };
+        let now = now_ms();
+        for state in sessions.values_mut() {
+            state.last_touch_ms = now;
+            state.lease_expires_ms = now.saturating_add(McpServer::session_ttl_ms());
+        }
+        if !sessions.is_empty() {
+            tracing::info!(count = sessions.len(), "restored drained sessions");
+        }
+        sessions
+    }
+}
+
+/// Persisted at `{db_path}.ranking.json` and applied at startup unless `PRX_MEMORY_TOKENIZER`
+/// is set.
+#[derive(Debug)]
+struct ActiveRankingFile {
+    path: PathBuf,
+}
+
+impl ActiveRankingFile {
+    fn load(&self) -> Result<Option<ActiveRanking>, String> {
+        match fs::read(&self.path) {
+            Ok(raw) => serde_json::from_slice(&raw)
+                .map(Some)
+                .map_err(|e| format!("failed to parse active ranking config: {e}")),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("failed to read active ranking config: {err}")),
+        }
+    }
+
+    fn save(&self, active: &ActiveRanking) -> Result<(), String> {
+        let raw = serde_json::to_vec_pretty(active).map_err(|e| e.to_string())?;
+        atomic_write(&self.path, &raw).map_err(|e| format!("failed to write active ranking config: {e}"))
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryReviewListInput {
+    scope: Option<String>,
+    category: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryReviewDecisionInput {
+    ids: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -3059,6 +11215,19 @@ struct MemoryReembedInput {
     scope: Option<String>,
     category: Option<String>,
     limit: Option<usize>,
+    batch_size: Option<usize>,
+    wait: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryJobStatusInput {
+    job_id: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemorySyncInput {
+    dry_run: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -3069,6 +11238,99 @@ struct MemoryCompactInput {
     dry_run: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+struct MemoryDuplicatesInput {
+    scope: Option<String>,
+    category: Option<String>,
+    method: Option<String>,
+    threshold: Option<f32>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryLintInput {
+    scope: Option<String>,
+    category: Option<String>,
+    limit: Option<usize>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryRecategorizeInput {
+    renames: Option<BTreeMap<String, String>>,
+    scope: Option<String>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryRetagInput {
+    add: Option<Vec<String>>,
+    remove: Option<Vec<String>>,
+    rename: Option<BTreeMap<String, String>>,
+    scope: Option<String>,
+    category: Option<String>,
+    tag: Option<String>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryMoveScopeInput {
+    from: String,
+    to: String,
+    category: Option<String>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryMergeInput {
+    ids: Vec<String>,
+    text: Option<String>,
+    category: Option<String>,
+    scope: Option<String>,
+    governed: Option<bool>,
+    use_vector: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemorySummarizeInput {
+    scope: Option<String>,
+    category: Option<String>,
+    batch_size: Option<usize>,
+    max_chars: Option<usize>,
+    instruction: Option<String>,
+    keep_originals: Option<bool>,
+    governed: Option<bool>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryLinkInput {
+    from_id: String,
+    relation: String,
+    to_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryUnlinkInput {
+    from_id: String,
+    relation: Option<String>,
+    to_id: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryEntitiesInput {
+    kind: Option<String>,
+    name: Option<String>,
+    scope: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct EntityAggregate {
+    source_ids: Vec<String>,
+    last_seen_ms: u64,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 struct ImportedMemoryEntry {
     text: String,
@@ -3081,6 +11343,8 @@ struct ImportedMemoryEntry {
     tool_tag: Option<String>,
     domain_tag: Option<String>,
     embedding: Option<Vec<f32>>,
+    embedding_model: Option<String>,
+    source: Option<MemorySource>,
 }
 
 #[derive(Debug)]
@@ -3088,6 +11352,8 @@ struct ImportOptions {
     governed: bool,
     use_vector: bool,
     skip_duplicates: bool,
+    /// Run every check but store nothing; `created` then counts what would be stored.
+    dry_run: bool,
 }
 
 #[derive(Debug)]
@@ -3096,11 +11362,28 @@ struct ImportSummary {
     skipped: usize,
     failed: usize,
     errors: Vec<String>,
+    /// Entries a dry run would store.
+    planned: Vec<Value>,
 }
 
 #[derive(Debug, Deserialize, Default)]
 struct MemoryStatsInput {
     scope: Option<String>,
+    top_tags: Option<usize>,
+    trend: Option<bool>,
+    bucket: Option<String>,
+    periods: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryIndexStatusInput {
+    ensure: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct MemoryUsageReportInput {
+    agent_id: Option<String>,
+    window_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -3109,14 +11392,41 @@ struct MemoryListInput {
     category: Option<String>,
     limit: Option<usize>,
     offset: Option<usize>,
+    cursor: Option<String>,
+    source: Option<MemorySource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryEvolveInput {
+    mode: Option<String>,
+    parent_score: Option<f32>,
+    lambda: Option<f32>,
+    mu: Option<f32>,
+    #[serde(default)]
+    candidates: Vec<MemoryEvolveCandidate>,
+    #[serde(default)]
+    variants: Vec<MemoryEvolveVariant>,
+    baseline: Option<String>,
+    holdout_ratio: Option<f64>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryEvolveVariant {
+    id: String,
+    #[serde(flatten)]
+    config: RankingConfig,
+    #[serde(default)]
+    cost_penalty: f32,
+    #[serde(default)]
+    risk_penalty: f32,
 }
 
-#[derive(Debug, Deserialize)]
-struct MemoryEvolveInput {
-    parent_score: f32,
-    lambda: Option<f32>,
-    mu: Option<f32>,
-    candidates: Vec<MemoryEvolveCandidate>,
+#[derive(Debug, Clone, Copy)]
+struct RankingTrial {
+    train: f32,
+    holdout: f32,
+    holdout_hits: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -3134,82 +11444,161 @@ struct MemorySkillManifestInput {
     include_content: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+struct MemoryToolSchemasInput {
+    format: Option<String>,
+    tools: Option<Vec<String>>,
+}
+
 impl ScopeManager {
-    fn from_env() -> Self {
-        let agent_id = std::env::var("PRX_MEMORY_AGENT_ID")
+    fn from_env() -> Result<Self, String> {
+        let default_agent_id = std::env::var("PRX_MEMORY_AGENT_ID")
             .ok()
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .unwrap_or_else(|| "default-agent".to_string());
+        let agent_from_request = std::env::var("PRX_MEMORY_AGENT_FROM_REQUEST").is_ok_and(|v| {
+            let lowered = v.trim().to_ascii_lowercase();
+            lowered == "1" || lowered == "true" || lowered == "on" || lowered == "yes"
+        });
         let default_scope = std::env::var("PRX_MEMORY_DEFAULT_SCOPE")
             .ok()
-            .map(|s| s.trim().replace("{agent_id}", &agent_id))
+            .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .unwrap_or_else(|| "global".to_string());
 
         let raw = std::env::var("PRX_MEMORY_ALLOWED_SCOPES").ok();
-        let allowed_scope_rules = match raw {
-            Some(v) => {
-                let items = v
-                    .split(',')
-                    .map(|s| s.trim().replace("{agent_id}", &agent_id))
+        let allowed_scope_rules = raw
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_string())
                     .filter(|s| !s.is_empty())
-                    .collect::<Vec<_>>();
-                if items.is_empty() {
-                    vec!["global".to_string(), format!("agent:{agent_id}")]
-                } else {
-                    items
-                }
-            }
-            None => vec!["global".to_string(), format!("agent:{agent_id}")],
+                    .collect::<Vec<_>>()
+            })
+            .filter(|items| !items.is_empty())
+            .unwrap_or_else(|| vec!["global".to_string(), "agent:{agent_id}".to_string()]);
+        let agent_access = match std::env::var("PRX_MEMORY_AGENT_ACCESS") {
+            Ok(raw) if !raw.trim().is_empty() => serde_json::from_str::<HashMap<String, AgentAccess>>(&raw)
+                .map_err(|e| format!("invalid PRX_MEMORY_AGENT_ACCESS: {e}"))?,
+            _ => HashMap::new(),
         };
-        let agent_access = std::env::var("PRX_MEMORY_AGENT_ACCESS")
-            .ok()
-            .and_then(|raw| serde_json::from_str::<HashMap<String, Vec<String>>>(&raw).ok())
-            .unwrap_or_default();
+        let mut agent_tokens: HashMap<String, HashSet<String>> = HashMap::new();
+        for pair in std::env::var("PRX_MEMORY_AGENT_TOKENS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        {
+            let (label, agent) = pair
+                .split_once(':')
+                .ok_or_else(|| format!("PRX_MEMORY_AGENT_TOKENS entries must be label:agent, got {pair}"))?;
+            let agent = validate_agent_id(agent)?;
+            agent_tokens.entry(label.trim().to_string()).or_default().insert(agent);
+        }
 
-        Self {
-            agent_id,
+        Ok(Self {
+            default_agent_id,
+            agent_from_request,
             default_scope,
             allowed_scope_rules,
             agent_access,
+            agent_tokens,
+        })
+    }
+
+    /// The calling agent: the per-request identity when one is in effect, else `PRX_MEMORY_AGENT_ID`.
+    fn agent_id(&self) -> String {
+        caller_agent_override().unwrap_or_else(|| self.default_agent_id.clone())
+    }
+
+    /// Identity a client named for itself, or `None` when `PRX_MEMORY_AGENT_FROM_REQUEST` is off.
+    fn requested_agent(&self, raw: Option<&str>) -> Result<Option<String>, String> {
+        match raw.map(str::trim).filter(|v| !v.is_empty()) {
+            Some(agent) if self.agent_from_request => validate_agent_id(agent).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether the authenticated token may act as `agent`. Without HTTP tokens any claim stands;
+    /// a token may only claim the agents `PRX_MEMORY_AGENT_TOKENS` binds to its label.
+    fn authorize_agent(&self, agent: &str, token_label: Option<&str>) -> Result<(), String> {
+        let Some(label) = token_label else {
+            return Ok(());
+        };
+        if self
+            .agent_tokens
+            .get(label)
+            .is_some_and(|agents| agents.contains(agent))
+        {
+            Ok(())
+        } else {
+            Err(format!("token {label} is not bound to agent {agent}"))
         }
     }
 
+    /// Where writes go when the caller names no scope: the configured default, else the agent's
+    /// own scope, else `global`, preferring scopes the caller may write to.
     fn default_scope(&self) -> String {
-        let preferred = self.default_scope.clone();
-        if self.can_access_scope(&preferred) {
+        let agent = self.agent_id();
+        let preferred = self.default_scope.replace("{agent_id}", &agent);
+        if self.permits(&preferred, ScopeAction::Write) {
             return preferred;
         }
-        let own = format!("agent:{}", self.agent_id);
-        if self.can_access_scope(&own) {
+        let own = format!("agent:{agent}");
+        if self.permits(&own, ScopeAction::Write) {
             return own;
         }
         "global".to_string()
     }
 
-    fn accessible_scope_rules(&self) -> Vec<String> {
-        if let Some(scopes) = self.agent_access.get(&self.agent_id) {
-            let expanded = scopes
+    /// The caller's scope rules with the actions each one grants.
+    fn scope_grants(&self) -> Vec<(String, Vec<ScopeAction>)> {
+        let agent = self.agent_id();
+        let expand = |rule: &str| rule.replace("{agent_id}", &agent);
+        let grants = match self.agent_access.get(&agent) {
+            Some(AgentAccess::Scopes(scopes)) => scopes
                 .iter()
-                .map(|s| s.replace("{agent_id}", &self.agent_id))
-                .filter(|s| !s.is_empty())
-                .collect::<Vec<_>>();
-            if !expanded.is_empty() {
-                return expanded;
-            }
+                .map(|s| (expand(s), ScopeAction::ALL.to_vec()))
+                .collect::<Vec<_>>(),
+            Some(AgentAccess::Actions(rules)) => rules
+                .iter()
+                .map(|(rule, actions)| (expand(rule), actions.clone()))
+                .collect(),
+            None => Vec::new(),
+        };
+        let grants = grants
+            .into_iter()
+            .filter(|(rule, _)| !rule.is_empty())
+            .collect::<Vec<_>>();
+        if !grants.is_empty() {
+            return grants;
         }
-        self.allowed_scope_rules.clone()
+        self.allowed_scope_rules
+            .iter()
+            .map(|s| (expand(s), ScopeAction::ALL.to_vec()))
+            .collect()
     }
 
     fn accessible_scopes(&self) -> Vec<String> {
-        self.accessible_scope_rules()
+        self.scope_grants()
+            .into_iter()
+            .filter(|(_, actions)| actions.contains(&ScopeAction::Read))
+            .map(|(rule, _)| rule)
+            .collect()
+    }
+
+    /// `{rule: [actions]}` for `memory_stats`.
+    fn scope_actions_json(&self) -> Value {
+        Value::Object(
+            self.scope_grants()
+                .into_iter()
+                .map(|(rule, actions)| (rule, json!(actions.iter().map(|a| a.as_str()).collect::<Vec<_>>())))
+                .collect(),
+        )
     }
 
     fn has_pattern_rule(&self) -> bool {
-        self.accessible_scope_rules()
-            .iter()
-            .any(|r| r == "*" || r.ends_with('*'))
+        self.accessible_scopes().iter().any(|r| r == "*" || r.ends_with('*'))
     }
 
     fn is_valid_scope(scope: &str) -> bool {
@@ -3230,18 +11619,32 @@ impl ScopeManager {
         rule == scope
     }
 
+    /// Whether the caller may read `scope`.
     fn can_access_scope(&self, scope: &str) -> bool {
+        self.permits(scope, ScopeAction::Read)
+    }
+
+    fn permits(&self, scope: &str, action: ScopeAction) -> bool {
         if !Self::is_valid_scope(scope) {
             return false;
         }
-        self.accessible_scope_rules()
+        self.scope_grants()
             .iter()
-            .any(|rule| Self::rule_matches_scope(rule, scope))
+            .any(|(rule, actions)| actions.contains(&action) && Self::rule_matches_scope(rule, scope))
+    }
+
+    /// The standard denial message when the caller lacks `action` in `scope`.
+    fn check(&self, scope: &str, action: ScopeAction) -> Result<(), String> {
+        if self.permits(scope, action) {
+            Ok(())
+        } else {
+            Err(format!("scope access denied: {scope} (needs {})", action.as_str()))
+        }
     }
 
     fn validate_scope_write(&self, scope: &str, tags: &[String]) -> Option<String> {
         if scope.starts_with("agent:") {
-            let own = format!("agent:{}", self.agent_id);
+            let own = format!("agent:{}", self.agent_id());
             if scope != own {
                 let cross_domain = tags.iter().any(|t| {
                     let lower = t.to_lowercase();
@@ -3256,51 +11659,516 @@ impl ScopeManager {
     }
 }
 
+impl TokenBucket {
+    const fn new(spec: RateLimitSpec, now: u64) -> Self {
+        Self {
+            tokens: spec.burst,
+            spec,
+            last_refill_ms: now,
+        }
+    }
+
+    fn refill(&mut self, now: u64) {
+        if now > self.last_refill_ms {
+            let refill = ((now - self.last_refill_ms) as f64 / 1000.0) * self.spec.rps;
+            self.tokens = (self.tokens + refill).min(self.spec.burst);
+            self.last_refill_ms = now;
+        }
+    }
+
+    /// Milliseconds until one token is available (0 when one is available now).
+    fn wait_ms(&self) -> u64 {
+        if self.tokens >= 1.0 {
+            return 0;
+        }
+        (((1.0 - self.tokens) / self.spec.rps) * 1000.0).ceil().max(1.0) as u64
+    }
+}
+
+impl ToolRateLimiter {
+    const MAX_SESSION_BUCKETS: usize = 10_000;
+    const DIRECT_SESSION: &'static str = "direct";
+
+    fn from_env() -> Result<Self, String> {
+        let tool_overrides = match std::env::var("PRX_MEMORY_RATE_LIMIT_TOOLS") {
+            Ok(raw) if !raw.trim().is_empty() => serde_json::from_str::<HashMap<String, RateLimitSpec>>(&raw)
+                .map_err(|e| format!("PRX_MEMORY_RATE_LIMIT_TOOLS is not a valid limit map: {e}"))?
+                .into_iter()
+                .filter_map(|(tool, spec)| Self::normalize(spec).map(|spec| (tool, spec)))
+                .collect(),
+            _ => HashMap::new(),
+        };
+        Ok(Self {
+            tool_default: Self::spec_from_env("PRX_MEMORY_RATE_LIMIT_TOOL"),
+            tool_overrides,
+            session: Self::spec_from_env("PRX_MEMORY_RATE_LIMIT_SESSION"),
+            tool_buckets: HashMap::new(),
+            session_buckets: HashMap::new(),
+        })
+    }
+
+    fn spec_from_env(prefix: &str) -> Option<RateLimitSpec> {
+        let rps = std::env::var(format!("{prefix}_RPS"))
+            .ok()?
+            .trim()
+            .parse::<f64>()
+            .ok()?;
+        let burst = std::env::var(format!("{prefix}_BURST"))
+            .ok()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .unwrap_or(rps);
+        Self::normalize(RateLimitSpec { rps, burst })
+    }
+
+    fn normalize(spec: RateLimitSpec) -> Option<RateLimitSpec> {
+        (spec.rps.is_finite() && spec.rps > 0.0).then(|| RateLimitSpec {
+            rps: spec.rps,
+            burst: if spec.burst.is_finite() {
+                spec.burst.max(1.0)
+            } else {
+                1.0
+            },
+        })
+    }
+
+    /// Takes one token from both the tool and the session bucket, or reports
+    /// which limit (`"tool"` or `"session"`) is exhausted and when to retry.
+    /// Neither bucket is charged when the call is rejected.
+    fn try_acquire(&mut self, tool: &str, session_id: Option<&str>, now: u64) -> Result<(), (&'static str, u64)> {
+        let tool_spec = self.tool_overrides.get(tool).copied().or(self.tool_default);
+        if let Some(spec) = tool_spec {
+            let bucket = self
+                .tool_buckets
+                .entry(tool.to_string())
+                .or_insert_with(|| TokenBucket::new(spec, now));
+            bucket.refill(now);
+            let wait = bucket.wait_ms();
+            if wait > 0 {
+                return Err(("tool", wait));
+            }
+        }
+        if let Some(spec) = self.session {
+            let key = session_id.unwrap_or(Self::DIRECT_SESSION);
+            if !self.session_buckets.contains_key(key) && self.session_buckets.len() >= Self::MAX_SESSION_BUCKETS {
+                // Full buckets carry no state worth keeping.
+                self.session_buckets
+                    .retain(|_, bucket| bucket.tokens < bucket.spec.burst);
+            }
+            let bucket = self
+                .session_buckets
+                .entry(key.to_string())
+                .or_insert_with(|| TokenBucket::new(spec, now));
+            bucket.refill(now);
+            let wait = bucket.wait_ms();
+            if wait > 0 {
+                return Err(("session", wait));
+            }
+            bucket.tokens -= 1.0;
+        }
+        if tool_spec.is_some()
+            && let Some(bucket) = self.tool_buckets.get_mut(tool)
+        {
+            bucket.tokens -= 1.0;
+        }
+        Ok(())
+    }
+}
+
+impl ToolPolicy {
+    fn from_env() -> Result<Self, String> {
+        let Ok(raw) = std::env::var("PRX_MEMORY_TOOL_POLICY") else {
+            return Ok(Self::default());
+        };
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let rules = serde_json::from_str::<HashMap<String, ToolPolicyRule>>(&raw)
+            .map_err(|e| format!("PRX_MEMORY_TOOL_POLICY is not a valid policy object: {e}"))?;
+        Ok(Self { rules })
+    }
+
+    fn pattern_matches(pattern: &str, action: &str) -> bool {
+        if pattern == "*" {
+            return true;
+        }
+        if let Some(prefix) = pattern.strip_suffix('*') {
+            return action.starts_with(prefix);
+        }
+        pattern == action
+    }
+
+    /// Returns the structured denial payload when `agent_id` may not run this call.
+    fn check(&self, agent_id: &str, tool: &str, arguments: Option<&Value>) -> Result<(), Value> {
+        let Some(rule) = self.rules.get(agent_id).or_else(|| self.rules.get("*")) else {
+            return Ok(());
+        };
+        let mut actions = vec![tool.to_string()];
+        if !is_dry_run_call(tool, arguments) {
+            actions.push(format!("{tool}:apply"));
+        }
+        let denial = |action: &str, rule: String| {
+            json!({
+                "kind": "permission_denied",
+                "agent_id": agent_id,
+                "tool": tool,
+                "action": action,
+                "rule": rule
+            })
+        };
+        for action in &actions {
+            if let Some(pattern) = rule.deny.iter().find(|p| Self::pattern_matches(p, action)) {
+                return Err(denial(action, format!("deny:{pattern}")));
+            }
+        }
+        if !rule.allow.is_empty()
+            && !actions
+                .iter()
+                .any(|action| rule.allow.iter().any(|p| Self::pattern_matches(p, action)))
+        {
+            return Err(denial(tool, "allow-list".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl QuotaConfig {
+    fn from_env() -> Result<Self, String> {
+        let scopes = match std::env::var("PRX_MEMORY_SCOPE_QUOTAS") {
+            Ok(raw) if !raw.trim().is_empty() => serde_json::from_str::<HashMap<String, QuotaLimit>>(&raw)
+                .map_err(|e| format!("PRX_MEMORY_SCOPE_QUOTAS is not a valid quota map: {e}"))?,
+            _ => HashMap::new(),
+        };
+        let positive = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|v| *v > 0)
+        };
+        Ok(Self {
+            scopes,
+            agent: QuotaLimit {
+                max_entries: positive("PRX_MEMORY_AGENT_QUOTA_ENTRIES"),
+                max_bytes: positive("PRX_MEMORY_AGENT_QUOTA_BYTES"),
+            },
+            agent_window_ms: env_usize("PRX_MEMORY_AGENT_QUOTA_WINDOW_MS", 86_400_000, 60_000, 30 * 86_400_000) as u64,
+        })
+    }
+
+    fn scope_limit(&self, scope: &str) -> Option<QuotaLimit> {
+        self.scopes
+            .get(scope)
+            .or_else(|| {
+                self.scopes
+                    .iter()
+                    .filter(|(pattern, _)| {
+                        pattern
+                            .strip_suffix('*')
+                            .is_some_and(|prefix| scope.starts_with(prefix))
+                    })
+                    .max_by_key(|(pattern, _)| pattern.len())
+                    .map(|(_, limit)| limit)
+            })
+            .copied()
+            .filter(|limit| limit.is_set())
+    }
+}
+
+impl QuotaLimit {
+    const fn is_set(self) -> bool {
+        self.max_entries.is_some() || self.max_bytes.is_some()
+    }
+
+    fn check(
+        self,
+        quota: &'static str,
+        subject: &str,
+        used: QuotaUsage,
+        adding: QuotaUsage,
+    ) -> Result<(), QuotaExceeded> {
+        for (resource, max, used, requested) in [
+            ("entries", self.max_entries, used.entries, adding.entries),
+            ("bytes", self.max_bytes, used.bytes, adding.bytes),
+        ] {
+            if let Some(max) = max
+                && used.saturating_add(requested) > max
+            {
+                return Err(QuotaExceeded {
+                    quota,
+                    subject: subject.to_string(),
+                    resource,
+                    max,
+                    used,
+                    requested,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The first resource that `adding` pushes across [`QUOTA_WARN_PERCENT`] of its limit.
+    fn warning(
+        self,
+        quota: &'static str,
+        subject: &str,
+        used: QuotaUsage,
+        adding: QuotaUsage,
+    ) -> Option<QuotaExceeded> {
+        [
+            ("entries", self.max_entries, used.entries, adding.entries),
+            ("bytes", self.max_bytes, used.bytes, adding.bytes),
+        ]
+        .into_iter()
+        .find_map(|(resource, max, used, requested)| {
+            let threshold = max?.saturating_mul(QUOTA_WARN_PERCENT);
+            let after = used.saturating_add(requested).saturating_mul(100);
+            (used.saturating_mul(100) < threshold && after >= threshold).then(|| QuotaExceeded {
+                quota,
+                subject: subject.to_string(),
+                resource,
+                max: max.unwrap_or_default(),
+                used,
+                requested,
+            })
+        })
+    }
+}
+
+impl QuotaUsage {
+    const fn of_text(text: &str) -> Self {
+        Self {
+            entries: 1,
+            bytes: text.len() as u64,
+        }
+    }
+
+    const fn plus(self, other: Self) -> Self {
+        Self {
+            entries: self.entries.saturating_add(other.entries),
+            bytes: self.bytes.saturating_add(other.bytes),
+        }
+    }
+}
+
+impl QuotaExceeded {
+    fn message(&self) -> String {
+        format!(
+            "quota exceeded: {} {} allows {} {} ({} used, {} requested)",
+            self.quota, self.subject, self.max, self.resource, self.used, self.requested
+        )
+    }
+
+    fn data(&self, kind: &str) -> Value {
+        json!({
+            "kind": kind,
+            "quota": self.quota,
+            "subject": self.subject,
+            "resource": self.resource,
+            "max": self.max,
+            "used": self.used,
+            "requested": self.requested
+        })
+    }
+
+    fn response(&self, id: Value) -> JsonRpcResponse {
+        JsonRpcResponse::error_with_data(id, -32007, self.message(), self.data("quota_exceeded"))
+    }
+}
+
+impl DecayTracker {
+    fn from_env(db_path: &str, cipher: Option<FieldCipher>) -> Self {
+        let defaults = DecayPolicy::default();
+        Self {
+            policy: DecayPolicy {
+                half_life_days: env_f64(
+                    "PRX_MEMORY_DECAY_HALF_LIFE_DAYS",
+                    defaults.half_life_days,
+                    0.1,
+                    36_500.0,
+                ),
+                access_boost: env_f64("PRX_MEMORY_DECAY_ACCESS_BOOST", defaults.access_boost, 0.0, 100.0),
+                ..defaults
+            },
+            archive_below: std::env::var("PRX_MEMORY_DECAY_ARCHIVE_BELOW")
+                .ok()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| *v > 0.0 && *v < 1.0),
+            archive: MemoryArchive {
+                path: PathBuf::from(format!("{db_path}.archive.jsonl")),
+                cipher,
+            },
+            access: Mutex::new(AccessLog::open(format!("{db_path}.access.json"))),
+        }
+    }
+
+    fn score(&self, entry: &MemoryEntry, access: EntryAccess, now: u64) -> RetentionScore {
+        self.policy.retention(
+            &RetentionInput {
+                created_ms: entry.timestamp_ms,
+                last_access_ms: (access.count > 0).then_some(access.last_ms),
+                access_count: access.count,
+                importance: entry.importance,
+            },
+            now,
+        )
+    }
+
+    /// Counts one access for every recalled entry; a failed write is logged and the recall still succeeds.
+    fn record_recall<'a>(&self, ids: impl IntoIterator<Item = &'a str>) {
+        let mut access = self.access.lock();
+        let now = now_ms();
+        for id in ids {
+            let seen = access.entries.entry(id.to_string()).or_default();
+            seen.count = seen.count.saturating_add(1);
+            seen.last_ms = now;
+        }
+        if let Err(err) = access.persist() {
+            tracing::warn!(error = %err, "failed to persist recall access log");
+        }
+    }
+}
+
+impl AccessLog {
+    /// Loads the persisted log; a missing or unreadable file starts empty.
+    fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice::<HashMap<String, EntryAccess>>(&raw).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    fn get(&self, id: &str) -> EntryAccess {
+        self.entries.get(id).copied().unwrap_or_default()
+    }
+
+    fn forget(&mut self, ids: &[String]) -> Result<(), String> {
+        let before = self.entries.len();
+        for id in ids {
+            self.entries.remove(id);
+        }
+        if self.entries.len() == before {
+            return Ok(());
+        }
+        self.persist()
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let raw = serde_json::to_vec(&self.entries).map_err(|e| e.to_string())?;
+        atomic_write(path, &raw).map_err(|e| format!("failed to write access log: {e}"))
+    }
+}
+
+fn scope_usage(store: &dyn StorageBackend, scope: &str) -> QuotaUsage {
+    store
+        .list(200_000)
+        .iter()
+        .filter(|e| e.scope == scope)
+        .fold(QuotaUsage::default(), |acc, e| acc.plus(QuotaUsage::of_text(&e.text)))
+}
+
+/// Whether a tool call only previews changes; `memory_compact`, `memory_lint`,
+/// `memory_recategorize`, `memory_retag` and `memory_move_scope` default to a dry run.
+fn is_dry_run_call(tool: &str, arguments: Option<&Value>) -> bool {
+    arguments
+        .and_then(|args| args.get("dry_run"))
+        .and_then(Value::as_bool)
+        .unwrap_or(matches!(
+            tool,
+            "memory_compact" | "memory_lint" | "memory_recategorize" | "memory_retag" | "memory_move_scope"
+        ))
+}
+
+/// Keywords stored with an entry whose text is `text`; none unless the governance profile enables
+/// keyword expansion.
+fn stored_keywords(policy: &GovernancePolicy, text: &str) -> Vec<String> {
+    let expansion = &policy.keyword_expansion;
+    if !expansion.enabled {
+        return Vec::new();
+    }
+    expansion_keywords(text, &expansion.aliases, expansion.synonyms, expansion.max_keywords)
+}
+
 fn store_layer_with_rules(
-    scopes: &ScopeManager,
-    auto_store_counter: &Mutex<usize>,
+    server: &McpServer,
     store: &mut dyn StorageBackend,
-    req: StoreLayerRequest,
-) -> Result<StoreLayerOutcome, String> {
-    if !scopes.can_access_scope(&req.scope) {
-        return Err(format!("scope access denied: {}", req.scope));
-    }
+    mut req: StoreLayerRequest,
+) -> Result<StoreLayerOutcome, StoreLayerError> {
+    let (scopes, policy) = (&server.scopes, &server.standards.governance);
+    scopes.check(&req.scope, ScopeAction::Write)?;
+    req.category = policy.resolve_category(&req.category)?;
+    let pii = apply_redaction(req.redaction, &mut req.text, &mut req.tags)?;
     if let Some(msg) = scopes.validate_scope_write(&req.scope, &req.tags) {
-        return Err(msg);
+        return Err(msg.into());
     }
+    let mut unlisted_tags = Vec::new();
     if req.governed {
-        validate_governed_input(&req.text, &req.category, &req.tags, req.importance_level)?;
+        policy.validate(&req.text, &req.category, &req.tags, req.importance_level)?;
+        unlisted_tags = policy.unlisted_tags(&req.tags);
+        if !unlisted_tags.is_empty() && !req.tags.iter().any(|t| t == UNLISTED_TAG) {
+            req.tags.push(UNLISTED_TAG.to_string());
+        }
     }
 
+    let mut similar = Vec::new();
     if req.governed {
-        let maybe_dup = store.recall(RecallQuery {
-            query: compact_query(&req.text, 10),
-            query_embedding: None,
-            scope: Some(req.scope.clone()),
-            category: Some(req.category.clone()),
-            limit: 3,
-            vector_weight: None,
-            lexical_weight: None,
-        });
-        if let Some(top) = maybe_dup.first() {
-            if top.score > 0.93 {
-                return Err(format!("duplicate memory likely exists: {}", top.entry.id));
-            }
+        similar = store
+            .recall(RecallQuery {
+                query: compact_query(&req.text, 10),
+                query_embedding: None,
+                scope: Some(req.scope.clone()),
+                category: Some(req.category.clone()),
+                limit: 3,
+                vector_weight: None,
+                lexical_weight: None,
+                diversity: None,
+                fusion: None,
+            })
+            .into_iter()
+            .filter(|r| r.score >= policy.similar_score)
+            .map(|r| SimilarMemory {
+                id: r.entry.id,
+                score: r.score,
+                text: r.entry.text,
+            })
+            .collect::<Vec<_>>();
+        if !req.override_duplicate && similar.first().is_some_and(|top| top.score > policy.duplicate_score) {
+            return Err(StoreLayerError::Duplicate {
+                text: req.text,
+                similar,
+            });
         }
-        if req.category == "decision" {
-            let ratio = decision_ratio_in_scope(store, &req.scope);
-            if ratio > 0.30 {
-                return Err("decision memory ratio exceeds 30% in current scope".to_string());
-            }
+        if let Some(cap) =
+            policy.exceeded_ratio_cap(&req.category, category_ratio_in_scope(store, &req.scope, &req.category))
+        {
+            return Err(format!(
+                "{} memory ratio exceeds {:.0}% in current scope",
+                req.category,
+                cap * 100.0
+            )
+            .into());
         }
     }
 
-    let embedding = if req.use_vector {
-        Some(embed_one(&req.text, EmbeddingTask::Passage)?)
+    let (embedding, embedding_model) = if req.use_vector {
+        split_embedded(Some(embed_one(
+            &server.runtime,
+            &server.embed,
+            &CallContext::default(),
+            &req.text,
+            EmbeddingTask::Passage,
+        )?))
     } else {
-        None
+        (None, None)
     };
 
+    let keywords = stored_keywords(policy, &req.text);
     let entry = store
         .store(NewMemoryEntry {
             text: req.text,
@@ -3309,6 +12177,9 @@ fn store_layer_with_rules(
             importance: req.importance,
             tags: req.tags,
             embedding,
+            embedding_model,
+            source: req.source,
+            keywords,
         })
         .map_err(|e| e.to_string())?;
 
@@ -3321,21 +12192,29 @@ fn store_layer_with_rules(
             limit: 5,
             vector_weight: None,
             lexical_weight: None,
+            diversity: None,
+            fusion: None,
         });
-        let found = verify.iter().any(|r| r.entry.id == entry.id);
+        // Recall folds entries with the same text, so an overridden duplicate shows up as its twin.
+        let found = verify
+            .iter()
+            .any(|r| r.entry.id == entry.id || (req.override_duplicate && r.entry.text == entry.text));
         if !found {
             let _ = store.forget_by_id(&entry.id);
-            return Err("post-store recall verification failed".to_string());
+            return Err("post-store recall verification failed".to_string().into());
         }
     }
 
-    let should_trigger = {
-        let mut counter = auto_store_counter.lock();
-        *counter = counter.saturating_add(1);
-        req.allow_auto_maintenance && (*counter % 100 == 0)
-    };
+    let stored_count = server.auto_store_counter.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+    let should_trigger = req.allow_auto_maintenance && stored_count.is_multiple_of(100);
     let auto_maintenance = if should_trigger {
-        Some(run_periodic_maintenance(scopes, store)?)
+        Some(run_periodic_maintenance(
+            scopes,
+            policy,
+            &server.decay,
+            store,
+            now_ms(),
+        )?)
     } else {
         None
     };
@@ -3343,55 +12222,95 @@ fn store_layer_with_rules(
     Ok(StoreLayerOutcome {
         entry,
         auto_maintenance,
+        pii,
+        unlisted_tags,
+        similar,
     })
 }
 
+/// Runs the PII detectors over `text` and applies `mode`: tags gain `pii:<kind>`, matches are
+/// masked in place, or the write is refused. Returns the detected kinds.
+fn apply_redaction(
+    mode: RedactionMode,
+    text: &mut String,
+    tags: &mut Vec<String>,
+) -> Result<Vec<&'static str>, String> {
+    if mode == RedactionMode::Off {
+        return Ok(Vec::new());
+    }
+    let findings = redact::detect(text);
+    let kinds: Vec<&'static str> = redact::kinds(&findings)
+        .into_iter()
+        .map(redact::PiiKind::label)
+        .collect();
+    if kinds.is_empty() {
+        return Ok(kinds);
+    }
+    match mode {
+        RedactionMode::Off => {}
+        RedactionMode::Tag => {
+            for kind in &kinds {
+                let tag = format!("pii:{kind}");
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+        RedactionMode::Mask => *text = redact::mask(text, &findings),
+        RedactionMode::Reject => {
+            return Err(format!(
+                "entry contains PII ({}); remove it before storing",
+                kinds.join(", ")
+            ));
+        }
+    }
+    Ok(kinds)
+}
+
+/// Plans every removal against one snapshot, writes the decayed entries to the archive, then
+/// forgets everything in a single batch. Concurrent recalls see the store either before or after
+/// the whole pass, never halfway through it.
 fn run_periodic_maintenance(
     scopes: &ScopeManager,
+    policy: &GovernancePolicy,
+    decay: &DecayTracker,
     store: &mut dyn StorageBackend,
+    now: u64,
 ) -> Result<AutoMaintenanceReport, String> {
-    let before_rows = filter_entries_by_acl(store.list(200_000), scopes, None, None);
+    let snapshot = store.snapshot();
+    let before_rows = filter_entries_by_acl(snapshot.list(usize::MAX), scopes, None, None);
+    drop(snapshot);
     let total_before = before_rows.len();
-    let mut duplicate_deleted = 0usize;
+    let mut duplicates = HashSet::new();
     let mut merged_groups = 0usize;
 
     if total_before > 1 {
-        let mut groups: HashMap<String, Vec<MemoryEntry>> = HashMap::new();
-        for row in before_rows {
+        let mut groups: HashMap<String, Vec<&MemoryEntry>> = HashMap::new();
+        for row in &before_rows {
             let key = format!("{}|{}|{}", row.scope, row.category, compact_query(&row.text, 16));
             groups.entry(key).or_default().push(row);
         }
 
-        for group in groups.into_values() {
-            if group.len() <= 1 {
+        for mut ranked in groups.into_values() {
+            if ranked.len() <= 1 {
                 continue;
             }
             merged_groups += 1;
-            let mut ranked = group;
             ranked.sort_by(|a, b| {
                 b.importance
                     .total_cmp(&a.importance)
                     .then_with(|| b.timestamp_ms.cmp(&a.timestamp_ms))
             });
-            let keep_id = ranked[0].id.clone();
-            for item in ranked.into_iter().skip(1) {
-                if item.id == keep_id {
-                    continue;
-                }
-                if matches!(store.forget_by_id(&item.id), Ok(true)) {
-                    duplicate_deleted += 1;
-                }
-            }
+            duplicates.extend(ranked.into_iter().skip(1).map(|item| item.id.clone()));
         }
     }
 
-    let after_dedup_rows = filter_entries_by_acl(store.list(200_000), scopes, None, None);
-    let mut by_scope: HashMap<String, Vec<MemoryEntry>> = HashMap::new();
-    for row in after_dedup_rows {
+    let mut by_scope: HashMap<String, Vec<&MemoryEntry>> = HashMap::new();
+    for row in before_rows.iter().filter(|row| !duplicates.contains(&row.id)) {
         by_scope.entry(row.scope.clone()).or_default().push(row);
     }
 
-    let mut rebalance_deleted = 0usize;
+    let mut rebalanced = HashSet::new();
     let mut rebalance_scopes = Vec::new();
     let mut notes = Vec::new();
     for (scope, rows) in by_scope {
@@ -3399,65 +12318,235 @@ fn run_periodic_maintenance(
         if total <= 0 {
             continue;
         }
-        let mut decisions = rows
-            .iter()
-            .filter(|e| e.category == "decision")
-            .cloned()
-            .collect::<Vec<_>>();
-        let mut decision_count = decisions.len() as isize;
-        if decision_count <= 0 {
-            continue;
-        }
-        if (decision_count as f32) / (total as f32) <= 0.30 {
-            continue;
-        }
-
-        decisions.sort_by(|a, b| {
-            a.importance
-                .total_cmp(&b.importance)
-                .then_with(|| a.timestamp_ms.cmp(&b.timestamp_ms))
-        });
-
         let mut scope_deleted = 0usize;
-        for item in decisions {
-            if (decision_count as f32) / (total as f32) <= 0.30 {
-                break;
-            }
-            if item.importance >= 1.0 {
+        for (category, &cap) in &policy.ratio_caps {
+            let mut capped = rows
+                .iter()
+                .filter(|e| &e.category == category)
+                .copied()
+                .collect::<Vec<_>>();
+            let mut capped_count = capped.len() as isize;
+            if capped_count <= 0 || (capped_count as f32) / (total as f32) <= cap {
                 continue;
             }
-            if matches!(store.forget_by_id(&item.id), Ok(true)) {
+
+            capped.sort_by(|a, b| {
+                a.importance
+                    .total_cmp(&b.importance)
+                    .then_with(|| a.timestamp_ms.cmp(&b.timestamp_ms))
+            });
+
+            for item in capped {
+                if (capped_count as f32) / (total as f32) <= cap {
+                    break;
+                }
+                if item.importance >= 1.0 {
+                    continue;
+                }
+                rebalanced.insert(item.id.clone());
                 scope_deleted += 1;
-                rebalance_deleted += 1;
-                decision_count -= 1;
+                capped_count -= 1;
                 total -= 1;
             }
+
+            if (capped_count as f32) / (total.max(1) as f32) > cap {
+                notes.push(format!(
+                    "scope {scope} still above {category} ratio after trimming non-critical {category} entries"
+                ));
+            }
         }
 
         if scope_deleted > 0 {
             rebalance_scopes.push(scope.clone());
         }
-        if (decision_count as f32) / (total.max(1) as f32) > 0.30 {
-            notes.push(format!(
-                "scope {} still above decision ratio after trimming non-critical decision entries",
-                scope
-            ));
-        }
     }
 
-    let total_after = filter_entries_by_acl(store.list(200_000), scopes, None, None).len();
+    let survivors = before_rows
+        .iter()
+        .filter(|row| !duplicates.contains(&row.id) && !rebalanced.contains(&row.id))
+        .cloned()
+        .collect::<Vec<_>>();
+    let cold = cold_entries(decay, survivors, now);
+    let cold_ids = cold.iter().map(|e| e.id.clone()).collect::<HashSet<_>>();
+    let removed = move_to_archive(
+        decay,
+        store,
+        cold,
+        duplicates.iter().chain(&rebalanced).cloned().collect(),
+        None,
+        now,
+    )?;
+    let count_in = |planned: &HashSet<String>| removed.iter().filter(|id| planned.contains(*id)).count();
+
+    let after = store.snapshot();
     Ok(AutoMaintenanceReport {
         trigger_every: 100,
         total_before,
-        total_after,
+        total_after: filter_entries_by_acl(after.list(usize::MAX), scopes, None, None).len(),
         merged_groups,
-        duplicate_deleted,
-        rebalance_deleted,
+        duplicate_deleted: count_in(&duplicates),
+        rebalance_deleted: count_in(&rebalanced),
         rebalance_scopes,
+        decay_archived: count_in(&cold_ids),
+        generation: after.generation(),
         notes,
     })
 }
 
+/// Entries whose retention fell below `PRX_MEMORY_DECAY_ARCHIVE_BELOW`; none when it is unset.
+fn cold_entries(decay: &DecayTracker, rows: Vec<MemoryEntry>, now: u64) -> Vec<MemoryEntry> {
+    let Some(threshold) = decay.archive_below else {
+        return Vec::new();
+    };
+    let access = decay.access.lock();
+    rows.into_iter()
+        .filter(|entry| {
+            !is_pending_review(entry) && decay.score(entry, access.get(&entry.id), now).retention < threshold
+        })
+        .collect()
+}
+
+/// Writes `entries` with their relations to the archive, then forgets them together with
+/// `also_forget` in one batch. The archive is written first, so a failed write loses nothing.
+/// With `superseded_by`, each archived entry also records a `supersedes` edge from that id, which
+/// `memory_restore` reconnects. Returns the ids that left the store.
+fn move_to_archive(
+    decay: &DecayTracker,
+    store: &mut dyn StorageBackend,
+    entries: Vec<MemoryEntry>,
+    also_forget: Vec<String>,
+    superseded_by: Option<&str>,
+    now: u64,
+) -> Result<Vec<String>, String> {
+    if entries.is_empty() && also_forget.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut access = decay.access.lock();
+    let archived = entries
+        .into_iter()
+        .map(|entry| ArchivedEntry {
+            relations: store
+                .relations_for(&entry.id)
+                .into_iter()
+                .chain(superseded_by.map(|from_id| MemoryRelation {
+                    from_id: from_id.to_string(),
+                    relation: "supersedes".to_string(),
+                    to_id: entry.id.clone(),
+                    timestamp_ms: now,
+                }))
+                .collect(),
+            retention: decay.score(&entry, access.get(&entry.id), now).retention,
+            entry,
+            archived_ms: now,
+            sealed_embedding: None,
+        })
+        .collect::<Vec<_>>();
+    decay.archive.append(&archived)?;
+    let mut doomed = archived.into_iter().map(|item| item.entry.id).collect::<Vec<_>>();
+    doomed.extend(also_forget);
+    let moved = store.forget_batch(&doomed).map_err(|e| e.to_string())?;
+    access.forget(&moved)?;
+    Ok(moved)
+}
+
+/// Re-stores archived entries under new ids and reconnects their relations, following
+/// restored endpoints to their new ids. Returns `(archived_id, restored entry)` pairs.
+fn restore_archived_entries(
+    store: &mut dyn StorageBackend,
+    items: &[ArchivedEntry],
+) -> Result<Vec<(String, MemoryEntry)>, String> {
+    let mut restored = Vec::with_capacity(items.len());
+    for item in items {
+        let entry = store
+            .store(NewMemoryEntry {
+                text: item.entry.text.clone(),
+                category: item.entry.category.clone(),
+                scope: item.entry.scope.clone(),
+                importance: item.entry.importance,
+                tags: item.entry.tags.clone(),
+                embedding: item.entry.embedding.clone(),
+                embedding_model: item.entry.embedding_model.clone(),
+                source: item.entry.source.clone(),
+                keywords: item.entry.keywords.clone(),
+            })
+            .map_err(|e| e.to_string())?;
+        restored.push((item.entry.id.clone(), entry));
+    }
+    let renamed = restored
+        .iter()
+        .map(|(old, entry)| (old.as_str(), entry.id.as_str()))
+        .collect::<HashMap<_, _>>();
+    for edge in items.iter().flat_map(|item| &item.relations) {
+        let from = renamed.get(edge.from_id.as_str()).copied().unwrap_or(&edge.from_id);
+        let to = renamed.get(edge.to_id.as_str()).copied().unwrap_or(&edge.to_id);
+        // The other endpoint may itself still be archived; its record keeps the edge for a later restore.
+        let _ = store.link(from, &edge.relation, to);
+    }
+    Ok(restored)
+}
+
+impl MemoryArchive {
+    /// Reads every archived entry; a missing file is an empty archive.
+    fn load(&self) -> Result<Vec<ArchivedEntry>, String> {
+        let raw = match fs::read_to_string(&self.path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(format!("failed to read memory archive: {err}")),
+        };
+        raw.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                let mut item = serde_json::from_str::<ArchivedEntry>(line)
+                    .map_err(|e| format!("memory archive line {} is invalid: {e}", idx + 1))?;
+                let sealed_embedding = item.sealed_embedding.take();
+                match &self.cipher {
+                    Some(cipher) => cipher
+                        .open_entry(&mut item.entry, sealed_embedding.as_deref())
+                        .map_err(|e| format!("memory archive line {}: {e}", idx + 1))?,
+                    None if FieldCipher::is_sealed(&item.entry.text) || sealed_embedding.is_some() => {
+                        return Err("memory archive is encrypted but no encryption key is configured".to_string());
+                    }
+                    None => {}
+                }
+                Ok(item)
+            })
+            .collect()
+    }
+
+    fn append(&self, entries: &[ArchivedEntry]) -> Result<(), String> {
+        let raw = self.encode(entries)?;
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(&raw))
+            .map_err(|e| format!("failed to append memory archive: {e}"))
+    }
+
+    /// Replaces the archive with `entries`.
+    fn rewrite(&self, entries: &[ArchivedEntry]) -> Result<(), String> {
+        let raw = self.encode(entries)?;
+        atomic_write(&self.path, &raw).map_err(|e| format!("failed to write memory archive: {e}"))
+    }
+
+    fn encode(&self, entries: &[ArchivedEntry]) -> Result<Vec<u8>, String> {
+        let mut raw = Vec::new();
+        for item in entries {
+            if let Some(cipher) = &self.cipher {
+                let mut sealed = item.clone();
+                sealed.sealed_embedding = cipher.seal_entry(&mut sealed.entry).map_err(|e| e.to_string())?;
+                serde_json::to_writer(&mut raw, &sealed)
+            } else {
+                serde_json::to_writer(&mut raw, item)
+            }
+            .map_err(|e| e.to_string())?;
+            raw.push(b'\n');
+        }
+        Ok(raw)
+    }
+}
+
 fn render_template_resource(uri: &str, standards: &StandardizationConfig) -> Option<RenderedResource> {
     let params = parse_uri_query(uri);
     if uri.starts_with("prx://templates/memory-store") {
@@ -3587,6 +12676,426 @@ fn parse_uri_query(uri: &str) -> HashMap<String, String> {
     out
 }
 
+/// `PRX_MEMORY_POSTGRES_URL` is required; the table defaults to `prx_memories`.
+#[cfg(feature = "postgres-backend")]
+fn postgres_config_from_env() -> Result<PostgresConfig, String> {
+    let url = std::env::var("PRX_MEMORY_POSTGRES_URL")
+        .map_err(|_| "PRX_MEMORY_POSTGRES_URL is required when PRX_MEMORY_BACKEND=postgres".to_string())?;
+    let dimensions = match std::env::var("PRX_MEMORY_POSTGRES_DIM") {
+        Ok(raw) => Some(
+            raw.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| format!("PRX_MEMORY_POSTGRES_DIM must be a positive integer, got {raw}"))?,
+        ),
+        Err(_) => None,
+    };
+    Ok(PostgresConfig {
+        url,
+        table: std::env::var("PRX_MEMORY_POSTGRES_TABLE").unwrap_or_else(|_| "prx_memories".to_string()),
+        dimensions,
+    })
+}
+
+/// `PRX_MEMORY_QDRANT_URL` and `PRX_MEMORY_QDRANT_DIM` are required; the collection defaults to
+/// `prx_memories`.
+#[cfg(feature = "qdrant-backend")]
+fn qdrant_config_from_env() -> Result<QdrantConfig, String> {
+    let url = std::env::var("PRX_MEMORY_QDRANT_URL")
+        .map_err(|_| "PRX_MEMORY_QDRANT_URL is required when PRX_MEMORY_BACKEND=qdrant".to_string())?;
+    let raw_dim = std::env::var("PRX_MEMORY_QDRANT_DIM").unwrap_or_default();
+    let dimensions = raw_dim
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("PRX_MEMORY_QDRANT_DIM must be the embedding dimension, got {raw_dim:?}"))?;
+    Ok(QdrantConfig {
+        url,
+        collection: std::env::var("PRX_MEMORY_QDRANT_COLLECTION").unwrap_or_else(|_| "prx_memories".to_string()),
+        api_key: std::env::var("PRX_MEMORY_QDRANT_API_KEY")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+        dimensions,
+    })
+}
+
+/// `PRX_MEMORY_S3_BUCKET` is required; the key defaults to `prx-memory/memory-db.json`.
+#[cfg(feature = "s3-backend")]
+fn s3_config_from_env() -> Result<S3Config, String> {
+    let var = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    Ok(S3Config {
+        bucket: var("PRX_MEMORY_S3_BUCKET")
+            .ok_or_else(|| "PRX_MEMORY_S3_BUCKET is required when PRX_MEMORY_BACKEND=s3".to_string())?,
+        key: var("PRX_MEMORY_S3_KEY").unwrap_or_else(|| "prx-memory/memory-db.json".to_string()),
+        endpoint: var("PRX_MEMORY_S3_ENDPOINT"),
+        region: var("PRX_MEMORY_S3_REGION"),
+        allow_http: var("PRX_MEMORY_S3_ALLOW_HTTP").is_some_and(|v| {
+            let lowered = v.to_ascii_lowercase();
+            lowered == "1" || lowered == "true" || lowered == "on" || lowered == "yes"
+        }),
+    })
+}
+
+/// `PRX_MEMORY_MIGRATE_BACKUP=0` skips the copy taken before an old store file is upgraded.
+fn migration_options_from_env() -> MigrationOptions {
+    let backup = std::env::var("PRX_MEMORY_MIGRATE_BACKUP").map_or(true, |v| {
+        !matches!(v.trim().to_ascii_lowercase().as_str(), "0" | "false" | "off" | "no")
+    });
+    MigrationOptions { dry_run: false, backup }
+}
+
+fn change_log_enabled() -> bool {
+    std::env::var("PRX_MEMORY_CHANGE_LOG").is_ok_and(|v| {
+        let lowered = v.trim().to_ascii_lowercase();
+        lowered == "1" || lowered == "true" || lowered == "on" || lowered == "yes"
+    })
+}
+
+fn session_log_enabled() -> bool {
+    std::env::var("PRX_MEMORY_SESSION_LOG")
+        .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on" | "yes"))
+}
+
+/// Rebuilds sessions from journal records, applied in order.
+fn sessions_from_records(records: Vec<SessionRecord>) -> HashMap<String, SessionState> {
+    let now = now_ms();
+    let mut sessions: HashMap<String, SessionState> = HashMap::new();
+    for record in records {
+        if let SessionRecord::Ended { session } = &record {
+            sessions.remove(session);
+            continue;
+        }
+        let state = sessions
+            .entry(record.session().to_string())
+            .or_insert_with(|| SessionState::new(None, now));
+        match record {
+            SessionRecord::Started { agent_id, .. } => state.agent_id = agent_id,
+            SessionRecord::Named { agent_id, .. } => state.agent_id = Some(agent_id),
+            SessionRecord::Event {
+                seq,
+                created_ms,
+                payload,
+                ..
+            } => {
+                state.events.push_back(StreamEvent {
+                    seq,
+                    payload,
+                    created_ms,
+                });
+                while state.events.len() > 512 {
+                    let _ = state.events.pop_front();
+                }
+                state.next_seq = state.next_seq.max(seq.saturating_add(1));
+            }
+            SessionRecord::Acked { seq, .. } => {
+                state.ack(seq);
+                state.next_seq = state.next_seq.max(seq.saturating_add(1));
+            }
+            SessionRecord::Noted { note, .. } => state.add_note(note),
+            SessionRecord::Filtered { kinds, .. } => state.notifications = Some(kinds.into_iter().collect()),
+            SessionRecord::Subscribed { uri, .. } => {
+                state.subscriptions.insert(uri);
+            }
+            SessionRecord::Unsubscribed { uri, .. } => {
+                state.subscriptions.remove(&uri);
+            }
+            SessionRecord::Ended { .. } => {}
+        }
+    }
+    sessions
+}
+
+fn query_log_enabled() -> bool {
+    std::env::var("PRX_MEMORY_QUERY_LOG").is_ok_and(|v| {
+        let lowered = v.trim().to_ascii_lowercase();
+        lowered == "1" || lowered == "true" || lowered == "on" || lowered == "yes"
+    })
+}
+
+/// Entry ids a successful call of `tool` counts as using, for the query log's `accessed_ids`.
+fn entry_ids_used_by(tool: &str, arguments: Option<&Value>) -> Vec<String> {
+    const USING_TOOLS: [&str; 5] = [
+        "memory_update",
+        "memory_link",
+        "memory_unlink",
+        "memory_merge",
+        "memory_approve",
+    ];
+    let Some(args) = arguments.filter(|_| USING_TOOLS.contains(&tool)) else {
+        return Vec::new();
+    };
+    let single = ["id", "from_id", "to_id"]
+        .into_iter()
+        .filter_map(|key| args.get(key).and_then(Value::as_str));
+    let many = args
+        .get("ids")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str);
+    single.chain(many).map(str::to_string).collect()
+}
+
+/// `PRX_MEMORY_SUGGEST_IMPORTANCE`: `memory_store` suggests the importance level when callers omit it.
+fn importance_suggestions_enabled() -> bool {
+    std::env::var("PRX_MEMORY_SUGGEST_IMPORTANCE")
+        .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on" | "yes"))
+}
+
+/// Scores `text` against the closest entries of `scope` with [`suggest_importance`].
+fn suggest_importance_for(store: &dyn StorageBackend, text: &str, category: &str, scope: &str) -> ImportanceSuggestion {
+    let neighbors = store
+        .recall(RecallQuery {
+            query: compact_query(text, 10),
+            query_embedding: None,
+            scope: Some(scope.to_string()),
+            category: None,
+            limit: 5,
+            vector_weight: None,
+            lexical_weight: None,
+            diversity: None,
+            fusion: None,
+        })
+        .into_iter()
+        .map(|r| ImportanceNeighbor {
+            score: r.score,
+            importance: r.entry.importance,
+        })
+        .collect::<Vec<_>>();
+    suggest_importance(text, category, &neighbors)
+}
+
+/// Logs a suggestion next to the entry it was applied to, so the heuristics can be tuned against
+/// later importance edits.
+fn log_importance_suggestion(entry: &MemoryEntry, suggestion: &ImportanceSuggestion) {
+    tracing::info!(
+        entry_id = %entry.id,
+        category = %entry.category,
+        scope = %entry.scope,
+        level = suggestion.level,
+        score = f64::from(suggestion.score),
+        reasons = %suggestion.reasons.join(","),
+        "importance suggested"
+    );
+}
+
+fn entity_extraction_enabled() -> bool {
+    match std::env::var("PRX_MEMORY_EXTRACT_ENTITIES") {
+        Ok(v) => {
+            let lowered = v.trim().to_ascii_lowercase();
+            lowered == "1" || lowered == "true" || lowered == "on" || lowered == "yes"
+        }
+        Err(_) => false,
+    }
+}
+
+/// Records one `entity` memory per entity detected in `source`, tagged with
+/// `entity:<kind>`, `entity-name:<name>` and `source:<id>` for later lookup.
+fn store_entity_links(store: &mut dyn StorageBackend, source: &MemoryEntry) -> Vec<Value> {
+    if source.category == "entity" {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    for entity in extract_entities(&source.text, &source.tags) {
+        let kind = entity.kind.as_str();
+        let stored = store.store(NewMemoryEntry {
+            text: format!("Entity {kind}: {}", entity.name),
+            category: "entity".to_string(),
+            scope: source.scope.clone(),
+            importance: 0.25,
+            tags: vec![
+                format!("entity:{kind}"),
+                format!("entity-name:{}", entity.name),
+                format!("source:{}", source.id),
+            ],
+            embedding: None,
+            embedding_model: None,
+            source: None,
+            keywords: Vec::new(),
+        });
+        if let Ok(entry) = stored {
+            out.push(json!({"id": entry.id, "kind": kind, "name": entity.name}));
+        }
+    }
+    out
+}
+
+fn matches_source(entry: &MemoryEntry, filter: &MemorySource) -> bool {
+    entry.source.as_ref().is_some_and(|source| source.matches(filter))
+}
+
+fn is_pending_review(entry: &MemoryEntry) -> bool {
+    entry.tags.iter().any(|t| t == PENDING_REVIEW_TAG)
+}
+
+/// Whether `entry` may be shared by team sync: reviewed, and within `policy`.
+fn sync_eligibility(policy: &GovernancePolicy, entry: &MemoryEntry) -> Result<(), String> {
+    if is_pending_review(entry) {
+        return Err("entry is pending review".to_string());
+    }
+    policy.validate(
+        &entry.text,
+        &entry.category,
+        &entry.tags,
+        importance_level_from_numeric(entry.importance),
+    )
+}
+
+/// Re-stores a pending entry without its review tag, moving its relations to the new id.
+fn approve_pending_entry(store: &mut dyn StorageBackend, entry: &MemoryEntry) -> Result<MemoryEntry, String> {
+    let relations = store.relations_for(&entry.id);
+    let approved = store
+        .replace(
+            &entry.id,
+            NewMemoryEntry {
+                text: entry.text.clone(),
+                category: entry.category.clone(),
+                scope: entry.scope.clone(),
+                importance: entry.importance,
+                tags: entry
+                    .tags
+                    .iter()
+                    .filter(|t| *t != PENDING_REVIEW_TAG)
+                    .cloned()
+                    .collect(),
+                embedding: entry.embedding.clone(),
+                embedding_model: entry.embedding_model.clone(),
+                source: entry.source.clone(),
+                keywords: entry.keywords.clone(),
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("{}: missing during approval", entry.id))?;
+    for edge in relations {
+        let from = if edge.from_id == entry.id {
+            &approved.id
+        } else {
+            &edge.from_id
+        };
+        let to = if edge.to_id == entry.id {
+            &approved.id
+        } else {
+            &edge.to_id
+        };
+        let _ = store.link(from, &edge.relation, to);
+    }
+    Ok(approved)
+}
+
+/// Rewrites `entry` with a new scope, category and tags, keeping its relations on the replacement id.
+fn rewrite_entry(
+    store: &mut dyn StorageBackend,
+    entry: &MemoryEntry,
+    scope: &str,
+    category: &str,
+    tags: Vec<String>,
+) -> Result<MemoryEntry, String> {
+    let relations = store.relations_for(&entry.id);
+    let updated = store
+        .replace(
+            &entry.id,
+            NewMemoryEntry {
+                text: entry.text.clone(),
+                category: category.to_string(),
+                scope: scope.to_string(),
+                importance: entry.importance,
+                tags,
+                embedding: entry.embedding.clone(),
+                embedding_model: entry.embedding_model.clone(),
+                source: entry.source.clone(),
+                keywords: entry.keywords.clone(),
+            },
+        )
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("{}: missing during rewrite", entry.id))?;
+    for edge in relations {
+        let from = if edge.from_id == entry.id {
+            &updated.id
+        } else {
+            &edge.from_id
+        };
+        let to = if edge.to_id == entry.id {
+            &updated.id
+        } else {
+            &edge.to_id
+        };
+        let _ = store.link(from, &edge.relation, to);
+    }
+    Ok(updated)
+}
+
+/// Ids of the `entity` memories extracted from `source_id`.
+fn entity_link_ids(store: &dyn StorageBackend, source_id: &str) -> Vec<String> {
+    let marker = format!("source:{source_id}");
+    store
+        .list(200_000)
+        .into_iter()
+        .filter(|e| e.category == "entity" && e.tags.contains(&marker))
+        .map(|e| e.id)
+        .collect()
+}
+
+fn forget_entity_links(store: &mut dyn StorageBackend, source_id: &str) -> usize {
+    entity_link_ids(store, source_id)
+        .iter()
+        .filter(|mid| matches!(store.forget_by_id(mid), Ok(true)))
+        .count()
+}
+
+/// One hop of relation expansion for a recalled entry; linked memories outside
+/// the caller's scopes are dropped.
+fn expand_entry_relations(store: &dyn StorageBackend, access: &ScopeManager, entry_id: &str) -> Vec<Value> {
+    let edges: Vec<MemoryRelation> = store.relations_for(entry_id);
+    if edges.is_empty() {
+        return Vec::new();
+    }
+    let rows = store.list(200_000);
+    edges
+        .into_iter()
+        .filter_map(|edge| {
+            let (direction, other) = if edge.from_id == entry_id {
+                ("out", edge.to_id)
+            } else {
+                ("in", edge.from_id)
+            };
+            let linked = rows
+                .iter()
+                .find(|e| e.id == other && access.can_access_scope(&e.scope))?;
+            Some(json!({
+                "relation": edge.relation,
+                "direction": direction,
+                "id": linked.id,
+                "category": linked.category,
+                "text": linked.text
+            }))
+        })
+        .collect()
+}
+
+fn parse_entity_tags(tags: &[String]) -> Option<(String, String, String)> {
+    let mut kind = None;
+    let mut name = None;
+    let mut source = None;
+    for tag in tags {
+        if let Some(v) = tag.strip_prefix("entity-name:") {
+            name = Some(v.to_string());
+        } else if let Some(v) = tag.strip_prefix("entity:") {
+            kind = Some(v.to_string());
+        } else if let Some(v) = tag.strip_prefix("source:") {
+            source = Some(v.to_string());
+        }
+    }
+    Some((kind?, name?, source?))
+}
+
 fn enforce_dual_layer() -> bool {
     match std::env::var("PRX_MEMORY_ENFORCE_DUAL_LAYER") {
         Ok(v) => {
@@ -3694,128 +13203,158 @@ fn canonicalize_tag(tag: &str) -> String {
     }
 }
 
-fn resolve_importance(level: Option<&str>, numeric: Option<f32>) -> Result<(f32, &'static str), String> {
-    if let Some(lv) = level {
-        return match lv {
-            "low" => Ok((0.25, "low")),
-            "medium" => Ok((0.50, "medium")),
-            "high" => Ok((0.75, "high")),
-            "critical" => Ok((1.0, "critical")),
-            _ => Err("importance_level must be low|medium|high|critical".to_string()),
-        };
-    }
+/// Entries added per day or week over the last `periods` buckets (oldest first, UTC-aligned, weeks
+/// starting Monday), with per-bucket category counts and running totals, plus average importance
+/// and embedding coverage over all of `entries`.
+fn stats_trend(entries: &[MemoryEntry], bucket_days: u64, periods: usize, now: u64) -> Value {
+    const DAY_MS: u64 = 86_400_000;
+    // 1970-01-01 was a Thursday; shifting by three days puts week boundaries on Mondays.
+    let offset_days = if bucket_days == 7 { 3 } else { 0 };
+    let bucket_ms = bucket_days * DAY_MS;
+    let bucket_start = |ms: u64| {
+        let shifted = ms + offset_days * DAY_MS;
+        (shifted - shifted % bucket_ms).saturating_sub(offset_days * DAY_MS)
+    };
+    let current = bucket_start(now);
+    let first = current.saturating_sub(bucket_ms * (periods as u64 - 1));
 
-    if let Some(score) = numeric {
-        let s = score.clamp(0.0, 1.0);
-        if (s - 0.25).abs() < 1e-3 {
-            return Ok((0.25, "low"));
-        }
-        if (s - 0.50).abs() < 1e-3 {
-            return Ok((0.50, "medium"));
+    let mut earlier = 0usize;
+    let mut buckets: BTreeMap<u64, Vec<&MemoryEntry>> = (0..periods as u64)
+        .map(|i| (first + i * bucket_ms, Vec::new()))
+        .collect();
+    for entry in entries {
+        let start = bucket_start(entry.timestamp_ms);
+        match buckets.get_mut(&start) {
+            Some(added) => added.push(entry),
+            None if start < first => earlier += 1,
+            None => {}
         }
-        if (s - 0.75).abs() < 1e-3 {
-            return Ok((0.75, "high"));
-        }
-        if (s - 1.0).abs() < 1e-3 {
-            return Ok((1.0, "critical"));
-        }
-        return Err("numeric importance must be one of 0.25/0.50/0.75/1.0; prefer importance_level".to_string());
     }
 
-    Ok((0.50, "medium"))
-}
+    let mut total = earlier;
+    let series = buckets
+        .into_iter()
+        .map(|(start_ms, added)| {
+            total += added.len();
+            let mut by_category: BTreeMap<&str, usize> = BTreeMap::new();
+            for entry in &added {
+                *by_category.entry(entry.category.as_str()).or_insert(0) += 1;
+            }
+            json!({
+                "start_ms": start_ms,
+                "start": utc_date(start_ms),
+                "added": added.len(),
+                "total": total,
+                "by_category": by_category,
+                "average_importance": average_importance(&added),
+                "embedded": added.iter().filter(|e| e.embedding.is_some()).count()
+            })
+        })
+        .collect::<Vec<_>>();
 
-fn importance_level_from_numeric(v: f32) -> &'static str {
-    if v >= 0.95 {
-        "critical"
-    } else if v >= 0.70 {
-        "high"
-    } else if v >= 0.45 {
-        "medium"
-    } else {
-        "low"
-    }
+    let all = entries.iter().collect::<Vec<_>>();
+    let embedded = entries.iter().filter(|e| e.embedding.is_some()).count();
+    json!({
+        "bucket": if bucket_days == 7 { "week" } else { "day" },
+        "periods": periods,
+        "earlier": earlier,
+        "series": series,
+        "average_importance": average_importance(&all),
+        "embedding_coverage_pct": percent(embedded, entries.len())
+    })
 }
 
-fn validate_governed_input(text: &str, category: &str, tags: &[String], importance_level: &str) -> Result<(), String> {
-    if text.trim().is_empty() {
-        return Err("text cannot be empty".to_string());
-    }
-    if text.chars().count() > 500 {
-        return Err("entry must be <= 500 chars".to_string());
-    }
-    if text.contains("```") || text.contains("stacktrace") || text.contains("raw conversation") {
-        return Err("log-like or raw content is not allowed".to_string());
+/// Tag usage over `entries`: per `project:`/`tool:`/`domain:` dimension the count of each value and
+/// of entries without one, the `top` most used tags, and tags `policy`'s taxonomy does not list.
+fn stats_tags(entries: &[MemoryEntry], policy: &GovernancePolicy, top: usize) -> Value {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for tag in entries.iter().flat_map(|e| &e.tags) {
+        *counts.entry(tag.as_str()).or_insert(0) += 1;
     }
+    let ranked = |filter: &dyn Fn(&str) -> bool, limit: usize| {
+        let mut ranked = counts
+            .iter()
+            .filter(|(tag, _)| filter(tag))
+            .map(|(tag, count)| (*tag, *count))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(tag, count)| json!({"tag": tag, "count": count}))
+            .collect::<Vec<_>>()
+    };
+    let dimensions = ["project", "tool", "domain"]
+        .into_iter()
+        .map(|dimension| {
+            let prefix = format!("{dimension}:");
+            let values = counts
+                .iter()
+                .filter_map(|(tag, count)| Some((tag.strip_prefix(prefix.as_str())?, *count)))
+                .collect::<BTreeMap<_, _>>();
+            let missing = entries
+                .iter()
+                .filter(|e| !e.tags.iter().any(|t| t.starts_with(&prefix)))
+                .count();
+            (dimension, json!({"values": values, "missing": missing}))
+        })
+        .collect::<BTreeMap<_, _>>();
+    json!({
+        "distinct": counts.len(),
+        "dimensions": dimensions,
+        "top": ranked(&|_| true, top),
+        "unlisted": ranked(&|tag| !policy.unlisted_tags(&[tag.to_string()]).is_empty(), usize::MAX)
+    })
+}
 
-    let cat = category.to_lowercase();
-    let allowed = ["preference", "fact", "decision", "entity", "other"];
-    if !allowed.contains(&cat.as_str()) {
-        return Err("category must be one of preference|fact|decision|entity|other".to_string());
-    }
-    if tags.is_empty() {
-        return Err("tags are required in governed mode".to_string());
-    }
-    let has_project = tags.iter().any(|t| t.starts_with("project:"));
-    let has_tool = tags.iter().any(|t| t.starts_with("tool:"));
-    let has_domain = tags.iter().any(|t| t.starts_with("domain:"));
-    if !(has_project && has_tool && has_domain) {
-        return Err("tags must include project:*, tool:*, domain:*".to_string());
+fn average_importance(entries: &[&MemoryEntry]) -> Option<f64> {
+    if entries.is_empty() {
+        return None;
     }
+    let sum: f64 = entries.iter().map(|e| f64::from(e.importance)).sum();
+    Some(sum / f64::from(u32::try_from(entries.len()).unwrap_or(u32::MAX)))
+}
 
-    let lower = text.to_lowercase();
-    if cat == "fact"
-        && !(lower.contains("pitfall:")
-            && lower.contains("cause:")
-            && lower.contains("fix:")
-            && lower.contains("prevention:"))
-    {
-        return Err("fact entry must follow Pitfall/Cause/Fix/Prevention template".to_string());
-    }
-    if cat == "decision" && !lower.contains("decision principle") {
-        return Err("decision entry must follow Decision principle template".to_string());
-    }
-    if cat == "decision" && importance_level == "low" {
-        return Err("decision importance must be medium/high/critical".to_string());
+fn percent(part: usize, whole: usize) -> f64 {
+    let as_f64 = |n: usize| f64::from(u32::try_from(n).unwrap_or(u32::MAX));
+    if whole == 0 {
+        0.0
+    } else {
+        as_f64(part) * 100.0 / as_f64(whole)
     }
-    Ok(())
 }
 
-fn compact_query(text: &str, max_terms: usize) -> String {
-    let mut out = Vec::new();
-    let mut seen = HashSet::new();
-    for t in text
-        .split(|c: char| !c.is_alphanumeric())
-        .filter(|s| s.len() >= 3)
-        .map(|s| s.to_lowercase())
-    {
-        if seen.insert(t.clone()) {
-            out.push(t);
-        }
-        if out.len() >= max_terms {
-            break;
-        }
-    }
-    out.join(" ")
+/// `YYYY-MM-DD` for a UTC millisecond timestamp (Hinnant's civil-from-days).
+fn utc_date(ms: u64) -> String {
+    let days = i64::try_from(ms / 86_400_000).unwrap_or(i64::MAX) + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
 }
 
-fn decision_ratio_in_scope(store: &dyn StorageBackend, scope: &str) -> f32 {
+fn category_ratio_in_scope(store: &dyn StorageBackend, scope: &str, category: &str) -> f32 {
     let rows = store.list(200_000);
     let mut total = 0usize;
-    let mut decision = 0usize;
+    let mut matching = 0usize;
     for e in rows {
         if e.scope != scope {
             continue;
         }
         total += 1;
-        if e.category == "decision" {
-            decision += 1;
+        if e.category == category {
+            matching += 1;
         }
     }
     if total == 0 {
         0.0
     } else {
-        decision as f32 / total as f32
+        matching as f32 / total as f32
     }
 }
 
@@ -3827,9 +13366,94 @@ struct RecallAclRequest {
     candidate_pool: usize,
     vector_weight: Option<f32>,
     lexical_weight: Option<f32>,
+    diversity: Option<f32>,
+    fusion: Option<FusionMode>,
+}
+
+/// Keyset position in `memory_list` order: newest timestamp first, then highest id.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ListCursor {
+    timestamp_ms: u64,
+    id_seq: u64,
+    id: String,
+}
+
+impl ListCursor {
+    fn of(entry: &MemoryEntry) -> Self {
+        Self {
+            timestamp_ms: entry.timestamp_ms,
+            id_seq: id_sequence(&entry.id),
+            id: entry.id.clone(),
+        }
+    }
+
+    fn encode(&self) -> String {
+        format!("l1:{}:{}", self.timestamp_ms, self.id)
+    }
+
+    fn decode(raw: &str) -> Option<Self> {
+        let mut parts = raw.splitn(3, ':');
+        if parts.next()? != "l1" {
+            return None;
+        }
+        let timestamp_ms = parts.next()?.parse::<u64>().ok()?;
+        let id = parts.next()?.to_string();
+        Some(Self {
+            timestamp_ms,
+            id_seq: id_sequence(&id),
+            id,
+        })
+    }
+}
+
+/// Keyset position in `memory_recall` order: highest score first, then lowest id.
+/// `served` counts items already returned so the next page can widen the candidate pool.
+#[derive(Debug, Clone)]
+struct RecallCursor {
+    score: f32,
+    id: String,
+    served: usize,
+}
+
+impl RecallCursor {
+    fn after(result: &RecallResult, served: usize) -> Self {
+        Self {
+            score: result.score,
+            id: result.entry.id.clone(),
+            served,
+        }
+    }
+
+    fn is_before(&self, result: &RecallResult) -> bool {
+        match result.score.total_cmp(&self.score) {
+            std::cmp::Ordering::Less => true,
+            std::cmp::Ordering::Equal => result.entry.id > self.id,
+            std::cmp::Ordering::Greater => false,
+        }
+    }
+
+    fn encode(&self) -> String {
+        format!("r1:{:08x}:{}:{}", self.score.to_bits(), self.served, self.id)
+    }
+
+    fn decode(raw: &str) -> Option<Self> {
+        let mut parts = raw.splitn(4, ':');
+        if parts.next()? != "r1" {
+            return None;
+        }
+        let score = f32::from_bits(u32::from_str_radix(parts.next()?, 16).ok()?);
+        let served = parts.next()?.parse::<usize>().ok()?;
+        let id = parts.next()?.to_string();
+        Some(Self { score, id, served })
+    }
+}
+
+/// 0 for ULID ids, which then order by the id itself.
+fn id_sequence(id: &str) -> u64 {
+    prx_memory_storage::id_sequence(id).unwrap_or(0)
 }
 
-fn recall_with_acl(store: &dyn StorageBackend, access: &ScopeManager, req: RecallAclRequest) -> Vec<RecallResult> {
+fn recall_with_acl(store: &StoreSnapshot, access: &ScopeManager, req: RecallAclRequest) -> Vec<RecallResult> {
     if let Some(scope) = req.requested_scope {
         if !access.can_access_scope(&scope) {
             return Vec::new();
@@ -3842,10 +13466,12 @@ fn recall_with_acl(store: &dyn StorageBackend, access: &ScopeManager, req: Recal
             limit: req.candidate_pool,
             vector_weight: req.vector_weight,
             lexical_weight: req.lexical_weight,
+            diversity: req.diversity,
+            fusion: req.fusion,
         });
     }
 
-    let rules = access.accessible_scope_rules();
+    let rules = access.accessible_scopes();
     if access.has_pattern_rule() {
         let mut all = store.recall(RecallQuery {
             query: req.query,
@@ -3855,6 +13481,8 @@ fn recall_with_acl(store: &dyn StorageBackend, access: &ScopeManager, req: Recal
             limit: req.candidate_pool,
             vector_weight: req.vector_weight,
             lexical_weight: req.lexical_weight,
+            diversity: req.diversity,
+            fusion: req.fusion,
         });
         all.retain(|r| access.can_access_scope(&r.entry.scope));
         all.sort_by(|a, b| b.score.total_cmp(&a.score));
@@ -3872,6 +13500,8 @@ fn recall_with_acl(store: &dyn StorageBackend, access: &ScopeManager, req: Recal
             limit: req.candidate_pool,
             vector_weight: req.vector_weight,
             lexical_weight: req.lexical_weight,
+            diversity: req.diversity,
+            fusion: req.fusion,
         });
         merged.append(&mut one);
     }
@@ -3882,9 +13512,29 @@ fn recall_with_acl(store: &dyn StorageBackend, access: &ScopeManager, req: Recal
     merged
 }
 
-fn filter_entries_by_acl(
+fn decode_import_entries(records: Vec<Value>) -> Result<Vec<ImportedMemoryEntry>, String> {
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| serde_json::from_value(record).map_err(|e| format!("record {}: {e}", i + 1)))
+        .collect()
+}
+
+fn filter_entries_by_acl(
+    entries: Vec<prx_memory_storage::MemoryEntry>,
+    access: &ScopeManager,
+    requested_scope: Option<&str>,
+    requested_category: Option<&str>,
+) -> Vec<prx_memory_storage::MemoryEntry> {
+    filter_entries_for(entries, access, ScopeAction::Read, requested_scope, requested_category)
+}
+
+/// Entries in the requested scope, or in every scope where the caller has `action` when none is
+/// requested. A requested scope must already have been checked.
+fn filter_entries_for(
     entries: Vec<prx_memory_storage::MemoryEntry>,
     access: &ScopeManager,
+    action: ScopeAction,
     requested_scope: Option<&str>,
     requested_category: Option<&str>,
 ) -> Vec<prx_memory_storage::MemoryEntry> {
@@ -3892,7 +13542,7 @@ fn filter_entries_by_acl(
         .into_iter()
         .filter(|e| match requested_scope {
             Some(scope) => e.scope == scope,
-            None => access.can_access_scope(&e.scope),
+            None => access.permits(&e.scope, action),
         })
         .filter(|e| match requested_category {
             Some(cat) => e.category == cat,
@@ -3901,66 +13551,476 @@ fn filter_entries_by_acl(
         .collect()
 }
 
-fn embed_one(text: &str, task: EmbeddingTask) -> Result<Vec<f32>, String> {
+fn embed_one(
+    rt: &tokio::runtime::Runtime,
+    embed: &Mutex<EmbedRuntime>,
+    ctx: &CallContext,
+    text: &str,
+    task: EmbeddingTask,
+) -> Result<EmbeddedText, String> {
+    embed_batch(rt, embed, ctx, &[text.to_string()], task)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "vector embedding returned empty vector".to_string())
+}
+
+/// Embeds several texts with a single provider call. Cache hits are served locally and the
+/// remaining inputs share one rate-limit token, so callers should chunk large inputs first.
+fn embed_batch(
+    rt: &tokio::runtime::Runtime,
+    embed: &Mutex<EmbedRuntime>,
+    ctx: &CallContext,
+    texts: &[String],
+    task: EmbeddingTask,
+) -> Result<Vec<EmbeddedText>, String> {
     let provider_hint = std::env::var("PRX_EMBED_PROVIDER")
         .unwrap_or_else(|_| "openai-compatible".to_string())
         .to_ascii_lowercase();
-    let key = format!("{}|{:?}|{}", provider_hint, task, text.trim().to_ascii_lowercase());
-
-    {
-        let mut runtime = embed_runtime().lock();
-        if let Some(v) = runtime.cache_get(&key, now_ms()) {
-            return Ok(v);
-        }
-    }
+    let model_hint = std::env::var("PRX_EMBED_MODEL").unwrap_or_default();
+    let keys = texts
+        .iter()
+        .map(|text| {
+            format!(
+                "{}|{}|{:?}|{}",
+                provider_hint,
+                model_hint,
+                task,
+                text.trim().to_ascii_lowercase()
+            )
+        })
+        .collect::<Vec<_>>();
 
-    let wait_ms = {
-        let mut runtime = embed_runtime().lock();
-        runtime.acquire_rate_limit(now_ms())
+    let mut vectors = {
+        let mut runtime = embed.lock();
+        let now = now_ms();
+        keys.iter().map(|key| runtime.lookup(key, now)).collect::<Vec<_>>()
     };
-    if wait_ms > 0 {
-        std::thread::sleep(Duration::from_millis(wait_ms));
-    }
+    let missing = vectors
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.is_none())
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
 
-    let provider = build_embedding_provider_from_env(None)?;
-    let rt = tokio::runtime::Runtime::new().map_err(|e| format!("vector runtime initialization failed: {e}"))?;
-    let output = rt
-        .block_on(async {
-            provider
-                .embed(EmbeddingRequest {
-                    inputs: vec![text.to_string()],
+    if !missing.is_empty() {
+        let wait_ms = {
+            let mut runtime = embed.lock();
+            runtime.acquire_rate_limit(now_ms())
+        };
+        if wait_ms > 0 {
+            std::thread::sleep(Duration::from_millis(wait_ms));
+        }
+
+        let inputs = missing
+            .iter()
+            .filter_map(|idx| texts.get(*idx).cloned())
+            .collect::<Vec<_>>();
+        let provider = build_embedding_provider_from_env(None)?;
+        let _span = tracing::info_span!(
+            "provider",
+            kind = "embed",
+            provider = provider.name(),
+            inputs = inputs.len()
+        )
+        .entered();
+        let output = rt
+            .block_on(cancellable(
+                ctx,
+                provider.embed(EmbeddingRequest {
+                    inputs,
                     task: Some(task),
                     dimensions: None,
                     normalized: Some(true),
-                })
-                .await
-        })
-        .map_err(|e| format!("vector embedding failed: {}", provider_error_en_embed(&e)))?;
+                }),
+            ))
+            .and_then(|res| res.map_err(|e| format!("vector embedding failed: {}", provider_error_en_embed(&e))))
+            .inspect_err(|err| tracing::warn!(error = %err, "embedding provider call failed"))?;
+        if output.vectors.len() != missing.len() {
+            return Err(format!(
+                "vector embedding returned {} vectors for {} inputs",
+                output.vectors.len(),
+                missing.len()
+            ));
+        }
 
-    let vector = output
-        .vectors
+        let model = format!("{}/{}", output.provider, output.model);
+        let mut runtime = embed.lock();
+        *runtime.stats.served_by.entry(output.provider).or_insert(0) += 1;
+        let now = now_ms();
+        for (idx, vector) in missing.into_iter().zip(output.vectors) {
+            if vector.is_empty() {
+                return Err("vector embedding returned empty vector".to_string());
+            }
+            if let (Some(key), Some(slot)) = (keys.get(idx), vectors.get_mut(idx)) {
+                let embedded = EmbeddedText {
+                    model: model.clone(),
+                    vector,
+                };
+                runtime.remember(key.clone(), embedded.clone(), now);
+                *slot = Some(embedded);
+            }
+        }
+        let writes = runtime.take_disk_writes();
+        drop(runtime);
+        if let Some((log, write)) = writes {
+            flush_embed_disk(embed, &log, &write);
+        }
+    }
+
+    vectors
         .into_iter()
-        .next()
-        .filter(|v| !v.is_empty())
-        .ok_or_else(|| "vector embedding returned empty vector".to_string())?;
+        .map(|v| v.ok_or_else(|| "vector embedding returned empty vector".to_string()))
+        .collect()
+}
 
-    {
-        let mut runtime = embed_runtime().lock();
-        runtime.cache_put(key, vector.clone(), now_ms());
+impl JobRegistry {
+    /// Loads persisted jobs; a missing or unreadable file starts an empty registry.
+    fn open(path: impl Into<std::path::PathBuf>) -> Self {
+        let path = path.into();
+        let jobs = fs::read(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice::<Vec<ReembedJob>>(&raw).ok())
+            .unwrap_or_default();
+        let next_seq = jobs
+            .iter()
+            .filter_map(|job| job.id.strip_prefix("job-").and_then(|n| n.parse::<u64>().ok()))
+            .max()
+            .map_or(1, |n| n + 1);
+        Self {
+            path: Some(path),
+            jobs,
+            next_seq,
+        }
+    }
+
+    fn running_job_ids(&self) -> Vec<String> {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == "running")
+            .map(|job| job.id.clone())
+            .collect()
+    }
+
+    fn get(&self, job_id: &str) -> Option<&ReembedJob> {
+        self.jobs.iter().find(|job| job.id == job_id)
+    }
+
+    fn get_mut(&mut self, job_id: &str) -> Option<&mut ReembedJob> {
+        self.jobs.iter_mut().find(|job| job.id == job_id)
+    }
+
+    fn create(
+        &mut self,
+        scope: Option<String>,
+        category: Option<String>,
+        batch_size: usize,
+        pending_ids: Vec<String>,
+    ) -> Result<String, String> {
+        let now = now_ms();
+        let id = format!("job-{}", self.next_seq);
+        self.next_seq += 1;
+        self.jobs.push(ReembedJob {
+            id: id.clone(),
+            status: "running".to_string(),
+            scope,
+            category,
+            batch_size,
+            total: pending_ids.len(),
+            pending_ids,
+            updated: 0,
+            failed: 0,
+            batches: 0,
+            errors: Vec::new(),
+            created_ms: now,
+            updated_ms: now,
+        });
+        self.prune_finished();
+        self.persist()?;
+        Ok(id)
+    }
+
+    fn prune_finished(&mut self) {
+        let finished = self.jobs.iter().filter(|job| job.status != "running").count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        self.jobs.retain(|job| {
+            if excess > 0 && job.status != "running" {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let raw = serde_json::to_vec_pretty(&self.jobs).map_err(|e| e.to_string())?;
+        atomic_write(path, &raw).map_err(|e| format!("failed to write job checkpoint: {e}"))
+    }
+}
+
+fn reembed_job_summary(job: &ReembedJob) -> Value {
+    json!({
+        "job_id": job.id,
+        "kind": "reembed",
+        "status": job.status,
+        "scope": job.scope,
+        "category": job.category,
+        "total": job.total,
+        "processed": job.total - job.pending_ids.len(),
+        "remaining": job.pending_ids.len(),
+        "updated": job.updated,
+        "failed": job.failed,
+        "batches": job.batches,
+        "batch_size": job.batch_size,
+        "errors": job.errors,
+        "created_ms": job.created_ms,
+        "updated_ms": job.updated_ms,
+    })
+}
+
+/// Builds missing indexes and folds unindexed rows in after the store opened, since a vector
+/// backfill can take minutes on a large table. Calls wait on the store lock while it runs.
+fn spawn_index_build(store: Arc<RwLock<Box<dyn StorageBackend>>>) {
+    let spawned = std::thread::Builder::new()
+        .name("prx-memory-index".to_string())
+        .spawn(move || {
+            let built = store.write().ensure_indexes();
+            match built {
+                Ok(status) => tracing::info!(%status, "memory store indexes built"),
+                Err(error) => tracing::warn!(%error, "failed to build memory store indexes"),
+            }
+        });
+    if let Err(error) = spawned {
+        tracing::warn!(%error, "failed to start the index build");
+    }
+}
+
+fn spawn_reembed_job(
+    rt: Arc<tokio::runtime::Runtime>,
+    embed: Arc<Mutex<EmbedRuntime>>,
+    store: Arc<RwLock<Box<dyn StorageBackend>>>,
+    jobs: Arc<Mutex<JobRegistry>>,
+    notices: Arc<Mutex<Vec<ServerNotice>>>,
+    job_id: String,
+) {
+    std::thread::spawn(move || run_reembed_job(&rt, &embed, &store, &jobs, &notices, &job_id));
+}
+
+/// Queues a `job` notice for a job that stopped running, visible to callers that can read its scope.
+fn notice_job_finished(notices: &Mutex<Vec<ServerNotice>>, job: &ReembedJob) {
+    notices.lock().push(ServerNotice {
+        kind: "job",
+        scope: job.scope.clone(),
+        agent: None,
+        params: reembed_job_summary(job),
+    });
+}
+
+/// Processes a reembed job batch by batch, checkpointing the remaining ids after each batch.
+/// Ids that no longer exist (already re-embedded before a crash, or forgotten) are skipped.
+fn run_reembed_job(
+    rt: &tokio::runtime::Runtime,
+    embed: &Mutex<EmbedRuntime>,
+    store: &RwLock<Box<dyn StorageBackend>>,
+    jobs: &Mutex<JobRegistry>,
+    notices: &Mutex<Vec<ServerNotice>>,
+    job_id: &str,
+) {
+    loop {
+        let Some(chunk_ids) = jobs
+            .lock()
+            .get(job_id)
+            .map(|job| job.pending_ids.iter().take(job.batch_size).cloned().collect::<Vec<_>>())
+        else {
+            return;
+        };
+        if chunk_ids.is_empty() {
+            let mut registry = jobs.lock();
+            if let Some(job) = registry.get_mut(job_id) {
+                job.status = "completed".to_string();
+                job.updated_ms = now_ms();
+                notice_job_finished(notices, job);
+            }
+            let _ = registry.persist();
+            return;
+        }
+
+        let chunk = {
+            let rows = store.read().list(200_000);
+            rows.into_iter()
+                .filter(|e| chunk_ids.contains(&e.id))
+                .collect::<Vec<_>>()
+        };
+        let mut updated = 0usize;
+        let mut errors = Vec::new();
+        if !chunk.is_empty() {
+            let texts = chunk.iter().map(|item| item.text.clone()).collect::<Vec<_>>();
+            match embed_batch(rt, embed, &CallContext::default(), &texts, EmbeddingTask::Passage) {
+                Ok(embeddings) => {
+                    let mut locked = store.write();
+                    for (item, embedding) in chunk.into_iter().zip(embeddings) {
+                        match reembed_entry(locked.as_mut(), item, embedding) {
+                            Ok(()) => updated += 1,
+                            Err(err) => errors.push(err),
+                        }
+                    }
+                }
+                Err(err) => errors.extend(chunk.iter().map(|item| format!("{}: {}", item.id, err))),
+            }
+        }
+
+        let mut registry = jobs.lock();
+        let Some(job) = registry.get_mut(job_id) else {
+            return;
+        };
+        job.pending_ids.drain(..chunk_ids.len().min(job.pending_ids.len()));
+        job.batches += 1;
+        job.updated += updated;
+        job.failed += errors.len();
+        let room = MAX_JOB_ERRORS.saturating_sub(job.errors.len());
+        job.errors.extend(errors.into_iter().take(room));
+        job.updated_ms = now_ms();
+        if registry.persist().is_err() {
+            if let Some(job) = registry.get_mut(job_id) {
+                job.status = "failed".to_string();
+                job.errors.push("failed to write job checkpoint".to_string());
+                notice_job_finished(notices, job);
+            }
+            return;
+        }
+    }
+}
+
+fn reembed_entry(
+    store: &mut dyn StorageBackend,
+    item: prx_memory_storage::MemoryEntry,
+    embedding: EmbeddedText,
+) -> Result<(), String> {
+    let replaced = store.replace(
+        &item.id,
+        NewMemoryEntry {
+            text: item.text,
+            category: item.category,
+            scope: item.scope,
+            importance: item.importance,
+            tags: item.tags,
+            embedding: Some(embedding.vector),
+            embedding_model: Some(embedding.model),
+            source: item.source,
+            keywords: item.keywords,
+        },
+    );
+    match replaced {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(format!("{}: missing during reembed", item.id)),
+        Err(err) => Err(format!("{}: {}", item.id, err)),
+    }
+}
+
+fn split_embedded(embedded: Option<EmbeddedText>) -> (Option<Vec<f32>>, Option<String>) {
+    embedded.map_or((None, None), |e| (Some(e.vector), Some(e.model)))
+}
+
+/// Compares stored document embeddings with the query embedding and describes any
+/// model or dimension mismatch, since cosine scores across models are meaningless.
+fn embedding_mismatch_report(entries: &[prx_memory_storage::MemoryEntry], query: &EmbeddedText) -> Option<Value> {
+    let query_dim = query.vector.len();
+    let mut stored: HashMap<(String, usize), usize> = HashMap::new();
+    let mut mismatched = 0usize;
+    for entry in entries {
+        let Some(embedding) = entry.embedding.as_ref() else {
+            continue;
+        };
+        let dim = entry.embedding_dim.unwrap_or(embedding.len());
+        let model = entry.embedding_model.clone().unwrap_or_else(|| "unknown".to_string());
+        let model_differs = entry.embedding_model.as_ref().is_some_and(|m| *m != query.model);
+        if dim != query_dim || model_differs {
+            mismatched += 1;
+        }
+        *stored.entry((model, dim)).or_insert(0) += 1;
+    }
+    if mismatched == 0 {
+        return None;
+    }
+    let mut stored_models = stored
+        .into_iter()
+        .map(|((model, dim), count)| json!({"model": model, "dim": dim, "count": count}))
+        .collect::<Vec<_>>();
+    stored_models.sort_by(|a, b| b["count"].as_u64().cmp(&a["count"].as_u64()));
+    Some(json!({
+        "kind": "embedding_mismatch",
+        "query_model": query.model,
+        "query_dim": query_dim,
+        "mismatched_entries": mismatched,
+        "stored_models": stored_models,
+        "hint": "The embedding model or dimension changed. Run memory_reembed for the affected scope to rebuild vectors."
+    }))
+}
+
+/// Cancellation state for one `tools/call`: a client cancel flag plus an optional deadline.
+#[derive(Debug, Clone, Default)]
+struct CallContext {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    /// Stream session the call arrived on, for tools that push events ahead of their response.
+    session: Option<String>,
+}
+
+impl CallContext {
+    fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.deadline = timeout.map(|t| Instant::now() + t);
+        self
+    }
+
+    fn cancel_reason(&self) -> Option<&'static str> {
+        if self.cancelled.load(AtomicOrdering::Relaxed) {
+            return Some("cancelled");
+        }
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+            .then_some("timed out")
+    }
+}
+
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Drives a provider future until it finishes or `ctx` is cancelled / past its deadline.
+/// Dropping the unfinished future aborts the provider's in-flight HTTP request.
+async fn cancellable<F: Future>(ctx: &CallContext, fut: F) -> Result<F::Output, String> {
+    let mut fut = std::pin::pin!(fut);
+    loop {
+        if let Some(reason) = ctx.cancel_reason() {
+            return Err(format!("request {reason}"));
+        }
+        let mut tick = std::pin::pin!(tokio::time::sleep(CANCEL_POLL_INTERVAL));
+        let done = std::future::poll_fn(|cx| match fut.as_mut().poll(cx) {
+            Poll::Ready(output) => Poll::Ready(Some(output)),
+            Poll::Pending => tick.as_mut().poll(cx).map(|()| None),
+        })
+        .await;
+        if let Some(output) = done {
+            return Ok(output);
+        }
     }
-    Ok(vector)
 }
 
 fn semantic_rerank_with_remote(
+    rt: &tokio::runtime::Runtime,
+    ctx: &CallContext,
     query: &str,
     results: &mut [RecallResult],
     embedding_provider_hint: Option<&str>,
     rerank_provider_hint: Option<&str>,
 ) -> Result<Option<String>, String> {
-    match cross_encoder_rerank_with_remote(query, results, rerank_provider_hint) {
+    match cross_encoder_rerank_with_remote(rt, ctx, query, results, rerank_provider_hint) {
         Ok(()) => Ok(None),
         Err(cross_err) => {
-            semantic_rerank_with_embeddings(query, results, embedding_provider_hint)?;
+            if let Some(reason) = ctx.cancel_reason() {
+                return Err(format!("remote rerank {reason}"));
+            }
+            semantic_rerank_with_embeddings(rt, ctx, query, results, embedding_provider_hint)?;
             Ok(Some(format!(
                 "Cross-encoder rerank unavailable: {}. Used embedding cosine fallback.",
                 cross_err
@@ -3970,24 +14030,32 @@ fn semantic_rerank_with_remote(
 }
 
 fn cross_encoder_rerank_with_remote(
+    rt: &tokio::runtime::Runtime,
+    ctx: &CallContext,
     query: &str,
     results: &mut [RecallResult],
     provider_hint: Option<&str>,
 ) -> Result<(), String> {
     let provider = build_rerank_provider_from_env(provider_hint)?;
     let docs = results.iter().map(|r| r.entry.text.clone()).collect::<Vec<_>>();
-    let rt = tokio::runtime::Runtime::new().map_err(|e| format!("Cross-encoder runtime initialization failed: {e}"))?;
+    let _span = tracing::info_span!(
+        "provider",
+        kind = "rerank",
+        provider = provider.name(),
+        inputs = docs.len()
+    )
+    .entered();
     let res = rt
-        .block_on(async {
-            provider
-                .rerank(RerankRequest {
-                    query: query.to_string(),
-                    documents: docs,
-                    top_n: Some(results.len()),
-                })
-                .await
-        })
-        .map_err(|e| format!("Cross-encoder request failed: {}", provider_error_en_rerank(&e)))?;
+        .block_on(cancellable(
+            ctx,
+            provider.rerank(RerankRequest {
+                query: query.to_string(),
+                documents: docs,
+                top_n: Some(results.len()),
+            }),
+        ))
+        .and_then(|res| res.map_err(|e| format!("Cross-encoder request failed: {}", provider_error_en_rerank(&e))))
+        .inspect_err(|err| tracing::warn!(error = %err, "rerank provider call failed"))?;
 
     if res.items.is_empty() {
         return Err("Cross-encoder returned empty results.".to_string());
@@ -4024,40 +14092,47 @@ fn cross_encoder_rerank_with_remote(
 }
 
 fn semantic_rerank_with_embeddings(
+    rt: &tokio::runtime::Runtime,
+    ctx: &CallContext,
     query: &str,
     results: &mut [RecallResult],
     provider_hint: Option<&str>,
 ) -> Result<(), String> {
     let provider = build_embedding_provider_from_env(provider_hint)?;
-    let rt =
-        tokio::runtime::Runtime::new().map_err(|e| format!("Third-party vector service initialization failed: {e}"))?;
+    let _span = tracing::info_span!(
+        "provider",
+        kind = "embed_rerank",
+        provider = provider.name(),
+        inputs = results.len() + 1
+    )
+    .entered();
 
     let query_embedding = rt
-        .block_on(async {
-            provider
-                .embed(EmbeddingRequest {
-                    inputs: vec![query.to_string()],
-                    task: Some(EmbeddingTask::Query),
-                    dimensions: None,
-                    normalized: Some(true),
-                })
-                .await
-        })
-        .map_err(|e| format!("Third-party vector request failed: {}", provider_error_en_embed(&e)))?;
+        .block_on(cancellable(
+            ctx,
+            provider.embed(EmbeddingRequest {
+                inputs: vec![query.to_string()],
+                task: Some(EmbeddingTask::Query),
+                dimensions: None,
+                normalized: Some(true),
+            }),
+        ))
+        .and_then(|res| res.map_err(|e| format!("Third-party vector request failed: {}", provider_error_en_embed(&e))))
+        .inspect_err(|err| tracing::warn!(error = %err, "embedding provider call failed"))?;
 
     let doc_inputs = results.iter().map(|r| r.entry.text.clone()).collect::<Vec<_>>();
     let doc_embedding = rt
-        .block_on(async {
-            provider
-                .embed(EmbeddingRequest {
-                    inputs: doc_inputs,
-                    task: Some(EmbeddingTask::Passage),
-                    dimensions: None,
-                    normalized: Some(true),
-                })
-                .await
-        })
-        .map_err(|e| format!("Third-party vector request failed: {}", provider_error_en_embed(&e)))?;
+        .block_on(cancellable(
+            ctx,
+            provider.embed(EmbeddingRequest {
+                inputs: doc_inputs,
+                task: Some(EmbeddingTask::Passage),
+                dimensions: None,
+                normalized: Some(true),
+            }),
+        ))
+        .and_then(|res| res.map_err(|e| format!("Third-party vector request failed: {}", provider_error_en_embed(&e))))
+        .inspect_err(|err| tracing::warn!(error = %err, "embedding provider call failed"))?;
 
     let q = query_embedding.vectors.first().ok_or_else(|| {
         "Third-party vector service returned an empty result. Check model name and account quota.".to_string()
@@ -4090,7 +14165,7 @@ fn build_rerank_provider_from_env(
         .or_else(|| std::env::var("PRX_RERANK_PROVIDER").ok().map(|s| s.to_lowercase()))
         .unwrap_or_else(|| "jina".to_string());
 
-    match provider.as_str() {
+    let built = match provider.as_str() {
         "none" => Err("cross-encoder rerank disabled by configuration".to_string()),
         "jina" => {
             let api_key = std::env::var("PRX_RERANK_API_KEY")
@@ -4146,7 +14221,8 @@ fn build_rerank_provider_from_env(
             })
         }
         _ => Err("Unsupported rerank provider. Use jina, cohere, pinecone, pinecone-compatible, or none.".to_string()),
-    }
+    };
+    built.map(|p| rerank_with_retry(p, retry_policy_from_env("PRX_RERANK")))
 }
 
 fn build_embedding_provider_from_env(
@@ -4157,13 +14233,59 @@ fn build_embedding_provider_from_env(
         .or_else(|| std::env::var("PRX_EMBED_PROVIDER").ok().map(|s| s.to_lowercase()))
         .unwrap_or_else(|| "openai-compatible".to_string());
 
-    match provider.as_str() {
+    let chain = provider
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    if chain.len() <= 1 {
+        return build_single_embedding_provider_from_env(chain.first().copied().unwrap_or("openai-compatible"));
+    }
+
+    // Providers that cannot be configured (for example a missing key) are left out of the chain;
+    // the chain only fails to build when none of them can.
+    let mut providers = Vec::new();
+    let mut first_err = None;
+    for name in chain {
+        match build_single_embedding_provider_from_env(name) {
+            Ok(p) => providers.push(p),
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+    }
+    if providers.is_empty() {
+        return Err(first_err.unwrap_or_else(|| "No embedding provider configured.".to_string()));
+    }
+    FailoverEmbeddingProvider::new(providers)
+        .map(|p| Arc::new(p) as Arc<dyn prx_memory_embed::EmbeddingProvider>)
+        .map_err(|e| {
+            format!(
+                "Embedding failover initialization failed: {}",
+                provider_error_en_embed(&e)
+            )
+        })
+}
+
+fn build_single_embedding_provider_from_env(
+    provider: &str,
+) -> Result<Arc<dyn prx_memory_embed::EmbeddingProvider>, String> {
+    build_single_embedding_provider(provider, &|name| std::env::var(name).ok())
+}
+
+/// Builds one embedding provider from the settings `env` returns.
+fn build_single_embedding_provider(
+    provider: &str,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<Arc<dyn prx_memory_embed::EmbeddingProvider>, String> {
+    let built = match provider {
         "openai-compatible" => {
-            let api_key = std::env::var("PRX_EMBED_API_KEY")
-                .map_err(|_| "PRX_EMBED_API_KEY is not configured. Remote semantic recall is disabled.".to_string())?;
-            let model = std::env::var("PRX_EMBED_MODEL").unwrap_or_else(|_| "text-embedding-3-small".to_string());
+            let api_key = env("PRX_EMBED_API_KEY").ok_or_else(|| {
+                "PRX_EMBED_API_KEY is not configured. Remote semantic recall is disabled.".to_string()
+            })?;
+            let model = env("PRX_EMBED_MODEL").unwrap_or_else(|| "text-embedding-3-small".to_string());
             let mut cfg = OpenAiCompatibleConfig::new(api_key, model);
-            if let Ok(base_url) = std::env::var("PRX_EMBED_BASE_URL") {
+            if let Some(base_url) = env("PRX_EMBED_BASE_URL") {
                 cfg.base_url = base_url;
             }
             build_embedding_provider(EmbeddingProviderConfig::OpenAiCompatible(cfg)).map_err(|e| {
@@ -4174,15 +14296,14 @@ fn build_embedding_provider_from_env(
             })
         }
         "jina" => {
-            let api_key = std::env::var("PRX_EMBED_API_KEY")
-                .or_else(|_| std::env::var("JINA_API_KEY"))
-                .map_err(|_| {
+            let api_key = env("PRX_EMBED_API_KEY")
+                .or_else(|| env("JINA_API_KEY"))
+                .ok_or_else(|| {
                     "PRX_EMBED_API_KEY or JINA_API_KEY is not configured. Jina recall is disabled.".to_string()
                 })?;
-            let model =
-                std::env::var("PRX_EMBED_MODEL").unwrap_or_else(|_| "jina-embeddings-v5-text-small".to_string());
+            let model = env("PRX_EMBED_MODEL").unwrap_or_else(|| "jina-embeddings-v5-text-small".to_string());
             let mut cfg = OpenAiCompatibleConfig::new(api_key, model);
-            cfg.base_url = std::env::var("PRX_EMBED_BASE_URL").unwrap_or_else(|_| "https://api.jina.ai".to_string());
+            cfg.base_url = env("PRX_EMBED_BASE_URL").unwrap_or_else(|| "https://api.jina.ai".to_string());
             cfg.task_query = Some("retrieval.query".to_string());
             cfg.task_passage = Some("retrieval.passage".to_string());
             build_embedding_provider(EmbeddingProviderConfig::Jina(cfg)).map_err(|e| {
@@ -4192,26 +14313,272 @@ fn build_embedding_provider_from_env(
                 )
             })
         }
-        "gemini" => {
-            let api_key = std::env::var("PRX_EMBED_API_KEY")
-                .or_else(|_| std::env::var("GEMINI_API_KEY"))
-                .map_err(|_| {
-                    "PRX_EMBED_API_KEY or GEMINI_API_KEY is not configured. Gemini recall is disabled.".to_string()
-                })?;
-            let model = std::env::var("PRX_EMBED_MODEL").unwrap_or_else(|_| "gemini-embedding-001".to_string());
-            let mut cfg = GeminiConfig::new(api_key, model);
-            if let Ok(base_url) = std::env::var("PRX_EMBED_BASE_URL") {
-                cfg.base_url = base_url;
+        "gemini" => {
+            let api_key = env("PRX_EMBED_API_KEY")
+                .or_else(|| env("GEMINI_API_KEY"))
+                .ok_or_else(|| {
+                    "PRX_EMBED_API_KEY or GEMINI_API_KEY is not configured. Gemini recall is disabled.".to_string()
+                })?;
+            let model = env("PRX_EMBED_MODEL").unwrap_or_else(|| "gemini-embedding-001".to_string());
+            let mut cfg = GeminiConfig::new(api_key, model);
+            if let Some(base_url) = env("PRX_EMBED_BASE_URL") {
+                cfg.base_url = base_url;
+            }
+            build_embedding_provider(EmbeddingProviderConfig::Gemini(cfg)).map_err(|e| {
+                format!(
+                    "Gemini vector service initialization failed: {}",
+                    provider_error_en_embed(&e)
+                )
+            })
+        }
+        "mistral" => {
+            let api_key = env("PRX_EMBED_API_KEY")
+                .or_else(|| env("MISTRAL_API_KEY"))
+                .ok_or_else(|| {
+                    "PRX_EMBED_API_KEY or MISTRAL_API_KEY is not configured. Mistral recall is disabled.".to_string()
+                })?;
+            let model = env("PRX_EMBED_MODEL").unwrap_or_else(|| "mistral-embed".to_string());
+            let mut cfg = OpenAiCompatibleConfig::new(api_key, model);
+            cfg.base_url = env("PRX_EMBED_BASE_URL").unwrap_or_else(|| "https://api.mistral.ai".to_string());
+            build_embedding_provider(EmbeddingProviderConfig::Mistral(cfg)).map_err(|e| {
+                format!(
+                    "Mistral vector service initialization failed: {}",
+                    provider_error_en_embed(&e)
+                )
+            })
+        }
+        "ollama" => {
+            let model = env("PRX_EMBED_MODEL").unwrap_or_else(|| "nomic-embed-text".to_string());
+            let mut cfg = OllamaConfig::new(model);
+            if let Some(base_url) = env("PRX_EMBED_BASE_URL") {
+                cfg.base_url = base_url;
+            }
+            build_embedding_provider(EmbeddingProviderConfig::Ollama(cfg)).map_err(|e| {
+                format!(
+                    "Ollama vector service initialization failed: {}",
+                    provider_error_en_embed(&e)
+                )
+            })
+        }
+        _ => Err("Unsupported provider. Use openai-compatible, jina, gemini, mistral, or ollama.".to_string()),
+    };
+    built.map(|p| embed_with_retry(p, retry_policy_from_env("PRX_EMBED")))
+}
+
+/// Reads `<PREFIX>_RETRY_MAX_ATTEMPTS`, `<PREFIX>_RETRY_BASE_MS`, `<PREFIX>_RETRY_MAX_MS`
+/// and `<PREFIX>_RETRY_JITTER` for the provider retry policy.
+fn retry_policy_from_env(prefix: &str) -> RetryPolicy {
+    let max_attempts = u32::try_from(env_usize(&format!("{prefix}_RETRY_MAX_ATTEMPTS"), 3, 1, 10)).unwrap_or(3);
+    let base_ms = env_usize(&format!("{prefix}_RETRY_BASE_MS"), 200, 1, 60_000);
+    let max_ms = env_usize(&format!("{prefix}_RETRY_MAX_MS"), 5_000, base_ms, 300_000);
+    let jitter = std::env::var(format!("{prefix}_RETRY_JITTER")).map_or(true, |v| {
+        !matches!(v.trim().to_ascii_lowercase().as_str(), "0" | "false" | "off" | "no")
+    });
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::from_millis(base_ms as u64),
+        max_delay: Duration::from_millis(max_ms as u64),
+        jitter,
+        ..RetryPolicy::default()
+    }
+}
+
+fn build_summarize_provider_from_env() -> Result<Arc<dyn prx_memory_summarize::SummarizeProvider>, String> {
+    let provider =
+        std::env::var("PRX_SUMMARIZE_PROVIDER").map_or_else(|_| "openai-compatible".to_string(), |s| s.to_lowercase());
+
+    match provider.as_str() {
+        "none" => Err("summarization disabled by configuration".to_string()),
+        "openai-compatible" => {
+            let api_key = std::env::var("PRX_SUMMARIZE_API_KEY")
+                .or_else(|_| std::env::var("PRX_EMBED_API_KEY"))
+                .map_err(|_| "PRX_SUMMARIZE_API_KEY/PRX_EMBED_API_KEY not configured for summarization.".to_string())?;
+            let model = std::env::var("PRX_SUMMARIZE_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+            let mut cfg = OpenAiCompatibleSummarizeConfig::new(api_key, model);
+            if let Ok(base_url) = std::env::var("PRX_SUMMARIZE_BASE_URL") {
+                cfg.base_url = base_url;
+            }
+            build_summarize_provider(SummarizeProviderConfig::OpenAiCompatible(cfg)).map_err(|e| {
+                format!(
+                    "Summarization service initialization failed: {}",
+                    provider_error_en_summarize(&e)
+                )
+            })
+        }
+        _ => Err("Unsupported summarize provider. Use openai-compatible or none.".to_string()),
+    }
+}
+
+/// Weights of the `memory_staleness` signals. An entry's confidence combines its signals as
+/// independent evidence, so an old entry nobody recalls (0.51) outranks one that is only old.
+const STALE_SUPERSEDED: f64 = 0.9;
+const STALE_CONTRADICTED: f64 = 0.7;
+const STALE_OLD: f64 = 0.3;
+const STALE_NEVER_RECALLED: f64 = 0.3;
+/// Days an entry may go unrecalled before that counts against it, capped at `older_than_days`.
+const STALE_RECALL_GRACE_DAYS: f64 = 30.0;
+
+fn age_days(timestamp_ms: u64, now: u64) -> f64 {
+    Duration::from_millis(now.saturating_sub(timestamp_ms)).as_secs_f64() / 86_400.0
+}
+
+/// The reasons to think `entry` is outdated, as `(signal, weight, report item)`. `relations` are the
+/// entry's edges and `visible` maps the ids the caller can read to their timestamps.
+fn stale_signals(
+    entry: &MemoryEntry,
+    relations: &[MemoryRelation],
+    visible: &HashMap<String, u64>,
+    used: EntryAccess,
+    now: u64,
+    older_than_days: f64,
+) -> Vec<(&'static str, f64, Value)> {
+    let mut signals = Vec::new();
+    let superseded_by = relations
+        .iter()
+        .filter(|r| r.relation == "supersedes" && r.to_id == entry.id && visible.contains_key(&r.from_id))
+        .map(|r| r.from_id.as_str())
+        .collect::<Vec<_>>();
+    if !superseded_by.is_empty() {
+        signals.push((
+            "superseded",
+            STALE_SUPERSEDED,
+            json!({"signal": "superseded", "weight": STALE_SUPERSEDED, "by": superseded_by}),
+        ));
+    }
+    let contradicted_by = relations
+        .iter()
+        .filter(|r| r.relation == "contradicts")
+        .map(|r| if r.from_id == entry.id { &r.to_id } else { &r.from_id })
+        .filter(|other| visible.get(*other).is_some_and(|ts| *ts > entry.timestamp_ms))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if !contradicted_by.is_empty() {
+        signals.push((
+            "contradicted",
+            STALE_CONTRADICTED,
+            json!({"signal": "contradicted", "weight": STALE_CONTRADICTED, "by": contradicted_by}),
+        ));
+    }
+    let age = age_days(entry.timestamp_ms, now);
+    if age >= older_than_days {
+        signals.push(("old", STALE_OLD, json!({"signal": "old", "weight": STALE_OLD})));
+    }
+    if used.count == 0 && age >= older_than_days.min(STALE_RECALL_GRACE_DAYS) {
+        signals.push((
+            "never_recalled",
+            STALE_NEVER_RECALLED,
+            json!({"signal": "never_recalled", "weight": STALE_NEVER_RECALLED}),
+        ));
+    }
+    signals
+}
+
+/// Near-duplicate entries with the suggested survivor first.
+struct DuplicateCluster<'a> {
+    members: Vec<&'a MemoryEntry>,
+    /// Each other member's similarity to the survivor, in member order.
+    similarity: Vec<f32>,
+}
+
+impl DuplicateCluster<'_> {
+    fn to_json(&self) -> Value {
+        let survivor = self.members.first().map(|e| e.id.as_str());
+        json!({
+            "survivor": survivor,
+            "scope": self.members.first().map(|e| e.scope.as_str()),
+            "category": self.members.first().map(|e| e.category.as_str()),
+            "members": self.members.iter().enumerate().map(|(i, e)| json!({
+                "id": e.id,
+                "text": e.text,
+                "importance": e.importance,
+                "timestamp_ms": e.timestamp_ms,
+                "similarity": i.checked_sub(1).and_then(|i| self.similarity.get(i))
+            })).collect::<Vec<_>>()
+        })
+    }
+}
+
+/// The representative of `i`'s set in a union-find `parent` table.
+fn union_root(parent: &[usize], mut i: usize) -> usize {
+    while let Some(&p) = parent.get(i).filter(|p| **p != i) {
+        i = p;
+    }
+    i
+}
+
+/// Groups `entries` of the same scope and category whose text signatures match (with `signature`,
+/// at similarity 1) or whose embeddings from the same model reach the `embedding` cosine threshold,
+/// largest cluster first. The survivor is the most important member, then the newest.
+fn duplicate_clusters(entries: &[MemoryEntry], signature: bool, embedding: Option<f32>) -> Vec<DuplicateCluster<'_>> {
+    let similarity = |a: &MemoryEntry, b: &MemoryEntry| -> Option<f32> {
+        if signature && compact_query(&a.text, 16) == compact_query(&b.text, 16) {
+            return Some(1.0);
+        }
+        let threshold = embedding?;
+        let (Some(x), Some(y)) = (&a.embedding, &b.embedding) else {
+            return None;
+        };
+        if a.embedding_model != b.embedding_model {
+            return None;
+        }
+        cosine_similarity(x, y).ok().filter(|score| *score >= threshold)
+    };
+
+    let mut groups: BTreeMap<(&str, &str), Vec<usize>> = BTreeMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        groups.entry((&entry.scope, &entry.category)).or_default().push(i);
+    }
+    let mut parent = (0..entries.len()).collect::<Vec<_>>();
+    for members in groups.values() {
+        for (n, &a) in members.iter().enumerate() {
+            for &b in members.iter().skip(n + 1) {
+                let (Some(x), Some(y)) = (entries.get(a), entries.get(b)) else {
+                    continue;
+                };
+                if similarity(x, y).is_some() {
+                    let (ra, rb) = (union_root(&parent, a), union_root(&parent, b));
+                    if let Some(slot) = parent.get_mut(rb) {
+                        *slot = ra;
+                    }
+                }
             }
-            build_embedding_provider(EmbeddingProviderConfig::Gemini(cfg)).map_err(|e| {
-                format!(
-                    "Gemini vector service initialization failed: {}",
-                    provider_error_en_embed(&e)
-                )
-            })
         }
-        _ => Err("Unsupported provider. Use openai-compatible, jina, or gemini.".to_string()),
     }
+
+    let mut clustered: BTreeMap<usize, Vec<&MemoryEntry>> = BTreeMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        clustered.entry(union_root(&parent, i)).or_default().push(entry);
+    }
+    let mut clusters = clustered
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|mut members| {
+            members.sort_by(|a, b| {
+                b.importance
+                    .total_cmp(&a.importance)
+                    .then(b.timestamp_ms.cmp(&a.timestamp_ms))
+                    .then(a.id.cmp(&b.id))
+            });
+            let similarity = members
+                .split_first()
+                .map(|(survivor, rest)| {
+                    rest.iter()
+                        .map(|e| {
+                            similarity(survivor, e)
+                                .or_else(|| {
+                                    let (x, y) = (survivor.embedding.as_ref()?, e.embedding.as_ref()?);
+                                    cosine_similarity(x, y).ok()
+                                })
+                                .unwrap_or(0.0)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            DuplicateCluster { members, similarity }
+        })
+        .collect::<Vec<_>>();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.members.len()));
+    clusters
 }
 
 fn cosine_similarity(a: &[f32], b: &[f32]) -> Result<f32, String> {
@@ -4271,16 +14638,36 @@ fn provider_error_en_rerank(err: &RerankProviderError) -> String {
     }
 }
 
+fn provider_error_en_summarize(err: &SummarizeProviderError) -> String {
+    match err {
+        SummarizeProviderError::Config(msg) => {
+            format!("Configuration error: {}", sanitize_sensitive(msg))
+        }
+        SummarizeProviderError::Http(msg) => {
+            format!("Network error: {}", sanitize_sensitive(&msg.to_string()))
+        }
+        SummarizeProviderError::Serde(msg) => format!("Serialization error: {}", sanitize_sensitive(&msg.to_string())),
+        SummarizeProviderError::InvalidResponse(msg) => {
+            format!("Invalid provider response: {}", sanitize_sensitive(msg))
+        }
+        SummarizeProviderError::Api { status, body } => {
+            format!("Provider API error (status {status}): {}", sanitize_sensitive(body))
+        }
+    }
+}
+
 fn sanitize_sensitive(input: &str) -> String {
     let mut out = input.to_string();
 
     for key_name in [
         "PRX_EMBED_API_KEY",
         "GEMINI_API_KEY",
+        "MISTRAL_API_KEY",
         "JINA_API_KEY",
         "PRX_RERANK_API_KEY",
         "COHERE_API_KEY",
         "PINECONE_API_KEY",
+        "PRX_SUMMARIZE_API_KEY",
     ] {
         if let Ok(secret) = std::env::var(key_name) {
             if !secret.is_empty() {
@@ -4295,16 +14682,6 @@ fn sanitize_sensitive(input: &str) -> String {
     out
 }
 
-static EMBED_RUNTIME: OnceLock<Mutex<EmbedRuntime>> = OnceLock::new();
-
-fn embed_runtime() -> &'static Mutex<EmbedRuntime> {
-    EMBED_RUNTIME.get_or_init(|| Mutex::new(EmbedRuntime::from_env()))
-}
-
-fn embed_runtime_stats() -> EmbedRuntimeStats {
-    embed_runtime().lock().stats.clone()
-}
-
 impl EmbedRuntime {
     fn from_env() -> Self {
         let capacity = std::env::var("PRX_EMBED_CACHE_CAPACITY")
@@ -4333,7 +14710,50 @@ impl EmbedRuntime {
             refill_per_sec: rps,
             last_refill_ms: now,
             stats: EmbedRuntimeStats::default(),
+            disk: None,
+        }
+    }
+
+    /// Looks up the in-memory cache first, then the disk cache, promoting disk hits.
+    fn lookup(&mut self, key: &str, now: u64) -> Option<EmbeddedText> {
+        if let Some(hit) = self.cache_get(key, now) {
+            return Some(hit);
+        }
+        let disk_hit = self.disk.as_mut().map(|disk| disk.get(key, now))?;
+        if let Some(vector) = disk_hit {
+            self.stats.disk_hits = self.stats.disk_hits.saturating_add(1);
+            self.cache_put(key.to_string(), vector.clone(), now);
+            return Some(vector);
+        }
+        self.stats.disk_misses = self.stats.disk_misses.saturating_add(1);
+        None
+    }
+
+    fn remember(&mut self, key: String, value: EmbeddedText, now: u64) {
+        if let Some(disk) = self.disk.as_mut() {
+            let evicted = disk.put(&key, value.clone(), now);
+            self.stats.disk_evictions = self.stats.disk_evictions.saturating_add(evicted);
         }
+        self.cache_put(key, value, now);
+    }
+
+    /// Hands pending disk records and the log they go to to the caller, which writes them after
+    /// releasing the runtime lock. Nothing is taken while another flush is still writing; those
+    /// records go out with the next flush.
+    fn take_disk_writes(&mut self) -> Option<(Arc<Mutex<EmbedDiskLog>>, EmbedDiskWrite)> {
+        let disk = self.disk.as_mut()?;
+        let log = Arc::clone(&disk.log);
+        let lines = log.try_lock()?.lines;
+        let write = disk.take_writes(lines)?;
+        Some((log, write))
+    }
+
+    /// Takes every live disk entry for a rewrite, which reseals them with the primary key.
+    fn take_disk_rewrite(&mut self) -> Option<(Arc<Mutex<EmbedDiskLog>>, EmbedDiskWrite)> {
+        let disk = self.disk.as_mut()?;
+        disk.rewrite_due = true;
+        let write = disk.take_writes(0)?;
+        Some((Arc::clone(&disk.log), write))
     }
 
     fn refresh_tokens(&mut self, now: u64) {
@@ -4361,7 +14781,7 @@ impl EmbedRuntime {
         wait_ms
     }
 
-    fn cache_get(&mut self, key: &str, now: u64) -> Option<Vec<f32>> {
+    fn cache_get(&mut self, key: &str, now: u64) -> Option<EmbeddedText> {
         if let Some(hit_value) = self.entries.get(key).and_then(|entry| {
             if entry.expire_at_ms >= now {
                 Some(entry.value.clone())
@@ -4380,7 +14800,7 @@ impl EmbedRuntime {
         None
     }
 
-    fn cache_put(&mut self, key: String, value: Vec<f32>, now: u64) {
+    fn cache_put(&mut self, key: String, value: EmbeddedText, now: u64) {
         let expire_at_ms = now.saturating_add(self.ttl_ms);
         self.entries
             .insert(key.clone(), EmbedCacheEntry { value, expire_at_ms });
@@ -4402,6 +14822,251 @@ impl EmbedRuntime {
     }
 }
 
+/// Writes taken disk cache records to the log, logging and counting failures.
+fn flush_embed_disk(embed: &Mutex<EmbedRuntime>, log: &Mutex<EmbedDiskLog>, write: &EmbedDiskWrite) {
+    let written = log.lock().write(write);
+    if let Err(err) = written {
+        tracing::warn!(error = %err, "embedding disk cache write failed");
+        let mut runtime = embed.lock();
+        runtime.stats.disk_write_errors = runtime.stats.disk_write_errors.saturating_add(1);
+    }
+}
+
+impl EmbedDiskCache {
+    /// `PRX_EMBED_CACHE_PATH`, else `<db_path>.embed-cache.jsonl`. `PRX_EMBED_CACHE_PATH=off`
+    /// disables the disk tier. A `tenant` store always uses its own default path, since an
+    /// explicit path would be shared by every tenant.
+    fn path_from_env(db_path: &str, tenant: bool) -> Option<std::path::PathBuf> {
+        match std::env::var("PRX_EMBED_CACHE_PATH") {
+            Ok(raw) if raw.eq_ignore_ascii_case("off") || raw.trim().is_empty() => None,
+            Ok(raw) if !tenant => Some(std::path::PathBuf::from(raw)),
+            _ => Some(std::path::PathBuf::from(format!("{db_path}.embed-cache.jsonl"))),
+        }
+    }
+
+    fn from_env(path: &std::path::Path, cipher: Option<&FieldCipher>, now: u64) -> Self {
+        let capacity = env_usize("PRX_EMBED_DISK_CACHE_CAPACITY", 50_000, 1, 10_000_000);
+        let ttl_ms = std::env::var("PRX_EMBED_DISK_CACHE_TTL_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30 * 24 * 3_600_000)
+            .max(1_000);
+        Self::open(path, cipher, capacity, ttl_ms, now)
+    }
+
+    /// Replays the log. A missing file starts empty; unreadable lines, and sealed lines that do
+    /// not open with `cipher`, are skipped and dropped at the next rewrite. With a cipher, a log
+    /// holding plaintext vectors is rewritten sealed at the next flush.
+    fn open(path: &std::path::Path, cipher: Option<&FieldCipher>, capacity: usize, ttl_ms: u64, now: u64) -> Self {
+        let mut cache = Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            next_seq: 0,
+            capacity,
+            ttl_ms,
+            pending: Vec::new(),
+            rewrite_due: false,
+            log: Arc::new(Mutex::new(EmbedDiskLog {
+                path: path.to_path_buf(),
+                cipher: cipher.cloned(),
+                lines: 0,
+            })),
+        };
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    tracing::warn!(path = %path.display(), error = %err, "embedding disk cache unreadable; starting empty");
+                }
+                return cache;
+            }
+        };
+        let mut lines = 0usize;
+        let mut skipped = 0usize;
+        for line in io::BufReader::new(file).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            lines = lines.saturating_add(1);
+            match serde_json::from_str::<EmbedDiskRecord>(&line) {
+                Ok(record) if record.stored_at_ms.saturating_add(ttl_ms) >= now => {
+                    if cipher.is_some() && record.sealed.is_none() {
+                        cache.rewrite_due = true;
+                    }
+                    match open_disk_record(record, cipher) {
+                        Some(record) => cache.insert(record),
+                        None => skipped = skipped.saturating_add(1),
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => skipped = skipped.saturating_add(1),
+            }
+        }
+        if skipped > 0 {
+            tracing::warn!(path = %path.display(), skipped, "skipped unreadable embedding disk cache lines");
+        }
+        cache.evict_overflow();
+        cache.log.lock().lines = lines;
+        cache
+    }
+
+    fn hash_key(key: &str) -> String {
+        use sha2::{Digest, Sha256};
+        use std::fmt::Write as _;
+        Sha256::digest(key.as_bytes())
+            .iter()
+            .fold(String::with_capacity(64), |mut out, b| {
+                let _ = write!(out, "{b:02x}");
+                out
+            })
+    }
+
+    fn get(&mut self, key: &str, now: u64) -> Option<EmbeddedText> {
+        let hashed = Self::hash_key(key);
+        let entry = self.entries.get_mut(&hashed)?;
+        if entry.stored_at_ms.saturating_add(self.ttl_ms) < now {
+            let seq = entry.seq;
+            self.entries.remove(&hashed);
+            self.recency.remove(&seq);
+            return None;
+        }
+        self.recency.remove(&entry.seq);
+        entry.seq = self.next_seq;
+        self.next_seq = self.next_seq.saturating_add(1);
+        let hit = EmbeddedText {
+            model: entry.model.clone(),
+            vector: entry.vector.clone(),
+        };
+        self.recency.insert(entry.seq, hashed);
+        Some(hit)
+    }
+
+    /// Inserts a vector and returns how many least recently used entries were evicted to stay
+    /// within capacity.
+    fn put(&mut self, key: &str, value: EmbeddedText, now: u64) -> u64 {
+        let record = EmbedDiskRecord {
+            key: Self::hash_key(key),
+            vector: value.vector,
+            sealed: None,
+            model: value.model,
+            stored_at_ms: now,
+        };
+        self.pending.push(record.clone());
+        self.insert(record);
+        self.evict_overflow()
+    }
+
+    fn insert(&mut self, record: EmbedDiskRecord) {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.saturating_add(1);
+        let entry = EmbedDiskEntry {
+            vector: record.vector,
+            model: record.model,
+            stored_at_ms: record.stored_at_ms,
+            seq,
+        };
+        if let Some(old) = self.entries.insert(record.key.clone(), entry) {
+            self.recency.remove(&old.seq);
+        }
+        self.recency.insert(seq, record.key);
+    }
+
+    fn evict_overflow(&mut self) -> u64 {
+        let mut evicted = 0u64;
+        while self.entries.len() > self.capacity {
+            let Some((_, key)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&key);
+            evicted = evicted.saturating_add(1);
+        }
+        evicted
+    }
+
+    /// The pending records to append, or every live entry once the log holds `log_lines` lines
+    /// and appending would take it past twice the capacity.
+    fn take_writes(&mut self, log_lines: usize) -> Option<EmbedDiskWrite> {
+        if self.pending.is_empty() && !self.rewrite_due {
+            return None;
+        }
+        let pending = std::mem::take(&mut self.pending);
+        if !self.rewrite_due && log_lines.saturating_add(pending.len()) <= self.capacity.saturating_mul(2) {
+            return Some(EmbedDiskWrite::Append(pending));
+        }
+        self.rewrite_due = false;
+        let live = self
+            .recency
+            .values()
+            .filter_map(|key| {
+                self.entries.get(key).map(|entry| EmbedDiskRecord {
+                    key: key.clone(),
+                    vector: entry.vector.clone(),
+                    sealed: None,
+                    model: entry.model.clone(),
+                    stored_at_ms: entry.stored_at_ms,
+                })
+            })
+            .collect();
+        Some(EmbedDiskWrite::Rewrite(live))
+    }
+}
+
+/// Opens a replayed record's sealed vector. Sealed records without a cipher, or that fail to
+/// open, are dropped.
+fn open_disk_record(mut record: EmbedDiskRecord, cipher: Option<&FieldCipher>) -> Option<EmbedDiskRecord> {
+    let Some(sealed) = record.sealed.take() else {
+        return Some(record);
+    };
+    let plain = cipher?.open(&sealed, &record.key, "embedding").ok()?;
+    record.vector = serde_json::from_str(&plain).ok()?;
+    Some(record)
+}
+
+impl EmbedDiskLog {
+    fn write(&mut self, write: &EmbedDiskWrite) -> io::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let (records, append) = match write {
+            EmbedDiskWrite::Append(records) => (records, true),
+            EmbedDiskWrite::Rewrite(records) => (records, false),
+        };
+        let mut raw = Vec::new();
+        for record in records {
+            match &self.cipher {
+                Some(cipher) => {
+                    let vector = serde_json::to_string(&record.vector).map_err(io::Error::other)?;
+                    let sealed = EmbedDiskRecord {
+                        vector: Vec::new(),
+                        sealed: Some(
+                            cipher
+                                .seal(&vector, &record.key, "embedding")
+                                .map_err(io::Error::other)?,
+                        ),
+                        ..record.clone()
+                    };
+                    serde_json::to_writer(&mut raw, &sealed)
+                }
+                None => serde_json::to_writer(&mut raw, record),
+            }
+            .map_err(io::Error::other)?;
+            raw.push(b'\n');
+        }
+        if append {
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?
+                .write_all(&raw)?;
+            self.lines = self.lines.saturating_add(records.len());
+        } else {
+            atomic_write(&self.path, &raw)?;
+            self.lines = records.len();
+        }
+        Ok(())
+    }
+}
+
 /// Validates that a user-supplied file path is safe for read/write operations.
 ///
 /// Rejects paths containing `..` path-traversal components.
@@ -4518,26 +15183,253 @@ mod tests {
             refill_per_sec: rps,
             last_refill_ms: now,
             stats: EmbedRuntimeStats::default(),
+            disk: None,
+        }
+    }
+
+    fn embedded(value: f32) -> EmbeddedText {
+        EmbeddedText {
+            model: "test/model".to_string(),
+            vector: vec![value],
+        }
+    }
+
+    fn flush_disk(rt: &mut EmbedRuntime) {
+        if let Some((log, write)) = rt.take_disk_writes() {
+            log.lock().write(&write).expect("write disk cache");
+        }
+    }
+
+    #[test]
+    fn embed_disk_cache_survives_reload_and_expires() {
+        let path = std::env::temp_dir().join(format!("prx-embed-cache-{}.jsonl", now_ms()));
+        let mut rt = runtime_for_test(4, 10, 100.0, 0);
+        rt.disk = Some(EmbedDiskCache::open(&path, None, 2, 1_000, 0));
+        rt.remember("a".to_string(), embedded(1.0), 0);
+        rt.remember("b".to_string(), embedded(2.0), 5);
+        // Reading "a" leaves "b" as the least recently used entry.
+        assert_eq!(rt.disk.as_mut().and_then(|d| d.get("a", 6)), Some(embedded(1.0)));
+        rt.remember("c".to_string(), embedded(3.0), 10);
+        assert_eq!(rt.stats.disk_evictions, 1);
+        assert_eq!(rt.disk.as_mut().and_then(|d| d.get("b", 10)), None);
+        flush_disk(&mut rt);
+
+        let mut reloaded = runtime_for_test(4, 10, 100.0, 20);
+        reloaded.disk = Some(EmbedDiskCache::open(&path, None, 2, 1_000, 20));
+        assert_eq!(reloaded.lookup("c", 20), Some(embedded(3.0)));
+        assert_eq!(reloaded.lookup("missing", 20), None);
+        assert_eq!(reloaded.stats.disk_hits, 1);
+        assert_eq!(reloaded.stats.disk_misses, 1);
+
+        // Appending past twice the capacity rewrites the log with only the live entries.
+        reloaded.remember("d".to_string(), embedded(4.0), 20);
+        reloaded.remember("e".to_string(), embedded(5.0), 21);
+        flush_disk(&mut reloaded);
+        let lines = std::fs::read_to_string(&path).map_or(0, |raw| raw.lines().count());
+        assert_eq!(lines, 2);
+
+        let mut expired = runtime_for_test(4, 10, 100.0, 5_000);
+        expired.disk = Some(EmbedDiskCache::open(&path, None, 2, 1_000, 5_000));
+        assert_eq!(expired.lookup("d", 5_000), None);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn embed_disk_cache_seals_vectors_when_encryption_is_on() {
+        let key = "11".repeat(32);
+        let cipher = FieldCipher::from_hex_keys(&key, &[]).expect("cipher");
+        let path = std::env::temp_dir().join(format!("prx-embed-sealed-{}.jsonl", now_ms()));
+        let mut rt = runtime_for_test(4, 10, 100.0, 0);
+        rt.disk = Some(EmbedDiskCache::open(&path, Some(&cipher), 4, 1_000, 0));
+        rt.remember("a".to_string(), embedded(0.123_456), 0);
+        flush_disk(&mut rt);
+        let raw = std::fs::read_to_string(&path).expect("read cache");
+        assert!(raw.contains("enc:v2:"));
+        assert!(!raw.contains("0.123456"));
+
+        let mut reloaded = runtime_for_test(4, 10, 100.0, 1);
+        reloaded.disk = Some(EmbedDiskCache::open(&path, Some(&cipher), 4, 1_000, 1));
+        assert_eq!(reloaded.lookup("a", 1), Some(embedded(0.123_456)));
+        let mut keyless = runtime_for_test(4, 10, 100.0, 1);
+        keyless.disk = Some(EmbedDiskCache::open(&path, None, 4, 1_000, 1));
+        assert_eq!(keyless.lookup("a", 1), None);
+
+        // A plaintext log written before encryption was turned on is rewritten sealed.
+        let mut plain = runtime_for_test(4, 10, 100.0, 2);
+        plain.disk = Some(EmbedDiskCache::open(&path, None, 4, 1_000, 2));
+        plain.remember("b".to_string(), embedded(0.654_321), 2);
+        flush_disk(&mut plain);
+        assert!(std::fs::read_to_string(&path).expect("read cache").contains("0.654321"));
+        let mut sealed = runtime_for_test(4, 10, 100.0, 3);
+        sealed.disk = Some(EmbedDiskCache::open(&path, Some(&cipher), 4, 1_000, 3));
+        flush_disk(&mut sealed);
+        let raw = std::fs::read_to_string(&path).expect("read cache");
+        assert!(!raw.contains("0.654321"));
+        assert_eq!(sealed.lookup("b", 3), Some(embedded(0.654_321)));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn rate_limiter_enforces_tool_and_session_buckets() {
+        let spec = |rps: f64, burst: f64| RateLimitSpec { rps, burst };
+        let mut limiter = ToolRateLimiter {
+            tool_overrides: HashMap::from([("memory_recall".to_string(), spec(1.0, 2.0))]),
+            session: Some(spec(10.0, 3.0)),
+            ..ToolRateLimiter::default()
+        };
+
+        assert!(limiter.try_acquire("memory_recall", Some("s1"), 0).is_ok());
+        assert!(limiter.try_acquire("memory_recall", Some("s1"), 0).is_ok());
+        assert_eq!(
+            limiter.try_acquire("memory_recall", Some("s2"), 0),
+            Err(("tool", 1_000))
+        );
+        assert!(limiter.try_acquire("memory_recall", Some("s2"), 1_000).is_ok());
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire("memory_list", Some("s1"), 1_000).is_ok());
         }
+        assert_eq!(
+            limiter.try_acquire("memory_list", Some("s1"), 1_000),
+            Err(("session", 100))
+        );
+        assert!(limiter.try_acquire("memory_list", None, 1_000).is_ok());
+        assert!(limiter.try_acquire("memory_list", Some("s1"), 1_100).is_ok());
+    }
+
+    #[test]
+    fn cancellable_aborts_pending_provider_calls() {
+        let rt = build_shared_runtime().expect("runtime");
+        assert_eq!(rt.block_on(cancellable(&CallContext::default(), async { 7 })), Ok(7));
+
+        let ctx = CallContext::default().with_timeout(Some(Duration::from_millis(30)));
+        let err = rt
+            .block_on(cancellable(&ctx, std::future::pending::<()>()))
+            .expect_err("deadline passed");
+        assert_eq!(err, "request timed out");
+        assert_eq!(ctx.cancel_reason(), Some("timed out"));
+
+        let ctx = CallContext::default();
+        let flag = Arc::clone(&ctx.cancelled);
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(30));
+            flag.store(true, AtomicOrdering::Relaxed);
+        });
+        let err = rt
+            .block_on(cancellable(&ctx, std::future::pending::<()>()))
+            .expect_err("client cancelled");
+        assert_eq!(err, "request cancelled");
+    }
+
+    #[test]
+    fn latency_histogram_renders_cumulative_buckets() {
+        let bounds = [2.5, 5.0, 10.0];
+        let mut buckets = Vec::new();
+        for latency in [1.0, 4.0, 5.0, 9.0, 50.0] {
+            observe_latency(&mut buckets, &bounds, latency);
+        }
+        assert_eq!(buckets, vec![1, 2, 1]);
+
+        let lines = histogram_bucket_lines(
+            "prx_memory_tool_latency_ms",
+            "tool=\"memory_recall\"",
+            &bounds,
+            &buckets,
+            5,
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "prx_memory_tool_latency_ms_bucket{tool=\"memory_recall\",le=\"2.5\"} 1",
+                "prx_memory_tool_latency_ms_bucket{tool=\"memory_recall\",le=\"5\"} 3",
+                "prx_memory_tool_latency_ms_bucket{tool=\"memory_recall\",le=\"10\"} 4",
+                "prx_memory_tool_latency_ms_bucket{tool=\"memory_recall\",le=\"+Inf\"} 5",
+            ]
+        );
+    }
+
+    #[test]
+    fn tool_policy_restricts_destructive_tools_per_agent() {
+        let rules = serde_json::from_str::<HashMap<String, ToolPolicyRule>>(
+            r#"{"*":{"deny":["memory_forget","memory_migrate","memory_compact:apply"]},"ops":{"allow":["*"]},"reader":{"allow":["memory_recall","memory_list"]}}"#,
+        )
+        .expect("policy json");
+        let policy = ToolPolicy { rules };
+
+        assert!(policy.check("worker", "memory_recall", None).is_ok());
+        assert!(policy.check("worker", "memory_compact", None).is_ok());
+        let denied = policy
+            .check("worker", "memory_compact", Some(&json!({"dry_run": false})))
+            .expect_err("compact apply denied");
+        assert_eq!(denied["kind"], "permission_denied");
+        assert_eq!(denied["action"], "memory_compact:apply");
+        assert_eq!(denied["rule"], "deny:memory_compact:apply");
+        assert!(policy.check("worker", "memory_forget", None).is_err());
+
+        assert!(policy.check("ops", "memory_forget", None).is_ok());
+        assert!(policy.check("reader", "memory_list", None).is_ok());
+        let denied = policy.check("reader", "memory_store", None).expect_err("not allowed");
+        assert_eq!(denied["rule"], "allow-list");
+
+        assert!(ToolPolicy::default().check("anyone", "memory_forget", None).is_ok());
     }
 
     #[test]
     fn embed_cache_lru_and_ttl_work() {
         let mut rt = runtime_for_test(2, 10, 100.0, 0);
-        rt.cache_put("a".to_string(), vec![1.0], 0);
-        rt.cache_put("b".to_string(), vec![2.0], 0);
-        assert_eq!(rt.cache_get("a", 1), Some(vec![1.0]));
-        rt.cache_put("c".to_string(), vec![3.0], 2);
+        rt.cache_put("a".to_string(), embedded(1.0), 0);
+        rt.cache_put("b".to_string(), embedded(2.0), 0);
+        assert_eq!(rt.cache_get("a", 1), Some(embedded(1.0)));
+        rt.cache_put("c".to_string(), embedded(3.0), 2);
         assert_eq!(rt.stats.cache_evictions, 1);
         assert_eq!(rt.cache_get("b", 2), None);
-        assert_eq!(rt.cache_get("a", 2), Some(vec![1.0]));
-        assert_eq!(rt.cache_get("c", 2), Some(vec![3.0]));
+        assert_eq!(rt.cache_get("a", 2), Some(embedded(1.0)));
+        assert_eq!(rt.cache_get("c", 2), Some(embedded(3.0)));
 
-        rt.cache_put("ttl".to_string(), vec![9.0], 0);
+        rt.cache_put("ttl".to_string(), embedded(9.0), 0);
         assert_eq!(rt.cache_get("ttl", 20), None);
         assert!(rt.stats.cache_misses >= 2);
     }
 
+    #[test]
+    fn embedding_mismatch_report_flags_model_and_dimension_changes() {
+        let entry = |id: &str, model: Option<&str>, vector: Vec<f32>| prx_memory_storage::MemoryEntry {
+            id: id.to_string(),
+            text: "t".to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: Vec::new(),
+            timestamp_ms: 0,
+            embedding_dim: Some(vector.len()),
+            embedding: Some(vector),
+            embedding_model: model.map(str::to_string),
+            source: None,
+            keywords: Vec::new(),
+        };
+        let query = EmbeddedText {
+            model: "jina/v5".to_string(),
+            vector: vec![1.0, 0.0],
+        };
+        let same = vec![
+            entry("a", Some("jina/v5"), vec![0.0, 1.0]),
+            entry("b", None, vec![1.0, 1.0]),
+        ];
+        assert!(embedding_mismatch_report(&same, &query).is_none());
+
+        let changed = vec![
+            entry("a", Some("jina/v5"), vec![0.0, 1.0]),
+            entry("b", Some("openai/small"), vec![0.0, 1.0]),
+            entry("c", None, vec![1.0, 0.0, 0.0]),
+        ];
+        let report = embedding_mismatch_report(&changed, &query).expect("mismatch report");
+        assert_eq!(report["mismatched_entries"], 2);
+        assert_eq!(report["query_dim"], 2);
+        assert!(report["hint"].as_str().unwrap_or_default().contains("memory_reembed"));
+    }
+
     #[test]
     fn embed_rate_limiter_waits_when_tokens_exhausted() {
         let mut rt = runtime_for_test(8, 1000, 1.0, 1000);
@@ -4569,4 +15461,392 @@ mod tests {
         let long = sanitize_label_value(&"A".repeat(200));
         assert_eq!(long.len(), 64);
     }
+
+    #[test]
+    fn query_components_are_percent_decoded() {
+        let (path, query) = parse_path_query("/v1/memories?query=stale+cache%20rows&scope=team%2Fops&bad=%zz");
+        assert_eq!(path, "/v1/memories");
+        assert_eq!(query.get("query").map(String::as_str), Some("stale cache rows"));
+        assert_eq!(query.get("scope").map(String::as_str), Some("team/ops"));
+        assert_eq!(query.get("bad").map(String::as_str), Some("%zz"));
+    }
+
+    #[test]
+    fn scope_quota_prefers_exact_then_longest_pattern() {
+        let limit = |max_entries| QuotaLimit {
+            max_entries: Some(max_entries),
+            max_bytes: None,
+        };
+        let quotas = QuotaConfig {
+            scopes: HashMap::from([
+                ("*".to_string(), limit(100)),
+                ("agent:*".to_string(), limit(10)),
+                ("agent:ops".to_string(), limit(50)),
+                ("user:*".to_string(), QuotaLimit::default()),
+            ]),
+            ..QuotaConfig::default()
+        };
+        assert_eq!(quotas.scope_limit("agent:ops"), Some(limit(50)));
+        assert_eq!(quotas.scope_limit("agent:dev"), Some(limit(10)));
+        assert_eq!(quotas.scope_limit("global"), Some(limit(100)));
+        assert_eq!(quotas.scope_limit("user:alice"), None);
+
+        let used = QuotaUsage { entries: 9, bytes: 500 };
+        assert!(
+            limit(10)
+                .check("scope", "agent:dev", used, QuotaUsage::of_text("x"))
+                .is_ok()
+        );
+        let exceeded = limit(10)
+            .check(
+                "scope",
+                "agent:dev",
+                used,
+                QuotaUsage::of_text("x").plus(QuotaUsage::of_text("y")),
+            )
+            .expect_err("over the entry quota");
+        assert_eq!(
+            exceeded.message(),
+            "quota exceeded: scope agent:dev allows 10 entries (9 used, 2 requested)"
+        );
+    }
+
+    #[test]
+    fn decay_auto_archive_moves_cold_entries_to_archive_file() {
+        let base = std::env::temp_dir().join(format!("prx-decay-archive-{}", now_ms()));
+        let db_path = base.with_extension("json");
+        let mut store = PersistentMemoryStore::open(&db_path).expect("open store");
+        let new_entry = |text: &str, importance: f32| NewMemoryEntry {
+            text: text.to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance,
+            tags: Vec::new(),
+            embedding: None,
+            embedding_model: None,
+            source: None,
+            keywords: Vec::new(),
+        };
+        let cold = store
+            .store(new_entry("cold low importance note", 0.2))
+            .expect("store cold");
+        let pinned = store
+            .store(new_entry("critical note that never fades", 1.0))
+            .expect("store pinned");
+        let decay = DecayTracker {
+            policy: DecayPolicy::default(),
+            archive_below: Some(0.1),
+            archive: MemoryArchive {
+                path: base.with_extension("archive.jsonl"),
+                cipher: None,
+            },
+            access: Mutex::new(AccessLog::default()),
+        };
+        let scopes = ScopeManager::from_env().expect("scopes");
+
+        assert_eq!(
+            run_periodic_maintenance(&scopes, &GovernancePolicy::default(), &decay, &mut store, now_ms())
+                .expect("maintenance")
+                .decay_archived,
+            0
+        );
+        let later = now_ms() + 365 * 86_400_000;
+        assert_eq!(
+            run_periodic_maintenance(&scopes, &GovernancePolicy::default(), &decay, &mut store, later)
+                .expect("maintenance")
+                .decay_archived,
+            1
+        );
+
+        let remaining = store.list(10).into_iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(remaining, vec![pinned.id]);
+        let archived = decay.archive.load().expect("archive file");
+        assert_eq!(archived.len(), 1);
+        let line = archived.first().expect("archived entry");
+        assert_eq!(line.entry.id, cold.id);
+        assert!(line.retention < 0.1);
+
+        let _ = fs::remove_file(&decay.archive.path);
+        let _ = fs::remove_file(&db_path);
+    }
+
+    #[test]
+    fn stats_trend_buckets_entries_by_utc_week() {
+        const DAY_MS: u64 = 86_400_000;
+        // 2024-01-10 (Wednesday) 12:00 UTC.
+        let now = 19_732 * DAY_MS + DAY_MS / 2;
+        let entry = |id: &str, category: &str, days_ago: u64, embedded: bool| prx_memory_storage::MemoryEntry {
+            id: id.to_string(),
+            text: "t".to_string(),
+            category: category.to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: Vec::new(),
+            timestamp_ms: now - days_ago * DAY_MS,
+            embedding: embedded.then(|| vec![0.1]),
+            embedding_model: None,
+            embedding_dim: None,
+            source: None,
+            keywords: Vec::new(),
+        };
+        let entries = vec![
+            entry("a", "fact", 0, true),
+            entry("b", "decision", 2, false),
+            entry("c", "fact", 3, false),
+            entry("d", "fact", 40, true),
+        ];
+        let trend = stats_trend(&entries, 7, 2, now);
+        assert_eq!(trend.pointer("/earlier"), Some(&json!(1)));
+        assert_eq!(trend.pointer("/series/0/start"), Some(&json!("2024-01-01")));
+        assert_eq!(trend.pointer("/series/0/added"), Some(&json!(1)));
+        assert_eq!(trend.pointer("/series/1/start"), Some(&json!("2024-01-08")));
+        assert_eq!(trend.pointer("/series/1/added"), Some(&json!(2)));
+        assert_eq!(trend.pointer("/series/1/total"), Some(&json!(4)));
+        assert_eq!(trend.pointer("/series/1/by_category/decision"), Some(&json!(1)));
+        assert_eq!(trend.pointer("/embedding_coverage_pct"), Some(&json!(50.0)));
+        assert_eq!(utc_date(0), "1970-01-01");
+    }
+
+    #[test]
+    fn stats_tags_break_down_dimensions_and_flag_unlisted_values() {
+        let entry = |tags: &[&str]| prx_memory_storage::MemoryEntry {
+            id: "m".to_string(),
+            text: "t".to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: tags.iter().map(|t| (*t).to_string()).collect(),
+            timestamp_ms: 0,
+            embedding: None,
+            embedding_model: None,
+            embedding_dim: None,
+            source: None,
+            keywords: Vec::new(),
+        };
+        let entries = vec![
+            entry(&["project:web", "domain:retrieval"]),
+            entry(&["project:web", "domain:general"]),
+            entry(&["project:api", "tool:mcp", "domain:retrieval"]),
+        ];
+        let policy = GovernancePolicy {
+            tag_taxonomy: TaxonomyMode::Flag,
+            allowed_tags: BTreeMap::from([("domain:".to_string(), vec!["retrieval".to_string()])]),
+            ..GovernancePolicy::default()
+        };
+        let tags = stats_tags(&entries, &policy, 2);
+        assert_eq!(tags.pointer("/distinct"), Some(&json!(5)));
+        assert_eq!(tags.pointer("/dimensions/project/values/web"), Some(&json!(2)));
+        assert_eq!(tags.pointer("/dimensions/tool/missing"), Some(&json!(2)));
+        assert_eq!(
+            tags.pointer("/top"),
+            Some(&json!([{"tag": "domain:retrieval", "count": 2}, {"tag": "project:web", "count": 2}]))
+        );
+        assert_eq!(
+            tags.pointer("/unlisted"),
+            Some(&json!([{"tag": "domain:general", "count": 1}]))
+        );
+    }
+
+    #[test]
+    fn http_tokens_keep_colons_in_bare_tokens() {
+        let tokens = parse_http_tokens("ci:s3cret, Zm9v:YmFy==, tok.en:with:colons, plain, empty:");
+        assert_eq!(
+            tokens,
+            vec![
+                ("ci".to_string(), "s3cret".to_string()),
+                ("token-2".to_string(), "Zm9v:YmFy==".to_string()),
+                ("token-3".to_string(), "tok.en:with:colons".to_string()),
+                ("token-4".to_string(), "plain".to_string()),
+                ("token-5".to_string(), "empty:".to_string()),
+            ]
+        );
+    }
+
+    /// Answers one HTTP request with `reply` and returns the request line, headers and body.
+    fn serve_one_request(listener: &TcpListener, reply: &str) -> (String, String, Value) {
+        let (stream, _) = listener.accept().expect("accept");
+        let mut reader = io::BufReader::new(stream);
+        let mut head = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).expect("header line");
+            if line.trim().is_empty() {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let content_length = head
+            .lines()
+            .find_map(|l| {
+                l.to_ascii_lowercase()
+                    .strip_prefix("content-length:")
+                    .map(|v| v.trim().to_string())
+            })
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(0);
+        let mut body = vec![0u8; content_length];
+        reader.read_exact(&mut body).expect("body");
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+            reply.len()
+        );
+        reader.get_mut().write_all(response.as_bytes()).expect("reply");
+        let (request_line, headers) = head.split_once("\r\n").unwrap_or((head.as_str(), ""));
+        (
+            request_line.to_string(),
+            headers.to_ascii_lowercase(),
+            serde_json::from_slice(&body).expect("json body"),
+        )
+    }
+
+    #[test]
+    fn mistral_embeddings_use_its_key_model_and_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let base_url = format!("http://{}", listener.local_addr().expect("addr"));
+        let collector = std::thread::spawn(move || {
+            serve_one_request(
+                &listener,
+                r#"{"model":"mistral-embed","data":[{"index":0,"embedding":[0.6,0.8]}],"usage":{"total_tokens":3}}"#,
+            )
+        });
+        let env = HashMap::from([
+            ("MISTRAL_API_KEY".to_string(), "mistral-secret".to_string()),
+            ("PRX_EMBED_BASE_URL".to_string(), base_url),
+        ]);
+        let provider = build_single_embedding_provider("mistral", &|name| env.get(name).cloned()).expect("provider");
+        assert_eq!(provider.name(), "mistral");
+
+        let mut request = EmbeddingRequest::single("prefers dark mode");
+        request.dimensions = Some(256);
+        request.normalized = Some(true);
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        let response = rt.block_on(provider.embed(request)).expect("embed");
+        assert_eq!(response.provider, "mistral");
+        assert_eq!(response.vectors, vec![vec![0.6, 0.8]]);
+
+        let (request_line, headers, body) = collector.join().expect("collector");
+        assert!(request_line.starts_with("POST /v1/embeddings "), "{request_line}");
+        assert!(headers.contains("authorization: bearer mistral-secret"));
+        assert_eq!(body, json!({"model": "mistral-embed", "input": "prefers dark mode"}));
+    }
+
+    #[test]
+    fn mistral_embeddings_need_a_key_and_prefer_the_shared_one() {
+        let err = build_single_embedding_provider("mistral", &|_| None)
+            .err()
+            .expect("missing key");
+        assert!(err.contains("MISTRAL_API_KEY"), "{err}");
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let base_url = format!("http://{}", listener.local_addr().expect("addr"));
+        let collector = std::thread::spawn(move || {
+            serve_one_request(
+                &listener,
+                r#"{"model":"custom-embed","data":[{"index":0,"embedding":[1.0]}]}"#,
+            )
+        });
+        let env = HashMap::from([
+            ("PRX_EMBED_API_KEY".to_string(), "shared-secret".to_string()),
+            ("MISTRAL_API_KEY".to_string(), "mistral-secret".to_string()),
+            ("PRX_EMBED_MODEL".to_string(), "custom-embed".to_string()),
+            ("PRX_EMBED_BASE_URL".to_string(), base_url),
+        ]);
+        let provider = build_single_embedding_provider("mistral", &|name| env.get(name).cloned()).expect("provider");
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        rt.block_on(provider.embed(EmbeddingRequest::single("x")))
+            .expect("embed");
+        let (_, headers, body) = collector.join().expect("collector");
+        assert!(headers.contains("authorization: bearer shared-secret"));
+        assert_eq!(body.get("model"), Some(&json!("custom-embed")));
+    }
+
+    #[test]
+    fn shared_runtime_runs_tasks_on_named_workers_for_many_callers() {
+        let rt = Arc::new(build_shared_runtime().expect("runtime"));
+        let callers = (0..4)
+            .map(|_| {
+                let rt = Arc::clone(&rt);
+                std::thread::spawn(move || {
+                    rt.block_on(async {
+                        tokio::spawn(async { std::thread::current().name().map(str::to_string) })
+                            .await
+                            .expect("task")
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        for caller in callers {
+            let worker = caller.join().expect("caller thread");
+            assert_eq!(worker.as_deref(), Some("prx-memory-rt"));
+        }
+    }
+
+    #[test]
+    fn tenant_servers_run_on_the_runtime_they_are_given() {
+        let base = std::env::temp_dir().join(format!("prx-shared-runtime-{}", now_ms()));
+        let rt = Arc::new(build_shared_runtime().expect("runtime"));
+        let a = McpServer::for_tenant(base.join("a.json").display().to_string(), Arc::clone(&rt)).expect("tenant a");
+        let b = McpServer::for_tenant(base.join("b.json").display().to_string(), Arc::clone(&rt)).expect("tenant b");
+        assert!(Arc::ptr_eq(&a.runtime, &rt));
+        assert!(Arc::ptr_eq(&b.runtime, &rt));
+
+        let own = McpServer::with_db_path(base.join("own.json").display().to_string()).expect("own store");
+        assert!(!Arc::ptr_eq(&own.runtime, &rt));
+        assert_eq!(
+            own.runtime
+                .block_on(async { tokio::spawn(async { 7 }).await })
+                .expect("task"),
+            7
+        );
+        drop((a, b, own));
+        let _ = fs::remove_dir_all(base);
+    }
+
+    #[test]
+    fn tenant_servers_keep_their_own_cache_and_lexical_settings() {
+        let base = std::env::temp_dir().join(format!("prx-tenant-settings-{}", now_ms()));
+        let rt = Arc::new(build_shared_runtime().expect("runtime"));
+        let open =
+            |name: &str| McpServer::for_tenant(base.join(name).display().to_string(), Arc::clone(&rt)).expect("tenant");
+        let (mut a, b) = (open("a.json"), open("b.json"));
+        assert!(!Arc::ptr_eq(&a.embed, &b.embed));
+        let cache_path = |server: &McpServer| {
+            server
+                .embed
+                .lock()
+                .disk
+                .as_ref()
+                .map(|disk| disk.log.lock().path.clone())
+        };
+        assert_eq!(cache_path(&a), Some(base.join("a.json.embed-cache.jsonl")));
+        assert_eq!(cache_path(&b), Some(base.join("b.json.embed-cache.jsonl")));
+
+        let unicode = RankingConfig {
+            tokenizer: TokenizerMode::Unicode,
+        };
+        *a.ranking.write() = unicode;
+        let mut synonyms = SynonymTable::default();
+        synonyms.register(vec![vec!["pr".to_string(), "pull request".to_string()]]);
+        a.synonyms = Arc::new(synonyms);
+        let settings = |server: &McpServer| {
+            server.with_lexical(server.ranking_config(), || {
+                (ranking_config(), prx_memory_storage::expand_term("pr"))
+            })
+        };
+        assert_eq!(
+            settings(&a),
+            (unicode, vec!["pr".to_string(), "pull request".to_string()])
+        );
+        assert_eq!(settings(&b), (RankingConfig::default(), vec!["pr".to_string()]));
+        assert_eq!(ranking_config(), RankingConfig::default());
+
+        drop((a, b));
+        let _ = fs::remove_dir_all(base);
+    }
 }
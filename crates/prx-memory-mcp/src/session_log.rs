@@ -0,0 +1,249 @@
+//! Opt-in write-through journal of stream sessions (`PRX_MEMORY_SESSION_LOG`): one
+//! [`SessionRecord`] per line of `<db>.session-events.jsonl`, so responses a client has not read
+//! yet survive a crash or restart. Startup replays the journal into live sessions and rewrites it
+//! with only what is still live.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use prx_memory_storage::{FieldCipher, atomic_write};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::distill::Message;
+
+/// Appends after which the journal is rewritten from the live sessions.
+const COMPACT_AFTER: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionRecord {
+    Started {
+        session: String,
+        agent_id: Option<String>,
+    },
+    /// The session's caller was named after it started.
+    Named {
+        session: String,
+        agent_id: String,
+    },
+    Event {
+        session: String,
+        seq: u64,
+        created_ms: u64,
+        payload: Value,
+    },
+    Acked {
+        session: String,
+        seq: u64,
+    },
+    Noted {
+        session: String,
+        note: Message,
+    },
+    /// The server notification kinds the session takes.
+    Filtered {
+        session: String,
+        kinds: Vec<String>,
+    },
+    Subscribed {
+        session: String,
+        uri: String,
+    },
+    Unsubscribed {
+        session: String,
+        uri: String,
+    },
+    Ended {
+        session: String,
+    },
+}
+
+impl SessionRecord {
+    pub fn session(&self) -> &str {
+        match self {
+            Self::Started { session, .. }
+            | Self::Named { session, .. }
+            | Self::Event { session, .. }
+            | Self::Acked { session, .. }
+            | Self::Noted { session, .. }
+            | Self::Filtered { session, .. }
+            | Self::Subscribed { session, .. }
+            | Self::Unsubscribed { session, .. }
+            | Self::Ended { session } => session,
+        }
+    }
+}
+
+/// Record id the journal's sealed lines are bound to.
+const JOURNAL_RECORD_ID: &str = "session-journal";
+
+/// A line on disk; with a cipher the whole record is sealed.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredRecord {
+    Sealed {
+        sealed: String,
+        ts_ms: u64,
+    },
+    Plain {
+        #[serde(flatten)]
+        record: SessionRecord,
+        ts_ms: u64,
+    },
+}
+
+#[derive(Debug)]
+pub struct SessionLog {
+    path: PathBuf,
+    cipher: Option<FieldCipher>,
+    appended: usize,
+}
+
+impl SessionLog {
+    pub fn new(path: impl Into<PathBuf>, cipher: Option<FieldCipher>) -> Self {
+        Self {
+            path: path.into(),
+            cipher,
+            appended: 0,
+        }
+    }
+
+    /// Records of the sessions that were live when the journal was last written: sessions that
+    /// ended, or whose last record is more than `ttl_ms` older than the newest one, are dropped.
+    /// Unreadable lines are skipped.
+    pub fn replay(&self, ttl_ms: u64) -> Vec<SessionRecord> {
+        let raw = match fs::read_to_string(&self.path) {
+            Ok(raw) => raw,
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!(error = %err, "failed to read session journal");
+                }
+                return Vec::new();
+            }
+        };
+        let records = raw.lines().filter_map(|line| self.decode(line)).collect::<Vec<_>>();
+        let newest = records.iter().map(|(_, ts)| *ts).max().unwrap_or_default();
+        let mut last_seen = std::collections::HashMap::new();
+        for (record, ts) in &records {
+            if matches!(record, SessionRecord::Ended { .. }) {
+                last_seen.insert(record.session().to_string(), None);
+            } else if let Some(seen) = last_seen.entry(record.session().to_string()).or_insert(Some(*ts)) {
+                *seen = (*seen).max(*ts);
+            }
+        }
+        records
+            .into_iter()
+            .filter(|(record, _)| {
+                last_seen
+                    .get(record.session())
+                    .copied()
+                    .flatten()
+                    .is_some_and(|ts| newest.saturating_sub(ts) <= ttl_ms)
+            })
+            .map(|(record, _)| record)
+            .collect()
+    }
+
+    pub fn append(&mut self, record: &SessionRecord, now_ms: u64) -> Result<(), String> {
+        let line = self.encode(record, now_ms)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("failed to write session journal: {e}"))?;
+        writeln!(file, "{line}").map_err(|e| format!("failed to write session journal: {e}"))?;
+        self.appended += 1;
+        Ok(())
+    }
+
+    pub const fn needs_compaction(&self) -> bool {
+        self.appended >= COMPACT_AFTER
+    }
+
+    /// Replaces the journal with `records`.
+    pub fn rewrite(&mut self, records: &[SessionRecord], now_ms: u64) -> Result<(), String> {
+        let mut raw = String::new();
+        for record in records {
+            raw.push_str(&self.encode(record, now_ms)?);
+            raw.push('\n');
+        }
+        atomic_write(&self.path, raw.as_bytes()).map_err(|e| format!("failed to write session journal: {e}"))?;
+        self.appended = 0;
+        Ok(())
+    }
+
+    fn encode(&self, record: &SessionRecord, ts_ms: u64) -> Result<String, String> {
+        let stored = match &self.cipher {
+            Some(cipher) => {
+                let plain = serde_json::to_string(record).map_err(|e| e.to_string())?;
+                StoredRecord::Sealed {
+                    sealed: cipher
+                        .seal(&plain, JOURNAL_RECORD_ID, "record")
+                        .map_err(|e| e.to_string())?,
+                    ts_ms,
+                }
+            }
+            None => StoredRecord::Plain {
+                record: record.clone(),
+                ts_ms,
+            },
+        };
+        serde_json::to_string(&stored).map_err(|e| e.to_string())
+    }
+
+    fn decode(&self, line: &str) -> Option<(SessionRecord, u64)> {
+        match serde_json::from_str::<StoredRecord>(line).ok()? {
+            StoredRecord::Plain { record, ts_ms } => Some((record, ts_ms)),
+            StoredRecord::Sealed { sealed, ts_ms } => {
+                let plain = self.cipher.as_ref()?.open(&sealed, JOURNAL_RECORD_ID, "record").ok()?;
+                Some((serde_json::from_str(&plain).ok()?, ts_ms))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(session: &str, seq: u64) -> SessionRecord {
+        SessionRecord::Event {
+            session: session.to_string(),
+            seq,
+            created_ms: 0,
+            payload: serde_json::json!({"id": seq}),
+        }
+    }
+
+    #[test]
+    fn replay_keeps_sessions_live_at_the_last_write() {
+        let path = std::env::temp_dir().join(format!("prx-session-log-{}.jsonl", std::process::id()));
+        let mut log = SessionLog::new(&path, None);
+        let started = |session: &str| SessionRecord::Started {
+            session: session.to_string(),
+            agent_id: None,
+        };
+        log.append(&started("stale"), 1_000).expect("append");
+        log.append(&started("ended"), 50_000).expect("append");
+        log.append(&started("live"), 50_000).expect("append");
+        log.append(&event("live", 1), 50_500).expect("append");
+        log.append(
+            &SessionRecord::Ended {
+                session: "ended".to_string(),
+            },
+            51_000,
+        )
+        .expect("append");
+        log.append(&event("live", 2), 60_000).expect("append");
+
+        let replayed = log.replay(20_000);
+        assert_eq!(replayed.len(), 3);
+        assert!(replayed.iter().all(|r| r.session() == "live"));
+
+        log.rewrite(&replayed, 70_000).expect("rewrite");
+        assert_eq!(log.replay(20_000).len(), 3);
+        let _ = fs::remove_file(path);
+    }
+}
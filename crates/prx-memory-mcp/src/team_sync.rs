@@ -0,0 +1,610 @@
+//! Team sync (`PRX_MEMORY_SYNC_URL`): pushes governed entries to a shared `prx-memoryd` and pulls
+//! the team's back, so several people's stores grow one knowledge base. Which scopes go which way
+//! is set per scope by `PRX_MEMORY_SYNC_SCOPES`.
+//!
+//! Both sides exchange change-feed events, so both need `PRX_MEMORY_CHANGE_LOG=1`: local events are
+//! sent to the remote's `POST /changes`, and the remote's `GET /changes` is applied locally with its
+//! ids. When both sides rewrote or deleted the same entry since the last sync, the newer change wins
+//! and the other side's version is deleted.
+//!
+//! Cursors, the ids both sides share and the echoes still expected back are kept in
+//! `<db>.sync.json`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::{Mutex, RwLock};
+use prx_memory_storage::{ChangeEvent, ChangeOp, ChangePage, MemoryEntry, StorageBackend, atomic_write};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    Push,
+    Pull,
+    Both,
+}
+
+impl SyncDirection {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "push" => Some(Self::Push),
+            "pull" => Some(Self::Pull),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+
+    const fn pushes(self) -> bool {
+        matches!(self, Self::Push | Self::Both)
+    }
+
+    const fn pulls(self) -> bool {
+        matches!(self, Self::Pull | Self::Both)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SyncRule {
+    /// A scope, or a scope prefix ending in `*`.
+    pattern: String,
+    direction: SyncDirection,
+}
+
+impl SyncRule {
+    fn matches(&self, scope: &str) -> bool {
+        self.pattern
+            .strip_suffix('*')
+            .map_or(self.pattern == scope, |prefix| scope.starts_with(prefix))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    remote: String,
+    token: Option<String>,
+    rules: Vec<SyncRule>,
+    interval: Option<Duration>,
+    batch: usize,
+    state_path: PathBuf,
+}
+
+impl SyncConfig {
+    /// `None` unless `PRX_MEMORY_SYNC_URL` is set. `PRX_MEMORY_SYNC_SCOPES` is required with it:
+    /// comma-separated `scope=push|pull|both` rules, where a scope ending in `*` is a prefix and the
+    /// first matching rule wins. `PRX_MEMORY_SYNC_TOKEN` is sent as a bearer token and
+    /// `PRX_MEMORY_SYNC_INTERVAL_MS` (default 0, only on request) schedules background syncs.
+    pub fn from_env(db_path: &str) -> Result<Option<Self>, String> {
+        let Some(remote) = std::env::var("PRX_MEMORY_SYNC_URL")
+            .ok()
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())
+        else {
+            return Ok(None);
+        };
+        if !remote.starts_with("http://") && !remote.starts_with("https://") {
+            return Err(format!("PRX_MEMORY_SYNC_URL must be an http(s) URL, got {remote}"));
+        }
+        let mut rules = Vec::new();
+        for pair in std::env::var("PRX_MEMORY_SYNC_SCOPES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        {
+            let (pattern, direction) = pair
+                .rsplit_once('=')
+                .and_then(|(pattern, direction)| Some((pattern.trim(), SyncDirection::parse(direction)?)))
+                .filter(|(pattern, _)| !pattern.is_empty())
+                .ok_or_else(|| format!("PRX_MEMORY_SYNC_SCOPES entries must be scope=push|pull|both, got {pair}"))?;
+            rules.push(SyncRule {
+                pattern: pattern.to_string(),
+                direction,
+            });
+        }
+        if rules.is_empty() {
+            return Err("PRX_MEMORY_SYNC_SCOPES is required with PRX_MEMORY_SYNC_URL".to_string());
+        }
+        let interval_ms = std::env::var("PRX_MEMORY_SYNC_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+        Ok(Some(Self {
+            remote,
+            token: std::env::var("PRX_MEMORY_SYNC_TOKEN")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+            rules,
+            interval: (interval_ms > 0).then(|| Duration::from_millis(interval_ms.max(1_000))),
+            batch: 500,
+            state_path: PathBuf::from(format!("{db_path}.sync.json")),
+        }))
+    }
+
+    fn direction(&self, scope: &str) -> Option<SyncDirection> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(scope))
+            .map(|rule| rule.direction)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SyncState {
+    remote: String,
+    /// Next remote change to pull.
+    pull_from: u64,
+    /// Next local change to push.
+    push_from: u64,
+    /// Ids held on both sides, with their scopes; their rewrites and deletes are passed on.
+    known: BTreeMap<String, String>,
+    /// Events applied locally that will come back through the local feed, by id.
+    pulled: BTreeMap<String, u32>,
+    /// Events sent to the remote that will come back through its feed, by id.
+    pushed: BTreeMap<String, u32>,
+}
+
+/// One entry both sides changed since the last sync.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncConflict {
+    /// The id both changes started from.
+    pub id: String,
+    pub winner: &'static str,
+    pub local_ms: u64,
+    pub remote_ms: u64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub dry_run: bool,
+    pub pulled: usize,
+    pub pushed: usize,
+    pub conflicts: Vec<SyncConflict>,
+    /// Events the remote refused, with its reasons.
+    pub rejected: Vec<Value>,
+}
+
+#[derive(Debug, Default)]
+struct SyncStatus {
+    last_sync_ms: Option<u64>,
+    last_error: Option<String>,
+    syncs: u64,
+}
+
+/// What the remote's `POST /changes` answers.
+#[derive(Debug, Deserialize)]
+struct PushOutcome {
+    applied: Vec<String>,
+    #[serde(default)]
+    rejected: Vec<Value>,
+}
+
+pub struct TeamSync {
+    config: SyncConfig,
+    state: Mutex<SyncState>,
+    status: Mutex<SyncStatus>,
+    /// Held for the length of a sync, so a manual one and the background loop do not overlap.
+    running: Mutex<()>,
+}
+
+impl TeamSync {
+    /// Resumes from the saved state when it was written for the same remote.
+    pub fn new(config: SyncConfig) -> Self {
+        let state = fs::read(&config.state_path)
+            .ok()
+            .and_then(|raw| serde_json::from_slice::<SyncState>(&raw).ok())
+            .filter(|state| state.remote == config.remote)
+            .unwrap_or_else(|| SyncState {
+                remote: config.remote.clone(),
+                pull_from: 1,
+                push_from: 1,
+                ..SyncState::default()
+            });
+        Self {
+            config,
+            state: Mutex::new(state),
+            status: Mutex::new(SyncStatus::default()),
+            running: Mutex::new(()),
+        }
+    }
+
+    /// Progress for `memory_stats`.
+    pub fn status_json(&self) -> Value {
+        let state = self.state.lock();
+        let status = self.status.lock();
+        json!({
+            "remote": self.config.remote,
+            "rules": self.config.rules.iter().map(|rule| {
+                let direction = match rule.direction {
+                    SyncDirection::Push => "push",
+                    SyncDirection::Pull => "pull",
+                    SyncDirection::Both => "both",
+                };
+                json!({"scope": rule.pattern, "direction": direction})
+            }).collect::<Vec<_>>(),
+            "pull_from": state.pull_from,
+            "push_from": state.push_from,
+            "shared": state.known.len(),
+            "syncs": status.syncs,
+            "last_sync_ms": status.last_sync_ms,
+            "last_error": status.last_error
+        })
+    }
+
+    /// Syncs every `PRX_MEMORY_SYNC_INTERVAL_MS` on a background thread, when that is set.
+    pub fn spawn(
+        self: &Arc<Self>,
+        rt: Arc<tokio::runtime::Runtime>,
+        store: Arc<RwLock<Box<dyn StorageBackend>>>,
+        eligible: impl Fn(&MemoryEntry) -> bool + Send + 'static,
+    ) {
+        let Some(interval) = self.config.interval else {
+            return;
+        };
+        let sync = Arc::clone(self);
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                if let Err(error) = sync.run(&rt, &store, &eligible, false) {
+                    tracing::warn!(remote = %sync.config.remote, %error, "team sync failed");
+                }
+            }
+        });
+    }
+
+    /// Pushes local changes and pulls remote ones. `eligible` picks the entries that may leave or
+    /// enter this store; a dry run reports what would happen without writing on either side.
+    pub fn run(
+        &self,
+        rt: &tokio::runtime::Runtime,
+        store: &RwLock<Box<dyn StorageBackend>>,
+        eligible: &dyn Fn(&MemoryEntry) -> bool,
+        dry_run: bool,
+    ) -> Result<SyncReport, String> {
+        let Some(_running) = self.running.try_lock() else {
+            return Err("a team sync is already running".to_string());
+        };
+        let result = self.exchange(rt, store, eligible, dry_run);
+        let mut status = self.status.lock();
+        if !dry_run {
+            status.last_sync_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .and_then(|d| u64::try_from(d.as_millis()).ok());
+            status.syncs += 1;
+            status.last_error = result.as_ref().err().cloned();
+        }
+        result
+    }
+
+    fn exchange(
+        &self,
+        rt: &tokio::runtime::Runtime,
+        store: &RwLock<Box<dyn StorageBackend>>,
+        eligible: &dyn Fn(&MemoryEntry) -> bool,
+        dry_run: bool,
+    ) -> Result<SyncReport, String> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| format!("http client init failed: {e}"))?;
+        let mut state = self.state.lock().clone();
+
+        let (mut remote_events, pull_to) = read_feed(state.pull_from, |from| rt.block_on(self.fetch(&client, from)))?;
+        let (mut local_events, push_to) = read_feed(state.push_from, |from| {
+            store
+                .read()
+                .changes(from, self.config.batch)
+                .map_err(|e| format!("local change feed unavailable: {e}; set PRX_MEMORY_CHANGE_LOG=1"))
+        })?;
+        remote_events.retain(|event| !take_echo(&mut state.pushed, &event.id));
+        local_events.retain(|event| !take_echo(&mut state.pulled, &event.id));
+
+        let mut report = SyncReport {
+            dry_run,
+            ..SyncReport::default()
+        };
+        let local_chains = chains(&local_events);
+        let remote_chains = chains(&remote_events);
+        let mut to_pull = Vec::new();
+        let mut to_push = Vec::new();
+        let mut dropped_local = BTreeSet::new();
+        let mut dropped_remote = BTreeSet::new();
+        for (root, local) in &local_chains {
+            // Both sides deleting the entry agree; anything else is a conflict.
+            let Some(remote) = remote_chains
+                .get(root)
+                .filter(|remote| !remote.ids.is_empty() || !local.ids.is_empty())
+            else {
+                continue;
+            };
+            let remote_wins = remote.last_ms >= local.last_ms;
+            report.conflicts.push(SyncConflict {
+                id: root.clone(),
+                winner: if remote_wins { "remote" } else { "local" },
+                local_ms: local.last_ms,
+                remote_ms: remote.last_ms,
+            });
+            // The loser's version is deleted on its side, and its events are not passed on.
+            if remote_wins {
+                to_pull.extend(local.ids.iter().map(|id| delete_event(id)));
+                dropped_local.insert(root.clone());
+            } else {
+                to_push.extend(remote.ids.iter().map(|id| delete_event(id)));
+                dropped_remote.insert(root.clone());
+            }
+        }
+
+        let roots_of = |chains: &HashMap<String, Chain>| {
+            chains
+                .iter()
+                .flat_map(|(root, chain)| chain.ids.iter().map(move |id| (id.clone(), root.clone())))
+                .collect::<HashMap<_, _>>()
+        };
+        // What the other side will hold as the batch goes through, so events on entries
+        // created earlier in the same batch are passed on too.
+        let mut pull_shared = state.known.clone();
+        let mut push_shared = state.known.clone();
+        let local_roots = roots_of(&local_chains);
+        let remote_roots = roots_of(&remote_chains);
+        for event in remote_events {
+            let root = remote_roots.get(&event.id).unwrap_or(&event.id);
+            if dropped_remote.contains(root) {
+                continue;
+            }
+            if let Some(event) = self.admit(event, &mut pull_shared, SyncDirection::pulls, eligible) {
+                to_pull.push(event);
+            }
+        }
+        for event in local_events {
+            let root = local_roots.get(&event.id).unwrap_or(&event.id);
+            if dropped_local.contains(root) {
+                continue;
+            }
+            if let Some(event) = self.admit(event, &mut push_shared, SyncDirection::pushes, eligible) {
+                to_push.push(event);
+            }
+        }
+
+        report.pulled = to_pull.len();
+        report.pushed = to_push.len();
+        if dry_run {
+            return Ok(report);
+        }
+
+        if !to_push.is_empty() {
+            let outcome = rt.block_on(self.push(&client, &to_push))?;
+            let applied = outcome.applied.into_iter().collect::<BTreeSet<_>>();
+            for event in to_push.iter().filter(|event| applied.contains(&event.id)) {
+                *state.pushed.entry(event.id.clone()).or_default() += 1;
+                record_known(&mut state.known, event);
+            }
+            report.pushed = applied.len();
+            report.rejected = outcome.rejected;
+        }
+        if !to_pull.is_empty() {
+            store.write().apply_changes(&to_pull).map_err(|e| e.to_string())?;
+            for event in &to_pull {
+                *state.pulled.entry(event.id.clone()).or_default() += 1;
+                record_known(&mut state.known, event);
+            }
+        }
+        state.pull_from = pull_to;
+        state.push_from = push_to;
+        self.save_state(&state)?;
+        *self.state.lock() = state;
+        Ok(report)
+    }
+
+    /// `event` as it may cross in a direction `allows`, or `None`; `shared` is updated with it. A
+    /// rewrite into an entry that may not cross still removes the shared entry it replaced; links to
+    /// entries the other side never had are dropped.
+    fn admit(
+        &self,
+        mut event: ChangeEvent,
+        shared: &mut BTreeMap<String, String>,
+        allows: fn(SyncDirection) -> bool,
+        eligible: &dyn Fn(&MemoryEntry) -> bool,
+    ) -> Option<ChangeEvent> {
+        if event.previous_id.as_ref().is_some_and(|id| !shared.contains_key(id)) {
+            event.previous_id = None;
+        }
+        let crosses = |scope: &str| self.config.direction(scope).is_some_and(allows);
+        let admitted = match (&event.op, &event.entry) {
+            (ChangeOp::Delete, _) => shared
+                .get(&event.id)
+                .is_some_and(|scope| crosses(scope))
+                .then_some(event),
+            (_, Some(entry)) if crosses(&entry.scope) && eligible(entry) => Some(event),
+            _ => event.previous_id.as_deref().map(delete_event),
+        }?;
+        record_known(shared, &admitted);
+        Some(admitted)
+    }
+
+    async fn fetch(&self, client: &reqwest::Client, from: u64) -> Result<ChangePage, String> {
+        let mut request = client.get(format!("{}/changes", self.config.remote)).query(&[
+            ("from", from),
+            ("limit", u64::try_from(self.config.batch).unwrap_or(500)),
+        ]);
+        if let Some(token) = &self.config.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("sync remote unreachable: {e}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("sync remote answered {status}: {}", body.trim()));
+        }
+        response
+            .json::<ChangePage>()
+            .await
+            .map_err(|e| format!("invalid change page: {e}"))
+    }
+
+    async fn push(&self, client: &reqwest::Client, events: &[ChangeEvent]) -> Result<PushOutcome, String> {
+        let mut request = client
+            .post(format!("{}/changes", self.config.remote))
+            .json(&json!({"events": events}));
+        if let Some(token) = &self.config.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("sync remote unreachable: {e}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("sync remote refused the push with {status}: {}", body.trim()));
+        }
+        response
+            .json::<PushOutcome>()
+            .await
+            .map_err(|e| format!("invalid push answer: {e}"))
+    }
+
+    fn save_state(&self, state: &SyncState) -> Result<(), String> {
+        let raw = serde_json::to_vec_pretty(state).map_err(|e| e.to_string())?;
+        atomic_write(&self.config.state_path, &raw).map_err(|e| format!("failed to write sync state: {e}"))
+    }
+}
+
+/// The rewrites and deletes descending from one entry within a batch of events.
+#[derive(Debug, Default)]
+struct Chain {
+    /// Ids the entry was rewritten into, oldest first.
+    ids: Vec<String>,
+    last_ms: u64,
+}
+
+/// Chains keyed by the id they started from.
+fn chains(events: &[ChangeEvent]) -> HashMap<String, Chain> {
+    let mut roots: HashMap<String, String> = HashMap::new();
+    let mut out: HashMap<String, Chain> = HashMap::new();
+    for event in events {
+        let from = match event.op {
+            ChangeOp::Delete => Some(&event.id),
+            ChangeOp::Update => event.previous_id.as_ref(),
+            ChangeOp::Store => None,
+        };
+        let Some(from) = from else {
+            continue;
+        };
+        let root = roots.get(from).cloned().unwrap_or_else(|| from.clone());
+        let chain = out.entry(root.clone()).or_default();
+        chain.last_ms = chain.last_ms.max(event.timestamp_ms);
+        if event.op != ChangeOp::Delete {
+            chain.ids.push(event.id.clone());
+            roots.insert(event.id.clone(), root);
+        }
+    }
+    out
+}
+
+/// Reads a change feed from `from` to its end; returns the events and where the next read starts.
+fn read_feed(
+    from: u64,
+    mut page: impl FnMut(u64) -> Result<ChangePage, String>,
+) -> Result<(Vec<ChangeEvent>, u64), String> {
+    let mut events = Vec::new();
+    let mut next = from;
+    loop {
+        let current = page(next)?;
+        let done = current.events.is_empty() || current.next_from > current.latest_seq;
+        next = current.next_from.max(next);
+        events.extend(current.events);
+        if done {
+            return Ok((events, next));
+        }
+    }
+}
+
+/// Counts off one expected echo of `id`; `true` when the event was one.
+fn take_echo(echoes: &mut BTreeMap<String, u32>, id: &str) -> bool {
+    let Some(count) = echoes.get_mut(id) else {
+        return false;
+    };
+    *count -= 1;
+    if *count == 0 {
+        echoes.remove(id);
+    }
+    true
+}
+
+fn record_known(known: &mut BTreeMap<String, String>, event: &ChangeEvent) {
+    if let Some(previous) = &event.previous_id {
+        known.remove(previous);
+    }
+    match (&event.op, &event.entry) {
+        (ChangeOp::Delete, _) | (_, None) => known.remove(&event.id),
+        (_, Some(entry)) => known.insert(event.id.clone(), entry.scope.clone()),
+    };
+}
+
+fn delete_event(id: &str) -> ChangeEvent {
+    ChangeEvent {
+        seq: 0,
+        op: ChangeOp::Delete,
+        id: id.to_string(),
+        previous_id: None,
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|d| u64::try_from(d.as_millis()).ok())
+            .unwrap_or(0),
+        entry: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(op: ChangeOp, id: &str, previous_id: Option<&str>, timestamp_ms: u64) -> ChangeEvent {
+        ChangeEvent {
+            seq: 0,
+            op,
+            id: id.to_string(),
+            previous_id: previous_id.map(str::to_string),
+            timestamp_ms,
+            entry: None,
+        }
+    }
+
+    #[test]
+    fn chains_follow_rewrites_back_to_the_entry_they_started_from() {
+        let events = [
+            event(ChangeOp::Store, "mem-a", None, 1),
+            event(ChangeOp::Update, "mem-b", Some("mem-x"), 2),
+            event(ChangeOp::Update, "mem-c", Some("mem-b"), 5),
+            event(ChangeOp::Delete, "mem-y", None, 3),
+        ];
+        let chains = chains(&events);
+        assert_eq!(chains.len(), 2);
+        assert_eq!(
+            chains.get("mem-x").map(|c| (c.ids.clone(), c.last_ms)),
+            Some((vec!["mem-b".to_string(), "mem-c".to_string()], 5))
+        );
+        assert_eq!(chains.get("mem-y").map(|c| c.ids.len()), Some(0));
+
+        let rule = SyncRule {
+            pattern: "team:*".to_string(),
+            direction: SyncDirection::Both,
+        };
+        assert!(rule.matches("team:core"));
+        assert!(!rule.matches("global"));
+
+        let mut echoes = BTreeMap::from([("mem-a".to_string(), 2)]);
+        assert!(take_echo(&mut echoes, "mem-a"));
+        assert!(take_echo(&mut echoes, "mem-a"));
+        assert!(!take_echo(&mut echoes, "mem-a"));
+    }
+}
@@ -0,0 +1,262 @@
+//! Multi-tenant daemons (`PRX_MEMORY_TENANTS_DIR`): one `prx-memoryd` hosts an isolated store per
+//! tenant under `<dir>/<tenant>/memory-db.json`, each with its own sidecar files, sessions, jobs
+//! and metrics. HTTP requests pick their tenant from the token they authenticated with or from the
+//! `x-prx-tenant` header; requests without a tenant use the daemon's own `PRX_MEMORY_DB`.
+//!
+//! Tenant servers share the daemon's tokio runtime and are exported by the daemon's OTLP exporter.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::McpServer;
+
+pub const TENANT_HEADER: &str = "x-prx-tenant";
+
+/// Backends whose location does not come from the db path; tenants on them would share one store.
+const SHARED_BACKENDS: [&str; 4] = ["postgres", "qdrant", "redis", "s3"];
+
+/// Settings that name one remote or file for the whole process: every tenant would tail the same
+/// leader, push to the same sync remote or search the same secondary stores.
+const SHARED_SETTINGS: [&str; 3] = [
+    "PRX_MEMORY_FOLLOW_URL",
+    "PRX_MEMORY_SYNC_URL",
+    "PRX_MEMORY_SECONDARY_STORES",
+];
+
+pub struct TenantRegistry {
+    dir: PathBuf,
+    /// Token label to the tenant it is bound to.
+    token_tenants: HashMap<String, String>,
+    max_tenants: usize,
+    runtime: Arc<tokio::runtime::Runtime>,
+    servers: OpenTenants,
+}
+
+/// Tenant ids and their servers.
+pub type TenantServers = Vec<(String, Arc<McpServer>)>;
+
+/// The open tenant servers, shared with the daemon's OTLP exporter.
+#[derive(Clone, Default)]
+pub struct OpenTenants(Arc<Mutex<BTreeMap<String, Arc<McpServer>>>>);
+
+impl OpenTenants {
+    /// Open tenants in name order.
+    pub fn loaded(&self) -> TenantServers {
+        self.0
+            .lock()
+            .iter()
+            .map(|(tenant, server)| (tenant.clone(), Arc::clone(server)))
+            .collect()
+    }
+}
+
+impl TenantRegistry {
+    /// `None` unless `PRX_MEMORY_TENANTS_DIR` is set. `PRX_MEMORY_TENANT_TOKENS` binds token labels
+    /// to tenants (`label:tenant,...`) and `PRX_MEMORY_MAX_TENANTS` (default 100) caps how many
+    /// stores are open at once. Tenant stores run on the daemon's `runtime`.
+    pub fn from_env(runtime: &Arc<tokio::runtime::Runtime>) -> Result<Option<Self>, String> {
+        let Some(dir) = std::env::var("PRX_MEMORY_TENANTS_DIR")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+        else {
+            return Ok(None);
+        };
+        let backend = std::env::var("PRX_MEMORY_BACKEND").unwrap_or_default();
+        if SHARED_BACKENDS.contains(&backend.as_str()) {
+            return Err(format!(
+                "PRX_MEMORY_TENANTS_DIR needs a path-based backend; PRX_MEMORY_BACKEND={backend} would share one store"
+            ));
+        }
+        let is_set = |name: &str| std::env::var(name).is_ok_and(|v| !v.trim().is_empty());
+        if backend == "tiered" && is_set("PRX_MEMORY_COLD_URI") {
+            return Err(
+                "PRX_MEMORY_TENANTS_DIR cannot be combined with PRX_MEMORY_COLD_URI; tiered tenants would share one cold store"
+                    .to_string(),
+            );
+        }
+        if let Some(name) = SHARED_SETTINGS.into_iter().find(|name| is_set(name)) {
+            return Err(format!(
+                "PRX_MEMORY_TENANTS_DIR cannot be combined with {name}; every tenant would share it"
+            ));
+        }
+        let mut token_tenants = HashMap::new();
+        for pair in std::env::var("PRX_MEMORY_TENANT_TOKENS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+        {
+            let (label, tenant) = pair
+                .split_once(':')
+                .ok_or_else(|| format!("PRX_MEMORY_TENANT_TOKENS entries must be label:tenant, got {pair}"))?;
+            let tenant = tenant.trim();
+            validate_tenant(tenant)?;
+            token_tenants.insert(label.trim().to_string(), tenant.to_string());
+        }
+        let max_tenants = std::env::var("PRX_MEMORY_MAX_TENANTS")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(100)
+            .max(1);
+        Ok(Some(Self {
+            dir: PathBuf::from(dir),
+            token_tenants,
+            max_tenants,
+            runtime: Arc::clone(runtime),
+            servers: OpenTenants::default(),
+        }))
+    }
+
+    /// Tenant for a request. A token bound to a tenant always gets that tenant; a header naming
+    /// another one is refused. Unbound tokens may pick any tenant with the header.
+    pub fn resolve(&self, token_label: Option<&str>, header: Option<&str>) -> Result<Option<String>, String> {
+        let header = header.map(str::trim).filter(|v| !v.is_empty());
+        let bound = token_label.and_then(|label| self.token_tenants.get(label));
+        match (bound, header) {
+            (Some(bound), Some(requested)) if bound != requested => {
+                Err(format!("token is bound to tenant {bound}, not {requested}"))
+            }
+            (Some(bound), _) => Ok(Some(bound.clone())),
+            (None, Some(requested)) => {
+                validate_tenant(requested)?;
+                Ok(Some(requested.to_string()))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// The tenant's server, opening its store on first use.
+    pub fn server(&self, tenant: &str) -> Result<Arc<McpServer>, String> {
+        validate_tenant(tenant)?;
+        let mut servers = self.servers.0.lock();
+        if let Some(server) = servers.get(tenant) {
+            return Ok(Arc::clone(server));
+        }
+        if servers.len() >= self.max_tenants {
+            return Err(format!("tenant limit reached ({} open)", self.max_tenants));
+        }
+        let db_path = self.dir.join(tenant).join("memory-db.json");
+        let server = Arc::new(McpServer::for_tenant(
+            db_path.to_string_lossy().into_owned(),
+            Arc::clone(&self.runtime),
+        )?);
+        tracing::info!(tenant, path = %db_path.display(), "opened tenant store");
+        servers.insert(tenant.to_string(), Arc::clone(&server));
+        drop(servers);
+        Ok(server)
+    }
+
+    /// Open tenants in name order.
+    pub fn loaded(&self) -> TenantServers {
+        self.servers.loaded()
+    }
+
+    /// The tenant a token label is bound to, if any.
+    pub fn bound_tenant(&self, token_label: &str) -> Option<&str> {
+        self.token_tenants.get(token_label).map(String::as_str)
+    }
+
+    #[cfg(feature = "otel")]
+    pub fn open_tenants(&self) -> OpenTenants {
+        self.servers.clone()
+    }
+}
+
+fn validate_tenant(tenant: &str) -> Result<(), String> {
+    let valid = !tenant.is_empty()
+        && tenant.len() <= 64
+        && tenant
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "tenant ids must be 1-64 chars of a-z, 0-9, - and _, got {tenant}"
+        ))
+    }
+}
+
+/// Merges Prometheus text from several stores into one exposition, adding a `tenant` label to
+/// every sample and grouping each metric family's samples under its `# TYPE` line.
+pub fn merge_metrics(sources: &[(String, String)]) -> String {
+    let mut families: Vec<(String, Vec<String>, Vec<String>)> = Vec::new();
+    for (tenant, text) in sources {
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let header = line.strip_prefix("# HELP ").or_else(|| line.strip_prefix("# TYPE "));
+            let name = match header {
+                Some(rest) => rest.split_whitespace().next().unwrap_or_default(),
+                None if line.starts_with('#') => continue,
+                None => line
+                    .split(|c: char| c == '{' || c.is_whitespace())
+                    .next()
+                    .unwrap_or_default(),
+            };
+            let family = families
+                .iter()
+                .position(|(family, _, _)| {
+                    name == family
+                        || (header.is_none()
+                            && ["_bucket", "_sum", "_count"]
+                                .iter()
+                                .any(|suffix| name.strip_suffix(suffix) == Some(family.as_str())))
+                })
+                .unwrap_or_else(|| {
+                    families.push((name.to_string(), Vec::new(), Vec::new()));
+                    families.len() - 1
+                });
+            let Some((_, headers, samples)) = families.get_mut(family) else {
+                continue;
+            };
+            if header.is_none() {
+                samples.push(with_tenant_label(line, tenant));
+            } else if !headers.iter().any(|h| h == line) {
+                headers.push(line.to_string());
+            }
+        }
+    }
+    let mut out = String::new();
+    for (_, headers, samples) in families {
+        for line in headers.iter().chain(samples.iter()) {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn with_tenant_label(sample: &str, tenant: &str) -> String {
+    let label = format!("tenant=\"{tenant}\"");
+    match sample.split_once('{') {
+        Some((name, rest)) if rest.starts_with('}') => format!("{name}{{{label}{rest}"),
+        Some((name, rest)) => format!("{name}{{{label},{rest}"),
+        None => match sample.split_once(' ') {
+            Some((name, value)) => format!("{name}{{{label}}} {value}"),
+            None => sample.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_metrics_keep_families_together_with_tenant_labels() {
+        let a = "# TYPE prx_up gauge\n# TYPE prx_calls counter\nprx_up 1\nprx_calls{tool=\"x\"} 2\n";
+        let b = "# TYPE prx_up gauge\n# TYPE prx_calls counter\nprx_up 1\nprx_calls{} 5\n";
+        let merged = merge_metrics(&[
+            ("default".to_string(), a.to_string()),
+            ("acme".to_string(), b.to_string()),
+        ]);
+        assert_eq!(
+            merged,
+            "# TYPE prx_up gauge\nprx_up{tenant=\"default\"} 1\nprx_up{tenant=\"acme\"} 1\n\
+             # TYPE prx_calls counter\nprx_calls{tenant=\"default\",tool=\"x\"} 2\nprx_calls{tenant=\"acme\"} 5\n"
+        );
+    }
+}
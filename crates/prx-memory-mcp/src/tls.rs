@@ -0,0 +1,52 @@
+//! TLS termination for the HTTP transport.
+
+use std::io;
+use std::sync::Arc;
+
+use rustls::ServerConfig;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+/// Builds the TLS server config named by `PRX_MEMORY_TLS_CERT` and
+/// `PRX_MEMORY_TLS_KEY` (PEM paths). Returns `Ok(None)` when neither is set,
+/// so the transport stays plain HTTP.
+pub fn server_config_from_env() -> io::Result<Option<Arc<ServerConfig>>> {
+    let cert = std::env::var("PRX_MEMORY_TLS_CERT")
+        .ok()
+        .filter(|v| !v.trim().is_empty());
+    let key = std::env::var("PRX_MEMORY_TLS_KEY")
+        .ok()
+        .filter(|v| !v.trim().is_empty());
+    match (cert, key) {
+        (None, None) => Ok(None),
+        (Some(cert), Some(key)) => load_server_config(&cert, &key).map(Some),
+        _ => Err(invalid_input(
+            "PRX_MEMORY_TLS_CERT and PRX_MEMORY_TLS_KEY must be set together",
+        )),
+    }
+}
+
+/// Loads a PEM certificate chain and private key into a TLS 1.2/1.3 server config.
+pub fn load_server_config(cert_path: &str, key_path: &str) -> io::Result<Arc<ServerConfig>> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+        .map_err(|e| invalid_input(format!("failed to read TLS certificate {cert_path}: {e}")))?;
+    if certs.is_empty() {
+        return Err(invalid_input(format!("no certificates found in {cert_path}")));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| invalid_input(format!("failed to read TLS private key {key_path}: {e}")))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| invalid_input(format!("TLS protocol setup failed: {e}")))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| invalid_input(format!("TLS certificate/key rejected: {e}")))?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+fn invalid_input(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
@@ -0,0 +1,107 @@
+//! Function-calling schemas for agents that mount the memory tools without an MCP client.
+//!
+//! The `tools/list` entries stay the single source of truth; this only reshapes them into the
+//! `tools` array of the OpenAI Chat Completions API or the Anthropic Messages API. Calls still go
+//! through `tools/call` on the HTTP transport, with the function name and arguments unchanged.
+
+use serde_json::{Value, json};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    OpenAi,
+    Anthropic,
+}
+
+impl SchemaFormat {
+    pub fn parse(raw: Option<&str>) -> Result<Self, String> {
+        match raw.map(str::to_ascii_lowercase).as_deref() {
+            None | Some("openai") => Ok(Self::OpenAi),
+            Some("anthropic") => Ok(Self::Anthropic),
+            Some(other) => Err(format!("format must be one of openai|anthropic, got {other}")),
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::Anthropic => "anthropic",
+        }
+    }
+}
+
+/// Reshapes `tools/list` entries, keeping only the names in `only` when it is given.
+pub fn export(tools: &[Value], format: SchemaFormat, only: Option<&[String]>) -> Result<Vec<Value>, String> {
+    let name_of = |tool: &Value| tool.get("name").and_then(Value::as_str).unwrap_or_default().to_string();
+    if let Some(only) = only {
+        let unknown = only
+            .iter()
+            .filter(|name| !tools.iter().any(|tool| name_of(tool) == **name))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            return Err(format!("unknown tools: {}", unknown.join(", ")));
+        }
+    }
+    Ok(tools
+        .iter()
+        .filter(|tool| only.is_none_or(|only| only.contains(&name_of(tool))))
+        .map(|tool| {
+            let name = name_of(tool);
+            let description = tool.get("description").cloned().unwrap_or_else(|| json!(""));
+            let schema = tool
+                .get("inputSchema")
+                .cloned()
+                .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+            match format {
+                SchemaFormat::OpenAi => json!({
+                    "type": "function",
+                    "function": {"name": name, "description": description, "parameters": schema}
+                }),
+                SchemaFormat::Anthropic => json!({
+                    "name": name,
+                    "description": description,
+                    "input_schema": schema
+                }),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Value> {
+        vec![
+            json!({"name": "memory_store", "description": "Store.", "inputSchema": {"type": "object", "required": ["text"]}}),
+            json!({"name": "memory_list", "description": "List.", "inputSchema": {"type": "object"}}),
+        ]
+    }
+
+    #[test]
+    fn reshapes_for_each_format_and_filters_by_name() {
+        let openai = export(&sample(), SchemaFormat::OpenAi, None).unwrap_or_default();
+        assert_eq!(openai.len(), 2);
+        assert_eq!(
+            openai
+                .first()
+                .and_then(|t| t.pointer("/function/parameters/required/0")),
+            Some(&json!("text"))
+        );
+        assert_eq!(openai.first().and_then(|t| t.get("type")), Some(&json!("function")));
+
+        let only = vec!["memory_list".to_string()];
+        let anthropic = export(&sample(), SchemaFormat::Anthropic, Some(&only)).unwrap_or_default();
+        assert_eq!(
+            anthropic,
+            vec![json!({"name": "memory_list", "description": "List.", "input_schema": {"type": "object"}})]
+        );
+
+        let missing = vec!["memory_nope".to_string()];
+        assert_eq!(
+            export(&sample(), SchemaFormat::OpenAi, Some(&missing)),
+            Err("unknown tools: memory_nope".to_string())
+        );
+        assert!(SchemaFormat::parse(Some("gemini")).is_err());
+    }
+}
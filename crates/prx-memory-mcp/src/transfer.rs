@@ -0,0 +1,439 @@
+//! File formats for `memory_export` (JSON, JSONL, CSV, Markdown) and for `memory_import` /
+//! `memory_migrate` (JSON, JSONL, CSV).
+//!
+//! Writers stream one entry at a time into the given sink, so a large export never holds a second,
+//! rendered copy of the store in memory. Readers turn external records into import-entry objects,
+//! renaming source columns through an optional mapping.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use prx_memory_storage::MemoryEntry;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Column order of CSV exports.
+pub const CSV_COLUMNS: [&str; 10] = [
+    "id",
+    "scope",
+    "category",
+    "importance",
+    "tags",
+    "timestamp_ms",
+    "text",
+    "embedding_model",
+    "embedding_dim",
+    "embedding",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Jsonl,
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn parse(raw: Option<&str>) -> Result<Self, String> {
+        match raw.map(str::to_ascii_lowercase).as_deref() {
+            None | Some("json") => Ok(Self::Json),
+            Some("jsonl") => Ok(Self::Jsonl),
+            Some("csv") => Ok(Self::Csv),
+            Some("markdown" | "md") => Ok(Self::Markdown),
+            Some(other) => Err(format!("format must be one of json|jsonl|csv|markdown, got {other}")),
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Jsonl => "jsonl",
+            Self::Csv => "csv",
+            Self::Markdown => "markdown",
+        }
+    }
+}
+
+/// Import-entry fields a column mapping may target.
+pub const IMPORT_FIELDS: [&str; 11] = [
+    "text",
+    "category",
+    "scope",
+    "importance",
+    "importance_level",
+    "tags",
+    "project_tag",
+    "tool_tag",
+    "domain_tag",
+    "embedding",
+    "embedding_model",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Json,
+    Jsonl,
+    Csv,
+}
+
+impl ImportFormat {
+    /// Parses an explicit `format`, falling back to the file extension of `path`, then to JSON.
+    pub fn resolve(raw: Option<&str>, path: Option<&Path>) -> Result<Self, String> {
+        if let Some(raw) = raw {
+            return match raw.to_ascii_lowercase().as_str() {
+                "json" => Ok(Self::Json),
+                "jsonl" | "ndjson" => Ok(Self::Jsonl),
+                "csv" => Ok(Self::Csv),
+                other => Err(format!("format must be one of json|jsonl|csv, got {other}")),
+            };
+        }
+        let extension = path
+            .and_then(Path::extension)
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        Ok(match extension.as_deref() {
+            Some("jsonl" | "ndjson") => Self::Jsonl,
+            Some("csv") => Self::Csv,
+            _ => Self::Json,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct JsonEnvelope<'a> {
+    entries: &'a [MemoryEntry],
+}
+
+pub fn write_entries<W: Write>(out: &mut W, format: ExportFormat, entries: &[MemoryEntry]) -> io::Result<()> {
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &JsonEnvelope { entries })?;
+        }
+        ExportFormat::Jsonl => {
+            for entry in entries {
+                serde_json::to_writer(&mut *out, entry)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        ExportFormat::Csv => {
+            writeln!(out, "{}", CSV_COLUMNS.join(","))?;
+            for entry in entries {
+                write_csv_row(out, entry)?;
+            }
+        }
+        ExportFormat::Markdown => {
+            writeln!(out, "# Memory export")?;
+            for entry in entries {
+                write_markdown_entry(out, entry)?;
+            }
+        }
+    }
+    out.flush()
+}
+
+fn write_csv_row<W: Write>(out: &mut W, entry: &MemoryEntry) -> io::Result<()> {
+    let embedding = entry
+        .embedding
+        .as_ref()
+        .map(|v| Value::from(v.clone()).to_string())
+        .unwrap_or_default();
+    let fields = [
+        entry.id.clone(),
+        entry.scope.clone(),
+        entry.category.clone(),
+        format!("{:.2}", entry.importance),
+        entry.tags.join(";"),
+        entry.timestamp_ms.to_string(),
+        entry.text.clone(),
+        entry.embedding_model.clone().unwrap_or_default(),
+        entry.embedding_dim.map(|d| d.to_string()).unwrap_or_default(),
+        embedding,
+    ];
+    let row = fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>();
+    writeln!(out, "{}", row.join(","))
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_markdown_entry<W: Write>(out: &mut W, entry: &MemoryEntry) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(out, "## {} · {} · `{}`", entry.category, entry.scope, entry.id)?;
+    writeln!(out)?;
+    writeln!(out, "- importance: {:.2}", entry.importance)?;
+    if !entry.tags.is_empty() {
+        let tags = entry.tags.iter().map(|t| format!("`{t}`")).collect::<Vec<_>>();
+        writeln!(out, "- tags: {}", tags.join(", "))?;
+    }
+    writeln!(out, "- timestamp_ms: {}", entry.timestamp_ms)?;
+    writeln!(out)?;
+    writeln!(out, "{}", entry.text.trim_end())
+}
+
+/// Parses `data` into import-entry objects.
+///
+/// `columns` maps an import field to the source key or CSV header holding it; unmapped fields are
+/// read from the key of the same name. CSV cells are strings, so `importance`, `tags` (split on `;`
+/// or `,`) and `embedding` (a JSON array) are converted.
+pub fn read_records(data: &str, format: ImportFormat, columns: &HashMap<String, String>) -> Result<Vec<Value>, String> {
+    if let Some(unknown) = columns.keys().find(|k| !IMPORT_FIELDS.contains(&k.as_str())) {
+        return Err(format!(
+            "columns maps unknown field {unknown}; expected one of {}",
+            IMPORT_FIELDS.join("|")
+        ));
+    }
+    let data = data.strip_prefix('\u{feff}').unwrap_or(data);
+    match format {
+        ImportFormat::Json => {
+            let parsed = serde_json::from_str::<Value>(data).map_err(|e| format!("invalid JSON: {e}"))?;
+            let rows = match parsed {
+                Value::Array(rows) => Some(rows),
+                Value::Object(mut obj) => match obj.remove("entries") {
+                    Some(Value::Array(rows)) => Some(rows),
+                    _ => None,
+                },
+                _ => None,
+            }
+            .ok_or_else(|| "JSON payload must be an array or an object with an entries array".to_string())?;
+            rows.iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    let obj = row
+                        .as_object()
+                        .ok_or_else(|| format!("record {}: not an object", i + 1))?;
+                    Ok(map_record(obj, columns))
+                })
+                .collect()
+        }
+        ImportFormat::Jsonl => data
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| match serde_json::from_str::<Value>(line) {
+                Ok(Value::Object(obj)) => Ok(map_record(&obj, columns)),
+                Ok(_) => Err(format!("line {}: not a JSON object", i + 1)),
+                Err(err) => Err(format!("line {}: {err}", i + 1)),
+            })
+            .collect(),
+        ImportFormat::Csv => {
+            let mut rows = parse_csv(data)?.into_iter();
+            let header = rows.next().ok_or_else(|| "CSV payload has no header row".to_string())?;
+            rows.enumerate()
+                .filter(|(_, cells)| cells.iter().any(|c| !c.is_empty()))
+                .map(|(i, cells)| {
+                    let obj = header
+                        .iter()
+                        .map(|h| h.trim().to_string())
+                        .zip(cells.into_iter().map(Value::String))
+                        .collect::<Map<String, Value>>();
+                    csv_types(map_record(&obj, columns)).map_err(|err| format!("row {}: {err}", i + 2))
+                })
+                .collect()
+        }
+    }
+}
+
+fn map_record(source: &Map<String, Value>, columns: &HashMap<String, String>) -> Value {
+    let mut out = Map::new();
+    for field in IMPORT_FIELDS {
+        let key = columns.get(field).map_or(field, String::as_str);
+        match source.get(key) {
+            None | Some(Value::Null) => {}
+            Some(Value::String(s)) if s.is_empty() => {}
+            Some(value) => {
+                out.insert(field.to_string(), value.clone());
+            }
+        }
+    }
+    Value::Object(out)
+}
+
+fn csv_types(mut record: Value) -> Result<Value, String> {
+    let Some(obj) = record.as_object_mut() else {
+        return Ok(record);
+    };
+    if let Some(Value::String(raw)) = obj.get("importance") {
+        let importance = raw
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("importance is not a number: {raw}"))?;
+        obj.insert("importance".to_string(), Value::from(importance));
+    }
+    if let Some(Value::String(raw)) = obj.get("tags") {
+        let tags = raw
+            .split([';', ','])
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| Value::String(t.to_string()))
+            .collect::<Vec<_>>();
+        obj.insert("tags".to_string(), Value::Array(tags));
+    }
+    if let Some(Value::String(raw)) = obj.get("embedding") {
+        let embedding =
+            serde_json::from_str::<Value>(raw).map_err(|e| format!("embedding is not a JSON array: {e}"))?;
+        obj.insert("embedding".to_string(), embedding);
+    }
+    Ok(record)
+}
+
+/// Splits RFC 4180 CSV into rows of cells: quoted fields may hold delimiters, `""` and line breaks.
+fn parse_csv(data: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut cell)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            (_, c) => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err("CSV payload ends inside a quoted field".to_string());
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, text: &str, tags: &[&str]) -> MemoryEntry {
+        MemoryEntry {
+            id: id.to_string(),
+            text: text.to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.75,
+            tags: tags.iter().map(|t| (*t).to_string()).collect(),
+            timestamp_ms: 1_700_000_000_000,
+            embedding: None,
+            embedding_model: None,
+            source: None,
+            embedding_dim: None,
+            keywords: Vec::new(),
+        }
+    }
+
+    fn render(format: ExportFormat, entries: &[MemoryEntry]) -> String {
+        let mut out = Vec::new();
+        write_entries(&mut out, format, entries).expect("write export");
+        String::from_utf8(out).expect("utf8 export")
+    }
+
+    #[test]
+    fn csv_quotes_delimiters_quotes_and_newlines() {
+        let rows = [
+            entry("mem-1", "plain text", &["project:prx"]),
+            entry("mem-2", "says \"hi\", then\nleaves", &["a", "b"]),
+        ];
+        let csv = render(ExportFormat::Csv, &rows);
+        assert_eq!(
+            csv,
+            "id,scope,category,importance,tags,timestamp_ms,text,embedding_model,embedding_dim,embedding\n\
+             mem-1,global,fact,0.75,project:prx,1700000000000,plain text,,,\n\
+             mem-2,global,fact,0.75,a;b,1700000000000,\"says \"\"hi\"\", then\nleaves\",,,\n"
+        );
+    }
+
+    #[test]
+    fn jsonl_and_markdown_emit_one_record_per_entry() {
+        let rows = [entry("mem-1", "first", &["x"]), entry("mem-2", "second", &[])];
+
+        let jsonl = render(ExportFormat::Jsonl, &rows);
+        let ids = jsonl
+            .lines()
+            .map(|l| serde_json::from_str::<MemoryEntry>(l).expect("jsonl row").id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["mem-1", "mem-2"]);
+
+        let markdown = render(ExportFormat::Markdown, &rows);
+        assert!(markdown.starts_with("# Memory export\n"));
+        assert!(markdown.contains("## fact · global · `mem-1`\n\n- importance: 0.75\n- tags: `x`\n"));
+        assert!(markdown.contains("`mem-2`\n\n- importance: 0.75\n- timestamp_ms: 1700000000000\n\nsecond\n"));
+    }
+
+    #[test]
+    fn format_parse_rejects_unknown_values() {
+        assert_eq!(ExportFormat::parse(None), Ok(ExportFormat::Json));
+        assert_eq!(ExportFormat::parse(Some("CSV")), Ok(ExportFormat::Csv));
+        assert!(ExportFormat::parse(Some("xml")).is_err());
+    }
+
+    #[test]
+    fn csv_export_reads_back_with_typed_fields() {
+        let rows = [entry("mem-1", "says \"hi\", then\nleaves", &["a", "b"])];
+        let csv = render(ExportFormat::Csv, &rows);
+        let records = read_records(&csv, ImportFormat::Csv, &HashMap::new()).expect("read csv");
+        assert_eq!(
+            records,
+            vec![serde_json::json!({
+                "text": "says \"hi\", then\nleaves",
+                "category": "fact",
+                "scope": "global",
+                "importance": 0.75,
+                "tags": ["a", "b"]
+            })]
+        );
+    }
+
+    #[test]
+    fn column_mapping_renames_source_fields() {
+        let columns = HashMap::from([
+            ("text".to_string(), "Note".to_string()),
+            ("tags".to_string(), "Labels".to_string()),
+        ]);
+        let csv = "Title,Note,Labels\r\nRust,\"Use clippy, always\",\"lang:rust, tool:clippy\"\r\n";
+        let records = read_records(csv, ImportFormat::Csv, &columns).expect("read mapped csv");
+        assert_eq!(
+            records,
+            vec![serde_json::json!({"text": "Use clippy, always", "tags": ["lang:rust", "tool:clippy"]})]
+        );
+
+        let jsonl = "{\"Note\": \"first\", \"scope\": \"global\"}\n\n{\"Note\": \"second\"}\n";
+        let records = read_records(jsonl, ImportFormat::Jsonl, &columns).expect("read mapped jsonl");
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records.first(),
+            Some(&serde_json::json!({"text": "first", "scope": "global"}))
+        );
+
+        let bad = HashMap::from([("title".to_string(), "Title".to_string())]);
+        assert!(read_records(csv, ImportFormat::Csv, &bad).is_err());
+        assert!(read_records("{\"text\": 1}\nnot json", ImportFormat::Jsonl, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn import_format_falls_back_to_file_extension() {
+        let resolve = |raw, path: &str| ImportFormat::resolve(raw, Some(Path::new(path)));
+        assert_eq!(resolve(None, "notes.CSV"), Ok(ImportFormat::Csv));
+        assert_eq!(resolve(None, "notes.ndjson"), Ok(ImportFormat::Jsonl));
+        assert_eq!(resolve(None, "notes.json"), Ok(ImportFormat::Json));
+        assert_eq!(resolve(Some("csv"), "notes.json"), Ok(ImportFormat::Csv));
+        assert!(resolve(Some("markdown"), "notes.md").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Per-agent operation counts behind `memory_usage_report` and the agent quota, persisted as one
+//! [`UsageRecord`] per line of `<db>.usage.jsonl` so attribution and quotas survive restarts.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use prx_memory_storage::atomic_write;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageOp {
+    Store,
+    Recall,
+    Forget,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UsageEvent {
+    pub op: UsageOp,
+    pub bytes: u64,
+    pub ts_ms: u64,
+}
+
+/// Lifetime totals for one agent plus its most recent events, newest last.
+#[derive(Debug, Default, Clone)]
+pub struct AgentUsage {
+    pub stores: u64,
+    pub recalls: u64,
+    pub forgets: u64,
+    pub bytes_written: u64,
+    pub events: VecDeque<UsageEvent>,
+}
+
+impl AgentUsage {
+    fn add(&mut self, event: UsageEvent, max_events: usize) {
+        match event.op {
+            UsageOp::Store => self.stores = self.stores.saturating_add(1),
+            UsageOp::Recall => self.recalls = self.recalls.saturating_add(1),
+            UsageOp::Forget => self.forgets = self.forgets.saturating_add(1),
+        }
+        self.bytes_written = self.bytes_written.saturating_add(event.bytes);
+        self.events.push_back(event);
+        while self.events.len() > max_events {
+            let _ = self.events.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum UsageRecord {
+    Event {
+        agent_id: String,
+        op: UsageOp,
+        bytes: u64,
+        ts_ms: u64,
+    },
+    /// Totals not covered by the agent's event lines that follow; written when the log is compacted.
+    Base {
+        agent_id: String,
+        stores: u64,
+        recalls: u64,
+        forgets: u64,
+        bytes_written: u64,
+    },
+}
+
+/// Usage for every agent seen. Event lines are buffered and appended in batches; once the file
+/// holds more than twice the lines needed for the live state it is rewritten through a temp file
+/// and rename.
+#[derive(Debug)]
+pub struct UsageLog {
+    path: PathBuf,
+    max_events: usize,
+    agents: HashMap<String, AgentUsage>,
+    lines: usize,
+    /// Event lines not yet appended to the file.
+    pending: Vec<String>,
+    last_flush_ms: u64,
+}
+
+/// Lines below which the log is never compacted.
+const MIN_COMPACT_LINES: usize = 1_024;
+/// Buffered event lines that trigger an append.
+const FLUSH_LINES: usize = 64;
+/// Age of the last append after which the next event flushes the buffer.
+const FLUSH_INTERVAL_MS: u64 = 1_000;
+
+impl UsageLog {
+    /// Replays the persisted log; a missing file starts empty and unreadable lines are skipped.
+    pub fn open(path: impl Into<PathBuf>, max_events: usize) -> Self {
+        let path = path.into();
+        let max_events = max_events.max(1);
+        let raw = match fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(error) => {
+                if error.kind() != io::ErrorKind::NotFound {
+                    tracing::warn!(path = %path.display(), %error, "usage log unreadable; starting empty");
+                }
+                String::new()
+            }
+        };
+        let mut agents: HashMap<String, AgentUsage> = HashMap::new();
+        let mut lines = 0usize;
+        for line in raw.lines() {
+            lines = lines.saturating_add(1);
+            match serde_json::from_str::<UsageRecord>(line) {
+                Ok(UsageRecord::Event {
+                    agent_id,
+                    op,
+                    bytes,
+                    ts_ms,
+                }) => agents
+                    .entry(agent_id)
+                    .or_default()
+                    .add(UsageEvent { op, bytes, ts_ms }, max_events),
+                Ok(UsageRecord::Base {
+                    agent_id,
+                    stores,
+                    recalls,
+                    forgets,
+                    bytes_written,
+                }) => {
+                    let usage = agents.entry(agent_id).or_default();
+                    usage.stores = usage.stores.saturating_add(stores);
+                    usage.recalls = usage.recalls.saturating_add(recalls);
+                    usage.forgets = usage.forgets.saturating_add(forgets);
+                    usage.bytes_written = usage.bytes_written.saturating_add(bytes_written);
+                }
+                Err(error) => {
+                    tracing::warn!(path = %path.display(), line = lines, %error, "skipped unreadable usage log line");
+                }
+            }
+        }
+        Self {
+            path,
+            max_events,
+            agents,
+            lines,
+            pending: Vec::new(),
+            last_flush_ms: 0,
+        }
+    }
+
+    pub fn max_events(&self) -> usize {
+        self.max_events
+    }
+
+    pub fn agents(&self) -> &HashMap<String, AgentUsage> {
+        &self.agents
+    }
+
+    pub fn get(&self, agent: &str) -> Option<&AgentUsage> {
+        self.agents.get(agent)
+    }
+
+    /// Counts one operation for `agent` and buffers its event line, appending the buffer once it
+    /// holds [`FLUSH_LINES`] lines or the last append is older than [`FLUSH_INTERVAL_MS`].
+    pub fn record(&mut self, agent: &str, op: UsageOp, bytes: u64, ts_ms: u64) -> Result<(), String> {
+        self.agents
+            .entry(agent.to_string())
+            .or_default()
+            .add(UsageEvent { op, bytes, ts_ms }, self.max_events);
+        let line = serde_json::to_string(&UsageRecord::Event {
+            agent_id: agent.to_string(),
+            op,
+            bytes,
+            ts_ms,
+        })
+        .map_err(|e| e.to_string())?;
+        self.pending.push(line);
+        if self.pending.len() >= FLUSH_LINES || ts_ms >= self.last_flush_ms.saturating_add(FLUSH_INTERVAL_MS) {
+            self.last_flush_ms = ts_ms;
+            return self.flush();
+        }
+        Ok(())
+    }
+
+    /// Appends the buffered event lines, or compacts the log when it has grown past twice the
+    /// live state. Called on shutdown and drop so no counted operation is lost.
+    pub fn flush(&mut self) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let live = self.agents.values().map(|u| u.events.len() + 1).sum::<usize>();
+        if self.lines.saturating_add(self.pending.len()) >= live.max(MIN_COMPACT_LINES).saturating_mul(2) {
+            return self.persist();
+        }
+        let mut raw = self.pending.join("\n");
+        raw.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("failed to write usage log: {e}"))?;
+        file.write_all(raw.as_bytes())
+            .map_err(|e| format!("failed to write usage log: {e}"))?;
+        self.lines = self.lines.saturating_add(self.pending.len());
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// Rewrites the log as one base line per agent followed by its retained events.
+    fn persist(&mut self) -> Result<(), String> {
+        let mut raw = String::new();
+        let mut lines = 0usize;
+        let mut agents = self.agents.iter().collect::<Vec<_>>();
+        agents.sort_by(|a, b| a.0.cmp(b.0));
+        for (agent, usage) in agents {
+            let mut base = (usage.stores, usage.recalls, usage.forgets, usage.bytes_written);
+            for event in &usage.events {
+                match event.op {
+                    UsageOp::Store => base.0 = base.0.saturating_sub(1),
+                    UsageOp::Recall => base.1 = base.1.saturating_sub(1),
+                    UsageOp::Forget => base.2 = base.2.saturating_sub(1),
+                }
+                base.3 = base.3.saturating_sub(event.bytes);
+            }
+            let records = std::iter::once(UsageRecord::Base {
+                agent_id: agent.clone(),
+                stores: base.0,
+                recalls: base.1,
+                forgets: base.2,
+                bytes_written: base.3,
+            })
+            .chain(usage.events.iter().map(|event| UsageRecord::Event {
+                agent_id: agent.clone(),
+                op: event.op,
+                bytes: event.bytes,
+                ts_ms: event.ts_ms,
+            }));
+            for record in records {
+                raw.push_str(&serde_json::to_string(&record).map_err(|e| e.to_string())?);
+                raw.push('\n');
+                lines += 1;
+            }
+        }
+        atomic_write(&self.path, raw.as_bytes()).map_err(|e| format!("failed to write usage log: {e}"))?;
+        self.lines = lines;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Appends what is still buffered when a server is dropped without a shutdown, such as an evicted
+/// tenant.
+impl Drop for UsageLog {
+    fn drop(&mut self) {
+        if let Err(error) = self.flush() {
+            tracing::warn!(path = %self.path.display(), %error, "failed to persist agent usage");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn totals_and_events_survive_reopen_and_compaction() {
+        let path = std::env::temp_dir().join(format!("prx-usage-log-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut log = UsageLog::open(&path, 2);
+        log.record("alice", UsageOp::Store, 10, 1).expect("record");
+        log.record("alice", UsageOp::Recall, 0, 2).expect("record");
+        log.record("alice", UsageOp::Store, 5, 3).expect("record");
+        log.record("bob", UsageOp::Forget, 0, 4).expect("record");
+        log.flush().expect("flush");
+
+        let reopened = UsageLog::open(&path, 2);
+        let alice = reopened.get("alice").expect("alice");
+        assert_eq!((alice.stores, alice.recalls, alice.bytes_written), (2, 1, 15));
+        assert_eq!(alice.events.len(), 2);
+        assert_eq!(reopened.get("bob").map(|u| u.forgets), Some(1));
+
+        let mut compacted = reopened;
+        compacted.persist().expect("persist");
+        let reopened = UsageLog::open(&path, 2);
+        let alice = reopened.get("alice").expect("alice");
+        assert_eq!((alice.stores, alice.recalls, alice.bytes_written), (2, 1, 15));
+        assert_eq!(alice.events.iter().map(|e| e.ts_ms).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(reopened.lines, 5);
+
+        let _ = fs::remove_file(path);
+    }
+}
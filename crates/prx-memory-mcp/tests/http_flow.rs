@@ -1,8 +1,13 @@
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use prx_memory_storage::{NewMemoryEntry, PersistentMemoryStore};
+use rustls::pki_types::CertificateDer;
+use rustls::pki_types::pem::PemObject;
+
 fn reserve_addr() -> String {
     let listener = TcpListener::bind("127.0.0.1:0").expect("reserve addr");
     let addr = listener.local_addr().expect("local addr");
@@ -20,10 +25,35 @@ fn wait_for_http(addr: &str) {
     panic!("http server not ready on {addr}");
 }
 
+/// `prx-memoryd` on the HTTP transport with `envs` set and its output discarded.
+fn daemon_command(envs: &[(&str, &str)]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_prx-memoryd"));
+    command
+        .env("PRX_MEMORYD_TRANSPORT", "http")
+        .envs(envs.iter().copied())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+fn spawn_daemon(envs: &[(&str, &str)]) -> Child {
+    daemon_command(envs).spawn().expect("spawn prx-memoryd")
+}
+
 fn send_http(addr: &str, method: &str, path: &str, body: &str) -> String {
+    send_http_with_headers(addr, method, path, body, &[])
+}
+
+/// One request on a fresh connection with `headers` added, returning the raw response.
+fn send_http_with_headers(addr: &str, method: &str, path: &str, body: &str, headers: &[(&str, &str)]) -> String {
     let mut stream = TcpStream::connect(addr).expect("connect http");
+    let headers = headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\r\n"))
+        .collect::<String>();
     let request = format!(
-        "{method} {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        "{method} {path} HTTP/1.1\r\nHost: {addr}\r\n{headers}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
         body.len(),
         body
     );
@@ -54,15 +84,7 @@ fn http_health_and_mcp_call_work() {
         .to_string();
     let addr = reserve_addr();
 
-    let mut child = Command::new(env!("CARGO_BIN_EXE_prx-memoryd"))
-        .env("PRX_MEMORYD_TRANSPORT", "http")
-        .env("PRX_MEMORY_HTTP_ADDR", &addr)
-        .env("PRX_MEMORY_DB", &db_path)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .expect("spawn prx-memoryd");
+    let mut child = spawn_daemon(&[("PRX_MEMORY_HTTP_ADDR", &addr), ("PRX_MEMORY_DB", &db_path)]);
 
     wait_for_http(&addr);
 
@@ -95,15 +117,7 @@ fn http_stream_session_and_metrics_work() {
         .to_string();
     let addr = reserve_addr();
 
-    let mut child = Command::new(env!("CARGO_BIN_EXE_prx-memoryd"))
-        .env("PRX_MEMORYD_TRANSPORT", "http")
-        .env("PRX_MEMORY_HTTP_ADDR", &addr)
-        .env("PRX_MEMORY_DB", &db_path)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .expect("spawn prx-memoryd");
+    let mut child = spawn_daemon(&[("PRX_MEMORY_HTTP_ADDR", &addr), ("PRX_MEMORY_DB", &db_path)]);
 
     wait_for_http(&addr);
 
@@ -168,15 +182,7 @@ fn http_stream_ack_and_sse_work() {
         .to_string();
     let addr = reserve_addr();
 
-    let mut child = Command::new(env!("CARGO_BIN_EXE_prx-memoryd"))
-        .env("PRX_MEMORYD_TRANSPORT", "http")
-        .env("PRX_MEMORY_HTTP_ADDR", &addr)
-        .env("PRX_MEMORY_DB", &db_path)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .expect("spawn prx-memoryd");
+    let mut child = spawn_daemon(&[("PRX_MEMORY_HTTP_ADDR", &addr), ("PRX_MEMORY_DB", &db_path)]);
 
     wait_for_http(&addr);
 
@@ -231,6 +237,64 @@ fn http_stream_ack_and_sse_work() {
     let _ = std::fs::remove_file(db_path);
 }
 
+#[test]
+fn streamed_recall_pushes_local_ranking_before_the_rerank() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-partial-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[("PRX_MEMORY_HTTP_ADDR", &addr), ("PRX_MEMORY_DB", &db_path)]);
+
+    wait_for_http(&addr);
+
+    let store = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"memory_store","arguments":{"text":"Streaming recall shows local hits before the rerank returns","category":"fact","scope":"global","governed":false}}}"#;
+    assert!(send_http(&addr, "POST", "/mcp", store).starts_with("HTTP/1.1 200"));
+
+    let start_resp = send_http(&addr, "POST", "/mcp/session/start", "{}");
+    let start_json: serde_json::Value = serde_json::from_str(response_body(&start_resp)).expect("start json");
+    let session_id = start_json
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .expect("session id")
+        .to_string();
+
+    let recall = r#"{"jsonrpc":"2.0","id":31,"method":"tools/call","params":{"name":"memory_recall","arguments":{"query":"streaming recall rerank","use_remote":true,"stream":true}}}"#;
+    let enqueue = send_http(&addr, "POST", &format!("/mcp/stream?session={session_id}"), recall);
+    assert!(enqueue.starts_with("HTTP/1.1 202"));
+
+    let sse = send_http(
+        &addr,
+        "GET",
+        &format!("/mcp/stream?session={session_id}&mode=sse&from=1&limit=2&wait_ms=300&heartbeat_ms=100"),
+        "",
+    );
+    let messages = response_body(&sse)
+        .split("\n\n")
+        .filter_map(|frame| frame.strip_prefix("event: message\ndata: "))
+        .map(|data| serde_json::from_str::<serde_json::Value>(data).expect("event json"))
+        .collect::<Vec<_>>();
+    assert_eq!(messages.len(), 2);
+    let partial = &messages[0]["payload"];
+    assert_eq!(partial["method"], "notifications/prx/recall_partial");
+    assert_eq!(partial["params"]["request_id"], 31);
+    assert_eq!(partial["params"]["stage"], "local");
+    assert_eq!(partial["params"]["count"], 1);
+    let last = &messages[1]["payload"];
+    assert_eq!(last["id"], 31);
+    assert_eq!(last["result"]["structuredContent"]["partial_seq"], messages[0]["seq"]);
+    assert_eq!(last["result"]["structuredContent"]["count"], 1);
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
 #[test]
 fn http_stream_session_expiry_work() {
     let now = SystemTime::now()
@@ -243,16 +307,11 @@ fn http_stream_session_expiry_work() {
         .to_string();
     let addr = reserve_addr();
 
-    let mut child = Command::new(env!("CARGO_BIN_EXE_prx-memoryd"))
-        .env("PRX_MEMORYD_TRANSPORT", "http")
-        .env("PRX_MEMORY_HTTP_ADDR", &addr)
-        .env("PRX_MEMORY_DB", &db_path)
-        .env("PRX_MEMORY_STREAM_SESSION_TTL_MS", "1000")
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .expect("spawn prx-memoryd");
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_STREAM_SESSION_TTL_MS", "1000"),
+    ]);
 
     wait_for_http(&addr);
 
@@ -279,3 +338,2083 @@ fn http_stream_session_expiry_work() {
     let _ = child.wait();
     let _ = std::fs::remove_file(db_path);
 }
+
+/// Answers OpenAI-compatible chat completions with one distilled lesson per request, numbered so
+/// successive promotions do not look like duplicates.
+fn spawn_fake_summarizer() -> String {
+    spawn_summarizer(
+        &[
+            "Pitfall: cache warmup stalled after deploy. Cause: stale config was cached. Fix: reload config on deploy. Prevention: add a deploy smoke check.\nDecision principle (config-reload): reload config on every deploy. Trigger: shipping a deploy. Action: run the smoke check.",
+            "Pitfall: queue drain hung at shutdown. Cause: workers ignored the stop signal. Fix: poll the stop flag between jobs. Prevention: test graceful shutdown in CI.\nDecision principle (graceful-stop): workers must honour stop signals. Trigger: writing a worker loop. Action: check the flag per job.",
+        ],
+        Duration::ZERO,
+    )
+}
+
+/// A chat-completions endpoint answering with `replies` in turn, each after `delay`.
+fn spawn_summarizer(replies: &'static [&'static str], delay: Duration) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind summarizer");
+    let addr = listener.local_addr().expect("summarizer addr").to_string();
+    std::thread::spawn(move || {
+        for (served, stream) in listener.incoming().enumerate() {
+            let Ok(mut stream) = stream else { continue };
+            let mut raw = Vec::new();
+            let mut buf = [0_u8; 4096];
+            while let Ok(n) = stream.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                raw.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&raw);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|l| {
+                            l.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().to_string())
+                        })
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break;
+                    }
+                }
+            }
+            std::thread::sleep(delay);
+            let reply = replies[served % replies.len()];
+            let body = serde_json::json!({
+                "model": "fake-summarizer",
+                "choices": [{"index": 0, "message": {"role": "assistant", "content": reply}}]
+            })
+            .to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    addr
+}
+
+#[test]
+fn summarize_keeps_originals_edited_during_the_provider_call() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-summarize-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+    let summarizer = spawn_summarizer(
+        &[
+            "Deploys need a config reload and a graceful queue drain.",
+            "Condensed from the stacktrace of the failed deploy.",
+        ],
+        Duration::from_millis(800),
+    );
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_SUMMARIZE_PROVIDER", "openai-compatible"),
+        ("PRX_SUMMARIZE_BASE_URL", &format!("http://{summarizer}")),
+        ("PRX_SUMMARIZE_API_KEY", "test"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let call = |addr: &str, id: u64, name: &str, arguments: serde_json::Value| -> serde_json::Value {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": arguments}
+        })
+        .to_string();
+        serde_json::from_str(response_body(&send_http(addr, "POST", "/mcp", &body))).expect("rpc json")
+    };
+    let mut ids = Vec::new();
+    for text in ["Cache warmup stalled after the deploy", "Queue drain hung at shutdown"] {
+        let body = format!(r#"{{"text":"{text}","category":"fact","scope":"global"}}"#);
+        let stored = send_http(&addr, "POST", "/v1/memories", &body);
+        let entry: serde_json::Value = serde_json::from_str(response_body(&stored)).expect("entry json");
+        ids.push(entry["id"].as_str().expect("id").to_string());
+    }
+
+    let summarize = {
+        let addr = addr.clone();
+        std::thread::spawn(move || {
+            call(
+                &addr,
+                1,
+                "memory_summarize",
+                serde_json::json!({"scope": "global", "batch_size": 2}),
+            )
+        })
+    };
+    std::thread::sleep(Duration::from_millis(300));
+    let updated = call(
+        &addr,
+        2,
+        "memory_update",
+        serde_json::json!({"id": ids[0], "text": "Cache warmup is fixed by reloading config"}),
+    );
+    assert!(updated["error"].is_null(), "{updated}");
+    let summarized = summarize.join().expect("summarize thread");
+    let content = &summarized["result"]["structuredContent"];
+    assert_eq!(content["changed_ids"], serde_json::json!([ids[0]]), "{summarized}");
+    assert_eq!(content["summarized_ids"], serde_json::json!([ids[1]]));
+
+    let governed = call(
+        &addr,
+        3,
+        "memory_summarize",
+        serde_json::json!({"scope": "global", "batch_size": 2, "governed": true}),
+    );
+    assert_eq!(governed["error"]["code"], -32602, "{governed}");
+    assert!(
+        governed["error"]["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("log-like"))
+    );
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn ended_sessions_promote_working_memory_to_review() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-promote-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+    let summarizer = spawn_fake_summarizer();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_STREAM_SESSION_TTL_MS", "1000"),
+        ("PRX_MEMORY_SESSION_REAP_MS", "200"),
+        ("PRX_SUMMARIZE_PROVIDER", "openai-compatible"),
+        ("PRX_SUMMARIZE_BASE_URL", &format!("http://{summarizer}")),
+        ("PRX_SUMMARIZE_API_KEY", "test"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let start_session = || {
+        let start: serde_json::Value =
+            serde_json::from_str(response_body(&send_http(&addr, "POST", "/mcp/session/start", "{}")))
+                .expect("start json");
+        start["session_id"].as_str().expect("session id").to_string()
+    };
+    let note = |session: &str, id: u64, text: &str| {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": "memory_session_note", "arguments": {"text": text}}
+        })
+        .to_string();
+        let resp = send_http(&addr, "POST", &format!("/mcp/stream?session={session}"), &body);
+        assert!(resp.starts_with("HTTP/1.1 202"), "{resp}");
+    };
+    let pending = || {
+        let body =
+            r#"{"jsonrpc":"2.0","id":90,"method":"tools/call","params":{"name":"memory_review_list","arguments":{}}}"#;
+        let resp: serde_json::Value =
+            serde_json::from_str(response_body(&send_http(&addr, "POST", "/mcp", body))).expect("review json");
+        resp["result"]["structuredContent"]["total"]
+            .as_u64()
+            .unwrap_or_default()
+    };
+
+    let outside = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"memory_session_note","arguments":{"text":"x"}}}"#;
+    let outside: serde_json::Value =
+        serde_json::from_str(response_body(&send_http(&addr, "POST", "/mcp", outside))).expect("note json");
+    assert_eq!(outside["error"]["code"], -32602);
+
+    // Ungoverned facts keep the second promoted principle under the decision ratio cap.
+    for (id, text) in [
+        (5, "Deploys run from the release branch"),
+        (12, "Staging mirrors production data nightly"),
+        (13, "Workers run in three regions"),
+    ] {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": "memory_store", "arguments": {"text": text, "category": "fact", "governed": false}}
+        })
+        .to_string();
+        assert!(send_http(&addr, "POST", "/mcp", &body).starts_with("HTTP/1.1 200"));
+    }
+
+    let closed_session = start_session();
+    note(&closed_session, 2, "Cache warmup stalled after the deploy");
+    note(&closed_session, 3, "Reloading config on deploy fixed it");
+    let closed = send_http(
+        &addr,
+        "POST",
+        &format!("/mcp/session/close?session={closed_session}"),
+        "",
+    );
+    assert!(closed.starts_with("HTTP/1.1 200"), "{closed}");
+    let closed: serde_json::Value = serde_json::from_str(response_body(&closed)).expect("close json");
+    assert_eq!(closed["closed"], true);
+    assert_eq!(closed["promotion"]["created"].as_array().map(Vec::len), Some(1));
+    assert_eq!(pending(), 2);
+    let again = send_http(
+        &addr,
+        "POST",
+        &format!("/mcp/session/close?session={closed_session}"),
+        "",
+    );
+    assert!(again.starts_with("HTTP/1.1 404"));
+
+    let expiring_session = start_session();
+    note(&expiring_session, 4, "Queue drain hung at shutdown");
+    let mut promoted = false;
+    for _ in 0..40 {
+        std::thread::sleep(Duration::from_millis(100));
+        if pending() == 4 {
+            promoted = true;
+            break;
+        }
+    }
+    assert!(promoted, "expired session was not promoted");
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn drain_refuses_new_sessions_and_restores_saved_ones() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-drain-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+    let spawn = || {
+        spawn_daemon(&[
+            ("PRX_MEMORY_HTTP_ADDR", &addr),
+            ("PRX_MEMORY_DB", &db_path),
+            ("PRX_MEMORY_HTTP_TOKENS", "ops:secret-ops"),
+            ("PRX_MEMORY_HTTP_ADMIN_TOKENS", "ops"),
+        ])
+    };
+
+    let mut child = spawn();
+    wait_for_http(&addr);
+    let ops_auth = [("Authorization", "Bearer secret-ops")];
+    let start = send_http_with_headers(&addr, "POST", "/mcp/session/start", "{}", &ops_auth);
+    let start: serde_json::Value = serde_json::from_str(response_body(&start)).expect("start json");
+    let session_id = start["session_id"].as_str().expect("session id").to_string();
+    let stats = r#"{"jsonrpc":"2.0","id":51,"method":"tools/call","params":{"name":"memory_stats","arguments":{}}}"#;
+    let queued = send_http_with_headers(
+        &addr,
+        "POST",
+        &format!("/mcp/stream?session={session_id}"),
+        stats,
+        &ops_auth,
+    );
+    assert!(queued.starts_with("HTTP/1.1 202"));
+
+    let drained = send_http_with_headers(&addr, "POST", "/admin/drain", "{}", &ops_auth);
+    assert!(drained.starts_with("HTTP/1.1 200"), "{drained}");
+    let drained: serde_json::Value = serde_json::from_str(response_body(&drained)).expect("drain json");
+    assert_eq!(drained["saved"][0]["sessions"]["count"], 1);
+    assert_eq!(drained["saved"][0]["sessions"]["events"], 1);
+    assert!(send_http(&addr, "GET", "/health", "").starts_with("HTTP/1.1 503"));
+    let refused = send_http_with_headers(&addr, "POST", "/mcp/session/start", "{}", &ops_auth);
+    assert!(refused.starts_with("HTTP/1.1 503"));
+
+    let exiting = send_http_with_headers(&addr, "POST", "/admin/drain", r#"{"exit":true}"#, &ops_auth);
+    assert!(exiting.starts_with("HTTP/1.1 200"));
+    let status = child.wait().expect("daemon exit");
+    assert!(status.success());
+
+    let mut child = spawn();
+    wait_for_http(&addr);
+    assert!(send_http(&addr, "GET", "/health", "").starts_with("HTTP/1.1 200"));
+    let poll = send_http_with_headers(
+        &addr,
+        "GET",
+        &format!("/mcp/stream?session={session_id}&from=1&limit=10"),
+        "",
+        &ops_auth,
+    );
+    assert!(poll.starts_with("HTTP/1.1 200"), "{poll}");
+    let poll: serde_json::Value = serde_json::from_str(response_body(&poll)).expect("poll json");
+    assert_eq!(poll["count"], 1);
+    assert_eq!(poll["events"][0]["payload"]["id"], 51);
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn session_journal_replays_unread_events_after_a_crash() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-journal-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+    let spawn = || {
+        spawn_daemon(&[
+            ("PRX_MEMORY_HTTP_ADDR", &addr),
+            ("PRX_MEMORY_DB", &db_path),
+            ("PRX_MEMORY_SESSION_LOG", "1"),
+        ])
+    };
+
+    let mut child = spawn();
+    wait_for_http(&addr);
+    let start = send_http(&addr, "POST", "/mcp/session/start", "{}");
+    let start: serde_json::Value = serde_json::from_str(response_body(&start)).expect("start json");
+    let session_id = start["session_id"].as_str().expect("session id").to_string();
+    for id in [61, 62] {
+        let call = format!(
+            r#"{{"jsonrpc":"2.0","id":{id},"method":"tools/call","params":{{"name":"memory_stats","arguments":{{}}}}}}"#
+        );
+        let queued = send_http(&addr, "POST", &format!("/mcp/stream?session={session_id}"), &call);
+        assert!(queued.starts_with("HTTP/1.1 202"));
+    }
+    let poll_path = format!("/mcp/stream?session={session_id}&from=1&limit=10");
+    let mut first = serde_json::Value::Null;
+    for _ in 0..50 {
+        first = serde_json::from_str(response_body(&send_http(&addr, "GET", &poll_path, ""))).expect("poll json");
+        if first["count"] == 2 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert_eq!(first["count"], 2);
+    let acked = send_http(&addr, "GET", &format!("{poll_path}&ack=1"), "");
+    assert!(acked.starts_with("HTTP/1.1 200"));
+
+    // No drain: the journal alone has to carry the session across the crash.
+    let _ = child.kill();
+    let _ = child.wait();
+    let mut child = spawn();
+    wait_for_http(&addr);
+    let poll = send_http(&addr, "GET", &poll_path, "");
+    assert!(poll.starts_with("HTTP/1.1 200"), "{poll}");
+    let poll: serde_json::Value = serde_json::from_str(response_body(&poll)).expect("poll json");
+    assert_eq!(poll["count"], 1);
+    assert_eq!(poll["events"][0]["payload"]["id"], 62);
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(&db_path);
+    let _ = std::fs::remove_file(format!("{db_path}.session-events.jsonl"));
+}
+
+#[test]
+fn sessions_get_the_server_notifications_they_filter_for() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-notices-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_SCOPE_QUOTAS", r#"{"global":{"max_entries":2}}"#),
+    ]);
+    wait_for_http(&addr);
+
+    let start = |body: &str| {
+        let response = send_http(&addr, "POST", "/mcp/session/start", body);
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        let start: serde_json::Value = serde_json::from_str(response_body(&response)).expect("start json");
+        start["session_id"].as_str().expect("session id").to_string()
+    };
+    let quota_session = start(r#"{"notifications":["quota"]}"#);
+    let job_session = start(r#"{"notifications":["job"]}"#);
+    let unknown = send_http(&addr, "POST", "/mcp/session/start", r#"{"notifications":["weather"]}"#);
+    assert!(unknown.starts_with("HTTP/1.1 400"));
+
+    for (id, text) in [
+        (71, "Nightly exports run at 02:00 UTC"),
+        (72, "Staging resets every Monday"),
+        (73, "Release branches are cut on Thursdays"),
+    ] {
+        let call = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": "memory_store", "arguments": {"text": text, "category": "fact", "governed": false}}
+        });
+        send_http(&addr, "POST", "/mcp", &call.to_string());
+    }
+    let reembed = r#"{"jsonrpc":"2.0","id":74,"method":"tools/call","params":{"name":"memory_reembed","arguments":{"scope":"global","wait":true}}}"#;
+    send_http(&addr, "POST", "/mcp", reembed);
+
+    let methods = |session: &str| {
+        let poll = send_http(
+            &addr,
+            "GET",
+            &format!("/mcp/stream?session={session}&from=1&limit=10"),
+            "",
+        );
+        let poll: serde_json::Value = serde_json::from_str(response_body(&poll)).expect("poll json");
+        poll["events"]
+            .as_array()
+            .expect("events")
+            .iter()
+            .map(|event| {
+                let payload = &event["payload"];
+                let detail = payload["params"]["status"]
+                    .as_str()
+                    .or_else(|| payload["params"]["kind"].as_str())
+                    .unwrap_or_default();
+                format!("{} {detail}", payload["method"].as_str().unwrap_or_default())
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        methods(&quota_session),
+        vec![
+            "notifications/prx/quota quota_warning",
+            "notifications/prx/quota quota_exceeded"
+        ]
+    );
+    assert_eq!(methods(&job_session), vec!["notifications/prx/job completed"]);
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[cfg(unix)]
+#[test]
+fn sigterm_flushes_and_leaves_a_clean_shutdown_marker() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-sigterm-{now}.json"))
+        .display()
+        .to_string();
+    let marker = format!("{db_path}.shutdown.json");
+    let addr = reserve_addr();
+    let spawn = || spawn_daemon(&[("PRX_MEMORY_HTTP_ADDR", &addr), ("PRX_MEMORY_DB", &db_path)]);
+
+    let mut child = spawn();
+    wait_for_http(&addr);
+    let store = r#"{"jsonrpc":"2.0","id":81,"method":"tools/call","params":{"name":"memory_store","arguments":{"text":"Deploys freeze on Fridays","category":"fact","governed":false}}}"#;
+    assert!(send_http(&addr, "POST", "/mcp", store).starts_with("HTTP/1.1 200"));
+    let start = send_http(&addr, "POST", "/mcp/session/start", "{}");
+    let start: serde_json::Value = serde_json::from_str(response_body(&start)).expect("start json");
+    let session_id = start["session_id"].as_str().expect("session id").to_string();
+
+    let killed = Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .expect("send SIGTERM");
+    assert!(killed.success());
+    let status = child.wait().expect("daemon exit");
+    assert!(status.success(), "{status}");
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&marker).expect("shutdown marker")).expect("marker json");
+    assert_eq!(written["reason"], "SIGTERM");
+
+    let mut child = spawn();
+    wait_for_http(&addr);
+    assert!(!std::path::Path::new(&marker).exists());
+    let poll = send_http(&addr, "GET", &format!("/mcp/stream?session={session_id}&from=1"), "");
+    assert!(poll.starts_with("HTTP/1.1 200"), "{poll}");
+    let stats = r#"{"jsonrpc":"2.0","id":82,"method":"tools/call","params":{"name":"memory_stats","arguments":{}}}"#;
+    let stats = send_http(&addr, "POST", "/mcp", stats);
+    let stats: serde_json::Value = serde_json::from_str(response_body(&stats)).expect("stats json");
+    assert_eq!(stats["result"]["structuredContent"]["count"], 1);
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn http_keep_alive_serves_pipelined_and_chunked_requests() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-keepalive-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[("PRX_MEMORY_HTTP_ADDR", &addr), ("PRX_MEMORY_DB", &db_path)]);
+
+    wait_for_http(&addr);
+
+    let init_body = r#"{"jsonrpc":"2.0","id":21,"method":"initialize","params":{}}"#;
+    let (head, tail) = init_body.split_at(10);
+    let list_body = r#"{"jsonrpc":"2.0","id":22,"method":"tools/list","params":{}}"#;
+    let pipelined = format!(
+        "GET /health HTTP/1.1\r\nHost: {addr}\r\n\r\n\
+         POST /mcp HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n\
+         {:x}\r\n{head}\r\n{:x};ext=1\r\n{tail}\r\n0\r\n\r\n\
+         POST /mcp HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{list_body}",
+        head.len(),
+        tail.len(),
+        list_body.len()
+    );
+    let mut stream = TcpStream::connect(&addr).expect("connect http");
+    stream.write_all(pipelined.as_bytes()).expect("write requests");
+    stream.flush().expect("flush");
+    let mut buf = String::new();
+    stream.read_to_string(&mut buf).expect("read responses");
+
+    assert_eq!(buf.matches("HTTP/1.1 200").count(), 3);
+    assert_eq!(buf.matches("Connection: keep-alive").count(), 2);
+    assert!(buf.contains("Connection: close"));
+    assert!(buf.contains("\"status\":\"ok\""));
+    assert!(buf.contains("\"id\":21"));
+    assert!(buf.contains("\"serverInfo\""));
+    assert!(buf.contains("\"id\":22"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn http_connections_past_the_limit_get_503() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-maxconn-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_HTTP_MAX_CONNECTIONS", "1"),
+    ]);
+
+    wait_for_http(&addr);
+    // The previous connection's slot frees once its thread exits, so retry until one is admitted.
+    let get_admitted = |addr: &str, path: &str| {
+        (0..80).find_map(|_| {
+            let response = send_http(addr, "GET", path, "");
+            if response.starts_with("HTTP/1.1 200") {
+                return Some(response);
+            }
+            std::thread::sleep(Duration::from_millis(25));
+            None
+        })
+    };
+    assert!(get_admitted(&addr, "/health").is_some());
+
+    let held = (0..80).find_map(|_| {
+        let mut stream = TcpStream::connect(&addr).expect("connect http");
+        stream
+            .write_all(format!("GET /health HTTP/1.1\r\nHost: {addr}\r\n\r\n").as_bytes())
+            .expect("write request");
+        let mut buf = [0u8; 1024];
+        let read = stream.read(&mut buf).expect("read response");
+        let first = String::from_utf8_lossy(buf.get(..read).unwrap_or_default()).to_string();
+        if first.starts_with("HTTP/1.1 200") && first.contains("Connection: keep-alive") {
+            return Some(stream);
+        }
+        std::thread::sleep(Duration::from_millis(25));
+        None
+    });
+    let held = held.expect("keep-alive connection admitted");
+
+    let rejected = send_http(&addr, "GET", "/health", "");
+    assert!(rejected.starts_with("HTTP/1.1 503"), "{rejected}");
+    assert!(rejected.contains("Connection: close"));
+    assert!(response_body(&rejected).contains("\"error\":\"overloaded\""));
+
+    drop(held);
+    let metrics = get_admitted(&addr, "/metrics").expect("admitted after the held connection closes");
+    let rejections = metrics
+        .lines()
+        .find_map(|line| line.strip_prefix("prx_memory_http_connections_rejected_total "))
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .expect("rejection counter");
+    assert!(rejections >= 1);
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+/// Sends a POST with the given framing headers followed by a pipelined `GET /health`, and checks
+/// that only a 400 comes back before the connection closes.
+fn assert_ambiguous_framing_rejected(name: &str, framing: &str) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-{name}-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[("PRX_MEMORY_HTTP_ADDR", &addr), ("PRX_MEMORY_DB", &db_path)]);
+
+    wait_for_http(&addr);
+    let body = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list","params":{}}"#;
+    let request = format!(
+        "POST /mcp HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\n{framing}\r\n{body}\
+         GET /health HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+    );
+    let mut stream = TcpStream::connect(&addr).expect("connect http");
+    stream.write_all(request.as_bytes()).expect("write request");
+    stream.flush().expect("flush");
+    let mut buf = String::new();
+    stream.read_to_string(&mut buf).expect("read response");
+
+    assert!(buf.starts_with("HTTP/1.1 400"), "{buf}");
+    assert!(buf.contains("Connection: close"));
+    assert!(response_body(&buf).contains("\"error\":\"invalid_request\""));
+    assert_eq!(buf.matches("HTTP/1.1 ").count(), 1, "{buf}");
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn http_rejects_malformed_content_length() {
+    assert_ambiguous_framing_rejected("bad-length", "Content-Length: 12abc\r\n");
+}
+
+#[test]
+fn http_rejects_duplicate_content_length() {
+    assert_ambiguous_framing_rejected("dup-length", "Content-Length: 0\r\nContent-Length: 59\r\n");
+}
+
+#[test]
+fn http_rejects_content_length_with_chunked_encoding() {
+    assert_ambiguous_framing_rejected(
+        "length-and-chunked",
+        "Content-Length: 59\r\nTransfer-Encoding: chunked\r\n",
+    );
+}
+
+#[test]
+fn http_tokens_reject_unauthenticated_mcp_calls() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-auth-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_HTTP_TOKENS", "ci:secret-ci, secret-bare"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let health = send_http(&addr, "GET", "/health", "");
+    assert!(health.starts_with("HTTP/1.1 200"));
+
+    let init_body = r#"{"jsonrpc":"2.0","id":31,"method":"initialize","params":{}}"#;
+    let anonymous = send_http(&addr, "POST", "/mcp", init_body);
+    assert!(anonymous.starts_with("HTTP/1.1 401"));
+    assert!(response_body(&anonymous).contains("\"unauthorized\""));
+
+    let wrong = send_http_with_headers(
+        &addr,
+        "POST",
+        "/mcp",
+        init_body,
+        &[("Authorization", "Bearer secret-wrong")],
+    );
+    assert!(wrong.starts_with("HTTP/1.1 401"));
+
+    let labelled = send_http_with_headers(
+        &addr,
+        "POST",
+        "/mcp",
+        init_body,
+        &[("Authorization", "Bearer secret-ci")],
+    );
+    assert!(labelled.starts_with("HTTP/1.1 200"));
+    assert!(response_body(&labelled).contains("\"serverInfo\""));
+    let bare = send_http_with_headers(
+        &addr,
+        "POST",
+        "/mcp/session/start",
+        "{}",
+        &[("Authorization", "Bearer secret-bare")],
+    );
+    assert!(bare.starts_with("HTTP/1.1 200"));
+
+    let metrics = send_http(&addr, "GET", "/metrics", "");
+    let metrics_body = response_body(&metrics);
+    assert!(metrics_body.contains("prx_memory_http_requests_total{token=\"ci\"} 1"));
+    assert!(metrics_body.contains("prx_memory_http_requests_total{token=\"token-2\"} 1"));
+    assert!(metrics_body.contains("prx_memory_http_auth_failures_total 2"));
+    assert!(!metrics_body.contains("secret-"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn admin_api_runs_maintenance_for_admin_tokens() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let data_dir = std::env::temp_dir().join(format!("prx-memory-http-admin-{now}"));
+    std::fs::create_dir_all(&data_dir).expect("create data dir");
+    let db_path = data_dir.join("memory-db.json").display().to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_DATA_DIR", &data_dir.display().to_string()),
+        ("PRX_MEMORY_HTTP_TOKENS", "ops:secret-ops,agent:secret-agent"),
+        ("PRX_MEMORY_HTTP_ADMIN_TOKENS", "ops"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let store_body = r#"{"jsonrpc":"2.0","id":41,"method":"tools/call","params":{"name":"memory_store","arguments":{"text":"Pitfall: admin backups must include embeddings. Fix: export with include_embeddings.","category":"fact","scope":"global"}}}"#;
+    let agent_auth = [("Authorization", "Bearer secret-agent")];
+    let stored = send_http_with_headers(&addr, "POST", "/mcp", store_body, &agent_auth);
+    assert!(stored.starts_with("HTTP/1.1 200"));
+
+    let anonymous = send_http(&addr, "GET", "/admin/jobs", "");
+    assert!(anonymous.starts_with("HTTP/1.1 401"));
+    let agent = send_http_with_headers(&addr, "GET", "/admin/jobs", "", &agent_auth);
+    assert!(agent.starts_with("HTTP/1.1 403"));
+
+    let ops_auth = [("Authorization", "Bearer secret-ops")];
+    let jobs = send_http_with_headers(&addr, "GET", "/admin/jobs", "", &ops_auth);
+    assert!(jobs.starts_with("HTTP/1.1 200"));
+    assert!(response_body(&jobs).contains("\"jobs\""));
+
+    let compact = send_http_with_headers(&addr, "POST", "/admin/compact", "", &ops_auth);
+    assert!(compact.starts_with("HTTP/1.1 200"));
+    assert!(response_body(&compact).contains("\"dry_run\":true"));
+
+    let backup = send_http_with_headers(&addr, "POST", "/admin/backup", "{}", &ops_auth);
+    assert!(backup.starts_with("HTTP/1.1 200"));
+    let backup_json: serde_json::Value = serde_json::from_str(response_body(&backup)).expect("backup json");
+    assert_eq!(backup_json["count"], 1);
+    let backup_path = backup_json["output_path"].as_str().expect("backup path");
+    assert!(backup_path.starts_with(&data_dir.display().to_string()));
+    let backup_file = std::fs::read_to_string(backup_path).expect("read backup");
+    assert!(backup_file.contains("admin backups must include embeddings"));
+
+    let bad = send_http_with_headers(&addr, "POST", "/admin/reembed", "[1]", &ops_auth);
+    assert!(bad.starts_with("HTTP/1.1 400"));
+    let wrong_method = send_http_with_headers(&addr, "GET", "/admin/compact", "", &ops_auth);
+    assert!(wrong_method.starts_with("HTTP/1.1 405"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn https_serves_mcp_over_tls() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-https-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let cert_path = format!("{fixtures}/tls-test-cert.pem");
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_TLS_CERT", &cert_path),
+        ("PRX_MEMORY_TLS_KEY", &format!("{fixtures}/tls-test-key.pem")),
+    ]);
+
+    wait_for_http(&addr);
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots
+        .add(CertificateDer::from_pem_file(&cert_path).expect("read test cert"))
+        .expect("trust test cert");
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("tls versions")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let conn = rustls::ClientConnection::new(Arc::new(config), "localhost".try_into().expect("server name"))
+        .expect("tls client");
+    let mut stream = rustls::StreamOwned::new(conn, TcpStream::connect(&addr).expect("connect https"));
+
+    let init_body = r#"{"jsonrpc":"2.0","id":41,"method":"initialize","params":{}}"#;
+    let request = format!(
+        "POST /mcp HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{init_body}",
+        init_body.len()
+    );
+    stream.write_all(request.as_bytes()).expect("write request");
+    stream.flush().expect("flush");
+    let mut buf = String::new();
+    stream.read_to_string(&mut buf).expect("read response");
+    assert!(buf.starts_with("HTTP/1.1 200"));
+    assert!(response_body(&buf).contains("\"serverInfo\""));
+
+    let mut plain = TcpStream::connect(&addr).expect("connect plain");
+    plain
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .expect("write plain request");
+    let mut raw = Vec::new();
+    let _ = plain.read_to_end(&mut raw);
+    assert!(!raw.starts_with(b"HTTP/1.1 200"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn http_stream_notifies_resource_subscribers() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-resources-{now}.json"))
+        .display()
+        .to_string();
+    let skill_dir = std::env::temp_dir().join(format!("prx-memory-skill-dir-{now}"));
+    std::fs::create_dir_all(&skill_dir).expect("create skill dir");
+    std::fs::write(skill_dir.join("SKILL.md"), "# Skill v1\n").expect("write skill");
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_SKILL_DIR", &skill_dir.display().to_string()),
+        ("PRX_MEMORY_RESOURCE_POLL_MS", "0"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let init_body = r#"{"jsonrpc":"2.0","id":51,"method":"initialize","params":{}}"#;
+    let init = send_http(&addr, "POST", "/mcp", init_body);
+    assert!(response_body(&init).contains("\"subscribe\":true"));
+
+    let start_resp = send_http(&addr, "POST", "/mcp/session/start", "{}");
+    let start_json: serde_json::Value = serde_json::from_str(response_body(&start_resp)).expect("start json");
+    let session_id = start_json
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .expect("session id")
+        .to_string();
+
+    let subscribe_body = r#"{"jsonrpc":"2.0","id":52,"method":"resources/subscribe","params":{"uri":"prx://skills/prx-memory-governance/SKILL.md"}}"#;
+    let subscribe = send_http(
+        &addr,
+        "POST",
+        &format!("/mcp/stream?session={session_id}"),
+        subscribe_body,
+    );
+    assert!(subscribe.starts_with("HTTP/1.1 202"));
+
+    std::fs::write(skill_dir.join("SKILL.md"), "# Skill v2\n").expect("rewrite skill");
+    std::fs::create_dir_all(skill_dir.join("references")).expect("create references");
+    std::fs::write(skill_dir.join("references/extra.md"), "extra\n").expect("write extra");
+
+    let poll = send_http(
+        &addr,
+        "GET",
+        &format!("/mcp/stream?session={session_id}&from=1&limit=10"),
+        "",
+    );
+    let poll_json: serde_json::Value = serde_json::from_str(response_body(&poll)).expect("poll json");
+    let methods = poll_json
+        .get("events")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|e| {
+            e.pointer("/payload/method")
+                .and_then(|m| m.as_str())
+                .map(str::to_string)
+        })
+        .collect::<Vec<_>>();
+    assert!(methods.contains(&"notifications/resources/list_changed".to_string()));
+    assert!(methods.contains(&"notifications/resources/updated".to_string()));
+    assert!(response_body(&poll).contains("\"uri\":\"prx://skills/prx-memory-governance/SKILL.md\""));
+
+    let list_body = r#"{"jsonrpc":"2.0","id":53,"method":"resources/list","params":{}}"#;
+    let list = send_http(&addr, "POST", "/mcp", list_body);
+    assert!(response_body(&list).contains("prx://skills/prx-memory-governance/references/extra.md"));
+    let read_body = r#"{"jsonrpc":"2.0","id":54,"method":"resources/read","params":{"uri":"prx://skills/prx-memory-governance/SKILL.md"}}"#;
+    let read = send_http(&addr, "POST", "/mcp", read_body);
+    assert!(response_body(&read).contains("Skill v2"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+    let _ = std::fs::remove_dir_all(skill_dir);
+}
+
+#[test]
+fn http_experiment_arms_tag_recalls_and_report_metrics() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-experiment-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+    let experiment = r#"{"name":"cjk-rollout","arms":[{"id":"control","weight":0},{"id":"cjk","weight":1,"config":{"tokenizer":"cjk-ngram"}}]}"#;
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_EXPERIMENT", experiment),
+    ]);
+
+    wait_for_http(&addr);
+
+    let call = |id: u64, name: &str, arguments: serde_json::Value| -> serde_json::Value {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": arguments}
+        });
+        let resp = send_http(&addr, "POST", "/mcp", &body.to_string());
+        let json: serde_json::Value = serde_json::from_str(response_body(&resp)).expect("tool json");
+        json.pointer("/result/structuredContent").cloned().unwrap_or_default()
+    };
+
+    let stored = call(
+        1,
+        "memory_store",
+        serde_json::json!({"text": "数据库连接池超时需要调大", "category": "fact", "scope": "global"}),
+    );
+    let memory_id = stored
+        .get("id")
+        .and_then(|v| v.as_str())
+        .expect("stored id")
+        .to_string();
+    let recalled = call(2, "memory_recall", serde_json::json!({"query": "连接池的超时"}));
+    assert_eq!(
+        recalled.pointer("/experiment/arm").and_then(|v| v.as_str()),
+        Some("cjk")
+    );
+    assert_eq!(recalled.get("count").and_then(|v| v.as_u64()), Some(1));
+    call(
+        3,
+        "memory_feedback",
+        serde_json::json!({"query": "连接池的超时", "useful": [memory_id]}),
+    );
+
+    let summary = send_http(&addr, "GET", "/metrics/summary", "");
+    let summary_json: serde_json::Value = serde_json::from_str(response_body(&summary)).expect("summary");
+    let arms = summary_json
+        .pointer("/experiment/arms")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let arm = |id: &str| {
+        arms.iter()
+            .find(|a| a.get("id").and_then(|v| v.as_str()) == Some(id))
+            .cloned()
+            .unwrap_or_default()
+    };
+    assert_eq!(arm("cjk").get("recalls").and_then(|v| v.as_u64()), Some(1));
+    assert_eq!(arm("cjk").pointer("/feedback/useful").and_then(|v| v.as_u64()), Some(1));
+    assert_eq!(arm("control").get("recalls").and_then(|v| v.as_u64()), Some(0));
+
+    let metrics = send_http(&addr, "GET", "/metrics", "");
+    assert!(response_body(&metrics).contains(
+        "prx_memory_experiment_recall_outcomes_total{experiment=\"cjk-rollout\",arm=\"cjk\",outcome=\"hit\"} 1"
+    ));
+
+    let _ = child.kill();
+    let _ = child.wait();
+    for suffix in ["", ".feedback.json", ".access.json", ".jobs.json"] {
+        let _ = std::fs::remove_file(format!("{db_path}{suffix}"));
+    }
+}
+
+#[test]
+fn http_exports_function_calling_tool_schemas() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-schemas-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[("PRX_MEMORY_HTTP_ADDR", &addr), ("PRX_MEMORY_DB", &db_path)]);
+
+    wait_for_http(&addr);
+
+    let openai = send_http(&addr, "GET", "/tools/schemas", "");
+    assert!(openai.starts_with("HTTP/1.1 200"));
+    let body: serde_json::Value = serde_json::from_str(response_body(&openai)).expect("json");
+    assert_eq!(body["format"], "openai");
+    let tools = body["tools"].as_array().expect("tools");
+    assert_eq!(body["count"], tools.len());
+    assert!(tools.iter().all(|t| t["type"] == "function"));
+    let store = tools
+        .iter()
+        .find(|t| t["function"]["name"] == "memory_store")
+        .expect("memory_store exported");
+    assert_eq!(store["function"]["parameters"]["required"], serde_json::json!(["text"]));
+    assert!(!tools.iter().any(|t| t["function"]["name"] == "memory_tool_schemas"));
+
+    let anthropic = send_http(
+        &addr,
+        "GET",
+        "/tools/schemas?format=anthropic&tools=memory_recall,memory_store",
+        "",
+    );
+    let body: serde_json::Value = serde_json::from_str(response_body(&anthropic)).expect("json");
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["tools"][0]["name"], "memory_store");
+    assert!(body["tools"][1]["input_schema"]["properties"]["query"].is_object());
+
+    let rejected = send_http(&addr, "GET", "/tools/schemas?format=gemini", "");
+    assert!(rejected.starts_with("HTTP/1.1 400"));
+
+    let via_tool = send_http(
+        &addr,
+        "POST",
+        "/mcp",
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"memory_tool_schemas","arguments":{"format":"anthropic","tools":["memory_list"]}}}"#,
+    );
+    let body: serde_json::Value = serde_json::from_str(response_body(&via_tool)).expect("json");
+    assert_eq!(body["result"]["structuredContent"]["tools"][0]["name"], "memory_list");
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn rest_api_stores_recalls_lists_and_deletes_memories() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-rest-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_HTTP_TOKENS", "zapier:secret-rest"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let anonymous = send_http(&addr, "GET", "/v1/memories", "");
+    assert!(anonymous.starts_with("HTTP/1.1 401"));
+
+    let rest_auth = [("Authorization", "Bearer secret-rest")];
+    let created = send_http_with_headers(
+        &addr,
+        "POST",
+        "/v1/memories",
+        r#"{"text":"Invoices sync from Stripe every night","category":"fact","scope":"global"}"#,
+        &rest_auth,
+    );
+    assert!(created.starts_with("HTTP/1.1 201"), "{created}");
+    let entry: serde_json::Value = serde_json::from_str(response_body(&created)).expect("entry json");
+    let id = entry["id"].as_str().expect("id").to_string();
+
+    let recalled = send_http_with_headers(
+        &addr,
+        "GET",
+        "/v1/memories?query=stripe+invoices%20sync&limit=3",
+        "",
+        &rest_auth,
+    );
+    assert!(recalled.starts_with("HTTP/1.1 200"));
+    let recalled: serde_json::Value = serde_json::from_str(response_body(&recalled)).expect("recall json");
+    assert_eq!(recalled["items"][0]["entry"]["id"], id.as_str());
+
+    let listed = send_http_with_headers(&addr, "GET", "/v1/memories?scope=global", "", &rest_auth);
+    let listed: serde_json::Value = serde_json::from_str(response_body(&listed)).expect("list json");
+    assert_eq!(listed["count"], 1);
+
+    let bad_limit = send_http_with_headers(&addr, "GET", "/v1/memories?limit=ten", "", &rest_auth);
+    assert!(bad_limit.starts_with("HTTP/1.1 400"));
+    let missing_text = send_http_with_headers(&addr, "POST", "/v1/memories", "{}", &rest_auth);
+    assert!(missing_text.starts_with("HTTP/1.1 400"));
+
+    let path = format!("/v1/memories/{id}");
+    let deleted = send_http_with_headers(&addr, "DELETE", &path, "", &rest_auth);
+    assert!(deleted.starts_with("HTTP/1.1 200"));
+    assert!(response_body(&deleted).contains("\"deleted\":true"));
+    let gone = send_http_with_headers(&addr, "DELETE", &path, "", &rest_auth);
+    assert!(gone.starts_with("HTTP/1.1 404"));
+    let wrong_method = send_http_with_headers(&addr, "PUT", "/v1/memories", "", &rest_auth);
+    assert!(wrong_method.starts_with("HTTP/1.1 405"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn change_feed_pages_store_update_and_delete_events() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-changes-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_CHANGE_LOG", "1"),
+        ("PRX_MEMORY_HTTP_TOKENS", "ops:secret-ops,agent:secret-agent"),
+        ("PRX_MEMORY_HTTP_ADMIN_TOKENS", "ops"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let agent_auth = [("Authorization", "Bearer secret-agent")];
+    let created = send_http_with_headers(
+        &addr,
+        "POST",
+        "/v1/memories",
+        r#"{"text":"Nightly backups run at 02:00 UTC","category":"fact","scope":"global"}"#,
+        &agent_auth,
+    );
+    let entry: serde_json::Value = serde_json::from_str(response_body(&created)).expect("entry json");
+    let first_id = entry["id"].as_str().expect("id").to_string();
+    let update_body = format!(
+        r#"{{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{{"name":"memory_update","arguments":{{"id":"{first_id}","text":"Nightly backups run at 03:00 UTC"}}}}}}"#
+    );
+    let updated = send_http_with_headers(&addr, "POST", "/mcp", &update_body, &agent_auth);
+    let updated: serde_json::Value = serde_json::from_str(response_body(&updated)).expect("update json");
+    let second_id = updated["result"]["structuredContent"]["entry"]["id"]
+        .as_str()
+        .expect("updated id")
+        .to_string();
+    let deleted = send_http_with_headers(&addr, "DELETE", &format!("/v1/memories/{second_id}"), "", &agent_auth);
+    assert!(deleted.starts_with("HTTP/1.1 200"));
+
+    let agent = send_http_with_headers(&addr, "GET", "/changes", "", &agent_auth);
+    assert!(agent.starts_with("HTTP/1.1 403"));
+
+    let ops_auth = [("Authorization", "Bearer secret-ops")];
+    let page = send_http_with_headers(&addr, "GET", "/changes?from=1&limit=2", "", &ops_auth);
+    assert!(page.starts_with("HTTP/1.1 200"));
+    let page: serde_json::Value = serde_json::from_str(response_body(&page)).expect("page json");
+    assert_eq!(page["latest_seq"], 3);
+    assert_eq!(page["next_from"], 3);
+    assert_eq!(page["events"][0]["op"], "store");
+    assert_eq!(page["events"][0]["entry"]["text"], entry["text"]);
+    assert_eq!(page["events"][1]["op"], "update");
+    assert_eq!(page["events"][1]["previous_id"], first_id.as_str());
+    assert_eq!(page["events"][1]["id"], second_id.as_str());
+
+    let rest = send_http_with_headers(&addr, "GET", "/changes?from=3", "", &ops_auth);
+    let rest: serde_json::Value = serde_json::from_str(response_body(&rest)).expect("page json");
+    assert_eq!(rest["events"][0]["op"], "delete");
+    assert_eq!(rest["events"][0]["id"], second_id.as_str());
+    assert_eq!(rest["next_from"], 4);
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(&db_path);
+    let _ = std::fs::remove_file(format!("{db_path}.changes.jsonl"));
+}
+
+#[test]
+fn follower_mirrors_leader_and_refuses_writes() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let base = std::env::temp_dir().join(format!("prx-memory-http-follow-{now}"));
+    let leader_db = base.with_extension("leader.json").display().to_string();
+    let follower_db = base.with_extension("follower.json").display().to_string();
+    let leader_addr = reserve_addr();
+    let follower_addr = reserve_addr();
+
+    let mut leader = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &leader_addr),
+        ("PRX_MEMORY_DB", &leader_db),
+        ("PRX_MEMORY_CHANGE_LOG", "1"),
+    ]);
+    wait_for_http(&leader_addr);
+    let created = send_http(
+        &leader_addr,
+        "POST",
+        "/v1/memories",
+        r#"{"text":"Staging deploys need the VPN","category":"fact","scope":"global"}"#,
+    );
+    let entry: serde_json::Value = serde_json::from_str(response_body(&created)).expect("entry json");
+    let id = entry["id"].as_str().expect("id").to_string();
+
+    let mut follower = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &follower_addr),
+        ("PRX_MEMORY_DB", &follower_db),
+        ("PRX_MEMORY_FOLLOW_URL", &format!("http://{leader_addr}")),
+        ("PRX_MEMORY_FOLLOW_POLL_MS", "50"),
+    ]);
+    wait_for_http(&follower_addr);
+
+    let recall_ids = || {
+        let recalled = send_http(&follower_addr, "GET", "/v1/memories?query=staging+vpn", "");
+        let recalled: serde_json::Value = serde_json::from_str(response_body(&recalled)).expect("recall json");
+        recalled["items"]
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item["entry"]["id"].as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    let wait_until = |done: &dyn Fn() -> bool| {
+        for _ in 0..100 {
+            if done() {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        false
+    };
+    assert!(
+        wait_until(&|| recall_ids() == vec![id.clone()]),
+        "follower never caught up"
+    );
+
+    let refused = send_http(
+        &follower_addr,
+        "POST",
+        "/v1/memories",
+        r#"{"text":"Writes belong on the leader","category":"fact","scope":"global"}"#,
+    );
+    assert!(refused.starts_with("HTTP/1.1 403"));
+    assert!(response_body(&refused).contains("read_only_follower"));
+
+    let follower_status = || {
+        let stats = send_http(
+            &follower_addr,
+            "POST",
+            "/mcp",
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"memory_stats","arguments":{}}}"#,
+        );
+        let stats: serde_json::Value = serde_json::from_str(response_body(&stats)).expect("stats json");
+        stats["result"]["structuredContent"]["follower"].clone()
+    };
+    assert_eq!(follower_status()["leader"], format!("http://{leader_addr}"));
+    assert!(wait_until(&|| follower_status()["lag"] == 0));
+
+    let deleted = send_http(&leader_addr, "DELETE", &format!("/v1/memories/{id}"), "");
+    assert!(deleted.starts_with("HTTP/1.1 200"));
+    assert!(
+        wait_until(&|| recall_ids().is_empty()),
+        "delete never reached the follower"
+    );
+
+    let _ = follower.kill();
+    let _ = follower.wait();
+    let _ = leader.kill();
+    let _ = leader.wait();
+    for path in [
+        leader_db.clone(),
+        format!("{leader_db}.changes.jsonl"),
+        follower_db.clone(),
+        format!("{follower_db}.follower.json"),
+    ] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[test]
+fn team_sync_pushes_and_pulls_governed_entries() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let base = std::env::temp_dir().join(format!("prx-memory-http-sync-{now}"));
+    let team_db = base.with_extension("team.json").display().to_string();
+    let local_db = base.with_extension("local.json").display().to_string();
+    let team_addr = reserve_addr();
+    let local_addr = reserve_addr();
+
+    let mut team = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &team_addr),
+        ("PRX_MEMORY_DB", &team_db),
+        ("PRX_MEMORY_CHANGE_LOG", "1"),
+    ]);
+    let mut local = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &local_addr),
+        ("PRX_MEMORY_DB", &local_db),
+        ("PRX_MEMORY_CHANGE_LOG", "1"),
+        ("PRX_MEMORY_SYNC_URL", &format!("http://{team_addr}")),
+        ("PRX_MEMORY_SYNC_SCOPES", "global=both"),
+    ]);
+    wait_for_http(&team_addr);
+    wait_for_http(&local_addr);
+
+    let store = |addr: &str, text: &str, category: &str| {
+        let body = format!(r#"{{"text":"{text}","category":"{category}","scope":"global"}}"#);
+        let created = send_http(addr, "POST", "/v1/memories", &body);
+        let entry: serde_json::Value = serde_json::from_str(response_body(&created)).expect("entry json");
+        entry["id"].as_str().expect("id").to_string()
+    };
+    let recall_ids = |addr: &str, query: &str| {
+        let recalled = send_http(addr, "GET", &format!("/v1/memories?query={query}"), "");
+        let recalled: serde_json::Value = serde_json::from_str(response_body(&recalled)).expect("recall json");
+        recalled["items"]
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item["entry"]["id"].as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    let sync = |id: u64| {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":{id},"method":"tools/call","params":{{"name":"memory_sync","arguments":{{}}}}}}"#
+        );
+        let response = send_http(&local_addr, "POST", "/mcp", &body);
+        let parsed: serde_json::Value = serde_json::from_str(response_body(&response)).expect("sync json");
+        parsed["result"]["structuredContent"].clone()
+    };
+
+    let local_id = store(&local_addr, "Staging deploys need the VPN", "other");
+    let team_id = store(&team_addr, "Release branches are cut on monday", "other");
+    // A fact outside the Pitfall/Cause/Fix/Prevention template breaks the governance policy and stays local.
+    store(&local_addr, "Lunch order is pizza", "fact");
+
+    let report = sync(1);
+    assert_eq!(
+        (report["pushed"].as_u64(), report["pulled"].as_u64()),
+        (Some(1), Some(1)),
+        "{report}"
+    );
+    assert_eq!(recall_ids(&team_addr, "staging+vpn"), vec![local_id.clone()]);
+    assert_eq!(recall_ids(&local_addr, "release+branches"), vec![team_id.clone()]);
+    assert!(recall_ids(&team_addr, "lunch+pizza").is_empty());
+
+    // Each side's copy of the other's entry is not sent back.
+    let report = sync(2);
+    assert_eq!(
+        (report["pushed"].as_u64(), report["pulled"].as_u64()),
+        (Some(0), Some(0)),
+        "{report}"
+    );
+
+    // Both sides rewrite the team's entry; the later rewrite wins on both.
+    let update = |addr: &str, text: &str| {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":9,"method":"tools/call","params":{{"name":"memory_update","arguments":{{"id":"{team_id}","text":"{text}"}}}}}}"#
+        );
+        assert!(send_http(addr, "POST", "/mcp", &body).starts_with("HTTP/1.1 200"));
+    };
+    update(&local_addr, "Release branches are cut on tuesday");
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    update(&team_addr, "Release branches are cut on wednesday");
+    let report = sync(3);
+    assert_eq!(report["conflicts"][0]["id"], team_id.as_str(), "{report}");
+    assert_eq!(report["conflicts"][0]["winner"], "remote");
+    let texts = |addr: &str| {
+        let listed = send_http(addr, "GET", "/v1/memories?query=release+branches", "");
+        let listed: serde_json::Value = serde_json::from_str(response_body(&listed)).expect("recall json");
+        listed["items"]
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item["entry"]["text"].as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    assert_eq!(texts(&local_addr), ["release branches are cut on wednesday"]);
+    assert_eq!(texts(&team_addr), ["release branches are cut on wednesday"]);
+
+    let deleted = send_http(&team_addr, "DELETE", &format!("/v1/memories/{local_id}"), "");
+    assert!(deleted.starts_with("HTTP/1.1 200"));
+    let report = sync(4);
+    assert_eq!(report["pulled"].as_u64(), Some(1), "{report}");
+    assert!(recall_ids(&local_addr, "staging+vpn").is_empty());
+
+    let _ = local.kill();
+    let _ = local.wait();
+    let _ = team.kill();
+    let _ = team.wait();
+    for path in [
+        team_db.clone(),
+        format!("{team_db}.changes.jsonl"),
+        local_db.clone(),
+        format!("{local_db}.changes.jsonl"),
+        format!("{local_db}.sync.json"),
+    ] {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[test]
+fn tenants_get_isolated_stores_and_labelled_metrics() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let data_dir = std::env::temp_dir().join(format!("prx-memory-http-tenants-{now}"));
+    let tenants_dir = data_dir.join("tenants");
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &data_dir.join("memory-db.json").display().to_string()),
+        ("PRX_MEMORY_HTTP_TOKENS", "ops:secret-ops,acme:secret-acme"),
+        ("PRX_MEMORY_TENANTS_DIR", &tenants_dir.display().to_string()),
+        ("PRX_MEMORY_TENANT_TOKENS", "acme:acme"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let store = |token: &str, tenant: &str, text: &str| {
+        let body = format!(r#"{{"text":"{text}","category":"fact","scope":"global"}}"#);
+        send_http_with_headers(
+            &addr,
+            "POST",
+            "/v1/memories",
+            &body,
+            &[("Authorization", &format!("Bearer {token}")), ("X-Prx-Tenant", tenant)],
+        )
+    };
+    assert!(store("secret-acme", "", "Acme deploys on Fridays").starts_with("HTTP/1.1 201"));
+    assert!(store("secret-ops", "globex", "Globex deploys on Mondays").starts_with("HTTP/1.1 201"));
+    assert!(store("secret-ops", "", "Root store keeps shared runbooks").starts_with("HTTP/1.1 201"));
+
+    let count = |token: &str, tenant: &str| {
+        let listed = send_http_with_headers(
+            &addr,
+            "GET",
+            "/v1/memories",
+            "",
+            &[("Authorization", &format!("Bearer {token}")), ("X-Prx-Tenant", tenant)],
+        );
+        let listed: serde_json::Value = serde_json::from_str(response_body(&listed)).expect("list json");
+        listed["items"]
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item["text"].as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    assert_eq!(count("secret-acme", ""), vec!["acme deploys on fridays"]);
+    assert_eq!(count("secret-ops", "acme"), vec!["acme deploys on fridays"]);
+    assert_eq!(count("secret-ops", "globex"), vec!["globex deploys on mondays"]);
+    assert_eq!(count("secret-ops", ""), vec!["root store keeps shared runbooks"]);
+    assert!(tenants_dir.join("globex").join("memory-db.json").exists());
+
+    let crossed = send_http_with_headers(
+        &addr,
+        "GET",
+        "/v1/memories",
+        "",
+        &[("Authorization", "Bearer secret-acme"), ("X-Prx-Tenant", "globex")],
+    );
+    assert!(crossed.starts_with("HTTP/1.1 403"));
+    let invalid = send_http_with_headers(
+        &addr,
+        "GET",
+        "/v1/memories",
+        "",
+        &[("Authorization", "Bearer secret-ops"), ("X-Prx-Tenant", "../etc")],
+    );
+    assert!(invalid.starts_with("HTTP/1.1 403"));
+
+    // Without a token /metrics names no tenants.
+    let public = send_http(&addr, "GET", "/metrics", "");
+    assert!(public.starts_with("HTTP/1.1 200"));
+    assert!(!response_body(&public).contains("tenant="));
+    let summary = send_http(&addr, "GET", "/metrics/summary", "");
+    let summary: serde_json::Value = serde_json::from_str(response_body(&summary)).expect("summary json");
+    assert!(summary.get("tenants").is_none());
+
+    let metrics = send_http_with_headers(&addr, "GET", "/metrics", "", &[("Authorization", "Bearer secret-ops")]);
+    let metrics_body = response_body(&metrics);
+    assert!(metrics_body.contains("tenant=\"default\""));
+    assert!(metrics_body.contains("tenant=\"acme\""));
+    assert!(metrics_body.contains("tenant=\"globex\""));
+    assert_eq!(
+        metrics_body
+            .matches("# TYPE prx_memory_http_auth_failures_total ")
+            .count(),
+        1
+    );
+    // A token bound to a tenant only sees that tenant's breakdown.
+    let bound = send_http_with_headers(
+        &addr,
+        "GET",
+        "/metrics/summary",
+        "",
+        &[("Authorization", "Bearer secret-acme")],
+    );
+    let bound: serde_json::Value = serde_json::from_str(response_body(&bound)).expect("summary json");
+    let visible = bound["tenants"]
+        .as_object()
+        .expect("tenants")
+        .keys()
+        .collect::<Vec<_>>();
+    assert_eq!(visible, vec!["acme"]);
+
+    let _ = child.kill();
+    let _ = child.wait();
+
+    // Settings naming one remote for the whole process are refused with tenants.
+    let shared = daemon_command(&[
+        ("PRX_MEMORY_HTTP_ADDR", &reserve_addr()),
+        ("PRX_MEMORY_DB", &data_dir.join("memory-db.json").display().to_string()),
+        ("PRX_MEMORY_TENANTS_DIR", &tenants_dir.display().to_string()),
+        ("PRX_MEMORY_FOLLOW_URL", "http://127.0.0.1:9"),
+    ])
+    .status()
+    .expect("run prx-memoryd");
+    assert!(!shared.success());
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn request_agent_identity_drives_scope_acls_and_tool_policy() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-agents-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_AGENT_FROM_REQUEST", "1"),
+        ("PRX_MEMORY_DEFAULT_SCOPE", "agent:{agent_id}"),
+        ("PRX_MEMORY_TOOL_POLICY", r#"{"intern":{"deny":["memory_store"]}}"#),
+    ]);
+
+    wait_for_http(&addr);
+
+    let stored = send_http_with_headers(
+        &addr,
+        "POST",
+        "/v1/memories",
+        r#"{"text":"Alice rotates the staging keys","category":"fact"}"#,
+        &[("X-Prx-Agent", "alice")],
+    );
+    assert!(stored.starts_with("HTTP/1.1 201"), "{stored}");
+    let entry: serde_json::Value = serde_json::from_str(response_body(&stored)).expect("entry json");
+    assert_eq!(entry["scope"], "agent:alice");
+
+    let own = send_http_with_headers(
+        &addr,
+        "GET",
+        "/v1/memories?scope=agent:alice",
+        "",
+        &[("X-Prx-Agent", "alice")],
+    );
+    assert!(own.starts_with("HTTP/1.1 200"));
+    let other = send_http_with_headers(
+        &addr,
+        "GET",
+        "/v1/memories?scope=agent:alice",
+        "",
+        &[("X-Prx-Agent", "bob")],
+    );
+    assert!(!other.starts_with("HTTP/1.1 200"), "{other}");
+
+    let denied = send_http_with_headers(
+        &addr,
+        "POST",
+        "/v1/memories",
+        r#"{"text":"Intern note"}"#,
+        &[("X-Prx-Agent", "intern")],
+    );
+    assert!(denied.starts_with("HTTP/1.1 403"), "{denied}");
+    assert!(response_body(&denied).contains("agent intern may not call memory_store"));
+    let invalid = send_http_with_headers(&addr, "GET", "/v1/memories", "", &[("X-Prx-Agent", "bad agent")]);
+    assert!(invalid.starts_with("HTTP/1.1 400"));
+
+    let start = send_http(&addr, "POST", "/mcp/session/start?agent=bob", "{}");
+    let start: serde_json::Value = serde_json::from_str(response_body(&start)).expect("start json");
+    let session_id = start["session_id"].as_str().expect("session id").to_string();
+    let store_body = r#"{"jsonrpc":"2.0","id":61,"method":"tools/call","params":{"name":"memory_store","arguments":{"text":"Bob owns the release calendar","category":"fact"}}}"#;
+    let queued = send_http(&addr, "POST", &format!("/mcp/stream?session={session_id}"), store_body);
+    assert!(queued.starts_with("HTTP/1.1 202"));
+    let poll = send_http(
+        &addr,
+        "GET",
+        &format!("/mcp/stream?session={session_id}&from=1&limit=10"),
+        "",
+    );
+    assert!(response_body(&poll).contains("agent:bob"), "{poll}");
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn tokens_may_only_claim_their_bound_agents() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-agent-tokens-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_HTTP_TOKENS", "ci:secret-ci,ops:secret-ops"),
+        ("PRX_MEMORY_AGENT_FROM_REQUEST", "1"),
+        ("PRX_MEMORY_AGENT_TOKENS", "ci:builder,ci:tester"),
+        ("PRX_MEMORY_DEFAULT_SCOPE", "agent:{agent_id}"),
+    ]);
+
+    wait_for_http(&addr);
+
+    let body = r#"{"text":"Builder caches the toolchain","category":"fact"}"#;
+    let bound = send_http_with_headers(
+        &addr,
+        "POST",
+        "/v1/memories",
+        body,
+        &[("Authorization", "Bearer secret-ci"), ("X-Prx-Agent", "builder")],
+    );
+    assert!(bound.starts_with("HTTP/1.1 201"), "{bound}");
+    let entry: serde_json::Value = serde_json::from_str(response_body(&bound)).expect("entry json");
+    assert_eq!(entry["scope"], "agent:builder");
+    let second = send_http_with_headers(
+        &addr,
+        "GET",
+        "/v1/memories",
+        "",
+        &[("Authorization", "Bearer secret-ci"), ("X-Prx-Agent", "tester")],
+    );
+    assert!(second.starts_with("HTTP/1.1 200"), "{second}");
+
+    let unbound = send_http_with_headers(
+        &addr,
+        "GET",
+        "/v1/memories",
+        "",
+        &[("Authorization", "Bearer secret-ci"), ("X-Prx-Agent", "admin")],
+    );
+    assert!(unbound.starts_with("HTTP/1.1 403"), "{unbound}");
+    assert!(response_body(&unbound).contains("not bound to agent admin"));
+    let other_token = send_http_with_headers(
+        &addr,
+        "GET",
+        "/v1/memories",
+        "",
+        &[("Authorization", "Bearer secret-ops"), ("X-Prx-Agent", "builder")],
+    );
+    assert!(other_token.starts_with("HTTP/1.1 403"), "{other_token}");
+    let session = send_http_with_headers(
+        &addr,
+        "POST",
+        "/mcp/session/start?agent=admin",
+        "{}",
+        &[("Authorization", "Bearer secret-ci")],
+    );
+    assert!(session.starts_with("HTTP/1.1 403"), "{session}");
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn read_only_agent_access_blocks_mutations() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-acl-actions-{now}.json"))
+        .display()
+        .to_string();
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_AGENT_FROM_REQUEST", "1"),
+        (
+            "PRX_MEMORY_AGENT_ACCESS",
+            r#"{"reviewer":{"*":["read"]},"writer":{"global":["read","write"]}}"#,
+        ),
+    ]);
+
+    wait_for_http(&addr);
+
+    let body = r#"{"text":"Release branches are cut on Tuesdays","category":"fact","scope":"global"}"#;
+    let stored = send_http_with_headers(&addr, "POST", "/v1/memories", body, &[("X-Prx-Agent", "writer")]);
+    assert!(stored.starts_with("HTTP/1.1 201"), "{stored}");
+    let entry: serde_json::Value = serde_json::from_str(response_body(&stored)).expect("entry json");
+    let path = format!("/v1/memories/{}", entry["id"].as_str().expect("id"));
+
+    let listed = send_http_with_headers(&addr, "GET", "/v1/memories", "", &[("X-Prx-Agent", "reviewer")]);
+    assert!(response_body(&listed).contains("release branches"), "{listed}");
+    let stats_body =
+        r#"{"jsonrpc":"2.0","id":71,"method":"tools/call","params":{"name":"memory_stats","arguments":{}}}"#;
+    let stats = send_http_with_headers(&addr, "POST", "/mcp", stats_body, &[("X-Prx-Agent", "reviewer")]);
+    assert!(
+        response_body(&stats).contains(r#""scope_actions":{"*":["read"]}"#),
+        "{stats}"
+    );
+
+    let denied_store = send_http_with_headers(&addr, "POST", "/v1/memories", body, &[("X-Prx-Agent", "reviewer")]);
+    assert!(response_body(&denied_store).contains("needs write"), "{denied_store}");
+    let denied_delete = send_http_with_headers(&addr, "DELETE", &path, "", &[("X-Prx-Agent", "reviewer")]);
+    assert!(denied_delete.starts_with("HTTP/1.1 403"), "{denied_delete}");
+    let writer_delete = send_http_with_headers(&addr, "DELETE", &path, "", &[("X-Prx-Agent", "writer")]);
+    assert!(writer_delete.starts_with("HTTP/1.1 403"), "{writer_delete}");
+
+    let compact = |dry_run: bool| {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":72,"method":"tools/call","params":{{"name":"memory_compact","arguments":{{"scope":"global","dry_run":{dry_run}}}}}}}"#
+        );
+        send_http_with_headers(&addr, "POST", "/mcp", &body, &[("X-Prx-Agent", "reviewer")])
+    };
+    assert!(response_body(&compact(true)).contains("\"result\""));
+    assert!(response_body(&compact(false)).contains("needs admin"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[test]
+fn governance_policy_file_replaces_built_in_rules() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-policy-{now}.json"))
+        .display()
+        .to_string();
+    let policy_path = std::env::temp_dir().join(format!("prx-memory-http-policy-{now}.policy.json"));
+    std::fs::write(&policy_path, r#"{"required_tag_prefixes":["team:"],"ratio_caps":{},"strict_categories":true,"category_aliases":{"note":"other"}}"#).expect("write policy");
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_GOVERNANCE_POLICY", &policy_path.display().to_string()),
+    ]);
+
+    wait_for_http(&addr);
+
+    let dual = |id: u64, tags: &str| {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":{id},"method":"tools/call","params":{{"name":"memory_store_dual","arguments":{{"symptom":"deploy stalled on a stale lock","cause":"crashed job kept the lock","fix":"expire locks after ten minutes","prevention":"heartbeat long jobs","principle_tag":"locks","principle_rule":"locks must expire","trigger":"adding a lock","action":"give it a ttl","scope":"global","tags":{tags},"project_tag":"prx-memory","tool_tag":"mcp","domain_tag":"ops"}}}}}}"#
+        );
+        send_http(&addr, "POST", "/mcp", &body)
+    };
+    let rejected = dual(81, "[]");
+    assert!(
+        response_body(&rejected).contains("tags must include team:*"),
+        "{rejected}"
+    );
+    let accepted = dual(82, r#"["team:core"]"#);
+    assert!(response_body(&accepted).contains("\"result\""), "{accepted}");
+
+    let rest_store = |category: &str| {
+        let body = format!(r#"{{"text":"Standup moved to 9:30","category":"{category}","scope":"global"}}"#);
+        send_http(&addr, "POST", "/v1/memories", &body)
+    };
+    let unknown = rest_store("scratch");
+    assert!(response_body(&unknown).contains("category must be one of"), "{unknown}");
+    let aliased = rest_store("Note");
+    assert!(response_body(&aliased).contains(r#""category":"other""#), "{aliased}");
+
+    let stats_body =
+        r#"{"jsonrpc":"2.0","id":83,"method":"tools/call","params":{"name":"memory_stats","arguments":{}}}"#;
+    let stats = send_http(&addr, "POST", "/mcp", stats_body);
+    assert!(
+        response_body(&stats).contains(r#""required_tag_prefixes":["team:"]"#),
+        "{stats}"
+    );
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+    let _ = std::fs::remove_file(policy_path);
+}
+
+#[test]
+fn keyword_expansion_recalls_paraphrases_without_embeddings() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-keywords-{now}.json"))
+        .display()
+        .to_string();
+    let policy_path = std::env::temp_dir().join(format!("prx-memory-http-keywords-{now}.policy.json"));
+    std::fs::write(
+        &policy_path,
+        r#"{"keyword_expansion":{"enabled":true,"aliases":{"rollback":["revert","undo deploy"]}}}"#,
+    )
+    .expect("write policy");
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_GOVERNANCE_POLICY", &policy_path.display().to_string()),
+    ]);
+
+    wait_for_http(&addr);
+
+    let stored = send_http(
+        &addr,
+        "POST",
+        "/v1/memories",
+        r#"{"text":"Rollbacks need the previous image tag","category":"fact","scope":"global"}"#,
+    );
+    assert!(response_body(&stored).contains(r#""category":"fact""#), "{stored}");
+
+    let recall = |id: u64, query: &str| {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":{id},"method":"tools/call","params":{{"name":"memory_recall","arguments":{{"query":"{query}","scope":"global"}}}}}}"#
+        );
+        send_http(&addr, "POST", "/mcp", &body)
+    };
+    let paraphrase = recall(91, "revert");
+    assert!(
+        response_body(&paraphrase).contains("previous image tag"),
+        "{paraphrase}"
+    );
+    let unrelated = recall(92, "pager");
+    assert!(!response_body(&unrelated).contains("previous image tag"), "{unrelated}");
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+    let _ = std::fs::remove_file(policy_path);
+}
+
+#[test]
+fn recall_merges_labelled_results_from_secondary_stores() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-personal-{now}.json"))
+        .display()
+        .to_string();
+    let team_path = std::env::temp_dir().join(format!("prx-memory-http-team-{now}.json"));
+    let mut team = PersistentMemoryStore::open(&team_path).expect("open team store");
+    team.store(NewMemoryEntry {
+        text: "Team deploys go through the blue-green switch".to_string(),
+        category: "fact".to_string(),
+        scope: "global".to_string(),
+        importance: 0.5,
+        tags: Vec::new(),
+        embedding: None,
+        embedding_model: None,
+        source: None,
+        keywords: Vec::new(),
+    })
+    .expect("store team entry");
+    drop(team);
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_STORE_NAME", "personal"),
+        (
+            "PRX_MEMORY_SECONDARY_STORES",
+            &format!("team:0.5={}", team_path.display()),
+        ),
+    ]);
+
+    wait_for_http(&addr);
+
+    let stored = send_http(
+        &addr,
+        "POST",
+        "/v1/memories",
+        r#"{"text":"My deploys go through a canary first","category":"fact","scope":"global"}"#,
+    );
+    assert!(response_body(&stored).contains(r#""category":"fact""#), "{stored}");
+
+    let recall = |id: u64, extra: &str| {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":{id},"method":"tools/call","params":{{"name":"memory_recall","arguments":{{"query":"deploys","scope":"global"{extra}}}}}}}"#
+        );
+        let response = send_http(&addr, "POST", "/mcp", &body);
+        let parsed: serde_json::Value = serde_json::from_str(response_body(&response)).expect("json body");
+        parsed
+            .pointer("/result/structuredContent/items")
+            .and_then(serde_json::Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let items = recall(101, "");
+    let labelled = items
+        .iter()
+        .map(|item| {
+            (
+                item.get("store")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default(),
+                item.pointer("/entry/text")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labelled,
+        [
+            ("personal", "my deploys go through a canary first"),
+            ("team", "team deploys go through the blue-green switch"),
+        ]
+    );
+    assert_eq!(recall(102, r#","include_secondary":false"#).len(), 1);
+
+    let stats_body =
+        r#"{"jsonrpc":"2.0","id":103,"method":"tools/call","params":{"name":"memory_stats","arguments":{}}}"#;
+    let stats = send_http(&addr, "POST", "/mcp", stats_body);
+    assert!(response_body(&stats).contains(r#""name":"team""#), "{stats}");
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+    let _ = std::fs::remove_file(team_path);
+}
+
+#[test]
+fn skill_tag_taxonomy_rejects_unlisted_governed_tags() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let db_path = std::env::temp_dir()
+        .join(format!("prx-memory-http-taxonomy-{now}.json"))
+        .display()
+        .to_string();
+    let skill_dir = std::env::temp_dir().join(format!("prx-memory-taxonomy-skill-{now}"));
+    std::fs::create_dir_all(skill_dir.join("references")).expect("create references");
+    std::fs::write(
+        skill_dir.join("references/governance-policy.json"),
+        r#"{"tag_taxonomy":"reject"}"#,
+    )
+    .expect("write policy");
+    std::fs::write(
+        skill_dir.join("references/tag-taxonomy.md"),
+        "- tool tag: `tool:mcp`\n- domain tag: `domain:ops`, `domain:billing`\n",
+    )
+    .expect("write taxonomy");
+    let addr = reserve_addr();
+
+    let mut child = spawn_daemon(&[
+        ("PRX_MEMORY_HTTP_ADDR", &addr),
+        ("PRX_MEMORY_DB", &db_path),
+        ("PRX_MEMORY_SKILL_DIR", &skill_dir.display().to_string()),
+    ]);
+
+    wait_for_http(&addr);
+
+    let dual = |id: u64, tool: &str, domain: &str| {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":{id},"method":"tools/call","params":{{"name":"memory_store_dual","arguments":{{"symptom":"invoice export timed out at month end","cause":"export ran in one transaction","fix":"export in pages of 500","prevention":"page every bulk export","principle_tag":"bulk-exports","principle_rule":"page bulk exports","trigger":"writing a bulk export","action":"page the query","scope":"global","project_tag":"prx-memory","tool_tag":"{tool}","domain_tag":"{domain}"}}}}}}"#
+        );
+        send_http(&addr, "POST", "/mcp", &body)
+    };
+    let rejected = dual(91, "mcp", "payments");
+    assert!(
+        response_body(&rejected).contains("tags are not in the tag taxonomy: domain:payments"),
+        "{rejected}"
+    );
+    let wrong_tool = dual(92, "lancedb", "billing");
+    assert!(response_body(&wrong_tool).contains("tool:lancedb"), "{wrong_tool}");
+    let accepted = dual(93, "mcp", "billing");
+    assert!(response_body(&accepted).contains("\"result\""), "{accepted}");
+
+    let _ = child.kill();
+    let _ = child.wait();
+    let _ = std::fs::remove_file(db_path);
+    let _ = std::fs::remove_dir_all(skill_dir);
+}
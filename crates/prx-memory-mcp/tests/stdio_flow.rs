@@ -147,3 +147,68 @@ fn stdio_content_length_initialize_and_tools_list_work() {
     let status = child.wait().expect("wait child");
     assert!(status.success());
 }
+
+#[test]
+fn stdio_query_log_records_recalls_and_later_use() {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("clock")
+        .as_nanos();
+    let db_path = std::env::temp_dir().join(format!("prx-memory-query-log-{nanos}.json"));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_prx-memory-mcp"))
+        .env("PRX_MEMORY_DB", &db_path)
+        .env("PRX_MEMORY_QUERY_LOG", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("spawn prx-memory-mcp");
+    let mut child_stdin = child.stdin.take().expect("stdin");
+    let mut reader = BufReader::new(child.stdout.take().expect("stdout"));
+    let mut call = |id: u64, name: &str, arguments: Value| -> Value {
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": arguments}
+        });
+        writeln!(child_stdin, "{req}").expect("write request");
+        let mut line = String::new();
+        reader.read_line(&mut line).expect("read response line");
+        serde_json::from_str::<Value>(&line).expect("parse response json")["result"]["structuredContent"].clone()
+    };
+
+    let stored = call(
+        1,
+        "memory_store",
+        json!({"text": "Pitfall: flaky CI. Cause: shared temp dir. Fix: unique paths.", "category": "fact", "scope": "global"}),
+    );
+    let memory_id = stored["id"].as_str().expect("stored id").to_string();
+    let recalled = call(
+        2,
+        "memory_recall",
+        json!({"query": "flaky CI temp dir", "scope": "global"}),
+    );
+    let query_id = recalled["query_id"].as_str().expect("query id").to_string();
+    call(3, "memory_recall", json!({"query": "unrelated release notes"}));
+    call(4, "memory_update", json!({"id": memory_id, "importance_level": "high"}));
+
+    let exported = call(5, "memory_query_log", json!({"accessed_only": true}));
+    assert_eq!(exported["enabled"], true);
+    assert_eq!(exported["total"], 1);
+    let record = &exported["items"][0];
+    assert_eq!(record["id"], query_id);
+    assert_eq!(record["query"], "flaky CI temp dir");
+    assert_eq!(record["scope"], "global");
+    assert_eq!(record["result_ids"], json!([memory_id]));
+    assert_eq!(record["accessed_ids"], json!([memory_id]));
+
+    let cleared = call(6, "memory_query_log", json!({"clear": true}));
+    assert_eq!(cleared["cleared"], 2);
+    assert_eq!(call(7, "memory_query_log", json!({}))["total"], 0);
+
+    drop(child_stdin);
+    assert!(child.wait().expect("wait child").success());
+    for suffix in ["", ".queries.jsonl", ".access.json", ".jobs.json"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", db_path.display()));
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+pub use prx_memory_core::retry::RetryPolicy;
+use prx_memory_core::retry::{Failure, RetryableError, Retrying};
+
+use crate::error::ProviderError;
+use crate::traits::RerankProvider;
+use crate::types::{RerankRequest, RerankResponse};
+
+impl RetryableError for ProviderError {
+    fn failure(&self) -> Failure {
+        match self {
+            Self::Http(e) if e.is_timeout() || e.is_connect() => Failure::Transient,
+            Self::Api { status, .. } => Failure::from_status(*status),
+            _ => Failure::Permanent,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RerankProvider for Retrying<dyn RerankProvider> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn rerank(&self, request: RerankRequest) -> Result<RerankResponse, ProviderError> {
+        self.policy.run(|| self.inner.rerank(request.clone())).await
+    }
+}
+
+pub fn with_retry(provider: Arc<dyn RerankProvider>, policy: RetryPolicy) -> Arc<dyn RerankProvider> {
+    if policy.max_attempts <= 1 {
+        return provider;
+    }
+    Arc::new(Retrying::new(provider, policy))
+}
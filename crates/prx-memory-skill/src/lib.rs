@@ -2,11 +2,14 @@ pub const SKILL_ID: &str = "prx-memory-governance";
 pub const SKILL_MAIN_URI: &str = "prx://skills/prx-memory-governance/SKILL.md";
 pub const SKILL_GOVERNANCE_URI: &str = "prx://skills/prx-memory-governance/references/memory-governance.md";
 pub const SKILL_TAGS_URI: &str = "prx://skills/prx-memory-governance/references/tag-taxonomy.md";
+pub const SKILL_POLICY_URI: &str = "prx://skills/prx-memory-governance/references/governance-policy.json";
 
 pub const SKILL_MAIN_TEXT: &str = include_str!("../../../skills/prx-memory-governance/SKILL.md");
 pub const SKILL_GOVERNANCE_TEXT: &str =
     include_str!("../../../skills/prx-memory-governance/references/memory-governance.md");
 pub const SKILL_TAGS_TEXT: &str = include_str!("../../../skills/prx-memory-governance/references/tag-taxonomy.md");
+pub const SKILL_POLICY_TEXT: &str =
+    include_str!("../../../skills/prx-memory-governance/references/governance-policy.json");
 
 #[derive(Debug, Clone, Copy)]
 pub struct SkillResource {
@@ -17,7 +20,7 @@ pub struct SkillResource {
     pub text: &'static str,
 }
 
-static SKILL_RESOURCES: [SkillResource; 3] = [
+static SKILL_RESOURCES: [SkillResource; 4] = [
     SkillResource {
         uri: SKILL_MAIN_URI,
         name: "prx-memory-governance/SKILL.md",
@@ -39,6 +42,13 @@ static SKILL_RESOURCES: [SkillResource; 3] = [
         mime_type: "text/markdown",
         text: SKILL_TAGS_TEXT,
     },
+    SkillResource {
+        uri: SKILL_POLICY_URI,
+        name: "prx-memory-governance/references/governance-policy.json",
+        description: "Default governance policy for governed writes.",
+        mime_type: "application/json",
+        text: SKILL_POLICY_TEXT,
+    },
 ];
 
 pub fn resources() -> &'static [SkillResource] {
@@ -0,0 +1,410 @@
+//! Durable change feed: every store, update and delete as a sequence-numbered event appended to a
+//! JSONL file next to the store, for downstream indexing, replication and incremental backups.
+//!
+//! [`ChangeRecordingBackend`] wraps any [`StorageBackend`] and records its entry writes, so the feed
+//! works the same over the JSON store and LanceDB. Relation edges are not part of the feed.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    FieldCipher, MemoryEntry, MemoryRelation, NewMemoryEntry, RecallQuery, RecallResult, StorageBackend, StorageError,
+    StoreSnapshot, now_ms,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeOp {
+    Store,
+    /// The entry was rewritten under a new id; `previous_id` names the one it replaced.
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    /// Starts at 1 and grows by one per event, without gaps.
+    pub seq: u64,
+    pub op: ChangeOp,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_id: Option<String>,
+    pub timestamp_ms: u64,
+    /// The written entry for `store` and `update`; `None` for `delete`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<MemoryEntry>,
+}
+
+/// An event as written to disk; with a cipher the entry is sealed like in the JSON store.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedChange {
+    #[serde(flatten)]
+    event: ChangeEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_embedding: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangePage {
+    pub events: Vec<ChangeEvent>,
+    /// `from` for the next page.
+    pub next_from: u64,
+    /// Sequence number of the newest event, 0 while the log is empty.
+    pub latest_seq: u64,
+}
+
+/// What opening a change log repaired, reported under `change_log_recovery` in the store stats.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeLogRepair {
+    /// Bytes of a half-written last event cut from the end of the file.
+    pub truncated_bytes: u64,
+}
+
+/// Append-only change log at `<db>.changes.jsonl`.
+#[derive(Debug)]
+pub struct ChangeLog {
+    path: PathBuf,
+    cipher: Option<FieldCipher>,
+    latest_seq: u64,
+    repair: Option<ChangeLogRepair>,
+}
+
+impl ChangeLog {
+    /// Opens the log and resumes numbering after its newest event; a missing file starts at 0.
+    /// A last event cut off by a crash is removed, so the next append starts on a clean line.
+    pub fn open(path: impl Into<PathBuf>, cipher: Option<FieldCipher>) -> Result<Self, StorageError> {
+        let path = path.into();
+        let raw = match fs::read(&path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        // Every complete event ends with a newline; anything after the last one is a torn write.
+        let complete = raw.iter().rposition(|b| *b == b'\n').map_or(0, |idx| idx + 1);
+        let repair = if complete < raw.len() {
+            OpenOptions::new().write(true).open(&path)?.set_len(complete as u64)?;
+            Some(ChangeLogRepair {
+                truncated_bytes: (raw.len() - complete) as u64,
+            })
+        } else {
+            None
+        };
+        let latest_seq = raw
+            .get(..complete)
+            .unwrap_or_default()
+            .split(|b| *b == b'\n')
+            .filter_map(|line| serde_json::from_slice::<PersistedChange>(line).ok())
+            .map(|persisted| persisted.event.seq)
+            .max()
+            .unwrap_or(0);
+        Ok(Self {
+            path,
+            cipher,
+            latest_seq,
+            repair,
+        })
+    }
+
+    /// What opening the log repaired, if anything.
+    pub const fn repair(&self) -> Option<&ChangeLogRepair> {
+        self.repair.as_ref()
+    }
+
+    pub const fn latest_seq(&self) -> u64 {
+        self.latest_seq
+    }
+
+    pub fn append(
+        &mut self,
+        op: ChangeOp,
+        id: &str,
+        previous_id: Option<&str>,
+        entry: Option<&MemoryEntry>,
+    ) -> Result<ChangeEvent, StorageError> {
+        let event = ChangeEvent {
+            seq: self.latest_seq + 1,
+            op,
+            id: id.to_string(),
+            previous_id: previous_id.map(str::to_string),
+            timestamp_ms: now_ms(),
+            entry: entry.cloned(),
+        };
+        let mut persisted = PersistedChange {
+            event: event.clone(),
+            sealed_embedding: None,
+        };
+        if let (Some(cipher), Some(entry)) = (&self.cipher, persisted.event.entry.as_mut()) {
+            persisted.sealed_embedding = cipher.seal_entry(entry)?;
+        }
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&persisted)?)?;
+        file.flush()?;
+        self.latest_seq = event.seq;
+        Ok(event)
+    }
+
+    /// Up to `limit` events with `seq >= from`, oldest first.
+    pub fn read(&self, from: u64, limit: usize) -> Result<ChangePage, StorageError> {
+        let mut events = Vec::new();
+        if limit > 0 && from <= self.latest_seq {
+            let file = match File::open(&self.path) {
+                Ok(file) => Some(file),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => return Err(err.into()),
+            };
+            for line in file.into_iter().flat_map(|f| BufReader::new(f).lines()) {
+                let Ok(mut persisted) = serde_json::from_str::<PersistedChange>(&line?) else {
+                    continue;
+                };
+                if persisted.event.seq < from {
+                    continue;
+                }
+                if let (Some(cipher), Some(entry)) = (&self.cipher, persisted.event.entry.as_mut()) {
+                    cipher.open_entry(entry, persisted.sealed_embedding.as_deref())?;
+                }
+                events.push(persisted.event);
+                if events.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(ChangePage {
+            next_from: events.last().map_or(from, |e| e.seq + 1),
+            latest_seq: self.latest_seq,
+            events,
+        })
+    }
+}
+
+/// Records the entry writes of `inner` in a [`ChangeLog`]. A write whose event cannot be appended
+/// still happened, but the call returns the log error.
+pub struct ChangeRecordingBackend {
+    inner: Box<dyn StorageBackend>,
+    log: ChangeLog,
+}
+
+impl ChangeRecordingBackend {
+    pub fn new(inner: Box<dyn StorageBackend>, log: ChangeLog) -> Self {
+        Self { inner, log }
+    }
+}
+
+impl StorageBackend for ChangeRecordingBackend {
+    fn store(&mut self, new_entry: NewMemoryEntry) -> Result<MemoryEntry, StorageError> {
+        let entry = self.inner.store(new_entry)?;
+        self.log.append(ChangeOp::Store, &entry.id, None, Some(&entry))?;
+        Ok(entry)
+    }
+
+    fn recall(&self, query: RecallQuery) -> Vec<RecallResult> {
+        self.inner.recall(query)
+    }
+
+    fn forget_by_id(&mut self, id: &str) -> Result<bool, StorageError> {
+        let removed = self.inner.forget_by_id(id)?;
+        if removed {
+            self.log.append(ChangeOp::Delete, id, None, None)?;
+        }
+        Ok(removed)
+    }
+
+    fn list(&self, limit: usize) -> Vec<MemoryEntry> {
+        self.inner.list(limit)
+    }
+
+    fn stats(&self) -> serde_json::Value {
+        let mut stats = self.inner.stats();
+        if let Some(obj) = stats.as_object_mut() {
+            obj.insert("change_seq".to_string(), self.log.latest_seq().into());
+            if let Some(repair) = self.log.repair() {
+                obj.insert("change_log_recovery".to_string(), serde_json::json!(repair));
+            }
+        }
+        stats
+    }
+
+    fn snapshot(&self) -> StoreSnapshot {
+        self.inner.snapshot()
+    }
+
+    fn forget_batch(&mut self, ids: &[String]) -> Result<Vec<String>, StorageError> {
+        let removed = self.inner.forget_batch(ids)?;
+        for id in &removed {
+            self.log.append(ChangeOp::Delete, id, None, None)?;
+        }
+        Ok(removed)
+    }
+
+    fn replace(&mut self, id: &str, new_entry: NewMemoryEntry) -> Result<Option<MemoryEntry>, StorageError> {
+        let replaced = self.inner.replace(id, new_entry)?;
+        if let Some(entry) = &replaced {
+            self.log.append(ChangeOp::Update, &entry.id, Some(id), Some(entry))?;
+        }
+        Ok(replaced)
+    }
+
+    /// Applied events are recorded again under this log's own sequence numbers, so a follower can
+    /// serve a change feed of its own.
+    fn apply_changes(&mut self, events: &[ChangeEvent]) -> Result<(), StorageError> {
+        self.inner.apply_changes(events)?;
+        for event in events {
+            self.log
+                .append(event.op, &event.id, event.previous_id.as_deref(), event.entry.as_ref())?;
+        }
+        Ok(())
+    }
+
+    fn link(&mut self, from_id: &str, relation: &str, to_id: &str) -> Result<bool, StorageError> {
+        self.inner.link(from_id, relation, to_id)
+    }
+
+    fn unlink(&mut self, from_id: &str, relation: Option<&str>, to_id: &str) -> Result<usize, StorageError> {
+        self.inner.unlink(from_id, relation, to_id)
+    }
+
+    fn relations_for(&self, id: &str) -> Vec<MemoryRelation> {
+        self.inner.relations_for(id)
+    }
+
+    fn rekey(&mut self) -> Result<usize, StorageError> {
+        self.inner.rekey()
+    }
+
+    fn index_status(&self) -> Result<serde_json::Value, StorageError> {
+        self.inner.index_status()
+    }
+
+    fn ensure_indexes(&mut self) -> Result<serde_json::Value, StorageError> {
+        self.inner.ensure_indexes()
+    }
+
+    fn changes(&self, from: u64, limit: usize) -> Result<ChangePage, StorageError> {
+        self.log.read(from, limit)
+    }
+
+    fn flush(&mut self) -> Result<(), StorageError> {
+        self.inner.flush()?;
+        crate::sync_file(&self.log.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PersistentMemoryStore;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    fn new_entry(text: &str) -> NewMemoryEntry {
+        NewMemoryEntry {
+            text: text.to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: Vec::new(),
+            embedding: Some(vec![0.5, 0.5]),
+            embedding_model: None,
+            source: None,
+            keywords: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn records_writes_in_order_and_resumes_after_reopen() {
+        let base = std::env::temp_dir().join(format!("prx-changes-{}-{}", std::process::id(), now_ms()));
+        let db = base.with_extension("json");
+        let log_path = base.with_extension("changes.jsonl");
+        let cipher = || Some(FieldCipher::from_hex_keys(KEY, &[]).expect("cipher"));
+        let inner = PersistentMemoryStore::open_with_cipher(&db, cipher()).expect("open store");
+        let mut store =
+            ChangeRecordingBackend::new(Box::new(inner), ChangeLog::open(&log_path, cipher()).expect("open log"));
+
+        let first = store.store(new_entry("rotate the signing key")).expect("store");
+        let second = store.store(new_entry("pin the toolchain")).expect("store");
+        let updated = store
+            .replace(&first.id, new_entry("rotate the signing key yearly"))
+            .expect("replace")
+            .expect("first exists");
+        assert_eq!(store.replace("mem-missing", new_entry("x")).expect("replace"), None);
+        assert_eq!(
+            store
+                .forget_batch(&[second.id.clone(), "mem-missing".to_string()])
+                .expect("forget"),
+            vec![second.id.clone()]
+        );
+        assert!(!fs::read_to_string(&log_path).expect("read log").contains("signing key"));
+
+        let page = store.changes(2, 10).expect("changes");
+        let ops = page.events.iter().map(|e| (e.seq, e.op)).collect::<Vec<_>>();
+        assert_eq!(
+            ops,
+            vec![(2, ChangeOp::Store), (3, ChangeOp::Update), (4, ChangeOp::Delete)]
+        );
+        assert_eq!(page.next_from, 5);
+        let update = page.events.get(1).expect("update event");
+        assert_eq!(update.previous_id.as_deref(), Some(first.id.as_str()));
+        assert_eq!(
+            update.entry.as_ref().map(|e| e.text.as_str()),
+            Some("rotate the signing key yearly")
+        );
+        assert_eq!(
+            update.entry.as_ref().and_then(|e| e.embedding.clone()),
+            Some(vec![0.5, 0.5])
+        );
+        assert_eq!(update.id, updated.id);
+
+        let reopened = ChangeLog::open(&log_path, cipher()).expect("reopen log");
+        assert_eq!(reopened.latest_seq(), 4);
+        let first_page = reopened.read(1, 1).expect("read");
+        assert_eq!(first_page.events.first().map(|e| e.id.clone()), Some(first.id));
+        assert_eq!((first_page.next_from, first_page.latest_seq), (2, 4));
+        assert!(reopened.read(5, 10).expect("read").events.is_empty());
+
+        let _ = fs::remove_file(db);
+        let _ = fs::remove_file(log_path);
+    }
+
+    #[test]
+    fn follower_mirrors_leader_ids_from_the_feed() {
+        let base = std::env::temp_dir().join(format!("prx-follow-{}-{}", std::process::id(), now_ms()));
+        let leader_db = base.with_extension("leader.json");
+        let follower_db = base.with_extension("follower.json");
+        let log_path = base.with_extension("changes.jsonl");
+        let inner = PersistentMemoryStore::open(&leader_db).expect("open leader");
+        let mut leader = ChangeRecordingBackend::new(Box::new(inner), ChangeLog::open(&log_path, None).expect("log"));
+        let kept = leader.store(new_entry("keep the release notes short")).expect("store");
+        let dropped = leader.store(new_entry("temporary note")).expect("store");
+        let edited = leader
+            .replace(&kept.id, new_entry("keep release notes under a page"))
+            .expect("replace")
+            .expect("kept exists");
+        leader.forget_by_id(&dropped.id).expect("forget");
+
+        let mut follower = PersistentMemoryStore::open(&follower_db).expect("open follower");
+        let page = leader.changes(1, 2).expect("first page");
+        follower.apply_changes(&page.events).expect("apply first page");
+        let page = leader.changes(page.next_from, 10).expect("second page");
+        follower.apply_changes(&page.events).expect("apply second page");
+        follower
+            .apply_changes(&page.events)
+            .expect("replaying a page is harmless");
+
+        let mirrored = PersistentMemoryStore::open(&follower_db).expect("reopen follower");
+        assert_eq!(mirrored.list(10), leader.list(10));
+        assert_eq!(mirrored.list(10).first().map(|e| e.id.clone()), Some(edited.id));
+        let mut local = mirrored;
+        let taken = local.list(10);
+        let next = local.store(new_entry("local write")).expect("store");
+        assert!(taken.iter().all(|e| e.id != next.id));
+
+        for path in [leader_db, follower_db, log_path] {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
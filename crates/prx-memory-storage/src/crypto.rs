@@ -0,0 +1,268 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::digest::{SHA256, digest};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{MemoryEntry, StorageError};
+
+const SEALED_PREFIX: &str = "enc:v2:";
+/// Values sealed before the associated data named the record and field; still opened, and
+/// resealed as v2 by a rekey.
+const LEGACY_PREFIX: &str = "enc:v1:";
+
+/// AES-256-GCM encryption for entry text and embeddings at rest.
+///
+/// Sealed values look like `enc:v2:<key_id>:<base64(nonce || ciphertext)>`, where `key_id`
+/// is the first 8 hex chars of the key's SHA-256. The associated data binds each value to its
+/// key id, the id of the record it belongs to and the field name, so a sealed value copied into
+/// another entry or field fails to open. New values are sealed with the primary key; previous
+/// keys only open values written before a rotation. Values without the prefix are treated as
+/// plaintext, so an existing store can be encrypted in place with a rekey; they are counted in
+/// [`Self::plaintext_values`].
+#[derive(Debug, Clone)]
+pub struct FieldCipher {
+    keys: Vec<(String, LessSafeKey)>,
+    rng: SystemRandom,
+    plaintext_values: Arc<AtomicU64>,
+}
+
+impl FieldCipher {
+    /// Builds a cipher from hex-encoded 32-byte keys; `primary` seals, every key opens.
+    pub fn from_hex_keys(primary: &str, previous: &[&str]) -> Result<Self, StorageError> {
+        let mut keys = Vec::with_capacity(previous.len() + 1);
+        for raw in std::iter::once(primary).chain(previous.iter().copied()) {
+            let bytes = decode_hex(raw.trim())
+                .filter(|b| b.len() == 32)
+                .ok_or_else(|| StorageError::InvalidInput("encryption keys must be 64 hex characters".to_string()))?;
+            let key_id = key_id(&bytes);
+            let key = UnboundKey::new(&AES_256_GCM, &bytes)
+                .map_err(|_| StorageError::InvalidInput("invalid AES-256-GCM key".to_string()))?;
+            if !keys.iter().any(|(id, _)| *id == key_id) {
+                keys.push((key_id, LessSafeKey::new(key)));
+            }
+        }
+        Ok(Self {
+            keys,
+            rng: SystemRandom::new(),
+            plaintext_values: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// How many unsealed values were read since this cipher (or any clone of it) was built.
+    pub fn plaintext_values(&self) -> u64 {
+        self.plaintext_values.load(Ordering::Relaxed)
+    }
+
+    fn count_plaintext(&self) {
+        self.plaintext_values.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Id of the key new values are sealed with.
+    pub fn primary_key_id(&self) -> &str {
+        self.keys.first().map_or("", |(id, _)| id.as_str())
+    }
+
+    pub fn is_sealed(value: &str) -> bool {
+        value.starts_with(SEALED_PREFIX) || value.starts_with(LEGACY_PREFIX)
+    }
+
+    /// Seals `plain` as field `field` of record `record_id`; opening it needs the same pair.
+    pub fn seal(&self, plain: &str, record_id: &str, field: &str) -> Result<String, StorageError> {
+        let (key_id, key) = self
+            .keys
+            .first()
+            .ok_or_else(|| StorageError::InvalidInput("no encryption key configured".to_string()))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| StorageError::InvalidInput("failed to generate nonce".to_string()))?;
+        let mut buf = plain.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(associated_data(key_id, record_id, field)),
+            &mut buf,
+        )
+        .map_err(|_| StorageError::InvalidInput("encryption failed".to_string()))?;
+        let mut sealed = nonce.to_vec();
+        sealed.append(&mut buf);
+        Ok(format!("{SEALED_PREFIX}{key_id}:{}", BASE64.encode(sealed)))
+    }
+
+    /// Opens a value sealed for `record_id` and `field`; plaintext values are returned unchanged.
+    pub fn open(&self, stored: &str, record_id: &str, field: &str) -> Result<String, StorageError> {
+        let (rest, legacy) = match (stored.strip_prefix(SEALED_PREFIX), stored.strip_prefix(LEGACY_PREFIX)) {
+            (Some(rest), _) => (rest, false),
+            (None, Some(rest)) => (rest, true),
+            (None, None) => {
+                self.count_plaintext();
+                return Ok(stored.to_string());
+            }
+        };
+        let (key_id, payload) = rest
+            .split_once(':')
+            .ok_or_else(|| StorageError::InvalidInput("malformed encrypted value".to_string()))?;
+        let (_, key) = self
+            .keys
+            .iter()
+            .find(|(id, _)| id == key_id)
+            .ok_or_else(|| StorageError::InvalidInput(format!("no encryption key with id {key_id}")))?;
+        let raw = BASE64
+            .decode(payload)
+            .map_err(|_| StorageError::InvalidInput("malformed encrypted value".to_string()))?;
+        if raw.len() < NONCE_LEN {
+            return Err(StorageError::InvalidInput("malformed encrypted value".to_string()));
+        }
+        let (nonce, ciphertext) = raw.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| StorageError::InvalidInput("malformed encrypted value".to_string()))?;
+        let mut buf = ciphertext.to_vec();
+        let aad = if legacy {
+            key_id.as_bytes().to_vec()
+        } else {
+            associated_data(key_id, record_id, field)
+        };
+        let plain = key
+            .open_in_place(nonce, Aad::from(aad), &mut buf)
+            .map_err(|_| StorageError::InvalidInput(format!("decryption failed with key {key_id}")))?;
+        String::from_utf8(plain.to_vec())
+            .map_err(|_| StorageError::InvalidInput("decrypted value is not UTF-8".to_string()))
+    }
+
+    /// Seals `entry.text` and its keywords in place and moves the embedding out as a sealed JSON string.
+    pub fn seal_entry(&self, entry: &mut MemoryEntry) -> Result<Option<String>, StorageError> {
+        entry.text = self.seal(&entry.text, &entry.id, "text")?;
+        if !entry.keywords.is_empty() {
+            entry.keywords = vec![self.seal(&serde_json::to_string(&entry.keywords)?, &entry.id, "keywords")?];
+        }
+        entry
+            .embedding
+            .take()
+            .map(|vector| self.seal(&serde_json::to_string(&vector)?, &entry.id, "embedding"))
+            .transpose()
+    }
+
+    /// Reverses [`Self::seal_entry`].
+    pub fn open_entry(&self, entry: &mut MemoryEntry, sealed_embedding: Option<&str>) -> Result<(), StorageError> {
+        entry.text = self.open(&entry.text, &entry.id, "text")?;
+        match entry.keywords.as_slice() {
+            [sealed] if Self::is_sealed(sealed) => {
+                entry.keywords = serde_json::from_str(&self.open(sealed, &entry.id, "keywords")?)?;
+            }
+            [] => {}
+            _ => self.count_plaintext(),
+        }
+        match sealed_embedding {
+            Some(sealed) => {
+                entry.embedding = Some(serde_json::from_str(&self.open(sealed, &entry.id, "embedding")?)?);
+            }
+            None if entry.embedding.is_some() => self.count_plaintext(),
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// `key_id`, record id and field name, NUL-separated; none of them contain NUL.
+fn associated_data(key_id: &str, record_id: &str, field: &str) -> Vec<u8> {
+    [key_id, record_id, field].join("\0").into_bytes()
+}
+
+fn key_id(key: &[u8]) -> String {
+    digest(&SHA256, key)
+        .as_ref()
+        .iter()
+        .take(4)
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn decode_hex(raw: &str) -> Option<Vec<u8>> {
+    if raw.len() % 2 != 0 {
+        return None;
+    }
+    (0..raw.len())
+        .step_by(2)
+        .map(|i| raw.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const KEY_B: &str = "ffeeddccbbaa99887766554433221100ffeeddccbbaa99887766554433221100";
+
+    #[test]
+    fn seal_roundtrip_and_rotation() {
+        let old = FieldCipher::from_hex_keys(KEY_A, &[]).expect("cipher");
+        let sealed = old.seal("prefers dark mode", "mem-1", "text").expect("seal");
+        assert!(FieldCipher::is_sealed(&sealed));
+        assert!(!sealed.contains("dark"));
+        assert_eq!(old.open(&sealed, "mem-1", "text").expect("open"), "prefers dark mode");
+        assert_eq!(old.plaintext_values(), 0);
+        assert_eq!(
+            old.open("legacy plaintext", "mem-1", "text").expect("plain"),
+            "legacy plaintext"
+        );
+        assert_eq!(old.plaintext_values(), 1);
+
+        let rotated = FieldCipher::from_hex_keys(KEY_B, &[KEY_A]).expect("cipher");
+        assert_eq!(
+            rotated.open(&sealed, "mem-1", "text").expect("open with previous key"),
+            "prefers dark mode"
+        );
+        let resealed = rotated.seal("prefers dark mode", "mem-1", "text").expect("seal");
+        assert!(resealed.contains(rotated.primary_key_id()));
+        assert!(old.open(&resealed, "mem-1", "text").is_err());
+
+        assert!(FieldCipher::from_hex_keys("abcd", &[]).is_err());
+    }
+
+    #[test]
+    fn sealed_values_only_open_for_their_record_and_field() {
+        let cipher = FieldCipher::from_hex_keys(KEY_A, &[]).expect("cipher");
+        let sealed = cipher.seal("prefers dark mode", "mem-1", "text").expect("seal");
+        assert!(cipher.open(&sealed, "mem-2", "text").is_err());
+        assert!(cipher.open(&sealed, "mem-1", "keywords").is_err());
+
+        let mut entry: MemoryEntry = serde_json::from_value(serde_json::json!({
+            "id": "mem-1", "text": "t", "category": "fact", "scope": "global", "importance": 0.5,
+            "tags": [], "timestamp_ms": 1, "embedding": [0.5], "keywords": ["k"]
+        }))
+        .expect("entry");
+        let plain = entry.clone();
+        cipher.open_entry(&mut entry, None).expect("plaintext entry");
+        assert_eq!(cipher.plaintext_values(), 3);
+        let sealed_embedding = cipher.seal_entry(&mut entry).expect("seal entry");
+        let mut moved = entry.clone();
+        moved.id = "mem-2".to_string();
+        assert!(cipher.open_entry(&mut moved, sealed_embedding.as_deref()).is_err());
+        cipher
+            .open_entry(&mut entry, sealed_embedding.as_deref())
+            .expect("open entry");
+        assert_eq!(entry, plain);
+    }
+
+    #[test]
+    fn legacy_values_still_open() {
+        let cipher = FieldCipher::from_hex_keys(KEY_A, &[]).expect("cipher");
+        let (key_id, key) = cipher.keys.first().expect("primary key");
+        let nonce = [7u8; NONCE_LEN];
+        let mut buf = b"old value".to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(key_id.as_bytes()),
+            &mut buf,
+        )
+        .expect("legacy seal");
+        let mut raw = nonce.to_vec();
+        raw.append(&mut buf);
+        let legacy = format!("{LEGACY_PREFIX}{key_id}:{}", BASE64.encode(raw));
+        assert!(FieldCipher::is_sealed(&legacy));
+        assert_eq!(cipher.open(&legacy, "mem-9", "text").expect("open legacy"), "old value");
+    }
+}
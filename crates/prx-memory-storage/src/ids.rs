@@ -0,0 +1,116 @@
+//! Entry ids, generated here for every backend. New ids are `mem-<ULID>`: unique across processes,
+//! restarts and deletes, and sortable by creation time. [`IdFormat::Sequential`] keeps handing out
+//! `mem-<n>` for deployments whose tooling expects numbers; ids of either shape are accepted everywhere.
+//! Each backend is given its format when it is opened.
+
+use parking_lot::Mutex;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::{StorageError, now_ms};
+
+/// Crockford base32, as ULIDs use, in lowercase: ids end up in tags, which are lowercased.
+const ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdFormat {
+    /// `mem-` and a 26-character ULID.
+    #[default]
+    Ulid,
+    /// `mem-<n>` from the backend's own sequence, as before ULIDs.
+    Sequential,
+}
+
+impl IdFormat {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "ulid" => Some(Self::Ulid),
+            "sequential" | "seq" => Some(Self::Sequential),
+            _ => None,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ulid => "ulid",
+            Self::Sequential => "sequential",
+        }
+    }
+}
+
+/// The `n` of a `mem-<n>` id; backends seed their sequence past the highest one.
+pub fn id_sequence(id: &str) -> Option<u64> {
+    id.strip_prefix("mem-")?.parse().ok()
+}
+
+/// The id for a new entry in `format`. `seq` is the backend's next sequence number and only shows
+/// up in the id under [`IdFormat::Sequential`].
+pub fn new_id(format: IdFormat, seq: u64) -> Result<String, StorageError> {
+    match format {
+        IdFormat::Ulid => Ok(format!("mem-{}", next_ulid()?)),
+        IdFormat::Sequential => Ok(format!("mem-{seq}")),
+    }
+}
+
+/// A ULID that sorts after every one this process handed out before: within one millisecond,
+/// or when the clock steps back, the previous random part is incremented instead of redrawn.
+/// Fails when the system random source does, rather than handing out a guessable id.
+fn next_ulid() -> Result<String, StorageError> {
+    static LAST: Mutex<(u64, u128)> = Mutex::new((0, 0));
+    let mut last = LAST.lock();
+    let now = now_ms() & ((1 << 48) - 1);
+    let (ms, random) = if now > last.0 {
+        let mut bytes = [0u8; 16];
+        let draw = bytes.get_mut(6..).map(|random| SystemRandom::new().fill(random));
+        if !matches!(draw, Some(Ok(()))) {
+            return Err(StorageError::Io(std::io::Error::other(
+                "system random source failed while generating an id",
+            )));
+        }
+        (now, u128::from_be_bytes(bytes))
+    } else {
+        (last.0, (last.1 + 1) & ((1 << 80) - 1))
+    };
+    *last = (ms, random);
+    drop(last);
+    Ok(encode_ulid((u128::from(ms) << 80) | random))
+}
+
+fn encode_ulid(value: u128) -> String {
+    (0..26)
+        .map(|i| {
+            let index = usize::try_from((value >> (125 - 5 * i)) & 31).unwrap_or_default();
+            ALPHABET.get(index).map_or('0', |&b| char::from(b))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ulids_sort_by_creation_and_never_parse_as_sequences() {
+        let ids = (0..1000)
+            .map(|seq| new_id(IdFormat::Ulid, seq).expect("id"))
+            .collect::<Vec<_>>();
+        let mut sorted = ids.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, ids);
+        let first = ids.first().expect("id");
+        assert_eq!(first.len(), 4 + 26);
+        assert!(
+            first
+                .chars()
+                .skip(4)
+                .all(|c| ALPHABET.iter().any(|&b| char::from(b) == c))
+        );
+        assert_eq!(id_sequence(first), None);
+
+        assert_eq!(id_sequence("mem-42"), Some(42));
+        assert_eq!(new_id(IdFormat::Sequential, 42).expect("id"), "mem-42");
+        assert_eq!(encode_ulid(0), "0".repeat(26));
+        assert_eq!(encode_ulid(u128::MAX), format!("7{}", "z".repeat(25)));
+        assert_eq!(IdFormat::parse(" Sequential "), Some(IdFormat::Sequential));
+    }
+}
@@ -0,0 +1,239 @@
+//! Crash safety for the JSON store file. Writes go through a temp file and keep the previous
+//! version as `<db>.bak`, every file carries a SHA-256 checksum, and opening a damaged file
+//! restores the backup instead of failing on a parse error.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use ring::digest::{SHA256, digest};
+use serde::Serialize;
+
+use crate::{Persisted, StorageError, now_ms};
+
+/// Stands in for the checksum while it is computed; as long as the hex digest.
+const CHECKSUM_PLACEHOLDER: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// What opening a store repaired, reported under `recovery` in its stats.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryReport {
+    /// Why the store file was rejected, e.g. a truncated write or a checksum mismatch.
+    pub reason: String,
+    /// Where the rejected file was moved, when there was one.
+    pub quarantined: Option<PathBuf>,
+    pub restored_from: PathBuf,
+    /// Entries in the restored snapshot.
+    pub entries: usize,
+    pub recovered_ms: u64,
+}
+
+/// `<path>.<suffix>`, keeping the store's own extension.
+pub(crate) fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Serializes `persisted` with its checksum filled in: the SHA-256 of the output with the
+/// checksum itself zeroed. `checksum` is the first field, so the first placeholder is its value.
+pub(crate) fn encode(persisted: &mut Persisted) -> Result<Vec<u8>, StorageError> {
+    persisted.checksum = Some(CHECKSUM_PLACEHOLDER.to_string());
+    let mut bytes = serde_json::to_vec_pretty(persisted)?;
+    let checksum = hex_digest(&bytes);
+    splice_checksum(&mut bytes, CHECKSUM_PLACEHOLDER, &checksum);
+    persisted.checksum = Some(checksum);
+    Ok(bytes)
+}
+
+/// Reads a store file: `Ok(None)` when there is none, `Err` with the reason when it is truncated,
+/// unreadable or fails its checksum. Files from before checksums were added pass if they parse.
+pub(crate) fn read_verified(path: &Path) -> Result<Option<Persisted>, String> {
+    let mut bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("unreadable: {err}")),
+    };
+    let persisted = match serde_json::from_slice::<Persisted>(&bytes) {
+        Ok(persisted) => persisted,
+        Err(err) if err.is_eof() => return Err(format!("truncated write: file ends after {} bytes", bytes.len())),
+        Err(err) => return Err(format!("invalid JSON: {err}")),
+    };
+    if let Some(expected) = persisted.checksum.as_deref() {
+        splice_checksum(&mut bytes, expected, CHECKSUM_PLACEHOLDER);
+        let actual = hex_digest(&bytes);
+        if actual != expected {
+            return Err(format!("checksum mismatch: expected {expected}, got {actual}"));
+        }
+    }
+    Ok(Some(persisted))
+}
+
+/// Replaces `path` with `bytes` so a crash leaves either the old or the new file: the bytes are
+/// fsynced to `<path>.tmp`, the current file becomes `<path>.bak`, and the temp file is renamed in.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), StorageError> {
+    let tmp = sidecar(path, "tmp");
+    write_synced(&tmp, bytes)?;
+    if path.exists() {
+        fs::rename(path, sidecar(path, "bak"))?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Replaces `path` with `bytes` through an fsynced `<path>.tmp` and a rename, so a crash leaves
+/// either the old or the new file. Unlike the store file no backup is kept.
+pub fn atomic_write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = sidecar(path, "tmp");
+    write_synced(&tmp, bytes)?;
+    fs::rename(&tmp, path)
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Loads the store at `path`. A damaged file is moved aside to `<path>.corrupt-<ms>` and the
+/// store comes back from `<path>.bak`, which is one write behind; a missing file with a backup
+/// next to it means a write stopped between the two renames. Fails when no good snapshot is left.
+pub(crate) fn load(path: &Path) -> Result<(Persisted, Option<RecoveryReport>), StorageError> {
+    let backup = sidecar(path, "bak");
+    let reason = match read_verified(path) {
+        Ok(Some(persisted)) => return Ok((persisted, None)),
+        Ok(None) if !backup.exists() => return Ok((Persisted::default(), None)),
+        Ok(None) => "store file missing after an interrupted write".to_string(),
+        Err(reason) => reason,
+    };
+    let restored = match read_verified(&backup) {
+        Ok(Some(restored)) => restored,
+        Ok(None) => {
+            return Err(StorageError::InvalidInput(format!(
+                "memory store {} is damaged ({reason}) and there is no backup at {}",
+                path.display(),
+                backup.display()
+            )));
+        }
+        Err(backup_reason) => {
+            return Err(StorageError::InvalidInput(format!(
+                "memory store {} is damaged ({reason}) and so is its backup {} ({backup_reason})",
+                path.display(),
+                backup.display()
+            )));
+        }
+    };
+    let recovered_ms = now_ms();
+    let quarantined = if path.exists() {
+        let target = sidecar(path, &format!("corrupt-{recovered_ms}"));
+        fs::rename(path, &target)?;
+        Some(target)
+    } else {
+        None
+    };
+    let report = RecoveryReport {
+        reason,
+        quarantined,
+        restored_from: backup,
+        entries: restored.entries.len(),
+        recovered_ms,
+    };
+    Ok((restored, Some(report)))
+}
+
+fn hex_digest(bytes: &[u8]) -> String {
+    digest(&SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Overwrites the first occurrence of `from` with `to`, which has the same length.
+fn splice_checksum(bytes: &mut [u8], from: &str, to: &str) {
+    if let Some(start) = bytes.windows(from.len()).position(|window| window == from.as_bytes())
+        && let Some(slot) = bytes.get_mut(start..start + to.len())
+    {
+        slot.copy_from_slice(to.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ChangeLog, ChangeOp, NewMemoryEntry, PersistentMemoryStore, StorageBackend};
+
+    use super::*;
+
+    fn entry(text: &str) -> NewMemoryEntry {
+        NewMemoryEntry {
+            text: text.to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: Vec::new(),
+            embedding: None,
+            embedding_model: None,
+            source: None,
+            keywords: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn damaged_store_files_come_back_from_the_backup() {
+        let path = std::env::temp_dir().join(format!("prx-integrity-{}-{}.json", std::process::id(), now_ms()));
+        let mut store = PersistentMemoryStore::open(&path).expect("open");
+        store.store(entry("first entry")).expect("store");
+        store.store(entry("second entry")).expect("store");
+        assert!(PersistentMemoryStore::open(&path).expect("reopen").recovery().is_none());
+
+        // A torn write: the file stops halfway.
+        let full = fs::read(&path).expect("read");
+        fs::write(&path, full.get(..full.len() / 2).expect("half")).expect("truncate");
+        let reopened = PersistentMemoryStore::open(&path).expect("recover");
+        let report = reopened.recovery().expect("recovery report");
+        assert!(report.reason.starts_with("truncated write"), "{}", report.reason);
+        assert_eq!(report.entries, 1);
+        assert!(report.quarantined.as_ref().is_some_and(|p| p.exists()));
+        assert_eq!(reopened.list(10).len(), 1);
+        assert!(reopened.stats()["recovery"]["restored_from"].is_string());
+
+        // Valid JSON with an edited entry fails the checksum.
+        let mut store = reopened;
+        store.store(entry("third entry")).expect("store");
+        let edited = fs::read_to_string(&path)
+            .expect("read")
+            .replace("third entry", "thirD entry");
+        fs::write(&path, edited).expect("edit");
+        let reopened = PersistentMemoryStore::open(&path).expect("recover");
+        assert!(
+            reopened
+                .recovery()
+                .expect("report")
+                .reason
+                .starts_with("checksum mismatch")
+        );
+
+        // With the backup damaged too, opening fails with both reasons instead of a bare parse error.
+        fs::write(&path, b"{\"entries\": [").expect("truncate");
+        fs::write(sidecar(&path, "bak"), b"").expect("truncate backup");
+        let err = PersistentMemoryStore::open(&path).err().expect("unrecoverable");
+        assert!(err.to_string().contains("so is its backup"), "{err}");
+
+        let log_path = sidecar(&path, "changes.jsonl");
+        let mut log = ChangeLog::open(&log_path, None).expect("log");
+        log.append(ChangeOp::Delete, "mem-1", None, None).expect("append");
+        let mut raw = fs::read(&log_path).expect("read log");
+        raw.extend_from_slice(b"{\"seq\":2,\"op\":\"del");
+        fs::write(&log_path, &raw).expect("tear log");
+        let reopened = ChangeLog::open(&log_path, None).expect("reopen log");
+        assert_eq!(reopened.latest_seq(), 1);
+        assert_eq!(reopened.repair().map(|r| r.truncated_bytes), Some(18));
+        assert!(fs::read(&log_path).expect("read log").ends_with(b"\n"));
+
+        for suffix in ["bak", "changes.jsonl"] {
+            let _ = fs::remove_file(sidecar(&path, suffix));
+        }
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,278 @@
+//! Schema upgrades and secondary indexes for the LanceDB table: columns newer releases write are
+//! added ALTER TABLE style, `scope` and `category` get scalar indexes, and once enough rows carry
+//! plaintext embeddings a `vector` column is backfilled and indexed with IVF-PQ.
+//!
+//! Building indexes can take a while on a large table, so the server runs
+//! [`LanceDbBackend::ensure_indexes`] in the background after opening, not while opening.
+
+use std::sync::Arc;
+
+use arrow_array::RecordBatchIterator;
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::index::Index;
+use lancedb::index::scalar::{BTreeIndexBuilder, BitmapIndexBuilder};
+use lancedb::index::vector::IvfPqIndexBuilder;
+use lancedb::query::{ExecutableQuery, QueryBase, VectorQuery};
+use lancedb::table::{NewColumnTransform, OptimizeAction, OptimizeOptions};
+use serde_json::{Value, json};
+
+use crate::{LANCE_RECALL_SCAN, LanceDbBackend, RecallQuery, StorageError};
+
+/// Columns added after the original eight, oldest first, with the SQL that fills existing rows.
+const ADDED_COLUMNS: [(&str, &str); 3] = [
+    ("embedding_model", "CAST(NULL AS VARCHAR)"),
+    ("source_json", "CAST(NULL AS VARCHAR)"),
+    ("keywords_json", "CAST(NULL AS VARCHAR)"),
+];
+
+/// Rows with embeddings needed to train the IVF-PQ index.
+const VECTOR_INDEX_MIN_ROWS: usize = 256;
+
+/// Rows upserted per `merge_insert` while backfilling the `vector` column.
+const BACKFILL_BATCH_ROWS: usize = 1024;
+
+const HAS_EMBEDDING: &str = "embedding_json != '[]'";
+
+fn lance_error(what: &str) -> impl Fn(lancedb::Error) -> StorageError + '_ {
+    move |e| StorageError::InvalidInput(format!("lancedb {what} failed: {e}"))
+}
+
+impl LanceDbBackend {
+    /// Adds every column in [`ADDED_COLUMNS`] the table lacks and returns their names.
+    pub(crate) fn upgrade_schema(&mut self) -> Result<Vec<String>, StorageError> {
+        let missing = ADDED_COLUMNS
+            .iter()
+            .filter(|(name, _)| self.schema.field_with_name(name).is_err())
+            .map(|(name, sql)| ((*name).to_string(), (*sql).to_string()))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(Vec::new());
+        }
+        let names = missing.iter().map(|(name, _)| name.clone()).collect();
+        self.rt
+            .block_on(async {
+                self.table
+                    .add_columns(NewColumnTransform::SqlExpressions(missing), None)
+                    .await
+            })
+            .map_err(lance_error("add columns"))?;
+        self.refresh_schema()?;
+        Ok(names)
+    }
+
+    fn refresh_schema(&mut self) -> Result<(), StorageError> {
+        self.schema = self
+            .rt
+            .block_on(async { self.table.schema().await })
+            .map_err(lance_error("schema read"))?;
+        Ok(())
+    }
+
+    /// Dimension of the `vector` column, when the table has one.
+    fn vector_dim(&self) -> Option<i32> {
+        match self.schema.field_with_name("vector").ok()?.data_type() {
+            DataType::FixedSizeList(_, dim) => Some(*dim),
+            _ => None,
+        }
+    }
+
+    /// A nearest-neighbour query for recall on tables too large to read whole; `None` when the
+    /// table fits in one scan or has no vector column matching the query embedding.
+    pub(crate) fn nearest_query(&self, query: &RecallQuery) -> Option<VectorQuery> {
+        let embedding = query.query_embedding.as_ref()?;
+        let dim = self.vector_dim()?;
+        if self.cipher.is_some() || i32::try_from(embedding.len()).ok()? != dim {
+            return None;
+        }
+        let rows = self.rt.block_on(async { self.table.count_rows(None).await }).ok()?;
+        if rows <= LANCE_RECALL_SCAN {
+            return None;
+        }
+        self.table.query().nearest_to(embedding.as_slice()).ok()
+    }
+
+    /// Adds an all-null `vector` column sized like the first stored embedding. Skipped while the
+    /// table has too few embeddings to train an index.
+    fn add_vector_column(&mut self) -> Result<bool, StorageError> {
+        let embedded = self
+            .rt
+            .block_on(async { self.table.count_rows(Some(HAS_EMBEDDING.to_string())).await })
+            .map_err(lance_error("count"))?;
+        if embedded < VECTOR_INDEX_MIN_ROWS {
+            return Ok(false);
+        }
+        let sample = self.table.query().only_if(HAS_EMBEDDING).limit(1);
+        let stream = self
+            .rt
+            .block_on(async { sample.execute().await })
+            .map_err(lance_error("query"))?;
+        let batches = self
+            .rt
+            .block_on(async { stream.try_collect::<Vec<_>>().await })
+            .map_err(lance_error("query"))?;
+        let Some(dim) = self
+            .parse_entries_from_batches(&batches)
+            .into_iter()
+            .find_map(|e| e.embedding_dim)
+            .and_then(|dim| i32::try_from(dim).ok())
+        else {
+            return Ok(false);
+        };
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let column = Schema::new(vec![Field::new("vector", DataType::FixedSizeList(item, dim), true)]);
+        self.rt
+            .block_on(async {
+                self.table
+                    .add_columns(NewColumnTransform::AllNulls(Arc::new(column)), None)
+                    .await
+            })
+            .map_err(lance_error("add columns"))?;
+        self.refresh_schema()?;
+        self.schema_upgrades.push("vector".to_string());
+        Ok(true)
+    }
+
+    /// Fills the `vector` column of every row whose embedding fits it. Rows are upserted by id in
+    /// batches with `merge_insert`, so each batch lands whole or not at all and no row is ever
+    /// missing from the table. Runs until the vector index exists, so an interrupted backfill is
+    /// redone by the next [`Self::ensure_indexes`].
+    fn backfill_vectors(&self, dim: i32) -> Result<(), StorageError> {
+        let entries = self
+            .parse_entries_from_batches(&self.all_batches()?)
+            .into_iter()
+            .filter(|e| e.embedding_dim.and_then(|d| i32::try_from(d).ok()) == Some(dim))
+            .collect::<Vec<_>>();
+        for chunk in entries.chunks(BACKFILL_BATCH_ROWS) {
+            let batch = self.entries_batch(chunk)?;
+            let schema = batch.schema();
+            let reader = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema);
+            let mut merge = self.table.merge_insert(&["id"]);
+            merge.when_matched_update_all(None);
+            self.rt
+                .block_on(async { merge.execute(Box::new(reader)).await })
+                .map_err(lance_error("merge insert"))?;
+        }
+        Ok(())
+    }
+
+    /// Builds the scalar indexes, the vector index once it can be trained, and refreshes existing
+    /// indexes with rows written since they were built. An empty table is left alone.
+    pub fn ensure_indexes(&mut self) -> Result<Value, StorageError> {
+        let rows = self
+            .rt
+            .block_on(async { self.table.count_rows(None).await })
+            .map_err(lance_error("count"))?;
+        if rows == 0 {
+            return self.index_status();
+        }
+        let indexed = self.indexed_columns()?;
+        let scalar = [
+            ("scope", Index::BTree(BTreeIndexBuilder::default())),
+            ("category", Index::Bitmap(BitmapIndexBuilder::default())),
+        ];
+        for (column, index) in scalar {
+            if !indexed.iter().any(|c| c == column) {
+                self.rt
+                    .block_on(async { self.table.create_index(&[column], index).execute().await })
+                    .map_err(lance_error("create index"))?;
+            }
+        }
+        let has_vector = self.vector_dim().is_some() || (self.cipher.is_none() && self.add_vector_column()?);
+        if let Some(dim) = self.vector_dim().filter(|_| has_vector && self.cipher.is_none())
+            && !indexed.iter().any(|c| c == "vector")
+        {
+            self.backfill_vectors(dim)?;
+            self.rt
+                .block_on(async {
+                    self.table
+                        .create_index(&["vector"], Index::IvfPq(IvfPqIndexBuilder::default()))
+                        .execute()
+                        .await
+                })
+                .map_err(lance_error("create index"))?;
+        }
+        self.rt
+            .block_on(async {
+                self.table
+                    .optimize(OptimizeAction::Index(OptimizeOptions::default()))
+                    .await
+            })
+            .map_err(lance_error("optimize"))?;
+        self.index_status()
+    }
+
+    fn indexed_columns(&self) -> Result<Vec<String>, StorageError> {
+        let indices = self
+            .rt
+            .block_on(async { self.table.list_indices().await })
+            .map_err(lance_error("list indices"))?;
+        Ok(indices.into_iter().flat_map(|index| index.columns).collect())
+    }
+
+    pub fn index_status(&self) -> Result<Value, StorageError> {
+        let indices = self
+            .rt
+            .block_on(async { self.table.list_indices().await })
+            .map_err(lance_error("list indices"))?;
+        let mut indexes = Vec::with_capacity(indices.len());
+        let mut unindexed_total = 0;
+        for index in indices {
+            let stats = self
+                .rt
+                .block_on(async { self.table.index_stats(&index.name).await })
+                .map_err(lance_error("index stats"))?;
+            let unindexed = stats.as_ref().map_or(0, |s| s.num_unindexed_rows);
+            unindexed_total += unindexed;
+            indexes.push(json!({
+                "name": index.name,
+                "columns": index.columns,
+                "type": format!("{:?}", index.index_type),
+                "indexed_rows": stats.as_ref().map(|s| s.num_indexed_rows),
+                "unindexed_rows": unindexed,
+            }));
+        }
+        let covered = |column: &str| {
+            indexes.iter().any(|i| {
+                i.get("columns")
+                    .and_then(Value::as_array)
+                    .is_some_and(|cols| cols.iter().any(|c| c == column))
+            })
+        };
+        let vector = if self.cipher.is_some() {
+            "disabled: embeddings are encrypted at rest".to_string()
+        } else if covered("vector") {
+            "indexed".to_string()
+        } else {
+            let embedded = self
+                .rt
+                .block_on(async { self.table.count_rows(Some(HAS_EMBEDDING.to_string())).await })
+                .map_err(lance_error("count"))?;
+            if embedded < VECTOR_INDEX_MIN_ROWS {
+                format!("waiting: {embedded} of {VECTOR_INDEX_MIN_ROWS} rows have embeddings")
+            } else {
+                "missing".to_string()
+            }
+        };
+        let missing = ["scope", "category"]
+            .into_iter()
+            .filter(|c| !covered(c))
+            .chain((vector == "missing").then_some("vector"))
+            .collect::<Vec<_>>();
+        let health = if !missing.is_empty() {
+            "missing"
+        } else if unindexed_total > 0 {
+            "stale"
+        } else {
+            "healthy"
+        };
+        Ok(json!({
+            "backend": "lancedb",
+            "health": health,
+            "indexes": indexes,
+            "missing": missing,
+            "unindexed_rows": unindexed_total,
+            "vector": vector,
+        }))
+    }
+}
@@ -1,15 +1,59 @@
 use std::cmp::{Ordering, Reverse};
-use std::collections::{BinaryHeap, HashSet, hash_map::DefaultHasher};
+use std::collections::{BinaryHeap, HashMap, HashSet, hash_map::DefaultHasher};
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+mod changes;
+mod crypto;
+mod ids;
+mod integrity;
+#[cfg(feature = "lancedb-backend")]
+mod lance_index;
+mod migrate;
+#[cfg(feature = "postgres-backend")]
+mod postgres_backend;
+#[cfg(feature = "qdrant-backend")]
+mod qdrant_backend;
+mod query_expansion;
+mod query_syntax;
+#[cfg(feature = "redis-backend")]
+mod redis_backend;
+#[cfg(feature = "s3-backend")]
+mod s3;
+mod tiered;
+mod tokenizer;
+
+pub use changes::{ChangeEvent, ChangeLog, ChangeLogRepair, ChangeOp, ChangePage, ChangeRecordingBackend};
+pub use crypto::FieldCipher;
+pub use ids::{IdFormat, id_sequence, new_id};
+pub use integrity::{RecoveryReport, atomic_write};
+pub use migrate::{MigrationOptions, MigrationReport, MigrationStep, SCHEMA_VERSION};
+#[cfg(feature = "postgres-backend")]
+pub use postgres_backend::{PostgresBackend, PostgresConfig};
+#[cfg(feature = "qdrant-backend")]
+pub use qdrant_backend::{QdrantBackend, QdrantConfig};
+pub use query_expansion::{SynonymTable, expand_term, expansion_keywords, register_synonyms, stem, with_synonyms};
+pub use query_syntax::{ParsedQuery, QueryField, QueryTerm, QueryTermKind, parse_query};
+#[cfg(feature = "redis-backend")]
+pub use redis_backend::RedisBackend;
+#[cfg(feature = "s3-backend")]
+pub use s3::{S3Backend, S3Config};
+pub use tiered::{TieredBackend, TieredConfig};
+use tokenizer::tokenize;
+pub use tokenizer::{RankingConfig, TokenizerMode, ranking_config, set_ranking_config, with_ranking_config};
+
+#[cfg(feature = "lancedb-backend")]
+use arrow_array::types::Float32Type;
 #[cfg(feature = "lancedb-backend")]
-use arrow_array::{Array, Float32Array, RecordBatch, RecordBatchIterator, StringArray, UInt64Array};
+use arrow_array::{
+    Array, ArrayRef, FixedSizeListArray, Float32Array, RecordBatch, RecordBatchIterator, StringArray, UInt64Array,
+};
 #[cfg(feature = "lancedb-backend")]
 use arrow_schema::{DataType, Field, Schema, SchemaRef};
 #[cfg(feature = "lancedb-backend")]
@@ -18,8 +62,6 @@ use futures::TryStreamExt;
 use lancedb::Table;
 #[cfg(feature = "lancedb-backend")]
 use lancedb::query::{ExecutableQuery, QueryBase};
-#[cfg(feature = "lancedb-backend")]
-use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MemoryEntry {
@@ -32,6 +74,60 @@ pub struct MemoryEntry {
     pub timestamp_ms: u64,
     #[serde(default)]
     pub embedding: Option<Vec<f32>>,
+    /// Provider model that produced `embedding`; `None` for entries written before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding_dim: Option<usize>,
+    /// Where the entry came from, for auditing facts that turn out to be wrong.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<MemorySource>,
+    /// Aliases and synonyms of the text's words, stored at write time so lexical recall can match
+    /// paraphrases without embeddings. Empty unless the governance profile enables expansion.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
+/// Provenance recorded at store or import time. Every field is optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MemorySource {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+impl MemorySource {
+    /// Trims every field and drops blank ones; `None` when nothing is left.
+    pub fn normalized(self) -> Option<Self> {
+        let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        let source = Self {
+            url: clean(self.url),
+            tool: clean(self.tool),
+            conversation_id: clean(self.conversation_id),
+            commit: clean(self.commit),
+        };
+        (source != Self::default()).then_some(source)
+    }
+
+    /// Whether `self` satisfies every field set in `filter`: `url` and `commit` match by prefix
+    /// (so a site or an abbreviated hash works), `tool` and `conversation_id` exactly.
+    pub fn matches(&self, filter: &Self) -> bool {
+        let exact =
+            |want: &Option<String>, have: &Option<String>| want.as_ref().is_none_or(|w| have.as_ref() == Some(w));
+        let prefix = |want: &Option<String>, have: &Option<String>| {
+            want.as_ref()
+                .is_none_or(|w| have.as_ref().is_some_and(|h| h.starts_with(w.as_str())))
+        };
+        prefix(&filter.url, &self.url)
+            && exact(&filter.tool, &self.tool)
+            && exact(&filter.conversation_id, &self.conversation_id)
+            && prefix(&filter.commit, &self.commit)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -42,6 +138,9 @@ pub struct NewMemoryEntry {
     pub importance: f32,
     pub tags: Vec<String>,
     pub embedding: Option<Vec<f32>>,
+    pub embedding_model: Option<String>,
+    pub source: Option<MemorySource>,
+    pub keywords: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +152,53 @@ pub struct RecallQuery {
     pub limit: usize,
     pub vector_weight: Option<f32>,
     pub lexical_weight: Option<f32>,
+    /// Opt-in MMR diversity in `[0, 1]`; replaces the prefix-signature dedup when set.
+    pub diversity: Option<f32>,
+    /// How lexical and vector signals are combined; defaults to [`FusionMode::Linear`].
+    pub fusion: Option<FusionMode>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FusionMode {
+    /// Weighted sum of lexical base and rescaled cosine.
+    #[default]
+    Linear,
+    /// Weighted reciprocal rank fusion of the lexical and vector rankings.
+    Rrf,
+}
+
+impl FusionMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "linear" => Some(Self::Linear),
+            "rrf" => Some(Self::Rrf),
+            _ => None,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Rrf => "rrf",
+        }
+    }
+}
+
+const RRF_K: f32 = 60.0;
+
+/// Lexical base given to every match of a query that has filters but no words to rank by.
+const FILTER_ONLY_BASE: f32 = 0.5;
+
+/// Term frequency of a query word found only among an entry's expansion keywords.
+const KEYWORD_TERM_FREQUENCY: f32 = 0.6;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MemoryRelation {
+    pub from_id: String,
+    pub relation: String,
+    pub to_id: String,
+    pub timestamp_ms: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -61,12 +207,143 @@ pub struct RecallResult {
     pub score: f32,
 }
 
-pub trait StorageBackend: Send {
+/// Immutable view of a store at one generation.
+///
+/// Cloning is cheap and later writes never show through, so list and recall over a snapshot see
+/// one consistent set of entries even while maintenance rewrites the store.
+#[derive(Debug, Clone, Default)]
+pub struct StoreSnapshot {
+    generation: u64,
+    entries: Arc<Vec<MemoryEntry>>,
+}
+
+impl StoreSnapshot {
+    /// `entries` in insertion order, oldest first.
+    pub fn new(generation: u64, entries: Vec<MemoryEntry>) -> Self {
+        Self {
+            generation,
+            entries: Arc::new(entries),
+        }
+    }
+
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn entries(&self) -> &[MemoryEntry] {
+        &self.entries
+    }
+
+    /// Newest first, like [`StorageBackend::list`].
+    pub fn list(&self, limit: usize) -> Vec<MemoryEntry> {
+        self.entries.iter().rev().take(limit.max(1)).cloned().collect()
+    }
+
+    pub fn recall(&self, query: RecallQuery) -> Vec<RecallResult> {
+        recall_entries(&self.entries, query)
+    }
+}
+
+/// `Sync` so a server can share one backend behind a read-write lock.
+pub trait StorageBackend: Send + Sync {
     fn store(&mut self, new_entry: NewMemoryEntry) -> Result<MemoryEntry, StorageError>;
     fn recall(&self, query: RecallQuery) -> Vec<RecallResult>;
     fn forget_by_id(&mut self, id: &str) -> Result<bool, StorageError>;
     fn list(&self, limit: usize) -> Vec<MemoryEntry>;
     fn stats(&self) -> serde_json::Value;
+
+    /// Consistent view of every entry. The default materializes `list` at generation 0 for
+    /// backends that do not track generations.
+    fn snapshot(&self) -> StoreSnapshot {
+        let mut entries = self.list(usize::MAX);
+        entries.reverse();
+        StoreSnapshot::new(0, entries)
+    }
+
+    /// Forgets `ids` as one change and returns the ids that were removed. Backends with
+    /// generations publish the result as a single new generation.
+    fn forget_batch(&mut self, ids: &[String]) -> Result<Vec<String>, StorageError> {
+        let mut removed = Vec::new();
+        for id in ids {
+            if self.forget_by_id(id)? {
+                removed.push(id.clone());
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Rewrites `id` as `new_entry` under a new id and returns it, or `None` when `id` is missing.
+    /// Change feeds record this as one `update` instead of a delete plus a store.
+    fn replace(&mut self, id: &str, new_entry: NewMemoryEntry) -> Result<Option<MemoryEntry>, StorageError> {
+        if !self.forget_by_id(id)? {
+            return Ok(None);
+        }
+        self.store(new_entry).map(Some)
+    }
+
+    /// Up to `limit` change events with `seq >= from`. Only backends wrapped in a
+    /// [`ChangeRecordingBackend`] keep a change log.
+    fn changes(&self, _from: u64, _limit: usize) -> Result<ChangePage, StorageError> {
+        Err(StorageError::InvalidInput("change log is not enabled".to_string()))
+    }
+
+    /// Applies change events recorded by another store, keeping their ids. Followers use this to
+    /// mirror a leader's entries.
+    fn apply_changes(&mut self, _events: &[ChangeEvent]) -> Result<(), StorageError> {
+        Err(StorageError::InvalidInput(
+            "replication is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Adds a directed `from -> relation -> to` edge. Returns `false` when it already exists.
+    fn link(&mut self, _from_id: &str, _relation: &str, _to_id: &str) -> Result<bool, StorageError> {
+        Err(StorageError::InvalidInput(
+            "relations are not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Removes matching edges; `relation: None` removes every edge between the pair.
+    fn unlink(&mut self, _from_id: &str, _relation: Option<&str>, _to_id: &str) -> Result<usize, StorageError> {
+        Ok(0)
+    }
+
+    /// Returns every edge where `id` is either endpoint.
+    fn relations_for(&self, _id: &str) -> Vec<MemoryRelation> {
+        Vec::new()
+    }
+
+    /// Rewrites every entry sealed with the primary encryption key. Returns how many entries were rewritten.
+    fn rekey(&mut self) -> Result<usize, StorageError> {
+        Err(StorageError::InvalidInput(
+            "encryption is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Secondary indexes and how many rows each has yet to take in. `health` is `healthy`,
+    /// `stale` (rows written since the last build), `missing`, or `scan` for backends without indexes.
+    fn index_status(&self) -> Result<serde_json::Value, StorageError> {
+        Ok(serde_json::json!({"health": "scan", "indexes": []}))
+    }
+
+    /// Creates missing indexes and folds unindexed rows into existing ones, then reports the status.
+    fn ensure_indexes(&mut self) -> Result<serde_json::Value, StorageError> {
+        self.index_status()
+    }
+
+    /// Forces written data to disk before shutdown. Writes already reach the backend when each call
+    /// returns, so only backends with local files have anything left to sync.
+    fn flush(&mut self) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+/// `fsync`s `path`; a file that was never written is fine.
+pub(crate) fn sync_file(path: &Path) -> Result<(), StorageError> {
+    match fs::File::open(path) {
+        Ok(file) => Ok(file.sync_all()?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
 }
 
 #[derive(Debug, Error)]
@@ -81,17 +358,70 @@ pub enum StorageError {
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct Persisted {
-    entries: Vec<MemoryEntry>,
+    /// Written first; see [`integrity::encode`]. Missing in files from older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checksum: Option<String>,
+    /// Format version, see [`migrate`]; 0 for files written before it was recorded.
+    #[serde(default)]
+    schema_version: u32,
+    entries: Vec<PersistedEntry>,
+    #[serde(default)]
+    relations: Vec<MemoryRelation>,
+}
+
+/// An entry as written to disk; with a cipher, `text` is sealed and the embedding moves to `sealed_embedding`.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedEntry {
+    #[serde(flatten)]
+    entry: MemoryEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_embedding: Option<String>,
 }
 
 pub struct PersistentMemoryStore {
     path: PathBuf,
-    entries: Vec<MemoryEntry>,
+    /// Copy-on-write: a write clones the list only while a [`StoreSnapshot`] still holds it.
+    entries: Arc<Vec<MemoryEntry>>,
+    /// Bumped on every change to `entries`.
+    generation: u64,
+    relations: Vec<MemoryRelation>,
     next_id: u64,
+    id_format: IdFormat,
+    cipher: Option<FieldCipher>,
+    /// Set when opening had to restore the backup.
+    recovery: Option<RecoveryReport>,
+    /// Version written on the next persist; stays behind [`SCHEMA_VERSION`] after a dry run.
+    schema_version: u32,
+    migration: Option<MigrationReport>,
 }
 
 impl PersistentMemoryStore {
     pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Self::open_with_cipher(path, None)
+    }
+
+    /// Hands out new ids in `format`; [`IdFormat::Ulid`] unless set.
+    #[must_use]
+    pub const fn with_id_format(mut self, format: IdFormat) -> Self {
+        self.id_format = format;
+        self
+    }
+
+    /// Opens the store, encrypting entry text and embeddings at rest when `cipher` is set.
+    /// Opening an encrypted store without a cipher fails instead of exposing sealed text.
+    /// A truncated or corrupted file is set aside and the store restored from its backup;
+    /// [`Self::recovery`] says what happened. Files in an older format are upgraded, keeping a copy.
+    pub fn open_with_cipher(path: impl AsRef<Path>, cipher: Option<FieldCipher>) -> Result<Self, StorageError> {
+        Self::open_with_options(path, cipher, MigrationOptions::default())
+    }
+
+    /// Like [`Self::open_with_cipher`], choosing how an outdated file is migrated; [`Self::migration`]
+    /// reports the steps. After a dry run the entries stay as loaded and writes keep the old version.
+    pub fn open_with_options(
+        path: impl AsRef<Path>,
+        cipher: Option<FieldCipher>,
+        migration: MigrationOptions,
+    ) -> Result<Self, StorageError> {
         let path = path.as_ref().to_path_buf();
         if let Some(parent) = path.parent() {
             if !parent.as_os_str().is_empty() {
@@ -99,27 +429,68 @@ impl PersistentMemoryStore {
             }
         }
 
+        let (mut persisted, recovery) = integrity::load(&path)?;
+        if !path.exists() && recovery.is_none() {
+            persisted.schema_version = SCHEMA_VERSION;
+        }
         if !path.exists() {
-            let persisted = Persisted::default();
-            let bytes = serde_json::to_vec_pretty(&persisted)?;
-            fs::write(&path, bytes)?;
+            integrity::write_atomic(&path, &integrity::encode(&mut persisted)?)?;
         }
+        let mut entries = Vec::with_capacity(persisted.entries.len());
+        for PersistedEntry {
+            mut entry,
+            sealed_embedding,
+        } in persisted.entries
+        {
+            match &cipher {
+                Some(cipher) => cipher.open_entry(&mut entry, sealed_embedding.as_deref())?,
+                None if FieldCipher::is_sealed(&entry.text) || sealed_embedding.is_some() => {
+                    return Err(StorageError::InvalidInput(
+                        "memory store is encrypted but no encryption key is configured".to_string(),
+                    ));
+                }
+                None => {}
+            }
+            entries.push(entry);
+        }
+        let migration = migrate::run(&path, persisted.schema_version, &mut entries, migration)?;
+        let schema_version = match &migration {
+            Some(report) if !report.dry_run => report.to_version,
+            _ => persisted.schema_version,
+        };
+        let next_id = entries.iter().filter_map(|e| id_sequence(&e.id)).max().unwrap_or(0) + 1;
 
-        let bytes = fs::read(&path)?;
-        let persisted: Persisted = serde_json::from_slice(&bytes)?;
-        let next_id = persisted
-            .entries
-            .iter()
-            .filter_map(|e| e.id.strip_prefix("mem-")?.parse::<u64>().ok())
-            .max()
-            .unwrap_or(0)
-            + 1;
-
-        Ok(Self {
+        let store = Self {
             path,
-            entries: persisted.entries,
+            entries: Arc::new(entries),
+            generation: 1,
+            relations: persisted.relations,
             next_id,
-        })
+            id_format: IdFormat::default(),
+            cipher,
+            recovery,
+            schema_version,
+            migration,
+        };
+        if store.migration.as_ref().is_some_and(|report| !report.dry_run) {
+            store.persist()?;
+        }
+        Ok(store)
+    }
+
+    /// What opening the store repaired, if anything.
+    pub const fn recovery(&self) -> Option<&RecoveryReport> {
+        self.recovery.as_ref()
+    }
+
+    /// Format version the store is written with.
+    pub const fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// What opening the store upgraded, or would have in a dry run.
+    pub const fn migration(&self) -> Option<&MigrationReport> {
+        self.migration.as_ref()
     }
 
     pub fn list(&self, limit: usize) -> Vec<MemoryEntry> {
@@ -130,54 +501,196 @@ impl PersistentMemoryStore {
     pub fn stats(&self) -> serde_json::Value {
         serde_json::json!({
             "count": self.entries.len(),
+            "relations": self.relations.len(),
+            "generation": self.generation,
             "path": self.path,
+            "recovery": self.recovery,
+            "schema_version": self.schema_version,
+            "migration": self.migration,
         })
     }
 
+    pub fn snapshot(&self) -> StoreSnapshot {
+        StoreSnapshot {
+            generation: self.generation,
+            entries: Arc::clone(&self.entries),
+        }
+    }
+
     pub fn store(&mut self, new_entry: NewMemoryEntry) -> Result<MemoryEntry, StorageError> {
         if new_entry.text.trim().is_empty() {
             return Err(StorageError::InvalidInput("text cannot be empty".to_string()));
         }
 
         let entry = MemoryEntry {
-            id: format!("mem-{}", self.next_id),
+            id: new_id(self.id_format, self.next_id)?,
             text: new_entry.text.to_lowercase(),
             category: new_entry.category,
             scope: new_entry.scope,
             importance: new_entry.importance.clamp(0.0, 1.0),
             tags: new_entry.tags.into_iter().map(|t| t.to_lowercase()).collect(),
             timestamp_ms: now_ms(),
+            embedding_dim: new_entry.embedding.as_ref().map(Vec::len),
+            embedding_model: new_entry.embedding.as_ref().and(new_entry.embedding_model),
             embedding: new_entry.embedding,
+            source: new_entry.source,
+            keywords: new_entry.keywords,
         };
 
         self.next_id += 1;
-        self.entries.push(entry.clone());
+        Arc::make_mut(&mut self.entries).push(entry.clone());
+        self.generation += 1;
         self.persist()?;
 
         Ok(entry)
     }
 
     pub fn forget_by_id(&mut self, id: &str) -> Result<bool, StorageError> {
-        let before = self.entries.len();
-        self.entries.retain(|e| e.id != id);
-        let changed = self.entries.len() != before;
-        if changed {
+        Ok(!self.forget_batch(&[id.to_string()])?.is_empty())
+    }
+
+    /// Builds the next generation without `ids` and swaps it in with one write, so readers see
+    /// either all of the removals or none of them.
+    pub fn forget_batch(&mut self, ids: &[String]) -> Result<Vec<String>, StorageError> {
+        let doomed: HashSet<&str> = ids.iter().map(String::as_str).collect();
+        let removed: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|e| doomed.contains(e.id.as_str()))
+            .map(|e| e.id.clone())
+            .collect();
+        if removed.is_empty() {
+            return Ok(removed);
+        }
+        let next: Vec<MemoryEntry> = self
+            .entries
+            .iter()
+            .filter(|e| !doomed.contains(e.id.as_str()))
+            .cloned()
+            .collect();
+        self.entries = Arc::new(next);
+        self.generation += 1;
+        self.relations
+            .retain(|r| !doomed.contains(r.from_id.as_str()) && !doomed.contains(r.to_id.as_str()));
+        self.persist()?;
+        Ok(removed)
+    }
+
+    /// Applies `events` in order as one generation with one write. Stored and updated entries
+    /// keep the id they have on the recording store and overwrite an entry with the same id.
+    pub fn apply_changes(&mut self, events: &[ChangeEvent]) -> Result<(), StorageError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let entries = Arc::make_mut(&mut self.entries);
+        let mut removed = HashSet::new();
+        for event in events {
+            let doomed = match event.op {
+                ChangeOp::Delete => Some(event.id.as_str()),
+                ChangeOp::Store | ChangeOp::Update => event.previous_id.as_deref(),
+            };
+            if let Some(doomed) = doomed {
+                entries.retain(|e| e.id != doomed);
+                removed.insert(doomed.to_string());
+            }
+            let Some(entry) = event.entry.as_ref().filter(|_| event.op != ChangeOp::Delete) else {
+                continue;
+            };
+            if let Some(seq) = id_sequence(&entry.id) {
+                self.next_id = self.next_id.max(seq + 1);
+            }
+            match entries.iter_mut().find(|e| e.id == entry.id) {
+                Some(existing) => *existing = entry.clone(),
+                None => entries.push(entry.clone()),
+            }
+        }
+        self.relations
+            .retain(|r| !removed.contains(&r.from_id) && !removed.contains(&r.to_id));
+        self.generation += 1;
+        self.persist()
+    }
+
+    pub fn link(&mut self, from_id: &str, relation: &str, to_id: &str) -> Result<bool, StorageError> {
+        if relation.trim().is_empty() {
+            return Err(StorageError::InvalidInput("relation cannot be empty".to_string()));
+        }
+        if from_id == to_id {
+            return Err(StorageError::InvalidInput("cannot link a memory to itself".to_string()));
+        }
+        for id in [from_id, to_id] {
+            if !self.entries.iter().any(|e| e.id == id) {
+                return Err(StorageError::InvalidInput(format!("memory id not found: {id}")));
+            }
+        }
+        if self
+            .relations
+            .iter()
+            .any(|r| r.from_id == from_id && r.relation == relation && r.to_id == to_id)
+        {
+            return Ok(false);
+        }
+        self.relations.push(MemoryRelation {
+            from_id: from_id.to_string(),
+            relation: relation.to_string(),
+            to_id: to_id.to_string(),
+            timestamp_ms: now_ms(),
+        });
+        self.persist()?;
+        Ok(true)
+    }
+
+    pub fn unlink(&mut self, from_id: &str, relation: Option<&str>, to_id: &str) -> Result<usize, StorageError> {
+        let before = self.relations.len();
+        self.relations
+            .retain(|r| !(r.from_id == from_id && r.to_id == to_id && relation.is_none_or(|rel| r.relation == rel)));
+        let removed = before - self.relations.len();
+        if removed > 0 {
             self.persist()?;
         }
-        Ok(changed)
+        Ok(removed)
+    }
+
+    pub fn relations_for(&self, id: &str) -> Vec<MemoryRelation> {
+        self.relations
+            .iter()
+            .filter(|r| r.from_id == id || r.to_id == id)
+            .cloned()
+            .collect()
     }
 
     pub fn recall(&self, query: RecallQuery) -> Vec<RecallResult> {
         recall_entries(&self.entries, query)
     }
 
+    /// Re-persists every entry, which seals all of them with the primary key.
+    pub fn rekey(&mut self) -> Result<usize, StorageError> {
+        if self.cipher.is_none() {
+            return Err(StorageError::InvalidInput("encryption is not configured".to_string()));
+        }
+        self.persist()?;
+        Ok(self.entries.len())
+    }
+
     fn persist(&self) -> Result<(), StorageError> {
-        let persisted = Persisted {
-            entries: self.entries.clone(),
+        let mut entries = Vec::with_capacity(self.entries.len());
+        for entry in self.entries.iter() {
+            let mut entry = entry.clone();
+            let sealed_embedding = match &self.cipher {
+                Some(cipher) => cipher.seal_entry(&mut entry)?,
+                None => None,
+            };
+            entries.push(PersistedEntry {
+                entry,
+                sealed_embedding,
+            });
+        }
+        let mut persisted = Persisted {
+            checksum: None,
+            schema_version: self.schema_version,
+            entries,
+            relations: self.relations.clone(),
         };
-        let bytes = serde_json::to_vec_pretty(&persisted)?;
-        fs::write(&self.path, bytes)?;
-        Ok(())
+        integrity::write_atomic(&self.path, &integrity::encode(&mut persisted)?)
     }
 }
 
@@ -201,24 +714,88 @@ impl StorageBackend for PersistentMemoryStore {
     fn stats(&self) -> serde_json::Value {
         Self::stats(self)
     }
+
+    fn snapshot(&self) -> StoreSnapshot {
+        Self::snapshot(self)
+    }
+
+    fn forget_batch(&mut self, ids: &[String]) -> Result<Vec<String>, StorageError> {
+        Self::forget_batch(self, ids)
+    }
+
+    fn apply_changes(&mut self, events: &[ChangeEvent]) -> Result<(), StorageError> {
+        Self::apply_changes(self, events)
+    }
+
+    fn link(&mut self, from_id: &str, relation: &str, to_id: &str) -> Result<bool, StorageError> {
+        Self::link(self, from_id, relation, to_id)
+    }
+
+    fn unlink(&mut self, from_id: &str, relation: Option<&str>, to_id: &str) -> Result<usize, StorageError> {
+        Self::unlink(self, from_id, relation, to_id)
+    }
+
+    fn relations_for(&self, id: &str) -> Vec<MemoryRelation> {
+        Self::relations_for(self, id)
+    }
+
+    fn rekey(&mut self) -> Result<usize, StorageError> {
+        Self::rekey(self)
+    }
+
+    fn flush(&mut self) -> Result<(), StorageError> {
+        sync_file(&self.path)
+    }
 }
 
 #[cfg(feature = "lancedb-backend")]
 pub struct LanceDbBackend {
     uri: String,
     table_name: String,
-    rt: tokio::runtime::Runtime,
+    rt: std::sync::Arc<tokio::runtime::Runtime>,
     table: Table,
+    /// The table's current columns; rows are written in this shape.
+    schema: SchemaRef,
+    /// Columns [`Self::upgrade_schema`] added when the table was opened.
+    schema_upgrades: Vec<String>,
     id_seq: u64,
+    id_format: IdFormat,
+    cipher: Option<FieldCipher>,
 }
 
 #[cfg(feature = "lancedb-backend")]
 impl LanceDbBackend {
     pub fn open(uri: impl Into<String>) -> Result<Self, StorageError> {
-        let uri = uri.into();
-        let table_name = "memories".to_string();
         let rt = tokio::runtime::Runtime::new()
             .map_err(|e| StorageError::InvalidInput(format!("tokio runtime init failed: {e}")))?;
+        Self::open_with_runtime(uri, std::sync::Arc::new(rt))
+    }
+
+    /// Hands out new ids in `format`; [`IdFormat::Ulid`] unless set.
+    #[must_use]
+    pub const fn with_id_format(mut self, format: IdFormat) -> Self {
+        self.id_format = format;
+        self
+    }
+
+    /// Opens the table on a runtime owned by the caller, so the backend does not spin up its own.
+    pub fn open_with_runtime(
+        uri: impl Into<String>,
+        rt: std::sync::Arc<tokio::runtime::Runtime>,
+    ) -> Result<Self, StorageError> {
+        Self::open_with_cipher(uri, rt, None)
+    }
+
+    /// Like [`Self::open_with_runtime`], sealing the `text` and `embedding_json` columns when `cipher` is set.
+    /// Tables from older releases get the columns added since. Fails when a stored row cannot be
+    /// opened with the configured keys.
+    pub fn open_with_cipher(
+        uri: impl Into<String>,
+        rt: std::sync::Arc<tokio::runtime::Runtime>,
+        cipher: Option<FieldCipher>,
+    ) -> Result<Self, StorageError> {
+        let uri = uri.into();
+        let table_name = "memories".to_string();
         let db = rt
             .block_on(async { lancedb::connect(&uri).execute().await })
             .map_err(|e| StorageError::InvalidInput(format!("lancedb connect failed: {e}")))?;
@@ -232,19 +809,175 @@ impl LanceDbBackend {
             }
         };
 
-        let count = rt
-            .block_on(async { table.count_rows(None).await })
-            .map_err(|e| StorageError::InvalidInput(format!("lancedb count failed: {e}")))?;
-
-        Ok(Self {
+        let schema = rt
+            .block_on(async { table.schema().await })
+            .map_err(|e| StorageError::InvalidInput(format!("lancedb schema read failed: {e}")))?;
+        let mut backend = Self {
             uri,
             table_name,
             rt,
             table,
-            id_seq: (count as u64) + 1,
-        })
+            schema,
+            schema_upgrades: Vec::new(),
+            id_seq: 1,
+            id_format: IdFormat::default(),
+            cipher,
+        };
+        backend.schema_upgrades = backend.upgrade_schema()?;
+        let batches = backend.all_batches()?;
+        for batch in &batches {
+            // Past the highest `mem-<n>`, not the row count, which shrinks with deletes.
+            if let Some(ids) = as_string(batch, "id") {
+                for i in 0..batch.num_rows() {
+                    if let Some(seq) = id_sequence(ids.value(i)) {
+                        backend.id_seq = backend.id_seq.max(seq + 1);
+                    }
+                }
+            }
+            let ids = as_string(batch, "id");
+            for (column, field) in [("text", "text"), ("embedding_json", "embedding")] {
+                let Some(values) = as_string(batch, column) else {
+                    continue;
+                };
+                for i in 0..batch.num_rows() {
+                    let id = ids.map_or("", |a| a.value(i));
+                    backend
+                        .open_column(values.value(i), id, field)
+                        .map_err(|e| StorageError::InvalidInput(format!("cannot open stored memory {field}: {e}")))?;
+                }
+            }
+        }
+        Ok(backend)
+    }
+
+    fn all_batches(&self) -> Result<Vec<RecordBatch>, StorageError> {
+        let rows = self
+            .rt
+            .block_on(async { self.table.count_rows(None).await })
+            .map_err(|e| StorageError::InvalidInput(format!("lancedb count failed: {e}")))?;
+        let query = self.table.query().limit(rows.max(1));
+        let stream = self
+            .rt
+            .block_on(async { query.execute().await })
+            .map_err(|e| StorageError::InvalidInput(format!("lancedb query failed: {e}")))?;
+        self.rt
+            .block_on(async { stream.try_collect::<Vec<_>>().await })
+            .map_err(|e| StorageError::InvalidInput(format!("lancedb query failed: {e}")))
+    }
+
+    /// Opens field `field` of row `id`; without a cipher, sealed values are an error.
+    fn open_column(&self, stored: &str, id: &str, field: &str) -> Result<String, StorageError> {
+        match &self.cipher {
+            Some(cipher) => cipher.open(stored, id, field),
+            None if FieldCipher::is_sealed(stored) => Err(StorageError::InvalidInput(
+                "memory store is encrypted but no encryption key is configured".to_string(),
+            )),
+            None => Ok(stored.to_string()),
+        }
+    }
+
+    fn seal_column(&self, plain: String, id: &str, field: &str) -> Result<String, StorageError> {
+        match &self.cipher {
+            Some(cipher) => cipher.seal(&plain, id, field),
+            None => Ok(plain),
+        }
+    }
+
+    /// One row in the table's current shape.
+    fn entry_batch(&self, entry: &MemoryEntry) -> Result<RecordBatch, StorageError> {
+        self.entries_batch(std::slice::from_ref(entry))
+    }
+
+    /// Rows in the table's current shape. `vector` stays null when an embedding is missing, has
+    /// another dimension than the column, or is sealed by the cipher.
+    fn entries_batch(&self, entries: &[MemoryEntry]) -> Result<RecordBatch, StorageError> {
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(self.schema.fields().len());
+        for field in self.schema.fields() {
+            let column: ArrayRef = match field.name().as_str() {
+                "id" => Arc::new(StringArray::from_iter_values(entries.iter().map(|e| e.id.as_str()))),
+                "text" => Arc::new(StringArray::from(
+                    entries
+                        .iter()
+                        .map(|e| self.seal_column(e.text.clone(), &e.id, "text"))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )),
+                "category" => Arc::new(StringArray::from_iter_values(
+                    entries.iter().map(|e| e.category.as_str()),
+                )),
+                "scope" => Arc::new(StringArray::from_iter_values(entries.iter().map(|e| e.scope.as_str()))),
+                "importance" => Arc::new(Float32Array::from_iter_values(entries.iter().map(|e| e.importance))),
+                "tags" => Arc::new(StringArray::from(
+                    entries
+                        .iter()
+                        .map(|e| serde_json::to_string(&e.tags))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )),
+                "timestamp_ms" => Arc::new(UInt64Array::from_iter_values(entries.iter().map(|e| e.timestamp_ms))),
+                "embedding_json" => Arc::new(StringArray::from(
+                    entries
+                        .iter()
+                        .map(|e| {
+                            let json = serde_json::to_string(e.embedding.as_deref().unwrap_or_default())?;
+                            self.seal_column(json, &e.id, "embedding")
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                )),
+                "embedding_model" => Arc::new(StringArray::from(
+                    entries.iter().map(|e| e.embedding_model.clone()).collect::<Vec<_>>(),
+                )),
+                "source_json" => Arc::new(StringArray::from(
+                    entries
+                        .iter()
+                        .map(|e| e.source.as_ref().map(serde_json::to_string).transpose())
+                        .collect::<Result<Vec<_>, _>>()?,
+                )),
+                "keywords_json" => Arc::new(StringArray::from(
+                    entries
+                        .iter()
+                        .map(|e| {
+                            if e.keywords.is_empty() {
+                                Ok(None)
+                            } else {
+                                self.seal_column(serde_json::to_string(&e.keywords)?, &e.id, "keywords")
+                                    .map(Some)
+                            }
+                        })
+                        .collect::<Result<Vec<_>, StorageError>>()?,
+                )),
+                "vector" => {
+                    let dim = match field.data_type() {
+                        DataType::FixedSizeList(_, dim) => *dim,
+                        _ => 0,
+                    };
+                    let values = entries.iter().map(|e| {
+                        e.embedding
+                            .as_ref()
+                            .filter(|_| self.cipher.is_none())
+                            .filter(|v| i32::try_from(v.len()).is_ok_and(|len| len == dim))
+                            .map(|v| v.iter().copied().map(Some).collect::<Vec<_>>())
+                    });
+                    Arc::new(FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+                        values, dim,
+                    ))
+                }
+                other => return Err(StorageError::InvalidInput(format!("unknown lancedb column {other}"))),
+            };
+            columns.push(column);
+        }
+        RecordBatch::try_new(Arc::clone(&self.schema), columns)
+            .map_err(|e| StorageError::InvalidInput(format!("record batch build failed: {e}")))
     }
 
+    fn add_batch(&self, batch: RecordBatch) -> Result<(), StorageError> {
+        let schema = batch.schema();
+        let reader = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema);
+        self.rt
+            .block_on(async { self.table.add(reader).execute().await })
+            .map_err(|e| StorageError::InvalidInput(format!("lancedb add failed: {e}")))?;
+        Ok(())
+    }
+
+    /// Rows that fail to open are skipped; `open_with_cipher` already rejected undecryptable tables.
     fn parse_entries_from_batches(&self, batches: &[RecordBatch]) -> Vec<MemoryEntry> {
         let mut out = Vec::new();
         for batch in batches {
@@ -256,17 +989,38 @@ impl LanceDbBackend {
             let tags = as_string(batch, "tags");
             let timestamps = as_u64(batch, "timestamp_ms");
             let embeddings = as_string(batch, "embedding_json");
+            let models = as_string(batch, "embedding_model");
+            let sources = as_string(batch, "source_json");
+            let keywords = as_string(batch, "keywords_json");
 
             let n = batch.num_rows();
             for i in 0..n {
                 let raw_tags = tags.map(|a| a.value(i).to_string()).unwrap_or_default();
                 let tags_vec = serde_json::from_str::<Vec<String>>(&raw_tags).unwrap_or_default();
+                let id = ids
+                    .map(|a| a.value(i).to_string())
+                    .unwrap_or_else(|| format!("unknown-{i}"));
+                let Ok(text) = texts.map_or(Ok(String::new()), |a| self.open_column(a.value(i), &id, "text")) else {
+                    continue;
+                };
+                let Ok(embedding_json) =
+                    embeddings.map_or(Ok(String::new()), |a| self.open_column(a.value(i), &id, "embedding"))
+                else {
+                    continue;
+                };
+                let embedding = serde_json::from_str::<Vec<f32>>(&embedding_json)
+                    .ok()
+                    .filter(|v| !v.is_empty());
+                let Ok(keywords_json) = keywords
+                    .filter(|a| !a.is_null(i))
+                    .map_or(Ok(String::new()), |a| self.open_column(a.value(i), &id, "keywords"))
+                else {
+                    continue;
+                };
 
                 out.push(MemoryEntry {
-                    id: ids
-                        .map(|a| a.value(i).to_string())
-                        .unwrap_or_else(|| format!("unknown-{i}")),
-                    text: texts.map(|a| a.value(i).to_string()).unwrap_or_default(),
+                    id,
+                    text,
                     category: categories
                         .map(|a| a.value(i).to_string())
                         .unwrap_or_else(|| "other".to_string()),
@@ -276,7 +1030,13 @@ impl LanceDbBackend {
                     importance: importances.map(|a| a.value(i)).unwrap_or(0.7),
                     tags: tags_vec,
                     timestamp_ms: timestamps.map(|a| a.value(i)).unwrap_or(0),
-                    embedding: embeddings.and_then(|a| serde_json::from_str::<Vec<f32>>(a.value(i)).ok()),
+                    embedding: embedding.clone(),
+                    embedding_model: models.filter(|a| !a.is_null(i)).map(|a| a.value(i).to_string()),
+                    source: sources
+                        .filter(|a| !a.is_null(i))
+                        .and_then(|a| serde_json::from_str(a.value(i)).ok()),
+                    embedding_dim: embedding.as_ref().map(Vec::len),
+                    keywords: serde_json::from_str(&keywords_json).unwrap_or_default(),
                 });
             }
         }
@@ -292,56 +1052,56 @@ impl StorageBackend for LanceDbBackend {
         }
 
         let entry = MemoryEntry {
-            id: format!("mem-{}", self.id_seq),
+            id: new_id(self.id_format, self.id_seq)?,
             text: new_entry.text.to_lowercase(),
             category: new_entry.category,
             scope: new_entry.scope,
             importance: new_entry.importance.clamp(0.0, 1.0),
             tags: new_entry.tags.into_iter().map(|t| t.to_lowercase()).collect(),
             timestamp_ms: now_ms(),
+            embedding_dim: new_entry.embedding.as_ref().map(Vec::len),
+            embedding_model: new_entry.embedding.as_ref().and(new_entry.embedding_model),
             embedding: new_entry.embedding,
+            source: new_entry.source,
+            keywords: new_entry.keywords,
         };
 
         self.id_seq += 1;
-        let tags_json = serde_json::to_string(&entry.tags)?;
-        let embedding_json = serde_json::to_string(&entry.embedding.clone().unwrap_or_default())?;
-
-        let batch = RecordBatch::try_new(
-            schema_ref(),
-            vec![
-                Arc::new(StringArray::from(vec![entry.id.clone()])),
-                Arc::new(StringArray::from(vec![entry.text.clone()])),
-                Arc::new(StringArray::from(vec![entry.category.clone()])),
-                Arc::new(StringArray::from(vec![entry.scope.clone()])),
-                Arc::new(Float32Array::from(vec![entry.importance])),
-                Arc::new(StringArray::from(vec![tags_json])),
-                Arc::new(UInt64Array::from(vec![entry.timestamp_ms])),
-                Arc::new(StringArray::from(vec![embedding_json])),
-            ],
-        )
-        .map_err(|e| StorageError::InvalidInput(format!("record batch build failed: {e}")))?;
-
-        let schema = batch.schema();
-        let reader = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema);
-        self.rt
-            .block_on(async { self.table.add(reader).execute().await })
-            .map_err(|e| StorageError::InvalidInput(format!("lancedb add failed: {e}")))?;
+        self.add_batch(self.entry_batch(&entry)?)?;
 
         Ok(entry)
     }
 
+    /// Reads at most [`LANCE_RECALL_SCAN`] rows matching the scope and category. Past that, a
+    /// query embedding picks the nearest rows through the vector index instead of arbitrary ones.
     fn recall(&self, query: RecallQuery) -> Vec<RecallResult> {
-        let mut lq = self.table.query();
+        let mut filters = Vec::new();
         if let Some(scope) = &query.scope {
-            lq = lq.only_if(format!("scope = '{}'", escape_sql(scope)));
+            filters.push(format!("scope = '{}'", escape_sql(scope)));
         }
         if let Some(category) = &query.category {
-            let expr = format!("category = '{}'", escape_sql(category));
-            lq = lq.only_if(expr);
+            filters.push(format!("category = '{}'", escape_sql(category)));
         }
-        lq = lq.limit(20_000);
+        let filter = (!filters.is_empty()).then(|| filters.join(" AND "));
+        let execution = match self.nearest_query(&query) {
+            Some(nearest) => {
+                let nearest = match filter {
+                    Some(filter) => nearest.only_if(filter),
+                    None => nearest,
+                };
+                self.rt
+                    .block_on(async { nearest.limit(LANCE_RECALL_SCAN).execute().await })
+            }
+            None => {
+                let mut lq = self.table.query();
+                if let Some(filter) = filter {
+                    lq = lq.only_if(filter);
+                }
+                self.rt.block_on(async { lq.limit(LANCE_RECALL_SCAN).execute().await })
+            }
+        };
 
-        let batches = match self.rt.block_on(async { lq.execute().await }) {
+        let batches = match execution {
             Ok(stream) => self
                 .rt
                 .block_on(async { stream.try_collect::<Vec<_>>().await })
@@ -391,9 +1151,59 @@ impl StorageBackend for LanceDbBackend {
             "backend": "lancedb",
             "lancedb_uri": self.uri,
             "table": self.table_name,
-            "count": count
+            "count": count,
+            "schema_upgrades": self.schema_upgrades
         })
     }
+
+    /// Writes the entries of `events` under their own ids, replacing rows with the same id.
+    fn apply_changes(&mut self, events: &[ChangeEvent]) -> Result<(), StorageError> {
+        for event in events {
+            let doomed = match event.op {
+                ChangeOp::Delete => Some(event.id.as_str()),
+                ChangeOp::Store | ChangeOp::Update => event.previous_id.as_deref(),
+            };
+            let entry = event.entry.as_ref().filter(|_| event.op != ChangeOp::Delete);
+            for id in doomed.into_iter().chain(entry.map(|e| e.id.as_str())) {
+                let escaped = escape_sql(id);
+                self.rt
+                    .block_on(async { self.table.delete(&format!("id = '{escaped}'")).await })
+                    .map_err(|e| StorageError::InvalidInput(format!("lancedb delete failed: {e}")))?;
+            }
+            if let Some(entry) = entry {
+                if let Some(seq) = id_sequence(&entry.id) {
+                    self.id_seq = self.id_seq.max(seq + 1);
+                }
+                self.add_batch(self.entry_batch(entry)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn index_status(&self) -> Result<serde_json::Value, StorageError> {
+        Self::index_status(self)
+    }
+
+    fn ensure_indexes(&mut self) -> Result<serde_json::Value, StorageError> {
+        Self::ensure_indexes(self)
+    }
+
+    /// Re-writes each row sealed with the primary key: the new row is built first, then the old one is swapped out.
+    fn rekey(&mut self) -> Result<usize, StorageError> {
+        if self.cipher.is_none() {
+            return Err(StorageError::InvalidInput("encryption is not configured".to_string()));
+        }
+        let entries = self.parse_entries_from_batches(&self.all_batches()?);
+        for entry in &entries {
+            let batch = self.entry_batch(entry)?;
+            let escaped = escape_sql(&entry.id);
+            self.rt
+                .block_on(async { self.table.delete(&format!("id = '{escaped}'")).await })
+                .map_err(|e| StorageError::InvalidInput(format!("lancedb delete failed: {e}")))?;
+            self.add_batch(batch)?;
+        }
+        Ok(entries.len())
+    }
 }
 
 #[cfg(feature = "lancedb-backend")]
@@ -407,9 +1217,16 @@ fn schema_ref() -> SchemaRef {
         Field::new("tags", DataType::Utf8, false),
         Field::new("timestamp_ms", DataType::UInt64, false),
         Field::new("embedding_json", DataType::Utf8, false),
+        Field::new("embedding_model", DataType::Utf8, true),
+        Field::new("source_json", DataType::Utf8, true),
+        Field::new("keywords_json", DataType::Utf8, true),
     ]))
 }
 
+/// Most rows one LanceDB recall reads.
+#[cfg(feature = "lancedb-backend")]
+const LANCE_RECALL_SCAN: usize = 20_000;
+
 #[cfg(feature = "lancedb-backend")]
 fn as_string<'a>(batch: &'a RecordBatch, name: &str) -> Option<&'a StringArray> {
     batch
@@ -438,10 +1255,11 @@ fn escape_sql(s: &str) -> String {
 
 pub fn recall_entries(entries: &[MemoryEntry], query: RecallQuery) -> Vec<RecallResult> {
     let now = now_ms();
-    let terms = tokenize(&query.query);
+    let parsed = parse_query(&query.query);
+    let terms = tokenize_query(&parsed.text);
     let limit = query.limit.clamp(1, 50);
     let has_vector = query.query_embedding.is_some();
-    if terms.is_empty() && !has_vector {
+    if terms.is_empty() && !has_vector && !parsed.has_filters() {
         return Vec::new();
     }
     let vector_weight = query.vector_weight.unwrap_or(0.6).clamp(0.0, 1.0);
@@ -461,7 +1279,7 @@ pub fn recall_entries(entries: &[MemoryEntry], query: RecallQuery) -> Vec<Recall
                     return None;
                 }
             }
-            Some(idx)
+            parsed.matches(entry).then_some(idx)
         })
         .collect();
 
@@ -471,65 +1289,59 @@ pub fn recall_entries(entries: &[MemoryEntry], query: RecallQuery) -> Vec<Recall
 
     let cap = (limit * 4).clamp(16, 96);
     let mut ranked: BinaryHeap<Reverse<RankedItem>> = BinaryHeap::with_capacity(cap);
-    let anchor = terms.first();
-    for idx in candidates {
-        let entry = &entries[idx];
-        if !has_vector && anchor.is_some_and(|a| !entry.text.contains(a)) {
-            continue;
+    let mut push_ranked = |idx: usize, score: f32| {
+        if score < 0.12 {
+            return;
         }
-        let doc_len = approx_doc_len(entry);
-        let mut lexical_hits = 0.0_f32;
-        let mut bm25_local = 0.0_f32;
-        for term in &terms {
-            let tf = term_frequency(entry, term);
-            if tf <= 0.0 {
-                continue;
+        let item = RankedItem { idx, score };
+        if ranked.len() < cap {
+            ranked.push(Reverse(item));
+        } else if let Some(Reverse(min_item)) = ranked.peek() {
+            if item.score > min_item.score {
+                let _ = ranked.pop();
+                ranked.push(Reverse(item));
             }
-            lexical_hits += 1.0;
-            let k1 = 1.2_f32;
-            let b = 0.75_f32;
-            let avg_anchor = 32.0_f32;
-            let denom = tf + k1 * (1.0 - b + b * (doc_len / avg_anchor));
-            bm25_local += (tf * (k1 + 1.0)) / denom.max(1e-6);
-        }
-        let vector_score = if let (Some(qv), Some(dv)) = (&query.query_embedding, &entry.embedding) {
-            cosine_similarity(qv, dv).unwrap_or(0.0)
-        } else {
-            0.0
+        }
+    };
+    let rrf = has_vector && query.fusion == Some(FusionMode::Rrf);
+    let mut fusion_inputs: Vec<(usize, f32, Option<f32>)> = Vec::new();
+    // Filters already decided membership, so OR'd words need not all share the first term.
+    let anchor = terms.first().filter(|_| !parsed.has_filters());
+    for idx in candidates {
+        let Some(entry) = entries.get(idx) else {
+            continue;
         };
-
-        if lexical_hits <= 0.0 && bm25_local <= 0.0 && vector_score <= 0.0 {
+        if !has_vector && anchor.is_some_and(|a| term_frequency(entry, a) <= 0.0) {
             continue;
         }
-        let lexical = if terms.is_empty() {
-            0.0
-        } else {
-            lexical_hits / (terms.len() as f32)
-        };
-        let bm25_norm = if terms.is_empty() {
-            0.0
-        } else {
-            bm25_local / (terms.len() as f32)
-        };
-        let lexical_base = 0.65 * bm25_norm + 0.35 * lexical;
-        let mut score = if has_vector {
-            (lexical_weight * lexical_base) + (vector_weight * ((vector_score + 1.0) / 2.0))
+        if terms.is_empty() && !has_vector {
+            push_ranked(idx, filter_only_score(entry, now));
+            continue;
+        }
+        let parts = score_breakdown(
+            entry,
+            &terms,
+            query.query_embedding.as_deref(),
+            vector_weight,
+            lexical_weight,
+            now,
+        );
+        if parts.term_hits.is_empty() && parts.vector_cosine.is_none_or(|v| v <= 0.0) && !parsed.has_filters() {
+            continue;
+        }
+        if rrf {
+            fusion_inputs.push((idx, parts.lexical_base, parts.vector_cosine));
         } else {
-            lexical_base
-        };
-        score = apply_recency_boost(score, now, entry.timestamp_ms);
-        score = apply_importance_weight(score, entry.importance);
-        score = apply_length_norm(score, entry.text.len());
+            push_ranked(idx, parts.score);
+        }
+    }
 
-        if score >= 0.12 {
-            let item = RankedItem { idx, score };
-            if ranked.len() < cap {
-                ranked.push(Reverse(item));
-            } else if let Some(Reverse(min_item)) = ranked.peek() {
-                if item.score > min_item.score {
-                    let _ = ranked.pop();
-                    ranked.push(Reverse(item));
-                }
+    if rrf {
+        for (idx, fused) in reciprocal_rank_fusion(&fusion_inputs, lexical_weight, vector_weight) {
+            if let Some(entry) = entries.get(idx) {
+                let boosted = apply_recency_boost(fused, now, entry.timestamp_ms);
+                let weighted = apply_importance_weight(boosted, entry.importance);
+                push_ranked(idx, apply_length_norm(weighted, entry.text.len()));
             }
         }
     }
@@ -540,6 +1352,19 @@ pub fn recall_entries(entries: &[MemoryEntry], query: RecallQuery) -> Vec<Recall
         .collect::<Vec<_>>();
     ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
 
+    if let Some(diversity) = query.diversity {
+        let pool = ranked
+            .into_iter()
+            .filter_map(|(idx, score)| {
+                entries.get(idx).map(|entry| RecallResult {
+                    entry: entry.clone(),
+                    score,
+                })
+            })
+            .collect();
+        return mmr_select(pool, diversity, limit);
+    }
+
     let mut out = Vec::with_capacity(limit);
     let mut selected_signatures: HashSet<u64> = HashSet::new();
     for (idx, score) in ranked {
@@ -562,6 +1387,189 @@ pub fn recall_entries(entries: &[MemoryEntry], query: RecallQuery) -> Vec<Recall
     out
 }
 
+/// Score for a query that is only filters, like `tag:deploy NOT staging`: every match starts level
+/// and recency, importance and length decide the order.
+fn filter_only_score(entry: &MemoryEntry, now: u64) -> f32 {
+    let boosted = apply_recency_boost(FILTER_ONLY_BASE, now, entry.timestamp_ms);
+    apply_length_norm(apply_importance_weight(boosted, entry.importance), entry.text.len())
+}
+
+/// Fuses lexical and vector rankings as `w_l / (k + rank_l) + w_v / (k + rank_v)`,
+/// normalised to `[0, 1]` so the usual recall threshold and boosts still apply.
+/// Input tuples are `(idx, lexical_base, vector_cosine)`; a missing signal earns no rank.
+fn reciprocal_rank_fusion(
+    inputs: &[(usize, f32, Option<f32>)],
+    lexical_weight: f32,
+    vector_weight: f32,
+) -> Vec<(usize, f32)> {
+    let rank_by = |signals: Vec<(usize, f32)>| {
+        let mut order = signals;
+        order.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        order
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (idx, _))| (idx, (rank + 1) as f32))
+            .collect::<HashMap<_, _>>()
+    };
+    let lexical_ranks = rank_by(inputs.iter().filter(|t| t.1 > 0.0).map(|t| (t.0, t.1)).collect());
+    let vector_ranks = rank_by(inputs.iter().filter_map(|t| t.2.map(|v| (t.0, v))).collect());
+    let best = (lexical_weight + vector_weight).max(1e-6) / (RRF_K + 1.0);
+
+    inputs
+        .iter()
+        .map(|&(idx, _, _)| {
+            let lexical_part = lexical_ranks.get(&idx).map_or(0.0, |r| lexical_weight / (RRF_K + r));
+            let vector_part = vector_ranks.get(&idx).map_or(0.0, |r| vector_weight / (RRF_K + r));
+            (idx, (lexical_part + vector_part) / best)
+        })
+        .collect()
+}
+
+/// Maximal-marginal-relevance selection of up to `limit` results from `pool`.
+///
+/// `diversity` 0.0 keeps pure relevance order; 1.0 favours the most dissimilar entries.
+/// Similarity uses embedding cosine when both entries have vectors, token Jaccard otherwise.
+pub fn mmr_select(mut pool: Vec<RecallResult>, diversity: f32, limit: usize) -> Vec<RecallResult> {
+    let lambda = 1.0 - diversity.clamp(0.0, 1.0);
+    pool.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let max_score = pool.first().map_or(1.0, |r| r.score.max(1e-6));
+
+    let mut remaining = pool
+        .into_iter()
+        .map(|r| {
+            let tokens = tokenize(&r.entry.text).into_iter().collect::<HashSet<_>>();
+            (r, tokens)
+        })
+        .collect::<Vec<_>>();
+    let mut picked: Vec<(RecallResult, HashSet<String>)> = Vec::with_capacity(limit.min(remaining.len()));
+    while picked.len() < limit && !remaining.is_empty() {
+        let mut best_pos = 0;
+        let mut best_value = f32::NEG_INFINITY;
+        for (pos, (cand, cand_tokens)) in remaining.iter().enumerate() {
+            let relevance = cand.score / max_score;
+            let redundancy = picked
+                .iter()
+                .map(|(sel, sel_tokens)| entry_similarity(&cand.entry, &sel.entry, cand_tokens, sel_tokens))
+                .fold(0.0_f32, f32::max);
+            let value = lambda * relevance - (1.0 - lambda) * redundancy;
+            if value > best_value {
+                best_value = value;
+                best_pos = pos;
+            }
+        }
+        picked.push(remaining.remove(best_pos));
+    }
+
+    picked.into_iter().map(|(r, _)| r).collect()
+}
+
+fn entry_similarity(a: &MemoryEntry, b: &MemoryEntry, a_tokens: &HashSet<String>, b_tokens: &HashSet<String>) -> f32 {
+    if let (Some(va), Some(vb)) = (&a.embedding, &b.embedding) {
+        if let Ok(cos) = cosine_similarity(va, vb) {
+            return cos.max(0.0);
+        }
+    }
+    let union = a_tokens.union(b_tokens).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a_tokens.intersection(b_tokens).count() as f32 / union as f32
+}
+
+/// Per-stage contribution to a local recall score, as computed by [`recall_entries`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreBreakdown {
+    pub term_hits: Vec<String>,
+    pub lexical_coverage: f32,
+    pub bm25: f32,
+    pub lexical_base: f32,
+    pub vector_cosine: Option<f32>,
+    pub vector_weight: f32,
+    pub lexical_weight: f32,
+    pub blended: f32,
+    pub recency_boost: f32,
+    pub importance_weight: f32,
+    pub length_norm: f32,
+    pub score: f32,
+}
+
+/// Recomputes the local score of `entry` for `query` with every stage exposed.
+pub fn explain_recall_score(entry: &MemoryEntry, query: &RecallQuery) -> ScoreBreakdown {
+    let vector_weight = query.vector_weight.unwrap_or(0.6).clamp(0.0, 1.0);
+    let lexical_weight = query.lexical_weight.unwrap_or(1.0 - vector_weight).clamp(0.0, 1.0);
+    score_breakdown(
+        entry,
+        &tokenize_query(&parse_query(&query.query).text),
+        query.query_embedding.as_deref(),
+        vector_weight,
+        lexical_weight,
+        now_ms(),
+    )
+}
+
+fn score_breakdown(
+    entry: &MemoryEntry,
+    terms: &[Vec<String>],
+    query_embedding: Option<&[f32]>,
+    vector_weight: f32,
+    lexical_weight: f32,
+    now: u64,
+) -> ScoreBreakdown {
+    let doc_len = approx_doc_len(entry);
+    let mut term_hits = Vec::new();
+    let mut bm25_local = 0.0_f32;
+    let mut lexical_hits = 0.0_f32;
+    for variants in terms {
+        let tf = term_frequency(entry, variants);
+        if tf <= 0.0 {
+            continue;
+        }
+        lexical_hits += tf;
+        if let Some(term) = variants.first().filter(|t| !term_hits.contains(*t)) {
+            term_hits.push(term.clone());
+        }
+        let k1 = 1.2_f32;
+        let b = 0.75_f32;
+        let avg_anchor = 32.0_f32;
+        let denom = tf + k1 * (1.0 - b + b * (doc_len / avg_anchor));
+        bm25_local += (tf * (k1 + 1.0)) / denom.max(1e-6);
+    }
+    let vector_cosine = match (query_embedding, &entry.embedding) {
+        (Some(qv), Some(dv)) => Some(cosine_similarity(qv, dv).unwrap_or(0.0)),
+        _ => None,
+    };
+
+    let (lexical, bm25_norm) = if terms.is_empty() {
+        (0.0, 0.0)
+    } else {
+        (lexical_hits / (terms.len() as f32), bm25_local / (terms.len() as f32))
+    };
+    let lexical_base = 0.65 * bm25_norm + 0.35 * lexical;
+    let blended = if query_embedding.is_some() {
+        (lexical_weight * lexical_base) + (vector_weight * ((vector_cosine.unwrap_or(0.0) + 1.0) / 2.0))
+    } else {
+        lexical_base
+    };
+    let boosted = apply_recency_boost(blended, now, entry.timestamp_ms);
+    let weighted = apply_importance_weight(boosted, entry.importance);
+    let score = apply_length_norm(weighted, entry.text.len());
+
+    ScoreBreakdown {
+        term_hits,
+        lexical_coverage: lexical,
+        bm25: bm25_norm,
+        lexical_base,
+        vector_cosine,
+        vector_weight,
+        lexical_weight,
+        blended,
+        recency_boost: boosted - blended,
+        importance_weight: if boosted == 0.0 { 1.0 } else { weighted / boosted },
+        length_norm: if weighted == 0.0 { 1.0 } else { score / weighted },
+        score,
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct RankedItem {
     idx: usize,
@@ -597,24 +1605,28 @@ fn now_ms() -> u64 {
         .unwrap_or(0)
 }
 
-fn tokenize(text: &str) -> Vec<String> {
-    text.split(|c: char| !c.is_alphanumeric())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_lowercase())
-        .collect()
-}
-
 fn approx_doc_len(entry: &MemoryEntry) -> f32 {
     let text_tokens = (entry.text.len() / 5).max(1);
     let tag_tokens = entry.tags.len().max(1);
     (text_tokens + tag_tokens) as f32
 }
 
-fn term_frequency(entry: &MemoryEntry, term: &str) -> f32 {
-    if term.is_empty() {
-        return 0.0;
+/// Query tokens, each expanded to its stem and synonyms for lexical matching.
+fn tokenize_query(text: &str) -> Vec<Vec<String>> {
+    tokenize(text).iter().map(|t| expand_term(t)).collect()
+}
+
+/// 1 for a term in the text; less for one only among the stored expansion keywords, which are
+/// a paraphrase rather than the words the entry used.
+fn term_frequency(entry: &MemoryEntry, variants: &[String]) -> f32 {
+    let found = |within: &str| variants.iter().any(|v| !v.is_empty() && within.contains(v.as_str()));
+    if found(&entry.text) {
+        1.0
+    } else if entry.keywords.iter().any(|k| found(k)) {
+        KEYWORD_TERM_FREQUENCY
+    } else {
+        0.0
     }
-    if entry.text.contains(term) { 1.0 } else { 0.0 }
 }
 
 fn cosine_similarity(a: &[f32], b: &[f32]) -> Result<f32, StorageError> {
@@ -681,6 +1693,9 @@ mod tests {
                 importance: 0.9,
                 tags: vec!["jina".to_string(), "embedding".to_string()],
                 embedding: None,
+                embedding_model: None,
+                source: None,
+                keywords: Vec::new(),
             })
             .expect("store");
 
@@ -692,6 +1707,8 @@ mod tests {
             limit: 3,
             vector_weight: None,
             lexical_weight: None,
+            diversity: None,
+            fusion: None,
         });
         assert!(!recalled.is_empty());
         assert_eq!(recalled[0].entry.id, stored.id);
@@ -707,12 +1724,99 @@ mod tests {
             limit: 3,
             vector_weight: None,
             lexical_weight: None,
+            diversity: None,
+            fusion: None,
         });
         assert!(recalled_after.is_empty());
 
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn relations_persist_and_cascade_on_forget() {
+        let path = std::env::temp_dir().join(format!("prx-store-rel-{}.json", now_ms()));
+        let mut store = PersistentMemoryStore::open(&path).expect("open store");
+        let mut ids = Vec::new();
+        for text in ["principle: pin toolchains", "pitfall: nightly drift broke ci"] {
+            let entry = store
+                .store(NewMemoryEntry {
+                    text: text.to_string(),
+                    category: "fact".to_string(),
+                    scope: "global".to_string(),
+                    importance: 0.5,
+                    tags: Vec::new(),
+                    embedding: None,
+                    embedding_model: None,
+                    source: None,
+                    keywords: Vec::new(),
+                })
+                .expect("store");
+            ids.push(entry.id);
+        }
+
+        assert!(store.link(&ids[0], "derived-from", &ids[1]).expect("link"));
+        assert!(!store.link(&ids[0], "derived-from", &ids[1]).expect("relink"));
+        assert!(store.link(&ids[0], "derived-from", "mem-missing").is_err());
+
+        let reopened = PersistentMemoryStore::open(&path).expect("reopen store");
+        assert_eq!(reopened.relations_for(&ids[1]).len(), 1);
+
+        store.forget_by_id(&ids[1]).expect("forget");
+        assert!(store.relations_for(&ids[0]).is_empty());
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn snapshots_keep_their_generation_across_batch_forget() {
+        let path = std::env::temp_dir().join(format!("prx-store-snap-{}.json", now_ms()));
+        let mut store = PersistentMemoryStore::open(&path).expect("open store");
+        let mut ids = Vec::new();
+        for text in ["retry flaky uploads", "retry dns lookups", "pin the toolchain"] {
+            let entry = store
+                .store(NewMemoryEntry {
+                    text: text.to_string(),
+                    category: "fact".to_string(),
+                    scope: "global".to_string(),
+                    importance: 0.5,
+                    tags: Vec::new(),
+                    embedding: None,
+                    embedding_model: None,
+                    source: None,
+                    keywords: Vec::new(),
+                })
+                .expect("store");
+            ids.push(entry.id);
+        }
+
+        let before = store.snapshot();
+        let retried = ids.get(..2).unwrap_or_default().to_vec();
+        let mut doomed = retried.clone();
+        doomed.push("mem-missing".to_string());
+        assert_eq!(store.forget_batch(&doomed).expect("forget batch"), retried);
+
+        let after = store.snapshot();
+        assert_eq!(after.generation(), before.generation() + 1);
+        assert_eq!(before.entries().len(), 3);
+        assert_eq!(after.list(10).len(), 1);
+        let query = RecallQuery {
+            query: "retry".to_string(),
+            query_embedding: None,
+            scope: None,
+            category: None,
+            limit: 5,
+            vector_weight: None,
+            lexical_weight: None,
+            diversity: None,
+            fusion: None,
+        };
+        assert_eq!(before.recall(query.clone()).len(), 2);
+        assert!(after.recall(query).is_empty());
+        assert_eq!(PersistentMemoryStore::open(&path).expect("reopen").list(10).len(), 1);
+
+        let _ = fs::remove_file(path);
+    }
+
     #[cfg(feature = "lancedb-backend")]
     #[test]
     fn lancedb_backend_roundtrip() {
@@ -728,6 +1832,9 @@ mod tests {
                 importance: 0.8,
                 tags: vec!["lancedb".to_string(), "storage".to_string()],
                 embedding: None,
+                embedding_model: None,
+                source: None,
+                keywords: Vec::new(),
             })
             .expect("store");
 
@@ -739,6 +1846,8 @@ mod tests {
             limit: 5,
             vector_weight: None,
             lexical_weight: None,
+            diversity: None,
+            fusion: None,
         });
         assert!(!recalled.is_empty());
         assert_eq!(recalled[0].entry.id, stored.id);
@@ -747,6 +1856,105 @@ mod tests {
         assert!(deleted);
     }
 
+    #[cfg(feature = "lancedb-backend")]
+    #[test]
+    fn lancedb_upgrades_tables_from_older_releases() {
+        let path = std::env::temp_dir().join(format!("prx-lancedb-upgrade-{}", now_ms()));
+        let uri = path.display().to_string();
+        let rt = Arc::new(tokio::runtime::Runtime::new().expect("runtime"));
+        // The original eight columns, as the first releases created the table.
+        let old_schema = Arc::new(Schema::new(
+            schema_ref().fields().iter().take(8).cloned().collect::<Vec<_>>(),
+        ));
+        let batch = RecordBatch::try_new(
+            Arc::clone(&old_schema),
+            vec![
+                Arc::new(StringArray::from(vec!["mem-7"])),
+                Arc::new(StringArray::from(vec!["Tables from older releases keep their rows"])),
+                Arc::new(StringArray::from(vec!["fact"])),
+                Arc::new(StringArray::from(vec!["global"])),
+                Arc::new(Float32Array::from(vec![0.5_f32])),
+                Arc::new(StringArray::from(vec!["[\"lancedb\"]"])),
+                Arc::new(UInt64Array::from(vec![1_u64])),
+                Arc::new(StringArray::from(vec!["[]"])),
+            ],
+        )
+        .expect("old batch");
+        rt.block_on(async {
+            let db = lancedb::connect(&uri).execute().await.expect("connect");
+            db.create_table("memories", RecordBatchIterator::new(vec![Ok(batch)], old_schema))
+                .execute()
+                .await
+                .expect("create old table");
+        });
+
+        let backend = LanceDbBackend::open_with_runtime(&uri, Arc::clone(&rt)).expect("open old table");
+        assert_eq!(
+            backend.stats()["schema_upgrades"],
+            serde_json::json!(["embedding_model", "source_json", "keywords_json"])
+        );
+        let entries = backend.list(10);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, "mem-7");
+        assert_eq!(entries[0].tags, vec!["lancedb".to_string()]);
+        drop(backend);
+
+        let reopened = LanceDbBackend::open_with_runtime(&uri, rt).expect("reopen");
+        assert_eq!(reopened.stats()["schema_upgrades"], serde_json::json!([]));
+        assert_eq!(reopened.list(10).len(), 1);
+        let _ = fs::remove_dir_all(path);
+    }
+
+    #[cfg(feature = "lancedb-backend")]
+    #[test]
+    fn lancedb_indexes_backfill_vectors_without_losing_rows() {
+        let path = std::env::temp_dir().join(format!("prx-lancedb-index-{}", now_ms()));
+        let mut backend = LanceDbBackend::open(path.display().to_string()).expect("open lancedb backend");
+        for i in 0..300_u16 {
+            backend
+                .store(NewMemoryEntry {
+                    text: format!("indexed memory {i}"),
+                    category: if i % 2 == 0 { "fact" } else { "decision" }.to_string(),
+                    scope: "global".to_string(),
+                    importance: 0.5,
+                    tags: Vec::new(),
+                    embedding: Some((0..16_u16).map(|d| f32::from((i * 31 + d * 7) % 97) / 97.0).collect()),
+                    embedding_model: None,
+                    source: None,
+                    keywords: Vec::new(),
+                })
+                .expect("store");
+        }
+        assert_eq!(backend.index_status().expect("status")["health"], "missing");
+
+        let status = backend.ensure_indexes().expect("ensure indexes");
+        assert_eq!(status["missing"], serde_json::json!([]));
+        assert_eq!(status["vector"], "indexed");
+        assert_eq!(backend.list(1000).len(), 300);
+        let with_vector = backend
+            .rt
+            .block_on(async { backend.table.count_rows(Some("vector IS NOT NULL".to_string())).await })
+            .expect("count vectors");
+        assert_eq!(with_vector, 300);
+
+        // Rows written after the build carry their vector and show up as unindexed.
+        backend
+            .store(NewMemoryEntry {
+                text: "written after the index".to_string(),
+                category: "fact".to_string(),
+                scope: "global".to_string(),
+                importance: 0.5,
+                tags: Vec::new(),
+                embedding: Some(vec![0.5; 16]),
+                embedding_model: None,
+                source: None,
+                keywords: Vec::new(),
+            })
+            .expect("store after index");
+        assert_eq!(backend.index_status().expect("status")["health"], "stale");
+        let _ = fs::remove_dir_all(path);
+    }
+
     #[test]
     fn vector_fusion_can_override_lexical_bias() {
         let path = std::env::temp_dir().join(format!("prx-store-vec-{}.json", now_ms()));
@@ -760,6 +1968,9 @@ mod tests {
                 importance: 0.7,
                 tags: vec!["alpha".to_string()],
                 embedding: Some(vec![0.0, 1.0]),
+                embedding_model: None,
+                source: None,
+                keywords: Vec::new(),
             })
             .expect("store alpha");
 
@@ -771,6 +1982,9 @@ mod tests {
                 importance: 0.7,
                 tags: vec!["beta".to_string()],
                 embedding: Some(vec![1.0, 0.0]),
+                embedding_model: None,
+                source: None,
+                keywords: Vec::new(),
             })
             .expect("store beta");
 
@@ -782,10 +1996,198 @@ mod tests {
             limit: 2,
             vector_weight: Some(0.95),
             lexical_weight: Some(0.05),
+            diversity: None,
+            fusion: None,
         });
 
         assert!(!recalled.is_empty());
         assert_eq!(recalled[0].entry.id, beta.id);
         let _ = fs::remove_file(path);
     }
+
+    #[test]
+    fn mmr_select_prefers_diverse_entries() {
+        let make = |id: &str, text: &str, score: f32| RecallResult {
+            entry: MemoryEntry {
+                id: id.to_string(),
+                text: text.to_string(),
+                category: "fact".to_string(),
+                scope: "global".to_string(),
+                importance: 0.5,
+                tags: Vec::new(),
+                timestamp_ms: 0,
+                embedding: None,
+                embedding_model: None,
+                source: None,
+                embedding_dim: None,
+                keywords: Vec::new(),
+            },
+            score,
+        };
+        let pool = vec![
+            make("mem-1", "retry the flaky network test with backoff", 0.9),
+            make("mem-2", "retry the flaky network test with a backoff", 0.88),
+            make("mem-3", "pin the toolchain version in ci", 0.6),
+        ];
+
+        let plain = mmr_select(pool.clone(), 0.0, 2);
+        assert_eq!(plain[1].entry.id, "mem-2");
+
+        let diverse = mmr_select(pool, 0.7, 2);
+        assert_eq!(diverse[0].entry.id, "mem-1");
+        assert_eq!(diverse[1].entry.id, "mem-3");
+    }
+
+    #[test]
+    fn rrf_fusion_uses_ranks_not_raw_scores() {
+        let make = |id: &str, text: &str, embedding: Vec<f32>| MemoryEntry {
+            id: id.to_string(),
+            text: text.to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: Vec::new(),
+            timestamp_ms: now_ms(),
+            embedding: Some(embedding),
+            embedding_model: None,
+            source: None,
+            embedding_dim: None,
+            keywords: Vec::new(),
+        };
+        let entries = vec![
+            make("mem-1", "alpha beta gamma lexical match", vec![0.2, 1.0]),
+            make("mem-2", "unrelated vector neighbour", vec![1.0, 0.0]),
+        ];
+        let query = |fusion| RecallQuery {
+            query: "alpha beta gamma".to_string(),
+            query_embedding: Some(vec![1.0, 0.0]),
+            scope: None,
+            category: None,
+            limit: 2,
+            vector_weight: Some(0.5),
+            lexical_weight: Some(0.5),
+            diversity: None,
+            fusion,
+        };
+
+        let fused = recall_entries(&entries, query(Some(FusionMode::Rrf)));
+        assert_eq!(fused.len(), 2);
+        // mem-1 holds lexical rank 1 and vector rank 2; mem-2 only vector rank 1.
+        assert_eq!(fused[0].entry.id, "mem-1");
+        assert!(fused.iter().all(|r| r.score <= 1.2));
+    }
+
+    #[test]
+    fn recall_matches_stems_and_synonyms() {
+        let entries = vec![MemoryEntry {
+            id: "mem-1".to_string(),
+            text: "rotate the embedding key stored in the database".to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: Vec::new(),
+            timestamp_ms: now_ms(),
+            embedding: None,
+            embedding_model: None,
+            source: None,
+            embedding_dim: None,
+            keywords: Vec::new(),
+        }];
+        let recalled = recall_entries(
+            &entries,
+            RecallQuery {
+                query: "embeddings db".to_string(),
+                query_embedding: None,
+                scope: None,
+                category: None,
+                limit: 3,
+                vector_weight: None,
+                lexical_weight: None,
+                diversity: None,
+                fusion: None,
+            },
+        );
+        assert_eq!(recalled.len(), 1);
+    }
+
+    #[test]
+    fn store_records_embedding_model_and_dimension() {
+        let path = std::env::temp_dir().join(format!("prx-store-embmodel-{}.json", now_ms()));
+        let mut store = PersistentMemoryStore::open(&path).expect("open store");
+        let stored = store
+            .store(NewMemoryEntry {
+                text: "vector entry".to_string(),
+                category: "fact".to_string(),
+                scope: "global".to_string(),
+                importance: 0.5,
+                tags: Vec::new(),
+                embedding: Some(vec![0.6, 0.8, 0.0]),
+                embedding_model: Some("jina/v5".to_string()),
+                source: None,
+                keywords: Vec::new(),
+            })
+            .expect("store vector entry");
+        let plain = store
+            .store(NewMemoryEntry {
+                text: "plain entry".to_string(),
+                category: "fact".to_string(),
+                scope: "global".to_string(),
+                importance: 0.5,
+                tags: Vec::new(),
+                embedding: None,
+                embedding_model: Some("ignored".to_string()),
+                source: None,
+                keywords: Vec::new(),
+            })
+            .expect("store plain entry");
+        assert_eq!(plain.embedding_model, None);
+        assert_eq!(plain.embedding_dim, None);
+
+        let reopened = PersistentMemoryStore::open(&path).expect("reopen store");
+        let entry = reopened
+            .list(10)
+            .into_iter()
+            .find(|e| e.id == stored.id)
+            .expect("entry");
+        assert_eq!(entry.embedding_model.as_deref(), Some("jina/v5"));
+        assert_eq!(entry.embedding_dim, Some(3));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn encrypted_store_seals_text_and_embeddings_on_disk() {
+        const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+        const NEXT_KEY: &str = "ffeeddccbbaa99887766554433221100ffeeddccbbaa99887766554433221100";
+        let cipher = |primary: &str, previous: &[&str]| FieldCipher::from_hex_keys(primary, previous).expect("cipher");
+        let path = std::env::temp_dir().join(format!("prx-store-enc-{}.json", now_ms()));
+        let mut store = PersistentMemoryStore::open_with_cipher(&path, Some(cipher(KEY, &[]))).expect("open store");
+        let stored = store
+            .store(NewMemoryEntry {
+                text: "user prefers tabs over spaces".to_string(),
+                category: "preference".to_string(),
+                scope: "global".to_string(),
+                importance: 0.5,
+                tags: Vec::new(),
+                embedding: Some(vec![0.25, 0.5]),
+                embedding_model: None,
+                source: None,
+                keywords: Vec::new(),
+            })
+            .expect("store entry");
+        let on_disk = std::fs::read_to_string(&path).expect("read store");
+        assert!(!on_disk.contains("tabs"));
+        assert!(!on_disk.contains("0.25"));
+        assert!(PersistentMemoryStore::open(&path).is_err());
+
+        let mut rotated =
+            PersistentMemoryStore::open_with_cipher(&path, Some(cipher(NEXT_KEY, &[KEY]))).expect("open rotated");
+        let entry = rotated.list(1).into_iter().next().expect("entry");
+        assert_eq!(entry.text, stored.text);
+        assert_eq!(entry.embedding, Some(vec![0.25, 0.5]));
+        assert_eq!(rotated.rekey().expect("rekey"), 1);
+        assert!(PersistentMemoryStore::open_with_cipher(&path, Some(cipher(NEXT_KEY, &[]))).is_ok());
+
+        let _ = std::fs::remove_file(path);
+    }
 }
@@ -0,0 +1,199 @@
+//! Versioned upgrades of the JSON store format. Every file records the `schema_version` it was
+//! written with; opening an older file runs the steps above that version in order before the
+//! store is used, and a file from a newer release is refused rather than silently rewritten.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::integrity::sidecar;
+use crate::{MemoryEntry, StorageError, now_ms};
+
+/// Format written by this release. Files without a version predate versioning and count as 0.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How [`crate::PersistentMemoryStore::open_with_options`] treats an outdated store file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationOptions {
+    /// Report what would change and leave both the file and the loaded entries as they are.
+    pub dry_run: bool,
+    /// Copy the file to `<db>.pre-migrate-v<from>` before rewriting it.
+    pub backup: bool,
+}
+
+impl Default for MigrationOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            backup: true,
+        }
+    }
+}
+
+/// One step of a migration and how many entries it touched.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStep {
+    pub version: u32,
+    pub description: &'static str,
+    pub entries_changed: usize,
+}
+
+/// What opening a store upgraded, reported under `migration` in its stats.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub steps: Vec<MigrationStep>,
+    pub dry_run: bool,
+    /// Copy of the file taken before it was rewritten.
+    pub backup: Option<PathBuf>,
+    pub migrated_ms: u64,
+}
+
+struct Migration {
+    version: u32,
+    description: &'static str,
+    /// Upgrades one entry; `true` when it changed.
+    apply: fn(&mut MemoryEntry) -> bool,
+}
+
+/// Ordered by `version`; a new field that needs more than a serde default adds a step here and
+/// bumps [`SCHEMA_VERSION`].
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "record embedding_dim for stored embeddings and drop blank source fields",
+    apply: backfill_dim_and_source,
+}];
+
+/// Brings `entries` from `from` up to [`SCHEMA_VERSION`]. Returns `None` when the file is current.
+/// A dry run counts the changes on a copy. Otherwise the file at `path`, if any, is copied aside
+/// first when `options.backup` is set; the caller writes the upgraded store.
+pub(crate) fn run(
+    path: &Path,
+    from: u32,
+    entries: &mut [MemoryEntry],
+    options: MigrationOptions,
+) -> Result<Option<MigrationReport>, StorageError> {
+    if from > SCHEMA_VERSION {
+        return Err(StorageError::InvalidInput(format!(
+            "memory store {} has schema version {from}, newer than the supported {SCHEMA_VERSION}; upgrade prx-memory \
+             instead of opening it with an older release",
+            path.display()
+        )));
+    }
+    if from == SCHEMA_VERSION {
+        return Ok(None);
+    }
+    let mut scratch;
+    let target = if options.dry_run {
+        scratch = entries.to_vec();
+        &mut scratch
+    } else {
+        entries
+    };
+    let steps = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > from)
+        .map(|m| MigrationStep {
+            version: m.version,
+            description: m.description,
+            entries_changed: target
+                .iter_mut()
+                .map(|e| (m.apply)(e))
+                .filter(|&changed| changed)
+                .count(),
+        })
+        .collect();
+    let backup = if options.backup && !options.dry_run && path.exists() {
+        let copy = sidecar(path, &format!("pre-migrate-v{from}"));
+        fs::copy(path, &copy)?;
+        Some(copy)
+    } else {
+        None
+    };
+    Ok(Some(MigrationReport {
+        from_version: from,
+        to_version: SCHEMA_VERSION,
+        steps,
+        dry_run: options.dry_run,
+        backup,
+        migrated_ms: now_ms(),
+    }))
+}
+
+fn backfill_dim_and_source(entry: &mut MemoryEntry) -> bool {
+    let mut changed = false;
+    let dim = entry.embedding.as_ref().map(Vec::len);
+    if entry.embedding_dim != dim {
+        entry.embedding_dim = dim;
+        changed = true;
+    }
+    if let Some(source) = entry.source.take() {
+        let normalized = source.clone().normalized();
+        changed |= normalized.as_ref() != Some(&source);
+        entry.source = normalized;
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::PersistentMemoryStore;
+
+    #[test]
+    fn unversioned_stores_are_upgraded_with_a_backup() {
+        let path = std::env::temp_dir().join(format!("prx-migrate-{}-{}.json", std::process::id(), now_ms()));
+        let legacy = json!({"entries": [
+            {"id": "mem-1", "text": "uses postgres 16", "category": "fact", "scope": "global", "importance": 0.5,
+             "tags": [], "timestamp_ms": 1, "embedding": [0.1, 0.2, 0.3], "source": {"url": " ", "tool": "cli"}},
+            {"id": "mem-2", "text": "likes tabs", "category": "preference", "scope": "global", "importance": 0.4,
+             "tags": [], "timestamp_ms": 2}
+        ]});
+        fs::write(&path, serde_json::to_vec(&legacy).expect("json")).expect("write legacy");
+
+        let dry = MigrationOptions {
+            dry_run: true,
+            ..MigrationOptions::default()
+        };
+        let inspected = PersistentMemoryStore::open_with_options(&path, None, dry).expect("dry run");
+        let report = inspected.migration().expect("pending migration");
+        assert_eq!((report.from_version, report.dry_run), (0, true));
+        assert_eq!(report.steps.first().map(|s| s.entries_changed), Some(1));
+        assert_eq!(
+            inspected.list(10).iter().filter(|e| e.embedding_dim.is_some()).count(),
+            0
+        );
+        assert!(!fs::read_to_string(&path).expect("read").contains("schema_version"));
+
+        let store = PersistentMemoryStore::open(&path).expect("migrate");
+        let report = store.migration().expect("migration");
+        let backup = report.backup.clone().expect("backup");
+        assert_eq!(
+            fs::read(&backup).expect("backup"),
+            serde_json::to_vec(&legacy).expect("json")
+        );
+        let migrated = store.list(10).into_iter().find(|e| e.id == "mem-1").expect("entry");
+        assert_eq!(migrated.embedding_dim, Some(3));
+        assert_eq!(migrated.source.and_then(|s| s.url), None);
+        assert_eq!(store.stats()["schema_version"], json!(SCHEMA_VERSION));
+
+        assert!(
+            PersistentMemoryStore::open(&path)
+                .expect("reopen")
+                .migration()
+                .is_none()
+        );
+
+        fs::write(&path, br#"{"schema_version": 99, "entries": []}"#).expect("write newer");
+        let err = PersistentMemoryStore::open(&path).err().expect("newer schema");
+        assert!(err.to_string().contains("newer than the supported"), "{err}");
+
+        for leftover in [backup, sidecar(&path, "bak"), path] {
+            let _ = fs::remove_file(leftover);
+        }
+    }
+}
@@ -0,0 +1,489 @@
+//! Qdrant persistence (feature `qdrant-backend`) over the REST API, for users who already run a
+//! vector database. One collection per store; every entry is a point whose payload carries the
+//! entry, and `scope`, `category`, `tags` and `text` get payload indexes so recall filters and
+//! searches server-side before ranking the candidates locally.
+//!
+//! Point ids come from a sequence handed out by this process, so run one writer per collection.
+//! Entries are looked up by their `id` payload, which carries the entry id.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use reqwest::{Method, StatusCode};
+use serde_json::{Value, json};
+
+use crate::{
+    FieldCipher, IdFormat, MemoryEntry, NewMemoryEntry, RecallQuery, RecallResult, StorageBackend, StorageError,
+    new_id, now_ms, recall_entries,
+};
+
+/// Name of the dense vector in the collection.
+const VECTOR: &str = "embedding";
+
+/// Points matched by the text and tag indexes per recall.
+const LEXICAL_CANDIDATES: usize = 1_000;
+
+/// Points ranked when Qdrant cannot narrow a recall beyond scope and category.
+const SCAN_LIMIT: usize = 20_000;
+
+#[derive(Debug, Clone)]
+pub struct QdrantConfig {
+    /// REST endpoint, e.g. `http://localhost:6333`.
+    pub url: String,
+    pub collection: String,
+    /// Sent as the `api-key` header.
+    pub api_key: Option<String>,
+    /// Embedding dimension used when the collection has to be created.
+    pub dimensions: usize,
+}
+
+pub struct QdrantBackend {
+    http: reqwest::Client,
+    config: QdrantConfig,
+    rt: Arc<tokio::runtime::Runtime>,
+    next_id: u64,
+    id_format: IdFormat,
+    cipher: Option<FieldCipher>,
+}
+
+impl QdrantBackend {
+    /// Hands out new ids in `format`; [`IdFormat::Ulid`] unless set.
+    #[must_use]
+    pub const fn with_id_format(mut self, format: IdFormat) -> Self {
+        self.id_format = format;
+        self
+    }
+
+    /// Creates the collection and payload indexes unless the collection exists.
+    pub fn open(
+        config: QdrantConfig,
+        rt: Arc<tokio::runtime::Runtime>,
+        cipher: Option<FieldCipher>,
+    ) -> Result<Self, StorageError> {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| StorageError::InvalidInput(format!("qdrant client init failed: {e}")))?;
+        let mut backend = Self {
+            http,
+            config: QdrantConfig {
+                url: config.url.trim_end_matches('/').to_string(),
+                ..config
+            },
+            rt,
+            next_id: 1,
+            id_format: IdFormat::default(),
+            cipher,
+        };
+        let collection = backend.collection_path("");
+        let (status, _) = backend.request(Method::GET, &collection, None)?;
+        if status == StatusCode::NOT_FOUND {
+            backend.call(
+                Method::PUT,
+                &collection,
+                Some(json!({
+                    "vectors": {VECTOR: {"size": backend.config.dimensions, "distance": "Cosine"}}
+                })),
+            )?;
+            let indexes = [
+                ("scope", "keyword"),
+                ("category", "keyword"),
+                ("tags", "keyword"),
+                ("text", "text"),
+                ("seq", "integer"),
+                ("id", "keyword"),
+            ];
+            for (field, schema) in indexes {
+                backend.call(
+                    Method::PUT,
+                    &backend.collection_path("/index?wait=true"),
+                    Some(json!({"field_name": field, "field_schema": schema})),
+                )?;
+            }
+        } else if !status.is_success() {
+            return Err(StorageError::InvalidInput(format!(
+                "qdrant collection lookup answered {status}"
+            )));
+        }
+        // Also fails early when stored text was sealed with a key that is not configured.
+        let newest = backend.scroll(&json!({}), 1)?;
+        backend.next_id = newest.first().map_or(0, point_seq) + 1;
+        Ok(backend)
+    }
+
+    fn collection_path(&self, tail: &str) -> String {
+        format!("/collections/{}{tail}", self.config.collection)
+    }
+
+    fn request(&self, method: Method, path: &str, body: Option<Value>) -> Result<(StatusCode, Value), StorageError> {
+        let mut request = self.http.request(method, format!("{}{path}", self.config.url));
+        if let Some(key) = &self.config.api_key {
+            request = request.header("api-key", key);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        self.rt
+            .block_on(async {
+                let response = request.send().await?;
+                let status = response.status();
+                let body = response.json::<Value>().await.unwrap_or(Value::Null);
+                Ok((status, body))
+            })
+            .map_err(|e: reqwest::Error| StorageError::InvalidInput(format!("qdrant request failed: {e}")))
+    }
+
+    /// Sends a request that must succeed and returns its `result`.
+    fn call(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value, StorageError> {
+        let (status, mut body) = self.request(method, path, body)?;
+        if !status.is_success() {
+            let reason = body
+                .pointer("/status/error")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            return Err(StorageError::InvalidInput(format!(
+                "qdrant answered {status}: {reason}"
+            )));
+        }
+        Ok(body.get_mut("result").map(Value::take).unwrap_or_default())
+    }
+
+    /// Newest points first.
+    fn scroll(&self, filter: &Value, limit: usize) -> Result<Vec<Value>, StorageError> {
+        let result = self.call(
+            Method::POST,
+            &self.collection_path("/points/scroll"),
+            Some(json!({
+                "filter": filter,
+                "limit": limit.max(1),
+                "with_payload": true,
+                "with_vector": true,
+                "order_by": {"key": "seq", "direction": "desc"}
+            })),
+        )?;
+        self.open_points(result.get("points"))
+    }
+
+    fn open_points(&self, points: Option<&Value>) -> Result<Vec<Value>, StorageError> {
+        let points = points.and_then(Value::as_array).cloned().unwrap_or_default();
+        for point in &points {
+            entry_from_point(point, self.cipher.as_ref())?;
+        }
+        Ok(points)
+    }
+
+    fn entries(&self, points: &[Value]) -> Vec<MemoryEntry> {
+        points
+            .iter()
+            .filter_map(|p| entry_from_point(p, self.cipher.as_ref()).ok())
+            .collect()
+    }
+
+    /// Candidate points for `query`: nearest neighbours plus text and tag matches, or a filtered
+    /// scan when neither applies.
+    fn candidates(&self, query: &RecallQuery) -> Result<Vec<MemoryEntry>, StorageError> {
+        let must = scope_category_filter(query);
+        if self.cipher.is_some() {
+            return Ok(self.entries(&self.scroll(&json!({"must": must}), SCAN_LIMIT)?));
+        }
+        let mut points = Vec::new();
+        if let Some(vector) = &query.query_embedding {
+            let result = self.call(
+                Method::POST,
+                &self.collection_path("/points/search"),
+                Some(json!({
+                    "vector": {"name": VECTOR, "vector": vector},
+                    "filter": {"must": must},
+                    "limit": query.limit.max(10).saturating_mul(10),
+                    "with_payload": true,
+                    "with_vector": true
+                })),
+            )?;
+            points.extend(self.open_points(Some(&result))?);
+        }
+        let terms = query_terms(&query.query);
+        if !terms.is_empty() {
+            let mut should = terms
+                .iter()
+                .map(|t| json!({"key": "text", "match": {"text": t}}))
+                .collect::<Vec<_>>();
+            should.push(json!({"key": "tags", "match": {"any": terms}}));
+            should.push(json!({"key": "keywords", "match": {"any": terms}}));
+            points.extend(self.scroll(&json!({"must": must, "should": should}), LEXICAL_CANDIDATES)?);
+        }
+        if query.query_embedding.is_none() && terms.is_empty() {
+            points = self.scroll(&json!({"must": must}), SCAN_LIMIT)?;
+        }
+        let mut seen = HashSet::new();
+        let mut entries = self.entries(&points);
+        entries.retain(|e| seen.insert(e.id.clone()));
+        Ok(entries)
+    }
+
+    fn point(&self, entry: &MemoryEntry, seq: u64) -> Result<Value, StorageError> {
+        let mut payload = entry.clone();
+        let sealed_embedding = match &self.cipher {
+            Some(cipher) => cipher.seal_entry(&mut payload)?,
+            None => None,
+        };
+        let vector = payload.embedding.take();
+        let mut payload = serde_json::to_value(payload)?;
+        if let Some(obj) = payload.as_object_mut() {
+            obj.remove("embedding");
+            obj.insert("seq".to_string(), seq.into());
+            if let Some(sealed) = sealed_embedding {
+                obj.insert("sealed_embedding".to_string(), sealed.into());
+            }
+        }
+        let vectors = vector.map_or_else(|| json!({}), |v| json!({VECTOR: v}));
+        Ok(json!({"id": seq, "vector": vectors, "payload": payload}))
+    }
+
+    /// Which of `ids` exist, as `(entry id, point id)` pairs.
+    fn existing(&self, ids: &[String]) -> Result<Vec<(String, u64)>, StorageError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let result = self.call(
+            Method::POST,
+            &self.collection_path("/points/scroll"),
+            Some(json!({
+                "filter": {"must": [{"key": "id", "match": {"any": ids}}]},
+                "limit": ids.len(),
+                "with_payload": ["id"],
+                "with_vector": false
+            })),
+        )?;
+        let mut seen = HashSet::new();
+        Ok(result
+            .get("points")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|p| Some((p.pointer("/payload/id")?.as_str()?.to_string(), p.get("id")?.as_u64()?)))
+            .filter(|(id, _)| seen.insert(id.clone()))
+            .collect())
+    }
+}
+
+impl StorageBackend for QdrantBackend {
+    fn store(&mut self, new_entry: NewMemoryEntry) -> Result<MemoryEntry, StorageError> {
+        if new_entry.text.trim().is_empty() {
+            return Err(StorageError::InvalidInput("text cannot be empty".to_string()));
+        }
+        let seq = self.next_id;
+        let entry = MemoryEntry {
+            id: new_id(self.id_format, seq)?,
+            text: new_entry.text.to_lowercase(),
+            category: new_entry.category,
+            scope: new_entry.scope,
+            importance: new_entry.importance.clamp(0.0, 1.0),
+            tags: new_entry.tags.into_iter().map(|t| t.to_lowercase()).collect(),
+            timestamp_ms: now_ms(),
+            embedding_dim: new_entry.embedding.as_ref().map(Vec::len),
+            embedding_model: new_entry.embedding.as_ref().and(new_entry.embedding_model),
+            embedding: new_entry.embedding,
+            source: new_entry.source,
+            keywords: new_entry.keywords,
+        };
+        let point = self.point(&entry, seq)?;
+        self.call(
+            Method::PUT,
+            &self.collection_path("/points?wait=true"),
+            Some(json!({"points": [point]})),
+        )?;
+        self.next_id += 1;
+        Ok(entry)
+    }
+
+    /// Request failures recall nothing, like the other backends.
+    fn recall(&self, query: RecallQuery) -> Vec<RecallResult> {
+        let entries = self.candidates(&query).unwrap_or_default();
+        recall_entries(&entries, query)
+    }
+
+    fn forget_by_id(&mut self, id: &str) -> Result<bool, StorageError> {
+        Ok(!self.forget_batch(&[id.to_string()])?.is_empty())
+    }
+
+    fn forget_batch(&mut self, ids: &[String]) -> Result<Vec<String>, StorageError> {
+        let existing = self.existing(ids)?;
+        if !existing.is_empty() {
+            self.call(
+                Method::POST,
+                &self.collection_path("/points/delete?wait=true"),
+                Some(json!({"points": existing.iter().map(|(_, seq)| seq).collect::<Vec<_>>()})),
+            )?;
+        }
+        Ok(existing.into_iter().map(|(id, _)| id).collect())
+    }
+
+    fn list(&self, limit: usize) -> Vec<MemoryEntry> {
+        self.scroll(&json!({}), limit.min(SCAN_LIMIT))
+            .map(|points| self.entries(&points))
+            .unwrap_or_default()
+    }
+
+    fn stats(&self) -> serde_json::Value {
+        let count = self
+            .call(
+                Method::POST,
+                &self.collection_path("/points/count"),
+                Some(json!({"exact": true})),
+            )
+            .ok()
+            .and_then(|r| r.get("count").and_then(Value::as_u64))
+            .unwrap_or(0);
+        json!({
+            "backend": "qdrant",
+            "url": self.config.url,
+            "collection": self.config.collection,
+            "count": count
+        })
+    }
+
+    /// Re-writes every point's payload sealed with the primary key, one scroll page at a time.
+    fn rekey(&mut self) -> Result<usize, StorageError> {
+        if self.cipher.is_none() {
+            return Err(StorageError::InvalidInput("encryption is not configured".to_string()));
+        }
+        let mut rewritten = 0;
+        let mut offset = Value::Null;
+        loop {
+            let page = self.call(
+                Method::POST,
+                &self.collection_path("/points/scroll"),
+                Some(json!({"limit": 256, "offset": offset, "with_payload": true, "with_vector": true})),
+            )?;
+            let points = self
+                .open_points(page.get("points"))?
+                .iter()
+                .map(|p| {
+                    let entry = entry_from_point(p, self.cipher.as_ref())?;
+                    self.point(&entry, point_seq(p))
+                })
+                .collect::<Result<Vec<_>, StorageError>>()?;
+            if !points.is_empty() {
+                rewritten += points.len();
+                self.call(
+                    Method::PUT,
+                    &self.collection_path("/points?wait=true"),
+                    Some(json!({"points": points})),
+                )?;
+            }
+            offset = page.get("next_page_offset").cloned().unwrap_or_default();
+            if offset.is_null() {
+                return Ok(rewritten);
+            }
+        }
+    }
+}
+
+fn point_seq(point: &Value) -> u64 {
+    point.get("id").and_then(Value::as_u64).unwrap_or(0)
+}
+
+fn scope_category_filter(query: &RecallQuery) -> Vec<Value> {
+    [("scope", &query.scope), ("category", &query.category)]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|v| json!({"key": key, "match": {"value": v}})))
+        .collect()
+}
+
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+fn entry_from_point(point: &Value, cipher: Option<&FieldCipher>) -> Result<MemoryEntry, StorageError> {
+    let mut payload = point.get("payload").cloned().unwrap_or_default();
+    let sealed_embedding = payload
+        .as_object_mut()
+        .and_then(|obj| {
+            obj.remove("seq");
+            obj.remove("sealed_embedding")
+        })
+        .and_then(|v| v.as_str().map(str::to_string));
+    let mut entry: MemoryEntry = serde_json::from_value(payload)?;
+    entry.embedding = point
+        .pointer(&format!("/vector/{VECTOR}"))
+        .map(|v| serde_json::from_value(v.clone()))
+        .transpose()?;
+    match cipher {
+        Some(cipher) => cipher.open_entry(&mut entry, sealed_embedding.as_deref())?,
+        None if FieldCipher::is_sealed(&entry.text) || sealed_embedding.is_some() => {
+            return Err(StorageError::InvalidInput(
+                "memory store is encrypted but no encryption key is configured".to_string(),
+            ));
+        }
+        None => {}
+    }
+    entry.embedding_dim = entry.embedding.as_ref().map(Vec::len);
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(scope: Option<&str>, category: Option<&str>) -> RecallQuery {
+        RecallQuery {
+            query: String::new(),
+            query_embedding: None,
+            scope: scope.map(str::to_string),
+            category: category.map(str::to_string),
+            limit: 5,
+            vector_weight: None,
+            lexical_weight: None,
+            diversity: None,
+            fusion: None,
+        }
+    }
+
+    #[test]
+    fn scope_and_category_become_payload_filters() {
+        assert!(scope_category_filter(&query(None, None)).is_empty());
+        assert_eq!(
+            scope_category_filter(&query(Some("project:infra"), Some("fact"))),
+            vec![
+                json!({"key": "scope", "match": {"value": "project:infra"}}),
+                json!({"key": "category", "match": {"value": "fact"}})
+            ]
+        );
+    }
+
+    #[test]
+    fn points_round_trip_to_entries() {
+        let point = json!({
+            "id": 7,
+            "vector": {"embedding": [0.5, -0.25]},
+            "payload": {
+                "id": "mem-7",
+                "seq": 7,
+                "text": "rollbacks use the blue-green switch",
+                "category": "fact",
+                "scope": "global",
+                "importance": 0.6,
+                "tags": ["deploy"],
+                "timestamp_ms": 1_700_000_000_000_u64
+            }
+        });
+        let entry = entry_from_point(&point, None).expect("entry");
+        assert_eq!(entry.id, "mem-7");
+        assert_eq!(entry.embedding, Some(vec![0.5, -0.25]));
+        assert_eq!(entry.embedding_dim, Some(2));
+        assert_eq!(point_seq(&point), 7);
+
+        let mut sealed = point;
+        if let Some(payload) = sealed.get_mut("payload").and_then(Value::as_object_mut) {
+            payload.insert("sealed_embedding".to_string(), json!("sealed"));
+        }
+        assert!(entry_from_point(&sealed, None).is_err());
+    }
+}
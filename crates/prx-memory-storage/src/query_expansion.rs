@@ -0,0 +1,271 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use parking_lot::RwLock;
+
+use crate::{StorageError, tokenize};
+
+const BUILTIN_SYNONYMS: &[&[&str]] = &[
+    &["db", "database"],
+    &["k8s", "kubernetes"],
+    &["config", "configuration"],
+    &["auth", "authentication"],
+    &["repo", "repository"],
+    &["env", "environment"],
+    &["deps", "dependencies"],
+    &["perf", "performance"],
+    &["postgres", "postgresql"],
+    &["llm", "model"],
+];
+
+/// A synonym dictionary: the built-in groups plus any added on top. The process-wide one is
+/// extended by [`register_synonyms`]; a store with its own dictionary runs its calls under
+/// [`with_synonyms`].
+#[derive(Debug, Clone)]
+pub struct SynonymTable {
+    terms: HashMap<String, Vec<String>>,
+}
+
+impl Default for SynonymTable {
+    fn default() -> Self {
+        let mut table = Self { terms: HashMap::new() };
+        for group in BUILTIN_SYNONYMS {
+            table.insert_group(&group.iter().map(|s| (*s).to_string()).collect::<Vec<_>>());
+        }
+        table
+    }
+}
+
+impl SynonymTable {
+    /// Adds synonym groups, each a set of interchangeable terms; returns how many had two or more.
+    pub fn register(&mut self, groups: Vec<Vec<String>>) -> usize {
+        let mut added = 0;
+        for group in groups {
+            let group = group
+                .into_iter()
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>();
+            if group.len() >= 2 {
+                self.insert_group(&group);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Adds the groups of a dictionary file: one comma-separated group per line, `#` starts a comment.
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<usize, StorageError> {
+        let raw = fs::read_to_string(path)?;
+        let groups = raw
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.split(',').map(str::to_string).collect())
+            .collect();
+        Ok(self.register(groups))
+    }
+
+    fn get(&self, term: &str) -> Option<&Vec<String>> {
+        self.terms.get(term)
+    }
+
+    fn insert_group(&mut self, group: &[String]) {
+        for term in group {
+            let entry = self.terms.entry(term.clone()).or_default();
+            for other in group {
+                if other != term && !entry.contains(other) {
+                    entry.push(other.clone());
+                }
+            }
+        }
+    }
+}
+
+thread_local! {
+    static TABLE_OVERRIDE: RefCell<Option<Arc<SynonymTable>>> = const { RefCell::new(None) };
+}
+
+fn synonym_table() -> &'static RwLock<SynonymTable> {
+    static TABLE: OnceLock<RwLock<SynonymTable>> = OnceLock::new();
+    TABLE.get_or_init(|| RwLock::new(SynonymTable::default()))
+}
+
+/// Runs `f` against the table in effect on this thread: a [`with_synonyms`] override, else the
+/// process-wide one.
+fn with_current_table<T>(f: impl FnOnce(&SynonymTable) -> T) -> T {
+    match TABLE_OVERRIDE.with_borrow(Clone::clone) {
+        Some(table) => f(&table),
+        None => f(&synonym_table().read()),
+    }
+}
+
+/// Runs `f` with `table` in effect on the current thread only, so stores with different
+/// dictionaries can share a process.
+pub fn with_synonyms<T>(table: Arc<SynonymTable>, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Arc<SynonymTable>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            TABLE_OVERRIDE.set(self.0.take());
+        }
+    }
+    let _restore = Restore(TABLE_OVERRIDE.replace(Some(table)));
+    f()
+}
+
+/// Adds synonym groups to the process-wide dictionary, on top of the built-in one.
+/// Each group is a set of interchangeable lowercase terms.
+pub fn register_synonyms(groups: Vec<Vec<String>>) -> usize {
+    synonym_table().write().register(groups)
+}
+
+/// Light English suffix stripping; only used to widen substring matches.
+pub fn stem(term: &str) -> String {
+    let len = term.chars().count();
+    if !term.is_ascii() || len <= 3 {
+        return term.to_string();
+    }
+    if let Some(base) = term.strip_suffix("ies").filter(|_| len > 4) {
+        return format!("{base}y");
+    }
+    for (suffix, min_len) in [("ing", 6), ("ed", 5), ("es", 5), ("s", 4)] {
+        if let Some(base) = term
+            .strip_suffix(suffix)
+            .filter(|b| len >= min_len && !b.ends_with('s'))
+        {
+            return base.to_string();
+        }
+    }
+    term.to_string()
+}
+
+/// Returns `term` followed by its stem and synonyms (deduplicated), for lexical matching.
+pub fn expand_term(term: &str) -> Vec<String> {
+    let mut variants = vec![term.to_string()];
+    let stemmed = stem(term);
+    with_current_table(|table| {
+        for key in [term, stemmed.as_str()] {
+            for syn in table.get(key).into_iter().flatten() {
+                if !variants.contains(syn) {
+                    variants.push(syn.clone());
+                }
+            }
+        }
+    });
+    if stemmed.len() >= 3 && !variants.contains(&stemmed) {
+        variants.insert(1, stemmed);
+    }
+    variants
+}
+
+/// Keywords to store with an entry whose text is `text`, so lexical recall finds it by paraphrase.
+///
+/// For each alias group (a word or phrase and its keywords) the text mentions, the group's other
+/// members, then with `synonyms` the dictionary's synonyms of the text's words. Words already in
+/// the text are left out and at most `max` keywords are returned.
+pub fn expansion_keywords(
+    text: &str,
+    aliases: &BTreeMap<String, Vec<String>>,
+    synonyms: bool,
+    max: usize,
+) -> Vec<String> {
+    let words = tokenize(text);
+    let stems = words.iter().map(|w| stem(w)).collect::<Vec<_>>();
+    let flat = words.join(" ");
+    let mentions = |term: &str| {
+        let term = tokenize(term);
+        match term.as_slice() {
+            [] => false,
+            [word] => stems.contains(&stem(word)),
+            phrase => format!(" {flat} ").contains(&format!(" {} ", phrase.join(" "))),
+        }
+    };
+    let mut out: Vec<String> = Vec::new();
+    let mut push = |keyword: &str| {
+        let keyword = keyword.trim().to_lowercase();
+        if !keyword.is_empty() && !words.contains(&keyword) && !out.contains(&keyword) {
+            out.push(keyword);
+        }
+    };
+    for (phrase, keywords) in aliases {
+        let group = std::iter::once(phrase).chain(keywords).collect::<Vec<_>>();
+        if group.iter().any(|term| mentions(term)) {
+            for term in group.iter().filter(|term| !mentions(term)) {
+                push(term);
+            }
+        }
+    }
+    if synonyms {
+        with_current_table(|table| {
+            for key in words.iter().chain(&stems) {
+                for syn in table.get(key).into_iter().flatten() {
+                    push(syn);
+                }
+            }
+        });
+    }
+    out.truncate(max);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stems_and_expands_synonyms() {
+        assert_eq!(stem("embeddings"), "embedding");
+        assert_eq!(stem("queries"), "query");
+        assert_eq!(stem("class"), "class");
+        assert!(expand_term("db").contains(&"database".to_string()));
+
+        register_synonyms(vec![vec!["mq".to_string(), "queue".to_string()]]);
+        assert!(expand_term("mq").contains(&"queue".to_string()));
+    }
+
+    #[test]
+    fn scoped_synonyms_stay_on_their_thread() {
+        let mut table = SynonymTable::default();
+        assert_eq!(
+            table.register(vec![vec!["pr".to_string(), " Pull Request ".to_string()]]),
+            1
+        );
+        let scoped = with_synonyms(Arc::new(table), || expand_term("pr"));
+        assert!(scoped.contains(&"pull request".to_string()));
+        assert!(scoped.contains(&"pr".to_string()));
+        assert!(!expand_term("pr").contains(&"pull request".to_string()));
+        let other = std::thread::spawn(|| expand_term("pr")).join().expect("thread");
+        assert_eq!(other, ["pr"]);
+    }
+
+    #[test]
+    fn expansion_keywords_cover_aliases_both_ways_and_synonyms() {
+        let aliases = BTreeMap::from([
+            (
+                "rollback".to_string(),
+                vec!["revert".to_string(), "undo deploy".to_string()],
+            ),
+            ("on call".to_string(), vec!["pager".to_string()]),
+        ]);
+        assert_eq!(
+            expansion_keywords("Rollbacks need the db snapshot", &aliases, true, 8),
+            ["revert", "undo deploy", "database"]
+        );
+        assert_eq!(
+            expansion_keywords("who is on call this week", &aliases, false, 8),
+            ["pager"]
+        );
+        assert_eq!(
+            expansion_keywords("we revert by hand", &aliases, false, 8),
+            ["rollback", "undo deploy"]
+        );
+        assert_eq!(
+            expansion_keywords("we revert by hand", &aliases, false, 1),
+            ["rollback"]
+        );
+        assert!(expansion_keywords("nothing to see", &aliases, true, 8).is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+//! Recall query syntax: `"quoted phrases"`, `AND` / `OR` / `NOT` (or a leading `-`), and
+//! `tag:` / `scope:` / `category:` prefixes. Plain words still only rank; everything else becomes a
+//! filter a result has to pass. `OR` binds tighter than `AND`, and there are no parentheses.
+
+use crate::{MemoryEntry, expand_term};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryField {
+    Tag,
+    Scope,
+    Category,
+}
+
+impl QueryField {
+    fn parse(key: &str) -> Option<Self> {
+        match key.to_ascii_lowercase().as_str() {
+            "tag" | "tags" => Some(Self::Tag),
+            "scope" => Some(Self::Scope),
+            "category" | "cat" => Some(Self::Category),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryTermKind {
+    Word(String),
+    Phrase(String),
+    Field(QueryField, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTerm {
+    pub kind: QueryTermKind,
+    pub negated: bool,
+}
+
+impl QueryTerm {
+    fn matches(&self, entry: &MemoryEntry, haystack: &str) -> bool {
+        let hit = match &self.kind {
+            QueryTermKind::Word(word) => {
+                let variants = expand_term(word);
+                let found = |within: &str| variants.iter().any(|v| !v.is_empty() && within.contains(v.as_str()));
+                found(haystack)
+                    || entry.keywords.iter().any(|k| found(k))
+                    || entry.tags.iter().any(|t| t.eq_ignore_ascii_case(word))
+            }
+            QueryTermKind::Phrase(phrase) => haystack.contains(phrase.as_str()),
+            QueryTermKind::Field(QueryField::Tag, value) => entry.tags.iter().any(|t| tag_matches(t, value)),
+            QueryTermKind::Field(QueryField::Scope, value) => entry.scope == *value,
+            QueryTermKind::Field(QueryField::Category, value) => entry.category.eq_ignore_ascii_case(value),
+        };
+        hit != self.negated
+    }
+}
+
+/// A recall query split into the text that ranks results and the filters that select them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedQuery {
+    /// Words and phrase words to rank by; operators, negated terms and field prefixes removed.
+    pub text: String,
+    /// Every group must have at least one matching term.
+    pub groups: Vec<Vec<QueryTerm>>,
+}
+
+impl ParsedQuery {
+    /// Whether the query filters results beyond ranking them.
+    pub fn has_filters(&self) -> bool {
+        !self.groups.is_empty()
+    }
+
+    pub fn matches(&self, entry: &MemoryEntry) -> bool {
+        if self.groups.is_empty() {
+            return true;
+        }
+        let haystack = normalize(&entry.text);
+        self.groups
+            .iter()
+            .all(|group| group.iter().any(|term| term.matches(entry, &haystack)))
+    }
+}
+
+/// Parses `raw`. A query without any syntax parses to its own text and no filters.
+pub fn parse_query(raw: &str) -> ParsedQuery {
+    let mut groups: Vec<(Vec<QueryTerm>, bool)> = Vec::new();
+    let mut text = Vec::new();
+    let (mut and, mut or, mut not) = (false, false, false);
+    for lexeme in lex(raw) {
+        if !lexeme.quoted {
+            match lexeme.text.as_str() {
+                "AND" => {
+                    and = true;
+                    if let Some(last) = groups.last_mut() {
+                        last.1 = true;
+                    }
+                    continue;
+                }
+                "OR" => {
+                    or = true;
+                    continue;
+                }
+                "NOT" => {
+                    not = true;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        let Some(term) = lexeme.into_term(not) else {
+            continue;
+        };
+        let filter = term.negated || !matches!(term.kind, QueryTermKind::Word(_));
+        if !term.negated {
+            if let QueryTermKind::Word(words) | QueryTermKind::Phrase(words) = &term.kind {
+                text.push(words.clone());
+            }
+        }
+        match groups.last_mut() {
+            Some(last) if or => {
+                last.0.push(term);
+                last.1 = true;
+            }
+            _ => groups.push((vec![term], and || filter)),
+        }
+        (and, or, not) = (false, false, false);
+    }
+    ParsedQuery {
+        text: text.join(" "),
+        groups: groups
+            .into_iter()
+            .filter_map(|(terms, required)| required.then_some(terms))
+            .collect(),
+    }
+}
+
+/// `value` is the whole tag, or the value of a structured `key:value` tag when it has no key itself.
+fn tag_matches(tag: &str, value: &str) -> bool {
+    tag.eq_ignore_ascii_case(value)
+        || (!value.contains(':') && tag.rsplit_once(':').is_some_and(|(_, v)| v.eq_ignore_ascii_case(value)))
+}
+
+/// Lowercased, with whitespace runs collapsed so phrases match across line breaks.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+struct Lexeme {
+    text: String,
+    /// The lexeme opened with a quote, after an optional `-`: a phrase, never an operator or field.
+    quoted: bool,
+}
+
+impl Lexeme {
+    fn into_term(self, not: bool) -> Option<QueryTerm> {
+        let (negated, body) = match self.text.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, self.text.as_str()),
+        };
+        let kind = if self.quoted {
+            let phrase = normalize(body);
+            if phrase.is_empty() {
+                return None;
+            }
+            QueryTermKind::Phrase(phrase)
+        } else if let Some((field, value)) = body
+            .split_once(':')
+            .and_then(|(key, value)| QueryField::parse(key).zip(Some(value.trim())))
+            .filter(|(_, value)| !value.is_empty())
+        {
+            let value = if field == QueryField::Scope {
+                value.to_string()
+            } else {
+                value.to_lowercase()
+            };
+            QueryTermKind::Field(field, value)
+        } else {
+            QueryTermKind::Word(body.to_lowercase())
+        };
+        Some(QueryTerm {
+            kind,
+            negated: negated != not,
+        })
+    }
+}
+
+/// Splits on whitespace outside quotes; quotes are dropped and an unclosed one runs to the end.
+fn lex(raw: &str) -> Vec<Lexeme> {
+    let mut out = Vec::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut text = String::new();
+        let mut quoted = false;
+        let mut in_quote = false;
+        while let Some(&c) = chars.peek() {
+            if c == '"' {
+                if !in_quote && (text.is_empty() || text == "-") {
+                    quoted = true;
+                }
+                in_quote = !in_quote;
+            } else if c.is_whitespace() && !in_quote {
+                break;
+            } else {
+                text.push(c);
+            }
+            chars.next();
+        }
+        if !text.is_empty() {
+            out.push(Lexeme { text, quoted });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str, scope: &str, category: &str, tags: &[&str]) -> MemoryEntry {
+        MemoryEntry {
+            id: "mem-1".to_string(),
+            text: text.to_string(),
+            category: category.to_string(),
+            scope: scope.to_string(),
+            importance: 0.5,
+            tags: tags.iter().map(|t| (*t).to_string()).collect(),
+            timestamp_ms: 0,
+            embedding: None,
+            embedding_model: None,
+            embedding_dim: None,
+            source: None,
+            keywords: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_phrases_operators_and_fields() {
+        let plain = parse_query("deploy pipeline cache");
+        assert_eq!(plain.text, "deploy pipeline cache");
+        assert!(!plain.has_filters());
+
+        let parsed =
+            parse_query(r#""rolling  Restart" tag:Ops scope:project:alpha redis OR postgres NOT flaky -category:todo"#);
+        assert_eq!(parsed.text, "rolling restart redis postgres");
+        assert_eq!(parsed.groups.len(), 6);
+        assert_eq!(
+            parsed.groups.get(2),
+            Some(&vec![QueryTerm {
+                kind: QueryTermKind::Field(QueryField::Scope, "project:alpha".to_string()),
+                negated: false,
+            }])
+        );
+        assert_eq!(parsed.groups.get(3).map(Vec::len), Some(2));
+
+        let hit = entry(
+            "Do a rolling\nrestart after the Redis upgrade",
+            "project:alpha",
+            "fact",
+            &["ops"],
+        );
+        assert!(parsed.matches(&hit));
+        let flaky = entry("rolling restart of redis is flaky", "project:alpha", "fact", &["ops"]);
+        assert!(!parsed.matches(&flaky));
+        let todo = entry("rolling restart of postgres", "project:alpha", "todo", &["ops"]);
+        assert!(!parsed.matches(&todo));
+        let other_scope = entry("rolling restart of postgres", "project:beta", "fact", &["ops"]);
+        assert!(!parsed.matches(&other_scope));
+
+        let and = parse_query("alpha AND beta gamma");
+        assert_eq!(and.groups.len(), 2);
+        assert_eq!(parse_query(r#"tag:"two words" url:http"#).text, "url:http");
+        assert!(parse_query("tag:ops").matches(&entry("x", "global", "fact", &["domain:ops"])));
+        assert!(!parse_query("tag:team:ops").matches(&entry("x", "global", "fact", &["domain:ops"])));
+    }
+}
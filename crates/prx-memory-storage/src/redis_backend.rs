@@ -0,0 +1,401 @@
+//! Redis persistence (feature `redis-backend`) for deployments that already run Redis and want
+//! several `prx-memoryd` replicas to share one memory.
+//!
+//! Every entry is a hash at `<prefix>:entry:<id>`. `<prefix>:ids` orders them by id and
+//! `<prefix>:next_id` hands out ids atomically, so replicas never collide. When `RediSearch` is
+//! available, recall narrows the candidates with `FT.SEARCH` before ranking them locally; without
+//! it every entry is ranked.
+
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use redis::{Connection, Pipeline};
+
+use crate::{
+    FieldCipher, IdFormat, MemoryEntry, NewMemoryEntry, RecallQuery, RecallResult, StorageBackend, StorageError,
+    new_id, now_ms, recall_entries,
+};
+
+/// Candidates fetched from the search index per recall before local ranking.
+const SEARCH_CANDIDATES: usize = 1_000;
+
+pub struct RedisBackend {
+    conn: Mutex<Connection>,
+    prefix: String,
+    /// `FT.SEARCH` index name when `RediSearch` is enabled and the text is not encrypted.
+    search_index: Option<String>,
+    id_format: IdFormat,
+    cipher: Option<FieldCipher>,
+}
+
+impl RedisBackend {
+    /// Connects to `url` (`redis://host:6379/0`) and keeps every key under `prefix`. With
+    /// `search`, creates the `RediSearch` index unless it exists; encrypted text cannot be indexed,
+    /// so `search` is ignored when `cipher` is set.
+    pub fn open(url: &str, prefix: &str, search: bool, cipher: Option<FieldCipher>) -> Result<Self, StorageError> {
+        let prefix = prefix.trim_end_matches(':').to_string();
+        if prefix.is_empty() {
+            return Err(StorageError::InvalidInput(
+                "redis key prefix cannot be empty".to_string(),
+            ));
+        }
+        let mut conn = redis::Client::open(url)
+            .and_then(|client| client.get_connection())
+            .map_err(|e| StorageError::InvalidInput(format!("redis connect failed: {e}")))?;
+        let search_index = if search && cipher.is_none() {
+            let index = format!("{prefix}:idx");
+            let created = redis::cmd("FT.CREATE")
+                .arg(&index)
+                .arg(&["ON", "HASH", "PREFIX", "1"])
+                .arg(format!("{prefix}:entry:"))
+                .arg(&["SCHEMA", "text", "TEXT", "category", "TAG", "scope", "TAG"])
+                .query::<()>(&mut conn);
+            match created {
+                Ok(()) => {}
+                Err(err) if err.to_string().contains("Index already exists") => {}
+                Err(err) => return Err(StorageError::InvalidInput(format!("redisearch index failed: {err}"))),
+            }
+            Some(index)
+        } else {
+            None
+        };
+        let backend = Self {
+            conn: Mutex::new(conn),
+            prefix,
+            search_index,
+            id_format: IdFormat::default(),
+            cipher,
+        };
+        // Fails early when stored text was sealed with a key that is not configured.
+        backend.load(&backend.ids(0, 1)?)?;
+        Ok(backend)
+    }
+
+    /// Hands out new ids in `format`; [`IdFormat::Ulid`] unless set.
+    #[must_use]
+    pub const fn with_id_format(mut self, format: IdFormat) -> Self {
+        self.id_format = format;
+        self
+    }
+
+    fn entry_key(&self, id: &str) -> String {
+        format!("{}:entry:{id}", self.prefix)
+    }
+
+    fn ids_key(&self) -> String {
+        format!("{}:ids", self.prefix)
+    }
+
+    /// Ids newest first, skipping `offset` and returning at most `limit`.
+    fn ids(&self, offset: usize, limit: usize) -> Result<Vec<String>, StorageError> {
+        let start = isize::try_from(offset).unwrap_or(isize::MAX);
+        let stop = isize::try_from(limit).map_or(-1, |n| start.saturating_add(n) - 1);
+        redis::cmd("ZREVRANGE")
+            .arg(self.ids_key())
+            .arg(start)
+            .arg(stop)
+            .query(&mut *self.conn.lock())
+            .map_err(redis_error("read"))
+    }
+
+    fn load(&self, ids: &[String]) -> Result<Vec<MemoryEntry>, StorageError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = redis::pipe();
+        for id in ids {
+            pipe.cmd("HGETALL").arg(self.entry_key(id));
+        }
+        let hashes: Vec<HashMap<String, String>> = pipe.query(&mut *self.conn.lock()).map_err(redis_error("read"))?;
+        hashes
+            .into_iter()
+            .filter(|fields| !fields.is_empty())
+            .map(|fields| entry_from_fields(fields, self.cipher.as_ref()))
+            .collect()
+    }
+
+    fn write_entry(&self, pipe: &mut Pipeline, entry: &MemoryEntry, seq: u64) -> Result<(), StorageError> {
+        pipe.cmd("HSET")
+            .arg(self.entry_key(&entry.id))
+            .arg(entry_fields(entry, self.cipher.as_ref())?)
+            .ignore();
+        pipe.cmd("ZADD").arg(self.ids_key()).arg(seq).arg(&entry.id).ignore();
+        Ok(())
+    }
+
+    /// Ids matching the lexical part of `query` in the search index.
+    fn search_ids(&self, index: &str, query: &RecallQuery) -> Result<Vec<String>, StorageError> {
+        let mut filter = Vec::new();
+        if let Some(scope) = &query.scope {
+            filter.push(format!("@scope:{{{}}}", escape_search(scope)));
+        }
+        if let Some(category) = &query.category {
+            filter.push(format!("@category:{{{}}}", escape_search(category)));
+        }
+        let terms = query
+            .query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|t| !t.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        if !terms.is_empty() {
+            filter.push(format!("@text:({})", terms.join("|")));
+        }
+        let expression = if filter.is_empty() {
+            "*".to_string()
+        } else {
+            filter.join(" ")
+        };
+        let reply: Vec<redis::Value> = redis::cmd("FT.SEARCH")
+            .arg(index)
+            .arg(expression)
+            .arg(&["NOCONTENT", "LIMIT", "0"])
+            .arg(SEARCH_CANDIDATES)
+            .query(&mut *self.conn.lock())
+            .map_err(redis_error("search"))?;
+        let entry_prefix = self.entry_key("");
+        Ok(reply
+            .iter()
+            .skip(1)
+            .filter_map(|value| redis::from_redis_value::<String>(value).ok())
+            .filter_map(|key| key.strip_prefix(&entry_prefix).map(str::to_string))
+            .collect())
+    }
+}
+
+impl StorageBackend for RedisBackend {
+    fn store(&mut self, new_entry: NewMemoryEntry) -> Result<MemoryEntry, StorageError> {
+        if new_entry.text.trim().is_empty() {
+            return Err(StorageError::InvalidInput("text cannot be empty".to_string()));
+        }
+        let seq: u64 = redis::cmd("INCR")
+            .arg(format!("{}:next_id", self.prefix))
+            .query(&mut *self.conn.lock())
+            .map_err(redis_error("write"))?;
+
+        let entry = MemoryEntry {
+            id: new_id(self.id_format, seq)?,
+            text: new_entry.text.to_lowercase(),
+            category: new_entry.category,
+            scope: new_entry.scope,
+            importance: new_entry.importance.clamp(0.0, 1.0),
+            tags: new_entry.tags.into_iter().map(|t| t.to_lowercase()).collect(),
+            timestamp_ms: now_ms(),
+            embedding_dim: new_entry.embedding.as_ref().map(Vec::len),
+            embedding_model: new_entry.embedding.as_ref().and(new_entry.embedding_model),
+            embedding: new_entry.embedding,
+            source: new_entry.source,
+            keywords: new_entry.keywords,
+        };
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        self.write_entry(&mut pipe, &entry, seq)?;
+        pipe.query::<()>(&mut *self.conn.lock()).map_err(redis_error("write"))?;
+        Ok(entry)
+    }
+
+    /// Search failures fall back to ranking every entry; read failures recall nothing.
+    fn recall(&self, query: RecallQuery) -> Vec<RecallResult> {
+        let searched = self
+            .search_index
+            .as_deref()
+            .filter(|_| query.query_embedding.is_none())
+            .and_then(|index| self.search_ids(index, &query).ok());
+        let ids = searched.unwrap_or_else(|| self.ids(0, usize::MAX).unwrap_or_default());
+        let entries = self.load(&ids).unwrap_or_default();
+        recall_entries(&entries, query)
+    }
+
+    fn forget_by_id(&mut self, id: &str) -> Result<bool, StorageError> {
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .cmd("DEL")
+            .arg(self.entry_key(id))
+            .cmd("ZREM")
+            .arg(self.ids_key())
+            .arg(id)
+            .ignore();
+        let (deleted,): (u64,) = pipe.query(&mut *self.conn.lock()).map_err(redis_error("delete"))?;
+        Ok(deleted > 0)
+    }
+
+    fn list(&self, limit: usize) -> Vec<MemoryEntry> {
+        self.ids(0, limit.max(1))
+            .and_then(|ids| self.load(&ids))
+            .unwrap_or_default()
+    }
+
+    fn stats(&self) -> serde_json::Value {
+        let count: u64 = redis::cmd("ZCARD")
+            .arg(self.ids_key())
+            .query(&mut *self.conn.lock())
+            .unwrap_or(0);
+        serde_json::json!({
+            "backend": "redis",
+            "prefix": self.prefix,
+            "search_index": self.search_index,
+            "count": count
+        })
+    }
+
+    /// Re-writes every hash sealed with the primary key, in one transaction per page.
+    fn rekey(&mut self) -> Result<usize, StorageError> {
+        if self.cipher.is_none() {
+            return Err(StorageError::InvalidInput("encryption is not configured".to_string()));
+        }
+        let ids = self.ids(0, usize::MAX)?;
+        let mut rewritten = 0;
+        for page in ids.chunks(500) {
+            let entries = self.load(page)?;
+            let mut pipe = redis::pipe();
+            pipe.atomic();
+            for entry in &entries {
+                pipe.cmd("HSET")
+                    .arg(self.entry_key(&entry.id))
+                    .arg(entry_fields(entry, self.cipher.as_ref())?)
+                    .ignore();
+            }
+            pipe.query::<()>(&mut *self.conn.lock()).map_err(redis_error("write"))?;
+            rewritten += entries.len();
+        }
+        Ok(rewritten)
+    }
+}
+
+fn redis_error(action: &'static str) -> impl Fn(redis::RedisError) -> StorageError {
+    move |e| StorageError::InvalidInput(format!("redis {action} failed: {e}"))
+}
+
+/// Hash fields for `entry`; with a cipher, `text` and `embedding` are sealed.
+fn entry_fields(
+    entry: &MemoryEntry,
+    cipher: Option<&FieldCipher>,
+) -> Result<Vec<(&'static str, String)>, StorageError> {
+    let mut sealed = entry.clone();
+    let embedding = match cipher {
+        Some(cipher) => cipher.seal_entry(&mut sealed)?,
+        None => sealed.embedding.take().map(|v| serde_json::to_string(&v)).transpose()?,
+    };
+    let mut fields = vec![
+        ("id", sealed.id),
+        ("text", sealed.text),
+        ("category", sealed.category),
+        ("scope", sealed.scope),
+        ("importance", sealed.importance.to_string()),
+        ("tags", serde_json::to_string(&sealed.tags)?),
+        ("timestamp_ms", sealed.timestamp_ms.to_string()),
+        ("embedding", embedding.unwrap_or_default()),
+        ("embedding_model", sealed.embedding_model.unwrap_or_default()),
+    ];
+    if let Some(source) = &sealed.source {
+        fields.push(("source", serde_json::to_string(source)?));
+    }
+    if !sealed.keywords.is_empty() {
+        fields.push(("keywords", serde_json::to_string(&sealed.keywords)?));
+    }
+    Ok(fields)
+}
+
+fn entry_from_fields(
+    mut fields: HashMap<String, String>,
+    cipher: Option<&FieldCipher>,
+) -> Result<MemoryEntry, StorageError> {
+    let mut take = |name: &str| fields.remove(name).filter(|v| !v.is_empty());
+    let id = take("id").ok_or_else(|| StorageError::InvalidInput("redis entry without id".to_string()))?;
+    let sealed_embedding = take("embedding");
+    let mut entry = MemoryEntry {
+        text: take("text").unwrap_or_default(),
+        category: take("category").unwrap_or_else(|| "other".to_string()),
+        scope: take("scope").unwrap_or_else(|| "global".to_string()),
+        importance: take("importance").and_then(|v| v.parse().ok()).unwrap_or(0.7),
+        tags: take("tags")
+            .map(|v| serde_json::from_str(&v))
+            .transpose()?
+            .unwrap_or_default(),
+        timestamp_ms: take("timestamp_ms").and_then(|v| v.parse().ok()).unwrap_or(0),
+        embedding: None,
+        embedding_model: take("embedding_model"),
+        embedding_dim: None,
+        source: take("source").map(|v| serde_json::from_str(&v)).transpose()?,
+        keywords: take("keywords")
+            .map(|v| serde_json::from_str(&v))
+            .transpose()?
+            .unwrap_or_default(),
+        id,
+    };
+    match cipher {
+        Some(cipher) => cipher.open_entry(&mut entry, sealed_embedding.as_deref())?,
+        None if FieldCipher::is_sealed(&entry.text)
+            || sealed_embedding.as_deref().is_some_and(FieldCipher::is_sealed) =>
+        {
+            return Err(StorageError::InvalidInput(
+                "memory store is encrypted but no encryption key is configured".to_string(),
+            ));
+        }
+        None => entry.embedding = sealed_embedding.map(|v| serde_json::from_str(&v)).transpose()?,
+    }
+    entry.embedding_dim = entry.embedding.as_ref().map(Vec::len);
+    Ok(entry)
+}
+
+/// Escapes `RediSearch` punctuation inside a TAG value.
+fn escape_search(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if !c.is_alphanumeric() && c != '_' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemorySource;
+
+    fn entry() -> MemoryEntry {
+        MemoryEntry {
+            id: "mem-7".to_string(),
+            text: "deploys go through the staging cluster".to_string(),
+            category: "fact".to_string(),
+            scope: "project:infra".to_string(),
+            importance: 0.8,
+            tags: vec!["ops".to_string(), "k8s".to_string()],
+            timestamp_ms: 1_700_000_000_000,
+            embedding: Some(vec![0.25, -0.5]),
+            embedding_model: Some("test-model".to_string()),
+            embedding_dim: Some(2),
+            source: Some(MemorySource {
+                tool: Some("runbook".to_string()),
+                ..MemorySource::default()
+            }),
+            keywords: vec!["rollout".to_string(), "release".to_string()],
+        }
+    }
+
+    fn as_map(fields: Vec<(&'static str, String)>) -> HashMap<String, String> {
+        fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+
+    #[test]
+    fn hash_fields_round_trip_with_and_without_encryption() {
+        let plain = as_map(entry_fields(&entry(), None).expect("fields"));
+        assert_eq!(plain.get("scope").map(String::as_str), Some("project:infra"));
+        assert_eq!(entry_from_fields(plain, None).expect("decode"), entry());
+
+        let cipher = FieldCipher::from_hex_keys(&"ab".repeat(32), &[]).expect("cipher");
+        let sealed = as_map(entry_fields(&entry(), Some(&cipher)).expect("fields"));
+        assert!(sealed.get("text").is_some_and(|t| FieldCipher::is_sealed(t)));
+        assert!(entry_from_fields(sealed.clone(), None).is_err());
+        assert_eq!(entry_from_fields(sealed, Some(&cipher)).expect("decode"), entry());
+    }
+
+    #[test]
+    fn tag_values_are_escaped_for_search() {
+        assert_eq!(escape_search("project:infra-2"), "project\\:infra\\-2");
+        assert_eq!(escape_search("global"), "global");
+    }
+}
@@ -0,0 +1,243 @@
+//! S3-compatible persistence for stateless deployments (feature `s3-backend`).
+//!
+//! The JSON store keeps working on a local cache file. Opening downloads the object over the cache
+//! and every write uploads the rewritten file, so a fresh container picks up where the last one
+//! stopped without a mounted volume. One writer per object: concurrent writers overwrite each
+//! other's uploads.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+
+use crate::{
+    ChangeEvent, FieldCipher, IdFormat, MemoryEntry, MemoryRelation, NewMemoryEntry, PersistentMemoryStore,
+    RecallQuery, RecallResult, StorageBackend, StorageError, StoreSnapshot, atomic_write,
+};
+
+/// Where the store lives. Credentials come from the usual `AWS_*` environment variables.
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    pub bucket: String,
+    /// Object key of the store file, e.g. `prx-memory/memory-db.json`.
+    pub key: String,
+    /// Custom endpoint for `MinIO`, R2 and other S3-compatible services.
+    pub endpoint: Option<String>,
+    pub region: Option<String>,
+    /// Allows a plain `http://` endpoint.
+    pub allow_http: bool,
+}
+
+pub struct S3Backend {
+    inner: PersistentMemoryStore,
+    cache_path: PathBuf,
+    objects: Arc<dyn ObjectStore>,
+    key: ObjectPath,
+    rt: Arc<tokio::runtime::Runtime>,
+    uploads: u64,
+}
+
+impl S3Backend {
+    /// Connects to the bucket in `config` and opens the store cached at `cache_path`.
+    pub fn open(
+        config: &S3Config,
+        cache_path: impl AsRef<Path>,
+        rt: Arc<tokio::runtime::Runtime>,
+        cipher: Option<FieldCipher>,
+    ) -> Result<Self, StorageError> {
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(&config.bucket)
+            .with_allow_http(config.allow_http);
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        if let Some(region) = &config.region {
+            builder = builder.with_region(region);
+        }
+        let objects = builder
+            .build()
+            .map_err(|e| StorageError::InvalidInput(format!("s3 client setup failed: {e}")))?;
+        Self::with_object_store(Arc::new(objects), &config.key, cache_path, rt, cipher)
+    }
+
+    /// Like [`Self::open`] over any [`ObjectStore`]. The object wins over the local cache; when
+    /// the object does not exist yet, an existing cache seeds it.
+    pub fn with_object_store(
+        objects: Arc<dyn ObjectStore>,
+        key: &str,
+        cache_path: impl AsRef<Path>,
+        rt: Arc<tokio::runtime::Runtime>,
+        cipher: Option<FieldCipher>,
+    ) -> Result<Self, StorageError> {
+        let cache_path = cache_path.as_ref().to_path_buf();
+        let key = ObjectPath::from(key);
+        let downloaded = rt.block_on(async {
+            match objects.get(&key).await {
+                Ok(result) => result.bytes().await.map(Some),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(err) => Err(err),
+            }
+        });
+        let downloaded = downloaded.map_err(|e| StorageError::InvalidInput(format!("s3 download failed: {e}")))?;
+        let seed = downloaded.is_none() && cache_path.exists();
+        if let Some(bytes) = downloaded {
+            if let Some(parent) = cache_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            atomic_write(&cache_path, &bytes)?;
+        }
+        let mut backend = Self {
+            inner: PersistentMemoryStore::open_with_cipher(&cache_path, cipher)?,
+            cache_path,
+            objects,
+            key,
+            rt,
+            uploads: 0,
+        };
+        if seed {
+            backend.upload()?;
+        }
+        Ok(backend)
+    }
+
+    fn upload(&mut self) -> Result<(), StorageError> {
+        let bytes = fs::read(&self.cache_path)?;
+        self.rt
+            .block_on(self.objects.put(&self.key, PutPayload::from(bytes)))
+            .map_err(|e| StorageError::InvalidInput(format!("s3 upload failed: {e}")))?;
+        self.uploads += 1;
+        Ok(())
+    }
+
+    /// Hands out new ids in `format`; [`IdFormat::Ulid`] unless set.
+    #[must_use]
+    pub fn with_id_format(mut self, format: IdFormat) -> Self {
+        self.inner = self.inner.with_id_format(format);
+        self
+    }
+
+    /// Uploads after a write that changed the cache file.
+    fn uploaded<T>(&mut self, result: T) -> Result<T, StorageError> {
+        self.upload()?;
+        Ok(result)
+    }
+}
+
+impl StorageBackend for S3Backend {
+    fn store(&mut self, new_entry: NewMemoryEntry) -> Result<MemoryEntry, StorageError> {
+        let entry = self.inner.store(new_entry)?;
+        self.uploaded(entry)
+    }
+
+    fn recall(&self, query: RecallQuery) -> Vec<RecallResult> {
+        self.inner.recall(query)
+    }
+
+    fn forget_by_id(&mut self, id: &str) -> Result<bool, StorageError> {
+        let removed = self.inner.forget_by_id(id)?;
+        if removed { self.uploaded(true) } else { Ok(false) }
+    }
+
+    fn list(&self, limit: usize) -> Vec<MemoryEntry> {
+        self.inner.list(limit)
+    }
+
+    fn stats(&self) -> serde_json::Value {
+        let mut stats = self.inner.stats();
+        if let Some(obj) = stats.as_object_mut() {
+            obj.insert("object_key".to_string(), self.key.to_string().into());
+            obj.insert("uploads".to_string(), self.uploads.into());
+        }
+        stats
+    }
+
+    fn snapshot(&self) -> StoreSnapshot {
+        self.inner.snapshot()
+    }
+
+    fn forget_batch(&mut self, ids: &[String]) -> Result<Vec<String>, StorageError> {
+        let removed = self.inner.forget_batch(ids)?;
+        if removed.is_empty() {
+            Ok(removed)
+        } else {
+            self.uploaded(removed)
+        }
+    }
+
+    fn apply_changes(&mut self, events: &[ChangeEvent]) -> Result<(), StorageError> {
+        self.inner.apply_changes(events)?;
+        self.uploaded(())
+    }
+
+    fn link(&mut self, from_id: &str, relation: &str, to_id: &str) -> Result<bool, StorageError> {
+        let added = self.inner.link(from_id, relation, to_id)?;
+        if added { self.uploaded(true) } else { Ok(false) }
+    }
+
+    fn unlink(&mut self, from_id: &str, relation: Option<&str>, to_id: &str) -> Result<usize, StorageError> {
+        let removed = self.inner.unlink(from_id, relation, to_id)?;
+        if removed > 0 { self.uploaded(removed) } else { Ok(0) }
+    }
+
+    fn relations_for(&self, id: &str) -> Vec<MemoryRelation> {
+        self.inner.relations_for(id)
+    }
+
+    fn rekey(&mut self) -> Result<usize, StorageError> {
+        let rewritten = self.inner.rekey()?;
+        self.uploaded(rewritten)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+
+    fn new_entry(text: &str) -> NewMemoryEntry {
+        NewMemoryEntry {
+            text: text.to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance: 0.5,
+            tags: Vec::new(),
+            embedding: None,
+            embedding_model: None,
+            source: None,
+            keywords: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fresh_cache_restores_from_the_bucket() {
+        let rt = Arc::new(tokio::runtime::Runtime::new().expect("runtime"));
+        let bucket: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let dir = std::env::temp_dir().join(format!("prx-s3-{}-{}", std::process::id(), crate::now_ms()));
+        let first_cache = dir.join("first.json");
+        let second_cache = dir.join("second.json");
+
+        let mut first = S3Backend::with_object_store(
+            Arc::clone(&bucket),
+            "agents/a/memory.json",
+            &first_cache,
+            Arc::clone(&rt),
+            None,
+        )
+        .expect("open first");
+        let kept = first.store(new_entry("survives a container restart")).expect("store");
+        let dropped = first.store(new_entry("forgotten before restart")).expect("store");
+        assert!(first.forget_by_id(&dropped.id).expect("forget"));
+        assert!(!first.forget_by_id("mem-missing").expect("forget missing"));
+        assert_eq!(first.stats().get("uploads"), Some(&serde_json::json!(3)));
+        drop(first);
+
+        let second =
+            S3Backend::with_object_store(bucket, "agents/a/memory.json", &second_cache, rt, None).expect("open second");
+        let ids = second.list(10).into_iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![kept.id]);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
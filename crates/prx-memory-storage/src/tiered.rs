@@ -0,0 +1,350 @@
+//! Two-tier storage: recent and important entries stay in the in-process JSON store, cold ones
+//! move to a larger backend such as `LanceDB`. Entries keep their ids when they move, and recall
+//! ranks candidates from both tiers together, so callers see one store.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::time::Instant;
+
+use crate::{
+    ChangeEvent, ChangeOp, IdFormat, MemoryEntry, NewMemoryEntry, PersistentMemoryStore, RecallQuery, RecallResult,
+    StorageBackend, StorageError, id_sequence, new_id, now_ms, recall_entries,
+};
+
+#[derive(Debug, Clone)]
+pub struct TieredConfig {
+    /// Hot entries beyond this count spill, least important and oldest first.
+    pub max_hot_entries: usize,
+    /// Entries older than this spill unless they reach `keep_importance`.
+    pub max_hot_age_ms: u64,
+    /// Entries at or above this importance stay hot until the hot tier overflows.
+    pub keep_importance: f32,
+}
+
+#[derive(Debug, Default)]
+struct TierMetrics {
+    hot_hits: AtomicU64,
+    cold_hits: AtomicU64,
+    cold_recalls: AtomicU64,
+    cold_recall_ms: AtomicU64,
+    spilled: AtomicU64,
+}
+
+pub struct TieredBackend {
+    hot: PersistentMemoryStore,
+    cold: Box<dyn StorageBackend>,
+    config: TieredConfig,
+    /// Ids are handed out here rather than by the hot store, which forgets spilled ids.
+    next_id: u64,
+    id_format: IdFormat,
+    metrics: TierMetrics,
+}
+
+impl TieredBackend {
+    pub fn new(
+        hot: PersistentMemoryStore,
+        cold: Box<dyn StorageBackend>,
+        config: TieredConfig,
+    ) -> Result<Self, StorageError> {
+        let next_id = hot
+            .list(usize::MAX)
+            .iter()
+            .chain(cold.list(usize::MAX).iter())
+            .filter_map(|e| id_sequence(&e.id))
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let mut backend = Self {
+            hot,
+            cold,
+            config,
+            next_id,
+            id_format: IdFormat::default(),
+            metrics: TierMetrics::default(),
+        };
+        backend.spill()?;
+        Ok(backend)
+    }
+
+    /// Hands out new ids in `format`; [`IdFormat::Ulid`] unless set.
+    #[must_use]
+    pub const fn with_id_format(mut self, format: IdFormat) -> Self {
+        self.id_format = format;
+        self
+    }
+
+    /// Moves cold entries out of the hot tier and returns how many moved.
+    pub fn spill(&mut self) -> Result<usize, StorageError> {
+        let now = now_ms();
+        let snapshot = self.hot.snapshot();
+        let entries = snapshot.entries();
+        let mut moving = entries
+            .iter()
+            .filter(|e| {
+                now.saturating_sub(e.timestamp_ms) > self.config.max_hot_age_ms
+                    && e.importance < self.config.keep_importance
+            })
+            .collect::<Vec<_>>();
+        let overflow = entries
+            .len()
+            .saturating_sub(moving.len())
+            .saturating_sub(self.config.max_hot_entries);
+        if overflow > 0 {
+            let chosen = moving.iter().map(|e| e.id.as_str()).collect::<HashSet<_>>();
+            let mut rest = entries
+                .iter()
+                .filter(|e| !chosen.contains(e.id.as_str()))
+                .collect::<Vec<_>>();
+            rest.sort_by_key(|e| (e.importance >= self.config.keep_importance, e.timestamp_ms));
+            moving.extend(rest.into_iter().take(overflow));
+        }
+        if moving.is_empty() {
+            return Ok(0);
+        }
+        let events = moving.iter().map(|e| store_event((*e).clone())).collect::<Vec<_>>();
+        let ids = moving.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        // Copy before removing: a crash in between leaves a duplicate, which recall drops.
+        self.cold.apply_changes(&events)?;
+        self.hot.forget_batch(&ids)?;
+        self.metrics
+            .spilled
+            .fetch_add(u64::try_from(ids.len()).unwrap_or(u64::MAX), AtomicOrdering::Relaxed);
+        Ok(ids.len())
+    }
+}
+
+fn store_event(entry: MemoryEntry) -> ChangeEvent {
+    ChangeEvent {
+        seq: 0,
+        op: ChangeOp::Store,
+        id: entry.id.clone(),
+        previous_id: None,
+        timestamp_ms: entry.timestamp_ms,
+        entry: Some(entry),
+    }
+}
+
+fn count(stats: &serde_json::Value) -> u64 {
+    stats.get("count").and_then(serde_json::Value::as_u64).unwrap_or(0)
+}
+
+impl StorageBackend for TieredBackend {
+    fn store(&mut self, new_entry: NewMemoryEntry) -> Result<MemoryEntry, StorageError> {
+        if new_entry.text.trim().is_empty() {
+            return Err(StorageError::InvalidInput("text cannot be empty".to_string()));
+        }
+        let entry = MemoryEntry {
+            id: new_id(self.id_format, self.next_id)?,
+            text: new_entry.text.to_lowercase(),
+            category: new_entry.category,
+            scope: new_entry.scope,
+            importance: new_entry.importance.clamp(0.0, 1.0),
+            tags: new_entry.tags.into_iter().map(|t| t.to_lowercase()).collect(),
+            timestamp_ms: now_ms(),
+            embedding_dim: new_entry.embedding.as_ref().map(Vec::len),
+            embedding_model: new_entry.embedding.as_ref().and(new_entry.embedding_model),
+            embedding: new_entry.embedding,
+            source: new_entry.source,
+            keywords: new_entry.keywords,
+        };
+        self.hot.apply_changes(&[store_event(entry.clone())])?;
+        self.next_id += 1;
+        self.spill()?;
+        Ok(entry)
+    }
+
+    /// Ranks every hot entry together with the cold tier's best candidates, so scores are
+    /// comparable across tiers.
+    fn recall(&self, query: RecallQuery) -> Vec<RecallResult> {
+        let started = Instant::now();
+        let cold = self.cold.recall(RecallQuery {
+            limit: query.limit.max(1).saturating_mul(3),
+            ..query.clone()
+        });
+        self.metrics.cold_recalls.fetch_add(1, AtomicOrdering::Relaxed);
+        self.metrics.cold_recall_ms.fetch_add(
+            u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+            AtomicOrdering::Relaxed,
+        );
+
+        let snapshot = self.hot.snapshot();
+        let hot_ids = snapshot.entries().iter().map(|e| e.id.as_str()).collect::<HashSet<_>>();
+        let cold = cold
+            .into_iter()
+            .map(|r| r.entry)
+            .filter(|e| !hot_ids.contains(e.id.as_str()))
+            .collect::<Vec<_>>();
+        let results = if cold.is_empty() {
+            snapshot.recall(query)
+        } else {
+            let mut pool = snapshot.entries().to_vec();
+            pool.extend(cold);
+            recall_entries(&pool, query)
+        };
+        let hot_hits = results.iter().filter(|r| hot_ids.contains(r.entry.id.as_str())).count();
+        let cold_hits = results.len() - hot_hits;
+        for (counter, hits) in [(&self.metrics.hot_hits, hot_hits), (&self.metrics.cold_hits, cold_hits)] {
+            counter.fetch_add(u64::try_from(hits).unwrap_or(u64::MAX), AtomicOrdering::Relaxed);
+        }
+        results
+    }
+
+    fn forget_by_id(&mut self, id: &str) -> Result<bool, StorageError> {
+        Ok(!self.forget_batch(&[id.to_string()])?.is_empty())
+    }
+
+    fn forget_batch(&mut self, ids: &[String]) -> Result<Vec<String>, StorageError> {
+        let mut removed = self.hot.forget_batch(ids)?;
+        let rest = ids
+            .iter()
+            .filter(|id| !removed.contains(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !rest.is_empty() {
+            removed.extend(self.cold.forget_batch(&rest)?);
+        }
+        Ok(removed)
+    }
+
+    /// Newest first across both tiers.
+    fn list(&self, limit: usize) -> Vec<MemoryEntry> {
+        let limit = limit.max(1);
+        let mut entries = self.hot.list(limit);
+        let mut seen = entries.iter().map(|e| e.id.clone()).collect::<HashSet<_>>();
+        entries.extend(self.cold.list(limit).into_iter().filter(|e| seen.insert(e.id.clone())));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp_ms));
+        entries.truncate(limit);
+        entries
+    }
+
+    fn stats(&self) -> serde_json::Value {
+        let hot = self.hot.stats();
+        let cold = self.cold.stats();
+        let cold_recalls = self.metrics.cold_recalls.load(AtomicOrdering::Relaxed);
+        let cold_recall_ms = self.metrics.cold_recall_ms.load(AtomicOrdering::Relaxed);
+        serde_json::json!({
+            "backend": "tiered",
+            "count": count(&hot) + count(&cold),
+            "hot": hot,
+            "cold": cold,
+            "tiers": {
+                "hot_hits": self.metrics.hot_hits.load(AtomicOrdering::Relaxed),
+                "cold_hits": self.metrics.cold_hits.load(AtomicOrdering::Relaxed),
+                "cold_recalls": cold_recalls,
+                "cold_recall_avg_ms": cold_recall_ms.checked_div(cold_recalls).unwrap_or(0),
+                "spilled": self.metrics.spilled.load(AtomicOrdering::Relaxed),
+                "max_hot_entries": self.config.max_hot_entries,
+                "max_hot_age_ms": self.config.max_hot_age_ms,
+                "keep_importance": self.config.keep_importance
+            }
+        })
+    }
+
+    fn rekey(&mut self) -> Result<usize, StorageError> {
+        Ok(self.hot.rekey()? + self.cold.rekey()?)
+    }
+
+    fn index_status(&self) -> Result<serde_json::Value, StorageError> {
+        let cold = self.cold.index_status()?;
+        Ok(serde_json::json!({
+            "health": cold.get("health").cloned(),
+            "hot": StorageBackend::index_status(&self.hot)?,
+            "cold": cold,
+        }))
+    }
+
+    fn ensure_indexes(&mut self) -> Result<serde_json::Value, StorageError> {
+        self.cold.ensure_indexes()?;
+        self.index_status()
+    }
+
+    fn flush(&mut self) -> Result<(), StorageError> {
+        StorageBackend::flush(&mut self.hot)?;
+        self.cold.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_entry(text: &str, importance: f32) -> NewMemoryEntry {
+        NewMemoryEntry {
+            text: text.to_string(),
+            category: "fact".to_string(),
+            scope: "global".to_string(),
+            importance,
+            tags: Vec::new(),
+            embedding: None,
+            embedding_model: None,
+            source: None,
+            keywords: Vec::new(),
+        }
+    }
+
+    fn recall(backend: &TieredBackend, text: &str) -> Vec<String> {
+        backend
+            .recall(RecallQuery {
+                query: text.to_string(),
+                query_embedding: None,
+                scope: None,
+                category: None,
+                limit: 5,
+                vector_weight: None,
+                lexical_weight: None,
+                diversity: None,
+                fusion: None,
+            })
+            .into_iter()
+            .map(|r| r.entry.id)
+            .collect()
+    }
+
+    #[test]
+    fn overflow_spills_to_the_cold_tier_and_recall_spans_both() {
+        let dir = std::env::temp_dir().join(format!("prx-tiered-{}-{}", std::process::id(), now_ms()));
+        let config = TieredConfig {
+            max_hot_entries: 2,
+            max_hot_age_ms: u64::MAX,
+            keep_importance: 0.9,
+        };
+        let open = || {
+            let hot = PersistentMemoryStore::open(dir.join("hot.json")).expect("hot");
+            let cold = PersistentMemoryStore::open(dir.join("cold.json")).expect("cold");
+            TieredBackend::new(hot, Box::new(cold), config.clone()).expect("tiered")
+        };
+
+        let mut backend = open();
+        let pinned = backend
+            .store(new_entry("pinned deploy checklist", 0.95))
+            .expect("store");
+        let old = backend
+            .store(new_entry("old staging password rotation", 0.3))
+            .expect("store");
+        let fresh = backend.store(new_entry("fresh release notes", 0.3)).expect("store");
+        let newest = backend.store(new_entry("newest incident summary", 0.3)).expect("store");
+
+        let hot_ids = backend.hot.list(10).into_iter().map(|e| e.id).collect::<HashSet<_>>();
+        assert_eq!(hot_ids, HashSet::from([pinned.id.clone(), newest.id.clone()]));
+        assert_eq!(recall(&backend, "staging password"), vec![old.id]);
+        assert_eq!(backend.list(10).len(), 4);
+        let stats = backend.stats();
+        assert_eq!(stats.get("count"), Some(&serde_json::json!(4)));
+        assert_eq!(stats.pointer("/tiers/spilled"), Some(&serde_json::json!(2)));
+        assert_eq!(stats.pointer("/tiers/cold_hits"), Some(&serde_json::json!(1)));
+
+        assert_eq!(
+            backend.forget_batch(&[pinned.id, newest.id]).expect("forget hot").len(),
+            2
+        );
+        drop(backend);
+
+        // The hot tier is empty now; new ids still differ from the spilled ones.
+        let mut backend = open();
+        let next = backend.store(new_entry("after restart", 0.5)).expect("store");
+        assert!(backend.list(10).iter().filter(|e| e.id == next.id).count() == 1);
+        assert!(backend.forget_by_id(&fresh.id).expect("forget cold"));
+        assert_eq!(backend.list(10).len(), 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
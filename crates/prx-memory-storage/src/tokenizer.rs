@@ -0,0 +1,172 @@
+use std::cell::Cell;
+use std::sync::OnceLock;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenizerMode {
+    /// Split on every non-alphanumeric char (original behaviour).
+    #[default]
+    Simple,
+    /// UAX #29 word boundaries.
+    Unicode,
+    /// Alphanumeric runs with CJK runs split into overlapping bigrams.
+    CjkNgram,
+}
+
+impl TokenizerMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "simple" | "ascii" => Some(Self::Simple),
+            "unicode" => Some(Self::Unicode),
+            "cjk-ngram" | "cjk" | "ngram" => Some(Self::CjkNgram),
+            _ => None,
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Simple => "simple",
+            Self::Unicode => "unicode",
+            Self::CjkNgram => "cjk-ngram",
+        }
+    }
+}
+
+/// Process-wide lexical ranking settings used by [`crate::recall_entries`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankingConfig {
+    pub tokenizer: TokenizerMode,
+}
+
+thread_local! {
+    static CONFIG_OVERRIDE: Cell<Option<RankingConfig>> = const { Cell::new(None) };
+}
+
+fn config_cell() -> &'static RwLock<RankingConfig> {
+    static CONFIG: OnceLock<RwLock<RankingConfig>> = OnceLock::new();
+    CONFIG.get_or_init(|| RwLock::new(RankingConfig::default()))
+}
+
+pub fn set_ranking_config(config: RankingConfig) {
+    *config_cell().write() = config;
+}
+
+/// The config in effect on this thread: a [`with_ranking_config`] override, else the global one.
+pub fn ranking_config() -> RankingConfig {
+    CONFIG_OVERRIDE.get().unwrap_or_else(|| *config_cell().read())
+}
+
+/// Runs `f` with `config` in effect on the current thread only, e.g. for one experiment arm's
+/// recall, without touching the process-wide config other callers see.
+pub fn with_ranking_config<T>(config: RankingConfig, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<RankingConfig>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CONFIG_OVERRIDE.set(self.0);
+        }
+    }
+    let _restore = Restore(CONFIG_OVERRIDE.replace(Some(config)));
+    f()
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    tokenize_with(text, ranking_config().tokenizer)
+}
+
+pub fn tokenize_with(text: &str, mode: TokenizerMode) -> Vec<String> {
+    match mode {
+        TokenizerMode::Simple => text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|s| !s.is_empty())
+            .map(str::to_lowercase)
+            .collect(),
+        TokenizerMode::Unicode => text.unicode_words().map(str::to_lowercase).collect(),
+        TokenizerMode::CjkNgram => {
+            let mut out = Vec::new();
+            for word in text.split(|c: char| !c.is_alphanumeric()).filter(|s| !s.is_empty()) {
+                push_cjk_aware(&mut out, &word.to_lowercase());
+            }
+            out
+        }
+    }
+}
+
+/// Splits `word` into non-CJK pieces and CJK runs; runs longer than two chars become bigrams.
+fn push_cjk_aware(out: &mut Vec<String>, word: &str) {
+    let mut run: Vec<char> = Vec::new();
+    let mut plain = String::new();
+    let flush_run = |run: &mut Vec<char>, out: &mut Vec<String>| {
+        if run.len() <= 2 {
+            if !run.is_empty() {
+                out.push(run.iter().collect());
+            }
+        } else {
+            out.extend(run.windows(2).map(|w| w.iter().collect::<String>()));
+        }
+        run.clear();
+    };
+    for c in word.chars() {
+        if is_cjk(c) {
+            if !plain.is_empty() {
+                out.push(std::mem::take(&mut plain));
+            }
+            run.push(c);
+        } else {
+            flush_run(&mut run, out);
+            plain.push(c);
+        }
+    }
+    flush_run(&mut run, out);
+    if !plain.is_empty() {
+        out.push(plain);
+    }
+}
+
+const fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30ff}'   // Hiragana, Katakana
+            | '\u{3400}'..='\u{4dbf}' // CJK Extension A
+            | '\u{4e00}'..='\u{9fff}' // CJK Unified Ideographs
+            | '\u{ac00}'..='\u{d7af}' // Hangul syllables
+            | '\u{f900}'..='\u{faff}' // CJK Compatibility Ideographs
+            | '\u{20000}'..='\u{2ffff}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cjk_ngram_mode_produces_bigrams() {
+        let simple = tokenize_with("数据库连接失败 retry", TokenizerMode::Simple);
+        assert_eq!(simple, vec!["数据库连接失败", "retry"]);
+
+        let unicode = tokenize_with("数据库 don't", TokenizerMode::Unicode);
+        assert!(unicode.contains(&"don't".to_string()));
+
+        let ngram = tokenize_with("数据库连接失败 retry", TokenizerMode::CjkNgram);
+        assert!(ngram.contains(&"数据".to_string()));
+        assert!(ngram.contains(&"失败".to_string()));
+        assert!(ngram.contains(&"retry".to_string()));
+        assert!(!ngram.contains(&"数据库连接失败".to_string()));
+    }
+
+    #[test]
+    fn thread_override_is_scoped() {
+        let global = ranking_config();
+        let inner = with_ranking_config(
+            RankingConfig {
+                tokenizer: TokenizerMode::CjkNgram,
+            },
+            || tokenize("数据库连接"),
+        );
+        assert!(inner.contains(&"数据".to_string()));
+        assert_eq!(ranking_config(), global);
+    }
+}
@@ -25,6 +25,10 @@ fn make_entries(n: usize) -> Vec<MemoryEntry> {
             tags: vec![provider.to_string(), "retrieval".to_string(), "mcp".to_string()],
             timestamp_ms: 1_700_000_000_000 + (i as u64 * 1000),
             embedding: None,
+            embedding_model: None,
+            source: None,
+            embedding_dim: None,
+            keywords: Vec::new(),
         });
     }
     out
@@ -59,6 +63,8 @@ fn recall_p95_under_threshold_on_100k() {
                 limit: 8,
                 vector_weight: None,
                 lexical_weight: None,
+                diversity: None,
+                fusion: None,
             },
         );
         let elapsed = started.elapsed().as_secs_f64() * 1000.0;
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatibleSummarizeConfig {
+    pub api_key: String,
+    pub base_url: String,
+    pub model: String,
+    pub timeout: Duration,
+    pub temperature: Option<f32>,
+}
+
+impl OpenAiCompatibleSummarizeConfig {
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: "https://api.openai.com".to_string(),
+            model: model.into(),
+            timeout: Duration::from_secs(30),
+            temperature: Some(0.2),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SummarizeProviderConfig {
+    OpenAiCompatible(OpenAiCompatibleSummarizeConfig),
+}
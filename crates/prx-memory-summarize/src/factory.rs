@@ -0,0 +1,12 @@
+use std::sync::Arc;
+
+use crate::config::SummarizeProviderConfig;
+use crate::error::ProviderError;
+use crate::providers::OpenAiCompatibleSummarizeProvider;
+use crate::traits::SummarizeProvider;
+
+pub fn build_summarize_provider(cfg: SummarizeProviderConfig) -> Result<Arc<dyn SummarizeProvider>, ProviderError> {
+    match cfg {
+        SummarizeProviderConfig::OpenAiCompatible(c) => Ok(Arc::new(OpenAiCompatibleSummarizeProvider::new(c)?)),
+    }
+}
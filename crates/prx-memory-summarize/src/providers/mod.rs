@@ -0,0 +1,3 @@
+pub mod openai_compatible;
+
+pub use openai_compatible::OpenAiCompatibleSummarizeProvider;
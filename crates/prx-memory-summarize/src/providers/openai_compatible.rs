@@ -0,0 +1,130 @@
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::config::OpenAiCompatibleSummarizeConfig;
+use crate::error::ProviderError;
+use crate::traits::SummarizeProvider;
+use crate::types::{SummarizeRequest, SummarizeResponse};
+
+const DEFAULT_INSTRUCTION: &str = "Condense the following memory entries into one reusable engineering note. \
+Keep concrete causes, fixes, and prevention rules; drop duplicates and conversational filler. \
+Reply with the note text only.";
+
+#[derive(Clone)]
+pub struct OpenAiCompatibleSummarizeProvider {
+    config: OpenAiCompatibleSummarizeConfig,
+    client: Client,
+}
+
+impl OpenAiCompatibleSummarizeProvider {
+    pub fn new(config: OpenAiCompatibleSummarizeConfig) -> Result<Self, ProviderError> {
+        let client = Client::builder().timeout(config.timeout).build()?;
+        Ok(Self { config, client })
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/v1/chat/completions", self.config.base_url.trim_end_matches('/'))
+    }
+}
+
+#[async_trait::async_trait]
+impl SummarizeProvider for OpenAiCompatibleSummarizeProvider {
+    fn name(&self) -> &'static str {
+        "openai-compatible"
+    }
+
+    async fn summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse, ProviderError> {
+        if request.documents.is_empty() {
+            return Err(ProviderError::Config("summarize documents is empty".to_string()));
+        }
+
+        let mut instruction = request.instruction.unwrap_or_else(|| DEFAULT_INSTRUCTION.to_string());
+        if let Some(max_chars) = request.max_chars {
+            instruction = format!("{instruction} Keep the note under {max_chars} characters.");
+        }
+        let user_content = request
+            .documents
+            .iter()
+            .enumerate()
+            .map(|(idx, doc)| format!("{}. {}", idx + 1, doc.trim()))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut payload = serde_json::json!({
+            "model": self.config.model,
+            "messages": [
+                {"role": "system", "content": instruction},
+                {"role": "user", "content": user_content}
+            ],
+        });
+        if let (Some(temperature), Some(obj)) = (self.config.temperature, payload.as_object_mut()) {
+            obj.insert("temperature".to_string(), serde_json::json!(temperature));
+        }
+
+        let res = self
+            .client
+            .post(self.endpoint())
+            .bearer_auth(&self.config.api_key)
+            .json(&payload)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let status = res.status().as_u16();
+            let body = res.text().await.unwrap_or_default();
+            return Err(ProviderError::Api { status, body });
+        }
+
+        let parsed: ChatCompletionResponse = res.json().await?;
+        let summary = parsed
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| c.message.content.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| ProviderError::InvalidResponse("no summary in response".to_string()))?;
+
+        Ok(SummarizeResponse {
+            provider: self.name().to_string(),
+            model: parsed.model.unwrap_or_else(|| self.config.model.clone()),
+            summary,
+            usage_tokens: parsed.usage.and_then(|u| u.total_tokens),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    model: Option<String>,
+    choices: Vec<ChatChoice>,
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Usage {
+    total_tokens: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_completion_response_parses() {
+        let raw = r#"{"model":"gpt-4o-mini","choices":[{"index":0,"message":{"role":"assistant","content":" merged note "}}],"usage":{"total_tokens":42}}"#;
+        let parsed: ChatCompletionResponse = serde_json::from_str(raw).expect("parse chat completion");
+        assert_eq!(parsed.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(parsed.choices[0].message.content.trim(), "merged note");
+        assert_eq!(parsed.usage.and_then(|u| u.total_tokens), Some(42));
+    }
+}
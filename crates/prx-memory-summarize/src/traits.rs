@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+
+use crate::error::ProviderError;
+use crate::types::{SummarizeRequest, SummarizeResponse};
+
+#[async_trait]
+pub trait SummarizeProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn summarize(&self, request: SummarizeRequest) -> Result<SummarizeResponse, ProviderError>;
+}
@@ -0,0 +1,14 @@
+#[derive(Debug, Clone)]
+pub struct SummarizeRequest {
+    pub documents: Vec<String>,
+    pub instruction: Option<String>,
+    pub max_chars: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SummarizeResponse {
+    pub provider: String,
+    pub model: String,
+    pub summary: String,
+    pub usage_tokens: Option<u64>,
+}